@@ -5,12 +5,14 @@ extern crate lazy_static;
 extern crate serde_derive;
 
 pub mod parse_account_data;
+pub mod parse_address_lookup_table;
 pub mod parse_bpf_loader;
 pub mod parse_config;
 pub mod parse_nonce;
 pub mod parse_stake;
 pub mod parse_sysvar;
 pub mod parse_token;
+pub mod parse_token_extension;
 pub mod parse_vote;
 pub mod validator_info;
 
@@ -78,6 +80,26 @@ impl UiAccount {
         encoding: UiAccountEncoding,
         additional_data: Option<AccountAdditionalData>,
         data_slice_config: Option<UiDataSliceConfig>,
+    ) -> Self {
+        Self::encode_with_compression_level(
+            pubkey,
+            account,
+            encoding,
+            additional_data,
+            data_slice_config,
+            None,
+        )
+    }
+
+    /// Like `encode`, but compresses "base64+zstd" data at `compression_level` rather than at
+    /// zstd's default level
+    pub fn encode_with_compression_level<T: ReadableAccount>(
+        pubkey: &Pubkey,
+        account: &T,
+        encoding: UiAccountEncoding,
+        additional_data: Option<AccountAdditionalData>,
+        data_slice_config: Option<UiDataSliceConfig>,
+        compression_level: Option<i32>,
     ) -> Self {
         let data = match encoding {
             UiAccountEncoding::Binary => {
@@ -93,7 +115,9 @@ impl UiAccount {
                 encoding,
             ),
             UiAccountEncoding::Base64Zstd => {
-                let mut encoder = zstd::stream::write::Encoder::new(Vec::new(), 0).unwrap();
+                let mut encoder =
+                    zstd::stream::write::Encoder::new(Vec::new(), compression_level.unwrap_or(0))
+                        .unwrap();
                 match encoder
                     .write_all(slice_data(account.data(), data_slice_config))
                     .and_then(|()| encoder.finish())
@@ -254,5 +278,22 @@ mod test {
         assert_eq!(decoded_account.data(), &vec![0; 1024]);
         let decoded_account = encoded_account.decode::<AccountSharedData>().unwrap();
         assert_eq!(decoded_account.data(), &vec![0; 1024]);
+
+        let encoded_account = UiAccount::encode_with_compression_level(
+            &Pubkey::default(),
+            &AccountSharedData::from(Account {
+                data: vec![0; 1024],
+                ..Account::default()
+            }),
+            UiAccountEncoding::Base64Zstd,
+            None,
+            Some(UiDataSliceConfig {
+                offset: 0,
+                length: 512,
+            }),
+            Some(19),
+        );
+        let decoded_account = encoded_account.decode::<Account>().unwrap();
+        assert_eq!(decoded_account.data(), &vec![0; 512]);
     }
 }
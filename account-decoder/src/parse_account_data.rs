@@ -1,28 +1,38 @@
 use crate::{
+    parse_address_lookup_table::{address_lookup_table_id, parse_address_lookup_table},
     parse_bpf_loader::parse_bpf_upgradeable_loader,
     parse_config::parse_config,
     parse_nonce::parse_nonce,
     parse_stake::parse_stake,
     parse_sysvar::parse_sysvar,
-    parse_token::{parse_token, spl_token_id_v2_0},
+    parse_token::{parse_token, spl_token_2022_id, spl_token_id_v2_0},
     parse_vote::parse_vote,
 };
 use inflector::Inflector;
 use serde_json::Value;
 use solana_sdk::{instruction::InstructionError, pubkey::Pubkey, stake, system_program, sysvar};
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
 use thiserror::Error;
 
 lazy_static! {
+    static ref ADDRESS_LOOKUP_TABLE_PROGRAM_ID: Pubkey = address_lookup_table_id();
     static ref BPF_UPGRADEABLE_LOADER_PROGRAM_ID: Pubkey = solana_sdk::bpf_loader_upgradeable::id();
     static ref CONFIG_PROGRAM_ID: Pubkey = solana_config_program::id();
     static ref STAKE_PROGRAM_ID: Pubkey = stake::program::id();
     static ref SYSTEM_PROGRAM_ID: Pubkey = system_program::id();
     static ref SYSVAR_PROGRAM_ID: Pubkey = sysvar::id();
     static ref TOKEN_PROGRAM_ID: Pubkey = spl_token_id_v2_0();
+    static ref TOKEN_2022_PROGRAM_ID: Pubkey = spl_token_2022_id();
     static ref VOTE_PROGRAM_ID: Pubkey = solana_vote_program::id();
     pub static ref PARSABLE_PROGRAM_IDS: HashMap<Pubkey, ParsableAccount> = {
         let mut m = HashMap::new();
+        m.insert(
+            *ADDRESS_LOOKUP_TABLE_PROGRAM_ID,
+            ParsableAccount::AddressLookupTable,
+        );
         m.insert(
             *BPF_UPGRADEABLE_LOADER_PROGRAM_ID,
             ParsableAccount::BpfUpgradeableLoader,
@@ -30,11 +40,53 @@ lazy_static! {
         m.insert(*CONFIG_PROGRAM_ID, ParsableAccount::Config);
         m.insert(*SYSTEM_PROGRAM_ID, ParsableAccount::Nonce);
         m.insert(*TOKEN_PROGRAM_ID, ParsableAccount::SplToken);
+        m.insert(*TOKEN_2022_PROGRAM_ID, ParsableAccount::SplToken2022);
         m.insert(*STAKE_PROGRAM_ID, ParsableAccount::Stake);
         m.insert(*SYSVAR_PROGRAM_ID, ParsableAccount::Sysvar);
         m.insert(*VOTE_PROGRAM_ID, ParsableAccount::Vote);
         m
     };
+    static ref CUSTOM_ACCOUNT_PARSERS: RwLock<HashMap<Pubkey, Arc<dyn AccountParser>>> =
+        RwLock::default();
+}
+
+/// A parser for the accounts of a program without a built-in parser, used for the `jsonParsed`
+/// encoding once registered with `register_account_parser()`. Validators load parsers from
+/// dynamic libraries given with `--account-parser`, which export a "C" function
+/// `_create_account_parser` returning a `Box<dyn AccountParser>` as a raw pointer.
+pub trait AccountParser: Send + Sync {
+    /// The name reported as the `program` of the parsed accounts
+    fn program_name(&self) -> &str;
+
+    /// Parses the data of an account owned by the program, failing with
+    /// `ParseAccountError::CustomAccountNotParsable` for data it doesn't recognize
+    fn parse(&self, pubkey: &Pubkey, data: &[u8]) -> Result<Value, ParseAccountError>;
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum RegisterAccountParserError {
+    #[error("the accounts of program {0} have a built-in parser")]
+    BuiltIn(Pubkey),
+
+    #[error("a parser is already registered for the accounts of program {0}")]
+    AlreadyRegistered(Pubkey),
+}
+
+/// Parses the accounts owned by `program_id` with `parser` from now on, in every `jsonParsed`
+/// encoding of the process
+pub fn register_account_parser(
+    program_id: Pubkey,
+    parser: Arc<dyn AccountParser>,
+) -> Result<(), RegisterAccountParserError> {
+    if PARSABLE_PROGRAM_IDS.contains_key(&program_id) {
+        return Err(RegisterAccountParserError::BuiltIn(program_id));
+    }
+    let mut parsers = CUSTOM_ACCOUNT_PARSERS.write().unwrap();
+    if parsers.contains_key(&program_id) {
+        return Err(RegisterAccountParserError::AlreadyRegistered(program_id));
+    }
+    parsers.insert(program_id, parser);
+    Ok(())
 }
 
 #[derive(Error, Debug)]
@@ -45,6 +97,9 @@ pub enum ParseAccountError {
     #[error("Program not parsable")]
     ProgramNotParsable,
 
+    #[error("{0} account not parsable")]
+    CustomAccountNotParsable(String),
+
     #[error("Additional data required to parse: {0}")]
     AdditionalDataMissing(String),
 
@@ -66,10 +121,12 @@ pub struct ParsedAccount {
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum ParsableAccount {
+    AddressLookupTable,
     BpfUpgradeableLoader,
     Config,
     Nonce,
     SplToken,
+    SplToken2022,
     Stake,
     Sysvar,
     Vote,
@@ -86,30 +143,57 @@ pub fn parse_account_data(
     data: &[u8],
     additional_data: Option<AccountAdditionalData>,
 ) -> Result<ParsedAccount, ParseAccountError> {
-    let program_name = PARSABLE_PROGRAM_IDS
-        .get(program_id)
-        .ok_or(ParseAccountError::ProgramNotParsable)?;
+    let program_name = match PARSABLE_PROGRAM_IDS.get(program_id) {
+        Some(program_name) => program_name,
+        None => return parse_custom_account_data(pubkey, program_id, data),
+    };
     let additional_data = additional_data.unwrap_or_default();
     let parsed_json = match program_name {
+        ParsableAccount::AddressLookupTable => {
+            serde_json::to_value(parse_address_lookup_table(data)?)?
+        }
         ParsableAccount::BpfUpgradeableLoader => {
             serde_json::to_value(parse_bpf_upgradeable_loader(data)?)?
         }
         ParsableAccount::Config => serde_json::to_value(parse_config(data, pubkey)?)?,
         ParsableAccount::Nonce => serde_json::to_value(parse_nonce(data)?)?,
-        ParsableAccount::SplToken => {
+        ParsableAccount::SplToken | ParsableAccount::SplToken2022 => {
             serde_json::to_value(parse_token(data, additional_data.spl_token_decimals)?)?
         }
         ParsableAccount::Stake => serde_json::to_value(parse_stake(data)?)?,
         ParsableAccount::Sysvar => serde_json::to_value(parse_sysvar(data, pubkey)?)?,
         ParsableAccount::Vote => serde_json::to_value(parse_vote(data)?)?,
     };
+    let program = match program_name {
+        // Kebab case doesn't separate the version from the name
+        ParsableAccount::SplToken2022 => "spl-token-2022".to_string(),
+        _ => format!("{:?}", program_name).to_kebab_case(),
+    };
     Ok(ParsedAccount {
-        program: format!("{:?}", program_name).to_kebab_case(),
+        program,
         parsed: parsed_json,
         space: data.len() as u64,
     })
 }
 
+fn parse_custom_account_data(
+    pubkey: &Pubkey,
+    program_id: &Pubkey,
+    data: &[u8],
+) -> Result<ParsedAccount, ParseAccountError> {
+    let parser = CUSTOM_ACCOUNT_PARSERS
+        .read()
+        .unwrap()
+        .get(program_id)
+        .cloned()
+        .ok_or(ParseAccountError::ProgramNotParsable)?;
+    Ok(ParsedAccount {
+        program: parser.program_name().to_string(),
+        parsed: parser.parse(pubkey, data)?,
+        space: data.len() as u64,
+    })
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -152,4 +236,56 @@ mod test {
         assert_eq!(parsed.program, "nonce".to_string());
         assert_eq!(parsed.space, State::size() as u64);
     }
+
+    #[test]
+    fn test_register_account_parser() {
+        struct CounterParser;
+        impl AccountParser for CounterParser {
+            fn program_name(&self) -> &str {
+                "counter"
+            }
+
+            fn parse(&self, _pubkey: &Pubkey, data: &[u8]) -> Result<Value, ParseAccountError> {
+                match data {
+                    [count] => Ok(serde_json::json!({ "count": count })),
+                    _ => Err(ParseAccountError::CustomAccountNotParsable(
+                        self.program_name().to_string(),
+                    )),
+                }
+            }
+        }
+
+        let account_pubkey = solana_sdk::pubkey::new_rand();
+        let program_id = solana_sdk::pubkey::new_rand();
+        assert!(matches!(
+            parse_account_data(&account_pubkey, &program_id, &[7], None),
+            Err(ParseAccountError::ProgramNotParsable)
+        ));
+
+        register_account_parser(program_id, Arc::new(CounterParser)).unwrap();
+        let parsed = parse_account_data(&account_pubkey, &program_id, &[7], None).unwrap();
+        assert_eq!(
+            parsed,
+            ParsedAccount {
+                program: "counter".to_string(),
+                parsed: serde_json::json!({ "count": 7 }),
+                space: 1,
+            }
+        );
+        assert!(matches!(
+            parse_account_data(&account_pubkey, &program_id, &[7, 8], None),
+            Err(ParseAccountError::CustomAccountNotParsable(_))
+        ));
+
+        assert_eq!(
+            register_account_parser(program_id, Arc::new(CounterParser)),
+            Err(RegisterAccountParserError::AlreadyRegistered(program_id))
+        );
+        assert_eq!(
+            register_account_parser(solana_vote_program::id(), Arc::new(CounterParser)),
+            Err(RegisterAccountParserError::BuiltIn(
+                solana_vote_program::id()
+            ))
+        );
+    }
 }
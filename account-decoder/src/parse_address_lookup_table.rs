@@ -0,0 +1,122 @@
+use crate::parse_account_data::{ParsableAccount, ParseAccountError};
+use solana_sdk::{
+    clock::Slot,
+    pubkey::{Pubkey, PUBKEY_BYTES},
+};
+
+mod address_lookup_table {
+    solana_sdk::declare_id!("AddressLookupTab1e1111111111111111111111111");
+}
+
+/// Addresses of a lookup table start after its metadata, padded to this size
+const LOOKUP_TABLE_META_SIZE: usize = 56;
+
+pub fn address_lookup_table_id() -> Pubkey {
+    address_lookup_table::id()
+}
+
+// The state of an address lookup table account, inlined to avoid a dependency on the
+// address lookup table program
+#[derive(Serialize, Deserialize)]
+enum ProgramState {
+    Uninitialized,
+    LookupTable(LookupTableMeta),
+}
+
+#[derive(Serialize, Deserialize)]
+struct LookupTableMeta {
+    deactivation_slot: Slot,
+    last_extended_slot: Slot,
+    last_extended_slot_start_index: u8,
+    authority: Option<Pubkey>,
+    _padding: u16,
+}
+
+pub fn parse_address_lookup_table(
+    data: &[u8],
+) -> Result<LookupTableAccountType, ParseAccountError> {
+    let not_parsable =
+        || ParseAccountError::AccountNotParsable(ParsableAccount::AddressLookupTable);
+    let state: ProgramState = bincode::deserialize(data).map_err(|_| not_parsable())?;
+    let meta = match state {
+        ProgramState::Uninitialized => return Ok(LookupTableAccountType::Uninitialized),
+        ProgramState::LookupTable(meta) => meta,
+    };
+    let addresses = data
+        .get(LOOKUP_TABLE_META_SIZE..)
+        .ok_or_else(not_parsable)?;
+    if addresses.len() % PUBKEY_BYTES != 0 {
+        return Err(not_parsable());
+    }
+    Ok(LookupTableAccountType::LookupTable(UiLookupTable {
+        deactivation_slot: meta.deactivation_slot.to_string(),
+        last_extended_slot: meta.last_extended_slot.to_string(),
+        last_extended_slot_start_index: meta.last_extended_slot_start_index,
+        authority: meta.authority.map(|authority| authority.to_string()),
+        addresses: addresses
+            .chunks(PUBKEY_BYTES)
+            .map(|address| Pubkey::new(address).to_string())
+            .collect(),
+    }))
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", tag = "type", content = "info")]
+pub enum LookupTableAccountType {
+    Uninitialized,
+    LookupTable(UiLookupTable),
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiLookupTable {
+    pub deactivation_slot: String,
+    pub last_extended_slot: String,
+    pub last_extended_slot_start_index: u8,
+    pub authority: Option<String>,
+    pub addresses: Vec<String>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_address_lookup_table() {
+        let authority = Pubkey::new_unique();
+        let addresses = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        let lookup_table = ProgramState::LookupTable(LookupTableMeta {
+            deactivation_slot: Slot::MAX,
+            last_extended_slot: 42,
+            last_extended_slot_start_index: 1,
+            authority: Some(authority),
+            _padding: 0,
+        });
+        let mut data = bincode::serialize(&lookup_table).unwrap();
+        data.resize(LOOKUP_TABLE_META_SIZE, 0);
+        for address in &addresses {
+            data.extend_from_slice(address.as_ref());
+        }
+        assert_eq!(
+            parse_address_lookup_table(&data).unwrap(),
+            LookupTableAccountType::LookupTable(UiLookupTable {
+                deactivation_slot: Slot::MAX.to_string(),
+                last_extended_slot: 42.to_string(),
+                last_extended_slot_start_index: 1,
+                authority: Some(authority.to_string()),
+                addresses: addresses.iter().map(Pubkey::to_string).collect(),
+            })
+        );
+
+        data.pop();
+        assert!(parse_address_lookup_table(&data).is_err());
+        assert!(parse_address_lookup_table(&data[..LOOKUP_TABLE_META_SIZE - 1]).is_err());
+
+        let data = bincode::serialize(&ProgramState::Uninitialized).unwrap();
+        assert_eq!(
+            parse_address_lookup_table(&data).unwrap(),
+            LookupTableAccountType::Uninitialized
+        );
+        assert!(parse_address_lookup_table(&[2, 0, 0, 0]).is_err());
+    }
+}
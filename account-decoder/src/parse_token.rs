@@ -1,5 +1,6 @@
 use crate::{
     parse_account_data::{ParsableAccount, ParseAccountError},
+    parse_token_extension::{parse_extensions, UiExtension},
     StringAmount, StringDecimals,
 };
 use solana_sdk::pubkey::Pubkey;
@@ -17,6 +18,37 @@ pub fn spl_token_id_v2_0() -> Pubkey {
     Pubkey::new_from_array(spl_token_v2_0::id().to_bytes())
 }
 
+mod spl_token_2022 {
+    solana_sdk::declare_id!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+}
+
+pub fn spl_token_2022_id() -> Pubkey {
+    spl_token_2022::id()
+}
+
+pub fn is_known_spl_token_id(program_id: &Pubkey) -> bool {
+    *program_id == spl_token_id_v2_0() || *program_id == spl_token_2022_id()
+}
+
+// Token-2022 accounts and mints with extensions are padded to at least the length of an Account,
+// followed by an AccountType byte and the extensions
+pub(crate) const ACCOUNT_TYPE_OFFSET: usize = 165;
+const ACCOUNTTYPE_MINT: u8 = 1;
+const ACCOUNTTYPE_ACCOUNT: u8 = 2;
+
+/// Returns the base Account or Mint of a Token-2022 account with extensions, which has the
+/// spl-token layout, or `data` itself otherwise
+pub fn strip_token_extensions(data: &[u8]) -> &[u8] {
+    if data.len() <= ACCOUNT_TYPE_OFFSET || data.len() == Multisig::get_packed_len() {
+        return data;
+    }
+    match data[ACCOUNT_TYPE_OFFSET] {
+        ACCOUNTTYPE_MINT => &data[..Mint::get_packed_len()],
+        ACCOUNTTYPE_ACCOUNT => &data[..Account::get_packed_len()],
+        _ => data,
+    }
+}
+
 // A helper function to convert spl_token_v2_0::native_mint::id() as spl_sdk::pubkey::Pubkey to
 // solana_sdk::pubkey::Pubkey
 pub fn spl_token_v2_0_native_mint() -> Pubkey {
@@ -37,6 +69,8 @@ pub fn parse_token(
     data: &[u8],
     mint_decimals: Option<u8>,
 ) -> Result<TokenAccountType, ParseAccountError> {
+    let extensions = parse_extensions(data);
+    let data = strip_token_extensions(data);
     if data.len() == Account::get_packed_len() {
         let account = Account::unpack(data)
             .map_err(|_| ParseAccountError::AccountNotParsable(ParsableAccount::SplToken))?;
@@ -71,6 +105,7 @@ pub fn parse_token(
                 COption::Some(pubkey) => Some(pubkey.to_string()),
                 COption::None => None,
             },
+            extensions,
         }))
     } else if data.len() == Mint::get_packed_len() {
         let mint = Mint::unpack(data)
@@ -87,6 +122,7 @@ pub fn parse_token(
                 COption::Some(pubkey) => Some(pubkey.to_string()),
                 COption::None => None,
             },
+            extensions,
         }))
     } else if data.len() == Multisig::get_packed_len() {
         let multisig = Multisig::unpack(data)
@@ -139,6 +175,9 @@ pub struct UiTokenAccount {
     pub delegated_amount: Option<UiTokenAmount>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub close_authority: Option<String>,
+    /// Token-2022 extensions
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extensions: Vec<UiExtension>,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
@@ -230,6 +269,9 @@ pub struct UiMint {
     pub decimals: u8,
     pub is_initialized: bool,
     pub freeze_authority: Option<String>,
+    /// Token-2022 extensions
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extensions: Vec<UiExtension>,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
@@ -242,7 +284,7 @@ pub struct UiMultisig {
 }
 
 pub fn get_token_account_mint(data: &[u8]) -> Option<Pubkey> {
-    if data.len() == Account::get_packed_len() {
+    if strip_token_extensions(data).len() == Account::get_packed_len() {
         Some(Pubkey::new(&data[0..32]))
     } else {
         None
@@ -285,6 +327,7 @@ mod test {
                 rent_exempt_reserve: None,
                 delegated_amount: None,
                 close_authority: Some(owner_pubkey.to_string()),
+                extensions: vec![],
             }),
         );
 
@@ -305,6 +348,7 @@ mod test {
                 decimals: 3,
                 is_initialized: true,
                 freeze_authority: Some(owner_pubkey.to_string()),
+                extensions: vec![],
             }),
         );
 
@@ -354,6 +398,56 @@ mod test {
             get_token_account_mint(&account_data),
             Some(expected_mint_pubkey)
         );
+
+        account_data.extend_from_slice(&[ACCOUNTTYPE_ACCOUNT, 0, 0, 0]);
+        assert_eq!(
+            get_token_account_mint(&account_data),
+            Some(expected_mint_pubkey)
+        );
+        account_data[ACCOUNT_TYPE_OFFSET] = ACCOUNTTYPE_MINT;
+        assert_eq!(get_token_account_mint(&account_data), None);
+    }
+
+    #[test]
+    fn test_parse_token_with_extensions() {
+        let mint_pubkey = SplTokenPubkey::new(&[2; 32]);
+        let mut account_data = vec![0; Account::get_packed_len()];
+        let mut account = Account::unpack_unchecked(&account_data).unwrap();
+        account.mint = mint_pubkey;
+        account.state = AccountState::Initialized;
+        Account::pack(account, &mut account_data).unwrap();
+        let expected = parse_token(&account_data, Some(2)).unwrap();
+        account_data.push(ACCOUNTTYPE_ACCOUNT);
+        assert_eq!(parse_token(&account_data, Some(2)).unwrap(), expected);
+
+        // MemoTransfer, requiring memos
+        account_data.extend_from_slice(&[8, 0, 1, 0, 1]);
+        match parse_token(&account_data, Some(2)).unwrap() {
+            TokenAccountType::Account(account) => assert_eq!(
+                account.extensions,
+                vec![UiExtension::MemoTransfer(
+                    crate::parse_token_extension::UiMemoTransfer {
+                        require_incoming_transfer_memos: true,
+                    }
+                )]
+            ),
+            _ => panic!("expected an account"),
+        }
+
+        let mut mint_data = vec![0; Account::get_packed_len()];
+        let mut mint = Mint::unpack_unchecked(&mint_data[..Mint::get_packed_len()]).unwrap();
+        mint.decimals = 3;
+        mint.is_initialized = true;
+        Mint::pack(mint, &mut mint_data[..Mint::get_packed_len()]).unwrap();
+        mint_data.extend_from_slice(&[ACCOUNTTYPE_MINT, 1, 2, 3]);
+        match parse_token(&mint_data, None).unwrap() {
+            TokenAccountType::Mint(mint) => assert_eq!(mint.decimals, 3),
+            _ => panic!("expected a mint"),
+        }
+
+        // Unknown account types aren't parsed
+        mint_data[ACCOUNT_TYPE_OFFSET] = 3;
+        assert!(parse_token(&mint_data, None).is_err());
     }
 
     #[test]
@@ -0,0 +1,275 @@
+//! Parsing of the extensions of SPL Token-2022 accounts and mints, which follow the base
+//! account as type-length-value entries. The layouts are inlined to avoid a dependency on the
+//! spl-token-2022 crate.
+
+use {
+    crate::parse_token::ACCOUNT_TYPE_OFFSET,
+    solana_sdk::pubkey::{Pubkey, PUBKEY_BYTES},
+    std::convert::TryInto,
+};
+
+// spl_token_2022::extension::ExtensionType
+const EXTENSION_TYPE_UNINITIALIZED: u16 = 0;
+const EXTENSION_TYPE_TRANSFER_FEE_CONFIG: u16 = 1;
+const EXTENSION_TYPE_TRANSFER_FEE_AMOUNT: u16 = 2;
+const EXTENSION_TYPE_MEMO_TRANSFER: u16 = 8;
+const EXTENSION_TYPE_INTEREST_BEARING_CONFIG: u16 = 10;
+const EXTENSION_TYPE_METADATA_POINTER: u16 = 18;
+
+const TLV_HEADER_LEN: usize = 4;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", tag = "extension", content = "state")]
+pub enum UiExtension {
+    TransferFeeConfig(UiTransferFeeConfig),
+    TransferFeeAmount(UiTransferFeeAmount),
+    MemoTransfer(UiMemoTransfer),
+    InterestBearingConfig(UiInterestBearingConfig),
+    MetadataPointer(UiMetadataPointer),
+    /// An extension this parser doesn't know, or whose state is malformed
+    UnparseableExtension,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiTransferFee {
+    pub epoch: u64,
+    pub maximum_fee: u64,
+    pub transfer_fee_basis_points: u16,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiTransferFeeConfig {
+    pub transfer_fee_config_authority: Option<String>,
+    pub withdraw_withheld_authority: Option<String>,
+    pub withheld_amount: u64,
+    pub older_transfer_fee: UiTransferFee,
+    pub newer_transfer_fee: UiTransferFee,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiTransferFeeAmount {
+    pub withheld_amount: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiMemoTransfer {
+    pub require_incoming_transfer_memos: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiInterestBearingConfig {
+    pub rate_authority: Option<String>,
+    pub initialization_timestamp: i64,
+    pub pre_update_average_rate: i16,
+    pub last_update_timestamp: i64,
+    pub current_rate: i16,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiMetadataPointer {
+    pub authority: Option<String>,
+    pub metadata_address: Option<String>,
+}
+
+/// Returns the extensions of a Token-2022 account or mint, or none for accounts without
+/// extensions
+pub fn parse_extensions(data: &[u8]) -> Vec<UiExtension> {
+    let mut extensions = vec![];
+    let mut tlv_data = match data.get(ACCOUNT_TYPE_OFFSET + 1..) {
+        Some(tlv_data) => tlv_data,
+        None => return extensions,
+    };
+    while tlv_data.len() >= TLV_HEADER_LEN {
+        let extension_type = u16::from_le_bytes([tlv_data[0], tlv_data[1]]);
+        let length = u16::from_le_bytes([tlv_data[2], tlv_data[3]]) as usize;
+        if extension_type == EXTENSION_TYPE_UNINITIALIZED {
+            break;
+        }
+        let state = match tlv_data.get(TLV_HEADER_LEN..TLV_HEADER_LEN + length) {
+            Some(state) => state,
+            None => {
+                extensions.push(UiExtension::UnparseableExtension);
+                break;
+            }
+        };
+        extensions.push(
+            parse_extension(extension_type, &mut ExtensionReader(state))
+                .unwrap_or(UiExtension::UnparseableExtension),
+        );
+        tlv_data = &tlv_data[TLV_HEADER_LEN + length..];
+    }
+    extensions
+}
+
+fn parse_extension(extension_type: u16, state: &mut ExtensionReader) -> Option<UiExtension> {
+    let extension = match extension_type {
+        EXTENSION_TYPE_TRANSFER_FEE_CONFIG => UiExtension::TransferFeeConfig(UiTransferFeeConfig {
+            transfer_fee_config_authority: state.optional_pubkey()?,
+            withdraw_withheld_authority: state.optional_pubkey()?,
+            withheld_amount: state.u64()?,
+            older_transfer_fee: state.transfer_fee()?,
+            newer_transfer_fee: state.transfer_fee()?,
+        }),
+        EXTENSION_TYPE_TRANSFER_FEE_AMOUNT => UiExtension::TransferFeeAmount(UiTransferFeeAmount {
+            withheld_amount: state.u64()?,
+        }),
+        EXTENSION_TYPE_MEMO_TRANSFER => UiExtension::MemoTransfer(UiMemoTransfer {
+            require_incoming_transfer_memos: state.take::<1>()? != [0],
+        }),
+        EXTENSION_TYPE_INTEREST_BEARING_CONFIG => {
+            UiExtension::InterestBearingConfig(UiInterestBearingConfig {
+                rate_authority: state.optional_pubkey()?,
+                initialization_timestamp: i64::from_le_bytes(state.take()?),
+                pre_update_average_rate: i16::from_le_bytes(state.take()?),
+                last_update_timestamp: i64::from_le_bytes(state.take()?),
+                current_rate: i16::from_le_bytes(state.take()?),
+            })
+        }
+        EXTENSION_TYPE_METADATA_POINTER => UiExtension::MetadataPointer(UiMetadataPointer {
+            authority: state.optional_pubkey()?,
+            metadata_address: state.optional_pubkey()?,
+        }),
+        _ => return None,
+    };
+    state.0.is_empty().then(|| extension)
+}
+
+/// Reads the fields of an extension's state in order
+struct ExtensionReader<'a>(&'a [u8]);
+
+impl ExtensionReader<'_> {
+    fn take<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let bytes = self.0.get(..N)?.try_into().ok()?;
+        self.0 = &self.0[N..];
+        Some(bytes)
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        self.take().map(u64::from_le_bytes)
+    }
+
+    /// An OptionalNonZeroPubkey, where the default pubkey means none
+    fn optional_pubkey(&mut self) -> Option<Option<String>> {
+        let pubkey = Pubkey::new_from_array(self.take::<PUBKEY_BYTES>()?);
+        Some((pubkey != Pubkey::default()).then(|| pubkey.to_string()))
+    }
+
+    fn transfer_fee(&mut self) -> Option<UiTransferFee> {
+        Some(UiTransferFee {
+            epoch: self.u64()?,
+            maximum_fee: self.u64()?,
+            transfer_fee_basis_points: u16::from_le_bytes(self.take()?),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn tlv(extension_type: u16, state: &[u8]) -> Vec<u8> {
+        let mut entry = extension_type.to_le_bytes().to_vec();
+        entry.extend_from_slice(&(state.len() as u16).to_le_bytes());
+        entry.extend_from_slice(state);
+        entry
+    }
+
+    #[test]
+    fn test_parse_extensions() {
+        let authority = Pubkey::new_unique();
+        let mut data = vec![0; ACCOUNT_TYPE_OFFSET];
+        assert!(parse_extensions(&data).is_empty());
+        data.push(1);
+        assert!(parse_extensions(&data).is_empty());
+
+        let mut transfer_fee_config = authority.to_bytes().to_vec();
+        transfer_fee_config.extend_from_slice(&[0; PUBKEY_BYTES]);
+        transfer_fee_config.extend_from_slice(&7u64.to_le_bytes());
+        for (epoch, maximum_fee, basis_points) in [(1u64, 100u64, 50u16), (2, 200, 25)] {
+            transfer_fee_config.extend_from_slice(&epoch.to_le_bytes());
+            transfer_fee_config.extend_from_slice(&maximum_fee.to_le_bytes());
+            transfer_fee_config.extend_from_slice(&basis_points.to_le_bytes());
+        }
+        data.extend(tlv(
+            EXTENSION_TYPE_TRANSFER_FEE_CONFIG,
+            &transfer_fee_config,
+        ));
+
+        let mut interest_bearing_config = vec![0; PUBKEY_BYTES];
+        interest_bearing_config.extend_from_slice(&10i64.to_le_bytes());
+        interest_bearing_config.extend_from_slice(&(-5i16).to_le_bytes());
+        interest_bearing_config.extend_from_slice(&20i64.to_le_bytes());
+        interest_bearing_config.extend_from_slice(&300i16.to_le_bytes());
+        data.extend(tlv(
+            EXTENSION_TYPE_INTEREST_BEARING_CONFIG,
+            &interest_bearing_config,
+        ));
+
+        let mut metadata_pointer = authority.to_bytes().to_vec();
+        metadata_pointer.extend_from_slice(&authority.to_bytes());
+        data.extend(tlv(EXTENSION_TYPE_METADATA_POINTER, &metadata_pointer));
+        // Unknown and malformed extensions
+        data.extend(tlv(42, &[1, 2, 3]));
+        data.extend(tlv(EXTENSION_TYPE_TRANSFER_FEE_AMOUNT, &[1, 2, 3]));
+        // Padding
+        data.extend_from_slice(&[0; 8]);
+
+        assert_eq!(
+            parse_extensions(&data),
+            vec![
+                UiExtension::TransferFeeConfig(UiTransferFeeConfig {
+                    transfer_fee_config_authority: Some(authority.to_string()),
+                    withdraw_withheld_authority: None,
+                    withheld_amount: 7,
+                    older_transfer_fee: UiTransferFee {
+                        epoch: 1,
+                        maximum_fee: 100,
+                        transfer_fee_basis_points: 50,
+                    },
+                    newer_transfer_fee: UiTransferFee {
+                        epoch: 2,
+                        maximum_fee: 200,
+                        transfer_fee_basis_points: 25,
+                    },
+                }),
+                UiExtension::InterestBearingConfig(UiInterestBearingConfig {
+                    rate_authority: None,
+                    initialization_timestamp: 10,
+                    pre_update_average_rate: -5,
+                    last_update_timestamp: 20,
+                    current_rate: 300,
+                }),
+                UiExtension::MetadataPointer(UiMetadataPointer {
+                    authority: Some(authority.to_string()),
+                    metadata_address: Some(authority.to_string()),
+                }),
+                UiExtension::UnparseableExtension,
+                UiExtension::UnparseableExtension,
+            ]
+        );
+
+        let mut data = vec![0; ACCOUNT_TYPE_OFFSET];
+        data.push(2);
+        data.extend(tlv(EXTENSION_TYPE_TRANSFER_FEE_AMOUNT, &9u64.to_le_bytes()));
+        data.extend(tlv(EXTENSION_TYPE_MEMO_TRANSFER, &[1]));
+        // Truncated entry
+        data.extend_from_slice(&EXTENSION_TYPE_MEMO_TRANSFER.to_le_bytes());
+        data.extend_from_slice(&1u16.to_le_bytes());
+        assert_eq!(
+            parse_extensions(&data),
+            vec![
+                UiExtension::TransferFeeAmount(UiTransferFeeAmount { withheld_amount: 9 }),
+                UiExtension::MemoTransfer(UiMemoTransfer {
+                    require_incoming_transfer_memos: true,
+                }),
+                UiExtension::UnparseableExtension,
+            ]
+        );
+    }
+}
@@ -1,12 +1,19 @@
 /// The interface for AccountsDb plugins. A plugin must implement
 /// the AccountsDbPlugin trait to work with the runtime.
 /// In addition, the dynamic library must export a "C" function _create_plugin which
-/// creates the implementation of the plugin.
+/// creates the implementation of the plugin, and a "C" function _plugin_interface_version
+/// which returns the ACCOUNTSDB_PLUGIN_INTERFACE_VERSION it was built with.
 use {
+    solana_sdk::{clock::UnixTimestamp, signature::Signature, transaction::SanitizedTransaction},
+    solana_transaction_status::{Reward, TransactionStatusMeta},
     std::{any::Any, error, io},
     thiserror::Error,
 };
 
+/// The version of the AccountsDbPlugin trait, bumped whenever its methods change. Plugins
+/// built against another version can't be called safely, so they are refused.
+pub const ACCOUNTSDB_PLUGIN_INTERFACE_VERSION: u32 = 2;
+
 impl Eq for ReplicaAccountInfo<'_> {}
 
 #[derive(Clone, PartialEq, Debug)]
@@ -24,6 +31,31 @@ pub enum ReplicaAccountInfoVersions<'a> {
     V0_0_1(&'a ReplicaAccountInfo<'a>),
 }
 
+#[derive(Clone, Debug)]
+pub struct ReplicaTransactionInfo<'a> {
+    pub signature: &'a Signature,
+    pub is_vote: bool,
+    pub transaction: &'a SanitizedTransaction,
+    pub transaction_status_meta: &'a TransactionStatusMeta,
+}
+
+pub enum ReplicaTransactionInfoVersions<'a> {
+    V0_0_1(&'a ReplicaTransactionInfo<'a>),
+}
+
+#[derive(Clone, Debug)]
+pub struct ReplicaBlockInfo<'a> {
+    pub slot: u64,
+    pub blockhash: &'a str,
+    pub rewards: &'a [Reward],
+    pub block_time: Option<UnixTimestamp>,
+    pub block_height: Option<u64>,
+}
+
+pub enum ReplicaBlockInfoVersions<'a> {
+    V0_0_1(&'a ReplicaBlockInfo<'a>),
+}
+
 #[derive(Error, Debug)]
 pub enum AccountsDbPluginError {
     #[error("Error opening config file. Error detail: ({0}).")]
@@ -38,6 +70,12 @@ pub enum AccountsDbPluginError {
     #[error("Error updating slot status. Error message: ({msg})")]
     SlotStatusUpdateError { msg: String },
 
+    #[error("Error notifying transaction. Error message: ({msg})")]
+    TransactionUpdateError { msg: String },
+
+    #[error("Error notifying block metadata. Error message: ({msg})")]
+    BlockMetadataUpdateError { msg: String },
+
     #[error("Plugin-defined custom error. Error message: ({0})")]
     Custom(Box<dyn error::Error + Send + Sync>),
 }
@@ -96,4 +134,36 @@ pub trait AccountsDbPlugin: Any + Send + Sync + std::fmt::Debug {
         parent: Option<u64>,
         status: SlotStatus,
     ) -> Result<()>;
+
+    /// Called when a transaction is committed in a slot, in the order of the entries of the
+    /// slot. Only called if `transaction_notifications_enabled` returns true.
+    fn notify_transaction(
+        &mut self,
+        _transaction: ReplicaTransactionInfoVersions,
+        _slot: u64,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called when the bank of a slot is frozen, with the metadata of its block. Only called if
+    /// `block_metadata_notifications_enabled` returns true.
+    fn notify_block_metadata(&mut self, _blockinfo: ReplicaBlockInfoVersions) -> Result<()> {
+        Ok(())
+    }
+
+    /// Whether the plugin is notified of account updates
+    fn account_data_notifications_enabled(&self) -> bool {
+        true
+    }
+
+    /// Whether the plugin is notified of transactions. The validator records the status of
+    /// every transaction it replays while a plugin asks for them.
+    fn transaction_notifications_enabled(&self) -> bool {
+        false
+    }
+
+    /// Whether the plugin is notified of the metadata of blocks
+    fn block_metadata_notifications_enabled(&self) -> bool {
+        false
+    }
 }
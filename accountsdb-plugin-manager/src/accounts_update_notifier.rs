@@ -149,6 +149,9 @@ impl AccountsUpdateNotifierImpl {
             return;
         }
         for plugin in plugin_manager.plugins.iter_mut() {
+            if !plugin.account_data_notifications_enabled() {
+                continue;
+            }
             let mut measure = Measure::start("accountsdb-plugin-update-account");
             match plugin.update_account(
                 ReplicaAccountInfoVersions::V0_0_1(&account),
@@ -2,7 +2,9 @@
 use {
     libloading::{Library, Symbol},
     log::*,
-    solana_accountsdb_plugin_interface::accountsdb_plugin_interface::AccountsDbPlugin,
+    solana_accountsdb_plugin_interface::accountsdb_plugin_interface::{
+        AccountsDbPlugin, ACCOUNTSDB_PLUGIN_INTERFACE_VERSION,
+    },
     std::error::Error,
 };
 
@@ -30,7 +32,18 @@ impl AccountsDbPluginManager {
         config_file: &str,
     ) -> Result<(), Box<dyn Error>> {
         type PluginConstructor = unsafe fn() -> *mut dyn AccountsDbPlugin;
+        type PluginInterfaceVersion = unsafe fn() -> u32;
         let lib = Library::new(libpath)?;
+        let interface_version: Symbol<PluginInterfaceVersion> =
+            lib.get(b"_plugin_interface_version")?;
+        let interface_version = interface_version();
+        if interface_version != ACCOUNTSDB_PLUGIN_INTERFACE_VERSION {
+            return Err(format!(
+                "plugin {} implements interface version {}, expected {}",
+                libpath, interface_version, ACCOUNTSDB_PLUGIN_INTERFACE_VERSION
+            )
+            .into());
+        }
         let constructor: Symbol<PluginConstructor> = lib.get(b"_create_plugin")?;
         let plugin_raw = constructor();
         let mut plugin = Box::from_raw(plugin_raw);
@@ -40,6 +53,27 @@ impl AccountsDbPluginManager {
         Ok(())
     }
 
+    /// Check if there is any plugin interested in account data
+    pub fn account_data_notifications_enabled(&self) -> bool {
+        self.plugins
+            .iter()
+            .any(|plugin| plugin.account_data_notifications_enabled())
+    }
+
+    /// Check if there is any plugin interested in transaction data
+    pub fn transaction_notifications_enabled(&self) -> bool {
+        self.plugins
+            .iter()
+            .any(|plugin| plugin.transaction_notifications_enabled())
+    }
+
+    /// Check if there is any plugin interested in block metadata
+    pub fn block_metadata_notifications_enabled(&self) -> bool {
+        self.plugins
+            .iter()
+            .any(|plugin| plugin.block_metadata_notifications_enabled())
+    }
+
     /// Unload all plugins and loaded plugin libraries, making sure to fire
     /// their `on_plugin_unload()` methods so they can do any necessary cleanup.
     pub fn unload(&mut self) {
@@ -2,12 +2,16 @@ use {
     crate::{
         accounts_update_notifier::AccountsUpdateNotifierImpl,
         accountsdb_plugin_manager::AccountsDbPluginManager,
-        slot_status_observer::SlotStatusObserver,
+        block_metadata_notifier::BlockMetadataNotifierImpl,
+        slot_status_observer::SlotStatusObserver, transaction_notifier::TransactionNotifierImpl,
     },
     crossbeam_channel::Receiver,
     log::*,
     serde_json,
-    solana_rpc::optimistically_confirmed_bank_tracker::BankNotification,
+    solana_rpc::{
+        optimistically_confirmed_bank_tracker::BankNotification,
+        transaction_notifier_interface::TransactionNotifierLock,
+    },
     solana_runtime::accounts_update_notifier_interface::AccountsUpdateNotifier,
     std::{
         fs::File,
@@ -45,6 +49,7 @@ pub struct AccountsDbPluginService {
     slot_status_observer: SlotStatusObserver,
     plugin_manager: Arc<RwLock<AccountsDbPluginManager>>,
     accounts_update_notifier: AccountsUpdateNotifier,
+    transaction_notifier: Option<TransactionNotifierLock>,
 }
 
 impl AccountsDbPluginService {
@@ -75,18 +80,38 @@ impl AccountsDbPluginService {
             Self::load_plugin(&mut plugin_manager, accountsdb_plugin_config_file)?;
         }
 
+        let transaction_notifications_enabled = plugin_manager.transaction_notifications_enabled();
+        let block_metadata_notifications_enabled =
+            plugin_manager.block_metadata_notifications_enabled();
         let plugin_manager = Arc::new(RwLock::new(plugin_manager));
         let accounts_update_notifier = Arc::new(RwLock::new(AccountsUpdateNotifierImpl::new(
             plugin_manager.clone(),
         )));
-        let slot_status_observer =
-            SlotStatusObserver::new(confirmed_bank_receiver, accounts_update_notifier.clone());
+        let transaction_notifier: Option<TransactionNotifierLock> =
+            if transaction_notifications_enabled {
+                Some(Arc::new(RwLock::new(TransactionNotifierImpl::new(
+                    plugin_manager.clone(),
+                ))))
+            } else {
+                None
+            };
+        let block_metadata_notifier = if block_metadata_notifications_enabled {
+            Some(BlockMetadataNotifierImpl::new(plugin_manager.clone()))
+        } else {
+            None
+        };
+        let slot_status_observer = SlotStatusObserver::new(
+            confirmed_bank_receiver,
+            accounts_update_notifier.clone(),
+            block_metadata_notifier,
+        );
 
         info!("Started AccountsDbPluginService");
         Ok(AccountsDbPluginService {
             slot_status_observer,
             plugin_manager,
             accounts_update_notifier,
+            transaction_notifier,
         })
     }
 
@@ -149,6 +174,12 @@ impl AccountsDbPluginService {
         self.accounts_update_notifier.clone()
     }
 
+    /// The notifier to pass to the TransactionStatusService, if any plugin asks for
+    /// transactions
+    pub fn get_transaction_notifier(&self) -> Option<TransactionNotifierLock> {
+        self.transaction_notifier.clone()
+    }
+
     pub fn join(mut self) -> thread::Result<()> {
         self.slot_status_observer.join()?;
         self.plugin_manager.write().unwrap().unload();
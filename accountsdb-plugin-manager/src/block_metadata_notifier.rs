@@ -0,0 +1,183 @@
+/// Module responsible for notifying plugins of the metadata of frozen blocks
+use {
+    crate::accountsdb_plugin_manager::AccountsDbPluginManager,
+    log::*,
+    solana_accountsdb_plugin_interface::accountsdb_plugin_interface::{
+        ReplicaBlockInfo, ReplicaBlockInfoVersions,
+    },
+    solana_measure::measure::Measure,
+    solana_metrics::*,
+    solana_runtime::bank::Bank,
+    solana_transaction_status::Reward,
+    std::sync::{Arc, RwLock},
+};
+
+/// Notifies the plugins of the blocks of the frozen banks reported by the bank notifications
+/// that also feed the RPC subscriptions. Only created if a plugin asks for block metadata.
+#[derive(Debug)]
+pub(crate) struct BlockMetadataNotifierImpl {
+    plugin_manager: Arc<RwLock<AccountsDbPluginManager>>,
+}
+
+impl BlockMetadataNotifierImpl {
+    pub fn new(plugin_manager: Arc<RwLock<AccountsDbPluginManager>>) -> Self {
+        Self { plugin_manager }
+    }
+
+    pub fn notify_block_metadata(&self, bank: &Bank) {
+        // The rewards are formatted and the plugins locked for writing only for the plugins
+        // that still ask for block metadata
+        if !self
+            .plugin_manager
+            .read()
+            .unwrap()
+            .block_metadata_notifications_enabled()
+        {
+            return;
+        }
+
+        let slot = bank.slot();
+        let blockhash = bank.last_blockhash().to_string();
+        let rewards: Vec<_> = bank
+            .rewards
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(pubkey, reward_info)| Reward {
+                pubkey: pubkey.to_string(),
+                lamports: reward_info.lamports,
+                post_balance: reward_info.post_balance,
+                reward_type: Some(reward_info.reward_type),
+                commission: reward_info.commission,
+            })
+            .collect();
+        let block_info = ReplicaBlockInfo {
+            slot,
+            blockhash: &blockhash,
+            rewards: &rewards,
+            block_time: Some(bank.clock().unix_timestamp),
+            block_height: Some(bank.block_height()),
+        };
+
+        let mut plugin_manager = self.plugin_manager.write().unwrap();
+        for plugin in plugin_manager.plugins.iter_mut() {
+            if !plugin.block_metadata_notifications_enabled() {
+                continue;
+            }
+            let mut measure = Measure::start("accountsdb-plugin-update-block-metadata");
+            match plugin.notify_block_metadata(ReplicaBlockInfoVersions::V0_0_1(&block_info)) {
+                Err(err) => {
+                    error!(
+                        "Failed to update block metadata at slot {}, error: {} to plugin {}",
+                        slot,
+                        err,
+                        plugin.name()
+                    )
+                }
+                Ok(_) => {
+                    trace!(
+                        "Successfully updated block metadata at slot {} to plugin {}",
+                        slot,
+                        plugin.name()
+                    );
+                }
+            }
+            measure.stop();
+            inc_new_counter_debug!(
+                "accountsdb-plugin-update-block-metadata-us",
+                measure.as_us() as usize,
+                1000,
+                1000
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_accountsdb_plugin_interface::accountsdb_plugin_interface::{
+            AccountsDbPlugin, ReplicaAccountInfoVersions, Result, SlotStatus,
+        },
+        solana_runtime::genesis_utils::create_genesis_config,
+        std::sync::Mutex,
+    };
+
+    /// Records the slot, blockhash and block height of the blocks it is notified of
+    #[derive(Debug, Default)]
+    struct TestPlugin {
+        block_metadata_notifications_enabled: bool,
+        blocks: Arc<Mutex<Vec<(u64, String, Option<u64>)>>>,
+    }
+
+    impl AccountsDbPlugin for TestPlugin {
+        fn name(&self) -> &'static str {
+            "test"
+        }
+
+        fn update_account(
+            &mut self,
+            _account: ReplicaAccountInfoVersions,
+            _slot: u64,
+            _is_startup: bool,
+        ) -> Result<()> {
+            Ok(())
+        }
+
+        fn notify_end_of_startup(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        fn update_slot_status(
+            &mut self,
+            _slot: u64,
+            _parent: Option<u64>,
+            _status: SlotStatus,
+        ) -> Result<()> {
+            Ok(())
+        }
+
+        fn notify_block_metadata(&mut self, blockinfo: ReplicaBlockInfoVersions) -> Result<()> {
+            let ReplicaBlockInfoVersions::V0_0_1(block_info) = blockinfo;
+            self.blocks.lock().unwrap().push((
+                block_info.slot,
+                block_info.blockhash.to_string(),
+                block_info.block_height,
+            ));
+            Ok(())
+        }
+
+        fn block_metadata_notifications_enabled(&self) -> bool {
+            self.block_metadata_notifications_enabled
+        }
+    }
+
+    #[test]
+    fn test_notify_block_metadata() {
+        let bank = Bank::new_for_tests(&create_genesis_config(10_000).genesis_config);
+        let plugin = TestPlugin {
+            block_metadata_notifications_enabled: true,
+            ..TestPlugin::default()
+        };
+        let blocks = plugin.blocks.clone();
+        let other_plugin = TestPlugin::default();
+        let other_blocks = other_plugin.blocks.clone();
+        let mut plugin_manager = AccountsDbPluginManager::new();
+        plugin_manager.plugins.push(Box::new(plugin));
+        plugin_manager.plugins.push(Box::new(other_plugin));
+        let notifier = BlockMetadataNotifierImpl::new(Arc::new(RwLock::new(plugin_manager)));
+
+        notifier.notify_block_metadata(&bank);
+        assert_eq!(
+            *blocks.lock().unwrap(),
+            vec![(
+                bank.slot(),
+                bank.last_blockhash().to_string(),
+                Some(bank.block_height())
+            )]
+        );
+        // Plugins that don't ask for block metadata aren't notified
+        assert!(other_blocks.lock().unwrap().is_empty());
+    }
+}
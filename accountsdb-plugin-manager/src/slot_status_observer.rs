@@ -1,4 +1,5 @@
 use {
+    crate::block_metadata_notifier::BlockMetadataNotifierImpl,
     crossbeam_channel::Receiver,
     solana_rpc::optimistically_confirmed_bank_tracker::BankNotification,
     solana_runtime::accounts_update_notifier_interface::AccountsUpdateNotifier,
@@ -21,6 +22,7 @@ impl SlotStatusObserver {
     pub fn new(
         bank_notification_receiver: Receiver<BankNotification>,
         accounts_update_notifier: AccountsUpdateNotifier,
+        block_metadata_notifier: Option<BlockMetadataNotifierImpl>,
     ) -> Self {
         let exit_updated_slot_server = Arc::new(AtomicBool::new(false));
 
@@ -29,6 +31,7 @@ impl SlotStatusObserver {
                 bank_notification_receiver,
                 exit_updated_slot_server.clone(),
                 accounts_update_notifier,
+                block_metadata_notifier,
             )),
             exit_updated_slot_server,
         }
@@ -46,6 +49,7 @@ impl SlotStatusObserver {
         bank_notification_receiver: Receiver<BankNotification>,
         exit: Arc<AtomicBool>,
         accounts_update_notifier: AccountsUpdateNotifier,
+        block_metadata_notifier: Option<BlockMetadataNotifierImpl>,
     ) -> JoinHandle<()> {
         Builder::new()
             .name("bank_notification_receiver".to_string())
@@ -64,6 +68,9 @@ impl SlotStatusObserver {
                                     .read()
                                     .unwrap()
                                     .notify_slot_processed(bank.slot(), Some(bank.parent_slot()));
+                                if let Some(block_metadata_notifier) = &block_metadata_notifier {
+                                    block_metadata_notifier.notify_block_metadata(&bank);
+                                }
                             }
                             BankNotification::Root(bank) => {
                                 accounts_update_notifier
@@ -24,6 +24,24 @@ CREATE TABLE slot (
     updated_on TIMESTAMP NOT NULL
 );
 
+-- The table storing transactions and their statuses
+CREATE TABLE transaction (
+    signature BYTEA NOT NULL,
+    slot BIGINT NOT NULL,
+    is_vote BOOL NOT NULL,
+    err TEXT,
+    fee BIGINT NOT NULL,
+    account_keys BYTEA[] NOT NULL,
+    recent_blockhash BYTEA NOT NULL,
+    pre_balances BIGINT[] NOT NULL,
+    post_balances BIGINT[] NOT NULL,
+    log_messages TEXT[],
+    updated_on TIMESTAMP NOT NULL,
+    CONSTRAINT transaction_pk PRIMARY KEY (slot, signature)
+);
+
+CREATE INDEX transaction_signature ON transaction (signature);
+
 /**
  * The following is for keeping historical data for accounts and is not required for plugin to work.
  */
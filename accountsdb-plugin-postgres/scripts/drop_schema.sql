@@ -6,4 +6,5 @@ DROP TRIGGER account_update_trigger;
 DROP FUNCTION audit_account_update;
 DROP TABLE account_audit;
 DROP TABLE account;
+DROP TABLE transaction;
 DROP TABLE slot;
@@ -5,13 +5,15 @@ use {
     crate::{
         accounts_selector::AccountsSelector,
         postgres_client::{ParallelPostgresClient, PostgresClientBuilder},
+        transaction_selector::TransactionSelector,
     },
     bs58,
     log::*,
     serde_derive::{Deserialize, Serialize},
     serde_json,
     solana_accountsdb_plugin_interface::accountsdb_plugin_interface::{
-        AccountsDbPlugin, AccountsDbPluginError, ReplicaAccountInfoVersions, Result, SlotStatus,
+        AccountsDbPlugin, AccountsDbPluginError, ReplicaAccountInfoVersions,
+        ReplicaTransactionInfoVersions, Result, SlotStatus, ACCOUNTSDB_PLUGIN_INTERFACE_VERSION,
     },
     solana_metrics::*,
     std::{fs::File, io::Read},
@@ -22,6 +24,7 @@ use {
 pub struct AccountsDbPluginPostgres {
     client: Option<ParallelPostgresClient>,
     accounts_selector: Option<AccountsSelector>,
+    transaction_selector: Option<TransactionSelector>,
 }
 
 impl std::fmt::Debug for AccountsDbPluginPostgres {
@@ -72,12 +75,20 @@ impl AccountsDbPlugin for AccountsDbPluginPostgres {
     /// "accounts_selector" : {
     ///     "accounts" : \["*"\],
     /// }
+    /// The optional `transaction_selector` section enables writing transactions and their
+    /// statuses into the `transaction` table, see `scripts/create_schema.sql`. Transactions
+    /// are only written when this section is present.
+    /// "transaction_selector" : {
+    ///     "mentions" : \["pubkey-1", "pubkey-2", ..., "pubkey-n"\],
+    /// }
+    /// Transactions mentioning any of the addresses are selected. "*" selects all transactions
+    /// and "all_votes" selects all vote transactions.
     /// "host" specifies the PostgreSQL server.
     /// "user" specifies the PostgreSQL user.
     /// "threads" optional, specifies the number of worker threads for the plugin. A thread
     /// maintains a PostgreSQL connection to the server. The default is 10.
     /// "batch_size" optional, specifies the batch size of bulk insert when the AccountsDb is created
-    /// from restoring a snapshot. The default is "10".
+    /// from restoring a snapshot, and of the bulk insert of transactions. The default is "10".
     /// # Examples
     /// {
     ///    "libpath": "/home/solana/target/release/libsolana_accountsdb_plugin_postgres.so",
@@ -101,6 +112,7 @@ impl AccountsDbPlugin for AccountsDbPluginPostgres {
 
         let result: serde_json::Value = serde_json::from_str(&contents).unwrap();
         self.accounts_selector = Some(Self::create_accounts_selector_from_config(&result));
+        self.transaction_selector = Self::create_transaction_selector_from_config(&result);
 
         let result: serde_json::Result<AccountsDbPluginPostgresConfig> =
             serde_json::from_str(&contents);
@@ -263,6 +275,50 @@ impl AccountsDbPlugin for AccountsDbPluginPostgres {
         }
         Ok(())
     }
+
+    fn notify_transaction(
+        &mut self,
+        transaction_info: ReplicaTransactionInfoVersions,
+        slot: u64,
+    ) -> Result<()> {
+        match &mut self.client {
+            None => {
+                return Err(AccountsDbPluginError::Custom(Box::new(
+                    AccountsDbPluginPostgresError::DataStoreConnectionError {
+                        msg: "There is no connection to the PostgreSQL database.".to_string(),
+                    },
+                )));
+            }
+            Some(client) => match transaction_info {
+                ReplicaTransactionInfoVersions::V0_0_1(transaction_info) => {
+                    if let Some(transaction_selector) = &self.transaction_selector {
+                        if !transaction_selector.is_transaction_selected(
+                            transaction_info.is_vote,
+                            transaction_info.transaction.message().account_keys_iter(),
+                        ) {
+                            return Ok(());
+                        }
+                    } else {
+                        return Ok(());
+                    }
+
+                    let result = client.log_transaction_info(transaction_info, slot);
+
+                    if let Err(err) = result {
+                        return Err(AccountsDbPluginError::TransactionUpdateError {
+                            msg: format!("Failed to persist the transaction info to the PostgreSQL database. Error: {:?}", err)
+                        });
+                    }
+                }
+            },
+        }
+
+        Ok(())
+    }
+
+    fn transaction_notifications_enabled(&self) -> bool {
+        self.transaction_selector.is_some()
+    }
 }
 
 impl AccountsDbPluginPostgres {
@@ -298,10 +354,34 @@ impl AccountsDbPluginPostgres {
         }
     }
 
+    fn create_transaction_selector_from_config(
+        config: &serde_json::Value,
+    ) -> Option<TransactionSelector> {
+        let transaction_selector = &config["transaction_selector"];
+
+        if transaction_selector.is_null() {
+            None
+        } else {
+            let mentions = &transaction_selector["mentions"];
+            let mentions: Vec<String> = if mentions.is_array() {
+                mentions
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|val| val.as_str().unwrap().to_string())
+                    .collect()
+            } else {
+                Vec::default()
+            };
+            Some(TransactionSelector::new(&mentions))
+        }
+    }
+
     pub fn new() -> Self {
         AccountsDbPluginPostgres {
             client: None,
             accounts_selector: None,
+            transaction_selector: None,
         }
     }
 }
@@ -317,6 +397,12 @@ pub unsafe extern "C" fn _create_plugin() -> *mut dyn AccountsDbPlugin {
     Box::into_raw(plugin)
 }
 
+#[no_mangle]
+/// Returns the version of the plugin interface the plugin was built with.
+pub extern "C" fn _plugin_interface_version() -> u32 {
+    ACCOUNTSDB_PLUGIN_INTERFACE_VERSION
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
     use {super::*, serde_json};
@@ -330,4 +416,22 @@ pub(crate) mod tests {
         let config: serde_json::Value = serde_json::from_str(config).unwrap();
         AccountsDbPluginPostgres::create_accounts_selector_from_config(&config);
     }
+
+    #[test]
+    fn test_transaction_selector_from_config() {
+        let config: serde_json::Value = serde_json::from_str("{}").unwrap();
+        assert!(
+            AccountsDbPluginPostgres::create_transaction_selector_from_config(&config).is_none()
+        );
+
+        let config = "{\"transaction_selector\" : { \
+           \"mentions\" : [\"all_votes\", \"9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin\"] \
+        }}";
+        let config: serde_json::Value = serde_json::from_str(config).unwrap();
+        let selector =
+            AccountsDbPluginPostgres::create_transaction_selector_from_config(&config).unwrap();
+        assert!(selector.select_all_vote_transactions);
+        assert!(!selector.select_all_transactions);
+        assert_eq!(selector.mentioned_addresses.len(), 1);
+    }
 }
@@ -1,3 +1,4 @@
 pub mod accounts_selector;
 pub mod accountsdb_plugin_postgres;
 pub mod postgres_client;
+pub mod transaction_selector;
@@ -8,14 +8,17 @@ use {
     chrono::Utc,
     crossbeam_channel::{bounded, Receiver, RecvTimeoutError, Sender},
     log::*,
-    postgres::{Client, NoTls, Statement},
+    postgres::{
+        binary_copy::BinaryCopyInWriter, types::Type, Client, GenericClient, NoTls, Statement,
+    },
     solana_accountsdb_plugin_interface::accountsdb_plugin_interface::{
-        AccountsDbPluginError, ReplicaAccountInfo, SlotStatus,
+        AccountsDbPluginError, ReplicaAccountInfo, ReplicaTransactionInfo, SlotStatus,
     },
     solana_measure::measure::Measure,
     solana_metrics::*,
     solana_sdk::timing::AtomicInterval,
     std::{
+        collections::{BTreeMap, HashMap, HashSet},
         sync::{
             atomic::{AtomicBool, AtomicUsize, Ordering},
             Arc, Mutex,
@@ -33,11 +36,38 @@ const DEFAULT_POSTGRES_PORT: u16 = 5432;
 const DEFAULT_THREADS_COUNT: usize = 100;
 const DEFAULT_ACCOUNTS_INSERT_BATCH_SIZE: usize = 10;
 const ACCOUNT_COLUMN_COUNT: usize = 9;
+const TRANSACTION_COLUMN_COUNT: usize = 11;
+/// Attempts at writing the transactions of a slot before the worker gives up
+const MAX_WRITE_ATTEMPTS: usize = 3;
+const WRITE_RETRY_DELAY: Duration = Duration::from_millis(500);
+/// The column types of the `transaction` table, in the order of its columns
+const TRANSACTION_COLUMN_TYPES: [Type; TRANSACTION_COLUMN_COUNT] = [
+    Type::BYTEA,
+    Type::INT8,
+    Type::BOOL,
+    Type::TEXT,
+    Type::INT8,
+    Type::BYTEA_ARRAY,
+    Type::BYTEA,
+    Type::INT8_ARRAY,
+    Type::INT8_ARRAY,
+    Type::TEXT_ARRAY,
+    Type::TIMESTAMP,
+];
 
 struct PostgresSqlClientWrapper {
     client: Client,
     update_account_stmt: Statement,
     bulk_account_insert_stmt: Statement,
+    /// Prepared on the first transaction, so that the transaction table is only required
+    /// when transactions are selected
+    transaction_stmts: Option<TransactionStatements>,
+}
+
+struct TransactionStatements {
+    /// Moves the rows copied into the `transaction_copy` temporary table into the
+    /// `transaction` table
+    insert_copied_transactions_stmt: Statement,
 }
 
 pub struct SimplePostgresClient {
@@ -82,6 +112,50 @@ impl DbAccountInfo {
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct DbTransaction {
+    pub signature: Vec<u8>,
+    pub slot: i64,
+    pub is_vote: bool,
+    pub err: Option<String>,
+    pub fee: i64,
+    pub account_keys: Vec<Vec<u8>>,
+    pub recent_blockhash: Vec<u8>,
+    pub pre_balances: Vec<i64>,
+    pub post_balances: Vec<i64>,
+    pub log_messages: Option<Vec<String>>,
+}
+
+impl DbTransaction {
+    fn new(transaction_info: &ReplicaTransactionInfo, slot: u64) -> Self {
+        let message = transaction_info.transaction.message();
+        let meta = transaction_info.transaction_status_meta;
+        Self {
+            signature: transaction_info.signature.as_ref().to_vec(),
+            slot: slot as i64,
+            is_vote: transaction_info.is_vote,
+            err: meta.status.as_ref().err().map(|err| err.to_string()),
+            fee: meta.fee as i64,
+            account_keys: message
+                .account_keys_iter()
+                .map(|key| key.as_ref().to_vec())
+                .collect(),
+            recent_blockhash: message.recent_blockhash().as_ref().to_vec(),
+            pre_balances: meta
+                .pre_balances
+                .iter()
+                .map(|balance| *balance as i64)
+                .collect(),
+            post_balances: meta
+                .post_balances
+                .iter()
+                .map(|balance| *balance as i64)
+                .collect(),
+            log_messages: meta.log_messages.clone(),
+        }
+    }
+}
+
 pub trait ReadableAccountInfo: Sized {
     fn pubkey(&self) -> &[u8];
     fn owner(&self) -> &[u8];
@@ -163,14 +237,21 @@ pub trait PostgresClient {
         is_startup: bool,
     ) -> Result<(), AccountsDbPluginError>;
 
+    /// Writes the status of `slot`, after `transactions` of the slot
     fn update_slot_status(
         &mut self,
         slot: u64,
         parent: Option<u64>,
         status: SlotStatus,
+        transactions: &[DbTransaction],
     ) -> Result<(), AccountsDbPluginError>;
 
     fn notify_end_of_startup(&mut self) -> Result<(), AccountsDbPluginError>;
+
+    fn log_transactions(
+        &mut self,
+        transactions: &[DbTransaction],
+    ) -> Result<(), AccountsDbPluginError>;
 }
 
 impl SimplePostgresClient {
@@ -399,64 +480,155 @@ impl SimplePostgresClient {
         Ok(())
     }
 
-    pub fn new(config: &AccountsDbPluginPostgresConfig) -> Result<Self, AccountsDbPluginError> {
-        info!("Creating SimplePostgresClient...");
-        let mut client = Self::connect_to_db(config)?;
-        let bulk_account_insert_stmt =
-            Self::build_bulk_account_insert_statement(&mut client, config)?;
-        let update_account_stmt = Self::build_single_account_upsert_statement(&mut client, config)?;
-
-        let batch_size = config
-            .batch_size
-            .unwrap_or(DEFAULT_ACCOUNTS_INSERT_BATCH_SIZE);
-        info!("Created SimplePostgresClient.");
-        Ok(Self {
-            batch_size,
-            pending_account_updates: Vec::with_capacity(batch_size),
-            client: Mutex::new(PostgresSqlClientWrapper {
-                client,
-                update_account_stmt,
-                bulk_account_insert_stmt,
-            }),
+    fn build_transaction_statements(
+        client: &mut Client,
+    ) -> Result<TransactionStatements, AccountsDbPluginError> {
+        let schema_error = |err: postgres::Error| {
+            AccountsDbPluginError::Custom(Box::new(
+                AccountsDbPluginPostgresError::DataSchemaError {
+                    msg: format!(
+                        "Error in preparing for the transaction update PostgreSQL database. \
+                        Is the transaction table created? Error: {}",
+                        err
+                    ),
+                },
+            ))
+        };
+        // COPY can't resolve conflicts with rows written before a restart, so rows are
+        // copied into a temporary table first and moved from there
+        client
+            .batch_execute(
+                "CREATE TEMPORARY TABLE transaction_copy (LIKE transaction) ON COMMIT DELETE ROWS",
+            )
+            .map_err(schema_error)?;
+        let insert_copied_transactions_stmt = client
+            .prepare(
+                "INSERT INTO transaction AS txn SELECT * FROM transaction_copy \
+                ON CONFLICT (slot, signature) DO UPDATE SET is_vote=excluded.is_vote, err=excluded.err, \
+                fee=excluded.fee, account_keys=excluded.account_keys, recent_blockhash=excluded.recent_blockhash, \
+                pre_balances=excluded.pre_balances, post_balances=excluded.post_balances, \
+                log_messages=excluded.log_messages, updated_on=excluded.updated_on",
+            )
+            .map_err(schema_error)?;
+        Ok(TransactionStatements {
+            insert_copied_transactions_stmt,
         })
     }
-}
 
-impl PostgresClient for SimplePostgresClient {
-    fn update_account(
+    /// Writes `transactions`, then the status of their slot if given, in one database
+    /// transaction, so that a slot is never marked confirmed or rooted without its
+    /// transactions. Failed attempts leave nothing behind and are retried; the rows are
+    /// only lost if the last attempt fails too, which the error reports.
+    fn write_slot(
         &mut self,
-        account: DbAccountInfo,
-        is_startup: bool,
+        transactions: &[DbTransaction],
+        slot_status: Option<(u64, Option<u64>, &SlotStatus)>,
     ) -> Result<(), AccountsDbPluginError> {
-        trace!(
-            "Updating account {} with owner {} at slot {}",
-            bs58::encode(account.pubkey()).into_string(),
-            bs58::encode(account.owner()).into_string(),
-            account.slot,
-        );
-        if !is_startup {
-            return self.upsert_account(&account);
+        let mut attempt = 1;
+        loop {
+            match self.try_write_slot(transactions, slot_status) {
+                Ok(()) => return Ok(()),
+                Err(err) if attempt < MAX_WRITE_ATTEMPTS => {
+                    warn!(
+                        "Failed to write {} transactions, attempt {}: {}",
+                        transactions.len(),
+                        attempt,
+                        err
+                    );
+                    attempt += 1;
+                    sleep(WRITE_RETRY_DELAY);
+                }
+                Err(err) => {
+                    let msg = format!(
+                        "Failed to persist {} transactions to the PostgreSQL database. Error: {:?}",
+                        transactions.len(),
+                        err
+                    );
+                    error!("{}", msg);
+                    return Err(AccountsDbPluginError::TransactionUpdateError { msg });
+                }
+            }
         }
-        self.insert_accounts_in_batch(account)
     }
 
-    fn update_slot_status(
+    fn try_write_slot(
         &mut self,
+        transactions: &[DbTransaction],
+        slot_status: Option<(u64, Option<u64>, &SlotStatus)>,
+    ) -> Result<(), AccountsDbPluginError> {
+        let mut measure = Measure::start("accountsdb-plugin-postgres-log-transactions");
+        let client = self.client.get_mut().unwrap();
+        if !transactions.is_empty() && client.transaction_stmts.is_none() {
+            client.transaction_stmts =
+                Some(Self::build_transaction_statements(&mut client.client)?);
+        }
+        let write_error = |err: postgres::Error| AccountsDbPluginError::TransactionUpdateError {
+            msg: err.to_string(),
+        };
+        let mut db_transaction = client.client.transaction().map_err(write_error)?;
+        if !transactions.is_empty() {
+            let updated_on = Utc::now().naive_utc();
+            let writer = db_transaction
+                .copy_in("COPY transaction_copy FROM STDIN BINARY")
+                .map_err(write_error)?;
+            let mut writer = BinaryCopyInWriter::new(writer, &TRANSACTION_COLUMN_TYPES);
+            for transaction in transactions {
+                writer
+                    .write(&[
+                        &transaction.signature,
+                        &transaction.slot,
+                        &transaction.is_vote,
+                        &transaction.err,
+                        &transaction.fee,
+                        &transaction.account_keys,
+                        &transaction.recent_blockhash,
+                        &transaction.pre_balances,
+                        &transaction.post_balances,
+                        &transaction.log_messages,
+                        &updated_on,
+                    ])
+                    .map_err(write_error)?;
+            }
+            writer.finish().map_err(write_error)?;
+            let stmts = client.transaction_stmts.as_ref().unwrap();
+            db_transaction
+                .execute(&stmts.insert_copied_transactions_stmt, &[])
+                .map_err(write_error)?;
+        }
+        if let Some((slot, parent, status)) = slot_status {
+            Self::upsert_slot_status(&mut db_transaction, slot, parent, status)?;
+        }
+        db_transaction.commit().map_err(write_error)?;
+        measure.stop();
+        inc_new_counter_debug!(
+            "accountsdb-plugin-postgres-log-transactions-us",
+            measure.as_us() as usize,
+            10000,
+            10000
+        );
+        inc_new_counter_debug!(
+            "accountsdb-plugin-postgres-log-transactions-count",
+            transactions.len(),
+            10000,
+            10000
+        );
+        Ok(())
+    }
+
+    fn upsert_slot_status(
+        client: &mut impl GenericClient,
         slot: u64,
         parent: Option<u64>,
-        status: SlotStatus,
+        status: &SlotStatus,
     ) -> Result<(), AccountsDbPluginError> {
-        info!("Updating slot {:?} at with status {:?}", slot, status);
-
         let slot = slot as i64; // postgres only supports i64
         let parent = parent.map(|parent| parent as i64);
         let updated_on = Utc::now().naive_utc();
         let status_str = status.as_str();
-        let client = self.client.get_mut().unwrap();
 
         let result = match parent {
                         Some(parent) => {
-                            client.client.execute(
+                            client.execute(
                                 "INSERT INTO slot (slot, parent, status, updated_on) \
                                 VALUES ($1, $2, $3, $4) \
                                 ON CONFLICT (slot) DO UPDATE SET parent=$2, status=$3, updated_on=$4",
@@ -469,7 +641,7 @@ impl PostgresClient for SimplePostgresClient {
                             )
                         }
                         None => {
-                            client.client.execute(
+                            client.execute(
                                 "INSERT INTO slot (slot, status, updated_on) \
                                 VALUES ($1, $2, $3) \
                                 ON CONFLICT (slot) DO UPDATE SET status=$2, updated_on=$3",
@@ -499,9 +671,75 @@ impl PostgresClient for SimplePostgresClient {
         Ok(())
     }
 
+    pub fn new(config: &AccountsDbPluginPostgresConfig) -> Result<Self, AccountsDbPluginError> {
+        info!("Creating SimplePostgresClient...");
+        let mut client = Self::connect_to_db(config)?;
+        let bulk_account_insert_stmt =
+            Self::build_bulk_account_insert_statement(&mut client, config)?;
+        let update_account_stmt = Self::build_single_account_upsert_statement(&mut client, config)?;
+
+        let batch_size = config
+            .batch_size
+            .unwrap_or(DEFAULT_ACCOUNTS_INSERT_BATCH_SIZE);
+        info!("Created SimplePostgresClient.");
+        Ok(Self {
+            batch_size,
+            pending_account_updates: Vec::with_capacity(batch_size),
+            client: Mutex::new(PostgresSqlClientWrapper {
+                client,
+                update_account_stmt,
+                bulk_account_insert_stmt,
+                transaction_stmts: None,
+            }),
+        })
+    }
+}
+
+impl PostgresClient for SimplePostgresClient {
+    fn update_account(
+        &mut self,
+        account: DbAccountInfo,
+        is_startup: bool,
+    ) -> Result<(), AccountsDbPluginError> {
+        trace!(
+            "Updating account {} with owner {} at slot {}",
+            bs58::encode(account.pubkey()).into_string(),
+            bs58::encode(account.owner()).into_string(),
+            account.slot,
+        );
+        if !is_startup {
+            return self.upsert_account(&account);
+        }
+        self.insert_accounts_in_batch(account)
+    }
+
+    fn update_slot_status(
+        &mut self,
+        slot: u64,
+        parent: Option<u64>,
+        status: SlotStatus,
+        transactions: &[DbTransaction],
+    ) -> Result<(), AccountsDbPluginError> {
+        info!(
+            "Updating slot {:?} at with status {:?} and {} transactions",
+            slot,
+            status,
+            transactions.len()
+        );
+        self.write_slot(transactions, Some((slot, parent, &status)))
+    }
+
     fn notify_end_of_startup(&mut self) -> Result<(), AccountsDbPluginError> {
         self.flush_buffered_writes()
     }
+
+    fn log_transactions(
+        &mut self,
+        transactions: &[DbTransaction],
+    ) -> Result<(), AccountsDbPluginError> {
+        trace!("Logging {} transactions", transactions.len());
+        self.write_slot(transactions, None)
+    }
 }
 
 struct UpdateAccountRequest {
@@ -513,11 +751,18 @@ struct UpdateSlotRequest {
     slot: u64,
     parent: Option<u64>,
     slot_status: SlotStatus,
+    /// Written along with the status, by the same worker
+    transactions: Vec<DbTransaction>,
+}
+
+struct LogTransactionsRequest {
+    transactions: Vec<DbTransaction>,
 }
 
 enum DbWorkItem {
     UpdateAccount(UpdateAccountRequest),
     UpdateSlot(UpdateSlotRequest),
+    LogTransactions(LogTransactionsRequest),
 }
 
 impl PostgresClientWorker {
@@ -563,8 +808,12 @@ impl PostgresClientWorker {
                             request.slot,
                             request.parent,
                             request.slot_status,
+                            &request.transactions,
                         )?;
                     }
+                    DbWorkItem::LogTransactions(request) => {
+                        self.client.log_transactions(&request.transactions)?;
+                    }
                 },
                 Err(err) => match err {
                     RecvTimeoutError::Timeout => {
@@ -586,6 +835,83 @@ impl PostgresClientWorker {
         Ok(())
     }
 }
+
+/// The transactions of slots that aren't confirmed yet. They are written along with the
+/// status of their slot once it is confirmed, or of the root they are an ancestor of, so that
+/// the `transaction` table only holds transactions of confirmed slots. Those of slots abandoned
+/// below a root are dropped.
+#[derive(Default)]
+struct PendingTransactions {
+    by_slot: BTreeMap<u64, Vec<DbTransaction>>,
+    /// Parents of the slots above the last root, to tell the ancestors of a new root from the
+    /// slots of abandoned forks
+    parents: HashMap<u64, u64>,
+    /// Confirmed slots above the last root, whose late transactions are written right away
+    confirmed_slots: HashSet<u64>,
+    last_root: Option<u64>,
+}
+
+impl PendingTransactions {
+    /// Holds `transaction` until its slot is confirmed, or returns it if it already is
+    fn add(&mut self, transaction: DbTransaction) -> Option<DbTransaction> {
+        let slot = transaction.slot as u64;
+        if self.confirmed_slots.contains(&slot) || self.last_root.map_or(false, |root| slot <= root)
+        {
+            return Some(transaction);
+        }
+        self.by_slot.entry(slot).or_default().push(transaction);
+        None
+    }
+
+    /// The transactions to write along with the `status` of `slot`
+    fn take(&mut self, slot: u64, parent: Option<u64>, status: &SlotStatus) -> Vec<DbTransaction> {
+        let last_root = self.last_root;
+        if let Some(parent) = parent {
+            if last_root.map_or(true, |root| root < slot) {
+                self.parents.insert(slot, parent);
+            }
+        }
+        match status {
+            SlotStatus::Processed => vec![],
+            SlotStatus::Confirmed => {
+                self.confirmed_slots.insert(slot);
+                self.by_slot.remove(&slot).unwrap_or_default()
+            }
+            SlotStatus::Rooted => {
+                if last_root.map_or(false, |root| slot <= root) {
+                    return self.by_slot.remove(&slot).unwrap_or_default();
+                }
+                // The ancestors of the root above the last one are rooted along with it
+                let mut rooted_slots = vec![slot];
+                while let Some(parent) = self
+                    .parents
+                    .get(rooted_slots.last().unwrap())
+                    .filter(|parent| last_root.map_or(true, |root| root < **parent))
+                {
+                    rooted_slots.push(*parent);
+                }
+                let transactions = rooted_slots
+                    .iter()
+                    .rev()
+                    .flat_map(|slot| self.by_slot.remove(slot).unwrap_or_default())
+                    .collect();
+
+                // Every other slot up to the root is on an abandoned fork
+                self.last_root = Some(slot);
+                let pending = self.by_slot.split_off(&(slot + 1));
+                let abandoned = std::mem::replace(&mut self.by_slot, pending);
+                inc_new_counter_info!(
+                    "accountsdb-plugin-postgres-abandoned-transactions",
+                    abandoned.values().map(Vec::len).sum::<usize>()
+                );
+                self.confirmed_slots.retain(|confirmed| *confirmed > slot);
+                self.parents.retain(|child, _| *child > slot);
+                transactions
+            }
+        }
+    }
+}
+
 pub struct ParallelPostgresClient {
     workers: Vec<JoinHandle<Result<(), AccountsDbPluginError>>>,
     exit_worker: Arc<AtomicBool>,
@@ -594,6 +920,7 @@ pub struct ParallelPostgresClient {
     initialized_worker_count: Arc<AtomicUsize>,
     sender: Sender<DbWorkItem>,
     last_report: AtomicInterval,
+    pending_transactions: PendingTransactions,
 }
 
 impl ParallelPostgresClient {
@@ -646,6 +973,7 @@ impl ParallelPostgresClient {
             startup_done_count,
             initialized_worker_count,
             sender,
+            pending_transactions: PendingTransactions::default(),
         })
     }
 
@@ -722,10 +1050,14 @@ impl ParallelPostgresClient {
         parent: Option<u64>,
         status: SlotStatus,
     ) -> Result<(), AccountsDbPluginError> {
+        // A slot's transactions go through the worker writing its status, which writes them
+        // first
+        let transactions = self.pending_transactions.take(slot, parent, &status);
         if let Err(err) = self.sender.send(DbWorkItem::UpdateSlot(UpdateSlotRequest {
             slot,
             parent,
             slot_status: status,
+            transactions,
         })) {
             return Err(AccountsDbPluginError::SlotStatusUpdateError {
                 msg: format!("Failed to update the slot {:?}, error: {:?}", slot, err),
@@ -734,6 +1066,31 @@ impl ParallelPostgresClient {
         Ok(())
     }
 
+    pub fn log_transaction_info(
+        &mut self,
+        transaction_info: &ReplicaTransactionInfo,
+        slot: u64,
+    ) -> Result<(), AccountsDbPluginError> {
+        let transaction = DbTransaction::new(transaction_info, slot);
+        let transaction = match self.pending_transactions.add(transaction) {
+            Some(transaction) => transaction,
+            None => return Ok(()),
+        };
+        let wrk_item = DbWorkItem::LogTransactions(LogTransactionsRequest {
+            transactions: vec![transaction],
+        });
+
+        if let Err(err) = self.sender.send(wrk_item) {
+            return Err(AccountsDbPluginError::TransactionUpdateError {
+                msg: format!(
+                    "Failed to update the transaction {}, error: {:?}",
+                    transaction_info.signature, err
+                ),
+            });
+        }
+        Ok(())
+    }
+
     pub fn notify_end_of_startup(&mut self) -> Result<(), AccountsDbPluginError> {
         info!("Notifying the end of startup");
         // Ensure all items in the queue has been received by the workers
@@ -774,3 +1131,82 @@ impl PostgresClientBuilder {
         SimplePostgresClient::new(config)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transaction(slot: u64) -> DbTransaction {
+        DbTransaction {
+            signature: vec![],
+            slot: slot as i64,
+            is_vote: false,
+            err: None,
+            fee: 0,
+            account_keys: vec![],
+            recent_blockhash: vec![],
+            pre_balances: vec![],
+            post_balances: vec![],
+            log_messages: None,
+        }
+    }
+
+    fn slots(transactions: &[DbTransaction]) -> Vec<i64> {
+        transactions
+            .iter()
+            .map(|transaction| transaction.slot)
+            .collect()
+    }
+
+    #[test]
+    fn test_pending_transactions() {
+        let mut pending = PendingTransactions::default();
+        for slot in [1, 1, 2, 3] {
+            assert!(pending.add(transaction(slot)).is_none());
+        }
+
+        // Transactions are held until their slot is confirmed
+        assert!(pending.take(1, Some(0), &SlotStatus::Processed).is_empty());
+        assert_eq!(
+            slots(&pending.take(1, Some(0), &SlotStatus::Confirmed)),
+            vec![1, 1]
+        );
+        assert!(pending.take(1, Some(0), &SlotStatus::Rooted).is_empty());
+
+        // Late transactions of confirmed or rooted slots are written right away
+        assert!(pending.add(transaction(1)).is_some());
+        assert_eq!(
+            slots(&pending.take(3, Some(1), &SlotStatus::Confirmed)),
+            vec![3]
+        );
+        assert!(pending.add(transaction(3)).is_some());
+
+        // Slot 2 was abandoned once slot 3 is rooted
+        assert!(pending.take(3, Some(1), &SlotStatus::Rooted).is_empty());
+        assert!(pending.by_slot.is_empty());
+        assert!(pending.confirmed_slots.is_empty());
+        assert!(pending.parents.is_empty());
+        assert!(pending.take(2, Some(1), &SlotStatus::Confirmed).is_empty());
+    }
+
+    #[test]
+    fn test_pending_transactions_rooted_ancestors() {
+        let mut pending = PendingTransactions::default();
+        for (slot, parent) in [(1, 0), (2, 1), (3, 1), (4, 2)] {
+            assert!(pending.add(transaction(slot)).is_none());
+            assert!(pending
+                .take(slot, Some(parent), &SlotStatus::Processed)
+                .is_empty());
+        }
+
+        // Slots 1 and 2 are rooted along with slot 4, while slot 3 was abandoned
+        assert_eq!(
+            slots(&pending.take(4, Some(2), &SlotStatus::Rooted)),
+            vec![1, 2, 4]
+        );
+        assert!(pending.by_slot.is_empty());
+        assert!(pending.parents.is_empty());
+        assert!(pending.take(2, Some(1), &SlotStatus::Rooted).is_empty());
+        assert!(pending.add(transaction(3)).is_some());
+    }
+}
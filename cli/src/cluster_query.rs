@@ -1569,6 +1569,8 @@ pub fn process_logs(config: &CliConfig, filter: &RpcTransactionLogsFilter) -> Pr
         filter.clone(),
         RpcTransactionLogsConfig {
             commitment: Some(config.commitment),
+            filter_status: None,
+            start_slot: None,
         },
     )?;
 
@@ -0,0 +1,477 @@
+//! An [`RpcSender`] that spreads requests over several RPC endpoints.
+//!
+//! [`FailoverRpcSender`] probes each of its endpoints with `getHealth` and
+//! `getSlot` in the background, and keeps a decaying error rate of the
+//! requests and probes sent to each. Requests go to the healthiest endpoints
+//! first, round-robin among ones of similar health, and fail over to the next
+//! endpoint when an endpoint cannot be reached or reports itself unhealthy.
+
+use {
+    crate::{
+        client_error::{ClientErrorKind, Result},
+        http_sender::HttpSender,
+        rpc_custom_error,
+        rpc_request::{RpcError, RpcRequest},
+        rpc_sender::*,
+    },
+    log::*,
+    serde_json::json,
+    solana_sdk::clock::Slot,
+    std::{
+        sync::{
+            atomic::{AtomicBool, AtomicUsize, Ordering},
+            Arc, RwLock,
+        },
+        thread::{self, sleep, JoinHandle},
+        time::{Duration, Instant},
+    },
+};
+
+pub const DEFAULT_PROBE_INTERVAL: Duration = Duration::from_secs(5);
+pub const DEFAULT_MAX_SLOT_LAG: u64 = 10;
+
+/// Weight of the latest outcome in the decaying error rate of an endpoint
+const ERROR_RATE_DECAY: f64 = 0.1;
+
+/// Endpoints whose error rates are within the same band are ranked equally
+const ERROR_RATE_BANDS: f64 = 10.0;
+
+/// The health of one endpoint of a [`FailoverRpcSender`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct EndpointHealth {
+    pub url: String,
+    /// Whether the last `getHealth` probe succeeded
+    pub healthy: bool,
+    /// The processed slot reported by the last `getSlot` probe
+    pub slot: Option<Slot>,
+    /// How far `slot` is behind the highest slot of all endpoints
+    pub slot_lag: Option<u64>,
+    /// The decaying fraction of failed requests, from 0 to 1
+    pub error_rate: f64,
+}
+
+#[derive(Debug, Default)]
+struct EndpointState {
+    healthy: bool,
+    slot: Option<Slot>,
+    error_rate: f64,
+}
+
+impl EndpointState {
+    fn record_outcome(&mut self, failed: bool) {
+        let outcome = if failed { 1.0 } else { 0.0 };
+        self.error_rate += ERROR_RATE_DECAY * (outcome - self.error_rate);
+    }
+
+    /// The error rate rounded down to its band. The rate of an endpoint that
+    /// ever failed only approaches zero, so ranking by the exact rate would keep
+    /// it behind its peers for good.
+    fn error_band(&self) -> u64 {
+        // Saturates, and maps NaN to 0
+        (self.error_rate * ERROR_RATE_BANDS) as u64
+    }
+}
+
+struct Endpoint {
+    url: String,
+    sender: Box<dyn RpcSender + Send + Sync>,
+    state: RwLock<EndpointState>,
+}
+
+impl Endpoint {
+    /// Successful probes count towards the error rate, so that an endpoint
+    /// that stopped getting requests after failing can recover its rank
+    fn probe(&self) {
+        let health = self
+            .sender
+            .send(RpcRequest::GetHealth, serde_json::Value::Null);
+        let slot = self
+            .sender
+            .send(RpcRequest::GetSlot, json!([{"commitment": "processed"}]))
+            .ok()
+            .and_then(|slot| slot.as_u64());
+        let mut state = self.state.write().unwrap();
+        state.record_outcome(matches!(&health, Err(err) if should_fail_over(err.kind())));
+        state.healthy = health.is_ok();
+        state.slot = slot;
+    }
+}
+
+struct Endpoints {
+    endpoints: Vec<Endpoint>,
+    max_slot_lag: u64,
+    next: AtomicUsize,
+}
+
+impl Endpoints {
+    fn max_slot(&self) -> Option<Slot> {
+        self.endpoints
+            .iter()
+            .filter_map(|endpoint| endpoint.state.read().unwrap().slot)
+            .max()
+    }
+
+    /// The endpoints in the order they should be tried: those that are healthy
+    /// and caught up first, each group by error band, rotating among equals
+    fn order(&self) -> Vec<&Endpoint> {
+        let max_slot = self.max_slot();
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        let mut ranked: Vec<_> = (0..self.endpoints.len())
+            .map(|i| &self.endpoints[(start + i) % self.endpoints.len()])
+            .map(|endpoint| {
+                let state = endpoint.state.read().unwrap();
+                let caught_up = match (state.slot, max_slot) {
+                    (Some(slot), Some(max_slot)) => {
+                        max_slot.saturating_sub(slot) <= self.max_slot_lag
+                    }
+                    _ => false,
+                };
+                let tier = if state.healthy && caught_up { 0 } else { 1 };
+                (tier, state.error_band(), endpoint)
+            })
+            .collect();
+        // A stable sort keeps the rotation among equally ranked endpoints
+        ranked.sort_by_key(|(tier, error_band, _)| (*tier, *error_band));
+        ranked
+            .into_iter()
+            .map(|(_, _, endpoint)| endpoint)
+            .collect()
+    }
+
+    /// Run `f` against the endpoints in order until one of them answers
+    fn send<T>(&self, f: impl Fn(&dyn RpcSender) -> Result<T>) -> Result<T> {
+        let mut last_err = None;
+        for endpoint in self.order() {
+            let result = f(endpoint.sender.as_ref());
+            let failed = matches!(&result, Err(err) if should_fail_over(err.kind()));
+            endpoint.state.write().unwrap().record_outcome(failed);
+            match result {
+                Err(err) if failed => {
+                    debug!("endpoint {} failed, failing over: {}", endpoint.url, err);
+                    last_err = Some(err);
+                }
+                result => return result,
+            }
+        }
+        Err(last_err
+            .unwrap_or_else(|| RpcError::RpcRequestError("No RPC endpoints".to_string()).into()))
+    }
+
+    #[cfg(test)]
+    fn probe(&self) {
+        for endpoint in &self.endpoints {
+            endpoint.probe();
+        }
+    }
+}
+
+/// Whether a request that failed with `kind` should be retried elsewhere.
+/// Errors about the request itself would fail on every endpoint alike.
+fn should_fail_over(kind: &ClientErrorKind) -> bool {
+    match kind {
+        ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_) => true,
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. }) => {
+            *code == rpc_custom_error::JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY
+        }
+        _ => false,
+    }
+}
+
+/// An [`RpcSender`] over several RPC endpoints, routing each request to the
+/// healthiest of them.
+pub struct FailoverRpcSender {
+    endpoints: Arc<Endpoints>,
+    exit: Arc<AtomicBool>,
+    t_probe: Vec<JoinHandle<()>>,
+}
+
+impl FailoverRpcSender {
+    /// Create a failover sender over HTTP endpoints, usually for port 8899.
+    ///
+    /// Endpoints are probed every [`DEFAULT_PROBE_INTERVAL`], and considered
+    /// behind once their slot lags by more than [`DEFAULT_MAX_SLOT_LAG`].
+    /// Requests are spread evenly until the first probes come back.
+    pub fn new(urls: Vec<String>) -> Self {
+        Self::new_with_senders(
+            urls.into_iter()
+                .map(|url| {
+                    let sender: Box<dyn RpcSender + Send + Sync> =
+                        Box::new(HttpSender::new(url.clone()));
+                    (url, sender)
+                })
+                .collect(),
+            DEFAULT_PROBE_INTERVAL,
+            DEFAULT_MAX_SLOT_LAG,
+        )
+    }
+
+    /// Create a failover sender over arbitrary senders, each named by a URL.
+    /// Each endpoint is probed on a thread of its own, so that an unresponsive
+    /// endpoint doesn't hold up the probes of the others.
+    pub fn new_with_senders(
+        senders: Vec<(String, Box<dyn RpcSender + Send + Sync>)>,
+        probe_interval: Duration,
+        max_slot_lag: u64,
+    ) -> Self {
+        let endpoints = Arc::new(Endpoints {
+            endpoints: senders
+                .into_iter()
+                .map(|(url, sender)| Endpoint {
+                    url,
+                    sender,
+                    state: RwLock::default(),
+                })
+                .collect(),
+            max_slot_lag,
+            next: AtomicUsize::default(),
+        });
+
+        let exit = Arc::new(AtomicBool::new(false));
+        let t_probe = (0..endpoints.endpoints.len())
+            .map(|i| {
+                let endpoints = endpoints.clone();
+                let exit = exit.clone();
+                thread::Builder::new()
+                    .name(format!("rpc-failover-{}", i))
+                    .spawn(move || {
+                        let endpoint = &endpoints.endpoints[i];
+                        let mut last_probe: Option<Instant> = None;
+                        while !exit.load(Ordering::Relaxed) {
+                            if last_probe
+                                .map_or(true, |last_probe| last_probe.elapsed() >= probe_interval)
+                            {
+                                endpoint.probe();
+                                last_probe = Some(Instant::now());
+                            }
+                            sleep(Duration::from_millis(100));
+                        }
+                    })
+                    .unwrap()
+            })
+            .collect();
+
+        Self {
+            endpoints,
+            exit,
+            t_probe,
+        }
+    }
+
+    /// The current health of each endpoint, in the order they were given.
+    pub fn endpoint_health(&self) -> Vec<EndpointHealth> {
+        let max_slot = self.endpoints.max_slot();
+        self.endpoints
+            .endpoints
+            .iter()
+            .map(|endpoint| {
+                let state = endpoint.state.read().unwrap();
+                EndpointHealth {
+                    url: endpoint.url.clone(),
+                    healthy: state.healthy,
+                    slot: state.slot,
+                    slot_lag: state
+                        .slot
+                        .zip(max_slot)
+                        .map(|(slot, max)| max.saturating_sub(slot)),
+                    error_rate: state.error_rate,
+                }
+            })
+            .collect()
+    }
+}
+
+impl Drop for FailoverRpcSender {
+    fn drop(&mut self) {
+        self.exit.store(true, Ordering::Relaxed);
+        for t_probe in self.t_probe.drain(..) {
+            t_probe.join().unwrap();
+        }
+    }
+}
+
+impl RpcSender for FailoverRpcSender {
+    fn send(&self, request: RpcRequest, params: serde_json::Value) -> Result<serde_json::Value> {
+        self.endpoints
+            .send(|sender| sender.send(request, params.clone()))
+    }
+
+    fn send_batch(
+        &self,
+        requests: Vec<(RpcRequest, serde_json::Value)>,
+    ) -> Result<Vec<Result<serde_json::Value>>> {
+        self.endpoints
+            .send(|sender| sender.send_batch(requests.clone()))
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.endpoints
+            .endpoints
+            .iter()
+            .fold(RpcTransportStats::default(), |mut stats, endpoint| {
+                let endpoint_stats = endpoint.sender.get_transport_stats();
+                stats.request_count += endpoint_stats.request_count;
+                stats.elapsed_time += endpoint_stats.elapsed_time;
+                stats.rate_limited_time += endpoint_stats.rate_limited_time;
+                stats
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        assert_matches::assert_matches,
+        std::{io, sync::atomic::AtomicU64},
+    };
+
+    #[derive(Default)]
+    struct TestNode {
+        unreachable: AtomicBool,
+        unhealthy: AtomicBool,
+        slot: AtomicU64,
+        requests: AtomicUsize,
+    }
+
+    struct TestSender(Arc<TestNode>);
+
+    impl RpcSender for TestSender {
+        fn send(
+            &self,
+            request: RpcRequest,
+            _params: serde_json::Value,
+        ) -> Result<serde_json::Value> {
+            let node = &self.0;
+            if node.unreachable.load(Ordering::Relaxed) {
+                return Err(io::Error::new(io::ErrorKind::ConnectionRefused, "down").into());
+            }
+            match request {
+                RpcRequest::GetHealth if node.unhealthy.load(Ordering::Relaxed) => {
+                    Err(RpcError::RpcResponseError {
+                        code: rpc_custom_error::JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY,
+                        message: "Node is unhealthy".to_string(),
+                        data: crate::rpc_request::RpcResponseErrorData::Empty,
+                    }
+                    .into())
+                }
+                RpcRequest::GetHealth => Ok(json!("ok")),
+                RpcRequest::GetSlot => Ok(json!(node.slot.load(Ordering::Relaxed))),
+                RpcRequest::GetVersion => Err(RpcError::RpcRequestError("bad".to_string()).into()),
+                _ => {
+                    node.requests.fetch_add(1, Ordering::Relaxed);
+                    Ok(json!(node.slot.load(Ordering::Relaxed)))
+                }
+            }
+        }
+
+        fn get_transport_stats(&self) -> RpcTransportStats {
+            RpcTransportStats::default()
+        }
+    }
+
+    /// Waits for the first probe of every endpoint
+    fn failover_sender(nodes: &[Arc<TestNode>]) -> FailoverRpcSender {
+        let sender = FailoverRpcSender::new_with_senders(
+            nodes
+                .iter()
+                .enumerate()
+                .map(|(i, node)| {
+                    let sender: Box<dyn RpcSender + Send + Sync> =
+                        Box::new(TestSender(node.clone()));
+                    (format!("node{}", i), sender)
+                })
+                .collect(),
+            Duration::from_secs(3600),
+            DEFAULT_MAX_SLOT_LAG,
+        );
+        while sender
+            .endpoint_health()
+            .iter()
+            .any(|health| health.slot.is_none())
+        {
+            sleep(Duration::from_millis(10));
+        }
+        sender
+    }
+
+    #[test]
+    fn test_failover_sender() {
+        let nodes: Vec<Arc<TestNode>> = (0..3).map(|_| Arc::default()).collect();
+        for node in &nodes {
+            node.slot.store(100, Ordering::Relaxed);
+        }
+        nodes[1].slot.store(50, Ordering::Relaxed);
+        nodes[2].unhealthy.store(true, Ordering::Relaxed);
+        let sender = failover_sender(&nodes);
+
+        let health = sender.endpoint_health();
+        assert!(health[0].healthy && !health[2].healthy);
+        assert_eq!(health[1].slot_lag, Some(50));
+
+        // Only the healthy, caught up endpoint serves requests
+        for _ in 0..4 {
+            assert_eq!(
+                sender.send(RpcRequest::GetBalance, json!([])).unwrap(),
+                json!(100)
+            );
+        }
+        assert_eq!(nodes[0].requests.load(Ordering::Relaxed), 4);
+
+        // Once all endpoints are caught up and healthy, requests are balanced
+        nodes[1].slot.store(100, Ordering::Relaxed);
+        nodes[2].unhealthy.store(false, Ordering::Relaxed);
+        sender.endpoints.probe();
+        for _ in 0..6 {
+            sender.send(RpcRequest::GetBalance, json!([])).unwrap();
+        }
+        assert_eq!(nodes[0].requests.load(Ordering::Relaxed), 6);
+        assert_eq!(nodes[1].requests.load(Ordering::Relaxed), 2);
+        assert_eq!(nodes[2].requests.load(Ordering::Relaxed), 2);
+
+        // An unreachable endpoint is failed over and loses its rank
+        nodes[0].unreachable.store(true, Ordering::Relaxed);
+        for _ in 0..3 {
+            sender.send(RpcRequest::GetBalance, json!([])).unwrap();
+        }
+        assert_eq!(
+            nodes[1].requests.load(Ordering::Relaxed) + nodes[2].requests.load(Ordering::Relaxed),
+            7
+        );
+        assert!(sender.endpoint_health()[0].error_rate > 0.0);
+
+        // Errors of the request itself are not failed over
+        let error_rates: Vec<_> = sender
+            .endpoint_health()
+            .into_iter()
+            .map(|health| health.error_rate)
+            .collect();
+        assert!(sender.send(RpcRequest::GetVersion, json!([])).is_err());
+        for (health, error_rate) in sender.endpoint_health().iter().zip(error_rates) {
+            assert!(health.error_rate <= error_rate);
+        }
+
+        // Successful probes bring a recovered endpoint back into the rotation,
+        // even though its error rate never gets back to zero
+        nodes[0].unreachable.store(false, Ordering::Relaxed);
+        for _ in 0..10 {
+            sender.endpoints.probe();
+        }
+        assert!(sender.endpoint_health()[0].error_rate > 0.0);
+        for _ in 0..6 {
+            sender.send(RpcRequest::GetBalance, json!([])).unwrap();
+        }
+        assert_eq!(nodes[0].requests.load(Ordering::Relaxed), 8);
+
+        // Without any reachable endpoint the last error is returned
+        for node in &nodes {
+            node.unreachable.store(true, Ordering::Relaxed);
+        }
+        assert_matches!(
+            sender
+                .send(RpcRequest::GetBalance, json!([]))
+                .unwrap_err()
+                .kind(),
+            ClientErrorKind::Io(_)
+        );
+    }
+}
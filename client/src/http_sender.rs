@@ -1,4 +1,10 @@
 //! The standard [`RpcSender`] over HTTP.
+//!
+//! The HTTP requests of an [`HttpSender`] pass through its
+//! [`HttpSenderMiddleware`] chain, which may amend them (e.g. add
+//! authentication headers), observe them (e.g. record latencies), send them
+//! elsewhere or more than once (e.g. hedge across endpoints, retry), or answer
+//! them without sending at all.
 
 use {
     crate::{
@@ -11,10 +17,11 @@ use {
     log::*,
     reqwest::{
         self,
-        header::{CONTENT_TYPE, RETRY_AFTER},
+        header::{HeaderMap, HeaderValue, CONTENT_TYPE, RETRY_AFTER},
         StatusCode,
     },
     std::{
+        collections::HashMap,
         sync::{
             atomic::{AtomicU64, Ordering},
             Arc, RwLock,
@@ -29,6 +36,53 @@ pub struct HttpSender {
     url: String,
     request_id: AtomicU64,
     stats: RwLock<RpcTransportStats>,
+    middleware: Vec<Arc<dyn HttpSenderMiddleware>>,
+    too_many_requests_retries: usize,
+}
+
+/// An HTTP request of an [`HttpSender`], as seen by its middleware.
+#[derive(Debug, Clone)]
+pub struct HttpSenderRequest {
+    /// The RPC methods called by the request, more than one for a batch
+    pub methods: Vec<RpcRequest>,
+    pub url: String,
+    pub headers: HeaderMap,
+    /// The JSON-RPC request body
+    pub body: String,
+}
+
+/// A hook around the HTTP requests of an [`HttpSender`].
+///
+/// Each middleware receives the request along with the [`Next`] link of the
+/// chain. It usually passes the request on with [`Next::run`], possibly
+/// amended, and returns the JSON response, but it may as well run the rest
+/// of the chain several times or not at all.
+pub trait HttpSenderMiddleware: Send + Sync {
+    fn handle(&self, request: HttpSenderRequest, next: Next<'_>) -> Result<serde_json::Value>;
+}
+
+/// The remainder of an [`HttpSender`] middleware chain, ending with the
+/// actual HTTP transport.
+#[derive(Clone, Copy)]
+pub struct Next<'a> {
+    sender: &'a HttpSender,
+    middleware: &'a [Arc<dyn HttpSenderMiddleware>],
+}
+
+impl<'a> Next<'a> {
+    /// Pass `request` to the rest of the chain and return its JSON response.
+    pub fn run(self, request: HttpSenderRequest) -> Result<serde_json::Value> {
+        match self.middleware.split_first() {
+            Some((middleware, rest)) => middleware.handle(
+                request,
+                Next {
+                    sender: self.sender,
+                    middleware: rest,
+                },
+            ),
+            None => self.sender.transact(request),
+        }
+    }
 }
 
 /// The standard [`RpcSender`] over HTTP.
@@ -61,8 +115,27 @@ impl HttpSender {
             url,
             request_id: AtomicU64::new(0),
             stats: RwLock::new(RpcTransportStats::default()),
+            middleware: vec![],
+            too_many_requests_retries: 5,
         }
     }
+
+    /// Add a middleware to the chain of the sender.
+    ///
+    /// Requests pass through the middleware in the order they were added, so
+    /// the first middleware added sees each request first.
+    pub fn with_middleware<M: HttpSenderMiddleware + 'static>(mut self, middleware: M) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Set how many times a request answered with `429 Too Many Requests` is
+    /// retried before the error is returned, 5 by default. Middleware that
+    /// implements its own retry policy may disable these retries with 0.
+    pub fn with_too_many_requests_retries(mut self, retries: usize) -> Self {
+        self.too_many_requests_retries = retries;
+        self
+    }
 }
 
 #[derive(Deserialize, Debug)]
@@ -106,23 +179,98 @@ impl RpcSender for HttpSender {
     }
 
     fn send(&self, request: RpcRequest, params: serde_json::Value) -> Result<serde_json::Value> {
-        let mut stats_updater = StatsUpdater::new(&self.stats);
-
         let request_id = self.request_id.fetch_add(1, Ordering::Relaxed);
         let request_json = request.build_request_json(request_id, params).to_string();
+        let mut json = self.post(vec![request], request_json)?;
+        parse_response(&mut json)
+    }
+
+    fn send_batch(
+        &self,
+        requests: Vec<(RpcRequest, serde_json::Value)>,
+    ) -> Result<Vec<Result<serde_json::Value>>> {
+        if requests.is_empty() {
+            return Ok(vec![]);
+        }
+        let count = requests.len() as u64;
+        let first_id = self.request_id.fetch_add(count, Ordering::Relaxed);
+        let methods = requests.iter().map(|(request, _)| *request).collect();
+        let request_json = serde_json::Value::Array(
+            requests
+                .into_iter()
+                .zip(first_id..)
+                .map(|((request, params), id)| request.build_request_json(id, params))
+                .collect(),
+        )
+        .to_string();
 
-        let mut too_many_requests_retries = 5;
+        let mut responses: HashMap<u64, serde_json::Value> =
+            match self.post(methods, request_json)? {
+                serde_json::Value::Array(responses) => responses
+                    .into_iter()
+                    .filter_map(|response| Some((response["id"].as_u64()?, response)))
+                    .collect(),
+                // A batch the server cannot process is answered with a single error
+                mut json => {
+                    parse_response(&mut json)?;
+                    return Err(RpcError::RpcRequestError(format!(
+                        "Unexpected response to a batch request: {}",
+                        json
+                    ))
+                    .into());
+                }
+            };
+
+        // The server may answer the requests of a batch in any order
+        Ok((first_id..first_id + count)
+            .map(|id| match responses.remove(&id) {
+                Some(mut json) => parse_response(&mut json),
+                None => Err(RpcError::RpcRequestError(format!(
+                    "Missing response to batch request {}",
+                    id
+                ))
+                .into()),
+            })
+            .collect())
+    }
+}
+
+impl HttpSender {
+    /// POST a JSON-RPC request body through the middleware chain and return
+    /// the JSON response.
+    fn post(&self, methods: Vec<RpcRequest>, body: String) -> Result<serde_json::Value> {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        let request = HttpSenderRequest {
+            methods,
+            url: self.url.clone(),
+            headers,
+            body,
+        };
+        Next {
+            sender: self,
+            middleware: &self.middleware,
+        }
+        .run(request)
+    }
+
+    /// Send an HTTP request, retrying while rate limited, and return the JSON
+    /// response.
+    fn transact(&self, request: HttpSenderRequest) -> Result<serde_json::Value> {
+        let mut stats_updater = StatsUpdater::new(&self.stats);
+
+        let mut too_many_requests_retries = self.too_many_requests_retries;
         loop {
             // `reqwest::blocking::Client` panics if run in a tokio async context.  Shuttle the
             // request to a different tokio thread to avoid this
             let response = {
                 let client = self.client.clone();
-                let request_json = request_json.clone();
+                let request = request.clone();
                 tokio::task::block_in_place(move || {
                     client
-                        .post(&self.url)
-                        .header(CONTENT_TYPE, "application/json")
-                        .body(request_json)
+                        .post(&request.url)
+                        .headers(request.headers)
+                        .body(request.body)
                         .send()
                 })
             }?;
@@ -155,55 +303,162 @@ impl RpcSender for HttpSender {
                 return Err(response.error_for_status().unwrap_err().into());
             }
 
-            let mut json =
-                tokio::task::block_in_place(move || response.json::<serde_json::Value>())?;
-            if json["error"].is_object() {
-                return match serde_json::from_value::<RpcErrorObject>(json["error"].clone()) {
-                    Ok(rpc_error_object) => {
-                        let data = match rpc_error_object.code {
-                                    rpc_custom_error::JSON_RPC_SERVER_ERROR_SEND_TRANSACTION_PREFLIGHT_FAILURE => {
-                                        match serde_json::from_value::<RpcSimulateTransactionResult>(json["error"]["data"].clone()) {
-                                            Ok(data) => RpcResponseErrorData::SendTransactionPreflightFailure(data),
-                                            Err(err) => {
-                                                debug!("Failed to deserialize RpcSimulateTransactionResult: {:?}", err);
-                                                RpcResponseErrorData::Empty
-                                            }
-                                        }
-                                    },
-                                    rpc_custom_error::JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY => {
-                                        match serde_json::from_value::<rpc_custom_error::NodeUnhealthyErrorData>(json["error"]["data"].clone()) {
-                                            Ok(rpc_custom_error::NodeUnhealthyErrorData {num_slots_behind}) => RpcResponseErrorData::NodeUnhealthy {num_slots_behind},
-                                            Err(_err) => {
-                                                RpcResponseErrorData::Empty
-                                            }
-                                        }
-                                    },
-                                    _ => RpcResponseErrorData::Empty
-                                };
-
-                        Err(RpcError::RpcResponseError {
-                            code: rpc_error_object.code,
-                            message: rpc_error_object.message,
-                            data,
+            return Ok(tokio::task::block_in_place(move || {
+                response.json::<serde_json::Value>()
+            })?);
+        }
+    }
+}
+
+/// Extract the result of a single JSON-RPC response, converting an error
+/// response into an `RpcError`.
+fn parse_response(json: &mut serde_json::Value) -> Result<serde_json::Value> {
+    if json["error"].is_object() {
+        return match serde_json::from_value::<RpcErrorObject>(json["error"].clone()) {
+            Ok(rpc_error_object) => {
+                let data = match rpc_error_object.code {
+                    rpc_custom_error::JSON_RPC_SERVER_ERROR_SEND_TRANSACTION_PREFLIGHT_FAILURE => {
+                        match serde_json::from_value::<RpcSimulateTransactionResult>(
+                            json["error"]["data"].clone(),
+                        ) {
+                            Ok(data) => RpcResponseErrorData::SendTransactionPreflightFailure(data),
+                            Err(err) => {
+                                debug!(
+                                    "Failed to deserialize RpcSimulateTransactionResult: {:?}",
+                                    err
+                                );
+                                RpcResponseErrorData::Empty
+                            }
                         }
-                        .into())
                     }
-                    Err(err) => Err(RpcError::RpcRequestError(format!(
-                        "Failed to deserialize RPC error response: {} [{}]",
-                        serde_json::to_string(&json["error"]).unwrap(),
-                        err
-                    ))
-                    .into()),
+                    rpc_custom_error::JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY => {
+                        match serde_json::from_value::<rpc_custom_error::NodeUnhealthyErrorData>(
+                            json["error"]["data"].clone(),
+                        ) {
+                            Ok(rpc_custom_error::NodeUnhealthyErrorData { num_slots_behind }) => {
+                                RpcResponseErrorData::NodeUnhealthy { num_slots_behind }
+                            }
+                            Err(_err) => RpcResponseErrorData::Empty,
+                        }
+                    }
+                    _ => RpcResponseErrorData::Empty,
                 };
+
+                Err(RpcError::RpcResponseError {
+                    code: rpc_error_object.code,
+                    message: rpc_error_object.message,
+                    data,
+                }
+                .into())
             }
-            return Ok(json["result"].take());
-        }
+            Err(err) => Err(RpcError::RpcRequestError(format!(
+                "Failed to deserialize RPC error response: {} [{}]",
+                serde_json::to_string(&json["error"]).unwrap(),
+                err
+            ))
+            .into()),
+        };
     }
+    Ok(json["result"].take())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use {super::*, reqwest::header::AUTHORIZATION, serde_json::json, std::sync::Mutex};
+
+    struct Recorder(Arc<Mutex<Vec<String>>>, &'static str);
+
+    impl HttpSenderMiddleware for Recorder {
+        fn handle(&self, request: HttpSenderRequest, next: Next<'_>) -> Result<serde_json::Value> {
+            self.0.lock().unwrap().push(format!(
+                "{} {:?} {}",
+                self.1,
+                request.methods,
+                request.headers.contains_key(AUTHORIZATION)
+            ));
+            next.run(request)
+        }
+    }
+
+    struct Auth;
+
+    impl HttpSenderMiddleware for Auth {
+        fn handle(
+            &self,
+            mut request: HttpSenderRequest,
+            next: Next<'_>,
+        ) -> Result<serde_json::Value> {
+            request
+                .headers
+                .insert(AUTHORIZATION, HeaderValue::from_static("Bearer token"));
+            next.run(request)
+        }
+    }
+
+    struct Retry;
+
+    impl HttpSenderMiddleware for Retry {
+        fn handle(&self, request: HttpSenderRequest, next: Next<'_>) -> Result<serde_json::Value> {
+            next.run(request.clone()).or_else(|_| next.run(request))
+        }
+    }
+
+    /// Answers in place of the HTTP transport, failing every other request
+    struct Flaky(AtomicU64);
+
+    impl HttpSenderMiddleware for Flaky {
+        fn handle(&self, request: HttpSenderRequest, _next: Next<'_>) -> Result<serde_json::Value> {
+            if self.0.fetch_add(1, Ordering::Relaxed) % 2 == 0 {
+                return Err(RpcError::RpcRequestError("flaky".to_string()).into());
+            }
+            let request: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+            Ok(match request {
+                serde_json::Value::Array(requests) => requests
+                    .iter()
+                    .map(|request| json!({"jsonrpc": "2.0", "id": request["id"], "result": 1}))
+                    .collect(),
+                request => json!({"jsonrpc": "2.0", "id": request["id"], "result": 1}),
+            })
+        }
+    }
+
+    #[test]
+    fn test_middleware() {
+        let log = Arc::new(Mutex::new(vec![]));
+        let http_sender = HttpSender::new("http://localhost:1234".to_string())
+            .with_middleware(Recorder(log.clone(), "outer"))
+            .with_middleware(Auth)
+            .with_middleware(Retry)
+            .with_middleware(Recorder(log.clone(), "inner"))
+            .with_middleware(Flaky(AtomicU64::default()));
+
+        assert_eq!(
+            http_sender
+                .send(RpcRequest::GetSlot, serde_json::Value::Null)
+                .unwrap(),
+            json!(1)
+        );
+        let results = http_sender
+            .send_batch(vec![
+                (RpcRequest::GetSlot, serde_json::Value::Null),
+                (RpcRequest::GetEpochInfo, serde_json::Value::Null),
+            ])
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|result| result.as_ref().unwrap() == 1));
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                "outer [GetSlot] false",
+                "inner [GetSlot] true",
+                "inner [GetSlot] true",
+                "outer [GetSlot, GetEpochInfo] false",
+                "inner [GetSlot, GetEpochInfo] true",
+                "inner [GetSlot, GetEpochInfo] true",
+            ]
+        );
+        assert_eq!(http_sender.get_transport_stats().request_count, 0);
+    }
 
     #[tokio::test(flavor = "multi_thread")]
     async fn http_sender_on_tokio_multi_thread() {
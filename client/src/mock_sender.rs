@@ -247,6 +247,7 @@ impl RpcSender for MockSender {
                                 first_slot: 1,
                                 last_slot: 2,
                             },
+                            skipped_slots_by_identity: None,
                         },
                     })
                 } else {
@@ -271,6 +272,7 @@ impl RpcSender for MockSender {
                                     }
                                 },
                             },
+                            skipped_slots_by_identity: None,
                         },
                     })
                 }
@@ -287,6 +289,7 @@ impl RpcSender for MockSender {
                     circulating: 50000,
                     non_circulating: 20000,
                     non_circulating_accounts: vec![PUBKEY.to_string()],
+                    non_circulating_breakdown: None,
                 },
             }),
             "getLargestAccounts" => {
@@ -313,6 +316,8 @@ impl RpcSender for MockSender {
                         last_vote: 0,
                         root_slot: Slot::default(),
                     }],
+                    slot: None,
+                    removed: None,
                 })
             }
             "sendTransaction" => {
@@ -333,6 +338,7 @@ impl RpcSender for MockSender {
                     logs: None,
                     accounts: None,
                     units_consumed: None,
+                    inner_instructions: None,
                 },
             })?,
             "getMinimumBalanceForRentExemption" => json![20],
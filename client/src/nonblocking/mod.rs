@@ -0,0 +1,2 @@
+pub mod pubsub_client;
+pub mod reconnecting_pubsub_client;
@@ -0,0 +1,618 @@
+//! An async client for the RPC PubSub websocket. All the subscriptions of a `PubsubClient`
+//! share a single connection, and each subscription is a typed `Stream` of notifications
+//! that unsubscribes when dropped.
+
+use {
+    crate::{
+        pubsub_client::PubsubClientError,
+        rpc_config::{
+            RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSignatureSubscribeConfig,
+            RpcTransactionLogsConfig, RpcTransactionLogsFilter,
+        },
+        rpc_response::{
+            Response as RpcResponse, RpcKeyedAccount, RpcLogsResponse, RpcSignatureResult, SlotInfo,
+        },
+    },
+    futures_util::{ready, sink::SinkExt, stream::Stream, stream::StreamExt},
+    log::*,
+    serde::de::DeserializeOwned,
+    serde_json::{json, Map, Value},
+    solana_account_decoder::UiAccount,
+    solana_sdk::{pubkey::Pubkey, signature::Signature},
+    std::{
+        collections::HashMap,
+        marker::PhantomData,
+        pin::Pin,
+        task::{Context, Poll},
+        time::Duration,
+    },
+    tokio::{
+        net::TcpStream,
+        select,
+        sync::{
+            mpsc::{self, error::TrySendError},
+            oneshot, watch,
+        },
+        task::JoinHandle,
+        time::{interval_at, Instant},
+    },
+    tokio_tungstenite::{
+        connect_async,
+        tungstenite::{protocol::CloseFrame, Error as WsError, Message},
+        MaybeTlsStream, WebSocketStream,
+    },
+    url::Url,
+};
+
+/// Notifications buffered per stream. A stream that falls further behind is ended, so that
+/// it can't hold up the other subscriptions of the connection.
+const NOTIFICATION_BUFFER_SIZE: usize = 1_024;
+
+/// Subscribe requests buffered for the connection
+const SUBSCRIBE_BUFFER_SIZE: usize = 64;
+
+/// The server is pinged when it has been this quiet, and is considered gone after twice as
+/// long without any message
+const PING_INTERVAL: Duration = Duration::from_secs(10);
+
+pub type PubsubClientResult<T = ()> = Result<T, PubsubClientError>;
+
+pub type SubscribeResult<T> = PubsubClientResult<PubsubSubscription<T>>;
+
+/// Notifications of a single subscription. The subscription ends when the stream is
+/// dropped, and the stream ends when the connection of its client goes away or when it
+/// falls `NOTIFICATION_BUFFER_SIZE` notifications behind.
+pub struct PubsubSubscription<T> {
+    id: u64,
+    stream_id: u64,
+    operation: &'static str,
+    receiver: mpsc::Receiver<Value>,
+    // Unbounded so that streams can unsubscribe when dropped; it holds at most one request
+    // per stream
+    unsubscribe_sender: mpsc::UnboundedSender<UnsubscribeRequest>,
+    message_type: PhantomData<fn() -> T>,
+}
+
+impl<T> PubsubSubscription<T> {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+}
+
+impl<T: DeserializeOwned> Stream for PubsubSubscription<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        loop {
+            match ready!(self.receiver.poll_recv(cx)) {
+                Some(result) => match serde_json::from_value(result) {
+                    Ok(item) => return Poll::Ready(Some(item)),
+                    Err(err) => warn!(
+                        "{} subscription {}: unexpected notification: {}",
+                        self.operation, self.id, err
+                    ),
+                },
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+impl<T> Drop for PubsubSubscription<T> {
+    fn drop(&mut self) {
+        // Nothing to unsubscribe from if the connection is already gone
+        let _ = self.unsubscribe_sender.send(UnsubscribeRequest {
+            operation: self.operation,
+            id: self.id,
+            stream_id: self.stream_id,
+        });
+    }
+}
+
+struct SubscribeRequest {
+    operation: &'static str,
+    params: Value,
+    notification_sender: mpsc::Sender<Value>,
+    /// Receives the ids of the subscription and of its stream
+    response_sender: oneshot::Sender<PubsubClientResult<(u64, u64)>>,
+}
+
+struct UnsubscribeRequest {
+    operation: &'static str,
+    id: u64,
+    stream_id: u64,
+}
+
+/// Dropping the client, or calling `shutdown`, closes the connection and ends all of its
+/// subscriptions.
+pub struct PubsubClient {
+    subscribe_sender: mpsc::Sender<SubscribeRequest>,
+    unsubscribe_sender: mpsc::UnboundedSender<UnsubscribeRequest>,
+    shutdown_sender: oneshot::Sender<()>,
+    /// Set once the connection is gone
+    closed_receiver: watch::Receiver<bool>,
+    ws: JoinHandle<PubsubClientResult>,
+}
+
+impl PubsubClient {
+    pub async fn new(url: &str) -> PubsubClientResult<Self> {
+        let url = Url::parse(url)?;
+        let (ws, _response) = connect_async(url).await?;
+
+        let (subscribe_sender, subscribe_receiver) = mpsc::channel(SUBSCRIBE_BUFFER_SIZE);
+        let (unsubscribe_sender, unsubscribe_receiver) = mpsc::unbounded_channel();
+        let (shutdown_sender, shutdown_receiver) = oneshot::channel();
+        let (closed_sender, closed_receiver) = watch::channel(false);
+        Ok(Self {
+            subscribe_sender,
+            unsubscribe_sender,
+            shutdown_sender,
+            closed_receiver,
+            ws: tokio::spawn(async move {
+                let result = run_ws(
+                    ws,
+                    subscribe_receiver,
+                    unsubscribe_receiver,
+                    shutdown_receiver,
+                )
+                .await;
+                if let Err(err) = &result {
+                    debug!("pubsub connection failed: {}", err);
+                }
+                let _ = closed_sender.send(true);
+                result
+            }),
+        })
+    }
+
+    /// Whether the connection is gone, closed by either side or failed
+    pub fn is_closed(&self) -> bool {
+        *self.closed_receiver.borrow()
+    }
+
+    /// Resolves once the connection is gone
+    pub async fn closed(&self) {
+        let mut closed_receiver = self.closed_receiver.clone();
+        while !*closed_receiver.borrow() {
+            if closed_receiver.changed().await.is_err() {
+                return;
+            }
+        }
+    }
+
+    /// Closes the connection, returning the error that ended it early, if any. A connection
+    /// closed by the server ends without error.
+    pub async fn shutdown(self) -> PubsubClientResult {
+        let _ = self.shutdown_sender.send(());
+        self.ws
+            .await
+            .map_err(|err| PubsubClientError::ConnectionClosed(err.to_string()))?
+    }
+
+    pub(crate) async fn subscribe<T>(
+        &self,
+        operation: &'static str,
+        params: Value,
+    ) -> SubscribeResult<T> {
+        let (notification_sender, receiver) = mpsc::channel(NOTIFICATION_BUFFER_SIZE);
+        let (response_sender, response_receiver) = oneshot::channel();
+        self.subscribe_sender
+            .send(SubscribeRequest {
+                operation,
+                params,
+                notification_sender,
+                response_sender,
+            })
+            .await
+            .map_err(|err| PubsubClientError::ConnectionClosed(err.to_string()))?;
+        let (id, stream_id) = response_receiver
+            .await
+            .map_err(|err| PubsubClientError::ConnectionClosed(err.to_string()))??;
+        Ok(PubsubSubscription {
+            id,
+            stream_id,
+            operation,
+            receiver,
+            unsubscribe_sender: self.unsubscribe_sender.clone(),
+            message_type: PhantomData,
+        })
+    }
+
+    pub async fn account_subscribe(
+        &self,
+        pubkey: &Pubkey,
+        config: Option<RpcAccountInfoConfig>,
+    ) -> SubscribeResult<RpcResponse<UiAccount>> {
+        self.subscribe("account", json!([pubkey.to_string(), config]))
+            .await
+    }
+
+    pub async fn program_subscribe(
+        &self,
+        pubkey: &Pubkey,
+        config: Option<RpcProgramAccountsConfig>,
+    ) -> SubscribeResult<RpcResponse<RpcKeyedAccount>> {
+        self.subscribe("program", json!([pubkey.to_string(), config]))
+            .await
+    }
+
+    pub async fn logs_subscribe(
+        &self,
+        filter: RpcTransactionLogsFilter,
+        config: RpcTransactionLogsConfig,
+    ) -> SubscribeResult<RpcResponse<RpcLogsResponse>> {
+        self.subscribe("logs", json!([filter, config])).await
+    }
+
+    pub async fn slot_subscribe(&self) -> SubscribeResult<SlotInfo> {
+        self.subscribe("slot", json!([])).await
+    }
+
+    pub async fn signature_subscribe(
+        &self,
+        signature: &Signature,
+        config: Option<RpcSignatureSubscribeConfig>,
+    ) -> SubscribeResult<RpcResponse<RpcSignatureResult>> {
+        self.subscribe("signature", json!([signature.to_string(), config]))
+            .await
+    }
+}
+
+type PubsubWebSocket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+async fn send_request(
+    ws: &mut PubsubWebSocket,
+    request_id: &mut u64,
+    method: String,
+    params: Value,
+) -> PubsubClientResult<u64> {
+    *request_id += 1;
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": *request_id,
+        "method": method,
+        "params": params,
+    });
+    ws.send(Message::Text(request.to_string())).await?;
+    Ok(*request_id)
+}
+
+/// Message received from the server
+enum ServerMessage {
+    /// Response to request `id`. Successful subscribe requests result in the id of the
+    /// subscription.
+    Response {
+        id: u64,
+        result: PubsubClientResult<u64>,
+    },
+    Notification {
+        subscription: u64,
+        result: Value,
+    },
+}
+
+/// What a read from the connection returned
+enum Received {
+    Text(String),
+    /// A control message; pings are answered by tungstenite
+    Control,
+    /// The server closed the connection
+    Closed,
+}
+
+fn received(message: Option<Result<Message, WsError>>) -> PubsubClientResult<Received> {
+    match message {
+        Some(Ok(Message::Text(text))) => Ok(Received::Text(text)),
+        Some(Ok(Message::Close(_))) | None => Ok(Received::Closed),
+        Some(Ok(_)) => Ok(Received::Control),
+        Some(Err(err)) => Err(err.into()),
+    }
+}
+
+fn parse_message(text: String) -> PubsubClientResult<ServerMessage> {
+    let mut json: Map<String, Value> = serde_json::from_str(&text)?;
+    if let Some(id) = json.get("id").and_then(Value::as_u64) {
+        let result = match json.get("result").and_then(Value::as_u64) {
+            Some(subscription_id) => Ok(subscription_id),
+            None => match json.get("error") {
+                Some(error) => Err(PubsubClientError::RequestError(error.to_string())),
+                None => Err(PubsubClientError::UnexpectedMessageError(text)),
+            },
+        };
+        return Ok(ServerMessage::Response { id, result });
+    }
+    if let Some(Value::Object(mut params)) = json.remove("params") {
+        if let (Some(subscription), Some(result)) = (
+            params.get("subscription").and_then(Value::as_u64),
+            params.remove("result"),
+        ) {
+            return Ok(ServerMessage::Notification {
+                subscription,
+                result,
+            });
+        }
+    }
+    Err(PubsubClientError::UnexpectedMessageError(text))
+}
+
+/// A subscription of the connection. The server hands out the same subscription to
+/// identical requests, so a subscription may feed several streams; it is only unsubscribed
+/// from once the last of them is gone.
+struct Subscription {
+    operation: &'static str,
+    /// Senders of the streams, by stream id
+    streams: Vec<(u64, mpsc::Sender<Value>)>,
+}
+
+impl Subscription {
+    /// Sends `result` to the streams, ending those that fell behind or were dropped
+    fn notify(&mut self, id: u64, result: Value) {
+        let operation = self.operation;
+        let mut result = Some(result);
+        let num_streams = self.streams.len();
+        let mut index = 0;
+        self.streams.retain(|(stream_id, sender)| {
+            index += 1;
+            // Only the streams of deduplicated subscriptions need a copy
+            let result = if index == num_streams {
+                result.take()
+            } else {
+                result.clone()
+            };
+            match sender.try_send(result.unwrap()) {
+                Ok(()) => true,
+                Err(TrySendError::Full(_)) => {
+                    warn!(
+                        "{} subscription {}: stream {} fell behind, ending it",
+                        operation, id, stream_id
+                    );
+                    false
+                }
+                Err(TrySendError::Closed(_)) => false,
+            }
+        });
+    }
+}
+
+/// Removes stream `stream_id` of subscription `id`, unsubscribing once none is left
+async fn remove_stream(
+    ws: &mut PubsubWebSocket,
+    request_id: &mut u64,
+    subscriptions: &mut HashMap<u64, Subscription>,
+    id: u64,
+    stream_id: Option<u64>,
+) -> PubsubClientResult {
+    let subscription = match subscriptions.get_mut(&id) {
+        Some(subscription) => subscription,
+        None => return Ok(()),
+    };
+    if let Some(stream_id) = stream_id {
+        subscription
+            .streams
+            .retain(|(other, _)| *other != stream_id);
+    }
+    if subscription.streams.is_empty() {
+        let method = format!("{}Unsubscribe", subscription.operation);
+        subscriptions.remove(&id);
+        send_request(ws, request_id, method, json!([id])).await?;
+    }
+    Ok(())
+}
+
+async fn run_ws(
+    mut ws: PubsubWebSocket,
+    mut subscribe_receiver: mpsc::Receiver<SubscribeRequest>,
+    mut unsubscribe_receiver: mpsc::UnboundedReceiver<UnsubscribeRequest>,
+    mut shutdown_receiver: oneshot::Receiver<()>,
+) -> PubsubClientResult {
+    let mut request_id = 0;
+    let mut next_stream_id = 0;
+    // Subscribe requests awaiting their response, by request id
+    let mut pending: HashMap<u64, SubscribeRequest> = HashMap::new();
+    let mut subscriptions: HashMap<u64, Subscription> = HashMap::new();
+    let mut ping = interval_at(Instant::now() + PING_INTERVAL, PING_INTERVAL);
+    let mut last_received = Instant::now();
+    loop {
+        select! {
+            // Also taken when the client is dropped
+            _ = &mut shutdown_receiver => {
+                ws.close(None::<CloseFrame>).await?;
+                return Ok(());
+            }
+            Some(request) = subscribe_receiver.recv() => {
+                let method = format!("{}Subscribe", request.operation);
+                let params = request.params.clone();
+                let id = send_request(&mut ws, &mut request_id, method, params).await?;
+                pending.insert(id, request);
+            }
+            Some(request) = unsubscribe_receiver.recv() => {
+                let UnsubscribeRequest { id, stream_id, .. } = request;
+                remove_stream(&mut ws, &mut request_id, &mut subscriptions, id, Some(stream_id))
+                    .await?;
+            }
+            _ = ping.tick() => {
+                if last_received.elapsed() > 2 * PING_INTERVAL {
+                    return Err(PubsubClientError::ConnectionClosed(
+                        "server stopped responding".to_string(),
+                    ));
+                }
+                if last_received.elapsed() >= PING_INTERVAL {
+                    ws.send(Message::Ping(vec![])).await?;
+                }
+            }
+            message = ws.next() => {
+                last_received = Instant::now();
+                let text = match received(message)? {
+                    Received::Text(text) => text,
+                    Received::Control => continue,
+                    Received::Closed => return Ok(()),
+                };
+                match parse_message(text) {
+                    Ok(ServerMessage::Response { id, result }) => {
+                        // Unsubscribe responses need no handling
+                        let request = match pending.remove(&id) {
+                            Some(request) => request,
+                            None => continue,
+                        };
+                        let id = match result {
+                            Ok(id) => id,
+                            Err(err) => {
+                                let _ = request.response_sender.send(Err(err));
+                                continue;
+                            }
+                        };
+                        next_stream_id += 1;
+                        let stream_id = next_stream_id;
+                        subscriptions
+                            .entry(id)
+                            .or_insert_with(|| Subscription {
+                                operation: request.operation,
+                                streams: vec![],
+                            })
+                            .streams
+                            .push((stream_id, request.notification_sender));
+                        if request.response_sender.send(Ok((id, stream_id))).is_err() {
+                            // Nobody is waiting for the subscription anymore
+                            remove_stream(
+                                &mut ws,
+                                &mut request_id,
+                                &mut subscriptions,
+                                id,
+                                Some(stream_id),
+                            )
+                            .await?;
+                        }
+                    }
+                    Ok(ServerMessage::Notification { subscription, result }) => {
+                        // Notifications of a dropped stream may still be in flight
+                        if let Some(entry) = subscriptions.get_mut(&subscription) {
+                            entry.notify(subscription, result);
+                            // Unsubscribes if that ended the last stream
+                            remove_stream(
+                                &mut ws,
+                                &mut request_id,
+                                &mut subscriptions,
+                                subscription,
+                                None,
+                            )
+                            .await?;
+                        }
+                    }
+                    Err(err) => warn!("{}", err),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, tokio::net::TcpListener, tokio_tungstenite::accept_async};
+
+    async fn next_request(ws: &mut WebSocketStream<TcpStream>) -> Value {
+        match ws.next().await.unwrap().unwrap() {
+            Message::Text(text) => serde_json::from_str(&text).unwrap(),
+            message => panic!("unexpected message: {:?}", message),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slot_subscribe() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (unsubscribed_sender, unsubscribed_receiver) = oneshot::channel();
+        let server = tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut ws = accept_async(socket).await.unwrap();
+
+            let request = next_request(&mut ws).await;
+            assert_eq!(request["method"], "slotSubscribe");
+            let response = json!({"jsonrpc": "2.0", "result": 42, "id": request["id"]});
+            ws.send(Message::Text(response.to_string())).await.unwrap();
+            let notification = json!({
+                "jsonrpc": "2.0",
+                "method": "slotNotification",
+                "params": {"result": {"slot": 3, "parent": 2, "root": 1}, "subscription": 42},
+            });
+            ws.send(Message::Text(notification.to_string()))
+                .await
+                .unwrap();
+
+            let request = next_request(&mut ws).await;
+            assert_eq!(request["method"], "slotUnsubscribe");
+            assert_eq!(request["params"], json!([42]));
+            unsubscribed_sender.send(()).unwrap();
+            assert!(matches!(ws.next().await, Some(Ok(Message::Close(_)))));
+        });
+
+        let client = PubsubClient::new(&url).await.unwrap();
+        let mut slots = client.slot_subscribe().await.unwrap();
+        assert_eq!(slots.id(), 42);
+        let slot_info = slots.next().await.unwrap();
+        assert_eq!(slot_info.slot, 3);
+        assert_eq!(slot_info.parent, 2);
+        assert_eq!(slot_info.root, 1);
+
+        drop(slots);
+        unsubscribed_receiver.await.unwrap();
+        client.shutdown().await.unwrap();
+        server.await.unwrap();
+    }
+    #[tokio::test]
+    async fn test_deduplicated_subscription() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut ws = accept_async(socket).await.unwrap();
+
+            // The server answers identical requests with the same subscription
+            for _ in 0..2 {
+                let request = next_request(&mut ws).await;
+                assert_eq!(request["method"], "slotSubscribe");
+                let response = json!({"jsonrpc": "2.0", "result": 42, "id": request["id"]});
+                ws.send(Message::Text(response.to_string())).await.unwrap();
+            }
+            let notification = json!({
+                "jsonrpc": "2.0",
+                "method": "slotNotification",
+                "params": {"result": {"slot": 3, "parent": 2, "root": 1}, "subscription": 42},
+            });
+            ws.send(Message::Text(notification.to_string()))
+                .await
+                .unwrap();
+
+            // Dropping the first stream doesn't unsubscribe
+            let request = next_request(&mut ws).await;
+            assert_eq!(request["method"], "accountSubscribe");
+            let response = json!({"jsonrpc": "2.0", "result": 7, "id": request["id"]});
+            ws.send(Message::Text(response.to_string())).await.unwrap();
+
+            let request = next_request(&mut ws).await;
+            assert_eq!(request["method"], "slotUnsubscribe");
+            assert_eq!(request["params"], json!([42]));
+            ws.close(None).await.unwrap();
+            assert!(!matches!(ws.next().await, Some(Ok(Message::Text(_)))));
+        });
+
+        let client = PubsubClient::new(&url).await.unwrap();
+        let mut first = client.slot_subscribe().await.unwrap();
+        let mut second = client.slot_subscribe().await.unwrap();
+        assert_eq!(first.id(), 42);
+        assert_eq!(second.id(), 42);
+        assert_eq!(first.next().await.unwrap().slot, 3);
+        assert_eq!(second.next().await.unwrap().slot, 3);
+
+        drop(first);
+        let mut accounts = client
+            .account_subscribe(&Pubkey::default(), None)
+            .await
+            .unwrap();
+        drop(second);
+
+        // The server closing the connection ends the remaining streams without error
+        assert!(accounts.next().await.is_none());
+        client.shutdown().await.unwrap();
+        server.await.unwrap();
+    }
+}
@@ -0,0 +1,469 @@
+//! A `PubsubClient` that survives disconnects. When its connection goes away, the client
+//! reconnects with exponential backoff and resubmits the active subscriptions. Notifications
+//! sent in between are lost, so each stream yields `PubsubEvent::Gap` once its subscription
+//! is back. Signature subscriptions that already got their final notification are not
+//! resubmitted.
+
+use {
+    crate::{
+        nonblocking::pubsub_client::{PubsubClient, PubsubClientResult},
+        pubsub_client::PubsubClientError,
+        rpc_config::{
+            RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSignatureSubscribeConfig,
+            RpcTransactionLogsConfig, RpcTransactionLogsFilter,
+        },
+        rpc_response::{
+            Response as RpcResponse, RpcKeyedAccount, RpcLogsResponse, RpcSignatureResult, SlotInfo,
+        },
+    },
+    futures_util::{ready, stream::Stream, stream::StreamExt},
+    log::*,
+    serde::de::DeserializeOwned,
+    serde_json::{json, Value},
+    solana_account_decoder::UiAccount,
+    solana_sdk::{pubkey::Pubkey, signature::Signature},
+    std::{
+        marker::PhantomData,
+        pin::Pin,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc,
+        },
+        task::{Context, Poll},
+        time::Duration,
+    },
+    tokio::{
+        select,
+        sync::{mpsc, oneshot, watch},
+        task::JoinHandle,
+        time::sleep,
+    },
+};
+
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_millis(500);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// Events buffered per stream. A stream that falls further behind holds up its subscription
+/// on the connection, which ends it there; it is then resubmitted and yields a gap.
+const EVENT_BUFFER_SIZE: usize = 1_024;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PubsubEvent<T> {
+    Notification(T),
+    /// The subscription was resubmitted after a reconnect. Notifications sent while the
+    /// client was disconnected are lost, so state built from the stream should be refreshed.
+    Gap,
+}
+
+pub type ReconnectingSubscribeResult<T> = PubsubClientResult<ReconnectingSubscription<T>>;
+
+/// Events of a single subscription. The subscription ends when the stream is dropped, and
+/// the stream ends when its client shuts down, the server refuses to resubmit it, or after
+/// the final notification of a signature subscription.
+pub struct ReconnectingSubscription<T> {
+    id: u64,
+    receiver: mpsc::Receiver<PubsubEvent<Value>>,
+    message_type: PhantomData<fn() -> T>,
+}
+
+impl<T> ReconnectingSubscription<T> {
+    /// Id of the subscription within its client. Unlike the id assigned by the server, it
+    /// is kept across reconnects.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+}
+
+impl<T: DeserializeOwned> Stream for ReconnectingSubscription<T> {
+    type Item = PubsubEvent<T>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            match ready!(self.receiver.poll_recv(cx)) {
+                Some(PubsubEvent::Notification(result)) => match serde_json::from_value(result) {
+                    Ok(item) => return Poll::Ready(Some(PubsubEvent::Notification(item))),
+                    Err(err) => warn!("subscription {}: unexpected notification: {}", self.id, err),
+                },
+                Some(PubsubEvent::Gap) => return Poll::Ready(Some(PubsubEvent::Gap)),
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+/// The connected client, `None` while reconnecting
+type ClientReceiver = watch::Receiver<Option<Arc<PubsubClient>>>;
+
+/// Dropping the client, or calling `shutdown`, closes the connection and ends all of its
+/// subscriptions.
+pub struct ReconnectingPubsubClient {
+    client_receiver: ClientReceiver,
+    next_id: AtomicU64,
+    shutdown_sender: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+impl ReconnectingPubsubClient {
+    /// Fails if the first connection can't be established
+    pub async fn new(url: &str) -> PubsubClientResult<Self> {
+        let client = Arc::new(PubsubClient::new(url).await?);
+
+        let (client_sender, client_receiver) = watch::channel(Some(client.clone()));
+        let (shutdown_sender, shutdown_receiver) = oneshot::channel();
+        Ok(Self {
+            client_receiver,
+            next_id: AtomicU64::default(),
+            shutdown_sender,
+            task: tokio::spawn(run(
+                url.to_string(),
+                client,
+                client_sender,
+                shutdown_receiver,
+            )),
+        })
+    }
+
+    pub async fn shutdown(self) {
+        let _ = self.shutdown_sender.send(());
+        let _ = self.task.await;
+    }
+
+    /// Resolves once the server accepts the subscription; while the client is disconnected
+    /// that waits for the next connection.
+    async fn subscribe<T>(
+        &self,
+        operation: &'static str,
+        params: Value,
+    ) -> ReconnectingSubscribeResult<T> {
+        let (event_sender, receiver) = mpsc::channel(EVENT_BUFFER_SIZE);
+        let (response_sender, response_receiver) = oneshot::channel();
+        tokio::spawn(run_subscription(
+            operation,
+            params,
+            self.client_receiver.clone(),
+            event_sender,
+            response_sender,
+        ));
+        response_receiver
+            .await
+            .map_err(|err| PubsubClientError::ConnectionClosed(err.to_string()))??;
+        Ok(ReconnectingSubscription {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed) + 1,
+            receiver,
+            message_type: PhantomData,
+        })
+    }
+
+    pub async fn account_subscribe(
+        &self,
+        pubkey: &Pubkey,
+        config: Option<RpcAccountInfoConfig>,
+    ) -> ReconnectingSubscribeResult<RpcResponse<UiAccount>> {
+        self.subscribe("account", json!([pubkey.to_string(), config]))
+            .await
+    }
+
+    pub async fn program_subscribe(
+        &self,
+        pubkey: &Pubkey,
+        config: Option<RpcProgramAccountsConfig>,
+    ) -> ReconnectingSubscribeResult<RpcResponse<RpcKeyedAccount>> {
+        self.subscribe("program", json!([pubkey.to_string(), config]))
+            .await
+    }
+
+    pub async fn logs_subscribe(
+        &self,
+        filter: RpcTransactionLogsFilter,
+        config: RpcTransactionLogsConfig,
+    ) -> ReconnectingSubscribeResult<RpcResponse<RpcLogsResponse>> {
+        self.subscribe("logs", json!([filter, config])).await
+    }
+
+    pub async fn slot_subscribe(&self) -> ReconnectingSubscribeResult<SlotInfo> {
+        self.subscribe("slot", json!([])).await
+    }
+
+    pub async fn signature_subscribe(
+        &self,
+        signature: &Signature,
+        config: Option<RpcSignatureSubscribeConfig>,
+    ) -> ReconnectingSubscribeResult<RpcResponse<RpcSignatureResult>> {
+        self.subscribe("signature", json!([signature.to_string(), config]))
+            .await
+    }
+}
+
+/// Replaces the client of a connection that went away, until shut down
+async fn run(
+    url: String,
+    mut client: Arc<PubsubClient>,
+    client_sender: watch::Sender<Option<Arc<PubsubClient>>>,
+    mut shutdown_receiver: oneshot::Receiver<()>,
+) {
+    'run: loop {
+        // Also taken when the client is dropped
+        select! {
+            _ = &mut shutdown_receiver => break,
+            _ = client.closed() => {}
+        }
+        warn!("pubsub client disconnected from {}", url);
+        let _ = client_sender.send(None);
+
+        let mut reconnect_delay = INITIAL_RECONNECT_DELAY;
+        client = loop {
+            select! {
+                _ = &mut shutdown_receiver => break 'run,
+                _ = sleep(reconnect_delay) => {}
+            }
+            match PubsubClient::new(&url).await {
+                Ok(client) => {
+                    info!("pubsub client reconnected to {}", url);
+                    break Arc::new(client);
+                }
+                Err(err) => {
+                    warn!("pubsub client failed to reconnect to {}: {}", url, err);
+                    reconnect_delay = (reconnect_delay * 2).min(MAX_RECONNECT_DELAY);
+                }
+            }
+        };
+        let _ = client_sender.send(Some(client.clone()));
+    }
+    // Drops the last client, closing its connection and ending the streams
+    let _ = client_sender.send(None);
+}
+
+/// Whether `result` is the final notification of a signature subscription, after which the
+/// server drops the subscription
+fn is_final_signature_notification(operation: &str, result: &Value) -> bool {
+    operation == "signature" && result["value"] != "receivedSignature"
+}
+
+/// Keeps a subscription on the connected client until its stream is dropped, resubmitting
+/// it whenever it ends early
+async fn run_subscription(
+    operation: &'static str,
+    params: Value,
+    mut client_receiver: ClientReceiver,
+    event_sender: mpsc::Sender<PubsubEvent<Value>>,
+    response_sender: oneshot::Sender<PubsubClientResult>,
+) {
+    let mut response_sender = Some(response_sender);
+    loop {
+        let client = loop {
+            let client = client_receiver.borrow().clone();
+            match client {
+                Some(client) if !client.is_closed() => break client,
+                _ => select! {
+                    // The client shut down
+                    changed = client_receiver.changed() => if changed.is_err() {
+                        return;
+                    },
+                    _ = event_sender.closed() => return,
+                },
+            }
+        };
+        let mut subscription = match client.subscribe::<Value>(operation, params.clone()).await {
+            Ok(subscription) => subscription,
+            // The connection went away, the subscription is submitted to the next one
+            Err(PubsubClientError::ConnectionClosed(_)) => {
+                client.closed().await;
+                continue;
+            }
+            Err(err) => {
+                match response_sender.take() {
+                    Some(response_sender) => {
+                        let _ = response_sender.send(Err(err));
+                    }
+                    None => warn!("pubsub client failed to resubscribe: {}", err),
+                }
+                return;
+            }
+        };
+        drop(client);
+        match response_sender.take() {
+            Some(response_sender) => {
+                if response_sender.send(Ok(())).is_err() {
+                    // Nobody is waiting for the subscription anymore
+                    return;
+                }
+            }
+            None => {
+                if event_sender.send(PubsubEvent::Gap).await.is_err() {
+                    return;
+                }
+            }
+        }
+
+        loop {
+            select! {
+                result = subscription.next() => match result {
+                    Some(result) => {
+                        let is_final = is_final_signature_notification(operation, &result);
+                        let event = PubsubEvent::Notification(result);
+                        if event_sender.send(event).await.is_err() || is_final {
+                            return;
+                        }
+                    }
+                    // Disconnected or fell behind
+                    None => break,
+                },
+                _ = event_sender.closed() => return,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        futures_util::sink::SinkExt,
+        tokio::net::{TcpListener, TcpStream},
+        tokio_tungstenite::{accept_async, tungstenite::Message, WebSocketStream},
+    };
+
+    async fn next_request(ws: &mut WebSocketStream<TcpStream>) -> Value {
+        match ws.next().await.unwrap().unwrap() {
+            Message::Text(text) => serde_json::from_str(&text).unwrap(),
+            message => panic!("unexpected message: {:?}", message),
+        }
+    }
+
+    async fn accept_slot_subscription(
+        listener: &TcpListener,
+        subscription: u64,
+        slot: u64,
+    ) -> WebSocketStream<TcpStream> {
+        let (socket, _) = listener.accept().await.unwrap();
+        let mut ws = accept_async(socket).await.unwrap();
+        let request = next_request(&mut ws).await;
+        assert_eq!(request["method"], "slotSubscribe");
+        let response = json!({"jsonrpc": "2.0", "result": subscription, "id": request["id"]});
+        ws.send(Message::Text(response.to_string())).await.unwrap();
+        let notification = json!({
+            "jsonrpc": "2.0",
+            "method": "slotNotification",
+            "params": {
+                "result": {"slot": slot, "parent": slot - 1, "root": 0},
+                "subscription": subscription,
+            },
+        });
+        ws.send(Message::Text(notification.to_string()))
+            .await
+            .unwrap();
+        ws
+    }
+
+    #[tokio::test]
+    async fn test_resubscribe_after_disconnect() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let ws = accept_slot_subscription(&listener, 1, 3).await;
+            drop(ws);
+            let mut ws = accept_slot_subscription(&listener, 7, 4).await;
+            let request = next_request(&mut ws).await;
+            assert_eq!(request["method"], "slotUnsubscribe");
+            assert_eq!(request["params"], json!([7]));
+        });
+
+        let client = ReconnectingPubsubClient::new(&url).await.unwrap();
+        let mut slots = client.slot_subscribe().await.unwrap();
+        let id = slots.id();
+        match slots.next().await.unwrap() {
+            PubsubEvent::Notification(slot_info) => assert_eq!(slot_info.slot, 3),
+            PubsubEvent::Gap => panic!("unexpected gap"),
+        }
+        assert_eq!(slots.next().await.unwrap(), PubsubEvent::Gap);
+        match slots.next().await.unwrap() {
+            PubsubEvent::Notification(slot_info) => assert_eq!(slot_info.slot, 4),
+            PubsubEvent::Gap => panic!("unexpected gap"),
+        }
+        assert_eq!(slots.id(), id);
+
+        drop(slots);
+        server.await.unwrap();
+        client.shutdown().await;
+    }
+    #[tokio::test]
+    async fn test_completed_signature_subscription_not_resubmitted() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut ws = accept_async(socket).await.unwrap();
+            let request = next_request(&mut ws).await;
+            assert_eq!(request["method"], "signatureSubscribe");
+            let response = json!({"jsonrpc": "2.0", "result": 1, "id": request["id"]});
+            ws.send(Message::Text(response.to_string())).await.unwrap();
+            for value in [json!("receivedSignature"), json!({"err": null})] {
+                let notification = json!({
+                    "jsonrpc": "2.0",
+                    "method": "signatureNotification",
+                    "params": {
+                        "result": {"context": {"slot": 5}, "value": value},
+                        "subscription": 1,
+                    },
+                });
+                ws.send(Message::Text(notification.to_string()))
+                    .await
+                    .unwrap();
+            }
+
+            // The completed subscription is dropped while the slot subscription comes in
+            let mut slot_request = None;
+            for _ in 0..2 {
+                let request = next_request(&mut ws).await;
+                match request["method"].as_str().unwrap() {
+                    "signatureUnsubscribe" => assert_eq!(request["params"], json!([1])),
+                    "slotSubscribe" => slot_request = Some(request),
+                    method => panic!("unexpected request: {}", method),
+                }
+            }
+            let request = slot_request.unwrap();
+            let response = json!({"jsonrpc": "2.0", "result": 2, "id": request["id"]});
+            ws.send(Message::Text(response.to_string())).await.unwrap();
+            drop(ws);
+
+            // Only the slot subscription is resubmitted
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut ws = accept_async(socket).await.unwrap();
+            let request = next_request(&mut ws).await;
+            assert_eq!(request["method"], "slotSubscribe");
+            let response = json!({"jsonrpc": "2.0", "result": 3, "id": request["id"]});
+            ws.send(Message::Text(response.to_string())).await.unwrap();
+            let request = next_request(&mut ws).await;
+            assert_eq!(request["method"], "slotUnsubscribe");
+            assert_eq!(request["params"], json!([3]));
+        });
+
+        let client = ReconnectingPubsubClient::new(&url).await.unwrap();
+        let mut signatures = client
+            .signature_subscribe(&Signature::default(), None)
+            .await
+            .unwrap();
+        assert!(matches!(
+            signatures.next().await.unwrap(),
+            PubsubEvent::Notification(RpcResponse {
+                value: RpcSignatureResult::ReceivedSignature(_),
+                ..
+            })
+        ));
+        assert!(matches!(
+            signatures.next().await.unwrap(),
+            PubsubEvent::Notification(RpcResponse {
+                value: RpcSignatureResult::ProcessedSignature(_),
+                ..
+            })
+        ));
+        assert!(signatures.next().await.is_none());
+
+        let mut slots = client.slot_subscribe().await.unwrap();
+        assert_eq!(slots.next().await.unwrap(), PubsubEvent::Gap);
+
+        drop(slots);
+        server.await.unwrap();
+        client.shutdown().await;
+    }
+}
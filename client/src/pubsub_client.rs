@@ -44,6 +44,12 @@ pub enum PubsubClientError {
 
     #[error("unexpected message format: {0}")]
     UnexpectedMessageError(String),
+
+    #[error("request failed: {0}")]
+    RequestError(String),
+
+    #[error("connection closed: {0}")]
+    ConnectionClosed(String),
 }
 
 pub struct PubsubClientSubscription<T>
@@ -27,14 +27,15 @@ impl LargestAccountsCache {
         }
     }
 
+    /// Returns the slot and accounts cached for `filter`, along with their age
     pub fn get_largest_accounts(
         &self,
         filter: &Option<RpcLargestAccountsFilter>,
-    ) -> Option<(u64, Vec<RpcAccountBalance>)> {
+    ) -> Option<(u64, Vec<RpcAccountBalance>, Duration)> {
         self.cache.get(filter).and_then(|value| {
             if let Ok(elapsed) = value.cached_time.elapsed() {
                 if elapsed < Duration::from_secs(self.duration) {
-                    return Some((value.slot, value.accounts.clone()));
+                    return Some((value.slot, value.accounts.clone(), elapsed));
                 }
             }
             None
@@ -71,6 +72,9 @@ pub mod test {
         let accounts: Vec<RpcAccountBalance> = Vec::new();
 
         cache.set_largest_accounts(&filter, 1000, &accounts);
+        let (slot, _, age) = cache.get_largest_accounts(&filter).unwrap();
+        assert_eq!(slot, 1000);
+        assert!(age < Duration::from_secs(1));
         std::thread::sleep(Duration::from_secs(1));
         assert_eq!(cache.get_largest_accounts(&filter), None);
     }
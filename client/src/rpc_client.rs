@@ -16,6 +16,7 @@ use {
         client_error::{ClientError, ClientErrorKind, Result as ClientResult},
         http_sender::HttpSender,
         mock_sender::{MockSender, Mocks},
+        pubsub_client::PubsubClient,
         rpc_config::RpcAccountInfoConfig,
         rpc_config::*,
         rpc_request::{RpcError, RpcRequest, RpcResponseErrorData, TokenAccountsFilter},
@@ -24,11 +25,12 @@ use {
         spinner,
     },
     bincode::serialize,
+    indicatif::ProgressBar,
     log::*,
     serde_json::{json, Value},
     solana_account_decoder::{
         parse_token::{TokenAccountType, UiTokenAccount, UiTokenAmount},
-        UiAccount, UiAccountData, UiAccountEncoding,
+        UiAccount, UiAccountData, UiAccountEncoding, UiDataSliceConfig,
     },
     solana_sdk::{
         account::Account,
@@ -52,7 +54,10 @@ use {
         cmp::min,
         net::SocketAddr,
         str::FromStr,
-        sync::RwLock,
+        sync::{
+            mpsc::{Receiver, RecvTimeoutError},
+            RwLock,
+        },
         thread::sleep,
         time::{Duration, Instant},
     },
@@ -65,7 +70,7 @@ pub struct RpcClientConfig {
 }
 
 impl RpcClientConfig {
-    fn with_commitment(commitment_config: CommitmentConfig) -> Self {
+    pub fn with_commitment(commitment_config: CommitmentConfig) -> Self {
         RpcClientConfig {
             commitment_config,
             ..Self::default()
@@ -73,6 +78,51 @@ impl RpcClientConfig {
     }
 }
 
+/// When to resubmit a transaction that is awaiting confirmation.
+///
+/// Used by [`RpcClient::send_and_confirm_transaction_with_subscription`].
+pub trait ResubmitBackoff {
+    /// How long to wait for confirmation before resubmitting the transaction
+    /// for the `attempt`th time, counting from 1, or `None` to stop
+    /// resubmitting it.
+    fn delay(&mut self, attempt: usize) -> Option<Duration>;
+}
+
+/// Resubmits a transaction at exponentially growing intervals.
+#[derive(Debug, Clone)]
+pub struct ExponentialBackoff {
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    pub max_resubmits: usize,
+}
+
+impl Default for ExponentialBackoff {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_secs(2),
+            max_delay: Duration::from_secs(10),
+            max_resubmits: 20,
+        }
+    }
+}
+
+impl ResubmitBackoff for ExponentialBackoff {
+    fn delay(&mut self, attempt: usize) -> Option<Duration> {
+        if attempt > self.max_resubmits {
+            return None;
+        }
+        let factor = 1u32
+            .checked_shl((attempt as u32).saturating_sub(1))
+            .unwrap_or(u32::MAX);
+        Some(
+            self.initial_delay
+                .checked_mul(factor)
+                .unwrap_or(self.max_delay)
+                .min(self.max_delay),
+        )
+    }
+}
+
 /// A client of a remote Solana node.
 ///
 /// `RpcClient` communicates with a Solana node over [JSON-RPC], with the
@@ -164,7 +214,40 @@ impl RpcClient {
     /// `RpcSender`. Most applications should use one of the other constructors,
     /// such as [`new`] and [`new_mock`], which create an `RpcClient`
     /// encapsulating an [`HttpSender`] and [`MockSender`] respectively.
-    fn new_sender<T: RpcSender + Send + Sync + 'static>(
+    ///
+    /// An [`HttpSender`] customized with middleware, for example, is used
+    /// through this constructor.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use solana_client::{
+    /// #     client_error::Result,
+    /// #     http_sender::{HttpSender, HttpSenderMiddleware, HttpSenderRequest, Next},
+    /// #     rpc_client::{RpcClient, RpcClientConfig},
+    /// # };
+    /// # use solana_sdk::commitment_config::CommitmentConfig;
+    /// struct Auth(&'static str);
+    ///
+    /// impl HttpSenderMiddleware for Auth {
+    ///     fn handle(
+    ///         &self,
+    ///         mut request: HttpSenderRequest,
+    ///         next: Next<'_>,
+    ///     ) -> Result<serde_json::Value> {
+    ///         request
+    ///             .headers
+    ///             .insert("authorization", self.0.parse().unwrap());
+    ///         next.run(request)
+    ///     }
+    /// }
+    ///
+    /// let sender = HttpSender::new("http://localhost:8899".to_string())
+    ///     .with_middleware(Auth("Bearer token"));
+    /// let config = RpcClientConfig::with_commitment(CommitmentConfig::default());
+    /// let client = RpcClient::new_sender(sender, config);
+    /// ```
+    pub fn new_sender<T: RpcSender + Send + Sync + 'static>(
         sender: T,
         config: RpcClientConfig,
     ) -> Self {
@@ -680,6 +763,212 @@ impl RpcClient {
         Ok(signature)
     }
 
+    /// Submits a signed transaction and waits for it to reach `commitment`,
+    /// displaying a spinner.
+    ///
+    /// Unlike [`send_and_confirm_transaction_with_spinner_and_config`], which
+    /// polls the status of the transaction, this method learns of its
+    /// confirmation from a [`signatureSubscribe`] subscription on
+    /// `websocket_url`. Until then, the transaction is resubmitted as directed
+    /// by `backoff`, and its status and blockhash are only checked when a
+    /// resubmission is due. Once the transaction lands, its status is polled
+    /// every couple of seconds too, in case the notification is missed.
+    /// Waiting ends with an error once the blockhash of an unprocessed
+    /// transaction expires.
+    ///
+    /// Should the subscription fail, confirmation falls back to polling as in
+    /// [`confirm_transaction_with_spinner`].
+    ///
+    /// [`send_and_confirm_transaction_with_spinner_and_config`]: RpcClient::send_and_confirm_transaction_with_spinner_and_config
+    /// [`confirm_transaction_with_spinner`]: RpcClient::confirm_transaction_with_spinner
+    /// [`signatureSubscribe`]: https://docs.solana.com/developing/clients/jsonrpc-api#signaturesubscribe
+    pub fn send_and_confirm_transaction_with_subscription(
+        &self,
+        transaction: &Transaction,
+        websocket_url: &str,
+        commitment: CommitmentConfig,
+        config: RpcSendTransactionConfig,
+        backoff: &mut dyn ResubmitBackoff,
+    ) -> ClientResult<Signature> {
+        let recent_blockhash = if uses_durable_nonce(transaction).is_some() {
+            self.get_latest_blockhash_with_commitment(CommitmentConfig::processed())?
+                .0
+        } else {
+            transaction.message.recent_blockhash
+        };
+        let signature = transaction.signatures[0];
+
+        // Subscribe before sending, so that the notification cannot be missed
+        let subscription = PubsubClient::signature_subscribe(
+            websocket_url,
+            &signature,
+            Some(RpcSignatureSubscribeConfig {
+                commitment: Some(commitment),
+                ..RpcSignatureSubscribeConfig::default()
+            }),
+        );
+        let (mut subscription, receiver) = match subscription {
+            Ok(subscription) => subscription,
+            Err(err) => {
+                warn!("signature subscription failed, polling instead: {}", err);
+                let signature = self.send_transaction_with_config(transaction, config)?;
+                self.confirm_transaction_with_spinner(&signature, &recent_blockhash, commitment)?;
+                return Ok(signature);
+            }
+        };
+        let result = self.send_and_confirm_transaction_with_receiver(
+            transaction,
+            &recent_blockhash,
+            commitment,
+            config,
+            backoff,
+            &receiver,
+        );
+        let _ = subscription.send_unsubscribe();
+        let _ = subscription.shutdown();
+        result
+    }
+
+    /// Sends `transaction` and waits for it to reach `commitment`, learning of
+    /// its confirmation from the signature notifications on `receiver`, or
+    /// from polling its status once it lands.
+    fn send_and_confirm_transaction_with_receiver(
+        &self,
+        transaction: &Transaction,
+        recent_blockhash: &Hash,
+        commitment: CommitmentConfig,
+        config: RpcSendTransactionConfig,
+        backoff: &mut dyn ResubmitBackoff,
+        receiver: &Receiver<Response<RpcSignatureResult>>,
+    ) -> ClientResult<Signature> {
+        let signature = self.send_transaction_with_config(transaction, config)?;
+
+        let progress_bar = spinner::new_progress_bar();
+        progress_bar.set_message(format!("Confirming transaction {}", signature));
+        let result = self.wait_for_signature_notification(
+            transaction,
+            &signature,
+            recent_blockhash,
+            commitment,
+            config,
+            backoff,
+            receiver,
+            &progress_bar,
+        );
+        // Cleared on every path, errors included
+        progress_bar.finish_and_clear();
+        result.map(|()| signature)
+    }
+
+    /// Waits for the already sent `transaction` to reach `commitment`,
+    /// resubmitting it until it lands and reporting progress on `progress_bar`.
+    #[allow(clippy::too_many_arguments)]
+    fn wait_for_signature_notification(
+        &self,
+        transaction: &Transaction,
+        signature: &Signature,
+        recent_blockhash: &Hash,
+        commitment: CommitmentConfig,
+        config: RpcSendTransactionConfig,
+        backoff: &mut dyn ResubmitBackoff,
+        receiver: &Receiver<Response<RpcSignatureResult>>,
+        progress_bar: &ProgressBar,
+    ) -> ClientResult<()> {
+        let resubmit_config = RpcSendTransactionConfig {
+            skip_preflight: true,
+            ..config
+        };
+        let mut attempt = 1;
+        let mut resubmit_delay = backoff.delay(attempt);
+        let mut landed_at = None;
+        loop {
+            let timeout = match landed_at {
+                None => resubmit_delay,
+                Some(_) => None,
+            }
+            .unwrap_or_else(|| Duration::from_secs(2));
+            match receiver.recv_timeout(timeout) {
+                Ok(response) => {
+                    if let RpcSignatureResult::ProcessedSignature(ProcessedSignatureResult {
+                        err: Some(err),
+                    }) = response.value
+                    {
+                        return Err(err.into());
+                    }
+                    return Ok(());
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    warn!("signature subscription closed, polling instead");
+                    // Polling displays a spinner of its own
+                    progress_bar.finish_and_clear();
+                    return self.confirm_transaction_with_spinner(
+                        signature,
+                        recent_blockhash,
+                        commitment,
+                    );
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+            }
+
+            if let Some(landed_at) = landed_at {
+                if Instant::now().duration_since(landed_at).as_secs()
+                    >= MAX_HASH_AGE_IN_SECONDS as u64
+                {
+                    return Err(RpcError::ForUser(
+                        "transaction not finalized. \
+                         This can happen when a transaction lands in an abandoned fork. \
+                         Please retry."
+                            .to_string(),
+                    )
+                    .into());
+                }
+            }
+            let status = self
+                .get_signature_statuses(&[*signature])?
+                .value
+                .pop()
+                .flatten();
+            match status {
+                Some(TransactionStatus {
+                    status: Err(err), ..
+                }) => return Err(err.into()),
+                Some(status) if status.satisfies_commitment(commitment) => {
+                    // The notification went missing
+                    return Ok(());
+                }
+                Some(_) => {
+                    if landed_at.is_none() {
+                        // Landed; only `commitment` remains to be reached
+                        progress_bar.set_message(format!("Finalizing transaction {}", signature));
+                        landed_at = Some(Instant::now());
+                    }
+                }
+                // Landed on a fork that was abandoned since; wait for it to land again
+                None if landed_at.is_some() => {}
+                None => {
+                    if !self.is_blockhash_valid(recent_blockhash, CommitmentConfig::processed())? {
+                        return Err(RpcError::ForUser(
+                            "unable to confirm transaction. \
+                             This can happen in situations such as transaction expiration \
+                             and insufficient fee-payer funds"
+                                .to_string(),
+                        )
+                        .into());
+                    }
+                    if resubmit_delay.is_some() {
+                        progress_bar.set_message(format!(
+                            "Resubmitting transaction {} ({})",
+                            signature, attempt
+                        ));
+                        self.send_transaction_with_config(transaction, resubmit_config)?;
+                        attempt += 1;
+                        resubmit_delay = backoff.delay(attempt);
+                    }
+                }
+            }
+        }
+    }
+
     /// Submits a signed transaction to the network.
     ///
     /// Before a transaction is processed, the receiving node runs a "preflight
@@ -856,6 +1145,7 @@ impl RpcClient {
         let config = RpcSendTransactionConfig {
             encoding: Some(encoding),
             preflight_commitment: Some(preflight_commitment.commitment),
+            return_units_consumed: false,
             ..config
         };
         let serialized_encoded = serialize_and_encode::<Transaction>(transaction, encoding)?;
@@ -906,6 +1196,56 @@ impl RpcClient {
         }
     }
 
+    /// Like [`send_transaction_with_config`], but also returns the compute
+    /// units the transaction consumed during its preflight simulation.
+    ///
+    /// Preflight can't be skipped, and with [`preflight_against_confirmed`]
+    /// set the larger of the two simulations is returned. The units are an
+    /// estimate: the transaction may consume more once it executes.
+    ///
+    /// [`send_transaction_with_config`]: RpcClient::send_transaction_with_config
+    /// [`preflight_against_confirmed`]: crate::rpc_config::RpcSendTransactionConfig::preflight_against_confirmed
+    ///
+    /// # RPC Reference
+    ///
+    /// This method is built on the [`sendTransaction`] RPC method.
+    ///
+    /// [`sendTransaction`]: https://docs.solana.com/developing/clients/jsonrpc-api#sendtransaction
+    pub fn send_transaction_with_units_consumed(
+        &self,
+        transaction: &Transaction,
+        config: RpcSendTransactionConfig,
+    ) -> ClientResult<RpcSendTransactionResult> {
+        let encoding = if let Some(encoding) = config.encoding {
+            encoding
+        } else {
+            self.default_cluster_transaction_encoding()?
+        };
+        let preflight_commitment = CommitmentConfig {
+            commitment: config.preflight_commitment.unwrap_or_default(),
+        };
+        let preflight_commitment = self.maybe_map_commitment(preflight_commitment)?;
+        let config = RpcSendTransactionConfig {
+            encoding: Some(encoding),
+            preflight_commitment: Some(preflight_commitment.commitment),
+            return_units_consumed: true,
+            ..config
+        };
+        let serialized_encoded = serialize_and_encode::<Transaction>(transaction, encoding)?;
+        let result: RpcSendTransactionResult = self.send(
+            RpcRequest::SendTransaction,
+            json!([serialized_encoded, config]),
+        )?;
+        if result.signature != transaction.signatures[0].to_string() {
+            return Err(RpcError::RpcRequestError(format!(
+                "RPC node returned mismatched signature {}, expected {}",
+                result.signature, transaction.signatures[0]
+            ))
+            .into());
+        }
+        Ok(result)
+    }
+
     pub fn send<T>(&self, request: RpcRequest, params: Value) -> ClientResult<T>
     where
         T: serde::de::DeserializeOwned,
@@ -920,6 +1260,69 @@ impl RpcClient {
             .map_err(|err| ClientError::new_with_request(err.into(), request))
     }
 
+    /// Send several RPC requests in a single JSON-RPC batch.
+    ///
+    /// Over HTTP the requests are transacted as one round trip, which is
+    /// considerably cheaper than issuing them one by one, e.g. when fetching
+    /// many accounts with [`getAccountInfo`]. The results are returned in the
+    /// order of `requests`, each deserialized as `T`; a request that fails
+    /// does not fail the others. Requests with differently typed results can
+    /// be batched with `T` as [`serde_json::Value`].
+    ///
+    /// [`getAccountInfo`]: https://docs.solana.com/developing/clients/jsonrpc-api#getaccountinfo
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the batch as a whole could not be transacted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use serde_json::json;
+    /// # use solana_client::{
+    /// #     client_error::ClientError,
+    /// #     rpc_client::RpcClient,
+    /// #     rpc_request::RpcRequest,
+    /// #     rpc_response::Response,
+    /// # };
+    /// # use solana_sdk::signature::{Keypair, Signer};
+    /// # let rpc_client = RpcClient::new_mock("succeeds".to_string());
+    /// let pubkeys: Vec<_> = (0..100).map(|_| Keypair::new().pubkey()).collect();
+    /// let requests = pubkeys
+    ///     .iter()
+    ///     .map(|pubkey| (RpcRequest::GetBalance, json!([pubkey.to_string()])))
+    ///     .collect();
+    /// let balances = rpc_client.send_batch::<Response<u64>>(requests)?;
+    /// assert_eq!(balances.len(), 100);
+    /// for balance in balances {
+    ///     println!("{}", balance?.value);
+    /// }
+    /// # Ok::<(), ClientError>(())
+    /// ```
+    pub fn send_batch<T>(
+        &self,
+        requests: Vec<(RpcRequest, Value)>,
+    ) -> ClientResult<Vec<ClientResult<T>>>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        assert!(requests
+            .iter()
+            .all(|(_, params)| params.is_array() || params.is_null()));
+
+        let methods: Vec<_> = requests.iter().map(|(request, _)| *request).collect();
+        let responses = self.sender.send_batch(requests)?;
+        Ok(methods
+            .into_iter()
+            .zip(responses)
+            .map(|(request, response)| {
+                let response = response.map_err(|err| err.into_with_request(request))?;
+                serde_json::from_value(response)
+                    .map_err(|err| ClientError::new_with_request(err.into(), request))
+            })
+            .collect())
+    }
+
     /// Check the confirmation status of a transaction.
     ///
     /// Returns `true` if the given transaction succeeded and has been committed
@@ -1307,6 +1710,44 @@ impl RpcClient {
         )
     }
 
+    /// Simulates sending several transactions in order, each seeing the account
+    /// changes of the ones before it.
+    ///
+    /// Simulation stops at the first transaction that fails, so the returned
+    /// list only holds the results up to and including that transaction.
+    ///
+    /// # RPC Reference
+    ///
+    /// This method corresponds directly to the [`simulateBundle`] RPC method.
+    ///
+    /// [`simulateBundle`]: https://docs.solana.com/developing/clients/jsonrpc-api#simulatebundle
+    pub fn simulate_bundle_with_config(
+        &self,
+        transactions: &[Transaction],
+        config: RpcSimulateTransactionConfig,
+    ) -> RpcResult<Vec<RpcSimulateTransactionResult>> {
+        let encoding = if let Some(encoding) = config.encoding {
+            encoding
+        } else {
+            self.default_cluster_transaction_encoding()?
+        };
+        let commitment = config.commitment.unwrap_or_default();
+        let commitment = self.maybe_map_commitment(commitment)?;
+        let config = RpcSimulateTransactionConfig {
+            encoding: Some(encoding),
+            commitment: Some(commitment),
+            ..config
+        };
+        let serialized_encoded = transactions
+            .iter()
+            .map(|transaction| serialize_and_encode::<Transaction>(transaction, encoding))
+            .collect::<ClientResult<Vec<_>>>()?;
+        self.send(
+            RpcRequest::SimulateBundle,
+            json!([serialized_encoded, config]),
+        )
+    }
+
     /// Returns the highest slot information that the node has snapshots for.
     ///
     /// This will find the highest full snapshot slot, and the highest incremental snapshot slot
@@ -1927,6 +2368,7 @@ impl RpcClient {
     ///     identity: Some(leader.to_string()),
     ///     range: Some(range),
     ///     commitment: Some(CommitmentConfig::processed()),
+    ///     ..RpcBlockProductionConfig::default()
     /// };
     /// let production = rpc_client.get_block_production_with_config(
     ///     config
@@ -2028,6 +2470,39 @@ impl RpcClient {
         )
     }
 
+    /// Returns the stake accounts whose authorized staker is `staker`.
+    pub fn get_stake_accounts_by_staker_with_config(
+        &self,
+        staker: &Pubkey,
+        config: RpcAccountInfoConfig,
+    ) -> RpcResult<Vec<RpcKeyedAccount>> {
+        self.get_stake_accounts_by_authority(RpcRequest::GetStakeAccountsByStaker, staker, config)
+    }
+
+    /// Returns the stake accounts whose authorized withdrawer is `withdrawer`.
+    pub fn get_stake_accounts_by_withdrawer_with_config(
+        &self,
+        withdrawer: &Pubkey,
+        config: RpcAccountInfoConfig,
+    ) -> RpcResult<Vec<RpcKeyedAccount>> {
+        self.get_stake_accounts_by_authority(
+            RpcRequest::GetStakeAccountsByWithdrawer,
+            withdrawer,
+            config,
+        )
+    }
+
+    fn get_stake_accounts_by_authority(
+        &self,
+        request: RpcRequest,
+        authority: &Pubkey,
+        mut config: RpcAccountInfoConfig,
+    ) -> RpcResult<Vec<RpcKeyedAccount>> {
+        let commitment = config.commitment.unwrap_or_else(|| self.commitment());
+        config.commitment = Some(self.maybe_map_commitment(commitment)?);
+        self.send(request, json!([authority.to_string(), config]))
+    }
+
     /// Returns information about the current supply.
     ///
     /// This method uses the configured [commitment level][cl].
@@ -2088,6 +2563,43 @@ impl RpcClient {
         )
     }
 
+    /// Returns information about the current supply, with a custom definition
+    /// of the non-circulating supply.
+    ///
+    /// # RPC Reference
+    ///
+    /// This method corresponds directly to the [`getSupply`] RPC method.
+    ///
+    /// [`getSupply`]: https://docs.solana.com/developing/clients/jsonrpc-api#getsupply
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use solana_client::{
+    /// #     rpc_client::RpcClient,
+    /// #     client_error::ClientError,
+    /// #     rpc_config::RpcSupplyConfig,
+    /// # };
+    /// # use solana_sdk::signature::{Keypair, Signer};
+    /// # let rpc_client = RpcClient::new_mock("succeeds".to_string());
+    /// # let treasury = Keypair::new();
+    /// let config = RpcSupplyConfig {
+    ///     non_circulating_accounts: Some(vec![treasury.pubkey().to_string()]),
+    ///     with_breakdown: true,
+    ///     ..RpcSupplyConfig::default()
+    /// };
+    /// let supply = rpc_client.supply_with_config(config)?;
+    /// # Ok::<(), ClientError>(())
+    /// ```
+    pub fn supply_with_config(&self, config: RpcSupplyConfig) -> RpcResult<RpcSupply> {
+        let commitment = config.commitment.unwrap_or_else(|| self.commitment());
+        let config = RpcSupplyConfig {
+            commitment: Some(self.maybe_map_commitment(commitment)?),
+            ..config
+        };
+        self.send(RpcRequest::GetSupply, json!([config]))
+    }
+
     /// Returns the 20 largest accounts, by lamport balance.
     ///
     /// # RPC Reference
@@ -2227,6 +2739,7 @@ impl RpcClient {
     ///     commitment: Some(commitment),
     ///     keep_unstaked_delinquents: Some(true),
     ///     delinquent_slot_distance: Some(10),
+    ///     ..RpcGetVoteAccountsConfig::default()
     /// };
     /// let accounts = rpc_client.get_vote_accounts_with_config(
     ///     config,
@@ -2296,6 +2809,64 @@ impl RpcClient {
         self.send(RpcRequest::GetClusterNodes, Value::Null)
     }
 
+    /// Returns information about the nodes participating in the cluster that
+    /// match the filters of `config`.
+    ///
+    /// # RPC Reference
+    ///
+    /// This method corresponds directly to the [`getClusterNodes`]
+    /// RPC method.
+    ///
+    /// [`getClusterNodes`]: https://docs.solana.com/developing/clients/jsonrpc-api#getclusternodes
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use solana_client::{
+    /// #     rpc_client::RpcClient,
+    /// #     client_error::ClientError,
+    /// #     rpc_config::RpcClusterNodesConfig,
+    /// # };
+    /// # let rpc_client = RpcClient::new_mock("succeeds".to_string());
+    /// let config = RpcClusterNodesConfig {
+    ///     rpc_only: Some(true),
+    ///     ..RpcClusterNodesConfig::default()
+    /// };
+    /// let rpc_nodes = rpc_client.get_cluster_nodes_with_config(config)?;
+    /// # Ok::<(), ClientError>(())
+    /// ```
+    pub fn get_cluster_nodes_with_config(
+        &self,
+        config: RpcClusterNodesConfig,
+    ) -> ClientResult<Vec<RpcContactInfo>> {
+        let config = RpcClusterNodesConfig {
+            summary: None,
+            ..config
+        };
+        self.send(RpcRequest::GetClusterNodes, json!([config]))
+    }
+
+    /// Like [`get_cluster_nodes_with_config`], but returns the distribution of
+    /// the versions and feature sets of the nodes, weighted by stake.
+    ///
+    /// [`get_cluster_nodes_with_config`]: RpcClient::get_cluster_nodes_with_config
+    ///
+    /// # RPC Reference
+    ///
+    /// This method is built on the [`getClusterNodes`] RPC method.
+    ///
+    /// [`getClusterNodes`]: https://docs.solana.com/developing/clients/jsonrpc-api#getclusternodes
+    pub fn get_cluster_nodes_summary(
+        &self,
+        config: RpcClusterNodesConfig,
+    ) -> ClientResult<RpcClusterNodesSummary> {
+        let config = RpcClusterNodesConfig {
+            summary: Some(true),
+            ..config
+        };
+        self.send(RpcRequest::GetClusterNodes, json!([config]))
+    }
+
     /// Returns identity and transaction information about a confirmed block in the ledger.
     ///
     /// The encodings are returned in [`UiTransactionEncoding::Json`][uite]
@@ -2608,7 +3179,99 @@ impl RpcClient {
         )
     }
 
-    /// Returns a list of confirmed blocks starting at the given slot.
+    /// Returns a list of confirmed blocks starting at the given slot.
+    ///
+    /// # Errors
+    ///
+    /// This method returns an error if the limit is greater than 500,000 slots.
+    ///
+    /// This method returns an error if the given [commitment level][cl] is below
+    /// [`Confirmed`].
+    ///
+    /// [cl]: https://docs.solana.com/developing/clients/jsonrpc-api#configuring-state-commitment
+    /// [`Confirmed`]: CommitmentLevel::Confirmed
+    ///
+    /// # RPC Reference
+    ///
+    /// This method corresponds directly to the [`getBlocksWithLimit`] RPC
+    /// method, unless the remote node version is less than 1.7, in which case
+    /// it maps to the `getConfirmedBlocksWithLimit` RPC method.
+    ///
+    /// [`getBlocksWithLimit`]: https://docs.solana.com/developing/clients/jsonrpc-api#getblockswithlimit
+    /// [`getConfirmedBlocksWithLimit`]: https://docs.solana.com/developing/clients/jsonrpc-api#getconfirmedblockswithlimit
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use solana_sdk::commitment_config::CommitmentConfig;
+    /// # use solana_client::{
+    /// #     rpc_client::RpcClient,
+    /// #     client_error::ClientError,
+    /// # };
+    /// # let rpc_client = RpcClient::new_mock("succeeds".to_string());
+    /// // Get the first 10 blocks
+    /// let start_slot = 0;
+    /// let limit = 10;
+    /// let commitment_config = CommitmentConfig::confirmed();
+    /// let blocks = rpc_client.get_blocks_with_limit_and_commitment(
+    ///     start_slot,
+    ///     limit,
+    ///     commitment_config,
+    /// )?;
+    /// # Ok::<(), ClientError>(())
+    /// ```
+    pub fn get_blocks_with_limit_and_commitment(
+        &self,
+        start_slot: Slot,
+        limit: usize,
+        commitment_config: CommitmentConfig,
+    ) -> ClientResult<Vec<Slot>> {
+        self.send(
+            self.maybe_map_request(RpcRequest::GetBlocksWithLimit)?,
+            json!([
+                start_slot,
+                limit,
+                self.maybe_map_commitment(commitment_config)?
+            ]),
+        )
+    }
+
+    /// Returns a list of blocks in the given slot range, optionally bounded by
+    /// block time and from newest to oldest.
+    ///
+    /// # Errors
+    ///
+    /// This method returns an error if the range is greater than 500,000 slots.
+    ///
+    /// This method returns an error if the given [commitment level][cl] is below
+    /// [`Confirmed`].
+    ///
+    /// [cl]: https://docs.solana.com/developing/clients/jsonrpc-api#configuring-state-commitment
+    /// [`Confirmed`]: CommitmentLevel::Confirmed
+    ///
+    /// # RPC Reference
+    ///
+    /// This method corresponds directly to the [`getBlocks`] RPC method.
+    ///
+    /// [`getBlocks`]: https://docs.solana.com/developing/clients/jsonrpc-api#getblocks
+    pub fn get_blocks_with_config(
+        &self,
+        start_slot: Slot,
+        end_slot: Option<Slot>,
+        config: RpcBlocksConfig,
+    ) -> ClientResult<Vec<Slot>> {
+        let config = self.map_blocks_config(config)?;
+        let json = if end_slot.is_some() {
+            json!([start_slot, end_slot, config])
+        } else {
+            json!([start_slot, config])
+        };
+        self.send(RpcRequest::GetBlocks, json)
+    }
+
+    /// Returns a list of at most `limit` blocks starting at the given slot,
+    /// optionally bounded by block time. With `config.reverse`, the blocks at or
+    /// before the given slot are returned from newest to oldest.
     ///
     /// # Errors
     ///
@@ -2623,48 +3286,49 @@ impl RpcClient {
     /// # RPC Reference
     ///
     /// This method corresponds directly to the [`getBlocksWithLimit`] RPC
-    /// method, unless the remote node version is less than 1.7, in which case
-    /// it maps to the `getConfirmedBlocksWithLimit` RPC method.
+    /// method.
     ///
     /// [`getBlocksWithLimit`]: https://docs.solana.com/developing/clients/jsonrpc-api#getblockswithlimit
-    /// [`getConfirmedBlocksWithLimit`]: https://docs.solana.com/developing/clients/jsonrpc-api#getconfirmedblockswithlimit
     ///
     /// # Examples
     ///
     /// ```
-    /// # use solana_sdk::commitment_config::CommitmentConfig;
     /// # use solana_client::{
     /// #     rpc_client::RpcClient,
+    /// #     rpc_config::RpcBlocksConfig,
     /// #     client_error::ClientError,
     /// # };
     /// # let rpc_client = RpcClient::new_mock("succeeds".to_string());
-    /// // Get the first 10 blocks
-    /// let start_slot = 0;
-    /// let limit = 10;
-    /// let commitment_config = CommitmentConfig::confirmed();
-    /// let blocks = rpc_client.get_blocks_with_limit_and_commitment(
-    ///     start_slot,
-    ///     limit,
-    ///     commitment_config,
-    /// )?;
+    /// // Get the 10 newest blocks produced before the start of 2021
+    /// let config = RpcBlocksConfig {
+    ///     reverse: Some(true),
+    ///     max_block_time: Some(1_609_459_199),
+    ///     ..RpcBlocksConfig::default()
+    /// };
+    /// let blocks = rpc_client.get_blocks_with_limit_and_config(u64::MAX, 10, config)?;
     /// # Ok::<(), ClientError>(())
     /// ```
-    pub fn get_blocks_with_limit_and_commitment(
+    pub fn get_blocks_with_limit_and_config(
         &self,
         start_slot: Slot,
         limit: usize,
-        commitment_config: CommitmentConfig,
+        config: RpcBlocksConfig,
     ) -> ClientResult<Vec<Slot>> {
+        let config = self.map_blocks_config(config)?;
         self.send(
-            self.maybe_map_request(RpcRequest::GetBlocksWithLimit)?,
-            json!([
-                start_slot,
-                limit,
-                self.maybe_map_commitment(commitment_config)?
-            ]),
+            RpcRequest::GetBlocksWithLimit,
+            json!([start_slot, limit, config]),
         )
     }
 
+    fn map_blocks_config(&self, config: RpcBlocksConfig) -> ClientResult<RpcBlocksConfig> {
+        let commitment = config.commitment.unwrap_or_else(|| self.commitment());
+        Ok(RpcBlocksConfig {
+            commitment: Some(self.maybe_map_commitment(commitment)?),
+            ..config
+        })
+    }
+
     #[deprecated(since = "1.7.0", note = "Please use RpcClient::get_blocks() instead")]
     #[allow(deprecated)]
     pub fn get_confirmed_blocks(
@@ -2847,14 +3511,52 @@ impl RpcClient {
             until: config.until.map(|signature| signature.to_string()),
             limit: config.limit,
             commitment: config.commitment,
+            ..RpcSignaturesForAddressConfig::default()
         };
+        self.get_filtered_signatures_for_address(address, config)
+            .map(|signatures| signatures.signatures)
+    }
 
-        let result: Vec<RpcConfirmedTransactionStatusWithSignature> = self.send(
+    /// Get confirmed signatures for transactions involving an address, keeping
+    /// only those that match the status, program and slot range filters of
+    /// `config`.
+    ///
+    /// The node applies the filters while walking its index, so `config.limit`
+    /// counts matching signatures only. It stops after examining
+    /// `MAX_GET_SIGNATURES_FOR_ADDRESS_EXAMINED` signatures; the search then
+    /// resumes with `resume_before` as `config.before`.
+    ///
+    /// # Errors
+    ///
+    /// This method returns an error if the given [commitment level][cl] is below
+    /// [`Confirmed`], or if both `failed_only` and `succeeded_only` are set.
+    ///
+    /// [cl]: https://docs.solana.com/developing/clients/jsonrpc-api#configuring-state-commitment
+    /// [`Confirmed`]: CommitmentLevel::Confirmed
+    ///
+    /// # RPC Reference
+    ///
+    /// This method corresponds directly to the [`getSignaturesForAddress`] RPC
+    /// method.
+    ///
+    /// [`getSignaturesForAddress`]: https://docs.solana.com/developing/clients/jsonrpc-api#getsignaturesforaddress
+    pub fn get_filtered_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        config: RpcSignaturesForAddressConfig,
+    ) -> ClientResult<RpcFilteredSignatures> {
+        let result: RpcSignaturesForAddress = self.send(
             self.maybe_map_request(RpcRequest::GetSignaturesForAddress)?,
             json!([address.to_string(), config]),
         )?;
 
-        Ok(result)
+        Ok(match result {
+            RpcSignaturesForAddress::Filtered(signatures) => signatures,
+            RpcSignaturesForAddress::Unfiltered(signatures) => RpcFilteredSignatures {
+                signatures,
+                resume_before: None,
+            },
+        })
     }
 
     #[deprecated(
@@ -3014,6 +3716,60 @@ impl RpcClient {
         )
     }
 
+    /// Returns transaction details for several confirmed transactions at once.
+    ///
+    /// The entries are returned in the order of `signatures`. A transaction
+    /// that isn't found or isn't confirmed has no details, and a failed lookup
+    /// reports its error in its own entry.
+    ///
+    /// # Errors
+    ///
+    /// This method returns an error if more than
+    /// [`MAX_GET_TRANSACTIONS_QUERY_ITEMS`] signatures are given, or if the
+    /// given [commitment level][cl] is below [`Confirmed`].
+    ///
+    /// [`MAX_GET_TRANSACTIONS_QUERY_ITEMS`]: crate::rpc_request::MAX_GET_TRANSACTIONS_QUERY_ITEMS
+    /// [cl]: https://docs.solana.com/developing/clients/jsonrpc-api#configuring-state-commitment
+    /// [`Confirmed`]: CommitmentLevel::Confirmed
+    ///
+    /// # RPC Reference
+    ///
+    /// This method corresponds directly to the [`getTransactions`] RPC method.
+    ///
+    /// [`getTransactions`]: https://docs.solana.com/developing/clients/jsonrpc-api#gettransactions
+    pub fn get_transactions_with_config(
+        &self,
+        signatures: &[Signature],
+        config: RpcTransactionConfig,
+    ) -> ClientResult<Vec<RpcTransactionsEntry>> {
+        let signatures: Vec<_> = signatures
+            .iter()
+            .map(|signature| signature.to_string())
+            .collect();
+        self.send(RpcRequest::GetTransactions, json!([signatures, config]))
+    }
+
+    /// Returns the progress of a transaction sent with
+    /// [`retry_until_expired`] through this node, or `None` if the node isn't
+    /// tracking it.
+    ///
+    /// [`retry_until_expired`]: crate::rpc_config::RpcSendTransactionConfig::retry_until_expired
+    ///
+    /// # RPC Reference
+    ///
+    /// This method corresponds directly to the [`getTransactionRetryStatus`] RPC method.
+    ///
+    /// [`getTransactionRetryStatus`]: https://docs.solana.com/developing/clients/jsonrpc-api#gettransactionretrystatus
+    pub fn get_transaction_retry_status(
+        &self,
+        signature: &Signature,
+    ) -> ClientResult<Option<RpcTransactionRetryStatus>> {
+        self.send(
+            RpcRequest::GetTransactionRetryStatus,
+            json!([signature.to_string()]),
+        )
+    }
+
     #[deprecated(
         since = "1.7.0",
         note = "Please use RpcClient::get_transaction() instead"
@@ -3240,6 +3996,7 @@ impl RpcClient {
     /// let config = RpcLeaderScheduleConfig {
     ///     identity: Some(validator_pubkey_str),
     ///     commitment: Some(CommitmentConfig::processed()),
+    ///     ..RpcLeaderScheduleConfig::default()
     /// };
     /// let leader_schedule = rpc_client.get_leader_schedule_with_config(
     ///     Some(slot),
@@ -3252,6 +4009,32 @@ impl RpcClient {
         slot: Option<Slot>,
         config: RpcLeaderScheduleConfig,
     ) -> ClientResult<Option<RpcLeaderSchedule>> {
+        let config = RpcLeaderScheduleConfig {
+            with_vote_accounts: None,
+            ..config
+        };
+        self.send(RpcRequest::GetLeaderSchedule, json!([slot, config]))
+    }
+
+    /// Like [`get_leader_schedule_with_config`], but also returns the vote
+    /// accounts and stake of each leader.
+    ///
+    /// [`get_leader_schedule_with_config`]: RpcClient::get_leader_schedule_with_config
+    ///
+    /// # RPC Reference
+    ///
+    /// This method is built on the [`getLeaderSchedule`] RPC method.
+    ///
+    /// [`getLeaderSchedule`]: https://docs.solana.com/developing/clients/jsonrpc-api#getleaderschedule
+    pub fn get_leader_schedule_with_vote_accounts(
+        &self,
+        slot: Option<Slot>,
+        config: RpcLeaderScheduleConfig,
+    ) -> ClientResult<Option<RpcLeaderScheduleWithVoteAccounts>> {
+        let config = RpcLeaderScheduleConfig {
+            with_vote_accounts: Some(true),
+            ..config
+        };
         self.send(RpcRequest::GetLeaderSchedule, json!([slot, config]))
     }
 
@@ -3445,6 +4228,30 @@ impl RpcClient {
         )
     }
 
+    /// Returns the inflation rewards paid for an epoch to a vote account and to
+    /// the stake accounts delegated to it.
+    ///
+    /// The stake rewards are paged with [`limit`] and [`after_pubkey`].
+    ///
+    /// [`limit`]: crate::rpc_config::RpcVoteAccountInflationRewardsConfig::limit
+    /// [`after_pubkey`]: crate::rpc_config::RpcVoteAccountInflationRewardsConfig::after_pubkey
+    ///
+    /// # RPC Reference
+    ///
+    /// This method corresponds directly to the [`getVoteAccountInflationRewards`] RPC method.
+    ///
+    /// [`getVoteAccountInflationRewards`]: https://docs.solana.com/developing/clients/jsonrpc-api#getvoteaccountinflationrewards
+    pub fn get_vote_account_inflation_rewards(
+        &self,
+        vote_pubkey: &Pubkey,
+        config: RpcVoteAccountInflationRewardsConfig,
+    ) -> ClientResult<RpcVoteAccountInflationRewards> {
+        self.send(
+            RpcRequest::GetVoteAccountInflationRewards,
+            json!([vote_pubkey.to_string(), config]),
+        )
+    }
+
     /// Returns the current solana version running on the node.
     ///
     /// # RPC Reference
@@ -3596,6 +4403,9 @@ impl RpcClient {
             encoding: Some(UiAccountEncoding::Base64Zstd),
             commitment: Some(self.maybe_map_commitment(commitment_config)?),
             data_slice: None,
+            send_initial: None,
+            compression_level: None,
+            with_proof: None,
         };
         let response = self.sender.send(
             RpcRequest::GetAccountInfo,
@@ -3753,6 +4563,9 @@ impl RpcClient {
                 encoding: Some(UiAccountEncoding::Base64Zstd),
                 commitment: Some(self.maybe_map_commitment(commitment_config)?),
                 data_slice: None,
+                send_initial: None,
+                compression_level: None,
+                with_proof: None,
             },
         )
     }
@@ -3800,12 +4613,86 @@ impl RpcClient {
         pubkeys: &[Pubkey],
         config: RpcAccountInfoConfig,
     ) -> RpcResult<Vec<Option<Account>>> {
-        let config = RpcAccountInfoConfig {
-            commitment: config.commitment.or_else(|| Some(self.commitment())),
+        let keys: Vec<_> = pubkeys.iter().map(|pubkey| (*pubkey, None)).collect();
+        self.get_multiple_accounts_with_data_slices(
+            &keys,
+            RpcMultipleAccountsConfig {
+                account_config: config,
+                owner: None,
+            },
+        )
+    }
+
+    /// Returns the account information for a list of pubkeys, each with its own
+    /// optional data slice.
+    ///
+    /// A data slice given along with a pubkey takes the place of the
+    /// `data_slice` of `config` for that account. If `config` names an
+    /// `owner`, accounts owned by any other program are returned as `None`.
+    ///
+    /// # RPC Reference
+    ///
+    /// This method is built on the [`getMultipleAccounts`] RPC method.
+    ///
+    /// [`getMultipleAccounts`]: https://docs.solana.com/developing/clients/jsonrpc-api#getmultipleaccounts
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use solana_client::{
+    /// #     rpc_client::RpcClient,
+    /// #     rpc_config::{RpcAccountInfoConfig, RpcMultipleAccountsConfig},
+    /// #     client_error::ClientError,
+    /// # };
+    /// # use solana_sdk::{
+    /// #     signature::Signer,
+    /// #     signer::keypair::Keypair,
+    /// # };
+    /// # use solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig};
+    /// # let rpc_client = RpcClient::new_mock("succeeds".to_string());
+    /// # let alice = Keypair::new();
+    /// # let bob = Keypair::new();
+    /// # let program_id = Keypair::new();
+    /// let keys = vec![
+    ///     (alice.pubkey(), Some(UiDataSliceConfig { offset: 0, length: 8 })),
+    ///     (bob.pubkey(), None),
+    /// ];
+    /// let config = RpcMultipleAccountsConfig {
+    ///     account_config: RpcAccountInfoConfig {
+    ///         encoding: Some(UiAccountEncoding::Base64),
+    ///         .. RpcAccountInfoConfig::default()
+    ///     },
+    ///     owner: Some(program_id.pubkey().to_string()),
+    /// };
+    /// let accounts = rpc_client.get_multiple_accounts_with_data_slices(&keys, config)?;
+    /// # Ok::<(), ClientError>(())
+    /// ```
+    pub fn get_multiple_accounts_with_data_slices(
+        &self,
+        keys: &[(Pubkey, Option<UiDataSliceConfig>)],
+        config: RpcMultipleAccountsConfig,
+    ) -> RpcResult<Vec<Option<Account>>> {
+        let config = RpcMultipleAccountsConfig {
+            account_config: RpcAccountInfoConfig {
+                commitment: config
+                    .account_config
+                    .commitment
+                    .or_else(|| Some(self.commitment())),
+                ..config.account_config
+            },
             ..config
         };
-        let pubkeys: Vec<_> = pubkeys.iter().map(|pubkey| pubkey.to_string()).collect();
-        let response = self.send(RpcRequest::GetMultipleAccounts, json!([pubkeys, config]))?;
+        let keys: Vec<_> = keys
+            .iter()
+            .map(|(pubkey, data_slice)| match data_slice {
+                Some(data_slice) => RpcMultipleAccountsKey::WithDataSlice {
+                    pubkey: pubkey.to_string(),
+                    data_slice: Some(*data_slice),
+                },
+                None => RpcMultipleAccountsKey::Pubkey(pubkey.to_string()),
+            })
+            .collect();
+        let response = self.send(RpcRequest::GetMultipleAccounts, json!([keys, config]))?;
         let Response {
             context,
             value: accounts,
@@ -4063,8 +4950,12 @@ impl RpcClient {
     ///             length: 5,
     ///         }),
     ///         commitment: Some(CommitmentConfig::processed()),
+    ///         send_initial: None,
+    ///         compression_level: None,
+    ///         with_proof: None,
     ///     },
     ///     with_context: Some(false),
+    ///     batch: None,
     /// };
     /// let accounts = rpc_client.get_program_accounts_with_config(
     ///     &alice.pubkey(),
@@ -4097,6 +4988,39 @@ impl RpcClient {
         parse_keyed_accounts(accounts, RpcRequest::GetProgramAccounts)
     }
 
+    /// Returns the number of accounts owned by the given program that match
+    /// the filters of `config`, without transferring the accounts.
+    ///
+    /// # RPC Reference
+    ///
+    /// This method corresponds directly to the [`getProgramAccountsCount`] RPC
+    /// method.
+    ///
+    /// [`getProgramAccountsCount`]: https://docs.solana.com/developing/clients/jsonrpc-api#getprogramaccountscount
+    pub fn get_program_accounts_count(
+        &self,
+        pubkey: &Pubkey,
+        config: RpcProgramAccountsConfig,
+    ) -> ClientResult<u64> {
+        let commitment = config
+            .account_config
+            .commitment
+            .unwrap_or_else(|| self.commitment());
+        let commitment = self.maybe_map_commitment(commitment)?;
+        let config = RpcProgramAccountsConfig {
+            account_config: RpcAccountInfoConfig {
+                commitment: Some(commitment),
+                ..config.account_config
+            },
+            with_context: None,
+            ..config
+        };
+        self.send(
+            RpcRequest::GetProgramAccountsCount,
+            json!([pubkey.to_string(), config]),
+        )
+    }
+
     /// Request the transaction count.
     pub fn get_transaction_count(&self) -> ClientResult<u64> {
         self.get_transaction_count_with_commitment(self.commitment())
@@ -4333,6 +5257,22 @@ impl RpcClient {
             .map(|_| ())
     }
 
+    /// Returns the node's extended health report, which is returned even if
+    /// the node is unhealthy.
+    ///
+    /// # RPC Reference
+    ///
+    /// This method corresponds directly to the [`getHealth`] RPC method with
+    /// `extended` set.
+    ///
+    /// [`getHealth`]: https://docs.solana.com/developing/clients/jsonrpc-api#gethealth
+    pub fn get_health_report(&self) -> ClientResult<RpcHealthReport> {
+        self.send(
+            RpcRequest::GetHealth,
+            json!([RpcHealthConfig { extended: true }]),
+        )
+    }
+
     pub fn get_token_account(&self, pubkey: &Pubkey) -> ClientResult<Option<UiTokenAccount>> {
         Ok(self
             .get_token_account_with_commitment(pubkey, self.commitment())?
@@ -4348,6 +5288,9 @@ impl RpcClient {
             encoding: Some(UiAccountEncoding::JsonParsed),
             commitment: Some(self.maybe_map_commitment(commitment_config)?),
             data_slice: None,
+            send_initial: None,
+            compression_level: None,
+            with_proof: None,
         };
         let response = self.sender.send(
             RpcRequest::GetAccountInfo,
@@ -4439,12 +5382,18 @@ impl RpcClient {
             TokenAccountsFilter::ProgramId(program_id) => {
                 RpcTokenAccountsFilter::ProgramId(program_id.to_string())
             }
+            TokenAccountsFilter::Mints(mints) => {
+                RpcTokenAccountsFilter::Mints(mints.iter().map(|mint| mint.to_string()).collect())
+            }
         };
 
         let config = RpcAccountInfoConfig {
             encoding: Some(UiAccountEncoding::JsonParsed),
             commitment: Some(self.maybe_map_commitment(commitment_config)?),
             data_slice: None,
+            send_initial: None,
+            compression_level: None,
+            with_proof: None,
         };
 
         self.send(
@@ -4478,13 +5427,48 @@ impl RpcClient {
             TokenAccountsFilter::ProgramId(program_id) => {
                 RpcTokenAccountsFilter::ProgramId(program_id.to_string())
             }
+            TokenAccountsFilter::Mints(mints) => {
+                RpcTokenAccountsFilter::Mints(mints.iter().map(|mint| mint.to_string()).collect())
+            }
         };
 
         let config = RpcAccountInfoConfig {
             encoding: Some(UiAccountEncoding::JsonParsed),
             commitment: Some(self.maybe_map_commitment(commitment_config)?),
             data_slice: None,
+            send_initial: None,
+            compression_level: None,
+            with_proof: None,
+        };
+
+        self.send(
+            RpcRequest::GetTokenAccountsByOwner,
+            json!([owner.to_string(), token_account_filter, config]),
+        )
+    }
+
+    /// Returns a page of the token accounts of `owner`. To fetch the next page, pass the
+    /// pubkey of the last account returned as `config.after_pubkey`.
+    pub fn get_token_accounts_by_owner_with_config(
+        &self,
+        owner: &Pubkey,
+        token_account_filter: TokenAccountsFilter,
+        mut config: RpcTokenAccountsByOwnerConfig,
+    ) -> RpcResult<Vec<RpcKeyedAccount>> {
+        let token_account_filter = match token_account_filter {
+            TokenAccountsFilter::Mint(mint) => RpcTokenAccountsFilter::Mint(mint.to_string()),
+            TokenAccountsFilter::ProgramId(program_id) => {
+                RpcTokenAccountsFilter::ProgramId(program_id.to_string())
+            }
+            TokenAccountsFilter::Mints(mints) => {
+                RpcTokenAccountsFilter::Mints(mints.iter().map(|mint| mint.to_string()).collect())
+            }
         };
+        let commitment = config
+            .account_config
+            .commitment
+            .unwrap_or_else(|| self.commitment());
+        config.account_config.commitment = Some(self.maybe_map_commitment(commitment)?);
 
         self.send(
             RpcRequest::GetTokenAccountsByOwner,
@@ -5021,6 +6005,98 @@ mod tests {
         let blockhash: ClientResult<String> =
             rpc_client.send(RpcRequest::GetRecentBlockhash, json!(["parameter"]));
         assert!(blockhash.is_err());
+
+        // Send a batch, one request of which fails
+        #[allow(deprecated)]
+        let results: Vec<ClientResult<Value>> = rpc_client
+            .send_batch(vec![
+                (RpcRequest::GetBalance, json!([PUBKEY])),
+                (RpcRequest::GetRecentBlockhash, json!(["parameter"])),
+                (RpcRequest::GetRecentBlockhash, Value::Null),
+            ])
+            .unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap(), &json!(50));
+        assert_matches!(
+            results[1].as_ref().unwrap_err().kind(),
+            ClientErrorKind::RpcError(RpcError::RpcResponseError { .. })
+        );
+        assert_eq!(
+            results[2].as_ref().unwrap(),
+            &json!("deadbeefXjn8o3yroDHxUtKsZZgoy4GPkPPXfouKNHhx")
+        );
+        assert!(rpc_client.send_batch::<Value>(vec![]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_exponential_backoff() {
+        let mut backoff = ExponentialBackoff {
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(5),
+            max_resubmits: 5,
+        };
+        let delays: Vec<_> = (1..=6).map(|attempt| backoff.delay(attempt)).collect();
+        assert_eq!(
+            delays,
+            vec![
+                Some(Duration::from_secs(1)),
+                Some(Duration::from_secs(2)),
+                Some(Duration::from_secs(4)),
+                Some(Duration::from_secs(5)),
+                Some(Duration::from_secs(5)),
+                None,
+            ]
+        );
+
+        let mut backoff = ExponentialBackoff {
+            max_resubmits: usize::MAX,
+            ..ExponentialBackoff::default()
+        };
+        assert_eq!(backoff.delay(100), Some(backoff.max_delay));
+    }
+
+    #[test]
+    fn test_send_and_confirm_transaction_without_notification() {
+        let key = Keypair::new();
+        let to = solana_sdk::pubkey::new_rand();
+        let blockhash = Hash::default();
+        let tx = system_transaction::transfer(&key, &to, 50, blockhash);
+        let mut backoff = ExponentialBackoff {
+            initial_delay: Duration::from_millis(10),
+            ..ExponentialBackoff::default()
+        };
+        // The subscription stays open, but the notification never arrives
+        let (_sender, receiver) = std::sync::mpsc::channel();
+
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+        let signature = rpc_client
+            .send_and_confirm_transaction_with_receiver(
+                &tx,
+                &blockhash,
+                CommitmentConfig::finalized(),
+                RpcSendTransactionConfig::default(),
+                &mut backoff,
+                &receiver,
+            )
+            .unwrap();
+        assert_eq!(signature, tx.signatures[0]);
+
+        let rpc_client = RpcClient::new_mock("instruction_error".to_string());
+        let result = rpc_client.send_and_confirm_transaction_with_receiver(
+            &tx,
+            &blockhash,
+            CommitmentConfig::finalized(),
+            RpcSendTransactionConfig::default(),
+            &mut backoff,
+            &receiver,
+        );
+        assert_matches!(
+            result.unwrap_err().kind(),
+            ClientErrorKind::TransactionError(TransactionError::InstructionError(
+                0,
+                InstructionError::UninitializedAccount
+            ))
+        );
     }
 
     #[test]
@@ -2,12 +2,34 @@ use {
     crate::rpc_filter::RpcFilterType,
     solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig},
     solana_sdk::{
-        clock::{Epoch, Slot},
+        clock::{Epoch, Slot, UnixTimestamp},
         commitment_config::{CommitmentConfig, CommitmentLevel},
     },
     solana_transaction_status::{TransactionDetails, UiTransactionEncoding},
 };
 
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcHealthConfig {
+    #[serde(default)]
+    pub extended: bool,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcClusterNodesConfig {
+    /// Only return nodes running this software version
+    pub version: Option<String>,
+    /// Return the nodes with this shred version instead of the node's own
+    pub shred_version: Option<u16>,
+    /// Only return nodes with an RPC address
+    pub rpc_only: Option<bool>,
+    /// Return the distribution of versions and feature sets instead of the nodes
+    pub summary: Option<bool>,
+    #[serde(flatten)]
+    pub commitment: Option<CommitmentConfig>,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcSignatureStatusConfig {
@@ -22,6 +44,12 @@ pub struct RpcSendTransactionConfig {
     pub preflight_commitment: Option<CommitmentLevel>,
     pub encoding: Option<UiTransactionEncoding>,
     pub max_retries: Option<usize>,
+    #[serde(default)]
+    pub retry_until_expired: bool,
+    #[serde(default)]
+    pub preflight_against_confirmed: bool,
+    #[serde(default)]
+    pub return_units_consumed: bool,
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
@@ -42,6 +70,8 @@ pub struct RpcSimulateTransactionConfig {
     pub commitment: Option<CommitmentConfig>,
     pub encoding: Option<UiTransactionEncoding>,
     pub accounts: Option<RpcSimulateTransactionAccountsConfig>,
+    #[serde(default)]
+    pub inner_instructions: bool,
 }
 
 #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
@@ -58,6 +88,17 @@ pub struct RpcLeaderScheduleConfig {
     pub identity: Option<String>, // validator identity, as a base-58 encoded string
     #[serde(flatten)]
     pub commitment: Option<CommitmentConfig>,
+    /// Return the leaders of this range instead of those of an epoch
+    pub range: Option<RpcLeaderScheduleConfigRange>,
+    /// Include the vote accounts and stake of each leader
+    pub with_vote_accounts: Option<bool>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcLeaderScheduleConfigRange {
+    pub first_slot: Slot,
+    pub last_slot: Option<Slot>,
 }
 
 #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
@@ -74,6 +115,10 @@ pub struct RpcBlockProductionConfig {
     pub range: Option<RpcBlockProductionConfigRange>, // current epoch if `None`
     #[serde(flatten)]
     pub commitment: Option<CommitmentConfig>,
+    /// Return the block production of this whole epoch instead of a range
+    pub epoch: Option<Epoch>,
+    /// Include the skipped slots of each leader
+    pub with_skipped_slots: Option<bool>,
 }
 
 #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
@@ -84,6 +129,10 @@ pub struct RpcGetVoteAccountsConfig {
     pub commitment: Option<CommitmentConfig>,
     pub keep_unstaked_delinquents: Option<bool>,
     pub delinquent_slot_distance: Option<u64>,
+    pub min_activated_stake: Option<u64>,
+    pub max_commission: Option<u8>,
+    pub min_epoch_credits: Option<u64>, // credits earned in the current epoch
+    pub changed_since_slot: Option<Slot>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -124,6 +173,16 @@ pub struct RpcSupplyConfig {
     pub commitment: Option<CommitmentConfig>,
     #[serde(default)]
     pub exclude_non_circulating_accounts_list: bool,
+    /// Ignore the built-in non-circulating accounts and stake lockups
+    #[serde(default)]
+    pub exclude_default_non_circulating: bool,
+    /// More accounts to consider non-circulating, as base-58 encoded strings
+    pub non_circulating_accounts: Option<Vec<String>>,
+    /// Consider all accounts owned by these programs non-circulating, as base-58 encoded strings
+    pub non_circulating_owners: Option<Vec<String>>,
+    /// Return the non-circulating supply by category
+    #[serde(default)]
+    pub with_breakdown: bool,
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
@@ -134,6 +193,18 @@ pub struct RpcEpochConfig {
     pub commitment: Option<CommitmentConfig>,
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcVoteAccountInflationRewardsConfig {
+    pub epoch: Option<Epoch>,
+    #[serde(flatten)]
+    pub commitment: Option<CommitmentConfig>,
+    /// Return at most this many stake rewards, ordered by stake pubkey
+    pub limit: Option<usize>,
+    /// Return the stake rewards ordered by stake pubkey, starting after this pubkey
+    pub after_pubkey: Option<String>,
+}
+
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcAccountInfoConfig {
@@ -141,6 +212,38 @@ pub struct RpcAccountInfoConfig {
     pub data_slice: Option<UiDataSliceConfig>,
     #[serde(flatten)]
     pub commitment: Option<CommitmentConfig>,
+    /// Only used by `accountSubscribe`: notify the current account state right after
+    /// subscribing
+    pub send_initial: Option<bool>,
+    /// Only used by `accountSubscribe` and `programSubscribe`, and refused by other methods:
+    /// the zstd level notifications are compressed at with the "base64+zstd" encoding
+    pub compression_level: Option<i32>,
+    /// Only used by `accountSubscribe`: accompany notifications with the proof that the
+    /// account was written in the slot it was last modified at
+    pub with_proof: Option<bool>,
+}
+
+/// A key of a `getMultipleAccounts` request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RpcMultipleAccountsKey {
+    Pubkey(String),
+    /// A pubkey along with the data slice to return for it, in place of the `data_slice`
+    /// of the request config
+    WithDataSlice {
+        pubkey: String,
+        #[serde(rename = "dataSlice")]
+        data_slice: Option<UiDataSliceConfig>,
+    },
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcMultipleAccountsConfig {
+    #[serde(flatten)]
+    pub account_config: RpcAccountInfoConfig,
+    /// Return `null` in place of the accounts that aren't owned by this program
+    pub owner: Option<String>,
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
@@ -150,6 +253,37 @@ pub struct RpcProgramAccountsConfig {
     #[serde(flatten)]
     pub account_config: RpcAccountInfoConfig,
     pub with_context: Option<bool>,
+    /// `programSubscribe` only: send all the accounts that changed in a slot in one
+    /// notification instead of one notification per account
+    pub batch: Option<bool>,
+}
+
+/// The configuration of `getProgramAccounts`: the configuration shared with
+/// `programSubscribe`, and how to page through the accounts
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcGetProgramAccountsConfig {
+    #[serde(flatten)]
+    pub program_accounts_config: RpcProgramAccountsConfig,
+    /// Return at most this many accounts, ordered by pubkey unless `order_by` says
+    /// otherwise; at most `MAX_GET_PROGRAM_ACCOUNTS_LIMIT`
+    pub limit: Option<usize>,
+    /// Return the accounts ordered by pubkey, starting after this pubkey
+    pub after_pubkey: Option<String>,
+    /// The order of the returned accounts
+    pub order_by: Option<RpcProgramAccountsOrder>,
+}
+
+/// The order of the accounts returned by `getProgramAccounts`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RpcProgramAccountsOrder {
+    /// Ascending pubkey
+    Pubkey,
+    /// Descending lamports, then ascending pubkey
+    Lamports,
+    /// Descending data length, then ascending pubkey
+    DataLen,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -165,6 +299,25 @@ pub enum RpcTransactionLogsFilter {
 pub struct RpcTransactionLogsConfig {
     #[serde(flatten)]
     pub commitment: Option<CommitmentConfig>,
+    pub filter_status: Option<RpcTransactionLogsStatusFilter>,
+    /// Replay the logs of the transactions from this slot on, read out of the blockstore,
+    /// ahead of the live notifications
+    pub start_slot: Option<Slot>,
+}
+
+/// Restricts `logsSubscribe` notifications by the outcome of the transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RpcTransactionLogsStatusFilter {
+    All,
+    Succeeded,
+    Failed,
+}
+
+impl Default for RpcTransactionLogsStatusFilter {
+    fn default() -> Self {
+        Self::All
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -172,6 +325,53 @@ pub struct RpcTransactionLogsConfig {
 pub enum RpcTokenAccountsFilter {
     Mint(String),
     ProgramId(String),
+    /// Accounts of any of these mints, as base-58 encoded strings
+    Mints(Vec<String>),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcTokenAccountsByOwnerConfig {
+    #[serde(flatten)]
+    pub account_config: RpcAccountInfoConfig,
+    /// Return at most this many accounts, ordered by pubkey
+    pub limit: Option<usize>,
+    /// Return the accounts ordered by pubkey, starting after this pubkey
+    pub after_pubkey: Option<String>,
+    /// Leave out the accounts holding no tokens
+    pub non_zero_balance_only: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcVoteSubscribeConfig {
+    /// Only notify votes from these vote accounts, as base-58 encoded strings
+    pub vote_pubkeys: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcRootSubscribeConfig {
+    /// Notify with an `RpcRootInfo` object instead of the bare root slot
+    pub enable_block_info: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcBlockhashValiditySubscribeConfig {
+    #[serde(flatten)]
+    pub commitment: Option<CommitmentConfig>,
+    /// Also notify once when the blockhash is valid for at most this many more blocks
+    pub expiry_warning_blocks: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcLeaderScheduleSubscribeConfig {
+    /// Only notify slots led by this validator identity, as a base-58 encoded string
+    pub identity: Option<String>,
+    /// Number of upcoming leader slots in each notification
+    pub num_slots: Option<usize>,
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
@@ -180,6 +380,66 @@ pub struct RpcSignatureSubscribeConfig {
     #[serde(flatten)]
     pub commitment: Option<CommitmentConfig>,
     pub enable_received_notification: Option<bool>,
+    /// Notify at each of these levels in turn instead of only at `commitment`
+    pub commitment_levels: Option<Vec<CommitmentLevel>>,
+}
+
+/// One item of a multiplexed subscription. Its notifications carry `tag`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcMultiplexSubscribeItem {
+    pub tag: String,
+    #[serde(flatten)]
+    pub kind: RpcMultiplexSubscribeKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum RpcMultiplexSubscribeKind {
+    Account {
+        pubkey: String,
+        config: Option<RpcAccountInfoConfig>,
+    },
+    Program {
+        pubkey: String,
+        config: Option<RpcProgramAccountsConfig>,
+    },
+    Signature {
+        signature: String,
+        config: Option<RpcSignatureSubscribeConfig>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RpcBlockSubscribeFilter {
+    All,
+    MentionsAccountOrProgram(String),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcBlockSubscribeConfig {
+    #[serde(flatten)]
+    pub commitment: Option<CommitmentConfig>,
+    pub encoding: Option<UiTransactionEncoding>,
+    pub transaction_details: Option<TransactionDetails>,
+    pub show_rewards: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcTransactionSubscribeConfig {
+    #[serde(flatten)]
+    pub commitment: Option<CommitmentConfig>,
+    pub encoding: Option<UiTransactionEncoding>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcMintSubscribeConfig {
+    #[serde(flatten)]
+    pub commitment: Option<CommitmentConfig>,
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
@@ -190,6 +450,16 @@ pub struct RpcSignaturesForAddressConfig {
     pub limit: Option<usize>,
     #[serde(flatten)]
     pub commitment: Option<CommitmentConfig>,
+    /// Return only the signatures of failed transactions
+    pub failed_only: Option<bool>,
+    /// Return only the signatures of successful transactions
+    pub succeeded_only: Option<bool>,
+    /// Return only the signatures of transactions invoking this program, as base-58 string
+    pub program_id: Option<String>,
+    /// Return only the signatures in this slot or later
+    pub min_slot: Option<Slot>,
+    /// Return only the signatures in this slot or earlier
+    pub max_slot: Option<Slot>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -199,11 +469,11 @@ pub enum RpcEncodingConfigWrapper<T> {
     Current(Option<T>),
 }
 
-impl<T: EncodingConfig + Default + Copy> RpcEncodingConfigWrapper<T> {
+impl<T: EncodingConfig + Default + Clone> RpcEncodingConfigWrapper<T> {
     pub fn convert_to_current(&self) -> T {
         match self {
             RpcEncodingConfigWrapper::Deprecated(encoding) => T::new_with_encoding(encoding),
-            RpcEncodingConfigWrapper::Current(config) => config.unwrap_or_default(),
+            RpcEncodingConfigWrapper::Current(config) => config.clone().unwrap_or_default(),
         }
     }
 
@@ -213,7 +483,7 @@ impl<T: EncodingConfig + Default + Copy> RpcEncodingConfigWrapper<T> {
                 RpcEncodingConfigWrapper::Deprecated(*encoding)
             }
             RpcEncodingConfigWrapper::Current(config) => {
-                RpcEncodingConfigWrapper::Current(config.map(|config| config.into()))
+                RpcEncodingConfigWrapper::Current(config.clone().map(|config| config.into()))
             }
         }
     }
@@ -265,6 +535,32 @@ impl From<RpcBlockConfig> for RpcEncodingConfigWrapper<RpcBlockConfig> {
     }
 }
 
+/// An `RpcBlockConfig` that also selects the fields of the block to return
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcBlockFieldsConfig {
+    #[serde(flatten)]
+    pub config: RpcBlockConfig,
+    /// Return only these fields of the block, as dot separated paths such as
+    /// `transactions.meta.err`. Such a block doesn't decode as a `UiConfirmedBlock`.
+    pub fields: Option<Vec<String>>,
+}
+
+impl EncodingConfig for RpcBlockFieldsConfig {
+    fn new_with_encoding(encoding: &Option<UiTransactionEncoding>) -> Self {
+        Self {
+            config: RpcBlockConfig::new_with_encoding(encoding),
+            ..Self::default()
+        }
+    }
+}
+
+impl From<RpcBlockFieldsConfig> for RpcBlockConfig {
+    fn from(config: RpcBlockFieldsConfig) -> Self {
+        config.config
+    }
+}
+
 #[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcTransactionConfig {
@@ -286,6 +582,8 @@ impl EncodingConfig for RpcTransactionConfig {
 #[serde(untagged)]
 pub enum RpcBlocksConfigWrapper {
     EndSlotOnly(Option<Slot>),
+    // Ahead of `CommitmentOnly`, which would otherwise drop all but the commitment of a config
+    ConfigOnly(Option<RpcBlocksConfig>),
     CommitmentOnly(Option<CommitmentConfig>),
 }
 
@@ -293,7 +591,44 @@ impl RpcBlocksConfigWrapper {
     pub fn unzip(&self) -> (Option<Slot>, Option<CommitmentConfig>) {
         match &self {
             RpcBlocksConfigWrapper::EndSlotOnly(end_slot) => (*end_slot, None),
+            RpcBlocksConfigWrapper::ConfigOnly(config) => {
+                (None, config.and_then(|config| config.commitment))
+            }
             RpcBlocksConfigWrapper::CommitmentOnly(commitment) => (None, *commitment),
         }
     }
+
+    pub fn unzip_config(&self) -> (Option<Slot>, Option<RpcBlocksConfig>) {
+        match &self {
+            RpcBlocksConfigWrapper::EndSlotOnly(end_slot) => (*end_slot, None),
+            RpcBlocksConfigWrapper::ConfigOnly(config) => (None, *config),
+            RpcBlocksConfigWrapper::CommitmentOnly(commitment) => (
+                None,
+                commitment.map(|commitment| RpcBlocksConfig::with_commitment(Some(commitment))),
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcBlocksConfig {
+    #[serde(flatten)]
+    pub commitment: Option<CommitmentConfig>,
+    /// Return the blocks from newest to oldest. `getBlocksWithLimit` then returns the
+    /// blocks at or before its start slot.
+    pub reverse: Option<bool>,
+    /// Return only the blocks produced at or after this time
+    pub min_block_time: Option<UnixTimestamp>,
+    /// Return only the blocks produced at or before this time
+    pub max_block_time: Option<UnixTimestamp>,
+}
+
+impl RpcBlocksConfig {
+    pub fn with_commitment(commitment: Option<CommitmentConfig>) -> Self {
+        Self {
+            commitment,
+            ..Self::default()
+        }
+    }
 }
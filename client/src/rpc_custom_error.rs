@@ -20,6 +20,8 @@ pub const JSON_RPC_SERVER_ERROR_TRANSACTION_HISTORY_NOT_AVAILABLE: i64 = -32011;
 pub const JSON_RPC_SCAN_ERROR: i64 = -32012;
 pub const JSON_RPC_SERVER_ERROR_TRANSACTION_SIGNATURE_LEN_MISMATCH: i64 = -32013;
 pub const JSON_RPC_SERVER_ERROR_BLOCK_STATUS_NOT_AVAILABLE_YET: i64 = -32014;
+pub const JSON_RPC_SERVER_ERROR_SCAN_QUEUE_FULL: i64 = -32015;
+pub const JSON_RPC_SERVER_ERROR_LARGEST_ACCOUNTS_NOT_AVAILABLE_YET: i64 = -32016;
 
 #[derive(Error, Debug)]
 pub enum RpcCustomError {
@@ -57,6 +59,10 @@ pub enum RpcCustomError {
     TransactionSignatureLenMismatch,
     #[error("BlockStatusNotAvailableYet")]
     BlockStatusNotAvailableYet { slot: Slot },
+    #[error("ScanQueueFull")]
+    ScanQueueFull,
+    #[error("LargestAccountsNotAvailableYet")]
+    LargestAccountsNotAvailableYet,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -169,6 +175,18 @@ impl From<RpcCustomError> for Error {
                 message: format!("Block status not yet available for slot {}", slot),
                 data: None,
             },
+            RpcCustomError::ScanQueueFull => Self {
+                code: ErrorCode::ServerError(JSON_RPC_SERVER_ERROR_SCAN_QUEUE_FULL),
+                message: "Too many account scans in progress, try again later".to_string(),
+                data: None,
+            },
+            RpcCustomError::LargestAccountsNotAvailableYet => Self {
+                code: ErrorCode::ServerError(
+                    JSON_RPC_SERVER_ERROR_LARGEST_ACCOUNTS_NOT_AVAILABLE_YET,
+                ),
+                message: "Largest accounts not yet computed, try again later".to_string(),
+                data: None,
+            },
         }
     }
 }
@@ -17,55 +17,50 @@ impl RpcFilterType {
         match self {
             RpcFilterType::DataSize(_) => Ok(()),
             RpcFilterType::Memcmp(compare) => {
-                let encoding = compare.encoding.as_ref().unwrap_or(&MemcmpEncoding::Binary);
-                match encoding {
-                    MemcmpEncoding::Binary => {
-                        use MemcmpEncodedBytes::*;
-                        match &compare.bytes {
-                            // DEPRECATED
-                            Binary(bytes) => {
-                                if bytes.len() > MAX_DATA_BASE58_SIZE {
-                                    return Err(RpcFilterError::Base58DataTooLarge);
-                                }
-                                let bytes = bs58::decode(&bytes)
-                                    .into_vec()
-                                    .map_err(RpcFilterError::DecodeError)?;
-                                if bytes.len() > MAX_DATA_SIZE {
-                                    Err(RpcFilterError::Base58DataTooLarge)
-                                } else {
-                                    Ok(())
-                                }
-                            }
-                            Base58(bytes) => {
-                                if bytes.len() > MAX_DATA_BASE58_SIZE {
-                                    return Err(RpcFilterError::DataTooLarge);
-                                }
-                                let bytes = bs58::decode(&bytes).into_vec()?;
-                                if bytes.len() > MAX_DATA_SIZE {
-                                    Err(RpcFilterError::DataTooLarge)
-                                } else {
-                                    Ok(())
-                                }
-                            }
-                            Base64(bytes) => {
-                                if bytes.len() > MAX_DATA_BASE64_SIZE {
-                                    return Err(RpcFilterError::DataTooLarge);
-                                }
-                                let bytes = base64::decode(&bytes)?;
-                                if bytes.len() > MAX_DATA_SIZE {
-                                    Err(RpcFilterError::DataTooLarge)
-                                } else {
-                                    Ok(())
-                                }
-                            }
-                            Bytes(bytes) => {
-                                if bytes.len() > MAX_DATA_SIZE {
-                                    return Err(RpcFilterError::DataTooLarge);
-                                }
-                                Ok(())
-                            }
+                use MemcmpEncodedBytes::*;
+                match compare.encoded_bytes().as_ref() {
+                    // DEPRECATED
+                    Binary(bytes) => {
+                        if bytes.len() > MAX_DATA_BASE58_SIZE {
+                            return Err(RpcFilterError::Base58DataTooLarge);
+                        }
+                        let bytes = bs58::decode(&bytes)
+                            .into_vec()
+                            .map_err(RpcFilterError::DecodeError)?;
+                        if bytes.len() > MAX_DATA_SIZE {
+                            Err(RpcFilterError::Base58DataTooLarge)
+                        } else {
+                            Ok(())
+                        }
+                    }
+                    Base58(bytes) => {
+                        if bytes.len() > MAX_DATA_BASE58_SIZE {
+                            return Err(RpcFilterError::DataTooLarge);
+                        }
+                        let bytes = bs58::decode(&bytes).into_vec()?;
+                        if bytes.len() > MAX_DATA_SIZE {
+                            Err(RpcFilterError::DataTooLarge)
+                        } else {
+                            Ok(())
+                        }
+                    }
+                    Base64(bytes) => {
+                        if bytes.len() > MAX_DATA_BASE64_SIZE {
+                            return Err(RpcFilterError::DataTooLarge);
+                        }
+                        let bytes = base64::decode(&bytes)?;
+                        if bytes.len() > MAX_DATA_SIZE {
+                            Err(RpcFilterError::DataTooLarge)
+                        } else {
+                            Ok(())
                         }
                     }
+                    Bytes(bytes) => {
+                        if bytes.len() > MAX_DATA_SIZE {
+                            return Err(RpcFilterError::DataTooLarge);
+                        }
+                        Ok(())
+                    }
                 }
             }
         }
@@ -97,7 +92,10 @@ pub enum RpcFilterError {
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum MemcmpEncoding {
+    /// Base-58, the default
     Binary,
+    Base58,
+    Base64,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -124,12 +122,27 @@ pub struct Memcmp {
 }
 
 impl Memcmp {
+    /// Strings deserialized from JSON always land in `MemcmpEncodedBytes::Binary`, so apply the
+    /// explicit `encoding`, if any, to tell how they are encoded
+    fn encoded_bytes(&self) -> Cow<MemcmpEncodedBytes> {
+        use MemcmpEncodedBytes::*;
+        match (&self.bytes, &self.encoding) {
+            (Binary(bytes), Some(MemcmpEncoding::Base64))
+            | (Base58(bytes), Some(MemcmpEncoding::Base64)) => Cow::Owned(Base64(bytes.clone())),
+            (Binary(bytes), Some(MemcmpEncoding::Base58)) => Cow::Owned(Base58(bytes.clone())),
+            _ => Cow::Borrowed(&self.bytes),
+        }
+    }
+
     pub fn bytes(&self) -> Option<Cow<Vec<u8>>> {
         use MemcmpEncodedBytes::*;
-        match &self.bytes {
+        if let Bytes(bytes) = &self.bytes {
+            return Some(Cow::Borrowed(bytes));
+        }
+        match self.encoded_bytes().as_ref() {
             Binary(bytes) | Base58(bytes) => bs58::decode(bytes).into_vec().ok().map(Cow::Owned),
             Base64(bytes) => base64::decode(bytes).ok().map(Cow::Owned),
-            Bytes(bytes) => Some(Cow::Borrowed(bytes)),
+            Bytes(bytes) => Some(Cow::Owned(bytes.clone())),
         }
     }
 
@@ -223,6 +236,31 @@ mod tests {
         .bytes_match(&data));
     }
 
+    #[test]
+    fn test_memcmp_base64_encoding() {
+        let data = vec![1, 2, 3, 4, 5];
+        let memcmp: Memcmp = serde_json::from_value(serde_json::json!({
+            "offset": 1,
+            "bytes": base64::encode(vec![2, 3]),
+            "encoding": "base64",
+        }))
+        .unwrap();
+        assert_eq!(memcmp.bytes().unwrap().as_ref(), &vec![2, 3]);
+        assert!(memcmp.bytes_match(&data));
+        assert_eq!(RpcFilterType::Memcmp(memcmp).verify(), Ok(()));
+
+        let memcmp = Memcmp {
+            offset: 0,
+            bytes: MemcmpEncodedBytes::Binary("not base64!".to_string()),
+            encoding: Some(MemcmpEncoding::Base64),
+        };
+        assert!(memcmp.bytes().is_none());
+        assert!(matches!(
+            RpcFilterType::Memcmp(memcmp).verify(),
+            Err(RpcFilterError::Base64DecodeError(_))
+        ));
+    }
+
     #[test]
     fn test_verify_memcmp() {
         let base58_bytes = "\
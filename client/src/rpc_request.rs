@@ -73,6 +73,7 @@ pub enum RpcRequest {
     GetMinimumBalanceForRentExemption,
     GetMultipleAccounts,
     GetProgramAccounts,
+    GetProgramAccountsCount,
     #[deprecated(
         since = "1.9.0",
         note = "Please use RpcRequest::GetLatestBlockhash instead"
@@ -94,6 +95,8 @@ pub enum RpcRequest {
     GetStorageTurnRate,
     GetSlotsPerSegment,
     GetStakeActivation,
+    GetStakeAccountsByStaker,
+    GetStakeAccountsByWithdrawer,
     GetStoragePubkeysForSlot,
     GetSupply,
     GetTokenAccountBalance,
@@ -102,13 +105,17 @@ pub enum RpcRequest {
     GetTokenSupply,
     GetTransaction,
     GetTransactionCount,
+    GetTransactionRetryStatus,
+    GetTransactions,
     GetVersion,
+    GetVoteAccountInflationRewards,
     GetVoteAccounts,
     IsBlockhashValid,
     MinimumLedgerSlot,
     RegisterNode,
     RequestAirdrop,
     SendTransaction,
+    SimulateBundle,
     SimulateTransaction,
     SignVote,
 }
@@ -154,6 +161,7 @@ impl fmt::Display for RpcRequest {
             RpcRequest::GetMinimumBalanceForRentExemption => "getMinimumBalanceForRentExemption",
             RpcRequest::GetMultipleAccounts => "getMultipleAccounts",
             RpcRequest::GetProgramAccounts => "getProgramAccounts",
+            RpcRequest::GetProgramAccountsCount => "getProgramAccountsCount",
             RpcRequest::GetRecentBlockhash => "getRecentBlockhash",
             RpcRequest::GetRecentPerformanceSamples => "getRecentPerformanceSamples",
             RpcRequest::GetHighestSnapshotSlot => "getHighestSnapshotSlot",
@@ -164,6 +172,8 @@ impl fmt::Display for RpcRequest {
             RpcRequest::GetSlotLeader => "getSlotLeader",
             RpcRequest::GetSlotLeaders => "getSlotLeaders",
             RpcRequest::GetStakeActivation => "getStakeActivation",
+            RpcRequest::GetStakeAccountsByStaker => "getStakeAccountsByStaker",
+            RpcRequest::GetStakeAccountsByWithdrawer => "getStakeAccountsByWithdrawer",
             RpcRequest::GetStorageTurn => "getStorageTurn",
             RpcRequest::GetStorageTurnRate => "getStorageTurnRate",
             RpcRequest::GetSlotsPerSegment => "getSlotsPerSegment",
@@ -175,13 +185,17 @@ impl fmt::Display for RpcRequest {
             RpcRequest::GetTokenSupply => "getTokenSupply",
             RpcRequest::GetTransaction => "getTransaction",
             RpcRequest::GetTransactionCount => "getTransactionCount",
+            RpcRequest::GetTransactionRetryStatus => "getTransactionRetryStatus",
+            RpcRequest::GetTransactions => "getTransactions",
             RpcRequest::GetVersion => "getVersion",
+            RpcRequest::GetVoteAccountInflationRewards => "getVoteAccountInflationRewards",
             RpcRequest::GetVoteAccounts => "getVoteAccounts",
             RpcRequest::IsBlockhashValid => "isBlockhashValid",
             RpcRequest::MinimumLedgerSlot => "minimumLedgerSlot",
             RpcRequest::RegisterNode => "registerNode",
             RpcRequest::RequestAirdrop => "requestAirdrop",
             RpcRequest::SendTransaction => "sendTransaction",
+            RpcRequest::SimulateBundle => "simulateBundle",
             RpcRequest::SimulateTransaction => "simulateTransaction",
             RpcRequest::SignVote => "signVote",
         };
@@ -191,13 +205,24 @@ impl fmt::Display for RpcRequest {
 }
 
 pub const MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS: usize = 256;
+pub const MAX_GET_TRANSACTIONS_QUERY_ITEMS: usize = 100;
 pub const MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS_SLOT_RANGE: u64 = 10_000;
 pub const MAX_GET_CONFIRMED_BLOCKS_RANGE: u64 = 500_000;
 pub const MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT: usize = 1_000;
+pub const MAX_GET_SIGNATURES_FOR_ADDRESS_EXAMINED: usize = 10_000;
+pub const MAX_GET_VOTE_ACCOUNT_INFLATION_REWARDS_LIMIT: usize = 1_000;
+pub const MAX_GET_SUPPLY_NON_CIRCULATING_ACCOUNTS: usize = 1_000;
+pub const MAX_GET_SUPPLY_NON_CIRCULATING_OWNERS: usize = 10;
 pub const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+pub const MAX_SIMULATE_BUNDLE_TRANSACTIONS: usize = 10;
+pub const MAX_TOKEN_ACCOUNTS_FILTER_MINTS: usize = 100;
 pub const NUM_LARGEST_ACCOUNTS: usize = 20;
 pub const MAX_GET_PROGRAM_ACCOUNT_FILTERS: usize = 4;
+pub const MAX_GET_PROGRAM_ACCOUNTS_LIMIT: usize = 10_000;
 pub const MAX_GET_SLOT_LEADERS: usize = 5000;
+// Compressing is paid for by the node on every notification, so levels beyond zstd's default
+// are not offered
+pub const MAX_PUBSUB_COMPRESSION_LEVEL: i32 = 3;
 
 // Validators that are this number of slots behind are considered delinquent
 pub const DELINQUENT_VALIDATOR_SLOT_DISTANCE: u64 = 128;
@@ -263,6 +288,7 @@ pub enum RpcError {
 pub enum TokenAccountsFilter {
     Mint(Pubkey),
     ProgramId(Pubkey),
+    Mints(Vec<Pubkey>),
 }
 
 #[cfg(test)]
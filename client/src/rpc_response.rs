@@ -1,17 +1,22 @@
 use {
     crate::client_error,
-    solana_account_decoder::{parse_token::UiTokenAmount, UiAccount},
+    solana_account_decoder::{
+        parse_stake::StakeAccountType, parse_token::UiTokenAmount, UiAccount,
+    },
     solana_sdk::{
         clock::{Epoch, Slot, UnixTimestamp},
+        commitment_config::CommitmentLevel,
         fee_calculator::{FeeCalculator, FeeRateGovernor},
         hash::Hash,
         inflation::Inflation,
         transaction::{Result, TransactionError},
     },
     solana_transaction_status::{
-        ConfirmedTransactionStatusWithSignature, TransactionConfirmationStatus,
+        ConfirmedTransactionStatusWithSignature, EncodedConfirmedTransaction, Rewards,
+        TransactionConfirmationStatus, UiConfirmedBlock, UiInnerInstructions,
     },
     std::{collections::HashMap, fmt, net::SocketAddr},
+    thiserror::Error,
 };
 
 pub type RpcResult<T> = client_error::Result<Response<T>>;
@@ -48,6 +53,29 @@ pub struct RpcBlockhash {
     pub last_valid_block_height: u64,
 }
 
+/// Value of an `isBlockhashValidNotification`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcBlockhashValidity {
+    pub blockhash: String,
+    /// False once the blockhash has expired, or if it is unknown
+    pub valid: bool,
+    /// Tells an expired blockhash from one the node never saw
+    pub status: RpcBlockhashStatus,
+    pub block_height: u64,
+    /// `None` if the blockhash was never seen by the node
+    pub last_valid_block_height: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum RpcBlockhashStatus {
+    Valid,
+    Expired,
+    /// The blockhash is not in the recent blockhashes of the node
+    Unknown,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcFees {
@@ -123,6 +151,65 @@ pub struct RpcKeyedAccount {
     pub account: UiAccount,
 }
 
+/// Account modified in a slot, as reported by `programChangesNotification`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcAccountChange {
+    pub pubkey: String,
+    pub lamports: u64,
+}
+
+/// Value of an `accountNotification`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcAccountUpdate {
+    #[serde(flatten)]
+    pub account: UiAccount,
+    /// Set when the account was closed, as opposed to missing because the node switched
+    /// to a fork on which it never existed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted: Option<bool>,
+    /// Set for subscriptions asking for proofs, when the node can prove the update; never
+    /// set at finalized commitment for writes older than the root, whose bank is unlinked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proof: Option<RpcAccountProof>,
+}
+
+/// Proof that an account was written in a slot. The account hash is computed from the
+/// account and `slot`, and leads to the accounts delta hash through `inclusionPath`, from
+/// which the bank hash of the slot follows.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcAccountProof {
+    pub slot: Slot,
+    pub account_hash: String,
+    pub inclusion_path: Vec<RpcMerkleProofLevel>,
+    pub accounts_delta_hash: String,
+    pub parent_bank_hash: String,
+    pub signature_count: u64,
+    pub last_blockhash: String,
+    pub bank_hash: String,
+}
+
+/// A level of a merkle inclusion path, from the leaves up: the node on the path is hashed
+/// at position `index` among `siblings`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcMerkleProofLevel {
+    pub index: usize,
+    pub siblings: Vec<String>,
+}
+
+/// A chunk of the accounts found by the initial scan of a `programSubscribe` request
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcProgramAccountsChunk {
+    pub accounts: Vec<RpcKeyedAccount>,
+    pub index: usize,
+    /// True for the final chunk; only incremental `programNotification`s follow it
+    pub last: bool,
+}
+
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
 pub struct SlotInfo {
     pub slot: Slot,
@@ -130,6 +217,92 @@ pub struct SlotInfo {
     pub root: Slot,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcRootInfo {
+    pub slot: Slot,
+    pub parent: Slot,
+    pub blockhash: String,
+    pub block_height: u64,
+}
+
+/// Value of a `rootNotification`: the bare root slot, or an `RpcRootInfo` for subscriptions
+/// with `enableBlockInfo`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum RpcRootNotification {
+    Slot(Slot),
+    Info(RpcRootInfo),
+}
+
+/// Result of a `notificationsDropped` notification, sent to a subscription once the node
+/// catches up after dropping notifications it would have received
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcNotificationsDropped {
+    pub count: u64,
+    /// Slots of the first and last dropped notifications, if they were about slots
+    pub first_slot: Option<Slot>,
+    pub last_slot: Option<Slot>,
+}
+
+impl RpcNotificationsDropped {
+    pub fn merge(&mut self, other: &Self) {
+        self.count += other.count;
+        self.first_slot = self.first_slot.into_iter().chain(other.first_slot).min();
+        self.last_slot = self.last_slot.into_iter().chain(other.last_slot).max();
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcEpochStart {
+    pub epoch: Epoch,
+    pub first_slot: Slot,
+    pub slots_in_epoch: u64,
+    /// First rooted slot of the new epoch
+    pub slot: Slot,
+    /// Whether stakes for the following epoch are known, so its leader schedule can be computed
+    pub leader_schedule_ready: bool,
+    /// Cluster stake activation at the end of the previous epoch
+    pub stake_activation: Option<RpcStakeActivationSummary>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcEpochRewards {
+    /// Epoch the rewards were earned in
+    pub epoch: Epoch,
+    /// Slot the rewards were credited in
+    pub slot: Slot,
+    pub rewards: Rewards,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcUpcomingLeaders {
+    /// Slot the upcoming leaders follow
+    pub slot: Slot,
+    pub leader_slots: Vec<RpcLeaderSlot>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcLeaderSlot {
+    pub slot: Slot,
+    /// Leader identity, as a base-58 encoded string
+    pub leader: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcStakeActivationSummary {
+    pub epoch: Epoch,
+    pub effective: u64,
+    pub activating: u64,
+    pub deactivating: u64,
+}
+
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct SlotTransactionStats {
@@ -173,6 +346,13 @@ pub enum SlotUpdate {
         slot: Slot,
         timestamp: u64,
     },
+    Pruned {
+        slot: Slot,
+        parent: Slot,
+        root: Slot,
+        timestamp: u64,
+        reason: SlotPruneReason,
+    },
 }
 
 impl SlotUpdate {
@@ -185,10 +365,21 @@ impl SlotUpdate {
             Self::Dead { slot, .. } => *slot,
             Self::OptimisticConfirmation { slot, .. } => *slot,
             Self::Root { slot, .. } => *slot,
+            Self::Pruned { slot, .. } => *slot,
         }
     }
 }
 
+/// Why a bank was dropped from the fork graph without being rooted
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum SlotPruneReason {
+    /// The slot failed replay and was marked dead
+    Dead,
+    /// The slot is on a fork that does not descend from the new root
+    AbandonedFork,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(rename_all = "camelCase", untagged)]
 pub enum RpcSignatureResult {
@@ -204,10 +395,74 @@ pub struct RpcLogsResponse {
     pub logs: Vec<String>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcRecentSignatureNotification {
+    pub slot: Slot,
+    pub commitment: CommitmentLevel,
+    pub result: RpcSignatureResult,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Error)]
+#[serde(rename_all = "camelCase")]
+pub enum RpcBlockUpdateError {
+    #[error("block store error")]
+    BlockStoreError,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcBlockUpdate {
+    pub slot: Slot,
+    pub block: Option<UiConfirmedBlock>,
+    pub err: Option<RpcBlockUpdateError>,
+}
+
+/// One entry of a `getTransactions` response. `transaction` is `None` when the transaction
+/// wasn't found or isn't confirmed, or when looking it up failed with `error`.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcTransactionsEntry {
+    pub signature: String,
+    pub transaction: Option<EncodedConfirmedTransaction>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcMintState {
+    pub supply: String,
+    pub decimals: u8,
+    pub mint_authority: Option<String>,
+    pub freeze_authority: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcMintUpdate {
+    pub mint: String, // Pubkey as base58 string
+    // None if the mint state had not been observed before this update
+    pub previous: Option<RpcMintState>,
+    pub current: RpcMintState,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcStakeUpdate {
+    pub stake: String, // Pubkey as base58 string
+    pub lamports: u64,
+    pub state: StakeAccountType,
+    // None if the account is uninitialized or closed
+    pub activation: Option<RpcStakeActivation>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct ProcessedSignatureResult {
     pub err: Option<TransactionError>,
+    /// Commitment level reached, only set for subscriptions with several commitment levels
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confirmation_status: Option<TransactionConfirmationStatus>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -235,9 +490,56 @@ pub struct RpcContactInfo {
     pub shred_version: Option<u16>,
 }
 
+/// The distribution of the versions and feature sets of the nodes returned by `getClusterNodes`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcClusterNodesSummary {
+    pub total_nodes: usize,
+    /// Stake of the current epoch, in lamports
+    pub total_stake: u64,
+    /// Ordered by stake, then by number of nodes
+    pub versions: Vec<RpcClusterNodesVersion>,
+    /// Ordered by stake, then by number of nodes
+    pub feature_sets: Vec<RpcClusterNodesFeatureSet>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcClusterNodesVersion {
+    /// Software version, if the node published it
+    pub version: Option<String>,
+    pub nodes: usize,
+    /// Stake of the nodes in the current epoch, in lamports
+    pub stake: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcClusterNodesFeatureSet {
+    /// First 4 bytes of the FeatureSet identifier, if the node published it
+    pub feature_set: Option<u32>,
+    pub nodes: usize,
+    /// Stake of the nodes in the current epoch, in lamports
+    pub stake: u64,
+}
+
 /// Map of leader base58 identity pubkeys to the slot indices relative to the first epoch slot
 pub type RpcLeaderSchedule = HashMap<String, Vec<usize>>;
 
+/// Map of leader base58 identity pubkeys to their slots and vote accounts
+pub type RpcLeaderScheduleWithVoteAccounts = HashMap<String, RpcLeaderScheduleEntry>;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcLeaderScheduleEntry {
+    /// Slot indices relative to the first epoch slot, or to the first slot of the range
+    pub slot_indices: Vec<usize>,
+    /// Vote accounts of the leader, as base-58 encoded strings
+    pub vote_accounts: Vec<String>,
+    /// Stake of the leader's vote accounts that the schedule was computed from, in lamports
+    pub stake: u64,
+}
+
 #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcBlockProductionRange {
@@ -251,6 +553,10 @@ pub struct RpcBlockProduction {
     /// Map of leader base58 identity pubkeys to a tuple of `(number of leader slots, number of blocks produced)`
     pub by_identity: HashMap<String, (usize, usize)>,
     pub range: RpcBlockProductionRange,
+    /// Map of leader base58 identity pubkeys to the slots they skipped, only set with
+    /// `withSkippedSlots`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skipped_slots_by_identity: Option<HashMap<String, Vec<Slot>>>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -291,6 +597,14 @@ pub struct RpcIdentity {
 pub struct RpcVoteAccountStatus {
     pub current: Vec<RpcVoteAccountInfo>,
     pub delinquent: Vec<RpcVoteAccountInfo>,
+    /// Slot the accounts were read at, only set with `changedSinceSlot` so that it can be
+    /// passed to the next request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slot: Option<Slot>,
+    /// Vote accounts returned at `changedSinceSlot` that no longer are, only set when the
+    /// result only holds the accounts that changed since
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub removed: Option<Vec<String>>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -329,6 +643,35 @@ pub struct RpcSignatureConfirmation {
     pub status: Result<()>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum RpcTransactionRetryState {
+    Pending,
+    Processed,
+    Rooted,
+    Expired,
+    Failed,
+    MaxRetriesElapsed,
+    Dropped,
+}
+
+/// The result of `sendTransaction` with `returnUnitsConsumed`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSendTransactionResult {
+    pub signature: String,
+    pub units_consumed: u64,
+}
+
+/// The progress of a transaction sent with `retryUntilExpired`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcTransactionRetryStatus {
+    pub state: RpcTransactionRetryState,
+    pub retries: usize,
+    pub last_valid_block_height: u64,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcSimulateTransactionResult {
@@ -336,6 +679,7 @@ pub struct RpcSimulateTransactionResult {
     pub logs: Option<Vec<String>>,
     pub accounts: Option<Vec<Option<UiAccount>>>,
     pub units_consumed: Option<u64>,
+    pub inner_instructions: Option<Vec<UiInnerInstructions>>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -352,6 +696,16 @@ pub struct RpcAccountBalance {
     pub lamports: u64,
 }
 
+/// The result of `getLargestAccounts`, which is served from a cache
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcLargestAccountsResponse {
+    pub context: RpcResponseContext,
+    pub value: Vec<RpcAccountBalance>,
+    /// Seconds since the accounts were computed, at `context.slot`
+    pub staleness: u64,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcSupply {
@@ -359,6 +713,25 @@ pub struct RpcSupply {
     pub circulating: u64,
     pub non_circulating: u64,
     pub non_circulating_accounts: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub non_circulating_breakdown: Option<RpcSupplyBreakdown>,
+}
+
+/// Non-circulating lamports by the reason the accounts holding them are non-circulating; an
+/// account matching several reasons is counted under the first one
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSupplyBreakdown {
+    /// Accounts of the built-in non-circulating list
+    pub listed: u64,
+    /// Stake accounts with a lockup in force
+    pub locked_stake: u64,
+    /// Stake accounts withdrawable by an autostake authority
+    pub autostaked: u64,
+    /// Accounts passed in `nonCirculatingAccounts`
+    pub accounts: u64,
+    /// Accounts owned by a program passed in `nonCirculatingOwners`
+    pub owners: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
@@ -397,6 +770,25 @@ pub struct RpcConfirmedTransactionStatusWithSignature {
     pub confirmation_status: Option<TransactionConfirmationStatus>,
 }
 
+/// Signatures found by a `getSignaturesForAddress` request with filters
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcFilteredSignatures {
+    pub signatures: Vec<RpcConfirmedTransactionStatusWithSignature>,
+    /// Set when the node stopped searching after examining
+    /// `MAX_GET_SIGNATURES_FOR_ADDRESS_EXAMINED` signatures; passed as `before`, it resumes
+    /// the search where it stopped
+    pub resume_before: Option<String>,
+}
+
+/// Result of `getSignaturesForAddress`, a plain list unless the request has filters
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RpcSignaturesForAddress {
+    Filtered(RpcFilteredSignatures),
+    Unfiltered(Vec<RpcConfirmedTransactionStatusWithSignature>),
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcPerfSample {
@@ -416,6 +808,28 @@ pub struct RpcInflationReward {
     pub commission: Option<u8>, // Vote account commission when the reward was credited
 }
 
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcStakeInflationReward {
+    pub stake_pubkey: String,
+    pub amount: u64,       // lamports
+    pub post_balance: u64, // lamports
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcVoteAccountInflationRewards {
+    pub epoch: Epoch,
+    pub effective_slot: Slot,
+    /// The vote account's own reward, from its commission
+    pub vote_reward: Option<RpcInflationReward>,
+    /// Rewards of the stake accounts delegated to the vote account, ordered by stake pubkey
+    pub stake_rewards: Vec<RpcStakeInflationReward>,
+    /// Whether the delegators were taken from the current delegations, because the stakes
+    /// the rewards were calculated from are no longer held by the node
+    pub approximate_delegations: bool,
+}
+
 impl From<ConfirmedTransactionStatusWithSignature> for RpcConfirmedTransactionStatusWithSignature {
     fn from(value: ConfirmedTransactionStatusWithSignature) -> Self {
         let ConfirmedTransactionStatusWithSignature {
@@ -441,3 +855,31 @@ pub struct RpcSnapshotSlotInfo {
     pub full: Slot,
     pub incremental: Option<Slot>,
 }
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum RpcHealthState {
+    Ok,
+    Behind,
+    Unknown,
+}
+
+/// The extended `getHealth` report
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcHealthReport {
+    pub status: RpcHealthState,
+    pub num_slots_behind: Option<Slot>,
+    /// Latest slot this node has published an accounts hash for
+    pub latest_accounts_hash_slot: Option<Slot>,
+    /// Latest slot any of the node's trusted validators have published an accounts hash for
+    pub latest_trusted_validator_accounts_hash_slot: Option<Slot>,
+    /// Highest full or incremental snapshot archive slot
+    pub latest_snapshot_slot: Option<Slot>,
+    /// Seconds since the latest snapshot archive was written
+    pub latest_snapshot_age_secs: Option<u64>,
+    /// How many slots the latest snapshot is behind the root
+    pub snapshot_slots_behind_root: Option<Slot>,
+    pub pubsub_notification_queue_depth: Option<usize>,
+    pub pubsub_notification_queue_capacity: Option<usize>,
+}
@@ -31,5 +31,21 @@ pub struct RpcTransportStats {
 /// [`MockSender`]: crate::mock_sender::MockSender
 pub trait RpcSender {
     fn send(&self, request: RpcRequest, params: serde_json::Value) -> Result<serde_json::Value>;
+
+    /// Send several requests at once, returning their results in request order.
+    ///
+    /// The outer `Result` fails if the batch as a whole could not be
+    /// transacted; each inner `Result` holds the outcome of one request. The
+    /// default implementation sends the requests one at a time.
+    fn send_batch(
+        &self,
+        requests: Vec<(RpcRequest, serde_json::Value)>,
+    ) -> Result<Vec<Result<serde_json::Value>>> {
+        Ok(requests
+            .into_iter()
+            .map(|(request, params)| self.send(request, params))
+            .collect())
+    }
+
     fn get_transport_stats(&self) -> RpcTransportStats;
 }
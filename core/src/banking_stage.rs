@@ -880,6 +880,7 @@ impl BankingStage {
             transaction_status_sender.is_some(),
             transaction_status_sender.is_some(),
             &mut execute_timings,
+            None,
         );
         load_execute_time.stop();
 
@@ -2651,6 +2652,8 @@ mod tests {
             let transaction_status_service = TransactionStatusService::new(
                 transaction_status_receiver,
                 Arc::new(AtomicU64::default()),
+                true,
+                None,
                 blockstore.clone(),
                 &Arc::new(AtomicBool::new(false)),
             );
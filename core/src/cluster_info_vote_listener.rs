@@ -36,6 +36,7 @@ use solana_sdk::{
     epoch_schedule::EpochSchedule,
     hash::Hash,
     pubkey::Pubkey,
+    signature::Signature,
     transaction::Transaction,
 };
 use solana_vote_program::{self, vote_state::Vote, vote_transaction};
@@ -568,6 +569,7 @@ impl ClusterInfoVoteListener {
     fn track_new_votes_and_notify_confirmations(
         vote: Vote,
         vote_pubkey: &Pubkey,
+        vote_signature: &Signature,
         vote_tracker: &VoteTracker,
         root_bank: &Bank,
         subscriptions: &RpcSubscriptions,
@@ -673,7 +675,7 @@ impl ClusterInfoVoteListener {
         }
 
         if is_new_vote {
-            subscriptions.notify_vote(&vote);
+            subscriptions.notify_vote(vote_pubkey, &vote, vote_signature);
             let _ = verified_vote_sender.send((*vote_pubkey, vote.slots));
         }
     }
@@ -726,20 +728,31 @@ impl ClusterInfoVoteListener {
         let mut new_optimistic_confirmed_slots = vec![];
 
         // Process votes from gossip and ReplayStage
-        for (is_gossip, (vote_pubkey, vote, _)) in gossip_vote_txs
+        for (is_gossip, (vote_pubkey, vote, _, vote_signature)) in gossip_vote_txs
             .iter()
             .filter_map(|gossip_tx| {
                 vote_transaction::parse_vote_transaction(gossip_tx)
                     .filter(|(vote_pubkey, vote, _)| {
                         Self::filter_gossip_votes(vote_tracker, vote_pubkey, vote, gossip_tx)
                     })
-                    .map(|v| (true, v))
+                    .map(|(vote_pubkey, vote, switch_proof_hash)| {
+                        (
+                            true,
+                            (
+                                vote_pubkey,
+                                vote,
+                                switch_proof_hash,
+                                gossip_tx.signatures[0],
+                            ),
+                        )
+                    })
             })
             .chain(replayed_votes.into_iter().map(|v| (false, v)))
         {
             Self::track_new_votes_and_notify_confirmations(
                 vote,
                 &vote_pubkey,
+                &vote_signature,
                 vote_tracker,
                 root_bank,
                 subscriptions,
@@ -1132,6 +1145,7 @@ mod tests {
                         vote_keypair.pubkey(),
                         replay_vote.clone(),
                         switch_proof_hash,
+                        Signature::default(),
                     ))
                     .unwrap();
             }
@@ -1419,6 +1433,7 @@ mod tests {
                             vote_keypair.pubkey(),
                             Vote::new(vec![vote_slot], Hash::default()),
                             switch_proof_hash,
+                            Signature::default(),
                         ))
                         .unwrap();
                 }
@@ -26,7 +26,7 @@ use {
         voting_service::VoteOp,
         window_service::DuplicateSlotReceiver,
     },
-    solana_client::rpc_response::SlotUpdate,
+    solana_client::rpc_response::{SlotPruneReason, SlotUpdate},
     solana_entry::entry::VerifyRecyclers,
     solana_gossip::cluster_info::ClusterInfo,
     solana_ledger::{
@@ -1652,7 +1652,7 @@ impl ReplayStage {
                     .unwrap()
                     .highest_confirmed_root(),
             );
-            Self::handle_new_root(
+            let pruned_banks = Self::handle_new_root(
                 new_root,
                 bank_forks,
                 progress,
@@ -1666,7 +1666,8 @@ impl ReplayStage {
                 vote_signatures,
                 epoch_slots_frozen_slots,
             );
-            rpc_subscriptions.notify_roots(rooted_slots);
+            rpc_subscriptions.notify_roots(rooted_banks);
+            rpc_subscriptions.notify_pruned_slots(new_root, pruned_banks);
             if let Some(sender) = bank_notification_sender {
                 sender
                     .send(BankNotification::Root(root_bank))
@@ -2723,12 +2724,24 @@ impl ReplayStage {
         has_new_vote_been_rooted: &mut bool,
         voted_signatures: &mut Vec<Signature>,
         epoch_slots_frozen_slots: &mut EpochSlotsFrozenSlots,
-    ) {
-        bank_forks.write().unwrap().set_root(
+    ) -> Vec<(Arc<Bank>, SlotPruneReason)> {
+        let abandoned_banks = bank_forks.write().unwrap().set_root(
             new_root,
             accounts_background_request_sender,
             highest_confirmed_root,
         );
+        // Classify the abandoned banks before `progress` forgets which of them were dead
+        let pruned_banks = abandoned_banks
+            .into_iter()
+            .map(|bank| {
+                let reason = if progress.is_dead(bank.slot()).unwrap_or(false) {
+                    SlotPruneReason::Dead
+                } else {
+                    SlotPruneReason::AbandonedFork
+                };
+                (bank, reason)
+            })
+            .collect();
         // Dropping the bank_forks write lock and reacquiring as a read lock is
         // safe because updates to bank_forks are only made by a single thread.
         let r_bank_forks = bank_forks.read().unwrap();
@@ -2758,6 +2771,7 @@ impl ReplayStage {
         let mut slots_ge_root = epoch_slots_frozen_slots.split_off(&new_root);
         // epoch_slots_frozen_slots now only contains entries >= `new_root`
         std::mem::swap(epoch_slots_frozen_slots, &mut slots_ge_root);
+        pruned_banks
     }
 
     fn generate_new_bank_forks(
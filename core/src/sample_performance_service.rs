@@ -1,4 +1,5 @@
 use solana_ledger::{blockstore::Blockstore, blockstore_meta::PerfSample};
+use solana_rpc::rpc_subscriptions::RpcSubscriptions;
 use solana_runtime::bank_forks::BankForks;
 use std::{
     sync::{
@@ -26,17 +27,19 @@ impl SamplePerformanceService {
     pub fn new(
         bank_forks: &Arc<RwLock<BankForks>>,
         blockstore: &Arc<Blockstore>,
+        rpc_subscriptions: &Arc<RpcSubscriptions>,
         exit: &Arc<AtomicBool>,
     ) -> Self {
         let exit = exit.clone();
         let blockstore = blockstore.clone();
         let bank_forks = bank_forks.clone();
+        let rpc_subscriptions = rpc_subscriptions.clone();
 
         info!("Starting SamplePerformance service");
         let thread_hdl = Builder::new()
             .name("sample-performance".to_string())
             .spawn(move || {
-                Self::run(bank_forks, &blockstore, exit);
+                Self::run(bank_forks, &blockstore, &rpc_subscriptions, exit);
             })
             .unwrap();
 
@@ -46,6 +49,7 @@ impl SamplePerformanceService {
     pub fn run(
         bank_forks: Arc<RwLock<BankForks>>,
         blockstore: &Arc<Blockstore>,
+        rpc_subscriptions: &RpcSubscriptions,
         exit: Arc<AtomicBool>,
     ) {
         let forks = bank_forks.read().unwrap();
@@ -87,6 +91,7 @@ impl SamplePerformanceService {
                 if let Err(e) = blockstore.write_perf_sample(highest_slot, &perf_sample) {
                     error!("write_perf_sample failed: slot {:?} {:?}", highest_slot, e);
                 }
+                rpc_subscriptions.notify_performance_sample(highest_slot, &perf_sample);
 
                 sample_snapshot = SamplePerformanceSnapshot {
                     num_transactions: bank.transaction_count(),
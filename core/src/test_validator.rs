@@ -11,7 +11,11 @@ use {
     },
     solana_ledger::{blockstore::create_new_ledger, create_new_tmp_ledger},
     solana_net_utils::PortRange,
-    solana_rpc::rpc::JsonRpcConfig,
+    solana_rpc::{
+        rpc::JsonRpcConfig,
+        rpc_pubsub_service::{PubSubConfig, PubSubDrain},
+        rpc_subscription_tracker::SubscriptionControl,
+    },
     solana_runtime::{
         genesis_utils::create_genesis_config_with_leader_ex,
         hardened_unpack::MAX_GENESIS_ARCHIVE_UNPACKED_SIZE, snapshot_config::SnapshotConfig,
@@ -91,6 +95,7 @@ pub struct TestValidatorGenesis {
     pub start_progress: Arc<RwLock<ValidatorStartProgress>>,
     pub authorized_voter_keypairs: Arc<RwLock<Vec<Arc<Keypair>>>>,
     pub max_ledger_shreds: Option<u64>,
+    pub rpc_pubsub_drain: Arc<PubSubDrain>,
 }
 
 impl TestValidatorGenesis {
@@ -513,6 +518,10 @@ impl TestValidator {
                 ),
             )),
             rpc_config: config.rpc_config.clone(),
+            pubsub_config: PubSubConfig {
+                drain: config.rpc_pubsub_drain.clone(),
+                ..PubSubConfig::default()
+            },
             accounts_hash_interval_slots: 100,
             account_paths: vec![ledger_path.join("accounts")],
             poh_verify: false, // Skip PoH verification of ledger on startup for speed
@@ -662,6 +671,14 @@ impl TestValidator {
     pub fn cluster_info(&self) -> Arc<ClusterInfo> {
         self.validator.as_ref().unwrap().cluster_info.clone()
     }
+
+    pub fn subscription_control(&self) -> SubscriptionControl {
+        self.validator
+            .as_ref()
+            .unwrap()
+            .subscription_control
+            .clone()
+    }
 }
 
 impl Drop for TestValidator {
@@ -57,9 +57,13 @@ use {
         },
         rpc::JsonRpcConfig,
         rpc_completed_slots_service::RpcCompletedSlotsService,
+        rpc_grpc_service::GrpcService,
         rpc_pubsub_service::{PubSubConfig, PubSubService},
         rpc_service::JsonRpcService,
+        rpc_sse_service::SseService,
+        rpc_subscription_tracker::SubscriptionControl,
         rpc_subscriptions::RpcSubscriptions,
+        transaction_notifier_interface::TransactionNotifierLock,
         transaction_status_service::TransactionStatusService,
     },
     solana_runtime::{
@@ -121,6 +125,10 @@ pub struct ValidatorConfig {
     pub accountsdb_repl_service_config: Option<AccountsDbReplServiceConfig>,
     pub accountsdb_plugin_config_files: Option<Vec<PathBuf>>,
     pub rpc_addrs: Option<(SocketAddr, SocketAddr)>, // (JsonRpc, JsonRpcPubSub)
+    /// Serves the pubsub subscriptions over gRPC as well; requires `rpc_addrs`
+    pub rpc_grpc_addr: Option<SocketAddr>,
+    /// Serves the pubsub subscriptions as server-sent events as well; requires `rpc_addrs`
+    pub rpc_sse_addr: Option<SocketAddr>,
     pub pubsub_config: PubSubConfig,
     pub snapshot_config: Option<SnapshotConfig>,
     pub max_ledger_shreds: Option<u64>,
@@ -182,6 +190,8 @@ impl Default for ValidatorConfig {
             accountsdb_repl_service_config: None,
             accountsdb_plugin_config_files: None,
             rpc_addrs: None,
+            rpc_grpc_addr: None,
+            rpc_sse_addr: None,
             pubsub_config: PubSubConfig::default(),
             snapshot_config: None,
             broadcast_stage_type: BroadcastStageType::Standard,
@@ -270,6 +280,8 @@ pub struct Validator {
     validator_exit: Arc<RwLock<Exit>>,
     json_rpc_service: Option<JsonRpcService>,
     pubsub_service: Option<PubSubService>,
+    grpc_service: Option<GrpcService>,
+    sse_service: Option<SseService>,
     rpc_completed_slots_service: JoinHandle<()>,
     optimistically_confirmed_bank_tracker: Option<OptimisticallyConfirmedBankTracker>,
     transaction_status_service: Option<TransactionStatusService>,
@@ -287,6 +299,7 @@ pub struct Validator {
     tvu: Tvu,
     ip_echo_server: Option<solana_net_utils::IpEchoServer>,
     pub cluster_info: Arc<ClusterInfo>,
+    pub subscription_control: SubscriptionControl,
     accountsdb_repl_service: Option<AccountsDbReplService>,
     accountsdb_plugin_service: Option<AccountsDbPluginService>,
 }
@@ -445,6 +458,9 @@ impl Validator {
             accountsdb_plugin_service
                 .as_ref()
                 .map(|plugin_service| plugin_service.get_accounts_update_notifier()),
+            accountsdb_plugin_service
+                .as_ref()
+                .and_then(|plugin_service| plugin_service.get_transaction_notifier()),
         );
 
         *start_progress.write().unwrap() = ValidatorStartProgress::StartingServices;
@@ -462,17 +478,6 @@ impl Validator {
         }
         let bank_forks = Arc::new(RwLock::new(bank_forks));
 
-        let sample_performance_service =
-            if config.rpc_addrs.is_some() && config.rpc_config.enable_rpc_transaction_history {
-                Some(SamplePerformanceService::new(
-                    &bank_forks,
-                    &blockstore,
-                    &exit,
-                ))
-            } else {
-                None
-            };
-
         info!("Starting validator with working bank slot {}", bank.slot());
         {
             let hard_forks: Vec<_> = bank.hard_forks().read().unwrap().iter().copied().collect();
@@ -514,11 +519,25 @@ impl Validator {
 
         let rpc_subscriptions = Arc::new(RpcSubscriptions::new_with_config(
             &exit,
+            Some(blockstore.clone()),
             bank_forks.clone(),
             block_commitment_cache.clone(),
             optimistically_confirmed_bank.clone(),
             &config.pubsub_config,
         ));
+        let subscription_control = rpc_subscriptions.control().clone();
+
+        let sample_performance_service =
+            if config.rpc_addrs.is_some() && config.rpc_config.enable_rpc_transaction_history {
+                Some(SamplePerformanceService::new(
+                    &bank_forks,
+                    &blockstore,
+                    &rpc_subscriptions,
+                    &exit,
+                ))
+            } else {
+                None
+            };
 
         let max_slots = Arc::new(MaxSlots::default());
         let (completed_data_sets_sender, completed_data_sets_receiver) =
@@ -619,6 +638,7 @@ impl Validator {
                     max_slots.clone(),
                     leader_schedule_cache.clone(),
                     max_complete_transaction_status_slot,
+                    Some(&rpc_subscriptions),
                 )),
                 if config.rpc_config.minimal_api {
                     None
@@ -651,6 +671,34 @@ impl Validator {
             (None, None, None, None, None)
         };
 
+        let grpc_service = config
+            .rpc_grpc_addr
+            .filter(|_| config.rpc_addrs.is_some() && !config.rpc_config.minimal_api)
+            .map(|grpc_addr| {
+                let (trigger, grpc_service) =
+                    GrpcService::new(config.pubsub_config.clone(), &rpc_subscriptions, grpc_addr);
+                config
+                    .validator_exit
+                    .write()
+                    .unwrap()
+                    .register_exit(Box::new(move || trigger.cancel()));
+                grpc_service
+            });
+
+        let sse_service = config
+            .rpc_sse_addr
+            .filter(|_| config.rpc_addrs.is_some() && !config.rpc_config.minimal_api)
+            .map(|sse_addr| {
+                let (trigger, sse_service) =
+                    SseService::new(config.pubsub_config.clone(), &rpc_subscriptions, sse_addr);
+                config
+                    .validator_exit
+                    .write()
+                    .unwrap()
+                    .register_exit(Box::new(move || trigger.cancel()));
+                sse_service
+            });
+
         if config.dev_halt_at_slot.is_some() {
             // Simulate a confirmed root to avoid RPC errors with CommitmentConfig::finalized() and
             // to ensure RPC endpoints like getConfirmedBlock, which require a confirmed root, work
@@ -883,6 +931,8 @@ impl Validator {
             serve_repair_service,
             json_rpc_service,
             pubsub_service,
+            grpc_service,
+            sse_service,
             rpc_completed_slots_service,
             optimistically_confirmed_bank_tracker,
             transaction_status_service,
@@ -899,6 +949,7 @@ impl Validator {
             ip_echo_server,
             validator_exit: config.validator_exit.clone(),
             cluster_info,
+            subscription_control,
             accountsdb_repl_service,
             accountsdb_plugin_service,
         }
@@ -953,6 +1004,14 @@ impl Validator {
             pubsub_service.join().expect("pubsub_service");
         }
 
+        if let Some(grpc_service) = self.grpc_service {
+            grpc_service.join().expect("grpc_service");
+        }
+
+        if let Some(sse_service) = self.sse_service {
+            sse_service.join().expect("sse_service");
+        }
+
         self.rpc_completed_slots_service
             .join()
             .expect("rpc_completed_slots_service");
@@ -1166,6 +1225,7 @@ fn new_banks_from_ledger(
     no_poh_speed_test: bool,
     accounts_package_sender: AccountsPackageSender,
     accounts_update_notifier: Option<AccountsUpdateNotifier>,
+    transaction_notifier: Option<TransactionNotifierLock>,
 ) -> (
     GenesisConfig,
     BankForks,
@@ -1258,12 +1318,16 @@ fn new_banks_from_ledger(
         ..blockstore_processor::ProcessOptions::default()
     };
 
+    let enable_rpc_transaction_history =
+        config.rpc_addrs.is_some() && config.rpc_config.enable_rpc_transaction_history;
     let transaction_history_services =
-        if config.rpc_addrs.is_some() && config.rpc_config.enable_rpc_transaction_history {
+        if enable_rpc_transaction_history || transaction_notifier.is_some() {
             initialize_rpc_transaction_history_services(
                 blockstore.clone(),
                 exit,
+                enable_rpc_transaction_history,
                 config.rpc_config.enable_cpi_and_log_storage,
+                transaction_notifier,
             )
         } else {
             TransactionHistoryServices::default()
@@ -1451,10 +1515,15 @@ fn backup_and_clear_blockstore(ledger_path: &Path, start_slot: Slot, shred_versi
     drop(blockstore);
 }
 
+/// Starts the services recording the transaction history for RPC when
+/// `enable_rpc_transaction_history` is set, or only the transaction statuses when they are
+/// needed by `transaction_notifier`
 fn initialize_rpc_transaction_history_services(
     blockstore: Arc<Blockstore>,
     exit: &Arc<AtomicBool>,
+    enable_rpc_transaction_history: bool,
     enable_cpi_and_log_storage: bool,
+    transaction_notifier: Option<TransactionNotifierLock>,
 ) -> TransactionHistoryServices {
     let max_complete_transaction_status_slot = Arc::new(AtomicU64::new(blockstore.max_root()));
     let (transaction_status_sender, transaction_status_receiver) = unbounded();
@@ -1465,9 +1534,19 @@ fn initialize_rpc_transaction_history_services(
     let transaction_status_service = Some(TransactionStatusService::new(
         transaction_status_receiver,
         max_complete_transaction_status_slot.clone(),
+        enable_rpc_transaction_history,
+        transaction_notifier,
         blockstore.clone(),
         exit,
     ));
+    if !enable_rpc_transaction_history {
+        return TransactionHistoryServices {
+            transaction_status_sender,
+            transaction_status_service,
+            max_complete_transaction_status_slot,
+            ..TransactionHistoryServices::default()
+        };
+    }
 
     let (rewards_recorder_sender, rewards_receiver) = unbounded();
     let rewards_recorder_sender = Some(rewards_recorder_sender);
@@ -215,7 +215,7 @@ impl VoteSimulator {
             &mut true,
             &mut Vec::new(),
             &mut EpochSlotsFrozenSlots::default(),
-        )
+        );
     }
 
     pub fn create_and_vote_new_branch(
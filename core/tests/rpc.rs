@@ -114,6 +114,9 @@ fn test_rpc_send_tx() {
         encoding: Some(UiAccountEncoding::Base64),
         commitment: None,
         data_slice: None,
+        send_initial: None,
+        compression_level: None,
+        with_proof: None,
     };
     let req = json_req!(
         "getAccountInfo",
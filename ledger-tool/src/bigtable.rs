@@ -4,13 +4,17 @@ use clap::{
 };
 use solana_clap_utils::{
     input_parsers::pubkey_of,
-    input_validators::{is_slot, is_valid_pubkey},
+    input_validators::{is_slot, is_valid_pubkey, is_within_range},
 };
 use solana_cli_output::{
     display::println_transaction, CliBlock, CliTransaction, CliTransactionConfirmation,
     OutputFormat,
 };
-use solana_ledger::{blockstore::Blockstore, blockstore_db::AccessType};
+use solana_ledger::{
+    bigtable_upload::{ConfirmedBlockUploadConfig, EXCLUDABLE_UPLOAD_DATA},
+    blockstore::Blockstore,
+    blockstore_db::AccessType,
+};
 use solana_sdk::{clock::Slot, pubkey::Pubkey, signature::Signature};
 use solana_transaction_status::{ConfirmedBlock, EncodedTransaction, UiTransactionEncoding};
 use std::{
@@ -24,8 +28,7 @@ async fn upload(
     blockstore: Blockstore,
     starting_slot: Slot,
     ending_slot: Option<Slot>,
-    allow_missing_metadata: bool,
-    force_reupload: bool,
+    config: ConfirmedBlockUploadConfig,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let bigtable = solana_storage_bigtable::LedgerStorage::new(false, None)
         .await
@@ -33,16 +36,41 @@ async fn upload(
 
     solana_ledger::bigtable_upload::upload_confirmed_blocks(
         Arc::new(blockstore),
-        bigtable,
+        &bigtable,
         starting_slot,
         ending_slot,
-        allow_missing_metadata,
-        force_reupload,
+        &config,
         Arc::new(AtomicBool::new(false)),
     )
     .await
 }
 
+async fn backfill(
+    blockstore: Blockstore,
+    starting_slot: Slot,
+    ending_slot: Slot,
+    config: ConfirmedBlockUploadConfig,
+    dry_run: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !dry_run {
+        return upload(blockstore, starting_slot, Some(ending_slot), config).await;
+    }
+    let bigtable = solana_storage_bigtable::LedgerStorage::new(true, None)
+        .await
+        .map_err(|err| format!("Failed to connect to storage: {:?}", err))?;
+
+    let slots = solana_ledger::bigtable_upload::find_missing_blocks(
+        &blockstore,
+        &bigtable,
+        starting_slot,
+        Some(ending_slot),
+    )
+    .await?;
+    println!("{:?}", slots);
+    println!("{} blocks missing", slots.len());
+    Ok(())
+}
+
 async fn delete_slots(slots: Vec<Slot>, dry_run: bool) -> Result<(), Box<dyn std::error::Error>> {
     let read_only = dry_run;
     let bigtable = solana_storage_bigtable::LedgerStorage::new(read_only, None)
@@ -229,6 +257,29 @@ pub trait BigTableSubCommand {
 
 impl BigTableSubCommand for App<'_, '_> {
     fn bigtable_subcommand(self) -> Self {
+        let num_parallel_uploads_arg = Arg::with_name("num_parallel_uploads")
+            .long("num-parallel-uploads")
+            .value_name("NUMBER")
+            .takes_value(true)
+            .validator(|value| is_within_range(value, 1, 1024))
+            .help("Number of blocks uploaded at once [default: 32]");
+        let read_ahead_depth_arg = Arg::with_name("read_ahead_depth")
+            .long("read-ahead-depth")
+            .value_name("NUMBER")
+            .takes_value(true)
+            .validator(|value| is_within_range(value, 1, 4096))
+            .help(
+                "Number of blocks read from the ledger ahead of their upload \
+                 [default: twice the number of parallel uploads]",
+            );
+        let exclude_arg = Arg::with_name("exclude")
+            .long("exclude")
+            .value_name("DATA")
+            .takes_value(true)
+            .multiple(true)
+            .possible_values(EXCLUDABLE_UPLOAD_DATA)
+            .help("Leave this data out of the uploaded blocks");
+
         self.subcommand(
             SubCommand::with_name("bigtable")
                 .about("Ledger data on a BigTable instance")
@@ -272,7 +323,47 @@ impl BigTableSubCommand for App<'_, '_> {
                                     Note: reupload will *not* delete any data from the tx-by-addr table;\
                                     Use with care.",
                                 ),
-                        ),
+                        )
+                        .arg(&num_parallel_uploads_arg)
+                        .arg(&read_ahead_depth_arg)
+                        .arg(&exclude_arg),
+                )
+                .subcommand(
+                    SubCommand::with_name("backfill")
+                        .about("Upload the blocks of the ledger that are missing from BigTable")
+                        .arg(
+                            Arg::with_name("starting_slot")
+                                .validator(is_slot)
+                                .value_name("START_SLOT")
+                                .takes_value(true)
+                                .index(1)
+                                .required(true)
+                                .help("Start looking for missing blocks at this slot"),
+                        )
+                        .arg(
+                            Arg::with_name("ending_slot")
+                                .validator(is_slot)
+                                .value_name("END_SLOT")
+                                .takes_value(true)
+                                .index(2)
+                                .required(true)
+                                .help("Stop looking for missing blocks at this slot"),
+                        )
+                        .arg(
+                            Arg::with_name("allow_missing_metadata")
+                                .long("allow-missing-metadata")
+                                .takes_value(false)
+                                .help("Don't panic if transaction metadata is missing"),
+                        )
+                        .arg(
+                            Arg::with_name("dry_run")
+                                .long("dry-run")
+                                .takes_value(false)
+                                .help("Only list the missing blocks"),
+                        )
+                        .arg(&num_parallel_uploads_arg)
+                        .arg(&read_ahead_depth_arg)
+                        .arg(&exclude_arg),
                 )
                 .subcommand(
                     SubCommand::with_name("delete-slots")
@@ -414,6 +505,22 @@ impl BigTableSubCommand for App<'_, '_> {
     }
 }
 
+fn upload_config_of(matches: &ArgMatches<'_>) -> ConfirmedBlockUploadConfig {
+    let default_config = ConfirmedBlockUploadConfig::default();
+    let num_blocks_to_upload_in_parallel = value_t!(matches, "num_parallel_uploads", usize)
+        .unwrap_or(default_config.num_blocks_to_upload_in_parallel);
+    let block_read_ahead_depth = value_t!(matches, "read_ahead_depth", usize)
+        .unwrap_or(num_blocks_to_upload_in_parallel * 2);
+    ConfirmedBlockUploadConfig {
+        force_reupload: matches.is_present("force_reupload"),
+        allow_missing_metadata: matches.is_present("allow_missing_metadata"),
+        num_blocks_to_upload_in_parallel,
+        block_read_ahead_depth,
+        ..default_config
+    }
+    .exclude(matches.values_of("exclude").unwrap_or_default())
+}
+
 pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
     let runtime = tokio::runtime::Runtime::new().unwrap();
 
@@ -424,17 +531,26 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
         ("upload", Some(arg_matches)) => {
             let starting_slot = value_t!(arg_matches, "starting_slot", Slot).unwrap_or(0);
             let ending_slot = value_t!(arg_matches, "ending_slot", Slot).ok();
-            let allow_missing_metadata = arg_matches.is_present("allow_missing_metadata");
-            let force_reupload = arg_matches.is_present("force_reupload");
+            let config = upload_config_of(arg_matches);
+            let blockstore =
+                crate::open_blockstore(ledger_path, AccessType::TryPrimaryThenSecondary, None);
+
+            runtime.block_on(upload(blockstore, starting_slot, ending_slot, config))
+        }
+        ("backfill", Some(arg_matches)) => {
+            let starting_slot = value_t_or_exit!(arg_matches, "starting_slot", Slot);
+            let ending_slot = value_t_or_exit!(arg_matches, "ending_slot", Slot);
+            let config = upload_config_of(arg_matches);
+            let dry_run = arg_matches.is_present("dry_run");
             let blockstore =
                 crate::open_blockstore(ledger_path, AccessType::TryPrimaryThenSecondary, None);
 
-            runtime.block_on(upload(
+            runtime.block_on(backfill(
                 blockstore,
                 starting_slot,
                 ending_slot,
-                allow_missing_metadata,
-                force_reupload,
+                config,
+                dry_run,
             ))
         }
         ("delete-slots", Some(arg_matches)) => {
@@ -2,6 +2,8 @@ use crate::blockstore::Blockstore;
 use log::*;
 use solana_measure::measure::Measure;
 use solana_sdk::clock::Slot;
+use solana_storage_bigtable::LedgerArchive;
+use solana_transaction_status::ConfirmedBlock;
 use std::{
     collections::HashSet,
     result::Result,
@@ -18,17 +20,73 @@ const NUM_BLOCKS_TO_UPLOAD_IN_PARALLEL: usize = 32;
 // Read up to this many blocks from blockstore before blocking on the upload process
 const BLOCK_READ_AHEAD_DEPTH: usize = NUM_BLOCKS_TO_UPLOAD_IN_PARALLEL * 2;
 
-pub async fn upload_confirmed_blocks(
-    blockstore: Arc<Blockstore>,
-    bigtable: solana_storage_bigtable::LedgerStorage,
+/// Names of the data that can be left out of the uploaded blocks
+pub const EXCLUDABLE_UPLOAD_DATA: &[&str] = &["token-balances", "rewards"];
+
+#[derive(Clone, Debug)]
+pub struct ConfirmedBlockUploadConfig {
+    pub force_reupload: bool,
+    pub allow_missing_metadata: bool,
+    pub num_blocks_to_upload_in_parallel: usize,
+    pub block_read_ahead_depth: usize,
+    /// Upload the token balances of the transactions, pre and post execution
+    pub upload_token_balances: bool,
+    /// Upload the rewards of the blocks and of their transactions
+    pub upload_rewards: bool,
+}
+
+impl Default for ConfirmedBlockUploadConfig {
+    fn default() -> Self {
+        ConfirmedBlockUploadConfig {
+            force_reupload: false,
+            allow_missing_metadata: false,
+            num_blocks_to_upload_in_parallel: NUM_BLOCKS_TO_UPLOAD_IN_PARALLEL,
+            block_read_ahead_depth: BLOCK_READ_AHEAD_DEPTH,
+            upload_token_balances: true,
+            upload_rewards: true,
+        }
+    }
+}
+
+impl ConfirmedBlockUploadConfig {
+    /// Leaves the data named by `EXCLUDABLE_UPLOAD_DATA` entries out of the uploaded blocks
+    pub fn exclude<'a>(mut self, data: impl IntoIterator<Item = &'a str>) -> Self {
+        for data in data {
+            match data {
+                "token-balances" => self.upload_token_balances = false,
+                "rewards" => self.upload_rewards = false,
+                _ => warn!("Unknown upload data: {}", data),
+            }
+        }
+        self
+    }
+}
+
+// Drop the data the config excludes from a block before it's uploaded
+fn strip_excluded_data(confirmed_block: &mut ConfirmedBlock, config: &ConfirmedBlockUploadConfig) {
+    if !config.upload_rewards {
+        confirmed_block.rewards.clear();
+    }
+    for meta in confirmed_block
+        .transactions
+        .iter_mut()
+        .filter_map(|transaction| transaction.meta.as_mut())
+    {
+        if !config.upload_token_balances {
+            meta.pre_token_balances = None;
+            meta.post_token_balances = None;
+        }
+        if !config.upload_rewards {
+            meta.rewards = None;
+        }
+    }
+}
+
+fn blockstore_slots(
+    blockstore: &Blockstore,
     starting_slot: Slot,
     ending_slot: Option<Slot>,
-    allow_missing_metadata: bool,
-    force_reupload: bool,
-    exit: Arc<AtomicBool>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let mut measure = Measure::start("entire upload");
-
+) -> Result<Vec<Slot>, Box<dyn std::error::Error>> {
     info!("Loading ledger slots starting at {}...", starting_slot);
     let blockstore_slots: Vec<_> = blockstore
         .slot_meta_iterator(starting_slot)
@@ -62,55 +120,78 @@ pub async fn upload_confirmed_blocks(
         blockstore_slots.first().unwrap(),
         blockstore_slots.last().unwrap()
     );
+    Ok(blockstore_slots)
+}
 
+// The blocks of `blockstore_slots` that are missing from bigtable
+async fn missing_blocks(bigtable: &dyn LedgerArchive, blockstore_slots: Vec<Slot>) -> Vec<Slot> {
     // Gather the blocks that are already present in bigtable, by slot
-    let bigtable_slots = if !force_reupload {
-        let mut bigtable_slots = vec![];
-        let first_blockstore_slot = *blockstore_slots.first().unwrap();
-        let last_blockstore_slot = *blockstore_slots.last().unwrap();
-        info!(
-            "Loading list of bigtable blocks between slots {} and {}...",
-            first_blockstore_slot, last_blockstore_slot
-        );
-
-        let mut start_slot = *blockstore_slots.first().unwrap();
-        while start_slot <= last_blockstore_slot {
-            let mut next_bigtable_slots = loop {
-                match bigtable.get_confirmed_blocks(start_slot, 1000).await {
-                    Ok(slots) => break slots,
-                    Err(err) => {
-                        error!("get_confirmed_blocks for {} failed: {:?}", start_slot, err);
-                        // Consider exponential backoff...
-                        tokio::time::sleep(Duration::from_secs(2)).await;
-                    }
+    let mut bigtable_slots = vec![];
+    let first_blockstore_slot = *blockstore_slots.first().unwrap();
+    let last_blockstore_slot = *blockstore_slots.last().unwrap();
+    info!(
+        "Loading list of bigtable blocks between slots {} and {}...",
+        first_blockstore_slot, last_blockstore_slot
+    );
+
+    let mut start_slot = first_blockstore_slot;
+    while start_slot <= last_blockstore_slot {
+        let mut next_bigtable_slots = loop {
+            match bigtable.get_confirmed_blocks(start_slot, 1000).await {
+                Ok(slots) => break slots,
+                Err(err) => {
+                    error!("get_confirmed_blocks for {} failed: {:?}", start_slot, err);
+                    // Consider exponential backoff...
+                    tokio::time::sleep(Duration::from_secs(2)).await;
                 }
-            };
-            if next_bigtable_slots.is_empty() {
-                break;
             }
-            bigtable_slots.append(&mut next_bigtable_slots);
-            start_slot = bigtable_slots.last().unwrap() + 1;
+        };
+        if next_bigtable_slots.is_empty() {
+            break;
         }
-        bigtable_slots
-            .into_iter()
-            .filter(|slot| *slot <= last_blockstore_slot)
-            .collect::<Vec<_>>()
-    } else {
-        Vec::new()
-    };
+        bigtable_slots.append(&mut next_bigtable_slots);
+        start_slot = bigtable_slots.last().unwrap() + 1;
+    }
 
     // The blocks that still need to be uploaded is the difference between what's already in the
     // bigtable and what's in blockstore...
-    let blocks_to_upload = {
-        let blockstore_slots = blockstore_slots.iter().cloned().collect::<HashSet<_>>();
-        let bigtable_slots = bigtable_slots.into_iter().collect::<HashSet<_>>();
-
-        let mut blocks_to_upload = blockstore_slots
-            .difference(&bigtable_slots)
-            .cloned()
-            .collect::<Vec<_>>();
-        blocks_to_upload.sort_unstable();
-        blocks_to_upload
+    let bigtable_slots = bigtable_slots
+        .into_iter()
+        .filter(|slot| *slot <= last_blockstore_slot)
+        .collect::<HashSet<_>>();
+    blockstore_slots
+        .into_iter()
+        .filter(|slot| !bigtable_slots.contains(slot))
+        .collect()
+}
+
+/// Returns the slots between `starting_slot` and `ending_slot` that have a block in the
+/// blockstore but not in bigtable
+pub async fn find_missing_blocks(
+    blockstore: &Blockstore,
+    bigtable: &dyn LedgerArchive,
+    starting_slot: Slot,
+    ending_slot: Option<Slot>,
+) -> Result<Vec<Slot>, Box<dyn std::error::Error>> {
+    let blockstore_slots = blockstore_slots(blockstore, starting_slot, ending_slot)?;
+    Ok(missing_blocks(bigtable, blockstore_slots).await)
+}
+
+pub async fn upload_confirmed_blocks(
+    blockstore: Arc<Blockstore>,
+    bigtable: &dyn LedgerArchive,
+    starting_slot: Slot,
+    ending_slot: Option<Slot>,
+    config: &ConfirmedBlockUploadConfig,
+    exit: Arc<AtomicBool>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut measure = Measure::start("entire upload");
+
+    let blockstore_slots = blockstore_slots(&blockstore, starting_slot, ending_slot)?;
+    let blocks_to_upload = if config.force_reupload {
+        blockstore_slots
+    } else {
+        missing_blocks(bigtable, blockstore_slots).await
     };
 
     if blocks_to_upload.is_empty() {
@@ -128,7 +209,8 @@ pub async fn upload_confirmed_blocks(
     let (_loader_thread, receiver) = {
         let exit = exit.clone();
 
-        let (sender, receiver) = std::sync::mpsc::sync_channel(BLOCK_READ_AHEAD_DEPTH);
+        let num_blocks_to_upload_in_parallel = config.num_blocks_to_upload_in_parallel;
+        let (sender, receiver) = std::sync::mpsc::sync_channel(config.block_read_ahead_depth);
         (
             std::thread::spawn(move || {
                 let mut measure = Measure::start("block loader thread");
@@ -148,7 +230,7 @@ pub async fn upload_confirmed_blocks(
                         }
                     };
 
-                    if i > 0 && i % NUM_BLOCKS_TO_UPLOAD_IN_PARALLEL == 0 {
+                    if i > 0 && i % num_blocks_to_upload_in_parallel == 0 {
                         info!(
                             "{}% of blocks processed ({}/{})",
                             i * 100 / blocks_to_upload.len(),
@@ -168,7 +250,7 @@ pub async fn upload_confirmed_blocks(
     use futures::stream::StreamExt;
 
     let mut stream =
-        tokio_stream::iter(receiver.into_iter()).chunks(NUM_BLOCKS_TO_UPLOAD_IN_PARALLEL);
+        tokio_stream::iter(receiver.into_iter()).chunks(config.num_blocks_to_upload_in_parallel);
 
     while let Some(blocks) = stream.next().await {
         if exit.load(Ordering::Relaxed) {
@@ -184,18 +266,19 @@ pub async fn upload_confirmed_blocks(
                 num_blocks -= 1;
                 None
             }
-            Some(confirmed_block) => {
+            Some(mut confirmed_block) => {
                 if confirmed_block
                     .transactions
                     .iter()
                     .any(|transaction| transaction.meta.is_none())
                 {
-                    if allow_missing_metadata {
+                    if config.allow_missing_metadata {
                         info!("Transaction metadata missing from slot {}", slot);
                     } else {
                         panic!("Transaction metadata missing from slot {}", slot);
                     }
                 }
+                strip_excluded_data(&mut confirmed_block, config);
                 Some(bigtable.upload_confirmed_block(slot, confirmed_block))
             }
         });
@@ -219,3 +302,75 @@ pub async fn upload_confirmed_blocks(
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_account_decoder::parse_token::token_amount_to_ui_amount,
+        solana_sdk::{hash::Hash, pubkey::Pubkey, signature::Keypair, system_transaction},
+        solana_transaction_status::{
+            Reward, TransactionStatusMeta, TransactionTokenBalance, TransactionWithStatusMeta,
+        },
+    };
+
+    #[test]
+    fn test_strip_excluded_data() {
+        let reward = Reward {
+            pubkey: Pubkey::new_unique().to_string(),
+            lamports: 42,
+            post_balance: 42,
+            reward_type: None,
+            commission: None,
+        };
+        let token_balance = TransactionTokenBalance {
+            account_index: 0,
+            mint: Pubkey::new_unique().to_string(),
+            ui_token_amount: token_amount_to_ui_amount(1, 0),
+            owner: String::new(),
+        };
+        let transaction = TransactionWithStatusMeta {
+            transaction: system_transaction::transfer(
+                &Keypair::new(),
+                &Pubkey::new_unique(),
+                1,
+                Hash::default(),
+            ),
+            meta: Some(TransactionStatusMeta {
+                pre_token_balances: Some(vec![token_balance.clone()]),
+                post_token_balances: Some(vec![token_balance]),
+                rewards: Some(vec![reward.clone()]),
+                ..TransactionStatusMeta::default()
+            }),
+        };
+        let block = ConfirmedBlock {
+            previous_blockhash: Hash::default().to_string(),
+            blockhash: Hash::default().to_string(),
+            parent_slot: 0,
+            transactions: vec![transaction],
+            rewards: vec![reward],
+            block_time: None,
+            block_height: None,
+        };
+
+        let mut stripped_block = block.clone();
+        strip_excluded_data(&mut stripped_block, &ConfirmedBlockUploadConfig::default());
+        assert_eq!(stripped_block, block);
+
+        let config = ConfirmedBlockUploadConfig::default().exclude(vec!["token-balances"]);
+        strip_excluded_data(&mut stripped_block, &config);
+        let meta = stripped_block.transactions[0].meta.as_ref().unwrap();
+        assert_eq!(meta.pre_token_balances, None);
+        assert_eq!(meta.post_token_balances, None);
+        assert!(meta.rewards.is_some());
+        assert_eq!(stripped_block.rewards.len(), 1);
+
+        let config = ConfirmedBlockUploadConfig::default().exclude(vec!["rewards"]);
+        let mut stripped_block = block;
+        strip_excluded_data(&mut stripped_block, &config);
+        let meta = stripped_block.transactions[0].meta.as_ref().unwrap();
+        assert!(meta.pre_token_balances.is_some());
+        assert_eq!(meta.rewards, None);
+        assert!(stripped_block.rewards.is_empty());
+    }
+}
@@ -1,6 +1,10 @@
 use {
-    crate::{bigtable_upload, blockstore::Blockstore},
+    crate::{
+        bigtable_upload::{self, ConfirmedBlockUploadConfig},
+        blockstore::Blockstore,
+    },
     solana_runtime::commitment::BlockCommitmentCache,
+    solana_storage_bigtable::LedgerArchive,
     std::{
         sync::atomic::{AtomicBool, Ordering},
         sync::{Arc, RwLock},
@@ -24,9 +28,10 @@ pub struct BigTableUploadService {
 impl BigTableUploadService {
     pub fn new(
         runtime: Arc<Runtime>,
-        bigtable_ledger_storage: solana_storage_bigtable::LedgerStorage,
+        bigtable_ledger_storage: Arc<dyn LedgerArchive>,
         blockstore: Arc<Blockstore>,
         block_commitment_cache: Arc<RwLock<BlockCommitmentCache>>,
+        upload_config: ConfirmedBlockUploadConfig,
         exit: Arc<AtomicBool>,
     ) -> Self {
         info!("Starting BigTable upload service");
@@ -38,6 +43,7 @@ impl BigTableUploadService {
                     bigtable_ledger_storage,
                     blockstore,
                     block_commitment_cache,
+                    upload_config,
                     exit,
                 )
             })
@@ -48,11 +54,18 @@ impl BigTableUploadService {
 
     fn run(
         runtime: Arc<Runtime>,
-        bigtable_ledger_storage: solana_storage_bigtable::LedgerStorage,
+        bigtable_ledger_storage: Arc<dyn LedgerArchive>,
         blockstore: Arc<Blockstore>,
         block_commitment_cache: Arc<RwLock<BlockCommitmentCache>>,
+        upload_config: ConfirmedBlockUploadConfig,
         exit: Arc<AtomicBool>,
     ) {
+        // The service never reuploads blocks, and uploads the blocks missing metadata as they are
+        let upload_config = ConfirmedBlockUploadConfig {
+            force_reupload: false,
+            allow_missing_metadata: true,
+            ..upload_config
+        };
         let mut start_slot = 0;
         loop {
             if exit.load(Ordering::Relaxed) {
@@ -72,11 +85,10 @@ impl BigTableUploadService {
 
             let result = runtime.block_on(bigtable_upload::upload_confirmed_blocks(
                 blockstore.clone(),
-                bigtable_ledger_storage.clone(),
+                &*bigtable_ledger_storage,
                 start_slot,
                 Some(end_slot),
-                true,
-                false,
+                &upload_config,
                 exit.clone(),
             ));
 
@@ -35,7 +35,7 @@ use {
         sanitize::Sanitize,
         signature::{Keypair, Signature, Signer},
         timing::timestamp,
-        transaction::VersionedTransaction,
+        transaction::{TransactionError, VersionedTransaction},
     },
     solana_storage_proto::{StoredExtendedRewards, StoredTransactionStatusMeta},
     solana_transaction_status::{
@@ -44,7 +44,7 @@ use {
     },
     std::{
         borrow::Cow,
-        cell::RefCell,
+        cell::{Cell, RefCell},
         cmp,
         collections::{hash_map::Entry as HashMapEntry, BTreeMap, HashMap, HashSet},
         convert::TryInto,
@@ -120,6 +120,43 @@ pub struct CompletedDataSetInfo {
     pub end_index: u32,
 }
 
+/// Narrows the signatures listed by `get_filtered_signatures_for_address`. The filter is
+/// applied while walking the address signatures index, so signatures it rejects don't count
+/// toward the `limit`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AddressSignaturesFilter {
+    /// Only list transactions that failed
+    pub failed_only: bool,
+    /// Only list transactions that succeeded
+    pub succeeded_only: bool,
+    /// Only list transactions that invoke this program, directly or through an inner
+    /// instruction
+    pub program_id: Option<Pubkey>,
+    /// Lowest slot to list signatures from, inclusive
+    pub min_slot: Option<Slot>,
+    /// Highest slot to list signatures from, inclusive
+    pub max_slot: Option<Slot>,
+}
+
+impl AddressSignaturesFilter {
+    pub fn matches_slot(&self, slot: Slot) -> bool {
+        slot >= self.min_slot.unwrap_or_default() && slot <= self.max_slot.unwrap_or(Slot::MAX)
+    }
+
+    pub fn matches_status(&self, err: &Option<TransactionError>) -> bool {
+        !((self.failed_only && err.is_none()) || (self.succeeded_only && err.is_some()))
+    }
+}
+
+/// Signatures listed by `get_filtered_signatures_for_address`
+#[derive(Debug, Default, PartialEq)]
+pub struct AddressSignatures {
+    pub infos: Vec<ConfirmedTransactionStatusWithSignature>,
+    /// Set when the search stopped after examining `max_examined` signatures, before `limit`
+    /// of them matched the filter. Passed as `before`, it resumes the search where it stopped.
+    pub resume_before: Option<Signature>,
+}
+
 pub struct BlockstoreSignals {
     pub blockstore: Blockstore,
     pub ledger_signal_receiver: Receiver<bool>,
@@ -2492,6 +2529,31 @@ impl Blockstore {
         until: Option<Signature>,
         limit: usize,
     ) -> Result<Vec<ConfirmedTransactionStatusWithSignature>> {
+        self.get_filtered_signatures_for_address(
+            address,
+            highest_slot,
+            before,
+            until,
+            limit,
+            &AddressSignaturesFilter::default(),
+            usize::MAX,
+        )
+        .map(|signatures| signatures.infos)
+    }
+
+    /// Lists up to `limit` signatures matching `filter`, examining at most `max_examined`
+    /// signatures of the address
+    #[allow(clippy::too_many_arguments)]
+    pub fn get_filtered_signatures_for_address(
+        &self,
+        address: Pubkey,
+        highest_slot: Slot, // highest_confirmed_root or highest_confirmed_slot
+        before: Option<Signature>,
+        until: Option<Signature>,
+        limit: usize,
+        filter: &AddressSignaturesFilter,
+        max_examined: usize,
+    ) -> Result<AddressSignatures> {
         datapoint_info!(
             "blockstore-rpc-api",
             (
@@ -2509,13 +2571,13 @@ impl Blockstore {
         // `before` signature if present.  Also generate a HashSet of signatures that should
         // be excluded from the results.
         let mut get_before_slot_timer = Measure::start("get_before_slot_timer");
-        let (slot, mut before_excluded_signatures) = match before {
+        let (mut slot, mut before_excluded_signatures) = match before {
             None => (highest_slot, None),
             Some(before) => {
                 let transaction_status =
                     self.get_transaction_status(before, &confirmed_unrooted_slots)?;
                 match transaction_status {
-                    None => return Ok(vec![]),
+                    None => return Ok(AddressSignatures::default()),
                     Some((slot, _)) => {
                         let mut slot_signatures = self.get_sorted_block_signatures(slot)?;
                         if let Some(pos) = slot_signatures.iter().position(|&x| x == before) {
@@ -2531,6 +2593,15 @@ impl Blockstore {
             }
         };
         get_before_slot_timer.stop();
+        if let Some(max_slot) = filter.max_slot {
+            if max_slot < slot {
+                slot = max_slot;
+                before_excluded_signatures = None;
+            }
+        }
+        if filter.min_slot.unwrap_or_default() > slot {
+            return Ok(AddressSignatures::default());
+        }
 
         // Generate a HashSet of signatures that should be excluded from the results based on
         // `until` signature
@@ -2554,6 +2625,36 @@ impl Blockstore {
             }
         };
         get_until_slot_timer.stop();
+        let lowest_slot = cmp::max(lowest_slot, filter.min_slot.unwrap_or_default());
+
+        // Signatures that pass the slot range may still be rejected by their transaction
+        let examined = Cell::new(0);
+        let last_examined = Cell::new(None);
+        let matches_filter = |signature: Signature| -> Result<bool> {
+            examined.set(examined.get() + 1);
+            last_examined.set(Some(signature));
+            if let Some(program_id) = &filter.program_id {
+                return match self.get_transaction_with_status(signature, &confirmed_unrooted_slots)
+                {
+                    Ok(Some(ConfirmedTransaction { transaction, .. })) => {
+                        let err = transaction
+                            .meta
+                            .as_ref()
+                            .and_then(|meta| meta.status.clone().err());
+                        Ok(filter.matches_status(&err) && transaction.invokes_program(program_id))
+                    }
+                    Ok(None) | Err(BlockstoreError::UnsupportedTransactionVersion) => Ok(false),
+                    Err(err) => Err(err),
+                };
+            }
+            if filter.failed_only || filter.succeeded_only {
+                let err = self
+                    .get_transaction_status(signature, &confirmed_unrooted_slots)?
+                    .and_then(|(_slot, status)| status.status.err());
+                return Ok(filter.matches_status(&err));
+            }
+            Ok(true)
+        };
 
         // Fetch the list of signatures that affect the given address
         let first_available_block = self.get_first_available_block()?;
@@ -2563,14 +2664,14 @@ impl Blockstore {
         let mut get_initial_slot_timer = Measure::start("get_initial_slot_timer");
         let mut signatures = self.find_address_signatures_for_slot(address, slot)?;
         signatures.reverse();
-        if let Some(excluded_signatures) = before_excluded_signatures.take() {
-            address_signatures.extend(
-                signatures
-                    .into_iter()
-                    .filter(|(_, signature)| !excluded_signatures.contains(signature)),
-            )
-        } else {
-            address_signatures.append(&mut signatures);
+        let excluded_signatures = before_excluded_signatures.take().unwrap_or_default();
+        for (slot, signature) in signatures {
+            if examined.get() >= max_examined {
+                break;
+            }
+            if !excluded_signatures.contains(&signature) && matches_filter(signature)? {
+                address_signatures.push((slot, signature));
+            }
         }
         get_initial_slot_timer.stop();
 
@@ -2585,14 +2686,14 @@ impl Blockstore {
             .max_slot;
 
         let mut starting_primary_index_iter_timer = Measure::start("starting_primary_index_iter");
-        if slot > next_max_slot {
+        if slot > next_max_slot && examined.get() < max_examined {
             let mut starting_iterator = self.address_signatures_cf.iter(IteratorMode::From(
                 (starting_primary_index, address, slot, Signature::default()),
                 IteratorDirection::Reverse,
             ))?;
 
             // Iterate through starting_iterator until limit is reached
-            while address_signatures.len() < limit {
+            while address_signatures.len() < limit && examined.get() < max_examined {
                 if let Some(((i, key_address, slot, signature), _)) = starting_iterator.next() {
                     if slot == next_max_slot || slot < lowest_slot {
                         break;
@@ -2601,7 +2702,9 @@ impl Blockstore {
                         && key_address == address
                         && slot >= first_available_block
                     {
-                        if self.is_root(slot) || confirmed_unrooted_slots.contains(&slot) {
+                        if (self.is_root(slot) || confirmed_unrooted_slots.contains(&slot))
+                            && matches_filter(signature)?
+                        {
                             address_signatures.push((slot, signature));
                         }
                         continue;
@@ -2615,7 +2718,14 @@ impl Blockstore {
                 let mut signatures =
                     self.find_address_signatures_for_slot(address, next_max_slot)?;
                 signatures.reverse();
-                address_signatures.append(&mut signatures);
+                for (slot, signature) in signatures {
+                    if examined.get() >= max_examined {
+                        break;
+                    }
+                    if matches_filter(signature)? {
+                        address_signatures.push((slot, signature));
+                    }
+                }
             }
         }
         starting_primary_index_iter_timer.stop();
@@ -2626,7 +2736,7 @@ impl Blockstore {
             (next_primary_index, address, slot, Signature::default()),
             IteratorDirection::Reverse,
         ))?;
-        while address_signatures.len() < limit {
+        while address_signatures.len() < limit && examined.get() < max_examined {
             if let Some(((i, key_address, slot, signature), _)) = next_iterator.next() {
                 // Skip next_max_slot, which is already included
                 if slot == next_max_slot {
@@ -2639,7 +2749,9 @@ impl Blockstore {
                     && key_address == address
                     && slot >= first_available_block
                 {
-                    if self.is_root(slot) || confirmed_unrooted_slots.contains(&slot) {
+                    if (self.is_root(slot) || confirmed_unrooted_slots.contains(&slot))
+                        && matches_filter(signature)?
+                    {
                         address_signatures.push((slot, signature));
                     }
                     continue;
@@ -2648,6 +2760,9 @@ impl Blockstore {
             break;
         }
         next_primary_index_iter_timer.stop();
+        let resume_before = (examined.get() >= max_examined && address_signatures.len() < limit)
+            .then(|| last_examined.get())
+            .flatten();
         let mut address_signatures: Vec<(Slot, Signature)> = address_signatures
             .into_iter()
             .filter(|(_, signature)| !until_excluded_signatures.contains(signature))
@@ -2707,7 +2822,10 @@ impl Blockstore {
             )
         );
 
-        Ok(infos)
+        Ok(AddressSignatures {
+            infos,
+            resume_before,
+        })
     }
 
     pub fn read_rewards(&self, index: Slot) -> Result<Option<Rewards>> {
@@ -7861,6 +7979,177 @@ pub mod tests {
         assert!(results2.len() < results.len());
     }
 
+    #[test]
+    fn test_get_filtered_signatures_for_address() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+
+        let address = solana_sdk::pubkey::new_rand();
+        let program0 = solana_sdk::pubkey::new_rand();
+        let program1 = solana_sdk::pubkey::new_rand();
+        for slot in 1..=4 {
+            let transactions: Vec<_> = [program0, program1]
+                .iter()
+                .map(|program_id| {
+                    Transaction::new_with_compiled_instructions(
+                        &[&Keypair::new()],
+                        &[address],
+                        Hash::default(),
+                        vec![*program_id],
+                        vec![CompiledInstruction::new(2, &(), vec![1])],
+                    )
+                })
+                .collect();
+            let entries = vec![next_entry_mut(
+                &mut Hash::default(),
+                0,
+                transactions.clone(),
+            )];
+            let shreds = entries_to_test_shreds(entries, slot, slot - 1, true, 0);
+            blockstore.insert_shreds(shreds, None, false).unwrap();
+
+            for transaction in transactions {
+                // Transactions invoking program1 fail
+                let status = if transaction.message.account_keys[2] == program1 {
+                    Err(TransactionError::AccountNotFound)
+                } else {
+                    Ok(())
+                };
+                blockstore
+                    .write_transaction_status(
+                        slot,
+                        transaction.signatures[0],
+                        transaction.message.account_keys.iter().collect(),
+                        vec![],
+                        TransactionStatusMeta {
+                            status,
+                            ..TransactionStatusMeta::default()
+                        },
+                    )
+                    .unwrap();
+            }
+        }
+        blockstore.set_roots(vec![1, 2, 3, 4].iter()).unwrap();
+
+        let get_signatures = |limit, filter: AddressSignaturesFilter| {
+            blockstore
+                .get_filtered_signatures_for_address(
+                    address,
+                    4,
+                    None,
+                    None,
+                    limit,
+                    &filter,
+                    usize::MAX,
+                )
+                .unwrap()
+                .infos
+        };
+        let slots = |results: &[ConfirmedTransactionStatusWithSignature]| {
+            results.iter().map(|result| result.slot).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            get_signatures(usize::MAX, AddressSignaturesFilter::default()).len(),
+            8
+        );
+
+        // Rejected signatures don't count toward the limit
+        let failed = get_signatures(
+            2,
+            AddressSignaturesFilter {
+                failed_only: true,
+                ..AddressSignaturesFilter::default()
+            },
+        );
+        assert_eq!(slots(&failed), vec![4, 3]);
+        assert!(failed.iter().all(|result| result.err.is_some()));
+
+        let succeeded = get_signatures(
+            usize::MAX,
+            AddressSignaturesFilter {
+                succeeded_only: true,
+                ..AddressSignaturesFilter::default()
+            },
+        );
+        assert_eq!(slots(&succeeded), vec![4, 3, 2, 1]);
+        assert!(succeeded.iter().all(|result| result.err.is_none()));
+
+        let invoking_program0 = get_signatures(
+            usize::MAX,
+            AddressSignaturesFilter {
+                program_id: Some(program0),
+                ..AddressSignaturesFilter::default()
+            },
+        );
+        assert_eq!(invoking_program0, succeeded);
+        assert!(get_signatures(
+            usize::MAX,
+            AddressSignaturesFilter {
+                failed_only: true,
+                program_id: Some(program0),
+                ..AddressSignaturesFilter::default()
+            },
+        )
+        .is_empty());
+
+        let in_range = get_signatures(
+            usize::MAX,
+            AddressSignaturesFilter {
+                min_slot: Some(2),
+                max_slot: Some(3),
+                ..AddressSignaturesFilter::default()
+            },
+        );
+        assert_eq!(slots(&in_range), vec![3, 3, 2, 2]);
+        assert_eq!(
+            slots(&get_signatures(
+                1,
+                AddressSignaturesFilter {
+                    program_id: Some(program1),
+                    max_slot: Some(2),
+                    ..AddressSignaturesFilter::default()
+                },
+            )),
+            vec![2]
+        );
+        assert!(get_signatures(
+            usize::MAX,
+            AddressSignaturesFilter {
+                min_slot: Some(5),
+                ..AddressSignaturesFilter::default()
+            },
+        )
+        .is_empty());
+
+        // A search examining at most 3 signatures at a time resumes where it stopped
+        let filter = AddressSignaturesFilter {
+            failed_only: true,
+            ..AddressSignaturesFilter::default()
+        };
+        let mut before = None;
+        let mut pages = vec![];
+        loop {
+            let page = blockstore
+                .get_filtered_signatures_for_address(
+                    address,
+                    4,
+                    before,
+                    None,
+                    usize::MAX,
+                    &filter,
+                    3,
+                )
+                .unwrap();
+            assert!(page.infos.len() <= 3);
+            pages.extend(page.infos);
+            before = page.resume_before;
+            if before.is_none() {
+                break;
+            }
+        }
+        assert_eq!(pages, get_signatures(usize::MAX, filter));
+    }
+
     #[test]
     #[allow(clippy::same_item_push)]
     fn test_get_last_hash() {
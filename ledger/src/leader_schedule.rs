@@ -65,7 +65,7 @@ impl LeaderSchedule {
 
     /// 'offset' is an index into the leader schedule. The function returns an
     /// iterator of indices i >= offset where the given pubkey is the leader.
-    pub(crate) fn get_indices(
+    pub fn get_indices(
         &self,
         pubkey: &Pubkey,
         offset: usize, // Starting index.
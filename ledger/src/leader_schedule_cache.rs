@@ -18,6 +18,7 @@ use std::{
 
 type CachedSchedules = (HashMap<Epoch, Arc<LeaderSchedule>>, VecDeque<u64>);
 const MAX_SCHEDULES: usize = 10;
+const MAX_HISTORICAL_SCHEDULES: usize = 4;
 
 struct CacheCapacity(usize);
 impl Default for CacheCapacity {
@@ -30,6 +31,9 @@ impl Default for CacheCapacity {
 pub struct LeaderScheduleCache {
     // Map from an epoch to a leader schedule for that epoch
     pub cached_schedules: RwLock<CachedSchedules>,
+    // Schedules of epochs older than the cached ones, computed on request. Kept apart so
+    // that they can't evict the schedules of current epochs.
+    historical_schedules: RwLock<CachedSchedules>,
     epoch_schedule: EpochSchedule,
     max_epoch: RwLock<Epoch>,
     max_schedules: CacheCapacity,
@@ -44,6 +48,7 @@ impl LeaderScheduleCache {
     pub fn new(epoch_schedule: EpochSchedule, root_bank: &Bank) -> Self {
         let cache = Self {
             cached_schedules: RwLock::new((HashMap::new(), VecDeque::new())),
+            historical_schedules: RwLock::new((HashMap::new(), VecDeque::new())),
             epoch_schedule,
             max_epoch: RwLock::new(0),
             max_schedules: CacheCapacity::default(),
@@ -199,6 +204,31 @@ impl LeaderScheduleCache {
         self.cached_schedules.read().unwrap().0.get(&epoch).cloned()
     }
 
+    /// Like `get_epoch_leader_schedule`, but also computes the schedule of an epoch the
+    /// cache no longer holds, while `bank` still has the stakes of the epoch. The last
+    /// `MAX_HISTORICAL_SCHEDULES` computed schedules are kept.
+    pub fn get_historical_epoch_leader_schedule(
+        &self,
+        epoch: Epoch,
+        bank: &Bank,
+    ) -> Option<Arc<LeaderSchedule>> {
+        if let Some(leader_schedule) = self.get_epoch_leader_schedule(epoch) {
+            return Some(leader_schedule);
+        }
+        if let Some(leader_schedule) = self.historical_schedules.read().unwrap().0.get(&epoch) {
+            return Some(leader_schedule.clone());
+        }
+        let leader_schedule = Arc::new(leader_schedule_utils::leader_schedule(epoch, bank)?);
+        let (ref mut historical_schedules, ref mut order) =
+            *self.historical_schedules.write().unwrap();
+        if let Entry::Vacant(entry) = historical_schedules.entry(epoch) {
+            entry.insert(leader_schedule.clone());
+            order.push_back(epoch);
+            Self::retain_latest(historical_schedules, order, MAX_HISTORICAL_SCHEDULES);
+        }
+        Some(leader_schedule)
+    }
+
     fn get_epoch_schedule_else_compute(
         &self,
         epoch: Epoch,
@@ -613,4 +643,47 @@ mod tests {
         cache.set_max_schedules(std::usize::MAX);
         assert_eq!(cache.max_schedules(), std::usize::MAX);
     }
+
+    #[test]
+    fn test_historical_epoch_leader_schedule() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(2);
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+        let cache = LeaderScheduleCache::new_from_bank(&bank);
+
+        // Evict the schedule of epoch 0
+        {
+            let (ref mut cached_schedules, ref mut order) =
+                *cache.cached_schedules.write().unwrap();
+            cached_schedules.remove(&0);
+            order.retain(|epoch| *epoch != 0);
+        }
+        assert!(cache.get_epoch_leader_schedule(0).is_none());
+
+        let leader_schedule = cache
+            .get_historical_epoch_leader_schedule(0, &bank)
+            .unwrap();
+        assert_eq!(
+            *leader_schedule,
+            leader_schedule_utils::leader_schedule(0, &bank).unwrap()
+        );
+        // Computed once, and kept apart from the cached schedules
+        assert!(Arc::ptr_eq(
+            &leader_schedule,
+            &cache
+                .get_historical_epoch_leader_schedule(0, &bank)
+                .unwrap()
+        ));
+        assert!(cache.get_epoch_leader_schedule(0).is_none());
+
+        // Cached schedules are served as they are
+        let leader_schedule_epoch = bank.get_leader_schedule_epoch(bank.slot());
+        assert!(Arc::ptr_eq(
+            &cache
+                .get_historical_epoch_leader_schedule(leader_schedule_epoch, &bank)
+                .unwrap(),
+            &cache
+                .get_epoch_leader_schedule(leader_schedule_epoch)
+                .unwrap()
+        ));
+    }
 }
@@ -15,6 +15,8 @@ pub fn safe_clone_config(config: &ValidatorConfig) -> ValidatorConfig {
         accountsdb_repl_service_config: config.accountsdb_repl_service_config.clone(),
         accountsdb_plugin_config_files: config.accountsdb_plugin_config_files.clone(),
         rpc_addrs: config.rpc_addrs,
+        rpc_grpc_addr: config.rpc_grpc_addr,
+        rpc_sse_addr: config.rpc_sse_addr,
         pubsub_config: config.pubsub_config.clone(),
         snapshot_config: config.snapshot_config.clone(),
         max_ledger_shreds: config.max_ledger_shreds,
@@ -209,6 +209,7 @@ fn test_local_cluster_signature_subscribe() {
         Some(RpcSignatureSubscribeConfig {
             commitment: Some(CommitmentConfig::processed()),
             enable_received_notification: Some(true),
+            commitment_levels: None,
         }),
     )
     .unwrap();
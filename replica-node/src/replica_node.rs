@@ -246,6 +246,7 @@ fn start_client_rpc_services(
             max_slots,
             leader_schedule_cache.clone(),
             max_complete_transaction_status_slot,
+            Some(&subscriptions),
         )),
         Some(pubsub_service),
         Some(OptimisticallyConfirmedBankTracker::new(
@@ -0,0 +1,97 @@
+use {
+    clap::{crate_description, crate_name, value_t_or_exit, App, Arg},
+    solana_rpc::rpc_subscription_simulator::{
+        simulate, CostModel, MatchingImplementation, SlotWorkload, SlotWorkloadRecord,
+        SubscriptionRecord,
+    },
+    std::{fs::File, io::BufReader, process::exit},
+};
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &str) -> T {
+    let file = File::open(path).unwrap_or_else(|err| {
+        eprintln!("Unable to open {}: {}", path, err);
+        exit(1);
+    });
+    serde_json::from_reader(BufReader::new(file)).unwrap_or_else(|err| {
+        eprintln!("Unable to parse {}: {}", path, err);
+        exit(1);
+    })
+}
+
+fn main() {
+    solana_logger::setup();
+    let matches = App::new(crate_name!())
+        .about(crate_description!())
+        .version(solana_version::version!())
+        .arg(
+            Arg::with_name("subscriptions")
+                .long("subscriptions")
+                .value_name("PATH")
+                .takes_value(true)
+                .required(true)
+                .help(
+                    "JSON array of exported subscriptions: \
+                     [{\"method\": ..., \"params\": [...], \"count\": N}]",
+                ),
+        )
+        .arg(
+            Arg::with_name("workload")
+                .long("workload")
+                .value_name("PATH")
+                .takes_value(true)
+                .required(true)
+                .help("JSON file with the account writes and transactions of a recorded slot"),
+        )
+        .arg(
+            Arg::with_name("ns_per_match_op")
+                .long("ns-per-match-op")
+                .value_name("NANOSECONDS")
+                .takes_value(true)
+                .default_value("200")
+                .help("Cost of evaluating one subscription against one write"),
+        )
+        .arg(
+            Arg::with_name("ns_per_serialized_kb")
+                .long("ns-per-serialized-kb")
+                .value_name("NANOSECONDS")
+                .takes_value(true)
+                .default_value("2000")
+                .help("Cost of serializing one kilobyte of notification"),
+        )
+        .get_matches();
+
+    let subscriptions: Vec<SubscriptionRecord> =
+        read_json(matches.value_of("subscriptions").unwrap());
+    let workload: SlotWorkloadRecord = read_json(matches.value_of("workload").unwrap());
+    let workload = SlotWorkload::from_record(&workload).unwrap_or_else(|err| {
+        eprintln!("Invalid workload: {}", err);
+        exit(1);
+    });
+    let cost_model = CostModel {
+        ns_per_match_op: value_t_or_exit!(matches, "ns_per_match_op", u64),
+        ns_per_serialized_kb: value_t_or_exit!(matches, "ns_per_serialized_kb", u64),
+    };
+
+    println!(
+        "{:<10} {:>12} {:>16} {:>16} {:>12}",
+        "matching", "match ops", "notifications/s", "bytes/s", "utilization"
+    );
+    for implementation in &[
+        MatchingImplementation::Linear,
+        MatchingImplementation::Indexed,
+    ] {
+        let report = simulate(&subscriptions, &workload, *implementation, &cost_model)
+            .unwrap_or_else(|err| {
+                eprintln!("Invalid subscriptions: {}", err);
+                exit(1);
+            });
+        println!(
+            "{:<10} {:>12} {:>16.1} {:>16.1} {:>11.1}%",
+            format!("{:?}", report.implementation),
+            report.match_ops,
+            report.notifications_per_sec,
+            report.bytes_per_sec,
+            report.loop_utilization * 100.0,
+        );
+    }
+}
@@ -0,0 +1,653 @@
+//! Long-running stability check for the RPC PubSub service.
+//!
+//! Keeps a mix of subscriptions open against a node, churns a fraction of them at a fixed
+//! interval and validates every message it receives. Exits with a non-zero status if an
+//! invariant was violated, if subscriptions were not released, or if the resident memory of
+//! the node grew beyond the allowed limit.
+//!
+//! Built with `cargo build -p solana-rpc --features tools --bin solana-pubsub-soak`.
+
+use {
+    clap::{crate_description, crate_name, value_t, value_t_or_exit, values_t, App, Arg},
+    log::*,
+    rand::{
+        seq::{IteratorRandom, SliceRandom},
+        thread_rng, Rng,
+    },
+    serde_json::{json, Value},
+    solana_sdk::{pubkey::Pubkey, signature::Signature, system_program, sysvar},
+    std::{
+        collections::HashMap,
+        fs,
+        io::ErrorKind,
+        net::TcpStream,
+        process::exit,
+        sync::{
+            atomic::{AtomicBool, AtomicU64, Ordering},
+            Arc,
+        },
+        thread::{self, sleep},
+        time::{Duration, Instant},
+    },
+    tungstenite::{connect, stream::MaybeTlsStream, Message, WebSocket},
+};
+
+/// How long notifications for a subscription are tolerated after its unsubscribe was
+/// acknowledged
+const UNSUBSCRIBE_GRACE: Duration = Duration::from_secs(5);
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone)]
+enum SoakSubscription {
+    Slot,
+    Root,
+    SlotsUpdates,
+    Account(Pubkey),
+    Program(Pubkey),
+    Logs(Pubkey),
+    /// A random signature that is never submitted, so it must never be notified
+    Signature(Signature),
+}
+
+impl SoakSubscription {
+    fn random(accounts: &[Pubkey]) -> Self {
+        let mut rng = thread_rng();
+        match rng.gen_range(0, 7) {
+            0 => Self::Slot,
+            1 => Self::Root,
+            2 => Self::SlotsUpdates,
+            3 => Self::Account(*accounts.choose(&mut rng).unwrap()),
+            4 => Self::Program(sysvar::id()),
+            5 => Self::Logs(system_program::id()),
+            _ => {
+                let bytes: Vec<u8> = (0..64).map(|_| rng.gen()).collect();
+                Self::Signature(Signature::new(&bytes))
+            }
+        }
+    }
+
+    fn subscribe_request(&self, id: u64) -> Value {
+        let finalized = json!({"commitment": "finalized"});
+        let account_config = json!({"commitment": "finalized", "encoding": "base64"});
+        let (method, params) = match self {
+            Self::Slot => ("slotSubscribe", json!([])),
+            Self::Root => ("rootSubscribe", json!([])),
+            Self::SlotsUpdates => ("slotsUpdatesSubscribe", json!([])),
+            Self::Account(pubkey) => (
+                "accountSubscribe",
+                json!([pubkey.to_string(), account_config]),
+            ),
+            Self::Program(pubkey) => (
+                "programSubscribe",
+                json!([pubkey.to_string(), account_config]),
+            ),
+            Self::Logs(pubkey) => (
+                "logsSubscribe",
+                json!([{"mentions": [pubkey.to_string()]}, finalized]),
+            ),
+            Self::Signature(signature) => (
+                "signatureSubscribe",
+                json!([signature.to_string(), finalized]),
+            ),
+        };
+        json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params})
+    }
+
+    fn unsubscribe_method(&self) -> &'static str {
+        match self {
+            Self::Slot => "slotUnsubscribe",
+            Self::Root => "rootUnsubscribe",
+            Self::SlotsUpdates => "slotsUpdatesUnsubscribe",
+            Self::Account(_) => "accountUnsubscribe",
+            Self::Program(_) => "programUnsubscribe",
+            Self::Logs(_) => "logsUnsubscribe",
+            Self::Signature(_) => "signatureUnsubscribe",
+        }
+    }
+
+    fn notification_method(&self) -> &'static str {
+        match self {
+            Self::Slot => "slotNotification",
+            Self::Root => "rootNotification",
+            Self::SlotsUpdates => "slotsUpdatesNotification",
+            Self::Account(_) => "accountNotification",
+            Self::Program(_) => "programNotification",
+            Self::Logs(_) => "logsNotification",
+            Self::Signature(_) => "signatureNotification",
+        }
+    }
+}
+
+#[derive(Default)]
+struct SoakStats {
+    active: AtomicU64,
+    subscribed: AtomicU64,
+    unsubscribed: AtomicU64,
+    rejected: AtomicU64,
+    notifications: AtomicU64,
+    violations: AtomicU64,
+}
+
+impl SoakStats {
+    fn report(&self, elapsed: Duration, rss_bytes: Option<u64>) {
+        println!(
+            "{:>8}s active: {} subscribed: {} unsubscribed: {} rejected: {} \
+             notifications: {} violations: {} node rss: {}",
+            elapsed.as_secs(),
+            self.active.load(Ordering::Relaxed),
+            self.subscribed.load(Ordering::Relaxed),
+            self.unsubscribed.load(Ordering::Relaxed),
+            self.rejected.load(Ordering::Relaxed),
+            self.notifications.load(Ordering::Relaxed),
+            self.violations.load(Ordering::Relaxed),
+            rss_bytes.map_or_else(|| "-".to_string(), |bytes| format!("{}MB", bytes >> 20)),
+        );
+    }
+}
+
+struct ActiveSubscription {
+    kind: SoakSubscription,
+    /// Highest slot notified so far, for the subscriptions whose slots must not go back
+    last_slot: Option<u64>,
+}
+
+impl ActiveSubscription {
+    fn check_notification(&mut self, method: &str, result: &Value) -> Result<(), String> {
+        if method != self.kind.notification_method() {
+            return Err(format!(
+                "expected {}, received {}",
+                self.kind.notification_method(),
+                method
+            ));
+        }
+        let context_slot = || {
+            result["context"]["slot"]
+                .as_u64()
+                .ok_or_else(|| format!("missing context slot: {}", result))
+        };
+        let (slot, strictly_increasing) = match &self.kind {
+            SoakSubscription::Slot => {
+                let (slot, parent, root) = (
+                    result["slot"].as_u64(),
+                    result["parent"].as_u64(),
+                    result["root"].as_u64(),
+                );
+                return match (slot, parent, root) {
+                    (Some(slot), Some(parent), Some(root)) if parent < slot && root <= slot => {
+                        Ok(())
+                    }
+                    _ => Err(format!("inconsistent slot info: {}", result)),
+                };
+            }
+            SoakSubscription::SlotsUpdates => {
+                return if result["type"].is_string() && result["slot"].is_u64() {
+                    Ok(())
+                } else {
+                    Err(format!("malformed slots update: {}", result))
+                };
+            }
+            SoakSubscription::Root => (
+                result
+                    .as_u64()
+                    .ok_or_else(|| format!("malformed root: {}", result))?,
+                true,
+            ),
+            SoakSubscription::Account(_) => {
+                let value = &result["value"];
+                if !value["lamports"].is_u64() || !value["owner"].is_string() {
+                    return Err(format!("malformed account: {}", result));
+                }
+                (context_slot()?, false)
+            }
+            SoakSubscription::Program(_) => {
+                let value = &result["value"];
+                if !value["pubkey"].is_string() || !value["account"]["lamports"].is_u64() {
+                    return Err(format!("malformed keyed account: {}", result));
+                }
+                (context_slot()?, false)
+            }
+            SoakSubscription::Logs(_) => {
+                let value = &result["value"];
+                if !value["signature"].is_string() || !value["logs"].is_array() {
+                    return Err(format!("malformed logs: {}", result));
+                }
+                (context_slot()?, false)
+            }
+            SoakSubscription::Signature(_) => {
+                return Err(format!(
+                    "notification for a signature that was never submitted: {}",
+                    result
+                ));
+            }
+        };
+        // All account, program and logs subscriptions are finalized, so their slots can
+        // only move forward
+        if let Some(last_slot) = self.last_slot {
+            if slot < last_slot || (strictly_increasing && slot == last_slot) {
+                return Err(format!("slot {} notified after slot {}", slot, last_slot));
+            }
+        }
+        self.last_slot = Some(slot);
+        Ok(())
+    }
+}
+
+struct SoakConnection {
+    socket: WebSocket<MaybeTlsStream<TcpStream>>,
+    accounts: Arc<Vec<Pubkey>>,
+    stats: Arc<SoakStats>,
+    next_request_id: u64,
+    pending_subscribes: HashMap<u64, SoakSubscription>,
+    /// Request id to subscription id
+    pending_unsubscribes: HashMap<u64, u64>,
+    active: HashMap<u64, ActiveSubscription>,
+    unsubscribed: HashMap<u64, Instant>,
+}
+
+impl SoakConnection {
+    fn new(url: &str, accounts: Arc<Vec<Pubkey>>, stats: Arc<SoakStats>) -> Self {
+        let (mut socket, _) = connect(url).unwrap_or_else(|err| {
+            eprintln!("Unable to connect to {}: {}", url, err);
+            exit(1);
+        });
+        if let MaybeTlsStream::Plain(stream) = socket.get_mut() {
+            stream
+                .set_read_timeout(Some(READ_TIMEOUT))
+                .expect("set_read_timeout");
+        }
+        Self {
+            socket,
+            accounts,
+            stats,
+            next_request_id: 0,
+            pending_subscribes: HashMap::new(),
+            pending_unsubscribes: HashMap::new(),
+            active: HashMap::new(),
+            unsubscribed: HashMap::new(),
+        }
+    }
+
+    fn violation(&self, message: String) {
+        error!("{}", message);
+        self.stats.violations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn send(&mut self, request: Value) -> bool {
+        if let Err(err) = self
+            .socket
+            .write_message(Message::Text(request.to_string()))
+        {
+            self.violation(format!("websocket write failed: {}", err));
+            return false;
+        }
+        true
+    }
+
+    fn subscribe(&mut self, kind: SoakSubscription) -> bool {
+        self.next_request_id += 1;
+        let request = kind.subscribe_request(self.next_request_id);
+        self.pending_subscribes.insert(self.next_request_id, kind);
+        self.send(request)
+    }
+
+    fn unsubscribe(&mut self, subscription_id: u64) -> bool {
+        let method = self.active[&subscription_id].kind.unsubscribe_method();
+        self.next_request_id += 1;
+        self.pending_unsubscribes
+            .insert(self.next_request_id, subscription_id);
+        self.send(json!({
+            "jsonrpc": "2.0",
+            "id": self.next_request_id,
+            "method": method,
+            "params": [subscription_id],
+        }))
+    }
+
+    /// Unsubscribes from a random `fraction` of the active subscriptions and tops the
+    /// connection back up to `target` subscriptions.
+    fn churn(&mut self, target: usize, fraction: f64) -> bool {
+        let count = (self.active.len() as f64 * fraction).ceil() as usize;
+        let pending_unsubscribes = &self.pending_unsubscribes;
+        let victims = self
+            .active
+            .keys()
+            .filter(|id| !pending_unsubscribes.values().any(|pending| pending == *id))
+            .copied()
+            .choose_multiple(&mut thread_rng(), count);
+        for subscription_id in victims {
+            if !self.unsubscribe(subscription_id) {
+                return false;
+            }
+        }
+        while self.active.len() - self.pending_unsubscribes.len() + self.pending_subscribes.len()
+            < target
+        {
+            let kind = SoakSubscription::random(&self.accounts);
+            if !self.subscribe(kind) {
+                return false;
+            }
+        }
+        self.unsubscribed
+            .retain(|_, unsubscribed_at| unsubscribed_at.elapsed() < UNSUBSCRIBE_GRACE);
+        true
+    }
+
+    fn handle_message(&mut self, text: &str) {
+        let message: Value = match serde_json::from_str(text) {
+            Ok(message) => message,
+            Err(err) => return self.violation(format!("invalid json ({}): {}", err, text)),
+        };
+        if let Some(request_id) = message["id"].as_u64() {
+            return self.handle_response(request_id, &message);
+        }
+
+        self.stats.notifications.fetch_add(1, Ordering::Relaxed);
+        let method = message["method"].as_str().unwrap_or_default();
+        let params = &message["params"];
+        let subscription_id = match params["subscription"].as_u64() {
+            Some(subscription_id) => subscription_id,
+            None => return self.violation(format!("unexpected message: {}", text)),
+        };
+        match self.active.get_mut(&subscription_id) {
+            Some(subscription) => {
+                if let Err(err) = subscription.check_notification(method, &params["result"]) {
+                    self.violation(format!("subscription {}: {}", subscription_id, err));
+                }
+            }
+            None => match self.unsubscribed.get(&subscription_id) {
+                Some(unsubscribed_at) if unsubscribed_at.elapsed() < UNSUBSCRIBE_GRACE => {}
+                _ => self.violation(format!(
+                    "{} for inactive subscription {}",
+                    method, subscription_id
+                )),
+            },
+        }
+    }
+
+    fn handle_response(&mut self, request_id: u64, message: &Value) {
+        if let Some(kind) = self.pending_subscribes.remove(&request_id) {
+            match message["result"].as_u64() {
+                Some(subscription_id) => {
+                    let subscription = ActiveSubscription {
+                        kind,
+                        last_slot: None,
+                    };
+                    if self.active.insert(subscription_id, subscription).is_some() {
+                        self.violation(format!(
+                            "subscription id {} handed out while still active",
+                            subscription_id
+                        ));
+                    } else {
+                        self.stats.active.fetch_add(1, Ordering::Relaxed);
+                    }
+                    self.stats.subscribed.fetch_add(1, Ordering::Relaxed);
+                }
+                None => {
+                    warn!("{:?} rejected: {}", kind, message["error"]);
+                    self.stats.rejected.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        } else if let Some(subscription_id) = self.pending_unsubscribes.remove(&request_id) {
+            if message["result"].as_bool() != Some(true) {
+                self.violation(format!(
+                    "unsubscribe from {} failed: {}",
+                    subscription_id, message
+                ));
+            }
+            self.active.remove(&subscription_id);
+            self.unsubscribed.insert(subscription_id, Instant::now());
+            self.stats.active.fetch_sub(1, Ordering::Relaxed);
+            self.stats.unsubscribed.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.violation(format!("response to unknown request: {}", message));
+        }
+    }
+
+    /// Reads the next message, returning false once the connection is unusable.
+    fn poll(&mut self) -> bool {
+        match self.socket.read_message() {
+            Ok(Message::Text(text)) => self.handle_message(&text),
+            Ok(Message::Close(frame)) => {
+                self.violation(format!("connection closed by the node: {:?}", frame));
+                return false;
+            }
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(err))
+                if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(err) => {
+                self.violation(format!("websocket read failed: {}", err));
+                return false;
+            }
+        }
+        true
+    }
+
+    fn run(mut self, exit: &AtomicBool, target: usize, churn_interval: Duration, fraction: f64) {
+        if !self.churn(target, 0.0) {
+            return;
+        }
+        let mut last_churn = Instant::now();
+        while !exit.load(Ordering::Relaxed) {
+            if !self.poll() {
+                return;
+            }
+            if last_churn.elapsed() >= churn_interval {
+                if !self.churn(target, fraction) {
+                    return;
+                }
+                last_churn = Instant::now();
+            }
+        }
+
+        // Release everything, then keep listening long enough to catch notifications
+        // that outlive their subscription
+        let pending_unsubscribes = &self.pending_unsubscribes;
+        let subscription_ids: Vec<_> = self
+            .active
+            .keys()
+            .filter(|id| !pending_unsubscribes.values().any(|pending| pending == *id))
+            .copied()
+            .collect();
+        for subscription_id in subscription_ids {
+            if !self.unsubscribe(subscription_id) {
+                return;
+            }
+        }
+        let draining = Instant::now();
+        while draining.elapsed() < UNSUBSCRIBE_GRACE * 2 {
+            self.unsubscribed
+                .retain(|_, unsubscribed_at| unsubscribed_at.elapsed() < UNSUBSCRIBE_GRACE);
+            if !self.poll() {
+                return;
+            }
+        }
+        if !self.pending_subscribes.is_empty() || !self.pending_unsubscribes.is_empty() {
+            self.violation(format!(
+                "{} subscribe and {} unsubscribe requests were never answered",
+                self.pending_subscribes.len(),
+                self.pending_unsubscribes.len()
+            ));
+        }
+        let _ = self.socket.close(None);
+    }
+}
+
+fn read_rss_bytes(pid: u32) -> Option<u64> {
+    let status = fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+    status
+        .lines()
+        .find(|line| line.starts_with("VmRSS:"))
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|kilobytes| kilobytes.parse::<u64>().ok())
+        .map(|kilobytes| kilobytes * 1024)
+}
+
+fn main() {
+    solana_logger::setup_with_default("solana=info");
+    let matches = App::new(crate_name!())
+        .about(crate_description!())
+        .version(solana_version::version!())
+        .arg(
+            Arg::with_name("url")
+                .long("url")
+                .value_name("URL")
+                .takes_value(true)
+                .default_value("ws://127.0.0.1:8900")
+                .help("RPC PubSub websocket URL of the node under test"),
+        )
+        .arg(
+            Arg::with_name("connections")
+                .long("connections")
+                .value_name("NUMBER")
+                .takes_value(true)
+                .default_value("16")
+                .help("Number of websocket connections"),
+        )
+        .arg(
+            Arg::with_name("subscriptions_per_connection")
+                .long("subscriptions-per-connection")
+                .value_name("NUMBER")
+                .takes_value(true)
+                .default_value("256")
+                .help("Number of subscriptions kept open on each connection"),
+        )
+        .arg(
+            Arg::with_name("duration_secs")
+                .long("duration-secs")
+                .value_name("SECONDS")
+                .takes_value(true)
+                .default_value("14400")
+                .help("How long to run before releasing all subscriptions"),
+        )
+        .arg(
+            Arg::with_name("churn_interval_ms")
+                .long("churn-interval-ms")
+                .value_name("MILLISECONDS")
+                .takes_value(true)
+                .default_value("1000")
+                .help("Interval between two rounds of subscription churn"),
+        )
+        .arg(
+            Arg::with_name("churn_fraction")
+                .long("churn-fraction")
+                .value_name("FRACTION")
+                .takes_value(true)
+                .default_value("0.05")
+                .help("Fraction of the subscriptions of a connection replaced every round"),
+        )
+        .arg(
+            Arg::with_name("report_interval_secs")
+                .long("report-interval-secs")
+                .value_name("SECONDS")
+                .takes_value(true)
+                .default_value("60")
+                .help("Interval between two progress reports"),
+        )
+        .arg(
+            Arg::with_name("account")
+                .long("account")
+                .value_name("PUBKEY")
+                .takes_value(true)
+                .multiple(true)
+                .help(
+                    "Additional account to subscribe to [default: clock, slot hashes and \
+                       slot history sysvars]",
+                ),
+        )
+        .arg(
+            Arg::with_name("pid")
+                .long("pid")
+                .value_name("PID")
+                .takes_value(true)
+                .help("Process id of a local node, to track its resident memory"),
+        )
+        .arg(
+            Arg::with_name("max_rss_growth_mb")
+                .long("max-rss-growth-mb")
+                .value_name("MEGABYTES")
+                .takes_value(true)
+                .default_value("512")
+                .requires("pid")
+                .help(
+                    "Fail if the resident memory of the node grows by more than this \
+                       after the first report",
+                ),
+        )
+        .get_matches();
+
+    let url = matches.value_of("url").unwrap().to_string();
+    let connections = value_t_or_exit!(matches, "connections", usize);
+    let target = value_t_or_exit!(matches, "subscriptions_per_connection", usize);
+    let duration = Duration::from_secs(value_t_or_exit!(matches, "duration_secs", u64));
+    let churn_interval = Duration::from_millis(value_t_or_exit!(matches, "churn_interval_ms", u64));
+    let churn_fraction = value_t_or_exit!(matches, "churn_fraction", f64);
+    let report_interval =
+        Duration::from_secs(value_t_or_exit!(matches, "report_interval_secs", u64));
+    let pid = value_t!(matches, "pid", u32).ok();
+    let max_rss_growth = value_t_or_exit!(matches, "max_rss_growth_mb", u64) << 20;
+    let mut accounts = vec![
+        sysvar::clock::id(),
+        sysvar::slot_hashes::id(),
+        sysvar::slot_history::id(),
+    ];
+    if matches.is_present("account") {
+        accounts.extend(values_t!(matches, "account", Pubkey).unwrap_or_else(|err| err.exit()));
+    }
+    let accounts = Arc::new(accounts);
+
+    let exit_signal = Arc::new(AtomicBool::new(false));
+    let stats = Arc::new(SoakStats::default());
+    let threads: Vec<_> = (0..connections)
+        .map(|i| {
+            let connection = SoakConnection::new(&url, accounts.clone(), stats.clone());
+            let exit_signal = exit_signal.clone();
+            thread::Builder::new()
+                .name(format!("soak-conn-{}", i))
+                .spawn(move || connection.run(&exit_signal, target, churn_interval, churn_fraction))
+                .unwrap()
+        })
+        .collect();
+
+    let started = Instant::now();
+    let mut baseline_rss = None;
+    while started.elapsed() < duration {
+        sleep(report_interval.min(duration.saturating_sub(started.elapsed())));
+        let rss = pid.and_then(read_rss_bytes);
+        baseline_rss = baseline_rss.or(rss);
+        stats.report(started.elapsed(), rss);
+    }
+    exit_signal.store(true, Ordering::Relaxed);
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    let final_rss = pid.and_then(read_rss_bytes);
+    stats.report(started.elapsed(), final_rss);
+
+    let mut failed = false;
+    let violations = stats.violations.load(Ordering::Relaxed);
+    if violations > 0 {
+        eprintln!("{} invariant violations", violations);
+        failed = true;
+    }
+    let subscribed = stats.subscribed.load(Ordering::Relaxed);
+    let unsubscribed = stats.unsubscribed.load(Ordering::Relaxed);
+    if subscribed != unsubscribed {
+        eprintln!(
+            "{} subscriptions were never released",
+            subscribed.saturating_sub(unsubscribed)
+        );
+        failed = true;
+    }
+    if let (Some(baseline_rss), Some(final_rss)) = (baseline_rss, final_rss) {
+        let growth = final_rss.saturating_sub(baseline_rss);
+        if growth > max_rss_growth {
+            eprintln!(
+                "Node resident memory grew by {}MB, more than the allowed {}MB",
+                growth >> 20,
+                max_rss_growth >> 20
+            );
+            failed = true;
+        }
+    }
+    exit(if failed { 1 } else { 0 });
+}
@@ -3,15 +3,27 @@ mod cluster_tpu_info;
 pub mod max_slots;
 pub mod optimistically_confirmed_bank_tracker;
 pub mod parsed_token_accounts;
+pub mod program_accounts_cache;
 pub mod rpc;
 pub mod rpc_completed_slots_service;
+pub mod rpc_grpc_service;
 pub mod rpc_health;
 pub mod rpc_pubsub;
+pub mod rpc_pubsub_auth;
+pub mod rpc_pubsub_memory;
 pub mod rpc_pubsub_service;
+pub mod rpc_rate_limit;
+pub mod rpc_request_trace;
 pub mod rpc_service;
+pub mod rpc_sse_service;
+pub mod rpc_subscription_simulator;
 pub mod rpc_subscription_tracker;
 pub mod rpc_subscriptions;
+pub mod rpc_tls;
+pub mod rpc_webhooks;
+pub mod transaction_notifier_interface;
 pub mod transaction_status_service;
+mod upcoming_leaders;
 
 #[macro_use]
 extern crate log;
@@ -2,7 +2,10 @@ use {
     jsonrpc_core::{Error, Result},
     solana_account_decoder::{
         parse_account_data::AccountAdditionalData,
-        parse_token::{get_token_account_mint, spl_token_id_v2_0, spl_token_v2_0_native_mint},
+        parse_token::{
+            get_token_account_mint, spl_token_id_v2_0, spl_token_v2_0_native_mint,
+            strip_token_extensions,
+        },
         UiAccount, UiAccountData, UiAccountEncoding,
     },
     solana_client::rpc_response::RpcKeyedAccount,
@@ -12,7 +15,7 @@ use {
         pubkey::Pubkey,
     },
     spl_token_v2_0::{solana_program::program_pack::Pack, state::Mint},
-    std::{collections::HashMap, sync::Arc},
+    std::{borrow::Borrow, collections::HashMap, sync::Arc},
 };
 
 pub fn get_parsed_token_account(
@@ -35,15 +38,17 @@ pub fn get_parsed_token_account(
     )
 }
 
-pub fn get_parsed_token_accounts<I>(
+pub fn get_parsed_token_accounts<I, K>(
     bank: Arc<Bank>,
     keyed_accounts: I,
 ) -> impl Iterator<Item = RpcKeyedAccount>
 where
-    I: Iterator<Item = (Pubkey, AccountSharedData)>,
+    I: Iterator<Item = K>,
+    K: Borrow<(Pubkey, AccountSharedData)>,
 {
     let mut mint_decimals: HashMap<Pubkey, u8> = HashMap::new();
-    keyed_accounts.filter_map(move |(pubkey, account)| {
+    keyed_accounts.filter_map(move |keyed_account| {
+        let (pubkey, account): &(Pubkey, AccountSharedData) = keyed_account.borrow();
         let additional_data = get_token_account_mint(account.data()).map(|mint_pubkey| {
             let spl_token_decimals = mint_decimals.get(&mint_pubkey).cloned().or_else(|| {
                 let (_, decimals) = get_mint_owner_and_decimals(&bank, &mint_pubkey).ok()?;
@@ -54,8 +59,8 @@ where
         });
 
         let maybe_encoded_account = UiAccount::encode(
-            &pubkey,
-            &account,
+            pubkey,
+            account,
             UiAccountEncoding::JsonParsed,
             additional_data,
             None,
@@ -86,7 +91,7 @@ pub fn get_mint_owner_and_decimals(bank: &Arc<Bank>, mint: &Pubkey) -> Result<(P
 }
 
 fn get_mint_decimals(data: &[u8]) -> Result<u8> {
-    Mint::unpack(data)
+    Mint::unpack(strip_token_extensions(data))
         .map_err(|_| {
             Error::invalid_params("Invalid param: Token mint could not be unpacked".to_string())
         })
@@ -0,0 +1,298 @@
+//! The `program_accounts_cache` module keeps the results of recent `getProgramAccounts`
+//! scans, so that requests paging through, sorting or counting the same accounts are
+//! served without scanning the accounts database again. A cached scan only keeps the
+//! accounts it found and the slot it was taken at, up to `MAX_CACHED_SCAN_BYTES` across
+//! scans.
+//!
+//! Scans are only kept when the cache is enabled with a TTL. Without it, every page of a
+//! request paging through the accounts scans and sorts them again.
+
+use {
+    solana_client::{rpc_config::RpcProgramAccountsOrder, rpc_filter::RpcFilterType},
+    solana_sdk::{
+        account::{AccountSharedData, ReadableAccount},
+        clock::Slot,
+        commitment_config::CommitmentConfig,
+        pubkey::Pubkey,
+    },
+    std::{
+        collections::HashMap,
+        mem::size_of,
+        sync::{Arc, Mutex},
+        time::{Duration, Instant},
+    },
+};
+
+/// Scans kept at once; when full, the oldest scan makes room for a new one
+const MAX_CACHED_SCANS: usize = 16;
+
+/// Bytes of accounts kept by the cached scans at once; larger scans aren't cached
+const MAX_CACHED_SCAN_BYTES: usize = 1 << 30;
+
+pub type KeyedAccounts = Arc<ScannedAccounts>;
+
+/// The accounts found by a scan, along with the orders they were sorted in so far
+#[derive(Default)]
+pub struct ScannedAccounts {
+    keyed_accounts: Vec<(Pubkey, AccountSharedData)>,
+    sorted: Mutex<HashMap<RpcProgramAccountsOrder, Arc<Vec<usize>>>>,
+    size: usize,
+}
+
+impl ScannedAccounts {
+    pub fn new(keyed_accounts: Vec<(Pubkey, AccountSharedData)>) -> Self {
+        let size = keyed_accounts
+            .iter()
+            .map(|(_, account)| size_of::<(Pubkey, AccountSharedData)>() + account.data().len())
+            .sum();
+        Self {
+            keyed_accounts,
+            sorted: Mutex::default(),
+            size,
+        }
+    }
+
+    pub fn keyed_accounts(&self) -> &[(Pubkey, AccountSharedData)] {
+        &self.keyed_accounts
+    }
+
+    /// The bytes taken by the accounts, sorted orders aside
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The indexes of the accounts in `order`. Each order is only sorted once; the lock
+    /// isn't held while sorting, so concurrent first requests may each sort.
+    pub fn sorted(&self, order: RpcProgramAccountsOrder) -> Arc<Vec<usize>> {
+        if let Some(sorted) = self.sorted.lock().unwrap().get(&order) {
+            return sorted.clone();
+        }
+        let keyed_accounts = &self.keyed_accounts;
+        let mut sorted: Vec<_> = (0..keyed_accounts.len()).collect();
+        match order {
+            RpcProgramAccountsOrder::Pubkey => {
+                sorted.sort_unstable_by_key(|i| keyed_accounts[*i].0);
+            }
+            RpcProgramAccountsOrder::Lamports => sorted.sort_unstable_by(|a, b| {
+                let ((a_pubkey, a), (b_pubkey, b)) = (&keyed_accounts[*a], &keyed_accounts[*b]);
+                (b.lamports().cmp(&a.lamports())).then_with(|| a_pubkey.cmp(b_pubkey))
+            }),
+            RpcProgramAccountsOrder::DataLen => sorted.sort_unstable_by(|a, b| {
+                let ((a_pubkey, a), (b_pubkey, b)) = (&keyed_accounts[*a], &keyed_accounts[*b]);
+                (b.data().len().cmp(&a.data().len())).then_with(|| a_pubkey.cmp(b_pubkey))
+            }),
+        }
+        self.sorted
+            .lock()
+            .unwrap()
+            .entry(order)
+            .or_insert_with(|| Arc::new(sorted))
+            .clone()
+    }
+}
+
+/// What a `getProgramAccounts` scan depends on
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ScanKey {
+    pub program_id: Pubkey,
+    pub commitment: Option<CommitmentConfig>,
+    pub filters: Vec<RpcFilterType>,
+}
+
+struct CachedScan {
+    scanned_at: Instant,
+    slot: Slot,
+    keyed_accounts: KeyedAccounts,
+}
+
+pub struct ProgramAccountsCache {
+    /// How long a scan is reused for; `None` disables the cache
+    ttl: Option<Duration>,
+    /// Bytes of accounts kept at once, `MAX_CACHED_SCAN_BYTES` outside of tests
+    max_bytes: usize,
+    scans: Mutex<HashMap<ScanKey, CachedScan>>,
+}
+
+impl ProgramAccountsCache {
+    pub fn new(ttl: Option<Duration>) -> Self {
+        Self {
+            ttl,
+            max_bytes: MAX_CACHED_SCAN_BYTES,
+            scans: Mutex::default(),
+        }
+    }
+
+    /// Returns the scan cached for `key` along with the slot it was taken at, or runs
+    /// `scan` and caches its result. The lock isn't held while scanning, so concurrent
+    /// misses on the same key may each scan.
+    pub fn get_or_scan<E, F>(&self, key: ScanKey, scan: F) -> Result<(Slot, KeyedAccounts), E>
+    where
+        F: FnOnce() -> Result<(Slot, Vec<(Pubkey, AccountSharedData)>), E>,
+    {
+        let ttl = match self.ttl {
+            Some(ttl) => ttl,
+            None => {
+                let (slot, keyed_accounts) = scan()?;
+                return Ok((slot, Arc::new(ScannedAccounts::new(keyed_accounts))));
+            }
+        };
+        if let Some(cached) = self
+            .scans
+            .lock()
+            .unwrap()
+            .get(&key)
+            .filter(|cached| cached.scanned_at.elapsed() < ttl)
+        {
+            inc_new_counter_info!("rpc-program-accounts-cache-hit", 1);
+            return Ok((cached.slot, cached.keyed_accounts.clone()));
+        }
+
+        let (slot, keyed_accounts) = scan()?;
+        let keyed_accounts = Arc::new(ScannedAccounts::new(keyed_accounts));
+        if keyed_accounts.size() > self.max_bytes {
+            inc_new_counter_info!("rpc-program-accounts-cache-too-large", 1);
+            return Ok((slot, keyed_accounts));
+        }
+        let mut scans = self.scans.lock().unwrap();
+        scans.retain(|cached_key, cached| cached.scanned_at.elapsed() < ttl && *cached_key != key);
+        let mut cached_bytes: usize = scans
+            .values()
+            .map(|cached| cached.keyed_accounts.size())
+            .sum();
+        while scans.len() >= MAX_CACHED_SCANS
+            || cached_bytes + keyed_accounts.size() > self.max_bytes
+        {
+            let oldest = scans
+                .iter()
+                .min_by_key(|(_, cached)| cached.scanned_at)
+                .map(|(key, _)| key.clone())
+                .unwrap();
+            cached_bytes -= scans.remove(&oldest).unwrap().keyed_accounts.size();
+        }
+        scans.insert(
+            key,
+            CachedScan {
+                scanned_at: Instant::now(),
+                slot,
+                keyed_accounts: keyed_accounts.clone(),
+            },
+        );
+        Ok((slot, keyed_accounts))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::cell::Cell};
+
+    #[test]
+    fn test_get_or_scan() {
+        let scans = Cell::new(0);
+        let scan = || -> Result<_, ()> {
+            scans.set(scans.get() + 1);
+            Ok((
+                1,
+                vec![(Pubkey::new_unique(), AccountSharedData::default())],
+            ))
+        };
+        let key = |program_id| ScanKey {
+            program_id,
+            commitment: None,
+            filters: vec![RpcFilterType::DataSize(0)],
+        };
+        let program_id = Pubkey::new_unique();
+
+        let cache = ProgramAccountsCache::new(None);
+        cache.get_or_scan(key(program_id), scan).unwrap();
+        cache.get_or_scan(key(program_id), scan).unwrap();
+        assert_eq!(scans.get(), 2);
+        assert!(cache.scans.lock().unwrap().is_empty());
+
+        scans.set(0);
+        let cache = ProgramAccountsCache::new(Some(Duration::from_secs(60)));
+        let (_, first) = cache.get_or_scan(key(program_id), scan).unwrap();
+        let (_, second) = cache.get_or_scan(key(program_id), scan).unwrap();
+        assert_eq!(scans.get(), 1);
+        assert!(Arc::ptr_eq(&first, &second));
+        assert!(Arc::ptr_eq(
+            &cache.get_or_scan(key(program_id), || Err(())).unwrap().1,
+            &first
+        ));
+
+        for _ in 0..MAX_CACHED_SCANS {
+            cache.get_or_scan(key(Pubkey::new_unique()), scan).unwrap();
+        }
+        assert_eq!(cache.scans.lock().unwrap().len(), MAX_CACHED_SCANS);
+        cache.get_or_scan(key(program_id), scan).unwrap();
+        assert_eq!(scans.get(), MAX_CACHED_SCANS + 2);
+
+        let cache = ProgramAccountsCache::new(Some(Duration::from_millis(1)));
+        cache.get_or_scan(key(program_id), scan).unwrap();
+        std::thread::sleep(Duration::from_millis(2));
+        cache.get_or_scan(key(program_id), scan).unwrap();
+        assert_eq!(scans.get(), MAX_CACHED_SCANS + 4);
+    }
+
+    #[test]
+    fn test_get_or_scan_max_bytes() {
+        let scan = |data_len: usize| {
+            move || -> Result<_, ()> {
+                let account = AccountSharedData::new(1, data_len, &Pubkey::default());
+                Ok((1, vec![(Pubkey::new_unique(), account)]))
+            }
+        };
+        let key = || ScanKey {
+            program_id: Pubkey::new_unique(),
+            commitment: None,
+            filters: vec![],
+        };
+        let account_size = size_of::<(Pubkey, AccountSharedData)>();
+        let mut cache = ProgramAccountsCache::new(Some(Duration::from_secs(60)));
+        cache.max_bytes = 2 * account_size + 300;
+
+        let (first, second) = (key(), key());
+        cache.get_or_scan(first.clone(), scan(100)).unwrap();
+        std::thread::sleep(Duration::from_millis(1));
+        cache.get_or_scan(second.clone(), scan(100)).unwrap();
+        assert_eq!(cache.scans.lock().unwrap().len(), 2);
+
+        // Making room for a third scan evicts the oldest one
+        cache.get_or_scan(key(), scan(150)).unwrap();
+        let scans = cache.scans.lock().unwrap();
+        assert_eq!(scans.len(), 2);
+        assert!(!scans.contains_key(&first));
+        assert!(scans.contains_key(&second));
+        drop(scans);
+
+        // Scans larger than the cap aren't cached
+        let large = key();
+        cache.get_or_scan(large.clone(), scan(1_000)).unwrap();
+        assert!(!cache.scans.lock().unwrap().contains_key(&large));
+    }
+
+    #[test]
+    fn test_sorted() {
+        let keyed_accounts: Vec<_> = [(3, 1), (1, 3), (2, 2)]
+            .iter()
+            .map(|(lamports, data_len)| {
+                let account = AccountSharedData::new(*lamports, *data_len, &Pubkey::default());
+                (Pubkey::new_unique(), account)
+            })
+            .collect();
+        let scanned = ScannedAccounts::new(keyed_accounts);
+        assert_eq!(
+            *scanned.sorted(RpcProgramAccountsOrder::Pubkey),
+            vec![0, 1, 2]
+        );
+        assert_eq!(
+            *scanned.sorted(RpcProgramAccountsOrder::Lamports),
+            vec![0, 2, 1]
+        );
+        let by_data_len = scanned.sorted(RpcProgramAccountsOrder::DataLen);
+        assert_eq!(*by_data_len, vec![1, 2, 0]);
+        assert!(Arc::ptr_eq(
+            &by_data_len,
+            &scanned.sorted(RpcProgramAccountsOrder::DataLen)
+        ));
+    }
+}
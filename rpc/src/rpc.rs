@@ -2,15 +2,24 @@
 
 use {
     crate::{
-        max_slots::MaxSlots, optimistically_confirmed_bank_tracker::OptimisticallyConfirmedBank,
-        parsed_token_accounts::*, rpc_health::*,
+        max_slots::MaxSlots,
+        optimistically_confirmed_bank_tracker::OptimisticallyConfirmedBank,
+        parsed_token_accounts::*,
+        program_accounts_cache::{KeyedAccounts, ProgramAccountsCache, ScanKey, ScannedAccounts},
+        rpc_health::*,
+        rpc_request_trace::{RequestTrace, TracePhase},
+        rpc_tls::TlsConfig,
     },
     bincode::{config::Options, serialize},
     jsonrpc_core::{futures::future, types::error, BoxFuture, Error, Metadata, Result},
     jsonrpc_derive::rpc,
+    rayon::{ThreadPool, ThreadPoolBuilder},
     serde::{Deserialize, Serialize},
     solana_account_decoder::{
-        parse_token::{spl_token_id_v2_0, token_amount_to_ui_amount, UiTokenAmount},
+        parse_token::{
+            is_known_spl_token_id, spl_token_id_v2_0, strip_token_extensions,
+            token_amount_to_ui_amount, UiTokenAmount,
+        },
         UiAccount, UiAccountEncoding, UiDataSliceConfig, MAX_BASE58_BYTES,
     },
     solana_client::{
@@ -22,9 +31,13 @@ use {
         rpc_request::{
             TokenAccountsFilter, DELINQUENT_VALIDATOR_SLOT_DISTANCE,
             MAX_GET_CONFIRMED_BLOCKS_RANGE, MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT,
-            MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS_SLOT_RANGE, MAX_GET_PROGRAM_ACCOUNT_FILTERS,
-            MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS, MAX_GET_SLOT_LEADERS, MAX_MULTIPLE_ACCOUNTS,
-            NUM_LARGEST_ACCOUNTS,
+            MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS_SLOT_RANGE, MAX_GET_PROGRAM_ACCOUNTS_LIMIT,
+            MAX_GET_PROGRAM_ACCOUNT_FILTERS, MAX_GET_SIGNATURES_FOR_ADDRESS_EXAMINED,
+            MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS, MAX_GET_SLOT_LEADERS,
+            MAX_GET_SUPPLY_NON_CIRCULATING_ACCOUNTS, MAX_GET_SUPPLY_NON_CIRCULATING_OWNERS,
+            MAX_GET_TRANSACTIONS_QUERY_ITEMS, MAX_GET_VOTE_ACCOUNT_INFLATION_REWARDS_LIMIT,
+            MAX_MULTIPLE_ACCOUNTS, MAX_SIMULATE_BUNDLE_TRANSACTIONS,
+            MAX_TOKEN_ACCOUNTS_FILTER_MINTS, NUM_LARGEST_ACCOUNTS,
         },
         rpc_response::Response as RpcResponse,
         rpc_response::*,
@@ -32,26 +45,36 @@ use {
     solana_faucet::faucet::request_airdrop_transaction,
     solana_gossip::{cluster_info::ClusterInfo, contact_info::ContactInfo},
     solana_ledger::{
-        blockstore::Blockstore, blockstore_db::BlockstoreError, get_tmp_ledger_path,
+        bigtable_upload::ConfirmedBlockUploadConfig,
+        blockstore::{AddressSignatures, AddressSignaturesFilter, Blockstore},
+        blockstore_db::BlockstoreError,
+        get_tmp_ledger_path,
         leader_schedule_cache::LeaderScheduleCache,
     },
     solana_metrics::inc_new_counter_info,
     solana_perf::packet::PACKET_DATA_SIZE,
     solana_runtime::{
         accounts::AccountAddressFilter,
-        accounts_index::{AccountIndex, AccountSecondaryIndexes, IndexKey},
-        bank::{Bank, TransactionSimulationResult},
+        accounts_index::{
+            AccountIndex, AccountSecondaryIndexes, IndexKey, STAKE_ACCOUNT_STAKER_OFFSET,
+            STAKE_ACCOUNT_WITHDRAWER_OFFSET,
+        },
+        bank::{Bank, InnerInstructionsList, TransactionSimulationResult},
         bank_forks::BankForks,
         commitment::{BlockCommitmentArray, BlockCommitmentCache, CommitmentSlots},
+        inline_spl_token_2022::{self, is_spl_token_account},
         inline_spl_token_v2_0::{SPL_TOKEN_ACCOUNT_MINT_OFFSET, SPL_TOKEN_ACCOUNT_OWNER_OFFSET},
-        non_circulating_supply::calculate_non_circulating_supply,
+        non_circulating_supply::{
+            calculate_non_circulating_supply, calculate_non_circulating_supply_with_config,
+            NonCirculatingBreakdown, NonCirculatingSupplyConfig,
+        },
         snapshot_config::SnapshotConfig,
         snapshot_utils,
     },
     solana_sdk::{
         account::{AccountSharedData, ReadableAccount},
         account_utils::StateMut,
-        clock::{Slot, UnixTimestamp, MAX_RECENT_BLOCKHASHES},
+        clock::{Epoch, Slot, UnixTimestamp, MAX_RECENT_BLOCKHASHES},
         commitment_config::{CommitmentConfig, CommitmentLevel},
         epoch_info::EpochInfo,
         epoch_schedule::EpochSchedule,
@@ -62,20 +85,27 @@ use {
         message::{Message, SanitizedMessage},
         pubkey::{Pubkey, PUBKEY_BYTES},
         signature::{Keypair, Signature, Signer},
-        stake::state::{StakeActivationStatus, StakeState},
+        stake::{
+            self,
+            state::{Delegation, StakeActivationStatus, StakeAuthorize, StakeState},
+        },
         stake_history::StakeHistory,
         system_instruction,
         sysvar::stake_history,
         transaction::{self, SanitizedTransaction, TransactionError, VersionedTransaction},
     },
     solana_send_transaction_service::{
-        send_transaction_service::{SendTransactionService, TransactionInfo},
+        send_transaction_service::{
+            RetryState, RetryStatus, RetryStatusCache, SendTransactionService, TransactionInfo,
+        },
         tpu_info::NullTpuInfo,
     },
+    solana_storage_bigtable::{LedgerArchive, ObjectStoreConfig},
     solana_streamer::socket::SocketAddrSpace,
     solana_transaction_status::{
-        ConfirmedBlock, EncodedConfirmedTransaction, Reward, RewardType,
-        TransactionConfirmationStatus, TransactionStatus, UiConfirmedBlock, UiTransactionEncoding,
+        ConfirmedBlock, ConfirmedTransactionStatusWithSignature, EncodedConfirmedTransaction,
+        InnerInstructions, Reward, RewardType, Rewards, TransactionConfirmationStatus,
+        TransactionStatus, UiConfirmedBlock, UiInnerInstructions, UiTransactionEncoding,
     },
     solana_vote_program::vote_state::{VoteState, MAX_LOCKOUT_HISTORY},
     spl_token_v2_0::{
@@ -84,18 +114,21 @@ use {
     },
     std::{
         any::type_name,
+        borrow::Borrow,
         cmp::{max, min},
-        collections::{HashMap, HashSet},
+        collections::{BTreeMap, HashMap, HashSet},
         convert::TryFrom,
         net::SocketAddr,
+        path::PathBuf,
         str::FromStr,
         sync::{
-            atomic::{AtomicBool, AtomicU64, Ordering},
+            atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
             mpsc::{channel, Receiver, Sender},
             Arc, Mutex, RwLock,
         },
         time::Duration,
     },
+    tokio::sync::oneshot,
 };
 
 type RpcCustomResult<T> = std::result::Result<T, RpcCustomError>;
@@ -112,6 +145,74 @@ fn new_response<T>(bank: &Bank, value: T) -> RpcResponse<T> {
     Response { context, value }
 }
 
+fn with_optional_context<T>(slot: Slot, with_context: bool, value: T) -> OptionalContext<T> {
+    match with_context {
+        true => OptionalContext::Context(Response {
+            context: RpcResponseContext { slot },
+            value,
+        }),
+        false => OptionalContext::NoContext(value),
+    }
+}
+
+/// Scans queued or running per thread of the scan pool, beyond which scans are refused
+const MAX_PENDING_SCANS_PER_THREAD: usize = 16;
+
+/// The thread pool of the methods that scan accounts, with a bounded queue
+pub(crate) struct ScanPool {
+    pool: ThreadPool,
+    pending: Arc<AtomicUsize>,
+    max_pending: usize,
+}
+
+impl ScanPool {
+    fn new(scan_threads: usize) -> Self {
+        let scan_threads = 1.max(scan_threads);
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(scan_threads)
+            .thread_name(|i| format!("solana-rpc-scan-{}", i))
+            // A panicking scan fails its request, as it would on the other threads, instead
+            // of aborting the process
+            .panic_handler(|panic| {
+                let message = panic
+                    .downcast_ref::<&str>()
+                    .copied()
+                    .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+                    .unwrap_or("unknown panic");
+                error!("RPC account scan panicked: {}", message);
+            })
+            .build()
+            .unwrap();
+        Self {
+            pool,
+            pending: Arc::default(),
+            max_pending: scan_threads * MAX_PENDING_SCANS_PER_THREAD,
+        }
+    }
+
+    /// Queues `f`, unless the queue is full
+    fn spawn(&self, f: impl FnOnce() + Send + 'static) -> Result<()> {
+        if self.pending.fetch_add(1, Ordering::Relaxed) >= self.max_pending {
+            self.pending.fetch_sub(1, Ordering::Relaxed);
+            inc_new_counter_info!("rpc-scan-queue-full", 1);
+            return Err(RpcCustomError::ScanQueueFull.into());
+        }
+        let pending = Arc::clone(&self.pending);
+        self.pool.spawn(move || {
+            // Also counts the scan as done if it panics
+            struct Done(Arc<AtomicUsize>);
+            impl Drop for Done {
+                fn drop(&mut self) {
+                    self.0.fetch_sub(1, Ordering::Relaxed);
+                }
+            }
+            let _done = Done(pending);
+            f()
+        });
+        Ok(())
+    }
+}
+
 /// Wrapper for rpc return types of methods that provide responses both with and without context.
 /// Main purpose of this is to fix methods that lack context information in their return type,
 /// without breaking backwards compatibility.
@@ -122,6 +223,194 @@ pub enum OptionalContext<T> {
     NoContext(T),
 }
 
+/// Wrapper for rpc return types of methods that can return only some of their fields
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum OptionalFields<T> {
+    All(T),
+    Selected(serde_json::Value),
+}
+
+/// The result of `getLeaderSchedule`, which includes each leader's vote accounts when
+/// `withVoteAccounts` is set
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GetLeaderScheduleResponse {
+    Schedule(RpcLeaderSchedule),
+    WithVoteAccounts(RpcLeaderScheduleWithVoteAccounts),
+}
+
+/// The result of `getClusterNodes`, which is a summary of the nodes when `summary` is set
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GetClusterNodesResponse {
+    Nodes(Vec<RpcContactInfo>),
+    Summary(RpcClusterNodesSummary),
+}
+
+/// The result of `getHealth`, which is a report rather than a status when `extended` is set
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GetHealthResponse {
+    Status(String),
+    Report(RpcHealthReport),
+}
+
+/// The result of `sendTransaction`, which carries the preflight compute units only when they
+/// are asked for
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SendTransactionResponse {
+    Signature(String),
+    WithUnitsConsumed(RpcSendTransactionResult),
+}
+
+/// The fields of a JSON value to keep, parsed from dot separated paths. Paths pass through
+/// arrays, applying to each of their elements.
+#[derive(Debug, Default, PartialEq)]
+struct FieldSelection(HashMap<String, Option<FieldSelection>>);
+
+impl FieldSelection {
+    fn parse(paths: &[String]) -> Result<Self> {
+        let mut selection = Self::default();
+        for path in paths {
+            let mut node = &mut selection;
+            let mut segments = path.split('.').peekable();
+            while let Some(segment) = segments.next() {
+                if segment.is_empty() {
+                    return Err(Error::invalid_params(format!("Invalid field: {:?}", path)));
+                }
+                let field = node
+                    .0
+                    .entry(segment.to_string())
+                    .or_insert_with(|| Some(Self::default()));
+                if segments.peek().is_none() {
+                    // The whole field is selected
+                    *field = None;
+                }
+                match field {
+                    Some(child) => node = child,
+                    None => break,
+                }
+            }
+        }
+        Ok(selection)
+    }
+
+    fn apply(&self, value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::Object(object) => {
+                *object = std::mem::take(object)
+                    .into_iter()
+                    .filter_map(|(key, mut field)| {
+                        let selection = self.0.get(&key)?;
+                        if let Some(selection) = selection {
+                            selection.apply(&mut field);
+                        }
+                        Some((key, field))
+                    })
+                    .collect();
+            }
+            serde_json::Value::Array(array) => {
+                array.iter_mut().for_each(|element| self.apply(element));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The blocks produced within a block time range. Block times don't decrease from one block
+/// to the next, so the range maps to the slots `start_slot..=end_slot`. As finalized blocks
+/// are mapped by their block times in the blockstore, newer blocks are also checked against
+/// the clock of their bank.
+struct BlockTimeRange {
+    min_block_time: Option<UnixTimestamp>,
+    max_block_time: Option<UnixTimestamp>,
+    start_slot: Slot,
+    end_slot: Slot,
+}
+
+// Keep at most this many block time samples, dropping the oldest slots first
+const MAX_BLOCK_TIME_SAMPLES: usize = 16_384;
+
+// Fetch this many blocks from long-term storage to find one with a block time
+const BIGTABLE_BLOCK_TIME_PROBE_BLOCKS: usize = 4;
+
+/// Block times of the finalized blocks probed by earlier block time range lookups. Block times
+/// don't decrease from one block to the next, so the samples bound the slots later lookups
+/// have to probe, and repeated lookups are served from memory.
+#[derive(Default)]
+struct BlockTimeSamples(RwLock<BTreeMap<Slot, UnixTimestamp>>);
+
+impl BlockTimeSamples {
+    /// The latest sample in `[low, high]` produced before `block_time`, and the first one
+    /// produced at or after it
+    fn bounds(
+        &self,
+        block_time: UnixTimestamp,
+        low: Slot,
+        high: Slot,
+    ) -> (Option<(Slot, UnixTimestamp)>, Option<(Slot, UnixTimestamp)>) {
+        if low > high {
+            return (None, None);
+        }
+        let samples = self.0.read().unwrap();
+        let mut below = None;
+        for (&slot, &slot_block_time) in samples.range(low..=high) {
+            if slot_block_time >= block_time {
+                return (below, Some((slot, slot_block_time)));
+            }
+            below = Some((slot, slot_block_time));
+        }
+        (below, None)
+    }
+
+    fn insert(&self, slot: Slot, block_time: UnixTimestamp) {
+        let mut samples = self.0.write().unwrap();
+        if samples.len() >= MAX_BLOCK_TIME_SAMPLES && !samples.contains_key(&slot) {
+            let oldest_slot = *samples.keys().next().unwrap();
+            samples.remove(&oldest_slot);
+        }
+        samples.insert(slot, block_time);
+    }
+}
+
+// Keep the vote accounts of at most this many slots for `getVoteAccounts` deltas, dropping the
+// oldest slots first
+const MAX_VOTE_ACCOUNTS_SNAPSHOTS: usize = 64;
+
+/// The fields of a vote account that `getVoteAccounts` filters on or reports the changes of
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct VoteAccountSnapshot {
+    activated_stake: u64,
+    commission: u8,
+    last_vote: Slot,
+    epoch_credits: u64, // credits earned in the epoch of the snapshot
+}
+
+/// The vote accounts of the frozen banks read by `getVoteAccounts` requests with
+/// `changedSinceSlot`, by slot, so that later requests can return only the accounts that changed
+#[derive(Default)]
+struct VoteAccountsSnapshots(RwLock<BTreeMap<Slot, Arc<HashMap<Pubkey, VoteAccountSnapshot>>>>);
+
+impl VoteAccountsSnapshots {
+    fn get(&self, slot: Slot) -> Option<Arc<HashMap<Pubkey, VoteAccountSnapshot>>> {
+        self.0.read().unwrap().get(&slot).cloned()
+    }
+
+    fn insert(&self, slot: Slot, snapshot: HashMap<Pubkey, VoteAccountSnapshot>) {
+        let mut snapshots = self.0.write().unwrap();
+        if snapshots.contains_key(&slot) {
+            return;
+        }
+        if snapshots.len() >= MAX_VOTE_ACCOUNTS_SNAPSHOTS {
+            let oldest_slot = *snapshots.keys().next().unwrap();
+            snapshots.remove(&oldest_slot);
+        }
+        snapshots.insert(slot, Arc::new(snapshot));
+    }
+}
+
 fn is_finalized(
     block_commitment_cache: &BlockCommitmentCache,
     bank: &Bank,
@@ -140,13 +429,38 @@ pub struct JsonRpcConfig {
     pub health_check_slot_distance: u64,
     pub enable_bigtable_ledger_storage: bool,
     pub enable_bigtable_ledger_upload: bool,
+    /// What `enable_bigtable_ledger_upload` uploads along with the blocks
+    pub bigtable_upload_config: ConfirmedBlockUploadConfig,
+    /// Serve and upload the ledger history with an S3-compatible object store instead of
+    /// BigTable
+    pub object_store_ledger_storage: Option<ObjectStoreConfig>,
+    pub enable_object_store_ledger_upload: bool,
     pub max_multiple_accounts: Option<usize>,
     pub account_indexes: AccountSecondaryIndexes,
     pub rpc_threads: usize,
+    /// Threads for the methods that scan accounts, apart from `rpc_threads`
+    pub rpc_scan_threads: usize,
     pub rpc_bigtable_timeout: Option<Duration>,
     pub minimal_api: bool,
     pub obsolete_v1_7_api: bool,
     pub rpc_scan_and_fix_roots: bool,
+    /// Serve every read at finalized commitment, regardless of the requested commitment
+    pub finalized_only: bool,
+    /// Also serve JSON RPC requests over HTTP on this unix socket
+    pub unix_socket_path: Option<PathBuf>,
+    /// Serve HTTPS instead of HTTP
+    pub tls: Option<TlsConfig>,
+    /// Reuse the results of `getProgramAccounts` scans for this long
+    pub program_accounts_cache_ttl: Option<Duration>,
+    /// Recompute the results of `getLargestAccounts` in the background this often, instead of
+    /// on demand once they expire
+    pub largest_accounts_refresh_interval: Option<Duration>,
+    /// Compress responses of at least this many bytes for clients that accept zstd or gzip
+    pub http_compression_min_size: Option<usize>,
+    /// Rate limit requests by client address with the limits in this JSON file
+    pub rate_limits_path: Option<PathBuf>,
+    /// Log requests that take at least this long, with the time spent in each phase
+    pub slow_request_threshold: Option<Duration>,
 }
 
 #[derive(Clone)]
@@ -162,22 +476,51 @@ pub struct JsonRpcRequestProcessor {
     cluster_info: Arc<ClusterInfo>,
     genesis_hash: Hash,
     transaction_sender: Arc<Mutex<Sender<TransactionInfo>>>,
-    bigtable_ledger_storage: Option<solana_storage_bigtable::LedgerStorage>,
+    bigtable_ledger_storage: Option<Arc<dyn LedgerArchive>>,
     optimistically_confirmed_bank: Arc<RwLock<OptimisticallyConfirmedBank>>,
     largest_accounts_cache: Arc<RwLock<LargestAccountsCache>>,
+    program_accounts_cache: Arc<ProgramAccountsCache>,
+    retry_status_cache: Arc<RetryStatusCache>,
+    block_time_samples: Arc<BlockTimeSamples>,
+    vote_accounts_snapshots: Arc<VoteAccountsSnapshots>,
     max_slots: Arc<MaxSlots>,
     leader_schedule_cache: Arc<LeaderScheduleCache>,
     max_complete_transaction_status_slot: Arc<AtomicU64>,
+    scan_pool: Arc<ScanPool>,
+    /// Trace of the request being handled, set by the `RequestTracer` middleware
+    pub(crate) request_trace: Option<Arc<RequestTrace>>,
 }
 impl Metadata for JsonRpcRequestProcessor {}
 
 impl JsonRpcRequestProcessor {
-    #[allow(deprecated)]
+    /// Runs `f`, counting the time it takes towards `phase` of the request trace
+    fn trace<T>(&self, phase: TracePhase, f: impl FnOnce() -> T) -> T {
+        match &self.request_trace {
+            Some(request_trace) => request_trace.time(phase, f),
+            None => f(),
+        }
+    }
+
     fn bank(&self, commitment: Option<CommitmentConfig>) -> Arc<Bank> {
+        self.trace(TracePhase::Bank, || self.commitment_bank(commitment))
+    }
+
+    /// The commitment a request is served at: the requested one, or finalized in
+    /// `finalized_only` mode
+    fn effective_commitment(&self, commitment: Option<CommitmentConfig>) -> CommitmentConfig {
+        if self.config.finalized_only {
+            CommitmentConfig::finalized()
+        } else {
+            commitment.unwrap_or_default()
+        }
+    }
+
+    #[allow(deprecated)]
+    fn commitment_bank(&self, commitment: Option<CommitmentConfig>) -> Arc<Bank> {
         debug!("RPC commitment_config: {:?}", commitment);
         let r_bank_forks = self.bank_forks.read().unwrap();
 
-        let commitment = commitment.unwrap_or_default();
+        let commitment = self.effective_commitment(commitment);
 
         if commitment.is_confirmed() {
             let bank = self
@@ -240,6 +583,31 @@ impl JsonRpcRequestProcessor {
         self.bank(None).genesis_creation_time()
     }
 
+    /// Runs `f` on the thread pool of the methods that scan accounts, which is bounded so
+    /// that they can't take up the threads serving the other methods. Fails if too many
+    /// scans are waiting for the pool already.
+    pub(crate) fn spawn_scan<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(Self) -> T + Send + 'static,
+    ) -> Result<oneshot::Receiver<T>> {
+        let (sender, receiver) = oneshot::channel();
+        let processor = self.clone();
+        self.scan_pool.spawn(move || {
+            let _ = sender.send(f(processor));
+        })?;
+        Ok(receiver)
+    }
+
+    async fn run_scan<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(Self) -> Result<T> + Send + 'static,
+    ) -> Result<T> {
+        // The sender is dropped without a result if the scan panicked
+        self.spawn_scan(f)?
+            .await
+            .unwrap_or_else(|_| Err(Error::internal_error()))
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         config: JsonRpcConfig,
@@ -251,7 +619,7 @@ impl JsonRpcRequestProcessor {
         health: Arc<RpcHealth>,
         cluster_info: Arc<ClusterInfo>,
         genesis_hash: Hash,
-        bigtable_ledger_storage: Option<solana_storage_bigtable::LedgerStorage>,
+        bigtable_ledger_storage: Option<Arc<dyn LedgerArchive>>,
         optimistically_confirmed_bank: Arc<RwLock<OptimisticallyConfirmedBank>>,
         largest_accounts_cache: Arc<RwLock<LargestAccountsCache>>,
         max_slots: Arc<MaxSlots>,
@@ -259,6 +627,9 @@ impl JsonRpcRequestProcessor {
         max_complete_transaction_status_slot: Arc<AtomicU64>,
     ) -> (Self, Receiver<TransactionInfo>) {
         let (sender, receiver) = channel();
+        let program_accounts_cache =
+            Arc::new(ProgramAccountsCache::new(config.program_accounts_cache_ttl));
+        let scan_pool = Arc::new(ScanPool::new(config.rpc_scan_threads));
         (
             Self {
                 config,
@@ -274,9 +645,15 @@ impl JsonRpcRequestProcessor {
                 bigtable_ledger_storage,
                 optimistically_confirmed_bank,
                 largest_accounts_cache,
+                program_accounts_cache,
+                retry_status_cache: Arc::default(),
+                block_time_samples: Arc::default(),
+                vote_accounts_snapshots: Arc::default(),
                 max_slots,
                 leader_schedule_cache,
                 max_complete_transaction_status_slot,
+                scan_pool,
+                request_trace: None,
             },
             receiver,
         )
@@ -318,7 +695,13 @@ impl JsonRpcRequestProcessor {
             ))),
             blockstore,
             validator_exit: create_validator_exit(&exit),
-            health: Arc::new(RpcHealth::new(cluster_info.clone(), None, 0, exit.clone())),
+            health: Arc::new(RpcHealth::new(
+                cluster_info.clone(),
+                None,
+                0,
+                exit.clone(),
+                None,
+            )),
             cluster_info,
             genesis_hash,
             transaction_sender: Arc::new(Mutex::new(sender)),
@@ -327,9 +710,15 @@ impl JsonRpcRequestProcessor {
                 bank: bank.clone(),
             })),
             largest_accounts_cache: Arc::new(RwLock::new(LargestAccountsCache::new(30))),
+            program_accounts_cache: Arc::new(ProgramAccountsCache::new(None)),
+            retry_status_cache: Arc::default(),
+            block_time_samples: Arc::default(),
+            vote_accounts_snapshots: Arc::default(),
             max_slots: Arc::new(MaxSlots::default()),
             leader_schedule_cache: Arc::new(LeaderScheduleCache::new_from_bank(bank)),
             max_complete_transaction_status_slot: Arc::new(AtomicU64::default()),
+            scan_pool: Arc::new(ScanPool::new(1)),
+            request_trace: None,
         }
     }
 
@@ -341,25 +730,50 @@ impl JsonRpcRequestProcessor {
         let config = config.unwrap_or_default();
         let bank = self.bank(config.commitment);
         let encoding = config.encoding.unwrap_or(UiAccountEncoding::Binary);
+        check_no_compression_level(&config)?;
         check_slice_and_encoding(&encoding, config.data_slice.is_some())?;
 
-        let response = get_encoded_account(&bank, pubkey, encoding, config.data_slice)?;
+        let response = self.trace(TracePhase::Serialize, || {
+            get_encoded_account(&bank, pubkey, encoding, config.data_slice)
+        })?;
         Ok(new_response(&bank, response))
     }
 
+    /// Returns the accounts of `keys`, each with the data slice given along with it or else
+    /// the one of `config`. Accounts not owned by `owner` are returned as `None`.
     pub fn get_multiple_accounts(
         &self,
-        pubkeys: Vec<Pubkey>,
+        keys: Vec<(Pubkey, Option<UiDataSliceConfig>)>,
         config: Option<RpcAccountInfoConfig>,
+        owner: Option<Pubkey>,
     ) -> Result<RpcResponse<Vec<Option<UiAccount>>>> {
         let config = config.unwrap_or_default();
         let bank = self.bank(config.commitment);
         let encoding = config.encoding.unwrap_or(UiAccountEncoding::Base64);
-        check_slice_and_encoding(&encoding, config.data_slice.is_some())?;
+        check_no_compression_level(&config)?;
+        check_slice_and_encoding(
+            &encoding,
+            config.data_slice.is_some() || keys.iter().any(|(_, data_slice)| data_slice.is_some()),
+        )?;
 
-        let accounts = pubkeys
+        let accounts = keys
             .into_iter()
-            .map(|pubkey| get_encoded_account(&bank, &pubkey, encoding, config.data_slice))
+            .map(|(pubkey, data_slice)| {
+                bank.get_account(&pubkey)
+                    .filter(|account| owner.map_or(true, |owner| account.owner() == &owner))
+                    .map(|account| {
+                        self.trace(TracePhase::Serialize, || {
+                            encode_bank_account(
+                                &bank,
+                                &pubkey,
+                                account,
+                                encoding,
+                                data_slice.or(config.data_slice),
+                            )
+                        })
+                    })
+                    .transpose()
+            })
             .collect::<Result<Vec<_>>>()?;
         Ok(new_response(&bank, accounts))
     }
@@ -377,42 +791,114 @@ impl JsonRpcRequestProcessor {
         &self,
         program_id: &Pubkey,
         config: Option<RpcAccountInfoConfig>,
-        mut filters: Vec<RpcFilterType>,
+        filters: Vec<RpcFilterType>,
         with_context: bool,
+        page: ProgramAccountsPage,
     ) -> Result<OptionalContext<Vec<RpcKeyedAccount>>> {
         let config = config.unwrap_or_default();
+        let encoding = config.encoding.unwrap_or(UiAccountEncoding::Binary);
+        let data_slice_config = config.data_slice;
+        check_no_compression_level(&config)?;
+        check_slice_and_encoding(&encoding, data_slice_config.is_some())?;
+        let (slot, keyed_accounts) =
+            self.get_program_keyed_accounts(program_id, config.commitment, filters, page)?;
+        // Parsed token accounts look their mint up in the current bank, which may be newer
+        // than a cached scan
         let bank = self.bank(config.commitment);
+        let result = self.trace(TracePhase::Serialize, || {
+            encode_program_accounts(
+                &bank,
+                program_id,
+                page.select(&keyed_accounts),
+                encoding,
+                data_slice_config,
+            )
+        })?;
+        Ok(with_optional_context(slot, with_context, result))
+    }
+
+    /// The number of accounts of `program_id` that pass `filters` at `commitment`
+    pub fn get_program_accounts_count(
+        &self,
+        program_id: &Pubkey,
+        commitment: Option<CommitmentConfig>,
+        filters: Vec<RpcFilterType>,
+        with_context: bool,
+    ) -> Result<OptionalContext<u64>> {
+        let (slot, keyed_accounts) = self.get_program_keyed_accounts(
+            program_id,
+            commitment,
+            filters,
+            ProgramAccountsPage::default(),
+        )?;
+        let count = keyed_accounts.keyed_accounts().len() as u64;
+        Ok(with_optional_context(slot, with_context, count))
+    }
+
+    /// Like `get_program_accounts`, but only scans the accounts up front and encodes them
+    /// lazily, `chunk_size` at a time, for streaming
+    pub(crate) fn get_program_accounts_chunks(
+        &self,
+        program_id_str: &str,
+        config: Option<RpcGetProgramAccountsConfig>,
+        chunk_size: usize,
+    ) -> Result<impl Iterator<Item = Result<Vec<RpcKeyedAccount>>>> {
+        let program_id = verify_pubkey(program_id_str)?;
+        let query = verify_program_accounts_config(config)?;
+        let config = query.account_config.unwrap_or_default();
         let encoding = config.encoding.unwrap_or(UiAccountEncoding::Binary);
         let data_slice_config = config.data_slice;
+        check_no_compression_level(&config)?;
         check_slice_and_encoding(&encoding, data_slice_config.is_some())?;
+        let (_slot, keyed_accounts) = self.get_program_keyed_accounts(
+            &program_id,
+            config.commitment,
+            query.filters,
+            query.page,
+        )?;
+        let bank = self.bank(config.commitment);
+        let mut indexes = query.page.select_indexes(&keyed_accounts).into_iter();
+        Ok(std::iter::from_fn(move || {
+            let chunk: Vec<_> = indexes
+                .by_ref()
+                .take(chunk_size)
+                .map(|i| &keyed_accounts.keyed_accounts()[i])
+                .collect();
+            (!chunk.is_empty()).then(|| {
+                encode_program_accounts(&bank, &program_id, chunk, encoding, data_slice_config)
+            })
+        }))
+    }
+
+    /// The accounts of `program_id` that pass `filters` at `commitment`, along with the
+    /// slot they were read at. Scans are reused for
+    /// `JsonRpcConfig::program_accounts_cache_ttl`, if set, so that the following pages of
+    /// `page` don't scan and sort again.
+    pub(crate) fn get_program_keyed_accounts(
+        &self,
+        program_id: &Pubkey,
+        commitment: Option<CommitmentConfig>,
+        mut filters: Vec<RpcFilterType>,
+        page: ProgramAccountsPage,
+    ) -> Result<(Slot, KeyedAccounts)> {
         optimize_filters(&mut filters);
-        let keyed_accounts = {
-            if let Some(owner) = get_spl_token_owner_filter(program_id, &filters) {
-                self.get_filtered_spl_token_accounts_by_owner(&bank, &owner, filters)?
+        let key = ScanKey {
+            program_id: *program_id,
+            commitment,
+            filters: filters.clone(),
+        };
+        self.program_accounts_cache.get_or_scan(key, || {
+            let bank = self.bank(commitment);
+            let keyed_accounts = if let Some(owner) =
+                get_spl_token_owner_filter(program_id, &filters)
+            {
+                self.get_filtered_spl_token_accounts_by_owner(&bank, program_id, &owner, filters)?
             } else if let Some(mint) = get_spl_token_mint_filter(program_id, &filters) {
-                self.get_filtered_spl_token_accounts_by_mint(&bank, &mint, filters)?
+                self.get_filtered_spl_token_accounts_by_mint(&bank, program_id, &mint, filters)?
             } else {
                 self.get_filtered_program_accounts(&bank, program_id, filters)?
-            }
-        };
-        let result = if program_id == &spl_token_id_v2_0()
-            && encoding == UiAccountEncoding::JsonParsed
-        {
-            get_parsed_token_accounts(bank.clone(), keyed_accounts.into_iter()).collect()
-        } else {
-            keyed_accounts
-                .into_iter()
-                .map(|(pubkey, account)| {
-                    Ok(RpcKeyedAccount {
-                        pubkey: pubkey.to_string(),
-                        account: encode_account(&account, &pubkey, encoding, data_slice_config)?,
-                    })
-                })
-                .collect::<Result<Vec<_>>>()?
-        };
-        Ok(result).map(|result| match with_context {
-            true => OptionalContext::Context(new_response(&bank, result)),
-            false => OptionalContext::NoContext(result),
+            };
+            Ok((bank.slot(), keyed_accounts))
         })
     }
 
@@ -422,13 +908,137 @@ impl JsonRpcRequestProcessor {
         config: Option<RpcEpochConfig>,
     ) -> Result<Vec<Option<RpcInflationReward>>> {
         let config = config.unwrap_or_default();
+        let epoch = config
+            .epoch
+            .unwrap_or_else(|| self.get_last_epoch(config.commitment));
+        let (first_confirmed_block_in_epoch, rewards) =
+            self.get_epoch_rewards(epoch, config.commitment).await?;
+
+        let addresses: Vec<String> = addresses
+            .into_iter()
+            .map(|pubkey| pubkey.to_string())
+            .collect();
+
+        let reward_hash: HashMap<String, Reward> = rewards
+            .into_iter()
+            .filter_map(|reward| match reward.reward_type? {
+                RewardType::Staking | RewardType::Voting => addresses
+                    .contains(&reward.pubkey)
+                    .then(|| (reward.clone().pubkey, reward)),
+                _ => None,
+            })
+            .collect();
+
+        let rewards = addresses
+            .iter()
+            .map(|address| {
+                if let Some(reward) = reward_hash.get(address) {
+                    return Some(RpcInflationReward {
+                        epoch,
+                        effective_slot: first_confirmed_block_in_epoch,
+                        amount: reward.lamports.abs() as u64,
+                        post_balance: reward.post_balance,
+                        commission: reward.commission,
+                    });
+                }
+                None
+            })
+            .collect();
+
+        Ok(rewards)
+    }
+
+    pub async fn get_vote_account_inflation_rewards(
+        &self,
+        vote_pubkey: Pubkey,
+        config: Option<RpcVoteAccountInflationRewardsConfig>,
+    ) -> Result<RpcVoteAccountInflationRewards> {
+        let config = config.unwrap_or_default();
+        let limit = config
+            .limit
+            .unwrap_or(MAX_GET_VOTE_ACCOUNT_INFLATION_REWARDS_LIMIT);
+        if limit == 0 || limit > MAX_GET_VOTE_ACCOUNT_INFLATION_REWARDS_LIMIT {
+            return Err(Error::invalid_params(format!(
+                "Invalid limit; max {}",
+                MAX_GET_VOTE_ACCOUNT_INFLATION_REWARDS_LIMIT
+            )));
+        }
+        let after_pubkey = config
+            .after_pubkey
+            .map(|after_pubkey| verify_pubkey(&after_pubkey))
+            .transpose()?;
+        let epoch = config
+            .epoch
+            .unwrap_or_else(|| self.get_last_epoch(config.commitment));
+
+        // The delegations that the rewards were calculated from are snapshotted into the epoch
+        // stakes at the start of the next epoch. Once the bank no longer holds that snapshot,
+        // the current delegations are used instead.
+        let bank = self.bank(config.commitment);
+        let epoch_schedule = bank.epoch_schedule();
+        let rewards_stakes_epoch = epoch_schedule.get_leader_schedule_epoch(
+            epoch_schedule.get_first_slot_in_epoch(epoch.saturating_add(1)),
+        );
+        let is_delegated = |delegation: &Delegation| delegation.voter_pubkey == vote_pubkey;
+        let epoch_stakes = bank.epoch_stakes(rewards_stakes_epoch);
+        let approximate_delegations = epoch_stakes.is_none();
+        let stake_pubkeys: HashSet<String> = match epoch_stakes {
+            Some(epoch_stakes) => epoch_stakes
+                .stakes()
+                .stake_delegations()
+                .iter()
+                .filter(|(_, delegation)| is_delegated(delegation))
+                .map(|(stake_pubkey, _)| stake_pubkey.to_string())
+                .collect(),
+            None => bank
+                .cloned_stake_delegations()
+                .iter()
+                .filter(|(_, delegation)| is_delegated(delegation))
+                .map(|(stake_pubkey, _)| stake_pubkey.to_string())
+                .collect(),
+        };
+        drop(bank);
+
+        let (effective_slot, rewards) = self.get_epoch_rewards(epoch, config.commitment).await?;
+        let (vote_reward, stake_rewards) = vote_account_rewards(
+            rewards,
+            &vote_pubkey.to_string(),
+            &stake_pubkeys,
+            after_pubkey,
+            limit,
+        )?;
+
+        Ok(RpcVoteAccountInflationRewards {
+            epoch,
+            effective_slot,
+            vote_reward: vote_reward.map(|reward| RpcInflationReward {
+                epoch,
+                effective_slot,
+                amount: reward.lamports.abs() as u64,
+                post_balance: reward.post_balance,
+                commission: reward.commission,
+            }),
+            stake_rewards,
+            approximate_delegations,
+        })
+    }
+
+    /// Returns the epoch before the one of the bank at `commitment`, the latest epoch with
+    /// rewards paid
+    fn get_last_epoch(&self, commitment: Option<CommitmentConfig>) -> Epoch {
+        self.get_epoch_schedule()
+            .get_epoch(self.get_slot(commitment))
+            .saturating_sub(1)
+    }
+
+    /// Returns the rewards paid for `epoch`, along with the slot of the block they are found in
+    async fn get_epoch_rewards(
+        &self,
+        epoch: Epoch,
+        commitment: Option<CommitmentConfig>,
+    ) -> Result<(Slot, Rewards)> {
         let epoch_schedule = self.get_epoch_schedule();
         let first_available_block = self.get_first_available_block().await;
-        let epoch = config.epoch.unwrap_or_else(|| {
-            epoch_schedule
-                .get_epoch(self.get_slot(config.commitment))
-                .saturating_sub(1)
-        });
 
         // Rewards for this epoch are found in the first confirmed block of the next epoch
         let first_slot_in_epoch = epoch_schedule.get_first_slot_in_epoch(epoch.saturating_add(1));
@@ -448,7 +1058,11 @@ impl JsonRpcRequestProcessor {
         }
 
         let first_confirmed_block_in_epoch = *self
-            .get_blocks_with_limit(first_slot_in_epoch, 1, config.commitment)
+            .get_blocks_with_limit(
+                first_slot_in_epoch,
+                1,
+                Some(RpcBlocksConfig::with_commitment(commitment)),
+            )
             .await?
             .get(0)
             .ok_or(RpcCustomError::BlockNotAvailable {
@@ -458,7 +1072,7 @@ impl JsonRpcRequestProcessor {
         let first_confirmed_block = if let Ok(Some(first_confirmed_block)) = self
             .get_block(
                 first_confirmed_block_in_epoch,
-                Some(RpcBlockConfig::rewards_with_commitment(config.commitment).into()),
+                Some(RpcBlockConfig::rewards_with_commitment(commitment).into()),
             )
             .await
         {
@@ -470,40 +1084,10 @@ impl JsonRpcRequestProcessor {
             .into());
         };
 
-        let addresses: Vec<String> = addresses
-            .into_iter()
-            .map(|pubkey| pubkey.to_string())
-            .collect();
-
-        let reward_hash: HashMap<String, Reward> = first_confirmed_block
-            .rewards
-            .unwrap_or_default()
-            .into_iter()
-            .filter_map(|reward| match reward.reward_type? {
-                RewardType::Staking | RewardType::Voting => addresses
-                    .contains(&reward.pubkey)
-                    .then(|| (reward.clone().pubkey, reward)),
-                _ => None,
-            })
-            .collect();
-
-        let rewards = addresses
-            .iter()
-            .map(|address| {
-                if let Some(reward) = reward_hash.get(address) {
-                    return Some(RpcInflationReward {
-                        epoch,
-                        effective_slot: first_confirmed_block_in_epoch,
-                        amount: reward.lamports.abs() as u64,
-                        post_balance: reward.post_balance,
-                        commission: reward.commission,
-                    });
-                }
-                None
-            })
-            .collect();
-
-        Ok(rewards)
+        Ok((
+            first_confirmed_block_in_epoch,
+            first_confirmed_block.rewards.unwrap_or_default(),
+        ))
     }
 
     pub fn get_inflation_governor(
@@ -643,6 +1227,15 @@ impl JsonRpcRequestProcessor {
         self.bank(commitment).block_height()
     }
 
+    fn get_health_report(&self) -> RpcHealthReport {
+        let snapshot_archives_dir = self
+            .snapshot_config
+            .as_ref()
+            .map(|snapshot_config| snapshot_config.snapshot_archives_dir.as_path());
+        let root = self.bank_forks.read().unwrap().root();
+        self.health.report(snapshot_archives_dir, root)
+    }
+
     fn get_max_retransmit_slot(&self) -> Slot {
         self.max_slots.retransmit.load(Ordering::Relaxed)
     }
@@ -668,8 +1261,11 @@ impl JsonRpcRequestProcessor {
 
         let mut slot_leaders = Vec::with_capacity(limit);
         while slot_leaders.len() < limit {
-            if let Some(leader_schedule) =
-                self.leader_schedule_cache.get_epoch_leader_schedule(epoch)
+            // The schedule of an epoch the cache no longer holds can still be computed while the
+            // bank has the epoch's stakes
+            if let Some(leader_schedule) = self
+                .leader_schedule_cache
+                .get_historical_epoch_leader_schedule(epoch, &bank)
             {
                 slot_leaders.extend(
                     leader_schedule
@@ -716,7 +1312,7 @@ impl JsonRpcRequestProcessor {
     fn get_cached_largest_accounts(
         &self,
         filter: &Option<RpcLargestAccountsFilter>,
-    ) -> Option<(u64, Vec<RpcAccountBalance>)> {
+    ) -> Option<(u64, Vec<RpcAccountBalance>, Duration)> {
         let largest_accounts_cache = self.largest_accounts_cache.read().unwrap();
         largest_accounts_cache.get_largest_accounts(filter)
     }
@@ -734,59 +1330,148 @@ impl JsonRpcRequestProcessor {
     fn get_largest_accounts(
         &self,
         config: Option<RpcLargestAccountsConfig>,
-    ) -> RpcCustomResult<RpcResponse<Vec<RpcAccountBalance>>> {
+    ) -> Result<RpcLargestAccountsResponse> {
         let config = config.unwrap_or_default();
-        let bank = self.bank(config.commitment);
+        // The background refresh only computes the results at the finalized bank
+        if self.config.largest_accounts_refresh_interval.is_some()
+            && !config.commitment.unwrap_or_default().is_finalized()
+        {
+            return Err(Error::invalid_params(
+                "Largest accounts are only available at the finalized commitment".to_string(),
+            ));
+        }
+        let (slot, accounts, staleness) = match self.get_cached_largest_accounts(&config.filter) {
+            Some(cached) => cached,
+            // Only the background refresh scans the accounts when there is one
+            None if self.config.largest_accounts_refresh_interval.is_some() => {
+                return Err(RpcCustomError::LargestAccountsNotAvailableYet.into());
+            }
+            None => {
+                let bank = self.bank(config.commitment);
+                let mut accounts = Vec::new();
+                for (filter, filter_accounts) in self.calculate_largest_accounts(&bank)? {
+                    self.set_cached_largest_accounts(&filter, bank.slot(), &filter_accounts);
+                    if filter == config.filter {
+                        accounts = filter_accounts;
+                    }
+                }
+                (bank.slot(), accounts, Duration::ZERO)
+            }
+        };
+        Ok(RpcLargestAccountsResponse {
+            context: RpcResponseContext { slot },
+            value: accounts,
+            staleness: staleness.as_secs(),
+        })
+    }
+
+    /// Recomputes the cached largest accounts of every filter, at the finalized bank
+    pub(crate) fn refresh_largest_accounts(&self) {
+        let bank = self.bank(Some(CommitmentConfig::finalized()));
+        match self.calculate_largest_accounts(&bank) {
+            Ok(largest_accounts) => {
+                for (filter, accounts) in largest_accounts {
+                    self.set_cached_largest_accounts(&filter, bank.slot(), &accounts);
+                }
+            }
+            Err(err) => warn!("Failed to refresh largest accounts: {}", err),
+        }
+    }
 
-        if let Some((slot, accounts)) = self.get_cached_largest_accounts(&config.filter) {
-            Ok(Response {
-                context: RpcResponseContext { slot },
-                value: accounts,
+    /// Returns the largest accounts of every filter, from a single scan of the accounts
+    fn calculate_largest_accounts(
+        &self,
+        bank: &Arc<Bank>,
+    ) -> RpcCustomResult<Vec<(Option<RpcLargestAccountsFilter>, Vec<RpcAccountBalance>)>> {
+        let non_circulating_accounts: HashSet<Pubkey> = calculate_non_circulating_supply(bank)
+            .map_err(|e| RpcCustomError::ScanError {
+                message: e.to_string(),
+            })?
+            .accounts
+            .into_iter()
+            .collect();
+        // Non-circulating accounts push at most as many circulating ones out of the largest
+        let largest_accounts = self
+            .trace(TracePhase::Scan, || {
+                bank.get_largest_accounts(
+                    NUM_LARGEST_ACCOUNTS + non_circulating_accounts.len(),
+                    &HashSet::new(),
+                    AccountAddressFilter::Exclude,
+                )
             })
-        } else {
-            let (addresses, address_filter) = if let Some(filter) = config.clone().filter {
-                let non_circulating_supply =
-                    calculate_non_circulating_supply(&bank).map_err(|e| {
-                        RpcCustomError::ScanError {
-                            message: e.to_string(),
-                        }
-                    })?;
-                let addresses = non_circulating_supply.accounts.into_iter().collect();
-                let address_filter = match filter {
-                    RpcLargestAccountsFilter::Circulating => AccountAddressFilter::Exclude,
-                    RpcLargestAccountsFilter::NonCirculating => AccountAddressFilter::Include,
-                };
-                (addresses, address_filter)
-            } else {
-                (HashSet::new(), AccountAddressFilter::Exclude)
-            };
-            let accounts = bank
-                .get_largest_accounts(NUM_LARGEST_ACCOUNTS, &addresses, address_filter)
-                .map_err(|e| RpcCustomError::ScanError {
-                    message: e.to_string(),
-                })?
-                .into_iter()
+            .map_err(|e| RpcCustomError::ScanError {
+                message: e.to_string(),
+            })?;
+        // Ordered like the scan, by balance then address, both descending
+        let mut non_circulating_balances: Vec<_> = non_circulating_accounts
+            .iter()
+            .map(|address| (bank.get_balance(address), *address))
+            .filter(|(lamports, _)| *lamports > 0)
+            .collect();
+        non_circulating_balances.sort_unstable_by(|a, b| b.cmp(a));
+
+        let account_balances = |accounts: &mut dyn Iterator<Item = (Pubkey, u64)>| {
+            accounts
+                .take(NUM_LARGEST_ACCOUNTS)
                 .map(|(address, lamports)| RpcAccountBalance {
                     address: address.to_string(),
                     lamports,
                 })
-                .collect::<Vec<RpcAccountBalance>>();
-
-            self.set_cached_largest_accounts(&config.filter, bank.slot(), &accounts);
-            Ok(new_response(&bank, accounts))
-        }
+                .collect()
+        };
+        Ok(vec![
+            (
+                None,
+                account_balances(&mut largest_accounts.iter().copied()),
+            ),
+            (
+                Some(RpcLargestAccountsFilter::Circulating),
+                account_balances(
+                    &mut largest_accounts
+                        .iter()
+                        .copied()
+                        .filter(|(address, _)| !non_circulating_accounts.contains(address)),
+                ),
+            ),
+            (
+                Some(RpcLargestAccountsFilter::NonCirculating),
+                account_balances(
+                    &mut non_circulating_balances
+                        .into_iter()
+                        .map(|(lamports, address)| (address, lamports)),
+                ),
+            ),
+        ])
     }
 
     fn get_supply(
         &self,
         config: Option<RpcSupplyConfig>,
+        non_circulating_config: &NonCirculatingSupplyConfig,
     ) -> RpcCustomResult<RpcResponse<RpcSupply>> {
         let config = config.unwrap_or_default();
+        if self
+            .config
+            .account_indexes
+            .contains(&AccountIndex::ProgramId)
+        {
+            if let Some(owner) = non_circulating_config
+                .owners
+                .iter()
+                .find(|owner| !self.config.account_indexes.include_key(owner))
+            {
+                return Err(RpcCustomError::KeyExcludedFromSecondaryIndex {
+                    index_key: owner.to_string(),
+                });
+            }
+        }
         let bank = self.bank(config.commitment);
         let non_circulating_supply =
-            calculate_non_circulating_supply(&bank).map_err(|e| RpcCustomError::ScanError {
-                message: e.to_string(),
-            })?;
+            calculate_non_circulating_supply_with_config(&bank, non_circulating_config).map_err(
+                |e| RpcCustomError::ScanError {
+                    message: e.to_string(),
+                },
+            )?;
         let total_supply = bank.capitalization();
         let non_circulating_accounts = if config.exclude_non_circulating_accounts_list {
             vec![]
@@ -805,6 +1490,22 @@ impl JsonRpcRequestProcessor {
                 circulating: total_supply - non_circulating_supply.lamports,
                 non_circulating: non_circulating_supply.lamports,
                 non_circulating_accounts,
+                non_circulating_breakdown: config.with_breakdown.then(|| {
+                    let NonCirculatingBreakdown {
+                        listed,
+                        locked_stake,
+                        autostaked,
+                        accounts,
+                        owners,
+                    } = non_circulating_supply.breakdown;
+                    RpcSupplyBreakdown {
+                        listed,
+                        locked_stake,
+                        autostaked,
+                        accounts,
+                        owners,
+                    }
+                }),
             },
         ))
     }
@@ -830,18 +1531,36 @@ impl JsonRpcRequestProcessor {
         let delinquent_validator_slot_distance = config
             .delinquent_slot_distance
             .unwrap_or(DELINQUENT_VALIDATOR_SLOT_DISTANCE);
+        let keep_unstaked_delinquents = config.keep_unstaked_delinquents.unwrap_or_default();
+        // Whether the request selects a vote account, as it was at `slot`
+        let is_selected = |vote_pubkey: &Pubkey, vote_account: &VoteAccountSnapshot, slot: Slot| {
+            let is_current =
+                vote_account.last_vote > slot.saturating_sub(delinquent_validator_slot_distance);
+            filter_by_vote_pubkey.map_or(true, |filter_by_vote_pubkey| {
+                *vote_pubkey == filter_by_vote_pubkey
+            }) && vote_account.activated_stake >= config.min_activated_stake.unwrap_or_default()
+                && config.max_commission.map_or(true, |max_commission| {
+                    vote_account.commission <= max_commission
+                })
+                && config.min_epoch_credits.map_or(true, |min_epoch_credits| {
+                    vote_account.epoch_credits >= min_epoch_credits
+                })
+                && (keep_unstaked_delinquents || is_current || vote_account.activated_stake > 0)
+        };
+        // The vote accounts read at `changed_since_slot` by an earlier request, if still kept.
+        // Without them, every selected account is returned.
+        let changed_since = config.changed_since_slot.and_then(|changed_since_slot| {
+            self.vote_accounts_snapshots
+                .get(changed_since_slot)
+                .map(|snapshot| (changed_since_slot, snapshot))
+        });
+        let mut snapshot = HashMap::new();
         let (current_vote_accounts, delinquent_vote_accounts): (
             Vec<RpcVoteAccountInfo>,
             Vec<RpcVoteAccountInfo>,
         ) = vote_accounts
             .iter()
             .filter_map(|(vote_pubkey, (activated_stake, account))| {
-                if let Some(filter_by_vote_pubkey) = filter_by_vote_pubkey {
-                    if *vote_pubkey != filter_by_vote_pubkey {
-                        return None;
-                    }
-                }
-
                 let vote_state = account.vote_state();
                 let vote_state = vote_state.as_ref().unwrap_or(&default_vote_state);
                 let last_vote = if let Some(vote) = vote_state.votes.iter().last() {
@@ -849,6 +1568,38 @@ impl JsonRpcRequestProcessor {
                 } else {
                     0
                 };
+                let vote_account = VoteAccountSnapshot {
+                    activated_stake: *activated_stake,
+                    commission: vote_state.commission,
+                    last_vote,
+                    epoch_credits: vote_state
+                        .epoch_credits()
+                        .last()
+                        .filter(|(epoch, ..)| *epoch == bank.epoch())
+                        .map(|(_, credits, prev_credits)| credits.saturating_sub(*prev_credits))
+                        .unwrap_or_default(),
+                };
+                if config.changed_since_slot.is_some() {
+                    snapshot.insert(*vote_pubkey, vote_account);
+                }
+                if !is_selected(vote_pubkey, &vote_account, bank.slot()) {
+                    return None;
+                }
+                if let Some((changed_since_slot, changed_since_snapshot)) = &changed_since {
+                    // An account that stopped voting is unmodified, but still changes once it
+                    // turns delinquent
+                    let became_delinquent = (last_vote
+                        > changed_since_slot.saturating_sub(delinquent_validator_slot_distance))
+                        != (last_vote
+                            > bank
+                                .slot()
+                                .saturating_sub(delinquent_validator_slot_distance));
+                    if changed_since_snapshot.get(vote_pubkey) == Some(&vote_account)
+                        && !became_delinquent
+                    {
+                        return None;
+                    }
+                }
 
                 let epoch_credits = vote_state.epoch_credits();
                 let epoch_credits = if epoch_credits.len() > MAX_RPC_EPOCH_CREDITS_HISTORY {
@@ -873,27 +1624,38 @@ impl JsonRpcRequestProcessor {
                 })
             })
             .partition(|vote_account_info| {
-                if bank.slot() >= delinquent_validator_slot_distance as u64 {
-                    vote_account_info.last_vote
-                        > bank.slot() - delinquent_validator_slot_distance as u64
-                } else {
-                    vote_account_info.last_vote > 0
-                }
+                vote_account_info.last_vote
+                    > bank
+                        .slot()
+                        .saturating_sub(delinquent_validator_slot_distance)
             });
 
-        let keep_unstaked_delinquents = config.keep_unstaked_delinquents.unwrap_or_default();
-        let delinquent_vote_accounts = if !keep_unstaked_delinquents {
-            delinquent_vote_accounts
-                .into_iter()
-                .filter(|vote_account_info| vote_account_info.activated_stake > 0)
-                .collect::<Vec<_>>()
-        } else {
-            delinquent_vote_accounts
-        };
+        // The accounts selected at `changed_since_slot` that are no longer selected, including
+        // the closed ones
+        let removed = changed_since.map(|(changed_since_slot, changed_since_snapshot)| {
+            changed_since_snapshot
+                .iter()
+                .filter(|(vote_pubkey, changed_since_vote_account)| {
+                    is_selected(vote_pubkey, changed_since_vote_account, changed_since_slot)
+                        && !snapshot.get(*vote_pubkey).map_or(false, |vote_account| {
+                            is_selected(vote_pubkey, vote_account, bank.slot())
+                        })
+                })
+                .map(|(vote_pubkey, _)| vote_pubkey.to_string())
+                .collect()
+        });
+        // Accounts still change while the bank is processed, so only the vote accounts of frozen
+        // banks are kept
+        let slot = config.changed_since_slot.map(|_| bank.slot());
+        if config.changed_since_slot.is_some() && bank.is_frozen() {
+            self.vote_accounts_snapshots.insert(bank.slot(), snapshot);
+        }
 
         Ok(RpcVoteAccountStatus {
             current: current_vote_accounts,
             delinquent: delinquent_vote_accounts,
+            slot,
+            removed,
         })
     }
 
@@ -986,7 +1748,7 @@ impl JsonRpcRequestProcessor {
             let encoding = config.encoding.unwrap_or(UiTransactionEncoding::Json);
             let transaction_details = config.transaction_details.unwrap_or_default();
             let show_rewards = config.rewards.unwrap_or(true);
-            let commitment = config.commitment.unwrap_or_default();
+            let commitment = self.effective_commitment(config.commitment);
             check_is_at_least_confirmed(commitment)?;
 
             // Block is old enough to be finalized
@@ -1001,8 +1763,9 @@ impl JsonRpcRequestProcessor {
                 let result = self.blockstore.get_rooted_block(slot, true);
                 self.check_blockstore_root(&result, slot)?;
                 let configure_block = |confirmed_block: ConfirmedBlock| {
-                    let mut confirmed_block =
-                        confirmed_block.configure(encoding, transaction_details, show_rewards);
+                    let mut confirmed_block = self.trace(TracePhase::Serialize, || {
+                        confirmed_block.configure(encoding, transaction_details, show_rewards)
+                    });
                     if slot == 0 {
                         confirmed_block.block_time = Some(self.genesis_creation_time());
                         confirmed_block.block_height = Some(0);
@@ -1040,7 +1803,9 @@ impl JsonRpcRequestProcessor {
                                 }
                             }
                         }
-                        confirmed_block.configure(encoding, transaction_details, show_rewards)
+                        self.trace(TracePhase::Serialize, || {
+                            confirmed_block.configure(encoding, transaction_details, show_rewards)
+                        })
                     }));
                 }
             }
@@ -1054,9 +1819,10 @@ impl JsonRpcRequestProcessor {
         &self,
         start_slot: Slot,
         end_slot: Option<Slot>,
-        commitment: Option<CommitmentConfig>,
+        config: Option<RpcBlocksConfig>,
     ) -> Result<Vec<Slot>> {
-        let commitment = commitment.unwrap_or_default();
+        let config = config.unwrap_or_default();
+        let commitment = self.effective_commitment(config.commitment);
         check_is_at_least_confirmed(commitment)?;
 
         let highest_confirmed_root = self
@@ -1064,6 +1830,10 @@ impl JsonRpcRequestProcessor {
             .read()
             .unwrap()
             .highest_confirmed_root();
+        let time_range = self
+            .get_block_time_range(&config, highest_confirmed_root)
+            .await;
+        let start_slot = max(start_slot, time_range.start_slot);
 
         let end_slot = min(
             end_slot.unwrap_or_else(|| start_slot.saturating_add(MAX_GET_CONFIRMED_BLOCKS_RANGE)),
@@ -1073,6 +1843,7 @@ impl JsonRpcRequestProcessor {
                 self.bank(Some(CommitmentConfig::confirmed())).slot()
             },
         );
+        let end_slot = min(end_slot, time_range.end_slot);
         if end_slot < start_slot {
             return Ok(vec![]);
         }
@@ -1094,6 +1865,9 @@ impl JsonRpcRequestProcessor {
                     .await
                     .map(|mut bigtable_blocks| {
                         bigtable_blocks.retain(|&slot| slot <= end_slot);
+                        if config.reverse.unwrap_or_default() {
+                            bigtable_blocks.reverse();
+                        }
                         bigtable_blocks
                     })
                     .map_err(|_| {
@@ -1123,11 +1897,18 @@ impl JsonRpcRequestProcessor {
             let mut confirmed_blocks = confirmed_bank
                 .status_cache_ancestors()
                 .into_iter()
-                .filter(|&slot| slot <= end_slot && slot > last_element)
+                .filter(|&slot| {
+                    slot <= end_slot
+                        && slot > last_element
+                        && self.is_unrooted_block_in_time_range(slot, &time_range)
+                })
                 .collect();
             blocks.append(&mut confirmed_blocks);
         }
 
+        if config.reverse.unwrap_or_default() {
+            blocks.reverse();
+        }
         Ok(blocks)
     }
 
@@ -1135,9 +1916,10 @@ impl JsonRpcRequestProcessor {
         &self,
         start_slot: Slot,
         limit: usize,
-        commitment: Option<CommitmentConfig>,
+        config: Option<RpcBlocksConfig>,
     ) -> Result<Vec<Slot>> {
-        let commitment = commitment.unwrap_or_default();
+        let config = config.unwrap_or_default();
+        let commitment = self.effective_commitment(config.commitment);
         check_is_at_least_confirmed(commitment)?;
 
         if limit > MAX_GET_CONFIRMED_BLOCKS_RANGE as usize {
@@ -1147,6 +1929,27 @@ impl JsonRpcRequestProcessor {
             )));
         }
 
+        let highest_confirmed_root = self
+            .block_commitment_cache
+            .read()
+            .unwrap()
+            .highest_confirmed_root();
+        let time_range = self
+            .get_block_time_range(&config, highest_confirmed_root)
+            .await;
+        if config.reverse.unwrap_or_default() {
+            return self
+                .get_blocks_with_limit_reverse(
+                    min(start_slot, time_range.end_slot),
+                    limit,
+                    commitment,
+                    highest_confirmed_root,
+                    &time_range,
+                )
+                .await;
+        }
+        let start_slot = max(start_slot, time_range.start_slot);
+
         let lowest_blockstore_slot = self.blockstore.lowest_slot();
 
         if start_slot < lowest_blockstore_slot {
@@ -1154,26 +1957,22 @@ impl JsonRpcRequestProcessor {
             // range can be fetched from BigTable. This range should not ever run into unfinalized
             // confirmed blocks due to MAX_GET_CONFIRMED_BLOCKS_RANGE
             if let Some(bigtable_ledger_storage) = &self.bigtable_ledger_storage {
-                return Ok(bigtable_ledger_storage
+                let mut bigtable_blocks = bigtable_ledger_storage
                     .get_confirmed_blocks(start_slot, limit)
                     .await
-                    .unwrap_or_default());
+                    .unwrap_or_default();
+                bigtable_blocks.retain(|&slot| slot <= time_range.end_slot);
+                return Ok(bigtable_blocks);
             }
         }
 
-        let highest_confirmed_root = self
-            .block_commitment_cache
-            .read()
-            .unwrap()
-            .highest_confirmed_root();
-
         // Finalized blocks
         let mut blocks: Vec<_> = self
             .blockstore
             .rooted_slot_iterator(max(start_slot, lowest_blockstore_slot))
             .map_err(|_| Error::internal_error())?
+            .take_while(|&slot| slot <= highest_confirmed_root && slot <= time_range.end_slot)
             .take(limit)
-            .filter(|&slot| slot <= highest_confirmed_root)
             .collect();
 
         // Maybe add confirmed blocks
@@ -1186,7 +1985,11 @@ impl JsonRpcRequestProcessor {
             let mut confirmed_blocks = confirmed_bank
                 .status_cache_ancestors()
                 .into_iter()
-                .filter(|&slot| slot > last_element)
+                .filter(|&slot| {
+                    slot > last_element
+                        && slot <= time_range.end_slot
+                        && self.is_unrooted_block_in_time_range(slot, &time_range)
+                })
                 .collect();
             blocks.append(&mut confirmed_blocks);
             blocks.truncate(limit);
@@ -1195,6 +1998,251 @@ impl JsonRpcRequestProcessor {
         Ok(blocks)
     }
 
+    /// The `limit` newest blocks at or before `start_slot`, from newest to oldest
+    async fn get_blocks_with_limit_reverse(
+        &self,
+        start_slot: Slot,
+        limit: usize,
+        commitment: CommitmentConfig,
+        highest_confirmed_root: Slot,
+        time_range: &BlockTimeRange,
+    ) -> Result<Vec<Slot>> {
+        // Confirmed blocks are newer than any finalized block
+        let mut blocks = vec![];
+        if commitment.is_confirmed() && start_slot > highest_confirmed_root {
+            let confirmed_bank = self.bank(Some(CommitmentConfig::confirmed()));
+            blocks = confirmed_bank
+                .status_cache_ancestors()
+                .into_iter()
+                .filter(|&slot| {
+                    slot > highest_confirmed_root
+                        && slot <= start_slot
+                        && self.is_unrooted_block_in_time_range(slot, time_range)
+                })
+                .collect();
+            blocks.reverse();
+            blocks.truncate(limit);
+        }
+
+        // Finalized blocks, scanning back from the start slot in windows of growing length.
+        // Windows below the lowest blockstore slot are fetched from BigTable.
+        let lowest_blockstore_slot = self.blockstore.lowest_slot();
+        let lowest_slot = if self.bigtable_ledger_storage.is_some() {
+            time_range.start_slot
+        } else {
+            max(lowest_blockstore_slot, time_range.start_slot)
+        };
+        let mut window_end = min(start_slot, highest_confirmed_root);
+        let mut window_len = max(limit, 1) as Slot;
+        while blocks.len() < limit && window_end >= lowest_slot {
+            let mut window_start = max(window_end.saturating_sub(window_len - 1), lowest_slot);
+            let mut rooted_slots: Vec<_> = if window_end >= lowest_blockstore_slot {
+                window_start = max(window_start, lowest_blockstore_slot);
+                self.blockstore
+                    .rooted_slot_iterator(window_start)
+                    .map_err(|_| Error::internal_error())?
+                    .take_while(|&slot| slot <= window_end)
+                    .collect()
+            } else if let Some(bigtable_ledger_storage) = &self.bigtable_ledger_storage {
+                let mut bigtable_blocks = bigtable_ledger_storage
+                    .get_confirmed_blocks(window_start, (window_end - window_start + 1) as usize)
+                    .await
+                    .unwrap_or_default();
+                bigtable_blocks.retain(|&slot| slot <= window_end);
+                bigtable_blocks
+            } else {
+                break;
+            };
+            rooted_slots.reverse();
+            let remaining = limit - blocks.len();
+            blocks.extend(rooted_slots.into_iter().take(remaining));
+            if window_start == lowest_slot {
+                break;
+            }
+            window_end = window_start - 1;
+            window_len = window_len.saturating_mul(2);
+        }
+        Ok(blocks)
+    }
+
+    /// Maps the `minBlockTime` and `maxBlockTime` of `config` to slots, by searching the
+    /// block times of the finalized blocks in the blockstore, and in BigTable when enabled
+    async fn get_block_time_range(
+        &self,
+        config: &RpcBlocksConfig,
+        highest_confirmed_root: Slot,
+    ) -> BlockTimeRange {
+        let lowest_slot = if self.bigtable_ledger_storage.is_some() {
+            0
+        } else {
+            self.blockstore.lowest_slot()
+        };
+        let mut time_range = BlockTimeRange {
+            min_block_time: config.min_block_time,
+            max_block_time: config.max_block_time,
+            start_slot: 0,
+            end_slot: Slot::MAX,
+        };
+        if let Some(min_block_time) = config.min_block_time {
+            // Without a late enough finalized block, only newer blocks may match
+            time_range.start_slot = self
+                .first_root_at_or_after_block_time(
+                    min_block_time,
+                    lowest_slot,
+                    highest_confirmed_root,
+                )
+                .await
+                .unwrap_or_else(|| highest_confirmed_root.saturating_add(1));
+        }
+        if let Some(max_block_time) = config.max_block_time {
+            if let Some(slot) = self
+                .first_root_at_or_after_block_time(
+                    max_block_time.saturating_add(1),
+                    lowest_slot,
+                    highest_confirmed_root,
+                )
+                .await
+            {
+                match slot.checked_sub(1) {
+                    Some(end_slot) => time_range.end_slot = end_slot,
+                    None => {
+                        // Even the genesis block is too new
+                        time_range.start_slot = 1;
+                        time_range.end_slot = 0;
+                    }
+                }
+            }
+        }
+        time_range
+    }
+
+    /// The first finalized slot in `[low, high]` whose block was produced at or after
+    /// `block_time`. The search starts from the samples of earlier lookups, and interpolates
+    /// between the block times found so far, falling back to bisection every other probe.
+    async fn first_root_at_or_after_block_time(
+        &self,
+        block_time: UnixTimestamp,
+        mut low: Slot,
+        mut high: Slot,
+    ) -> Option<Slot> {
+        let (mut below, mut above) = self.block_time_samples.bounds(block_time, low, high);
+        if let Some((slot, _)) = below {
+            low = slot + 1;
+        }
+        if let Some((slot, _)) = above {
+            match slot.checked_sub(1) {
+                Some(slot) => high = slot,
+                None => return Some(0),
+            }
+        }
+        let mut interpolate = true;
+        while low <= high {
+            let mid = match (below, above) {
+                (Some((below_slot, below_time)), Some((above_slot, above_time)))
+                    if interpolate && above_time > below_time =>
+                {
+                    let offset = (block_time - below_time) as u128
+                        * (above_slot - below_slot) as u128
+                        / (above_time - below_time) as u128;
+                    (below_slot + offset as Slot).clamp(low, high)
+                }
+                _ => low + (high - low) / 2,
+            };
+            interpolate = !interpolate;
+            match self.first_timed_root_at_or_after(mid, high).await {
+                Some((slot, slot_block_time)) => {
+                    self.block_time_samples.insert(slot, slot_block_time);
+                    if slot_block_time >= block_time {
+                        above = Some((slot, slot_block_time));
+                        if mid == 0 {
+                            break;
+                        }
+                        high = mid - 1;
+                    } else {
+                        below = Some((slot, slot_block_time));
+                        low = slot + 1;
+                    }
+                }
+                None => {
+                    if mid == 0 {
+                        break;
+                    }
+                    high = mid - 1;
+                }
+            }
+        }
+        above.map(|(slot, _)| slot)
+    }
+
+    /// The first finalized block in `[slot, high]` with a known block time, and its block time
+    async fn first_timed_root_at_or_after(
+        &self,
+        slot: Slot,
+        high: Slot,
+    ) -> Option<(Slot, UnixTimestamp)> {
+        let lowest_blockstore_slot = self.blockstore.lowest_slot();
+        if slot < lowest_blockstore_slot {
+            if let Some(bigtable_ledger_storage) = &self.bigtable_ledger_storage {
+                let bigtable_blocks = bigtable_ledger_storage
+                    .get_confirmed_blocks(slot, BIGTABLE_BLOCK_TIME_PROBE_BLOCKS)
+                    .await
+                    .unwrap_or_default();
+                for slot in bigtable_blocks.into_iter().take_while(|&slot| slot <= high) {
+                    if slot >= lowest_blockstore_slot {
+                        return self.first_timed_blockstore_root_at_or_after(slot, high);
+                    }
+                    let block_time = bigtable_ledger_storage
+                        .get_confirmed_block(slot)
+                        .await
+                        .ok()
+                        .and_then(|confirmed_block| confirmed_block.block_time);
+                    if let Some(block_time) = block_time {
+                        return Some((slot, block_time));
+                    }
+                }
+                return None;
+            }
+        }
+        self.first_timed_blockstore_root_at_or_after(slot, high)
+    }
+
+    fn first_timed_blockstore_root_at_or_after(
+        &self,
+        slot: Slot,
+        high: Slot,
+    ) -> Option<(Slot, UnixTimestamp)> {
+        self.blockstore
+            .rooted_slot_iterator(slot)
+            .ok()?
+            .take_while(|&slot| slot <= high)
+            .find_map(|slot| Some((slot, self.get_rooted_block_time(slot)?)))
+    }
+
+    fn get_rooted_block_time(&self, slot: Slot) -> Option<UnixTimestamp> {
+        if slot == 0 {
+            return Some(self.genesis_creation_time());
+        }
+        self.blockstore.get_block_time(slot).ok().flatten()
+    }
+
+    /// Whether the block of `slot`, which isn't finalized yet, was produced within
+    /// `time_range` according to the clock of its bank
+    fn is_unrooted_block_in_time_range(&self, slot: Slot, time_range: &BlockTimeRange) -> bool {
+        if time_range.min_block_time.is_none() && time_range.max_block_time.is_none() {
+            return true;
+        }
+        let bank_forks = self.bank_forks.read().unwrap();
+        bank_forks.get(slot).map_or(false, |bank| {
+            let block_time = bank.clock().unix_timestamp;
+            time_range
+                .min_block_time
+                .map_or(true, |min| block_time >= min)
+                && time_range
+                    .max_block_time
+                    .map_or(true, |max| block_time <= max)
+        })
+    }
+
     pub async fn get_block_time(&self, slot: Slot) -> Result<Option<UnixTimestamp>> {
         if slot == 0 {
             return Ok(Some(self.genesis_creation_time()));
@@ -1361,7 +2409,7 @@ impl JsonRpcRequestProcessor {
             .map(|config| config.convert_to_current())
             .unwrap_or_default();
         let encoding = config.encoding.unwrap_or(UiTransactionEncoding::Json);
-        let commitment = config.commitment.unwrap_or_default();
+        let commitment = self.effective_commitment(config.commitment);
         check_is_at_least_confirmed(commitment)?;
 
         if self.config.enable_rpc_transaction_history {
@@ -1386,7 +2434,9 @@ impl JsonRpcRequestProcessor {
                                 .get(confirmed_transaction.slot)
                                 .map(|bank| bank.clock().unix_timestamp);
                         }
-                        return Ok(Some(confirmed_transaction.encode(encoding)));
+                        return Ok(Some(self.trace(TracePhase::Serialize, || {
+                            confirmed_transaction.encode(encoding)
+                        })));
                     }
                     if confirmed_transaction.slot
                         <= self
@@ -1395,7 +2445,9 @@ impl JsonRpcRequestProcessor {
                             .unwrap()
                             .highest_confirmed_root()
                     {
-                        return Ok(Some(confirmed_transaction.encode(encoding)));
+                        return Ok(Some(self.trace(TracePhase::Serialize, || {
+                            confirmed_transaction.encode(encoding)
+                        })));
                     }
                 }
                 None => {
@@ -1404,7 +2456,9 @@ impl JsonRpcRequestProcessor {
                             .get_confirmed_transaction(&signature)
                             .await
                             .unwrap_or(None)
-                            .map(|confirmed| confirmed.encode(encoding)));
+                            .map(|confirmed| {
+                                self.trace(TracePhase::Serialize, || confirmed.encode(encoding))
+                            }));
                     }
                 }
             }
@@ -1414,6 +2468,65 @@ impl JsonRpcRequestProcessor {
         Ok(None)
     }
 
+    pub fn get_transaction_retry_status(
+        &self,
+        signature: &Signature,
+    ) -> Option<RpcTransactionRetryStatus> {
+        self.retry_status_cache
+            .get(signature)
+            .map(|status| RpcTransactionRetryStatus {
+                state: match status.state {
+                    RetryState::Pending => RpcTransactionRetryState::Pending,
+                    RetryState::Processed => RpcTransactionRetryState::Processed,
+                    RetryState::Rooted => RpcTransactionRetryState::Rooted,
+                    RetryState::Expired => RpcTransactionRetryState::Expired,
+                    RetryState::Failed => RpcTransactionRetryState::Failed,
+                    RetryState::MaxRetriesElapsed => RpcTransactionRetryState::MaxRetriesElapsed,
+                    RetryState::Dropped => RpcTransactionRetryState::Dropped,
+                },
+                retries: status.retries,
+                last_valid_block_height: status.last_valid_block_height,
+            })
+    }
+
+    pub async fn get_transactions(
+        &self,
+        signature_strs: Vec<String>,
+        config: Option<RpcTransactionConfig>,
+    ) -> Result<Vec<RpcTransactionsEntry>> {
+        let config = config.unwrap_or_default();
+        check_is_at_least_confirmed(self.effective_commitment(config.commitment))?;
+        if !self.config.enable_rpc_transaction_history {
+            return Err(RpcCustomError::TransactionHistoryNotAvailable.into());
+        }
+
+        let lookups = signature_strs.into_iter().map(|signature_str| async move {
+            let transaction = match verify_signature(&signature_str) {
+                Ok(signature) => {
+                    self.get_transaction(
+                        signature,
+                        Some(RpcEncodingConfigWrapper::Current(Some(config))),
+                    )
+                    .await
+                }
+                Err(err) => Err(err),
+            };
+            match transaction {
+                Ok(transaction) => RpcTransactionsEntry {
+                    signature: signature_str,
+                    transaction,
+                    error: None,
+                },
+                Err(err) => RpcTransactionsEntry {
+                    signature: signature_str,
+                    transaction: None,
+                    error: Some(err.message),
+                },
+            }
+        });
+        Ok(future::join_all(lookups).await)
+    }
+
     pub fn get_confirmed_signatures_for_address(
         &self,
         pubkey: Pubkey,
@@ -1438,6 +2551,9 @@ impl JsonRpcRequestProcessor {
         }
     }
 
+    /// Lists the signatures of the transactions involving `address`. With a filter, the
+    /// search stops after examining `MAX_GET_SIGNATURES_FOR_ADDRESS_EXAMINED` signatures,
+    /// and `resume_before` is set to resume it.
     pub async fn get_signatures_for_address(
         &self,
         address: Pubkey,
@@ -1445,8 +2561,9 @@ impl JsonRpcRequestProcessor {
         until: Option<Signature>,
         mut limit: usize,
         commitment: Option<CommitmentConfig>,
-    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
-        let commitment = commitment.unwrap_or_default();
+        filter: AddressSignaturesFilter,
+    ) -> Result<RpcFilteredSignatures> {
+        let commitment = self.effective_commitment(commitment);
         check_is_at_least_confirmed(commitment)?;
 
         if self.config.enable_rpc_transaction_history {
@@ -1462,29 +2579,54 @@ impl JsonRpcRequestProcessor {
                 highest_confirmed_root
             };
 
-            let mut results = self
+            let AddressSignatures {
+                infos: mut results,
+                mut resume_before,
+            } = self
                 .blockstore
-                .get_confirmed_signatures_for_address2(address, highest_slot, before, until, limit)
+                .get_filtered_signatures_for_address(
+                    address,
+                    highest_slot,
+                    before,
+                    until,
+                    limit,
+                    &filter,
+                    MAX_GET_SIGNATURES_FOR_ADDRESS_EXAMINED,
+                )
                 .map_err(|err| Error::invalid_params(format!("{}", err)))?;
 
-            if results.len() < limit {
+            if results.len() < limit && resume_before.is_none() {
                 if let Some(bigtable_ledger_storage) = &self.bigtable_ledger_storage {
                     if !results.is_empty() {
                         limit -= results.len();
                         before = results.last().map(|x| x.signature);
                     }
 
-                    let bigtable_results = bigtable_ledger_storage
-                        .get_confirmed_signatures_for_address(
+                    let bigtable_results = if filter == AddressSignaturesFilter::default() {
+                        bigtable_ledger_storage
+                            .get_confirmed_signatures_for_address(
+                                &address,
+                                before.as_ref(),
+                                until.as_ref(),
+                                limit,
+                            )
+                            .await
+                            .map(|results| (results.into_iter().map(|x| x.0).collect(), None))
+                    } else {
+                        get_filtered_bigtable_signatures_for_address(
+                            &**bigtable_ledger_storage,
                             &address,
-                            before.as_ref(),
-                            until.as_ref(),
+                            before,
+                            until,
                             limit,
+                            &filter,
                         )
-                        .await;
+                        .await
+                    };
                     match bigtable_results {
-                        Ok(bigtable_results) => {
-                            results.extend(bigtable_results.into_iter().map(|x| x.0));
+                        Ok((bigtable_results, bigtable_resume_before)) => {
+                            results.extend(bigtable_results);
+                            resume_before = bigtable_resume_before;
                         }
                         Err(err) => {
                             warn!("{:?}", err);
@@ -1493,7 +2635,7 @@ impl JsonRpcRequestProcessor {
                 }
             }
 
-            Ok(results
+            let signatures = results
                 .into_iter()
                 .map(|x| {
                     let mut item: RpcConfirmedTransactionStatusWithSignature = x.into();
@@ -1510,7 +2652,11 @@ impl JsonRpcRequestProcessor {
                     }
                     item
                 })
-                .collect())
+                .collect();
+            Ok(RpcFilteredSignatures {
+                signatures,
+                resume_before: resume_before.map(|signature| signature.to_string()),
+            })
         } else {
             Err(RpcCustomError::TransactionHistoryNotAvailable.into())
         }
@@ -1590,31 +2736,11 @@ impl JsonRpcRequestProcessor {
             solana_sdk::account::from_account::<StakeHistory, _>(&stake_history_account)
                 .ok_or_else(Error::internal_error)?;
 
-        let StakeActivationStatus {
-            effective,
-            activating,
-            deactivating,
-        } = delegation.stake_activating_and_deactivating(epoch, Some(&stake_history));
-        let stake_activation_state = if deactivating > 0 {
-            StakeActivationState::Deactivating
-        } else if activating > 0 {
-            StakeActivationState::Activating
-        } else if effective > 0 {
-            StakeActivationState::Active
-        } else {
-            StakeActivationState::Inactive
-        };
-        let inactive_stake = match stake_activation_state {
-            StakeActivationState::Activating => activating,
-            StakeActivationState::Active => 0,
-            StakeActivationState::Deactivating => delegation.stake.saturating_sub(effective),
-            StakeActivationState::Inactive => delegation.stake,
-        };
-        Ok(RpcStakeActivation {
-            state: stake_activation_state,
-            active: effective,
-            inactive: inactive_stake,
-        })
+        Ok(get_delegation_activation(
+            &delegation,
+            epoch,
+            &stake_history,
+        ))
     }
 
     pub fn get_token_account_balance(
@@ -1627,14 +2753,15 @@ impl JsonRpcRequestProcessor {
             Error::invalid_params("Invalid param: could not find account".to_string())
         })?;
 
-        if account.owner() != &spl_token_id_v2_0() {
+        if !is_known_spl_token_id(account.owner()) {
             return Err(Error::invalid_params(
                 "Invalid param: not a v2.0 Token account".to_string(),
             ));
         }
-        let token_account = TokenAccount::unpack(account.data()).map_err(|_| {
-            Error::invalid_params("Invalid param: not a v2.0 Token account".to_string())
-        })?;
+        let token_account =
+            TokenAccount::unpack(strip_token_extensions(account.data())).map_err(|_| {
+                Error::invalid_params("Invalid param: not a v2.0 Token account".to_string())
+            })?;
         let mint = &Pubkey::from_str(&token_account.mint.to_string())
             .expect("Token account mint should be convertible to Pubkey");
         let (_, decimals) = get_mint_owner_and_decimals(&bank, mint)?;
@@ -1651,12 +2778,12 @@ impl JsonRpcRequestProcessor {
         let mint_account = bank.get_account(mint).ok_or_else(|| {
             Error::invalid_params("Invalid param: could not find account".to_string())
         })?;
-        if mint_account.owner() != &spl_token_id_v2_0() {
+        if !is_known_spl_token_id(mint_account.owner()) {
             return Err(Error::invalid_params(
                 "Invalid param: not a v2.0 Token mint".to_string(),
             ));
         }
-        let mint = Mint::unpack(mint_account.data()).map_err(|_| {
+        let mint = Mint::unpack(strip_token_extensions(mint_account.data())).map_err(|_| {
             Error::invalid_params("Invalid param: mint could not be unpacked".to_string())
         })?;
 
@@ -1671,16 +2798,16 @@ impl JsonRpcRequestProcessor {
     ) -> Result<RpcResponse<Vec<RpcTokenAccountBalance>>> {
         let bank = self.bank(commitment);
         let (mint_owner, decimals) = get_mint_owner_and_decimals(&bank, mint)?;
-        if mint_owner != spl_token_id_v2_0() {
+        if !is_known_spl_token_id(&mint_owner) {
             return Err(Error::invalid_params(
                 "Invalid param: not a v2.0 Token mint".to_string(),
             ));
         }
         let mut token_balances: Vec<RpcTokenAccountBalance> = self
-            .get_filtered_spl_token_accounts_by_mint(&bank, mint, vec![])?
+            .get_filtered_spl_token_accounts_by_mint(&bank, &mint_owner, mint, vec![])?
             .into_iter()
             .map(|(address, account)| {
-                let amount = TokenAccount::unpack(account.data())
+                let amount = TokenAccount::unpack(strip_token_extensions(account.data()))
                     .map(|account| account.amount)
                     .unwrap_or(0);
                 let amount = token_amount_to_ui_amount(amount, decimals);
@@ -1707,16 +2834,20 @@ impl JsonRpcRequestProcessor {
         owner: &Pubkey,
         token_account_filter: TokenAccountsFilter,
         config: Option<RpcAccountInfoConfig>,
+        page: ProgramAccountsPage,
+        non_zero_balance_only: bool,
     ) -> Result<RpcResponse<Vec<RpcKeyedAccount>>> {
         let config = config.unwrap_or_default();
         let bank = self.bank(config.commitment);
         let encoding = config.encoding.unwrap_or(UiAccountEncoding::Binary);
         let data_slice_config = config.data_slice;
+        check_no_compression_level(&config)?;
         check_slice_and_encoding(&encoding, data_slice_config.is_some())?;
-        let (_, mint) = get_token_program_id_and_mint(&bank, token_account_filter)?;
+        let (token_program_id, mints) =
+            get_token_program_id_and_mints(&bank, token_account_filter)?;
 
         let mut filters = vec![];
-        if let Some(mint) = mint {
+        if let [mint] = mints.as_slice() {
             // Optional filter on Mint address
             filters.push(RpcFilterType::Memcmp(Memcmp {
                 offset: 0,
@@ -1725,25 +2856,36 @@ impl JsonRpcRequestProcessor {
             }));
         }
 
-        let keyed_accounts =
-            self.get_filtered_spl_token_accounts_by_owner(&bank, owner, filters)?;
-        let accounts = if encoding == UiAccountEncoding::JsonParsed {
-            get_parsed_token_accounts(bank.clone(), keyed_accounts.into_iter()).collect()
-        } else {
-            keyed_accounts
-                .into_iter()
-                .map(|(pubkey, account)| RpcKeyedAccount {
-                    pubkey: pubkey.to_string(),
-                    account: UiAccount::encode(
-                        &pubkey,
-                        &account,
-                        encoding,
-                        None,
-                        data_slice_config,
-                    ),
-                })
-                .collect()
-        };
+        let mut keyed_accounts = self.get_filtered_spl_token_accounts_by_owner(
+            &bank,
+            &token_program_id,
+            owner,
+            filters,
+        )?;
+        if mints.len() > 1 || non_zero_balance_only {
+            retain_token_accounts(&mut keyed_accounts, &mints, non_zero_balance_only);
+        }
+        let keyed_accounts = ScannedAccounts::new(keyed_accounts);
+        let keyed_accounts = page.select(&keyed_accounts);
+        let accounts = self.trace(TracePhase::Serialize, || {
+            if encoding == UiAccountEncoding::JsonParsed {
+                get_parsed_token_accounts(bank.clone(), keyed_accounts.into_iter()).collect()
+            } else {
+                keyed_accounts
+                    .into_iter()
+                    .map(|(pubkey, account)| RpcKeyedAccount {
+                        pubkey: pubkey.to_string(),
+                        account: UiAccount::encode(
+                            pubkey,
+                            account,
+                            encoding,
+                            None,
+                            data_slice_config,
+                        ),
+                    })
+                    .collect()
+            }
+        });
         Ok(new_response(&bank, accounts))
     }
 
@@ -1757,8 +2899,10 @@ impl JsonRpcRequestProcessor {
         let bank = self.bank(config.commitment);
         let encoding = config.encoding.unwrap_or(UiAccountEncoding::Binary);
         let data_slice_config = config.data_slice;
+        check_no_compression_level(&config)?;
         check_slice_and_encoding(&encoding, data_slice_config.is_some())?;
-        let (token_program_id, mint) = get_token_program_id_and_mint(&bank, token_account_filter)?;
+        let (token_program_id, mints) =
+            get_token_program_id_and_mints(&bank, token_account_filter)?;
 
         let mut filters = vec![
             // Filter on Delegate is_some()
@@ -1775,32 +2919,70 @@ impl JsonRpcRequestProcessor {
             }),
         ];
         // Optional filter on Mint address, uses mint account index for scan
-        let keyed_accounts = if let Some(mint) = mint {
-            self.get_filtered_spl_token_accounts_by_mint(&bank, &mint, filters)?
+        let keyed_accounts = if let [mint] = mints.as_slice() {
+            self.get_filtered_spl_token_accounts_by_mint(&bank, &token_program_id, mint, filters)?
         } else {
             // Filter on Token Account state
-            filters.push(RpcFilterType::DataSize(
-                TokenAccount::get_packed_len() as u64
-            ));
-            self.get_filtered_program_accounts(&bank, &token_program_id, filters)?
-        };
-        let accounts = if encoding == UiAccountEncoding::JsonParsed {
-            get_parsed_token_accounts(bank.clone(), keyed_accounts.into_iter()).collect()
-        } else {
+            if token_program_id == spl_token_id_v2_0() {
+                filters.push(RpcFilterType::DataSize(
+                    TokenAccount::get_packed_len() as u64
+                ));
+            }
+            let mut keyed_accounts =
+                self.get_filtered_program_accounts(&bank, &token_program_id, filters)?;
+            keyed_accounts
+                .retain(|(_, account)| is_spl_token_account(&token_program_id, account.data()));
+            if mints.len() > 1 {
+                retain_token_accounts(&mut keyed_accounts, &mints, false);
+            }
             keyed_accounts
-                .into_iter()
-                .map(|(pubkey, account)| RpcKeyedAccount {
-                    pubkey: pubkey.to_string(),
-                    account: UiAccount::encode(
-                        &pubkey,
-                        &account,
-                        encoding,
-                        None,
-                        data_slice_config,
-                    ),
-                })
-                .collect()
         };
+        let accounts = self.trace(TracePhase::Serialize, || {
+            if encoding == UiAccountEncoding::JsonParsed {
+                get_parsed_token_accounts(bank.clone(), keyed_accounts.into_iter()).collect()
+            } else {
+                keyed_accounts
+                    .into_iter()
+                    .map(|(pubkey, account)| RpcKeyedAccount {
+                        pubkey: pubkey.to_string(),
+                        account: UiAccount::encode(
+                            &pubkey,
+                            &account,
+                            encoding,
+                            None,
+                            data_slice_config,
+                        ),
+                    })
+                    .collect()
+            }
+        });
+        Ok(new_response(&bank, accounts))
+    }
+
+    pub fn get_stake_accounts_by_authority(
+        &self,
+        authority: &Pubkey,
+        stake_authorize: StakeAuthorize,
+        config: Option<RpcAccountInfoConfig>,
+    ) -> Result<RpcResponse<Vec<RpcKeyedAccount>>> {
+        let config = config.unwrap_or_default();
+        let bank = self.bank(config.commitment);
+        let encoding = config.encoding.unwrap_or(UiAccountEncoding::Binary);
+        let data_slice_config = config.data_slice;
+        check_no_compression_level(&config)?;
+        check_slice_and_encoding(&encoding, data_slice_config.is_some())?;
+
+        let keyed_accounts =
+            self.get_filtered_stake_accounts_by_authority(&bank, authority, stake_authorize)?;
+        let accounts = self.trace(TracePhase::Serialize, || {
+            encode_program_accounts(
+                &bank,
+                &stake::program::id(),
+                keyed_accounts,
+                encoding,
+                data_slice_config,
+            )
+        })?;
         Ok(new_response(&bank, accounts))
     }
 
@@ -1828,31 +3010,41 @@ impl JsonRpcRequestProcessor {
                     index_key: program_id.to_string(),
                 });
             }
-            Ok(bank
-                .get_filtered_indexed_accounts(&IndexKey::ProgramId(*program_id), |account| {
-                    // The program-id account index checks for Account owner on inclusion. However, due
-                    // to the current AccountsDb implementation, an account may remain in storage as a
-                    // zero-lamport AccountSharedData::Default() after being wiped and reinitialized in later
-                    // updates. We include the redundant filters here to avoid returning these
-                    // accounts.
-                    account.owner() == program_id && filter_closure(account)
+            Ok(self
+                .trace(TracePhase::Scan, || {
+                    bank.get_filtered_indexed_accounts(
+                        &IndexKey::ProgramId(*program_id),
+                        |account| {
+                            // The program-id account index checks for Account owner on
+                            // inclusion. However, due to the current AccountsDb implementation,
+                            // an account may remain in storage as a zero-lamport
+                            // AccountSharedData::Default() after being wiped and reinitialized in
+                            // later updates. We include the redundant filters here to avoid
+                            // returning these accounts.
+                            account.owner() == program_id && filter_closure(account)
+                        },
+                    )
                 })
                 .map_err(|e| RpcCustomError::ScanError {
                     message: e.to_string(),
                 })?)
         } else {
-            Ok(bank
-                .get_filtered_program_accounts(program_id, filter_closure)
+            Ok(self
+                .trace(TracePhase::Scan, || {
+                    bank.get_filtered_program_accounts(program_id, filter_closure)
+                })
                 .map_err(|e| RpcCustomError::ScanError {
                     message: e.to_string(),
                 })?)
         }
     }
 
-    /// Get an iterator of spl-token accounts by owner address
+    /// Get an iterator of spl-token accounts of `program_id`, either token program, by owner
+    /// address
     fn get_filtered_spl_token_accounts_by_owner(
         &self,
         bank: &Arc<Bank>,
+        program_id: &Pubkey,
         owner_key: &Pubkey,
         mut filters: Vec<RpcFilterType>,
     ) -> RpcCustomResult<Vec<(Pubkey, AccountSharedData)>> {
@@ -1861,10 +3053,13 @@ impl JsonRpcRequestProcessor {
         // in storage as a zero-lamport AccountSharedData::Default() after being wiped and reinitialized in
         // later updates. We include the redundant filters here to avoid returning these accounts.
         //
-        // Filter on Token Account state
-        filters.push(RpcFilterType::DataSize(
-            TokenAccount::get_packed_len() as u64
-        ));
+        // Filter on Token Account state; Token-2022 accounts may be longer, and are checked
+        // by `is_spl_token_account()` instead
+        if program_id == &spl_token_id_v2_0() {
+            filters.push(RpcFilterType::DataSize(
+                TokenAccount::get_packed_len() as u64
+            ));
+        }
         // Filter on Owner address
         filters.push(RpcFilterType::Memcmp(Memcmp {
             offset: SPL_TOKEN_ACCOUNT_OWNER_OFFSET,
@@ -1882,26 +3077,94 @@ impl JsonRpcRequestProcessor {
                     index_key: owner_key.to_string(),
                 });
             }
-            Ok(bank
-                .get_filtered_indexed_accounts(&IndexKey::SplTokenOwner(*owner_key), |account| {
-                    account.owner() == &spl_token_id_v2_0()
-                        && filters.iter().all(|filter_type| match filter_type {
-                            RpcFilterType::DataSize(size) => account.data().len() as u64 == *size,
-                            RpcFilterType::Memcmp(compare) => compare.bytes_match(account.data()),
-                        })
+            Ok(self
+                .trace(TracePhase::Scan, || {
+                    bank.get_filtered_indexed_accounts(
+                        &IndexKey::SplTokenOwner(*owner_key),
+                        |account| {
+                            account.owner() == program_id
+                                && is_spl_token_account(program_id, account.data())
+                                && filters.iter().all(|filter_type| match filter_type {
+                                    RpcFilterType::DataSize(size) => {
+                                        account.data().len() as u64 == *size
+                                    }
+                                    RpcFilterType::Memcmp(compare) => {
+                                        compare.bytes_match(account.data())
+                                    }
+                                })
+                        },
+                    )
+                })
+                .map_err(|e| RpcCustomError::ScanError {
+                    message: e.to_string(),
+                })?)
+        } else {
+            let mut keyed_accounts =
+                self.get_filtered_program_accounts(bank, program_id, filters)?;
+            keyed_accounts.retain(|(_, account)| is_spl_token_account(program_id, account.data()));
+            Ok(keyed_accounts)
+        }
+    }
+
+    /// Get stake accounts by authorized staker or withdrawer
+    fn get_filtered_stake_accounts_by_authority(
+        &self,
+        bank: &Arc<Bank>,
+        authority: &Pubkey,
+        stake_authorize: StakeAuthorize,
+    ) -> RpcCustomResult<Vec<(Pubkey, AccountSharedData)>> {
+        let (account_index, index_key, offset) = match stake_authorize {
+            StakeAuthorize::Staker => (
+                AccountIndex::StakeStaker,
+                IndexKey::StakeStaker(*authority),
+                STAKE_ACCOUNT_STAKER_OFFSET,
+            ),
+            StakeAuthorize::Withdrawer => (
+                AccountIndex::StakeWithdrawer,
+                IndexKey::StakeWithdrawer(*authority),
+                STAKE_ACCOUNT_WITHDRAWER_OFFSET,
+            ),
+        };
+        // Filter on the authority address
+        let authority_filter = Memcmp {
+            offset,
+            bytes: MemcmpEncodedBytes::Bytes(authority.to_bytes().into()),
+            encoding: None,
+        };
+
+        if self.config.account_indexes.contains(&account_index) {
+            if !self.config.account_indexes.include_key(authority) {
+                return Err(RpcCustomError::KeyExcludedFromSecondaryIndex {
+                    index_key: authority.to_string(),
+                });
+            }
+            // As with the spl-token indexes, a wiped account may linger in the index, so the
+            // owner and authority are checked again
+            Ok(self
+                .trace(TracePhase::Scan, || {
+                    bank.get_filtered_indexed_accounts(&index_key, |account| {
+                        account.owner() == &stake::program::id()
+                            && authority_filter.bytes_match(account.data())
+                    })
                 })
                 .map_err(|e| RpcCustomError::ScanError {
                     message: e.to_string(),
                 })?)
         } else {
-            self.get_filtered_program_accounts(bank, &spl_token_id_v2_0(), filters)
+            self.get_filtered_program_accounts(
+                bank,
+                &stake::program::id(),
+                vec![RpcFilterType::Memcmp(authority_filter)],
+            )
         }
     }
 
-    /// Get an iterator of spl-token accounts by mint address
+    /// Get an iterator of spl-token accounts of `program_id`, either token program, by mint
+    /// address
     fn get_filtered_spl_token_accounts_by_mint(
         &self,
         bank: &Arc<Bank>,
+        program_id: &Pubkey,
         mint_key: &Pubkey,
         mut filters: Vec<RpcFilterType>,
     ) -> RpcCustomResult<Vec<(Pubkey, AccountSharedData)>> {
@@ -1910,10 +3173,13 @@ impl JsonRpcRequestProcessor {
         // as be zero-lamport AccountSharedData::Default() after being wiped and reinitialized in later
         // updates. We include the redundant filters here to avoid returning these accounts.
         //
-        // Filter on Token Account state
-        filters.push(RpcFilterType::DataSize(
-            TokenAccount::get_packed_len() as u64
-        ));
+        // Filter on Token Account state; Token-2022 accounts may be longer, and are checked
+        // by `is_spl_token_account()` instead
+        if program_id == &spl_token_id_v2_0() {
+            filters.push(RpcFilterType::DataSize(
+                TokenAccount::get_packed_len() as u64
+            ));
+        }
         // Filter on Mint address
         filters.push(RpcFilterType::Memcmp(Memcmp {
             offset: SPL_TOKEN_ACCOUNT_MINT_OFFSET,
@@ -1930,19 +3196,32 @@ impl JsonRpcRequestProcessor {
                     index_key: mint_key.to_string(),
                 });
             }
-            Ok(bank
-                .get_filtered_indexed_accounts(&IndexKey::SplTokenMint(*mint_key), |account| {
-                    account.owner() == &spl_token_id_v2_0()
-                        && filters.iter().all(|filter_type| match filter_type {
-                            RpcFilterType::DataSize(size) => account.data().len() as u64 == *size,
-                            RpcFilterType::Memcmp(compare) => compare.bytes_match(account.data()),
-                        })
+            Ok(self
+                .trace(TracePhase::Scan, || {
+                    bank.get_filtered_indexed_accounts(
+                        &IndexKey::SplTokenMint(*mint_key),
+                        |account| {
+                            account.owner() == program_id
+                                && is_spl_token_account(program_id, account.data())
+                                && filters.iter().all(|filter_type| match filter_type {
+                                    RpcFilterType::DataSize(size) => {
+                                        account.data().len() as u64 == *size
+                                    }
+                                    RpcFilterType::Memcmp(compare) => {
+                                        compare.bytes_match(account.data())
+                                    }
+                                })
+                        },
+                    )
                 })
                 .map_err(|e| RpcCustomError::ScanError {
                     message: e.to_string(),
                 })?)
         } else {
-            self.get_filtered_program_accounts(bank, &spl_token_id_v2_0(), filters)
+            let mut keyed_accounts =
+                self.get_filtered_program_accounts(bank, program_id, filters)?;
+            keyed_accounts.retain(|(_, account)| is_spl_token_account(program_id, account.data()));
+            Ok(keyed_accounts)
         }
     }
 
@@ -1985,20 +3264,280 @@ impl JsonRpcRequestProcessor {
     }
 }
 
-fn optimize_filters(filters: &mut Vec<RpcFilterType>) {
+/// Picks the reward of `vote_pubkey` and the page of rewards of the stake accounts in
+/// `stake_pubkeys` that follows `after_pubkey`, ordered by stake pubkey
+fn vote_account_rewards(
+    rewards: Rewards,
+    vote_pubkey: &str,
+    stake_pubkeys: &HashSet<String>,
+    after_pubkey: Option<Pubkey>,
+    limit: usize,
+) -> Result<(Option<Reward>, Vec<RpcStakeInflationReward>)> {
+    let mut vote_reward = None;
+    let mut stake_rewards = vec![];
+    for reward in rewards {
+        match reward.reward_type {
+            Some(RewardType::Voting) if reward.pubkey == vote_pubkey => {
+                vote_reward = Some(reward);
+            }
+            Some(RewardType::Staking) if stake_pubkeys.contains(&reward.pubkey) => {
+                stake_rewards.push((verify_pubkey(&reward.pubkey)?, reward));
+            }
+            _ => {}
+        }
+    }
+    stake_rewards.sort_unstable_by_key(|(stake_pubkey, _)| *stake_pubkey);
+    let stake_rewards = stake_rewards
+        .into_iter()
+        .filter(|(stake_pubkey, _)| Some(*stake_pubkey) > after_pubkey)
+        .take(limit)
+        .map(|(stake_pubkey, reward)| RpcStakeInflationReward {
+            stake_pubkey: stake_pubkey.to_string(),
+            amount: reward.lamports.abs() as u64,
+            post_balance: reward.post_balance,
+        })
+        .collect();
+    Ok((vote_reward, stake_rewards))
+}
+
+pub(crate) fn get_delegation_activation(
+    delegation: &Delegation,
+    epoch: Epoch,
+    stake_history: &StakeHistory,
+) -> RpcStakeActivation {
+    let StakeActivationStatus {
+        effective,
+        activating,
+        deactivating,
+    } = delegation.stake_activating_and_deactivating(epoch, Some(stake_history));
+    let stake_activation_state = if deactivating > 0 {
+        StakeActivationState::Deactivating
+    } else if activating > 0 {
+        StakeActivationState::Activating
+    } else if effective > 0 {
+        StakeActivationState::Active
+    } else {
+        StakeActivationState::Inactive
+    };
+    let inactive_stake = match stake_activation_state {
+        StakeActivationState::Activating => activating,
+        StakeActivationState::Active => 0,
+        StakeActivationState::Deactivating => delegation.stake.saturating_sub(effective),
+        StakeActivationState::Inactive => delegation.stake,
+    };
+    RpcStakeActivation {
+        state: stake_activation_state,
+        active: effective,
+        inactive: inactive_stake,
+    }
+}
+
+/// A page of `getProgramAccounts` results: the first `limit` accounts in `order_by`
+/// order, after `after_pubkey`, which requires pubkey order. With only `limit` or
+/// `after_pubkey`, the accounts are ordered by pubkey. Without any of them, all accounts
+/// are returned in no particular order.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ProgramAccountsPage {
+    pub limit: Option<usize>,
+    pub after_pubkey: Option<Pubkey>,
+    pub order_by: Option<RpcProgramAccountsOrder>,
+}
+
+impl ProgramAccountsPage {
+    /// Validates the paging parameters of a request. A request that pages or orders the
+    /// accounts returns at most `MAX_GET_PROGRAM_ACCOUNTS_LIMIT` of them.
+    fn new(
+        limit: Option<usize>,
+        after_pubkey: Option<String>,
+        order_by: Option<RpcProgramAccountsOrder>,
+    ) -> Result<Self> {
+        let after_pubkey = after_pubkey
+            .map(|after_pubkey| verify_pubkey(&after_pubkey))
+            .transpose()?;
+        if after_pubkey.is_some()
+            && !matches!(order_by, None | Some(RpcProgramAccountsOrder::Pubkey))
+        {
+            return Err(Error::invalid_params(
+                "afterPubkey requires the accounts to be ordered by pubkey",
+            ));
+        }
+        let limit = match limit {
+            Some(limit) if limit == 0 || limit > MAX_GET_PROGRAM_ACCOUNTS_LIMIT => {
+                return Err(Error::invalid_params(format!(
+                    "Invalid limit; max {}",
+                    MAX_GET_PROGRAM_ACCOUNTS_LIMIT
+                )));
+            }
+            Some(limit) => Some(limit),
+            None if after_pubkey.is_some() || order_by.is_some() => {
+                Some(MAX_GET_PROGRAM_ACCOUNTS_LIMIT)
+            }
+            None => None,
+        };
+        Ok(Self {
+            limit,
+            after_pubkey,
+            order_by,
+        })
+    }
+
+    /// The indexes in `scanned` of the accounts of the page, in order
+    fn select_indexes(&self, scanned: &ScannedAccounts) -> Vec<usize> {
+        let keyed_accounts = scanned.keyed_accounts();
+        if self.limit.is_none() && self.after_pubkey.is_none() && self.order_by.is_none() {
+            return (0..keyed_accounts.len()).collect();
+        }
+        let sorted = scanned.sorted(self.order_by.unwrap_or(RpcProgramAccountsOrder::Pubkey));
+        let start = self.after_pubkey.map_or(0, |after_pubkey| {
+            sorted.partition_point(|i| keyed_accounts[*i].0 <= after_pubkey)
+        });
+        sorted[start..]
+            .iter()
+            .take(self.limit.unwrap_or(usize::MAX))
+            .copied()
+            .collect()
+    }
+
+    fn select<'a>(&self, scanned: &'a ScannedAccounts) -> Vec<&'a (Pubkey, AccountSharedData)> {
+        let keyed_accounts = scanned.keyed_accounts();
+        self.select_indexes(scanned)
+            .into_iter()
+            .map(|i| &keyed_accounts[i])
+            .collect()
+    }
+}
+
+/// A validated `getProgramAccounts` request configuration
+#[derive(Debug, Default)]
+pub(crate) struct ProgramAccountsQuery {
+    pub(crate) account_config: Option<RpcAccountInfoConfig>,
+    pub(crate) filters: Vec<RpcFilterType>,
+    pub(crate) with_context: bool,
+    pub(crate) page: ProgramAccountsPage,
+}
+
+/// Validates the configuration of a `getProgramAccounts` request
+pub(crate) fn verify_program_accounts_config(
+    config: Option<RpcGetProgramAccountsConfig>,
+) -> Result<ProgramAccountsQuery> {
+    let config = match config {
+        Some(config) => config,
+        None => return Ok(ProgramAccountsQuery::default()),
+    };
+    let RpcGetProgramAccountsConfig {
+        program_accounts_config,
+        limit,
+        after_pubkey,
+        order_by,
+    } = config;
+    let filters = verify_program_accounts_filters(program_accounts_config.filters)?;
+    let page = ProgramAccountsPage::new(limit, after_pubkey, order_by)?;
+    Ok(ProgramAccountsQuery {
+        account_config: Some(program_accounts_config.account_config),
+        filters,
+        with_context: program_accounts_config.with_context.unwrap_or_default(),
+        page,
+    })
+}
+
+fn verify_program_accounts_filters(
+    filters: Option<Vec<RpcFilterType>>,
+) -> Result<Vec<RpcFilterType>> {
+    let filters = filters.unwrap_or_default();
+    if filters.len() > MAX_GET_PROGRAM_ACCOUNT_FILTERS {
+        return Err(Error::invalid_params(format!(
+            "Too many filters provided; max {}",
+            MAX_GET_PROGRAM_ACCOUNT_FILTERS
+        )));
+    }
+    for filter in &filters {
+        verify_filter(filter)?;
+    }
+    Ok(filters)
+}
+
+/// Encodes the accounts of `program_id` as returned by `getProgramAccounts`
+pub(crate) fn encode_program_accounts<K: Borrow<(Pubkey, AccountSharedData)>>(
+    bank: &Arc<Bank>,
+    program_id: &Pubkey,
+    keyed_accounts: impl IntoIterator<Item = K>,
+    encoding: UiAccountEncoding,
+    data_slice_config: Option<UiDataSliceConfig>,
+) -> Result<Vec<RpcKeyedAccount>> {
+    if is_known_spl_token_id(program_id) && encoding == UiAccountEncoding::JsonParsed {
+        Ok(get_parsed_token_accounts(bank.clone(), keyed_accounts.into_iter()).collect())
+    } else {
+        keyed_accounts
+            .into_iter()
+            .map(|keyed_account| {
+                let (pubkey, account): &(Pubkey, AccountSharedData) = keyed_account.borrow();
+                Ok(RpcKeyedAccount {
+                    pubkey: pubkey.to_string(),
+                    account: encode_account(account, pubkey, encoding, data_slice_config)?,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Simulates `transaction` before it is sent, returning the compute units it consumed
+fn run_preflight(bank: &Bank, transaction: SanitizedTransaction) -> Result<u64> {
+    let TransactionSimulationResult {
+        result,
+        logs,
+        post_simulation_accounts: _,
+        units_consumed,
+        inner_instructions: _,
+    } = bank.simulate_transaction(transaction, false);
+    if let Err(err) = result {
+        match err {
+            TransactionError::BlockhashNotFound => {
+                inc_new_counter_info!("rpc-send-tx_err-blockhash-not-found", 1);
+            }
+            _ => {
+                inc_new_counter_info!("rpc-send-tx_err-other", 1);
+            }
+        }
+        return Err(RpcCustomError::SendTransactionPreflightFailure {
+            message: format!("Transaction simulation failed: {}", err),
+            result: RpcSimulateTransactionResult {
+                err: Some(err),
+                logs: Some(logs),
+                accounts: None,
+                units_consumed: Some(units_consumed),
+                inner_instructions: None,
+            },
+        }
+        .into());
+    }
+    Ok(units_consumed)
+}
+
+fn encode_inner_instructions(
+    inner_instructions: Option<InnerInstructionsList>,
+) -> Vec<UiInnerInstructions> {
+    inner_instructions
+        .unwrap_or_default()
+        .into_iter()
+        .enumerate()
+        .map(|(index, instructions)| InnerInstructions {
+            index: index as u8,
+            instructions,
+        })
+        .filter(|i| !i.instructions.is_empty())
+        .map(UiInnerInstructions::from)
+        .collect()
+}
+
+pub(crate) fn optimize_filters(filters: &mut Vec<RpcFilterType>) {
     filters.iter_mut().for_each(|filter_type| {
         if let RpcFilterType::Memcmp(compare) = filter_type {
-            use MemcmpEncodedBytes::*;
-            match &compare.bytes {
-                #[allow(deprecated)]
-                Binary(bytes) | Base58(bytes) => {
-                    compare.bytes = Bytes(bs58::decode(bytes).into_vec().unwrap());
-                }
-                Base64(bytes) => {
-                    compare.bytes = Bytes(base64::decode(bytes).unwrap());
-                }
-                _ => {}
+            if let MemcmpEncodedBytes::Bytes(_) = compare.bytes {
+                return;
             }
+            let bytes = compare.bytes().expect("filters are verified").into_owned();
+            compare.bytes = MemcmpEncodedBytes::Bytes(bytes);
+            compare.encoding = None;
         }
     })
 }
@@ -2059,6 +3598,13 @@ fn verify_token_account_filter(
             let program_id = verify_pubkey(&program_id_str)?;
             Ok(TokenAccountsFilter::ProgramId(program_id))
         }
+        RpcTokenAccountsFilter::Mints(mint_strs) => {
+            let mints = mint_strs
+                .iter()
+                .map(|mint_str| verify_pubkey(mint_str))
+                .collect::<Result<_>>()?;
+            Ok(TokenAccountsFilter::Mints(mints))
+        }
     }
 }
 
@@ -2084,6 +3630,95 @@ fn verify_and_parse_signatures_for_address_params(
     Ok((address, before, until, limit))
 }
 
+fn verify_signatures_for_address_filter(
+    config: &RpcSignaturesForAddressConfig,
+) -> Result<AddressSignaturesFilter> {
+    let failed_only = config.failed_only.unwrap_or_default();
+    let succeeded_only = config.succeeded_only.unwrap_or_default();
+    if failed_only && succeeded_only {
+        return Err(Error::invalid_params(
+            "failedOnly and succeededOnly cannot both be set",
+        ));
+    }
+    if let (Some(min_slot), Some(max_slot)) = (config.min_slot, config.max_slot) {
+        if min_slot > max_slot {
+            return Err(Error::invalid_params(format!(
+                "Invalid slot range; minSlot {} is greater than maxSlot {}",
+                min_slot, max_slot
+            )));
+        }
+    }
+    Ok(AddressSignaturesFilter {
+        failed_only,
+        succeeded_only,
+        program_id: config
+            .program_id
+            .as_deref()
+            .map(verify_pubkey)
+            .transpose()?,
+        min_slot: config.min_slot,
+        max_slot: config.max_slot,
+    })
+}
+
+/// Long-term storage can't filter its address index, so its signatures are fetched a page at a time
+/// and filtered here until `limit` of them match, the history runs out, or
+/// `MAX_GET_SIGNATURES_FOR_ADDRESS_EXAMINED` of them were examined. Matching a program loads
+/// the block of each candidate transaction. Returns the matching signatures, and the last one
+/// examined if the search stopped early.
+async fn get_filtered_bigtable_signatures_for_address(
+    bigtable_ledger_storage: &dyn LedgerArchive,
+    address: &Pubkey,
+    mut before: Option<Signature>,
+    until: Option<Signature>,
+    limit: usize,
+    filter: &AddressSignaturesFilter,
+) -> solana_storage_bigtable::Result<(
+    Vec<ConfirmedTransactionStatusWithSignature>,
+    Option<Signature>,
+)> {
+    let mut results = vec![];
+    let mut examined = 0;
+    let mut last_examined = None;
+    loop {
+        let page = bigtable_ledger_storage
+            .get_confirmed_signatures_for_address(address, before.as_ref(), until.as_ref(), limit)
+            .await?;
+        let exhausted = page.len() < limit;
+        before = page.last().map(|(status, _)| status.signature);
+        for (status, _) in page {
+            if examined == MAX_GET_SIGNATURES_FOR_ADDRESS_EXAMINED {
+                return Ok((results, last_examined));
+            }
+            examined += 1;
+            last_examined = Some(status.signature);
+            if status.slot < filter.min_slot.unwrap_or_default() {
+                return Ok((results, None));
+            }
+            if !filter.matches_slot(status.slot) || !filter.matches_status(&status.err) {
+                continue;
+            }
+            if let Some(program_id) = &filter.program_id {
+                let invokes_program = bigtable_ledger_storage
+                    .get_confirmed_transaction(&status.signature)
+                    .await?
+                    .map(|confirmed| confirmed.transaction.invokes_program(program_id))
+                    .unwrap_or(false);
+                if !invokes_program {
+                    continue;
+                }
+            }
+            results.push(status);
+            if results.len() == limit {
+                return Ok((results, None));
+            }
+        }
+        if exhausted {
+            return Ok((results, None));
+        }
+    }
+}
+
 fn check_is_at_least_confirmed(commitment: CommitmentConfig) -> Result<()> {
     if !commitment.is_at_least_confirmed() {
         return Err(Error::invalid_params(
@@ -2093,6 +3728,17 @@ fn check_is_at_least_confirmed(commitment: CommitmentConfig) -> Result<()> {
     Ok(())
 }
 
+/// Refuses the compression level of account configs outside of subscriptions, which always
+/// compress at the default level
+fn check_no_compression_level(config: &RpcAccountInfoConfig) -> Result<()> {
+    if config.compression_level.is_some() {
+        return Err(Error::invalid_params(
+            "compressionLevel is only supported by subscriptions",
+        ));
+    }
+    Ok(())
+}
+
 fn check_slice_and_encoding(encoding: &UiAccountEncoding, data_slice_is_some: bool) -> Result<()> {
     match encoding {
         UiAccountEncoding::JsonParsed => {
@@ -2122,18 +3768,22 @@ fn get_encoded_account(
     encoding: UiAccountEncoding,
     data_slice: Option<UiDataSliceConfig>,
 ) -> Result<Option<UiAccount>> {
-    match bank.get_account(pubkey) {
-        Some(account) => {
-            let response = if account.owner() == &spl_token_id_v2_0()
-                && encoding == UiAccountEncoding::JsonParsed
-            {
-                get_parsed_token_account(bank.clone(), pubkey, account)
-            } else {
-                encode_account(&account, pubkey, encoding, data_slice)?
-            };
-            Ok(Some(response))
-        }
-        None => Ok(None),
+    bank.get_account(pubkey)
+        .map(|account| encode_bank_account(bank, pubkey, account, encoding, data_slice))
+        .transpose()
+}
+
+fn encode_bank_account(
+    bank: &Arc<Bank>,
+    pubkey: &Pubkey,
+    account: AccountSharedData,
+    encoding: UiAccountEncoding,
+    data_slice: Option<UiDataSliceConfig>,
+) -> Result<UiAccount> {
+    if is_known_spl_token_id(account.owner()) && encoding == UiAccountEncoding::JsonParsed {
+        Ok(get_parsed_token_account(bank.clone(), pubkey, account))
+    } else {
+        encode_account(&account, pubkey, encoding, data_slice)
     }
 }
 
@@ -2164,15 +3814,23 @@ fn encode_account<T: ReadableAccount>(
 /// NOTE: `optimize_filters()` should almost always be called before using this method because of
 /// the strict match on `MemcmpEncodedBytes::Bytes`.
 fn get_spl_token_owner_filter(program_id: &Pubkey, filters: &[RpcFilterType]) -> Option<Pubkey> {
-    if program_id != &spl_token_id_v2_0() {
+    if !is_known_spl_token_id(program_id) {
         return None;
     }
     let mut data_size_filter: Option<u64> = None;
+    let mut account_type_filter = false;
     let mut owner_key: Option<Pubkey> = None;
     let mut incorrect_owner_len: Option<usize> = None;
     for filter in filters {
         match filter {
             RpcFilterType::DataSize(size) => data_size_filter = Some(*size),
+            RpcFilterType::Memcmp(Memcmp {
+                offset: inline_spl_token_2022::ACCOUNT_TYPE_OFFSET,
+                bytes: MemcmpEncodedBytes::Bytes(bytes),
+                ..
+            }) if program_id == &inline_spl_token_2022::id() => {
+                account_type_filter = bytes == &[inline_spl_token_2022::ACCOUNTTYPE_ACCOUNT];
+            }
             RpcFilterType::Memcmp(Memcmp {
                 offset: SPL_TOKEN_ACCOUNT_OWNER_OFFSET,
                 bytes: MemcmpEncodedBytes::Bytes(bytes),
@@ -2187,7 +3845,7 @@ fn get_spl_token_owner_filter(program_id: &Pubkey, filters: &[RpcFilterType]) ->
             _ => {}
         }
     }
-    if data_size_filter == Some(TokenAccount::get_packed_len() as u64) {
+    if data_size_filter == Some(TokenAccount::get_packed_len() as u64) || account_type_filter {
         if let Some(incorrect_owner_len) = incorrect_owner_len {
             info!(
                 "Incorrect num bytes ({:?}) provided for spl_token_owner_filter",
@@ -2206,15 +3864,23 @@ fn get_spl_token_owner_filter(program_id: &Pubkey, filters: &[RpcFilterType]) ->
 /// NOTE: `optimize_filters()` should almost always be called before using this method because of
 /// the strict match on `MemcmpEncodedBytes::Bytes`.
 fn get_spl_token_mint_filter(program_id: &Pubkey, filters: &[RpcFilterType]) -> Option<Pubkey> {
-    if program_id != &spl_token_id_v2_0() {
+    if !is_known_spl_token_id(program_id) {
         return None;
     }
     let mut data_size_filter: Option<u64> = None;
+    let mut account_type_filter = false;
     let mut mint: Option<Pubkey> = None;
     let mut incorrect_mint_len: Option<usize> = None;
     for filter in filters {
         match filter {
             RpcFilterType::DataSize(size) => data_size_filter = Some(*size),
+            RpcFilterType::Memcmp(Memcmp {
+                offset: inline_spl_token_2022::ACCOUNT_TYPE_OFFSET,
+                bytes: MemcmpEncodedBytes::Bytes(bytes),
+                ..
+            }) if program_id == &inline_spl_token_2022::id() => {
+                account_type_filter = bytes == &[inline_spl_token_2022::ACCOUNTTYPE_ACCOUNT];
+            }
             RpcFilterType::Memcmp(Memcmp {
                 offset: SPL_TOKEN_ACCOUNT_MINT_OFFSET,
                 bytes: MemcmpEncodedBytes::Bytes(bytes),
@@ -2229,7 +3895,7 @@ fn get_spl_token_mint_filter(program_id: &Pubkey, filters: &[RpcFilterType]) ->
             _ => {}
         }
     }
-    if data_size_filter == Some(TokenAccount::get_packed_len() as u64) {
+    if data_size_filter == Some(TokenAccount::get_packed_len() as u64) || account_type_filter {
         if let Some(incorrect_mint_len) = incorrect_mint_len {
             info!(
                 "Incorrect num bytes ({:?}) provided for spl_token_mint_filter",
@@ -2245,32 +3911,79 @@ fn get_spl_token_mint_filter(program_id: &Pubkey, filters: &[RpcFilterType]) ->
 
 /// Analyze a passed Pubkey that may be a Token program id or Mint address to determine the program
 /// id and optional Mint
-fn get_token_program_id_and_mint(
+/// Returns the token program and the mints the accounts are limited to; no mints means
+/// accounts of any mint
+fn get_token_program_id_and_mints(
     bank: &Arc<Bank>,
     token_account_filter: TokenAccountsFilter,
-) -> Result<(Pubkey, Option<Pubkey>)> {
+) -> Result<(Pubkey, Vec<Pubkey>)> {
     match token_account_filter {
         TokenAccountsFilter::Mint(mint) => {
             let (mint_owner, _) = get_mint_owner_and_decimals(bank, &mint)?;
-            if mint_owner != spl_token_id_v2_0() {
+            if !is_known_spl_token_id(&mint_owner) {
                 return Err(Error::invalid_params(
                     "Invalid param: not a v2.0 Token mint".to_string(),
                 ));
             }
-            Ok((mint_owner, Some(mint)))
+            Ok((mint_owner, vec![mint]))
         }
         TokenAccountsFilter::ProgramId(program_id) => {
-            if program_id == spl_token_id_v2_0() {
-                Ok((program_id, None))
+            if is_known_spl_token_id(&program_id) {
+                Ok((program_id, vec![]))
             } else {
                 Err(Error::invalid_params(
                     "Invalid param: unrecognized Token program id".to_string(),
                 ))
             }
         }
+        TokenAccountsFilter::Mints(mints) => {
+            if mints.is_empty() || mints.len() > MAX_TOKEN_ACCOUNTS_FILTER_MINTS {
+                return Err(Error::invalid_params(format!(
+                    "Invalid param: between 1 and {} mints must be provided",
+                    MAX_TOKEN_ACCOUNTS_FILTER_MINTS
+                )));
+            }
+            let mut token_program_id = None;
+            for mint in &mints {
+                let (mint_owner, _) = get_mint_owner_and_decimals(bank, mint)?;
+                if !is_known_spl_token_id(&mint_owner) {
+                    return Err(Error::invalid_params(format!(
+                        "Invalid param: {} is not a v2.0 Token mint",
+                        mint
+                    )));
+                }
+                if *token_program_id.get_or_insert(mint_owner) != mint_owner {
+                    return Err(Error::invalid_params(
+                        "Invalid param: mints must belong to the same Token program".to_string(),
+                    ));
+                }
+            }
+            Ok((token_program_id.unwrap_or_else(spl_token_id_v2_0), mints))
+        }
     }
 }
 
+/// Keeps the token accounts of one of `mints`, or of any mint when `mints` has at most one
+/// mint, which the scan already filtered on. With `non_zero_balance_only`, also drops the
+/// accounts holding no tokens.
+fn retain_token_accounts(
+    keyed_accounts: &mut Vec<(Pubkey, AccountSharedData)>,
+    mints: &[Pubkey],
+    non_zero_balance_only: bool,
+) {
+    let mints: HashSet<_> = mints.iter().collect();
+    keyed_accounts.retain(|(_, account)| {
+        match TokenAccount::unpack(strip_token_extensions(account.data())) {
+            Ok(token_account) => {
+                let mint = Pubkey::new_from_array(token_account.mint.to_bytes());
+                (mints.len() < 2 || mints.contains(&mint))
+                    && (!non_zero_balance_only || token_account.amount > 0)
+            }
+            Err(_) => false,
+        }
+    });
+}
+
 fn _send_transaction(
     meta: JsonRpcRequestProcessor,
     signature: Signature,
@@ -2278,19 +3991,35 @@ fn _send_transaction(
     last_valid_block_height: u64,
     durable_nonce_info: Option<(Pubkey, Hash)>,
     max_retries: Option<usize>,
+    retry_until_expired: bool,
 ) -> Result<String> {
-    let transaction_info = TransactionInfo::new(
+    let mut transaction_info = TransactionInfo::new(
         signature,
         wire_transaction,
         last_valid_block_height,
         durable_nonce_info,
         max_retries,
     );
+    if retry_until_expired {
+        // Tracked from the start, so the status is known before the service picks it up
+        meta.retry_status_cache.set(
+            signature,
+            RetryStatus {
+                state: RetryState::Pending,
+                retries: 0,
+                last_valid_block_height,
+            },
+        );
+        transaction_info.retry_status_cache = Some(meta.retry_status_cache.clone());
+    }
     meta.transaction_sender
         .lock()
         .unwrap()
         .send(transaction_info)
-        .unwrap_or_else(|err| warn!("Failed to enqueue transaction: {}", err));
+        .unwrap_or_else(|err| {
+            warn!("Failed to enqueue transaction: {}", err);
+            err.0.set_retry_state(RetryState::Dropped);
+        });
 
     Ok(signature.to_string())
 }
@@ -2318,7 +4047,11 @@ pub mod rpc_minimal {
         ) -> Result<EpochInfo>;
 
         #[rpc(meta, name = "getHealth")]
-        fn get_health(&self, meta: Self::Metadata) -> Result<String>;
+        fn get_health(
+            &self,
+            meta: Self::Metadata,
+            config: Option<RpcHealthConfig>,
+        ) -> Result<GetHealthResponse>;
 
         #[rpc(meta, name = "getIdentity")]
         fn get_identity(&self, meta: Self::Metadata) -> Result<RpcIdentity>;
@@ -2367,7 +4100,7 @@ pub mod rpc_minimal {
             meta: Self::Metadata,
             options: Option<RpcLeaderScheduleConfigWrapper>,
             config: Option<RpcLeaderScheduleConfig>,
-        ) -> Result<Option<RpcLeaderSchedule>>;
+        ) -> Result<Option<GetLeaderScheduleResponse>>;
     }
 
     pub struct MinimalImpl;
@@ -2395,9 +4128,16 @@ pub mod rpc_minimal {
             Ok(bank.get_epoch_info())
         }
 
-        fn get_health(&self, meta: Self::Metadata) -> Result<String> {
+        fn get_health(
+            &self,
+            meta: Self::Metadata,
+            config: Option<RpcHealthConfig>,
+        ) -> Result<GetHealthResponse> {
+            if config.unwrap_or_default().extended {
+                return Ok(GetHealthResponse::Report(meta.get_health_report()));
+            }
             match meta.health.check() {
-                RpcHealthStatus::Ok => Ok("ok".to_string()),
+                RpcHealthStatus::Ok => Ok(GetHealthResponse::Status("ok".to_string())),
                 RpcHealthStatus::Unknown => Err(RpcCustomError::NodeUnhealthy {
                     num_slots_behind: None,
                 }
@@ -2497,7 +4237,7 @@ pub mod rpc_minimal {
             meta: Self::Metadata,
             options: Option<RpcLeaderScheduleConfigWrapper>,
             config: Option<RpcLeaderScheduleConfig>,
-        ) -> Result<Option<RpcLeaderSchedule>> {
+        ) -> Result<Option<GetLeaderScheduleResponse>> {
             let (slot, maybe_config) = options.map(|options| options.unzip()).unwrap_or_default();
             let config = maybe_config.or(config).unwrap_or_default();
 
@@ -2506,24 +4246,107 @@ pub mod rpc_minimal {
             }
 
             let bank = meta.bank(config.commitment);
-            let slot = slot.unwrap_or_else(|| bank.slot());
-            let epoch = bank.epoch_schedule().get_epoch(slot);
+            let epoch_schedule = bank.epoch_schedule();
+            let (first_slot, last_slot) = match config.range {
+                Some(range) => {
+                    if slot.is_some() {
+                        return Err(Error::invalid_params("slot may not be used with range"));
+                    }
+                    let last_slot = range.last_slot.unwrap_or_else(|| {
+                        epoch_schedule
+                            .get_last_slot_in_epoch(epoch_schedule.get_epoch(range.first_slot))
+                    });
+                    if last_slot < range.first_slot {
+                        return Err(Error::invalid_params(format!(
+                            "lastSlot, {}, cannot be less than firstSlot, {}",
+                            last_slot, range.first_slot
+                        )));
+                    }
+                    (range.first_slot, last_slot)
+                }
+                None => {
+                    let epoch = epoch_schedule.get_epoch(slot.unwrap_or_else(|| bank.slot()));
+                    (
+                        epoch_schedule.get_first_slot_in_epoch(epoch),
+                        epoch_schedule.get_last_slot_in_epoch(epoch),
+                    )
+                }
+            };
 
-            debug!("get_leader_schedule rpc request received: {:?}", slot);
+            debug!(
+                "get_leader_schedule rpc request received: {:?}-{:?}",
+                first_slot, last_slot
+            );
 
-            Ok(meta
-                .leader_schedule_cache
-                .get_epoch_leader_schedule(epoch)
-                .map(|leader_schedule| {
-                    let mut schedule_by_identity =
-                        solana_ledger::leader_schedule_utils::leader_schedule_by_identity(
-                            leader_schedule.get_slot_leaders().iter().enumerate(),
-                        );
-                    if let Some(identity) = config.identity {
-                        schedule_by_identity.retain(|k, _| *k == identity);
-                    }
-                    schedule_by_identity
-                }))
+            let epochs = epoch_schedule.get_epoch(first_slot)..=epoch_schedule.get_epoch(last_slot);
+            let mut slot_leaders = vec![];
+            for epoch in epochs.clone() {
+                let leader_schedule =
+                    match meta.leader_schedule_cache.get_epoch_leader_schedule(epoch) {
+                        Some(leader_schedule) => leader_schedule,
+                        None => return Ok(None),
+                    };
+                let first_slot_in_epoch = epoch_schedule.get_first_slot_in_epoch(epoch);
+                slot_leaders.extend(
+                    leader_schedule
+                        .get_slot_leaders()
+                        .iter()
+                        .skip(first_slot.saturating_sub(first_slot_in_epoch) as usize)
+                        .take(
+                            (last_slot.saturating_add(1) - first_slot_in_epoch.max(first_slot))
+                                as usize,
+                        ),
+                );
+            }
+
+            let mut schedule_by_identity =
+                solana_ledger::leader_schedule_utils::leader_schedule_by_identity(
+                    slot_leaders.iter().enumerate(),
+                );
+            if let Some(identity) = config.identity {
+                schedule_by_identity.retain(|k, _| *k == identity);
+            }
+            if !config.with_vote_accounts.unwrap_or_default() {
+                return Ok(Some(GetLeaderScheduleResponse::Schedule(
+                    schedule_by_identity,
+                )));
+            }
+
+            // A leader's vote accounts are taken from the earliest epoch of the range that it
+            // leads in
+            let epoch_stakes: Vec<_> = epochs
+                .filter_map(|epoch| bank.epoch_stakes(epoch))
+                .map(|epoch_stakes| epoch_stakes.node_id_to_vote_accounts())
+                .collect();
+            Ok(Some(GetLeaderScheduleResponse::WithVoteAccounts(
+                schedule_by_identity
+                    .into_iter()
+                    .map(|(identity, slot_indices)| {
+                        let node_vote_accounts =
+                            Pubkey::from_str(&identity).ok().and_then(|identity| {
+                                epoch_stakes.iter().find_map(|node_id_to_vote_accounts| {
+                                    node_id_to_vote_accounts.get(&identity)
+                                })
+                            });
+                        let entry = RpcLeaderScheduleEntry {
+                            slot_indices,
+                            vote_accounts: node_vote_accounts
+                                .map(|node_vote_accounts| {
+                                    node_vote_accounts
+                                        .vote_accounts
+                                        .iter()
+                                        .map(|vote_pubkey| vote_pubkey.to_string())
+                                        .collect()
+                                })
+                                .unwrap_or_default(),
+                            stake: node_vote_accounts
+                                .map(|node_vote_accounts| node_vote_accounts.total_stake)
+                                .unwrap_or_default(),
+                        };
+                        (identity, entry)
+                    })
+                    .collect(),
+            )))
         }
     }
 }
@@ -2669,12 +4492,24 @@ pub mod rpc_bank {
             };
 
             let bank = meta.bank(config.commitment);
-            let (first_slot, last_slot) = match config.range {
-                None => (
+            let (first_slot, last_slot) = match (config.range, config.epoch) {
+                (Some(_), Some(_)) => {
+                    return Err(Error::invalid_params("epoch may not be used with range"));
+                }
+                (None, Some(epoch)) => {
+                    let epoch_schedule = bank.epoch_schedule();
+                    (
+                        epoch_schedule.get_first_slot_in_epoch(epoch),
+                        epoch_schedule
+                            .get_last_slot_in_epoch(epoch)
+                            .min(bank.slot()),
+                    )
+                }
+                (None, None) => (
                     bank.epoch_schedule().get_first_slot_in_epoch(bank.epoch()),
                     bank.slot(),
                 ),
-                Some(range) => {
+                (Some(range), None) => {
                     let first_slot = range.first_slot;
                     let last_slot = range.last_slot.unwrap_or_else(|| bank.slot());
                     if last_slot < first_slot {
@@ -2687,13 +4522,27 @@ pub mod rpc_bank {
                 }
             };
 
+            // Slots older than the slot history are looked up in the blockstore's roots
             let slot_history = bank.get_slot_history();
+            let mut blockstore_roots = HashSet::new();
             if first_slot < slot_history.oldest() {
-                return Err(Error::invalid_params(format!(
-                    "firstSlot, {}, is too small; min {}",
-                    first_slot,
-                    slot_history.oldest()
-                )));
+                let first_available_block = meta
+                    .blockstore
+                    .get_first_available_block()
+                    .unwrap_or_default()
+                    .min(slot_history.oldest());
+                if first_slot < first_available_block {
+                    return Err(Error::invalid_params(format!(
+                        "firstSlot, {}, is too small; min {}",
+                        first_slot, first_available_block
+                    )));
+                }
+                blockstore_roots = meta
+                    .blockstore
+                    .rooted_slot_iterator(first_slot)
+                    .map_err(|err| Error::invalid_params(format!("{:?}", err)))?
+                    .take_while(|slot| *slot < slot_history.oldest())
+                    .collect();
             }
             if last_slot > slot_history.newest() {
                 return Err(Error::invalid_params(format!(
@@ -2710,6 +4559,7 @@ pub mod rpc_bank {
             )?;
 
             let mut block_production: HashMap<_, (usize, usize)> = HashMap::new();
+            let mut skipped_slots: HashMap<_, Vec<Slot>> = HashMap::new();
 
             let mut slot = first_slot;
             for identity in slot_leaders {
@@ -2721,8 +4571,15 @@ pub mod rpc_bank {
                 }
 
                 let mut entry = block_production.entry(identity).or_default();
-                if slot_history.check(slot) == solana_sdk::slot_history::Check::Found {
+                let produced = if slot < slot_history.oldest() {
+                    blockstore_roots.contains(&slot)
+                } else {
+                    slot_history.check(slot) == solana_sdk::slot_history::Check::Found
+                };
+                if produced {
                     entry.1 += 1; // Increment blocks_produced
+                } else if config.with_skipped_slots.unwrap_or_default() {
+                    skipped_slots.entry(identity).or_default().push(slot);
                 }
                 entry.0 += 1; // Increment leader_slots
                 slot += 1;
@@ -2739,6 +4596,14 @@ pub mod rpc_bank {
                         first_slot,
                         last_slot,
                     },
+                    skipped_slots_by_identity: config.with_skipped_slots.unwrap_or_default().then(
+                        || {
+                            skipped_slots
+                                .into_iter()
+                                .map(|(k, v)| (k.to_string(), v))
+                                .collect()
+                        },
+                    ),
                 },
             ))
         }
@@ -2766,17 +4631,25 @@ pub mod rpc_accounts {
         fn get_multiple_accounts(
             &self,
             meta: Self::Metadata,
-            pubkey_strs: Vec<String>,
-            config: Option<RpcAccountInfoConfig>,
+            keys: Vec<RpcMultipleAccountsKey>,
+            config: Option<RpcMultipleAccountsConfig>,
         ) -> Result<RpcResponse<Vec<Option<UiAccount>>>>;
 
         #[rpc(meta, name = "getProgramAccounts")]
         fn get_program_accounts(
+            &self,
+            meta: Self::Metadata,
+            program_id_str: String,
+            config: Option<RpcGetProgramAccountsConfig>,
+        ) -> BoxFuture<Result<OptionalContext<Vec<RpcKeyedAccount>>>>;
+
+        #[rpc(meta, name = "getProgramAccountsCount")]
+        fn get_program_accounts_count(
             &self,
             meta: Self::Metadata,
             program_id_str: String,
             config: Option<RpcProgramAccountsConfig>,
-        ) -> Result<OptionalContext<Vec<RpcKeyedAccount>>>;
+        ) -> BoxFuture<Result<OptionalContext<u64>>>;
 
         #[rpc(meta, name = "getBlockCommitment")]
         fn get_block_commitment(
@@ -2790,14 +4663,14 @@ pub mod rpc_accounts {
             &self,
             meta: Self::Metadata,
             config: Option<RpcLargestAccountsConfig>,
-        ) -> Result<RpcResponse<Vec<RpcAccountBalance>>>;
+        ) -> BoxFuture<Result<RpcLargestAccountsResponse>>;
 
         #[rpc(meta, name = "getSupply")]
         fn get_supply(
             &self,
             meta: Self::Metadata,
             config: Option<RpcSupplyConfig>,
-        ) -> Result<RpcResponse<RpcSupply>>;
+        ) -> BoxFuture<Result<RpcResponse<RpcSupply>>>;
 
         #[rpc(meta, name = "getStakeActivation")]
         fn get_stake_activation(
@@ -2841,7 +4714,7 @@ pub mod rpc_accounts {
             meta: Self::Metadata,
             owner_str: String,
             token_account_filter: RpcTokenAccountsFilter,
-            config: Option<RpcAccountInfoConfig>,
+            config: Option<RpcTokenAccountsByOwnerConfig>,
         ) -> Result<RpcResponse<Vec<RpcKeyedAccount>>>;
 
         #[rpc(meta, name = "getTokenAccountsByDelegate")]
@@ -2852,6 +4725,22 @@ pub mod rpc_accounts {
             token_account_filter: RpcTokenAccountsFilter,
             config: Option<RpcAccountInfoConfig>,
         ) -> Result<RpcResponse<Vec<RpcKeyedAccount>>>;
+
+        #[rpc(meta, name = "getStakeAccountsByStaker")]
+        fn get_stake_accounts_by_staker(
+            &self,
+            meta: Self::Metadata,
+            staker_str: String,
+            config: Option<RpcAccountInfoConfig>,
+        ) -> Result<RpcResponse<Vec<RpcKeyedAccount>>>;
+
+        #[rpc(meta, name = "getStakeAccountsByWithdrawer")]
+        fn get_stake_accounts_by_withdrawer(
+            &self,
+            meta: Self::Metadata,
+            withdrawer_str: String,
+            config: Option<RpcAccountInfoConfig>,
+        ) -> Result<RpcResponse<Vec<RpcKeyedAccount>>>;
     }
 
     pub struct AccountsDataImpl;
@@ -2872,61 +4761,95 @@ pub mod rpc_accounts {
         fn get_multiple_accounts(
             &self,
             meta: Self::Metadata,
-            pubkey_strs: Vec<String>,
-            config: Option<RpcAccountInfoConfig>,
+            keys: Vec<RpcMultipleAccountsKey>,
+            config: Option<RpcMultipleAccountsConfig>,
         ) -> Result<RpcResponse<Vec<Option<UiAccount>>>> {
             debug!(
                 "get_multiple_accounts rpc request received: {:?}",
-                pubkey_strs.len()
+                keys.len()
             );
 
             let max_multiple_accounts = meta
                 .config
                 .max_multiple_accounts
                 .unwrap_or(MAX_MULTIPLE_ACCOUNTS);
-            if pubkey_strs.len() > max_multiple_accounts {
+            if keys.len() > max_multiple_accounts {
                 return Err(Error::invalid_params(format!(
                     "Too many inputs provided; max {}",
                     max_multiple_accounts
                 )));
             }
-            let pubkeys = pubkey_strs
+            let keys = keys
                 .into_iter()
-                .map(|pubkey_str| verify_pubkey(&pubkey_str))
+                .map(|key| match key {
+                    RpcMultipleAccountsKey::Pubkey(pubkey_str) => {
+                        Ok((verify_pubkey(&pubkey_str)?, None))
+                    }
+                    RpcMultipleAccountsKey::WithDataSlice { pubkey, data_slice } => {
+                        Ok((verify_pubkey(&pubkey)?, data_slice))
+                    }
+                })
                 .collect::<Result<Vec<_>>>()?;
-            meta.get_multiple_accounts(pubkeys, config)
+            let (config, owner) = match config {
+                Some(config) => (
+                    Some(config.account_config),
+                    config
+                        .owner
+                        .map(|owner| verify_pubkey(&owner))
+                        .transpose()?,
+                ),
+                None => (None, None),
+            };
+            meta.get_multiple_accounts(keys, config, owner)
         }
 
         fn get_program_accounts(
             &self,
             meta: Self::Metadata,
             program_id_str: String,
-            config: Option<RpcProgramAccountsConfig>,
-        ) -> Result<OptionalContext<Vec<RpcKeyedAccount>>> {
+            config: Option<RpcGetProgramAccountsConfig>,
+        ) -> BoxFuture<Result<OptionalContext<Vec<RpcKeyedAccount>>>> {
             debug!(
                 "get_program_accounts rpc request received: {:?}",
                 program_id_str
             );
-            let program_id = verify_pubkey(&program_id_str)?;
-            let (config, filters, with_context) = if let Some(config) = config {
-                (
-                    Some(config.account_config),
-                    config.filters.unwrap_or_default(),
-                    config.with_context.unwrap_or_default(),
-                )
-            } else {
-                (None, vec![], false)
-            };
-            if filters.len() > MAX_GET_PROGRAM_ACCOUNT_FILTERS {
-                return Err(Error::invalid_params(format!(
-                    "Too many filters provided; max {}",
-                    MAX_GET_PROGRAM_ACCOUNT_FILTERS
-                )));
-            }
-            for filter in &filters {
-                verify_filter(filter)?;
-            }
-            meta.get_program_accounts(&program_id, config, filters, with_context)
+            Box::pin(async move {
+                let program_id = verify_pubkey(&program_id_str)?;
+                let query = verify_program_accounts_config(config)?;
+                meta.run_scan(move |meta| {
+                    meta.get_program_accounts(
+                        &program_id,
+                        query.account_config,
+                        query.filters,
+                        query.with_context,
+                        query.page,
+                    )
+                })
+                .await
+            })
+        }
+
+        fn get_program_accounts_count(
+            &self,
+            meta: Self::Metadata,
+            program_id_str: String,
+            config: Option<RpcProgramAccountsConfig>,
+        ) -> BoxFuture<Result<OptionalContext<u64>>> {
+            debug!(
+                "get_program_accounts_count rpc request received: {:?}",
+                program_id_str
+            );
+            Box::pin(async move {
+                let program_id = verify_pubkey(&program_id_str)?;
+                let config = config.unwrap_or_default();
+                let filters = verify_program_accounts_filters(config.filters)?;
+                let commitment = config.account_config.commitment;
+                let with_context = config.with_context.unwrap_or_default();
+                meta.run_scan(move |meta| {
+                    meta.get_program_accounts_count(&program_id, commitment, filters, with_context)
+                })
+                .await
+            })
         }
 
         fn get_block_commitment(
@@ -2942,18 +4865,54 @@ pub mod rpc_accounts {
             &self,
             meta: Self::Metadata,
             config: Option<RpcLargestAccountsConfig>,
-        ) -> Result<RpcResponse<Vec<RpcAccountBalance>>> {
+        ) -> BoxFuture<Result<RpcLargestAccountsResponse>> {
             debug!("get_largest_accounts rpc request received");
-            Ok(meta.get_largest_accounts(config)?)
+            Box::pin(async move {
+                meta.run_scan(move |meta| meta.get_largest_accounts(config))
+                    .await
+            })
         }
 
         fn get_supply(
             &self,
             meta: Self::Metadata,
             config: Option<RpcSupplyConfig>,
-        ) -> Result<RpcResponse<RpcSupply>> {
+        ) -> BoxFuture<Result<RpcResponse<RpcSupply>>> {
             debug!("get_supply rpc request received");
-            Ok(meta.get_supply(config)?)
+            let verify_pubkeys = |pubkeys: Option<&Vec<String>>, max: usize| {
+                let pubkeys = pubkeys.map(Vec::as_slice).unwrap_or_default();
+                if pubkeys.len() > max {
+                    return Err(Error::invalid_params(format!(
+                        "Too many non-circulating accounts or owners provided; max {}",
+                        max
+                    )));
+                }
+                pubkeys
+                    .iter()
+                    .map(|pubkey_str| verify_pubkey(pubkey_str))
+                    .collect::<Result<Vec<_>>>()
+            };
+            let non_circulating_config = config
+                .as_ref()
+                .map(|config| -> Result<_> {
+                    Ok(NonCirculatingSupplyConfig {
+                        exclude_defaults: config.exclude_default_non_circulating,
+                        accounts: verify_pubkeys(
+                            config.non_circulating_accounts.as_ref(),
+                            MAX_GET_SUPPLY_NON_CIRCULATING_ACCOUNTS,
+                        )?,
+                        owners: verify_pubkeys(
+                            config.non_circulating_owners.as_ref(),
+                            MAX_GET_SUPPLY_NON_CIRCULATING_OWNERS,
+                        )?,
+                    })
+                })
+                .transpose();
+            Box::pin(async move {
+                let non_circulating_config = non_circulating_config?.unwrap_or_default();
+                meta.run_scan(move |meta| Ok(meta.get_supply(config, &non_circulating_config)?))
+                    .await
+            })
         }
 
         fn get_stake_activation(
@@ -3014,7 +4973,7 @@ pub mod rpc_accounts {
             meta: Self::Metadata,
             owner_str: String,
             token_account_filter: RpcTokenAccountsFilter,
-            config: Option<RpcAccountInfoConfig>,
+            config: Option<RpcTokenAccountsByOwnerConfig>,
         ) -> Result<RpcResponse<Vec<RpcKeyedAccount>>> {
             debug!(
                 "get_token_accounts_by_owner rpc request received: {:?}",
@@ -3022,7 +4981,15 @@ pub mod rpc_accounts {
             );
             let owner = verify_pubkey(&owner_str)?;
             let token_account_filter = verify_token_account_filter(token_account_filter)?;
-            meta.get_token_accounts_by_owner(&owner, token_account_filter, config)
+            let config = config.unwrap_or_default();
+            let page = ProgramAccountsPage::new(config.limit, config.after_pubkey, None)?;
+            meta.get_token_accounts_by_owner(
+                &owner,
+                token_account_filter,
+                Some(config.account_config),
+                page,
+                config.non_zero_balance_only.unwrap_or_default(),
+            )
         }
 
         fn get_token_accounts_by_delegate(
@@ -3040,6 +5007,34 @@ pub mod rpc_accounts {
             let token_account_filter = verify_token_account_filter(token_account_filter)?;
             meta.get_token_accounts_by_delegate(&delegate, token_account_filter, config)
         }
+
+        fn get_stake_accounts_by_staker(
+            &self,
+            meta: Self::Metadata,
+            staker_str: String,
+            config: Option<RpcAccountInfoConfig>,
+        ) -> Result<RpcResponse<Vec<RpcKeyedAccount>>> {
+            debug!(
+                "get_stake_accounts_by_staker rpc request received: {:?}",
+                staker_str
+            );
+            let staker = verify_pubkey(&staker_str)?;
+            meta.get_stake_accounts_by_authority(&staker, StakeAuthorize::Staker, config)
+        }
+
+        fn get_stake_accounts_by_withdrawer(
+            &self,
+            meta: Self::Metadata,
+            withdrawer_str: String,
+            config: Option<RpcAccountInfoConfig>,
+        ) -> Result<RpcResponse<Vec<RpcKeyedAccount>>> {
+            debug!(
+                "get_stake_accounts_by_withdrawer rpc request received: {:?}",
+                withdrawer_str
+            );
+            let withdrawer = verify_pubkey(&withdrawer_str)?;
+            meta.get_stake_accounts_by_authority(&withdrawer, StakeAuthorize::Withdrawer, config)
+        }
     }
 }
 
@@ -3059,8 +5054,20 @@ pub mod rpc_full {
             config: Option<RpcEpochConfig>,
         ) -> BoxFuture<Result<Vec<Option<RpcInflationReward>>>>;
 
+        #[rpc(meta, name = "getVoteAccountInflationRewards")]
+        fn get_vote_account_inflation_rewards(
+            &self,
+            meta: Self::Metadata,
+            vote_pubkey_str: String,
+            config: Option<RpcVoteAccountInflationRewardsConfig>,
+        ) -> BoxFuture<Result<RpcVoteAccountInflationRewards>>;
+
         #[rpc(meta, name = "getClusterNodes")]
-        fn get_cluster_nodes(&self, meta: Self::Metadata) -> Result<Vec<RpcContactInfo>>;
+        fn get_cluster_nodes(
+            &self,
+            meta: Self::Metadata,
+            config: Option<RpcClusterNodesConfig>,
+        ) -> Result<GetClusterNodesResponse>;
 
         #[rpc(meta, name = "getRecentPerformanceSamples")]
         fn get_recent_performance_samples(
@@ -3101,7 +5108,7 @@ pub mod rpc_full {
             meta: Self::Metadata,
             data: String,
             config: Option<RpcSendTransactionConfig>,
-        ) -> Result<String>;
+        ) -> Result<SendTransactionResponse>;
 
         #[rpc(meta, name = "simulateTransaction")]
         fn simulate_transaction(
@@ -3111,6 +5118,14 @@ pub mod rpc_full {
             config: Option<RpcSimulateTransactionConfig>,
         ) -> Result<RpcResponse<RpcSimulateTransactionResult>>;
 
+        #[rpc(meta, name = "simulateBundle")]
+        fn simulate_bundle(
+            &self,
+            meta: Self::Metadata,
+            data: Vec<String>,
+            config: Option<RpcSimulateTransactionConfig>,
+        ) -> Result<RpcResponse<Vec<RpcSimulateTransactionResult>>>;
+
         #[rpc(meta, name = "minimumLedgerSlot")]
         fn minimum_ledger_slot(&self, meta: Self::Metadata) -> Result<Slot>;
 
@@ -3119,8 +5134,8 @@ pub mod rpc_full {
             &self,
             meta: Self::Metadata,
             slot: Slot,
-            config: Option<RpcEncodingConfigWrapper<RpcBlockConfig>>,
-        ) -> BoxFuture<Result<Option<UiConfirmedBlock>>>;
+            config: Option<RpcEncodingConfigWrapper<RpcBlockFieldsConfig>>,
+        ) -> BoxFuture<Result<Option<OptionalFields<UiConfirmedBlock>>>>;
 
         #[rpc(meta, name = "getBlockTime")]
         fn get_block_time(
@@ -3134,8 +5149,8 @@ pub mod rpc_full {
             &self,
             meta: Self::Metadata,
             start_slot: Slot,
-            config: Option<RpcBlocksConfigWrapper>,
-            commitment: Option<CommitmentConfig>,
+            wrapper: Option<RpcBlocksConfigWrapper>,
+            config: Option<RpcBlocksConfig>,
         ) -> BoxFuture<Result<Vec<Slot>>>;
 
         #[rpc(meta, name = "getBlocksWithLimit")]
@@ -3144,7 +5159,7 @@ pub mod rpc_full {
             meta: Self::Metadata,
             start_slot: Slot,
             limit: usize,
-            commitment: Option<CommitmentConfig>,
+            config: Option<RpcBlocksConfig>,
         ) -> BoxFuture<Result<Vec<Slot>>>;
 
         #[rpc(meta, name = "getTransaction")]
@@ -3155,13 +5170,28 @@ pub mod rpc_full {
             config: Option<RpcEncodingConfigWrapper<RpcTransactionConfig>>,
         ) -> BoxFuture<Result<Option<EncodedConfirmedTransaction>>>;
 
+        #[rpc(meta, name = "getTransactions")]
+        fn get_transactions(
+            &self,
+            meta: Self::Metadata,
+            signature_strs: Vec<String>,
+            config: Option<RpcTransactionConfig>,
+        ) -> BoxFuture<Result<Vec<RpcTransactionsEntry>>>;
+
+        #[rpc(meta, name = "getTransactionRetryStatus")]
+        fn get_transaction_retry_status(
+            &self,
+            meta: Self::Metadata,
+            signature_str: String,
+        ) -> Result<Option<RpcTransactionRetryStatus>>;
+
         #[rpc(meta, name = "getSignaturesForAddress")]
         fn get_signatures_for_address(
             &self,
             meta: Self::Metadata,
             address: String,
             config: Option<RpcSignaturesForAddressConfig>,
-        ) -> BoxFuture<Result<Vec<RpcConfirmedTransactionStatusWithSignature>>>;
+        ) -> BoxFuture<Result<RpcSignaturesForAddress>>;
 
         #[rpc(meta, name = "getFirstAvailableBlock")]
         fn get_first_available_block(&self, meta: Self::Metadata) -> BoxFuture<Result<Slot>>;
@@ -3227,8 +5257,13 @@ pub mod rpc_full {
                 .collect())
         }
 
-        fn get_cluster_nodes(&self, meta: Self::Metadata) -> Result<Vec<RpcContactInfo>> {
+        fn get_cluster_nodes(
+            &self,
+            meta: Self::Metadata,
+            config: Option<RpcClusterNodesConfig>,
+        ) -> Result<GetClusterNodesResponse> {
             debug!("get_cluster_nodes rpc request received");
+            let config = config.unwrap_or_default();
             let cluster_info = &meta.cluster_info;
             let socket_addr_space = cluster_info.socket_addr_space();
             let valid_address_or_none = |addr: &SocketAddr| -> Option<SocketAddr> {
@@ -3238,12 +5273,14 @@ pub mod rpc_full {
                     None
                 }
             };
-            let my_shred_version = cluster_info.my_shred_version();
-            Ok(cluster_info
+            let shred_version = config
+                .shred_version
+                .unwrap_or_else(|| cluster_info.my_shred_version());
+            let nodes: Vec<_> = cluster_info
                 .all_peers()
                 .iter()
                 .filter_map(|(contact_info, _)| {
-                    if my_shred_version == contact_info.shred_version
+                    if shred_version == contact_info.shred_version
                         && ContactInfo::is_valid_address(&contact_info.gossip, socket_addr_space)
                     {
                         let (version, feature_set) = if let Some(version) =
@@ -3260,13 +5297,66 @@ pub mod rpc_full {
                             rpc: valid_address_or_none(&contact_info.rpc),
                             version,
                             feature_set,
-                            shred_version: Some(my_shred_version),
+                            shred_version: Some(shred_version),
                         })
                     } else {
                         None // Exclude spy nodes
                     }
                 })
-                .collect())
+                .filter(|node| {
+                    config
+                        .version
+                        .as_ref()
+                        .map_or(true, |version| node.version.as_ref() == Some(version))
+                        && (!config.rpc_only.unwrap_or_default() || node.rpc.is_some())
+                })
+                .collect();
+            if !config.summary.unwrap_or_default() {
+                return Ok(GetClusterNodesResponse::Nodes(nodes));
+            }
+
+            let bank = meta.bank(config.commitment);
+            let staked_nodes = bank.epoch_staked_nodes(bank.epoch()).unwrap_or_default();
+            let mut versions: HashMap<Option<String>, (usize, u64)> = HashMap::new();
+            let mut feature_sets: HashMap<Option<u32>, (usize, u64)> = HashMap::new();
+            for node in &nodes {
+                let stake = verify_pubkey(&node.pubkey)
+                    .ok()
+                    .and_then(|pubkey| staked_nodes.get(&pubkey).copied())
+                    .unwrap_or_default();
+                for entry in [
+                    versions.entry(node.version.clone()).or_default(),
+                    feature_sets.entry(node.feature_set).or_default(),
+                ] {
+                    entry.0 += 1;
+                    entry.1 += stake;
+                }
+            }
+            let mut versions: Vec<_> = versions
+                .into_iter()
+                .map(|(version, (nodes, stake))| RpcClusterNodesVersion {
+                    version,
+                    nodes,
+                    stake,
+                })
+                .collect();
+            versions.sort_by(|a, b| (b.stake, b.nodes).cmp(&(a.stake, a.nodes)));
+            let mut feature_sets: Vec<_> = feature_sets
+                .into_iter()
+                .map(|(feature_set, (nodes, stake))| RpcClusterNodesFeatureSet {
+                    feature_set,
+                    nodes,
+                    stake,
+                })
+                .collect();
+            feature_sets.sort_by(|a, b| (b.stake, b.nodes).cmp(&(a.stake, a.nodes)));
+
+            Ok(GetClusterNodesResponse::Summary(RpcClusterNodesSummary {
+                total_nodes: nodes.len(),
+                total_stake: bank.total_epoch_stake(),
+                versions,
+                feature_sets,
+            }))
         }
 
         fn get_genesis_hash(&self, meta: Self::Metadata) -> Result<String> {
@@ -3368,6 +5458,7 @@ pub mod rpc_full {
                 last_valid_block_height,
                 None,
                 None,
+                false,
             )
         }
 
@@ -3376,9 +5467,22 @@ pub mod rpc_full {
             meta: Self::Metadata,
             data: String,
             config: Option<RpcSendTransactionConfig>,
-        ) -> Result<String> {
+        ) -> Result<SendTransactionResponse> {
             debug!("send_transaction rpc request received");
             let config = config.unwrap_or_default();
+            if config.retry_until_expired && config.max_retries.is_some() {
+                return Err(Error::invalid_params(
+                    "maxRetries may not be used with retryUntilExpired",
+                ));
+            }
+            if config.skip_preflight
+                && (config.preflight_against_confirmed || config.return_units_consumed)
+            {
+                return Err(Error::invalid_params(
+                    "skipPreflight may not be used with preflightAgainstConfirmed or \
+                     returnUnitsConsumed",
+                ));
+            }
             let encoding = config.encoding.unwrap_or(UiTransactionEncoding::Base58);
             let (wire_transaction, unsanitized_tx) =
                 decode_and_deserialize::<VersionedTransaction>(data, encoding)?;
@@ -3406,6 +5510,7 @@ pub mod rpc_full {
                     preflight_bank.block_height() + MAX_RECENT_BLOCKHASHES as u64;
             }
 
+            let mut units_consumed = 0;
             if !config.skip_preflight {
                 if let Err(e) = verify_transaction(&transaction, &preflight_bank.feature_set) {
                     return Err(e);
@@ -3429,42 +5534,32 @@ pub mod rpc_full {
                     }
                 }
 
-                if let TransactionSimulationResult {
-                    result: Err(err),
-                    logs,
-                    post_simulation_accounts: _,
-                    units_consumed,
-                } = preflight_bank.simulate_transaction(transaction)
-                {
-                    match err {
-                        TransactionError::BlockhashNotFound => {
-                            inc_new_counter_info!("rpc-send-tx_err-blockhash-not-found", 1);
-                        }
-                        _ => {
-                            inc_new_counter_info!("rpc-send-tx_err-other", 1);
-                        }
-                    }
-                    return Err(RpcCustomError::SendTransactionPreflightFailure {
-                        message: format!("Transaction simulation failed: {}", err),
-                        result: RpcSimulateTransactionResult {
-                            err: Some(err),
-                            logs: Some(logs),
-                            accounts: None,
-                            units_consumed: Some(units_consumed),
-                        },
+                if config.preflight_against_confirmed {
+                    let confirmed_bank = meta.bank(Some(CommitmentConfig::confirmed()));
+                    if confirmed_bank.slot() != preflight_bank.slot() {
+                        units_consumed = run_preflight(&confirmed_bank, transaction.clone())?;
                     }
-                    .into());
                 }
+                units_consumed = units_consumed.max(run_preflight(preflight_bank, transaction)?);
             }
 
-            _send_transaction(
+            let signature = _send_transaction(
                 meta,
                 signature,
                 wire_transaction,
                 last_valid_block_height,
                 durable_nonce_info,
                 config.max_retries,
-            )
+                config.retry_until_expired,
+            )?;
+            Ok(if config.return_units_consumed {
+                SendTransactionResponse::WithUnitsConsumed(RpcSendTransactionResult {
+                    signature,
+                    units_consumed,
+                })
+            } else {
+                SendTransactionResponse::Signature(signature)
+            })
         }
 
         fn simulate_transaction(
@@ -3501,7 +5596,8 @@ pub mod rpc_full {
                 logs,
                 post_simulation_accounts,
                 units_consumed,
-            } = bank.simulate_transaction(transaction);
+                inner_instructions,
+            } = bank.simulate_transaction(transaction, config.inner_instructions);
 
             let accounts = if let Some(config_accounts) = config.accounts {
                 let accounts_encoding = config_accounts
@@ -3540,6 +5636,12 @@ pub mod rpc_full {
                 None
             };
 
+            let inner_instructions = if config.inner_instructions {
+                Some(encode_inner_instructions(inner_instructions))
+            } else {
+                None
+            };
+
             Ok(new_response(
                 bank,
                 RpcSimulateTransactionResult {
@@ -3547,10 +5649,132 @@ pub mod rpc_full {
                     logs: Some(logs),
                     accounts,
                     units_consumed: Some(units_consumed),
+                    inner_instructions,
                 },
             ))
         }
 
+        fn simulate_bundle(
+            &self,
+            meta: Self::Metadata,
+            data: Vec<String>,
+            config: Option<RpcSimulateTransactionConfig>,
+        ) -> Result<RpcResponse<Vec<RpcSimulateTransactionResult>>> {
+            debug!("simulate_bundle rpc request received: {:?}", data.len());
+            if data.is_empty() || data.len() > MAX_SIMULATE_BUNDLE_TRANSACTIONS {
+                return Err(Error::invalid_params(format!(
+                    "Bundles must have 1 to {} transactions",
+                    MAX_SIMULATE_BUNDLE_TRANSACTIONS
+                )));
+            }
+            let config = config.unwrap_or_default();
+            let encoding = config.encoding.unwrap_or(UiTransactionEncoding::Base58);
+            if config.replace_recent_blockhash && config.sig_verify {
+                return Err(Error::invalid_params(
+                    "sigVerify may not be used with replaceRecentBlockhash",
+                ));
+            }
+
+            let (accounts_encoding, addresses) = match config.accounts {
+                Some(config_accounts) => {
+                    let accounts_encoding = config_accounts
+                        .encoding
+                        .unwrap_or(UiAccountEncoding::Base64);
+                    if accounts_encoding == UiAccountEncoding::Binary
+                        || accounts_encoding == UiAccountEncoding::Base58
+                    {
+                        return Err(Error::invalid_params("base58 encoding not supported"));
+                    }
+                    if config_accounts.addresses.len() > MAX_MULTIPLE_ACCOUNTS {
+                        return Err(Error::invalid_params(format!(
+                            "Too many accounts provided; max {}",
+                            MAX_MULTIPLE_ACCOUNTS
+                        )));
+                    }
+                    let addresses = config_accounts
+                        .addresses
+                        .iter()
+                        .map(|address_str| verify_pubkey(address_str))
+                        .collect::<Result<Vec<_>>>()?;
+                    (accounts_encoding, Some(addresses))
+                }
+                None => (UiAccountEncoding::Base64, None),
+            };
+
+            let bank = &*meta.bank(config.commitment);
+            let transactions = data
+                .into_iter()
+                .map(|data| {
+                    let (_, mut unsanitized_tx) =
+                        decode_and_deserialize::<VersionedTransaction>(data, encoding)?;
+                    if config.replace_recent_blockhash {
+                        unsanitized_tx
+                            .message
+                            .set_recent_blockhash(bank.last_blockhash());
+                    }
+                    let transaction = sanitize_transaction(unsanitized_tx)?;
+                    if config.sig_verify {
+                        verify_transaction(&transaction, &bank.feature_set)?;
+                    }
+                    Ok(transaction)
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            // The accounts as left by each transaction, for looking up the requested addresses
+            let mut bundle_accounts = HashMap::new();
+            let results = bank
+                .simulate_bundle(transactions, config.inner_instructions)
+                .into_iter()
+                .map(
+                    |TransactionSimulationResult {
+                         result,
+                         logs,
+                         post_simulation_accounts,
+                         units_consumed,
+                         inner_instructions,
+                     }| {
+                        let accounts = addresses.as_ref().map(|addresses| {
+                            if result.is_err() {
+                                return vec![None; addresses.len()];
+                            }
+                            bundle_accounts.extend(post_simulation_accounts);
+                            addresses
+                                .iter()
+                                .map(|address| {
+                                    bundle_accounts
+                                        .get(address)
+                                        .cloned()
+                                        .or_else(|| bank.get_account(address))
+                                        .map(|account| {
+                                            UiAccount::encode(
+                                                address,
+                                                &account,
+                                                accounts_encoding,
+                                                None,
+                                                None,
+                                            )
+                                        })
+                                })
+                                .collect()
+                        });
+                        let inner_instructions = if config.inner_instructions {
+                            Some(encode_inner_instructions(inner_instructions))
+                        } else {
+                            None
+                        };
+                        RpcSimulateTransactionResult {
+                            err: result.err(),
+                            logs: Some(logs),
+                            accounts,
+                            units_consumed: Some(units_consumed),
+                            inner_instructions,
+                        }
+                    },
+                )
+                .collect();
+            Ok(new_response(bank, results))
+        }
+
         fn minimum_ledger_slot(&self, meta: Self::Metadata) -> Result<Slot> {
             debug!("minimum_ledger_slot rpc request received");
             meta.minimum_ledger_slot()
@@ -3560,27 +5784,48 @@ pub mod rpc_full {
             &self,
             meta: Self::Metadata,
             slot: Slot,
-            config: Option<RpcEncodingConfigWrapper<RpcBlockConfig>>,
-        ) -> BoxFuture<Result<Option<UiConfirmedBlock>>> {
+            config: Option<RpcEncodingConfigWrapper<RpcBlockFieldsConfig>>,
+        ) -> BoxFuture<Result<Option<OptionalFields<UiConfirmedBlock>>>> {
             debug!("get_block rpc request received: {:?}", slot);
-            Box::pin(async move { meta.get_block(slot, config).await })
+            let fields = match config
+                .as_ref()
+                .and_then(|config| config.convert_to_current().fields)
+                .map(|fields| FieldSelection::parse(&fields))
+                .transpose()
+            {
+                Ok(fields) => fields,
+                Err(err) => return Box::pin(future::err(err)),
+            };
+            let config = config.map(|config| config.convert::<RpcBlockConfig>());
+            Box::pin(async move {
+                let block = meta.get_block(slot, config).await?;
+                Ok(block.map(|block| match fields {
+                    Some(fields) => {
+                        let mut block = serde_json::to_value(block).expect("block serializes");
+                        fields.apply(&mut block);
+                        OptionalFields::Selected(block)
+                    }
+                    None => OptionalFields::All(block),
+                }))
+            })
         }
 
         fn get_blocks(
             &self,
             meta: Self::Metadata,
             start_slot: Slot,
-            config: Option<RpcBlocksConfigWrapper>,
-            commitment: Option<CommitmentConfig>,
+            wrapper: Option<RpcBlocksConfigWrapper>,
+            config: Option<RpcBlocksConfig>,
         ) -> BoxFuture<Result<Vec<Slot>>> {
-            let (end_slot, maybe_commitment) =
-                config.map(|config| config.unzip()).unwrap_or_default();
+            let (end_slot, maybe_config) = wrapper
+                .map(|wrapper| wrapper.unzip_config())
+                .unwrap_or_default();
             debug!(
                 "get_blocks rpc request received: {}-{:?}",
                 start_slot, end_slot
             );
             Box::pin(async move {
-                meta.get_blocks(start_slot, end_slot, commitment.or(maybe_commitment))
+                meta.get_blocks(start_slot, end_slot, config.or(maybe_config))
                     .await
             })
         }
@@ -3590,16 +5835,13 @@ pub mod rpc_full {
             meta: Self::Metadata,
             start_slot: Slot,
             limit: usize,
-            commitment: Option<CommitmentConfig>,
+            config: Option<RpcBlocksConfig>,
         ) -> BoxFuture<Result<Vec<Slot>>> {
             debug!(
                 "get_blocks_with_limit rpc request received: {}-{}",
                 start_slot, limit,
             );
-            Box::pin(async move {
-                meta.get_blocks_with_limit(start_slot, limit, commitment)
-                    .await
-            })
+            Box::pin(async move { meta.get_blocks_with_limit(start_slot, limit, config).await })
         }
 
         fn get_block_time(
@@ -3624,14 +5866,50 @@ pub mod rpc_full {
             Box::pin(async move { meta.get_transaction(signature.unwrap(), config).await })
         }
 
+        fn get_transactions(
+            &self,
+            meta: Self::Metadata,
+            signature_strs: Vec<String>,
+            config: Option<RpcTransactionConfig>,
+        ) -> BoxFuture<Result<Vec<RpcTransactionsEntry>>> {
+            debug!(
+                "get_transactions rpc request received: {:?}",
+                signature_strs.len()
+            );
+            if signature_strs.len() > MAX_GET_TRANSACTIONS_QUERY_ITEMS {
+                return Box::pin(future::err(Error::invalid_params(format!(
+                    "Too many inputs provided; max {}",
+                    MAX_GET_TRANSACTIONS_QUERY_ITEMS
+                ))));
+            }
+            Box::pin(async move { meta.get_transactions(signature_strs, config).await })
+        }
+
+        fn get_transaction_retry_status(
+            &self,
+            meta: Self::Metadata,
+            signature_str: String,
+        ) -> Result<Option<RpcTransactionRetryStatus>> {
+            debug!(
+                "get_transaction_retry_status rpc request received: {:?}",
+                signature_str
+            );
+            let signature = verify_signature(&signature_str)?;
+            Ok(meta.get_transaction_retry_status(&signature))
+        }
+
         fn get_signatures_for_address(
             &self,
             meta: Self::Metadata,
             address: String,
             config: Option<RpcSignaturesForAddressConfig>,
-        ) -> BoxFuture<Result<Vec<RpcConfirmedTransactionStatusWithSignature>>> {
+        ) -> BoxFuture<Result<RpcSignaturesForAddress>> {
             let config = config.unwrap_or_default();
             let commitment = config.commitment;
+            let filter = match verify_signatures_for_address_filter(&config) {
+                Ok(filter) => filter,
+                Err(err) => return Box::pin(future::err(err)),
+            };
             let verification = verify_and_parse_signatures_for_address_params(
                 address,
                 config.before,
@@ -3642,8 +5920,16 @@ pub mod rpc_full {
             match verification {
                 Err(err) => Box::pin(future::err(err)),
                 Ok((address, before, until, limit)) => Box::pin(async move {
-                    meta.get_signatures_for_address(address, before, until, limit, commitment)
-                        .await
+                    let signatures = meta
+                        .get_signatures_for_address(
+                            address, before, until, limit, commitment, filter,
+                        )
+                        .await?;
+                    Ok(if filter == AddressSignaturesFilter::default() {
+                        RpcSignaturesForAddress::Unfiltered(signatures.signatures)
+                    } else {
+                        RpcSignaturesForAddress::Filtered(signatures)
+                    })
                 }),
             }
         }
@@ -3677,6 +5963,27 @@ pub mod rpc_full {
             Box::pin(async move { meta.get_inflation_reward(addresses, config).await })
         }
 
+        fn get_vote_account_inflation_rewards(
+            &self,
+            meta: Self::Metadata,
+            vote_pubkey_str: String,
+            config: Option<RpcVoteAccountInflationRewardsConfig>,
+        ) -> BoxFuture<Result<RpcVoteAccountInflationRewards>> {
+            debug!(
+                "get_vote_account_inflation_rewards rpc request received: {:?}",
+                vote_pubkey_str
+            );
+            let vote_pubkey = match verify_pubkey(&vote_pubkey_str) {
+                Ok(vote_pubkey) => vote_pubkey,
+                Err(err) => return Box::pin(future::err(err)),
+            };
+
+            Box::pin(async move {
+                meta.get_vote_account_inflation_rewards(vote_pubkey, config)
+                    .await
+            })
+        }
+
         fn get_latest_blockhash(
             &self,
             meta: Self::Metadata,
@@ -3898,8 +6205,14 @@ pub mod rpc_deprecated_v1_7 {
                 start_slot, end_slot
             );
             Box::pin(async move {
-                meta.get_blocks(start_slot, end_slot, commitment.or(maybe_commitment))
-                    .await
+                meta.get_blocks(
+                    start_slot,
+                    end_slot,
+                    Some(RpcBlocksConfig::with_commitment(
+                        commitment.or(maybe_commitment),
+                    )),
+                )
+                .await
             })
         }
 
@@ -3915,8 +6228,12 @@ pub mod rpc_deprecated_v1_7 {
                 start_slot, limit,
             );
             Box::pin(async move {
-                meta.get_blocks_with_limit(start_slot, limit, commitment)
-                    .await
+                meta.get_blocks_with_limit(
+                    start_slot,
+                    limit,
+                    Some(RpcBlocksConfig::with_commitment(commitment)),
+                )
+                .await
             })
         }
 
@@ -3958,8 +6275,16 @@ pub mod rpc_deprecated_v1_7 {
             match verification {
                 Err(err) => Box::pin(future::err(err)),
                 Ok((address, before, until, limit)) => Box::pin(async move {
-                    meta.get_signatures_for_address(address, before, until, limit, commitment)
-                        .await
+                    meta.get_signatures_for_address(
+                        address,
+                        before,
+                        until,
+                        limit,
+                        commitment,
+                        AddressSignaturesFilter::default(),
+                    )
+                    .await
+                    .map(|signatures| signatures.signatures)
                 }),
             }
         }
@@ -4238,6 +6563,8 @@ pub fn create_test_transactions_and_populate_blockstore(
         crate::transaction_status_service::TransactionStatusService::new(
             transaction_status_receiver,
             max_complete_transaction_status_slot,
+            true,
+            None,
             blockstore,
             &Arc::new(AtomicBool::new(false)),
         );
@@ -4277,7 +6604,10 @@ pub mod tests {
         bincode::deserialize,
         jsonrpc_core::{futures, ErrorCode, MetaIoHandler, Output, Response, Value},
         jsonrpc_core_client::transports::local,
-        solana_client::rpc_filter::{Memcmp, MemcmpEncodedBytes},
+        solana_client::{
+            rpc_custom_error::JSON_RPC_SERVER_ERROR_LARGEST_ACCOUNTS_NOT_AVAILABLE_YET,
+            rpc_filter::{Memcmp, MemcmpEncodedBytes},
+        },
         solana_gossip::{contact_info::ContactInfo, socketaddr},
         solana_ledger::{
             blockstore_meta::PerfSample,
@@ -4297,6 +6627,7 @@ pub mod tests {
             message::Message,
             nonce, rpc_port,
             signature::{Keypair, Signer},
+            stake::state::{Authorized, Meta},
             system_program, system_transaction,
             timing::slot_duration_from_slots_per_year,
             transaction::{self, Transaction, TransactionError},
@@ -4333,6 +6664,19 @@ pub mod tests {
         confirmed_block_signatures: Vec<Signature>,
     }
 
+    /// Sends a request for `method` with `params` and returns the parsed response
+    fn rpc_request(
+        io: &MetaIoHandler<JsonRpcRequestProcessor>,
+        meta: &JsonRpcRequestProcessor,
+        method: &str,
+        params: Value,
+    ) -> Value {
+        let req = json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": params});
+        let res = io.handle_request_sync(&req.to_string(), meta.clone());
+        serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization")
+    }
+
     fn start_rpc_handler_with_tx(pubkey: &Pubkey) -> RpcHandler {
         start_rpc_handler_with_tx_and_blockstore(pubkey, vec![])
     }
@@ -4620,6 +6964,55 @@ pub mod tests {
         assert_eq!(expected, result);
     }
 
+    #[test]
+    fn test_rpc_get_cluster_nodes_with_config() {
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+        let RpcHandler {
+            io,
+            meta,
+            bank,
+            leader_pubkey,
+            ..
+        } = start_rpc_handler_with_tx(&bob_pubkey);
+
+        let get_cluster_nodes = |config: Value| {
+            let result = rpc_request(&io, &meta, "getClusterNodes", json!([config]));
+            result["result"].clone()
+        };
+
+        let nodes = get_cluster_nodes(json!({"rpcOnly": true, "shredVersion": 0}));
+        assert_eq!(nodes.as_array().unwrap().len(), 1);
+        assert_eq!(nodes[0]["pubkey"], json!(leader_pubkey.to_string()));
+        assert_eq!(get_cluster_nodes(json!({"shredVersion": 1})), json!([]));
+        assert_eq!(get_cluster_nodes(json!({"version": "1.0.0"})), json!([]));
+
+        let summary: RpcClusterNodesSummary =
+            serde_json::from_value(get_cluster_nodes(json!({"summary": true}))).unwrap();
+        let leader_stake = bank
+            .epoch_staked_nodes(bank.epoch())
+            .unwrap()
+            .get(&leader_pubkey)
+            .copied()
+            .unwrap_or_default();
+        assert_eq!(
+            summary,
+            RpcClusterNodesSummary {
+                total_nodes: 1,
+                total_stake: bank.total_epoch_stake(),
+                versions: vec![RpcClusterNodesVersion {
+                    version: None,
+                    nodes: 1,
+                    stake: leader_stake,
+                }],
+                feature_sets: vec![RpcClusterNodesFeatureSet {
+                    feature_set: None,
+                    nodes: 1,
+                    stake: leader_stake,
+                }],
+            }
+        );
+    }
+
     #[test]
     fn test_rpc_get_recent_performance_samples() {
         let bob_pubkey = solana_sdk::pubkey::new_rand();
@@ -4730,14 +7123,51 @@ pub mod tests {
         let bob_pubkey = solana_sdk::pubkey::new_rand();
         let RpcHandler { io, meta, .. } = start_rpc_handler_with_tx(&bob_pubkey);
 
-        let req = r#"{"jsonrpc":"2.0","id":1,"method":"minimumLedgerSlot"}"#;
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"minimumLedgerSlot"}"#;
+        let res = io.handle_request_sync(req, meta);
+        let expected = r#"{"jsonrpc":"2.0","result":0,"id":1}"#;
+        let expected: Response =
+            serde_json::from_str(expected).expect("expected response deserialization");
+        let result: Response = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_rpc_scan_thread_pool() {
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+        let RpcHandler { io, meta, .. } = start_rpc_handler_with_tx(&bob_pubkey);
+        assert_eq!(meta.scan_pool.pool.current_num_threads(), 1);
+
+        // Cheap methods are served while every scan thread is busy
+        let (sender, receiver) = channel::<()>();
+        let blocked_scan = meta.spawn_scan(move |_| receiver.recv().unwrap()).unwrap();
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"getSlot"}"#;
+        let res = io.handle_request_sync(req, meta.clone());
+        let json: Value = serde_json::from_str(&res.unwrap()).unwrap();
+        assert!(json["result"].is_u64());
+
+        // Scans are refused once the queue is full
+        let queued_scans: Vec<_> = (1..MAX_PENDING_SCANS_PER_THREAD)
+            .map(|_| meta.spawn_scan(|_| ()).unwrap())
+            .collect();
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"getLargestAccounts"}"#;
+        let res = io.handle_request_sync(req, meta.clone());
+        let json: Value = serde_json::from_str(&res.unwrap()).unwrap();
+        assert_eq!(
+            json["error"]["code"],
+            solana_client::rpc_custom_error::JSON_RPC_SERVER_ERROR_SCAN_QUEUE_FULL
+        );
+
+        sender.send(()).unwrap();
+        futures::executor::block_on(blocked_scan).unwrap();
+        for queued_scan in queued_scans {
+            futures::executor::block_on(queued_scan).unwrap();
+        }
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"getLargestAccounts"}"#;
         let res = io.handle_request_sync(req, meta);
-        let expected = r#"{"jsonrpc":"2.0","result":0,"id":1}"#;
-        let expected: Response =
-            serde_json::from_str(expected).expect("expected response deserialization");
-        let result: Response = serde_json::from_str(&res.expect("actual response"))
-            .expect("actual response deserialization");
-        assert_eq!(expected, result);
+        let json: Value = serde_json::from_str(&res.unwrap()).unwrap();
+        assert!(!json["result"]["value"].as_array().unwrap().is_empty());
     }
 
     #[test]
@@ -4780,6 +7210,68 @@ pub mod tests {
         assert!(supply.non_circulating_accounts.is_empty());
     }
 
+    #[test]
+    fn test_get_supply_with_config() {
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+        let RpcHandler {
+            io,
+            meta,
+            bank,
+            alice,
+            ..
+        } = start_rpc_handler_with_tx(&bob_pubkey);
+        let get_supply = |config: Value| rpc_request(&io, &meta, "getSupply", json!([config]));
+
+        let json = get_supply(json!({"withBreakdown": true}));
+        let supply: RpcSupply = serde_json::from_value(json["result"]["value"].clone())
+            .expect("actual response deserialization");
+        assert_eq!(supply.non_circulating, 20);
+        assert_eq!(
+            supply.non_circulating_breakdown,
+            Some(RpcSupplyBreakdown {
+                listed: 20,
+                ..RpcSupplyBreakdown::default()
+            })
+        );
+
+        let vote_lamports: u64 = bank
+            .get_program_accounts(&solana_vote_program::id())
+            .unwrap()
+            .iter()
+            .map(|(_, account)| account.lamports())
+            .sum();
+        let json = get_supply(json!({
+            "excludeDefaultNonCirculating": true,
+            "nonCirculatingAccounts": [alice.pubkey().to_string()],
+            "nonCirculatingOwners": [solana_vote_program::id().to_string()],
+            "withBreakdown": true,
+        }));
+        let supply: RpcSupply = serde_json::from_value(json["result"]["value"].clone())
+            .expect("actual response deserialization");
+        let alice_lamports = bank.get_balance(&alice.pubkey());
+        assert_eq!(supply.non_circulating, alice_lamports + vote_lamports);
+        assert_eq!(
+            supply.circulating,
+            supply.total - alice_lamports - vote_lamports
+        );
+        assert_eq!(
+            supply.non_circulating_breakdown,
+            Some(RpcSupplyBreakdown {
+                accounts: alice_lamports,
+                owners: vote_lamports,
+                ..RpcSupplyBreakdown::default()
+            })
+        );
+
+        let json = get_supply(json!({
+            "nonCirculatingOwners": vec![
+                bob_pubkey.to_string();
+                MAX_GET_SUPPLY_NON_CIRCULATING_OWNERS + 1
+            ],
+        }));
+        assert_eq!(json["error"]["code"], ErrorCode::InvalidParams.code());
+    }
+
     #[test]
     fn test_get_largest_accounts() {
         let bob_pubkey = solana_sdk::pubkey::new_rand();
@@ -4839,6 +7331,58 @@ pub mod tests {
         assert_eq!(largest_accounts.len(), 1);
     }
 
+    #[test]
+    fn test_refresh_largest_accounts() {
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+        let RpcHandler { io, mut meta, .. } = start_rpc_handler_with_tx(&bob_pubkey);
+        meta.config.largest_accounts_refresh_interval = Some(Duration::from_secs(60));
+
+        // Requests don't scan the accounts themselves until the first refresh
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"getLargestAccounts"}"#;
+        let res = io.handle_request_sync(req, meta.clone());
+        let json: Value = serde_json::from_str(&res.unwrap()).unwrap();
+        assert_eq!(
+            json["error"]["code"],
+            JSON_RPC_SERVER_ERROR_LARGEST_ACCOUNTS_NOT_AVAILABLE_YET
+        );
+
+        // Nor for any other commitment than finalized, which is all the refresh computes
+        let req_confirmed = r#"{"jsonrpc":"2.0","id":1,"method":"getLargestAccounts","params":[{"commitment":"confirmed"}]}"#;
+        let res = io.handle_request_sync(req_confirmed, meta.clone());
+        let json: Value = serde_json::from_str(&res.unwrap()).unwrap();
+        assert_eq!(json["error"]["code"], ErrorCode::InvalidParams.code());
+
+        meta.refresh_largest_accounts();
+        let finalized_slot = meta.bank(Some(CommitmentConfig::finalized())).slot();
+        for (filter, len) in &[
+            (None, 20),
+            (Some(RpcLargestAccountsFilter::Circulating), 20),
+            (Some(RpcLargestAccountsFilter::NonCirculating), 1),
+        ] {
+            let (slot, accounts, _) = meta.get_cached_largest_accounts(filter).unwrap();
+            assert_eq!(slot, finalized_slot);
+            assert_eq!(accounts.len(), *len);
+        }
+        let (_, non_circulating, _) = meta
+            .get_cached_largest_accounts(&Some(RpcLargestAccountsFilter::NonCirculating))
+            .unwrap();
+        let (_, circulating, _) = meta
+            .get_cached_largest_accounts(&Some(RpcLargestAccountsFilter::Circulating))
+            .unwrap();
+        assert!(!circulating.contains(&non_circulating[0]));
+
+        let res = io.handle_request_sync(req, meta.clone());
+        let json: Value = serde_json::from_str(&res.unwrap()).unwrap();
+        let response: RpcLargestAccountsResponse = serde_json::from_value(json["result"].clone())
+            .expect("actual response deserialization");
+        assert_eq!(response.context.slot, finalized_slot);
+        assert_eq!(response.value.len(), 20);
+        assert_eq!(response.staleness, 0);
+        let res = io.handle_request_sync(req_confirmed, meta);
+        let json: Value = serde_json::from_str(&res.unwrap()).unwrap();
+        assert_eq!(json["error"]["code"], ErrorCode::InvalidParams.code());
+    }
+
     #[test]
     fn test_rpc_get_minimum_balance_for_rent_exemption() {
         let bob_pubkey = solana_sdk::pubkey::new_rand();
@@ -5024,6 +7568,55 @@ pub mod tests {
         assert_eq!(schedule, Some(HashMap::default()));
     }
 
+    #[test]
+    fn test_rpc_get_leader_schedule_range() {
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+        let RpcHandler {
+            io,
+            meta,
+            bank,
+            leader_vote_keypair,
+            ..
+        } = start_rpc_handler_with_tx(&bob_pubkey);
+        let get_leader_schedule =
+            |params: Value| rpc_request(&io, &meta, "getLeaderSchedule", params);
+
+        // The last slot of the first epoch and the first two of the next
+        let range =
+            json!({"firstSlot": TEST_SLOTS_PER_EPOCH - 1, "lastSlot": TEST_SLOTS_PER_EPOCH + 1});
+        let result = get_leader_schedule(json!([{ "range": range }]));
+        let schedule: RpcLeaderSchedule = serde_json::from_value(result["result"].clone()).unwrap();
+        assert_eq!(
+            schedule,
+            vec![(bank.collector_id().to_string(), vec![0, 1, 2])]
+                .into_iter()
+                .collect()
+        );
+
+        let result = get_leader_schedule(json!([{ "range": range, "withVoteAccounts": true }]));
+        let schedule: RpcLeaderScheduleWithVoteAccounts =
+            serde_json::from_value(result["result"].clone()).unwrap();
+        let entry = &schedule[&bank.collector_id().to_string()];
+        assert_eq!(entry.slot_indices, vec![0, 1, 2]);
+        assert_eq!(
+            entry.vote_accounts,
+            vec![leader_vote_keypair.pubkey().to_string()]
+        );
+        assert_ne!(entry.stake, 0);
+
+        // Past the cached leader schedules
+        let result = get_leader_schedule(json!([{ "range": {"firstSlot": 42424242} }]));
+        assert_eq!(result["result"], Value::Null);
+
+        for params in [
+            json!([0, { "range": range }]),
+            json!([{ "range": {"firstSlot": 2, "lastSlot": 1} }]),
+        ] {
+            let result = get_leader_schedule(params);
+            assert_eq!(result["error"]["code"], ErrorCode::InvalidParams.code());
+        }
+    }
+
     #[test]
     fn test_rpc_get_slot_leaders() {
         let bob_pubkey = solana_sdk::pubkey::new_rand();
@@ -5156,10 +7749,20 @@ pub mod tests {
             r#"{{"jsonrpc":"2.0","id":1,"method":"getAccountInfo","params":["{}", {{"encoding":"jsonParsed", "dataSlice": {{"length": 2, "offset": 1}}}}]}}"#,
             address
         );
-        let res = io.handle_request_sync(&req, meta);
+        let res = io.handle_request_sync(&req, meta.clone());
         let result: Value = serde_json::from_str(&res.expect("actual response"))
             .expect("actual response deserialization");
         result["error"].as_object().unwrap();
+
+        // The compression level only applies to subscriptions
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getAccountInfo","params":["{}", {{"encoding":"base64+zstd", "compressionLevel": 1}}]}}"#,
+            address
+        );
+        let res = io.handle_request_sync(&req, meta);
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(result["error"]["code"], ErrorCode::InvalidParams.code());
     }
 
     #[test]
@@ -5279,6 +7882,213 @@ pub mod tests {
         result["error"].as_object().unwrap();
     }
 
+    #[test]
+    fn test_rpc_get_multiple_accounts_data_slices_and_owner() {
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+        let RpcHandler { io, meta, bank, .. } = start_rpc_handler_with_tx(&bob_pubkey);
+
+        let program_id = solana_sdk::pubkey::new_rand();
+        let address = solana_sdk::pubkey::new_rand();
+        let data = vec![1, 2, 3, 4, 5];
+        let mut account = AccountSharedData::new(42, 5, &program_id);
+        account.set_data(data.clone());
+        bank.store_account(&address, &account);
+
+        let request = |params: Value| rpc_request(&io, &meta, "getMultipleAccounts", params);
+
+        // A per-key data slice takes the place of the one of the config
+        let result = request(json!([
+            [
+                {"pubkey": address.to_string(), "dataSlice": {"offset": 1, "length": 2}},
+                address.to_string(),
+                {"pubkey": address.to_string()},
+            ],
+            {"dataSlice": {"offset": 4, "length": 1}},
+        ]));
+        let value = &result["result"]["value"];
+        assert_eq!(
+            value[0]["data"],
+            json!([base64::encode(&data[1..3]), "base64"])
+        );
+        assert_eq!(
+            value[1]["data"],
+            json!([base64::encode(&data[4..]), "base64"])
+        );
+        assert_eq!(
+            value[2]["data"],
+            json!([base64::encode(&data[4..]), "base64"])
+        );
+
+        // Accounts of other owners are returned as null
+        let result = request(json!([
+            [address.to_string(), bob_pubkey.to_string()],
+            {"owner": program_id.to_string()},
+        ]));
+        let value = &result["result"]["value"];
+        assert_eq!(value[0]["lamports"], json!(42));
+        assert_eq!(value[1], Value::Null);
+
+        let result = request(json!([
+            [{"pubkey": address.to_string(), "dataSlice": {"offset": 1, "length": 2}}],
+            {"encoding": "jsonParsed"},
+        ]));
+        assert_eq!(
+            result["error"]["code"],
+            json!(ErrorCode::InvalidRequest.code())
+        );
+        let result = request(json!([[address.to_string()], {"owner": "not a pubkey"}]));
+        assert_eq!(
+            result["error"]["code"],
+            json!(ErrorCode::InvalidParams.code())
+        );
+    }
+
+    #[test]
+    fn test_rpc_get_program_accounts_pagination() {
+        let RpcHandler {
+            io, mut meta, bank, ..
+        } = start_rpc_handler_with_tx(&solana_sdk::pubkey::new_rand());
+        meta.program_accounts_cache =
+            Arc::new(ProgramAccountsCache::new(Some(Duration::from_secs(60))));
+
+        let program_id = solana_sdk::pubkey::new_rand();
+        let mut pubkeys: Vec<_> = (0..5).map(|_| solana_sdk::pubkey::new_rand()).collect();
+        for pubkey in &pubkeys {
+            bank.store_account(pubkey, &AccountSharedData::new(1, 0, &program_id));
+        }
+        pubkeys.sort();
+
+        let get_page = |config: Value| {
+            let json = rpc_request(
+                &io,
+                &meta,
+                "getProgramAccounts",
+                json!([program_id.to_string(), config]),
+            );
+            serde_json::from_value::<Vec<RpcKeyedAccount>>(json["result"].clone())
+                .unwrap()
+                .into_iter()
+                .map(|keyed_account| keyed_account.pubkey)
+                .collect::<Vec<_>>()
+        };
+        let expected = |range: std::ops::Range<usize>| {
+            pubkeys[range]
+                .iter()
+                .map(|pubkey| pubkey.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(get_page(json!({"limit": 2})), expected(0..2));
+        assert_eq!(
+            get_page(json!({"limit": 2, "afterPubkey": pubkeys[1].to_string()})),
+            expected(2..4)
+        );
+        assert_eq!(
+            get_page(json!({"limit": 2, "afterPubkey": pubkeys[3].to_string()})),
+            expected(4..5)
+        );
+        assert_eq!(
+            get_page(json!({"afterPubkey": pubkeys[0].to_string()})),
+            expected(1..5)
+        );
+
+        // Later pages are cut from the scan of the first page
+        let new_pubkey = Pubkey::new(&[u8::MAX; 32]);
+        bank.store_account(&new_pubkey, &AccountSharedData::new(1, 0, &program_id));
+        assert_eq!(
+            get_page(json!({"limit": 2, "afterPubkey": pubkeys[3].to_string()})),
+            expected(4..5)
+        );
+
+        for config in [
+            json!({"afterPubkey": "not a pubkey"}),
+            json!({"limit": 0}),
+            json!({"limit": MAX_GET_PROGRAM_ACCOUNTS_LIMIT + 1}),
+        ] {
+            let json = rpc_request(
+                &io,
+                &meta,
+                "getProgramAccounts",
+                json!([program_id.to_string(), config]),
+            );
+            assert_eq!(
+                json["error"]["code"],
+                json!(ErrorCode::InvalidParams.code())
+            );
+        }
+    }
+
+    #[test]
+    fn test_rpc_get_program_accounts_order_and_count() {
+        let RpcHandler { io, meta, bank, .. } =
+            start_rpc_handler_with_tx(&solana_sdk::pubkey::new_rand());
+
+        let program_id = solana_sdk::pubkey::new_rand();
+        // (lamports, data_len) of each account; the last two tie on both
+        let accounts = [(3, 0), (1, 4), (2, 2), (5, 1), (5, 1)];
+        let pubkeys: Vec<_> = accounts
+            .iter()
+            .map(|(lamports, data_len)| {
+                let pubkey = solana_sdk::pubkey::new_rand();
+                let account = AccountSharedData::new(*lamports, *data_len, &program_id);
+                bank.store_account(&pubkey, &account);
+                pubkey
+            })
+            .collect();
+
+        let request_method = |method: &str, config: Value| {
+            rpc_request(&io, &meta, method, json!([program_id.to_string(), config]))
+        };
+        let request = |config: Value| request_method("getProgramAccounts", config);
+        let count = |config: Value| request_method("getProgramAccountsCount", config);
+        let get_accounts = |config: Value| {
+            serde_json::from_value::<Vec<RpcKeyedAccount>>(request(config)["result"].clone())
+                .unwrap()
+                .into_iter()
+                .map(|keyed_account| keyed_account.pubkey)
+                .collect::<Vec<_>>()
+        };
+        let expected = |order: &[usize]| {
+            order
+                .iter()
+                .map(|i| pubkeys[*i].to_string())
+                .collect::<Vec<_>>()
+        };
+        let (first_tie, second_tie) = if pubkeys[3] < pubkeys[4] {
+            (3, 4)
+        } else {
+            (4, 3)
+        };
+
+        assert_eq!(
+            get_accounts(json!({"orderBy": "lamports"})),
+            expected(&[first_tie, second_tie, 0, 2, 1])
+        );
+        assert_eq!(
+            get_accounts(json!({"orderBy": "dataLen", "limit": 3})),
+            expected(&[1, 2, first_tie])
+        );
+
+        assert_eq!(count(json!({}))["result"], json!(5));
+        assert_eq!(
+            count(json!({"withContext": true}))["result"]["value"],
+            json!(5)
+        );
+        assert_eq!(
+            count(json!({"filters": [{"dataSize": 1}]}))["result"],
+            json!(2)
+        );
+
+        let json = request(json!({
+            "orderBy": "lamports",
+            "afterPubkey": pubkeys[0].to_string(),
+        }));
+        assert_eq!(
+            json["error"]["code"],
+            json!(ErrorCode::InvalidParams.code())
+        );
+    }
+
     #[test]
     fn test_rpc_get_program_accounts() {
         let bob = Keypair::new();
@@ -5561,7 +8371,8 @@ pub mod tests {
                         "Program 11111111111111111111111111111111 invoke [1]",
                         "Program 11111111111111111111111111111111 success"
                     ],
-                    "unitsConsumed":0
+                    "unitsConsumed":0,
+                    "innerInstructions":null
                 }
             },
             "id": 1,
@@ -5647,7 +8458,8 @@ pub mod tests {
                         "Program 11111111111111111111111111111111 invoke [1]",
                         "Program 11111111111111111111111111111111 success"
                     ],
-                    "unitsConsumed":0
+                    "unitsConsumed":0,
+                    "innerInstructions":null
                 }
             },
             "id": 1,
@@ -5675,7 +8487,8 @@ pub mod tests {
                         "Program 11111111111111111111111111111111 invoke [1]",
                         "Program 11111111111111111111111111111111 success"
                     ],
-                    "unitsConsumed":0
+                    "unitsConsumed":0,
+                    "innerInstructions":null
                 }
             },
             "id": 1,
@@ -5724,7 +8537,8 @@ pub mod tests {
                     "err":"BlockhashNotFound",
                     "accounts":null,
                     "logs":[],
-                    "unitsConsumed":0
+                    "unitsConsumed":0,
+                    "innerInstructions":null
                 }
             },
             "id":1
@@ -5736,6 +8550,17 @@ pub mod tests {
             .expect("actual response deserialization");
         assert_eq!(expected, result);
 
+        // Inner instructions requested; a system transfer makes no cross-program invocations
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"simulateTransaction","params":["{}", {{"innerInstructions": true}}]}}"#,
+            tx_serialized_encoded,
+        );
+        let res = io.handle_request_sync(&req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(result["result"]["value"]["err"], Value::Null);
+        assert_eq!(result["result"]["value"]["innerInstructions"], json!([]));
+
         // Bad recent blockhash with replaceRecentBlockhash=true
         let req = format!(
             r#"{{"jsonrpc":"2.0","id":1,"method":"simulateTransaction","params":["{}", {{"replaceRecentBlockhash": true}}]}}"#,
@@ -5753,7 +8578,8 @@ pub mod tests {
                         "Program 11111111111111111111111111111111 invoke [1]",
                         "Program 11111111111111111111111111111111 success"
                     ],
-                    "unitsConsumed":0
+                    "unitsConsumed":0,
+                    "innerInstructions":null
                 }
             },
             "id": 1,
@@ -5766,6 +8592,74 @@ pub mod tests {
         assert_eq!(expected, result);
     }
 
+    #[test]
+    fn test_rpc_simulate_bundle() {
+        let RpcHandler {
+            io,
+            meta,
+            blockhash,
+            alice,
+            bank,
+            ..
+        } = start_rpc_handler_with_tx(&solana_sdk::pubkey::new_rand());
+        bank.freeze();
+
+        let bob = Keypair::new();
+        let carol_pubkey = solana_sdk::pubkey::new_rand();
+        let encode = |tx: &Transaction| bs58::encode(serialize(tx).unwrap()).into_string();
+        let bundle = vec![
+            encode(&system_transaction::transfer(
+                &alice,
+                &bob.pubkey(),
+                1234,
+                blockhash,
+            )),
+            encode(&system_transaction::transfer(
+                &bob,
+                &carol_pubkey,
+                100,
+                blockhash,
+            )),
+        ];
+        let simulate_bundle = |bundle: Vec<String>| {
+            rpc_request(
+                &io,
+                &meta,
+                "simulateBundle",
+                json!([
+                    bundle,
+                    {"accounts": {"addresses": [carol_pubkey.to_string()]}},
+                ]),
+            )
+        };
+
+        // Bob pays Carol out of what the first transaction gave him
+        let result = simulate_bundle(bundle.clone());
+        let results: Vec<RpcSimulateTransactionResult> =
+            serde_json::from_value(result["result"]["value"].clone()).unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|result| result.err.is_none()));
+        assert!(results[0].accounts.as_ref().unwrap()[0].is_none());
+        assert_eq!(
+            results[1].accounts.as_ref().unwrap()[0]
+                .as_ref()
+                .unwrap()
+                .lamports,
+            100
+        );
+        assert_eq!(bank.get_balance(&carol_pubkey), 0);
+
+        // Simulation stops at the first failed transaction
+        let result = simulate_bundle(bundle.into_iter().rev().collect());
+        let results: Vec<RpcSimulateTransactionResult> =
+            serde_json::from_value(result["result"]["value"].clone()).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].err, Some(TransactionError::AccountNotFound));
+
+        let result = simulate_bundle(vec![]);
+        assert_eq!(result["error"]["code"], ErrorCode::InvalidParams.code());
+    }
+
     #[test]
     #[should_panic]
     fn test_rpc_simulate_transaction_panic_on_unfrozen_bank() {
@@ -5837,29 +8731,94 @@ pub mod tests {
             r#"{{"jsonrpc":"2.0","id":1,"method":"getSignatureStatuses","params":[["{}"]]}}"#,
             confirmed_block_signatures[1]
         );
-        let res = io.handle_request_sync(&req, meta.clone());
-        let expected_res: transaction::Result<()> = Err(TransactionError::InstructionError(
-            0,
-            InstructionError::Custom(1),
-        ));
-        let json: Value = serde_json::from_str(&res.unwrap()).unwrap();
-        let result: Option<TransactionStatus> =
-            serde_json::from_value(json["result"]["value"][0].clone())
-                .expect("actual response deserialization");
-        assert_eq!(expected_res, result.as_ref().unwrap().status);
+        let res = io.handle_request_sync(&req, meta.clone());
+        let expected_res: transaction::Result<()> = Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(1),
+        ));
+        let json: Value = serde_json::from_str(&res.unwrap()).unwrap();
+        let result: Option<TransactionStatus> =
+            serde_json::from_value(json["result"]["value"][0].clone())
+                .expect("actual response deserialization");
+        assert_eq!(expected_res, result.as_ref().unwrap().status);
+
+        // disable rpc-tx-history, but attempt historical query
+        meta.config.enable_rpc_transaction_history = false;
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getSignatureStatuses","params":[["{}"], {{"searchTransactionHistory": true}}]}}"#,
+            confirmed_block_signatures[1]
+        );
+        let res = io.handle_request_sync(&req, meta);
+        assert_eq!(
+            res,
+            Some(
+                r#"{"jsonrpc":"2.0","error":{"code":-32011,"message":"Transaction history is not available from this node"},"id":1}"#.to_string(),
+            )
+        );
+    }
+
+    #[test]
+    fn test_rpc_get_transactions() {
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+        let RpcHandler {
+            io,
+            mut meta,
+            confirmed_block_signatures,
+            ..
+        } = start_rpc_handler_with_tx(&bob_pubkey);
+
+        let get_transactions = |meta: &JsonRpcRequestProcessor, signatures: Vec<String>| {
+            rpc_request(
+                &io,
+                meta,
+                "getTransactions",
+                json!([signatures, {"encoding": "json"}]),
+            )
+        };
+
+        let unknown_signature = Signature::new(&[7u8; 64]).to_string();
+        let result = get_transactions(
+            &meta,
+            vec![
+                confirmed_block_signatures[0].to_string(),
+                "not-a-signature".to_string(),
+                unknown_signature.clone(),
+            ],
+        );
+        let entries: Vec<RpcTransactionsEntry> =
+            serde_json::from_value(result["result"].clone()).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(
+            entries[0].signature,
+            confirmed_block_signatures[0].to_string()
+        );
+        assert_eq!(entries[0].transaction.as_ref().unwrap().slot, 0);
+        assert_eq!(entries[0].error, None);
+        assert_eq!(entries[1].transaction, None);
+        assert_eq!(entries[1].error.as_deref(), Some("Invalid param: Invalid"));
+        assert_eq!(
+            entries[2],
+            RpcTransactionsEntry {
+                signature: unknown_signature.clone(),
+                transaction: None,
+                error: None,
+            }
+        );
+
+        let result = get_transactions(
+            &meta,
+            vec![unknown_signature.clone(); MAX_GET_TRANSACTIONS_QUERY_ITEMS + 1],
+        );
+        assert_eq!(
+            result["error"]["message"],
+            "Too many inputs provided; max 100"
+        );
 
-        // disable rpc-tx-history, but attempt historical query
         meta.config.enable_rpc_transaction_history = false;
-        let req = format!(
-            r#"{{"jsonrpc":"2.0","id":1,"method":"getSignatureStatuses","params":[["{}"], {{"searchTransactionHistory": true}}]}}"#,
-            confirmed_block_signatures[1]
-        );
-        let res = io.handle_request_sync(&req, meta);
+        let result = get_transactions(&meta, vec![unknown_signature]);
         assert_eq!(
-            res,
-            Some(
-                r#"{"jsonrpc":"2.0","error":{"code":-32011,"message":"Transaction history is not available from this node"},"id":1}"#.to_string(),
-            )
+            result["error"]["message"],
+            "Transaction history is not available from this node"
         );
     }
 
@@ -6043,6 +9002,145 @@ pub mod tests {
         assert_eq!(error["code"], ErrorCode::InvalidParams.code());
     }
 
+    #[test]
+    fn test_rpc_send_transaction_retry_until_expired() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(100);
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+        let meta = JsonRpcRequestProcessor::new_from_bank(&bank, SocketAddrSpace::Unspecified);
+
+        let mut io = MetaIoHandler::default();
+        io.extend_with(rpc_full::FullImpl.to_delegate());
+
+        let blockhash = bank.last_blockhash();
+        let transaction = system_transaction::transfer(
+            &mint_keypair,
+            &solana_sdk::pubkey::new_rand(),
+            42,
+            blockhash,
+        );
+        let send_transaction = |config: Value| {
+            rpc_request(
+                &io,
+                &meta,
+                "sendTransaction",
+                json!([
+                    bs58::encode(serialize(&transaction).unwrap()).into_string(),
+                    config
+                ]),
+            )
+        };
+        let get_retry_status = |signature: &Signature| {
+            let json = rpc_request(
+                &io,
+                &meta,
+                "getTransactionRetryStatus",
+                json!([signature.to_string()]),
+            );
+            serde_json::from_value::<Option<RpcTransactionRetryStatus>>(json["result"].clone())
+                .unwrap()
+        };
+
+        let result = send_transaction(
+            json!({"skipPreflight": true, "retryUntilExpired": true, "maxRetries": 1}),
+        );
+        assert_eq!(result["error"]["code"], ErrorCode::InvalidParams.code());
+
+        assert_eq!(get_retry_status(&transaction.signatures[0]), None);
+        let result = send_transaction(json!({"skipPreflight": true, "retryUntilExpired": true}));
+        assert_eq!(result["result"], transaction.signatures[0].to_string());
+        let status = get_retry_status(&transaction.signatures[0]).unwrap();
+        assert_eq!(status.state, RpcTransactionRetryState::Pending);
+        assert_eq!(
+            Some(status.last_valid_block_height),
+            bank.get_blockhash_last_valid_block_height(&blockhash)
+        );
+    }
+
+    #[test]
+    fn test_rpc_send_transaction_units_consumed() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(100);
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+        bank.freeze();
+        let meta = JsonRpcRequestProcessor::new_from_bank(&bank, SocketAddrSpace::Unspecified);
+
+        let mut io = MetaIoHandler::default();
+        io.extend_with(rpc_full::FullImpl.to_delegate());
+
+        let transaction = system_transaction::transfer(
+            &mint_keypair,
+            &solana_sdk::pubkey::new_rand(),
+            42,
+            bank.last_blockhash(),
+        );
+        let send_transaction = |config: Value| {
+            rpc_request(
+                &io,
+                &meta,
+                "sendTransaction",
+                json!([
+                    bs58::encode(serialize(&transaction).unwrap()).into_string(),
+                    config
+                ]),
+            )
+        };
+
+        let result = send_transaction(json!({"skipPreflight": true, "returnUnitsConsumed": true}));
+        assert_eq!(result["error"]["code"], ErrorCode::InvalidParams.code());
+        let result =
+            send_transaction(json!({"skipPreflight": true, "preflightAgainstConfirmed": true}));
+        assert_eq!(result["error"]["code"], ErrorCode::InvalidParams.code());
+
+        let result = send_transaction(json!({"preflightAgainstConfirmed": true}));
+        assert_eq!(result["result"], transaction.signatures[0].to_string());
+
+        let result = send_transaction(
+            json!({"preflightAgainstConfirmed": true, "returnUnitsConsumed": true}),
+        );
+        let result: RpcSendTransactionResult =
+            serde_json::from_value(result["result"].clone()).unwrap();
+        assert_eq!(result.signature, transaction.signatures[0].to_string());
+        let units_consumed = |bank: &Bank, transaction: &Transaction| {
+            bank.simulate_transaction(
+                SanitizedTransaction::from_transaction_for_tests(transaction.clone()),
+                false,
+            )
+            .units_consumed
+        };
+        assert_eq!(result.units_consumed, units_consumed(&bank, &transaction));
+
+        // The transaction fails against a confirmed bank that is ahead of the finalized one,
+        // without the mint account, while the preflight bank still accepts it
+        let confirmed_bank = Bank::new_from_parent(&bank, &Pubkey::default(), 1);
+        confirmed_bank.store_account(&mint_keypair.pubkey(), &AccountSharedData::default());
+        confirmed_bank.freeze();
+        let confirmed_bank = meta.bank_forks.write().unwrap().insert(confirmed_bank);
+        meta.optimistically_confirmed_bank.write().unwrap().bank = confirmed_bank.clone();
+        assert_ne!(meta.bank(None).slot(), confirmed_bank.slot());
+
+        let result = send_transaction(json!({"returnUnitsConsumed": true}));
+        let result: RpcSendTransactionResult =
+            serde_json::from_value(result["result"].clone()).unwrap();
+        assert_eq!(result.units_consumed, units_consumed(&bank, &transaction));
+
+        let result = send_transaction(
+            json!({"preflightAgainstConfirmed": true, "returnUnitsConsumed": true}),
+        );
+        assert_eq!(result["error"]["code"], -32002);
+        assert_eq!(result["error"]["data"]["err"], "AccountNotFound");
+        assert_eq!(
+            result["error"]["data"]["unitsConsumed"],
+            units_consumed(&confirmed_bank, &transaction)
+        );
+    }
+
     #[test]
     fn test_rpc_send_transaction_preflight() {
         let exit = Arc::new(AtomicBool::new(false));
@@ -6106,7 +9204,7 @@ pub mod tests {
         assert_eq!(
             res,
             Some(
-                r#"{"jsonrpc":"2.0","error":{"code":-32002,"message":"Transaction simulation failed: Blockhash not found","data":{"accounts":null,"err":"BlockhashNotFound","logs":[],"unitsConsumed":0}},"id":1}"#.to_string(),
+                r#"{"jsonrpc":"2.0","error":{"code":-32002,"message":"Transaction simulation failed: Blockhash not found","data":{"accounts":null,"err":"BlockhashNotFound","logs":[],"unitsConsumed":0,"innerInstructions":null}},"id":1}"#.to_string(),
             )
         );
 
@@ -6283,6 +9381,132 @@ pub mod tests {
         assert_eq!(expected, result);
     }
 
+    #[test]
+    fn test_rpc_get_health_extended() {
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+        let RpcHandler { io, meta, .. } = start_rpc_handler_with_tx(&bob_pubkey);
+
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"getHealth"}"#;
+        let res = io.handle_request_sync(req, meta.clone());
+        assert_eq!(
+            res,
+            Some(r#"{"jsonrpc":"2.0","result":"ok","id":1}"#.to_string())
+        );
+
+        meta.health
+            .stub_set_health_status(Some(RpcHealthStatus::Behind { num_slots: 42 }));
+        let res = io.handle_request_sync(req, meta.clone());
+        let json: Value = serde_json::from_str(&res.unwrap()).unwrap();
+        assert_eq!(json["error"]["data"]["numSlotsBehind"], 42);
+
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"getHealth","params":[{"extended":true}]}"#;
+        let res = io.handle_request_sync(req, meta);
+        let json: Value = serde_json::from_str(&res.unwrap()).unwrap();
+        let report: RpcHealthReport = serde_json::from_value(json["result"].clone()).unwrap();
+        assert_eq!(
+            report,
+            RpcHealthReport {
+                status: RpcHealthState::Behind,
+                num_slots_behind: Some(42),
+                latest_accounts_hash_slot: None,
+                latest_trusted_validator_accounts_hash_slot: None,
+                latest_snapshot_slot: None,
+                latest_snapshot_age_secs: None,
+                snapshot_slots_behind_root: None,
+                pubsub_notification_queue_depth: None,
+                pubsub_notification_queue_capacity: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_rpc_get_vote_account_inflation_rewards_params() {
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+        let RpcHandler {
+            io,
+            meta,
+            leader_vote_keypair,
+            ..
+        } = start_rpc_handler_with_tx(&bob_pubkey);
+
+        for config in [
+            json!({"limit": 0}),
+            json!({"limit": MAX_GET_VOTE_ACCOUNT_INFLATION_REWARDS_LIMIT + 1}),
+            json!({"afterPubkey": "a1b2c3d4"}),
+        ] {
+            let json = rpc_request(
+                &io,
+                &meta,
+                "getVoteAccountInflationRewards",
+                json!([leader_vote_keypair.pubkey().to_string(), config]),
+            );
+            assert_eq!(json["error"]["code"], ErrorCode::InvalidParams.code());
+        }
+    }
+
+    #[test]
+    fn test_vote_account_rewards() {
+        let vote_pubkey = solana_sdk::pubkey::new_rand().to_string();
+        let other_vote_pubkey = solana_sdk::pubkey::new_rand().to_string();
+        let mut stake_pubkeys: Vec<_> = (0..3).map(|_| solana_sdk::pubkey::new_rand()).collect();
+        stake_pubkeys.sort();
+        let other_stake_pubkey = solana_sdk::pubkey::new_rand().to_string();
+        let reward = |pubkey: &str, reward_type: RewardType, lamports: i64| Reward {
+            pubkey: pubkey.to_string(),
+            lamports,
+            post_balance: 1_000 + lamports as u64,
+            reward_type: Some(reward_type),
+            commission: (reward_type == RewardType::Voting).then(|| 10),
+        };
+        let rewards = vec![
+            reward(&stake_pubkeys[2].to_string(), RewardType::Staking, 30),
+            reward(&other_vote_pubkey, RewardType::Voting, 5),
+            reward(&stake_pubkeys[0].to_string(), RewardType::Staking, 10),
+            reward(&other_stake_pubkey, RewardType::Staking, 40),
+            reward(&vote_pubkey, RewardType::Voting, 7),
+            reward(&vote_pubkey, RewardType::Fee, 2),
+            reward(&stake_pubkeys[1].to_string(), RewardType::Staking, 20),
+        ];
+        let delegators: HashSet<String> = stake_pubkeys.iter().map(|p| p.to_string()).collect();
+        let stake_reward = |i: usize| RpcStakeInflationReward {
+            stake_pubkey: stake_pubkeys[i].to_string(),
+            amount: 10 * (i as u64 + 1),
+            post_balance: 1_000 + 10 * (i as u64 + 1),
+        };
+
+        // Only the vote reward and the rewards of delegators are picked, ordered by stake pubkey
+        let (vote_reward, stake_rewards) =
+            vote_account_rewards(rewards.clone(), &vote_pubkey, &delegators, None, 1_000).unwrap();
+        assert_eq!(
+            vote_reward,
+            Some(reward(&vote_pubkey, RewardType::Voting, 7))
+        );
+        assert_eq!(
+            stake_rewards,
+            vec![stake_reward(0), stake_reward(1), stake_reward(2)]
+        );
+
+        // Pages start after `after_pubkey`
+        let (_, stake_rewards) =
+            vote_account_rewards(rewards.clone(), &vote_pubkey, &delegators, None, 2).unwrap();
+        assert_eq!(stake_rewards, vec![stake_reward(0), stake_reward(1)]);
+        let (_, stake_rewards) = vote_account_rewards(
+            rewards.clone(),
+            &vote_pubkey,
+            &delegators,
+            Some(stake_pubkeys[1]),
+            2,
+        )
+        .unwrap();
+        assert_eq!(stake_rewards, vec![stake_reward(2)]);
+
+        // A vote account without rewards has none
+        let (vote_reward, stake_rewards) =
+            vote_account_rewards(rewards, &other_stake_pubkey, &HashSet::new(), None, 2).unwrap();
+        assert_eq!(vote_reward, None);
+        assert!(stake_rewards.is_empty());
+    }
+
     fn test_basic_slot(method: &str, expected: Slot) {
         let bob_pubkey = solana_sdk::pubkey::new_rand();
         let RpcHandler { io, meta, .. } = start_rpc_handler_with_tx(&bob_pubkey);
@@ -6617,6 +9841,101 @@ pub mod tests {
         assert_eq!(confirmed_block.rewards.unwrap(), vec![]);
     }
 
+    #[test]
+    fn test_get_signatures_for_address_filter_params() {
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+        let RpcHandler { io, meta, .. } =
+            start_rpc_handler_with_tx_and_blockstore(&bob_pubkey, vec![1]);
+
+        let get_error_message = |config: Value| {
+            let result = rpc_request(
+                &io,
+                &meta,
+                "getSignaturesForAddress",
+                json!([bob_pubkey.to_string(), config]),
+            );
+            assert_eq!(result["error"]["code"], ErrorCode::InvalidParams.code());
+            result["error"]["message"].as_str().unwrap().to_string()
+        };
+
+        assert_eq!(
+            get_error_message(json!({"failedOnly": true, "succeededOnly": true})),
+            "failedOnly and succeededOnly cannot both be set"
+        );
+        assert_eq!(
+            get_error_message(json!({"minSlot": 3, "maxSlot": 2})),
+            "Invalid slot range; minSlot 3 is greater than maxSlot 2"
+        );
+        assert_eq!(
+            get_error_message(json!({"programId": "not a pubkey"})),
+            "Invalid param: Invalid"
+        );
+
+        let filter = verify_signatures_for_address_filter(&RpcSignaturesForAddressConfig {
+            failed_only: Some(true),
+            program_id: Some(bob_pubkey.to_string()),
+            min_slot: Some(2),
+            ..RpcSignaturesForAddressConfig::default()
+        })
+        .unwrap();
+        assert_eq!(
+            filter,
+            AddressSignaturesFilter {
+                failed_only: true,
+                succeeded_only: false,
+                program_id: Some(bob_pubkey),
+                min_slot: Some(2),
+                max_slot: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_get_block_fields() {
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+        let RpcHandler {
+            io,
+            meta,
+            confirmed_block_signatures,
+            ..
+        } = start_rpc_handler_with_tx(&bob_pubkey);
+
+        let get_block =
+            |fields: Value| rpc_request(&io, &meta, "getBlock", json!([0, {"fields": fields}]));
+
+        let result = get_block(json!([
+            "blockTime",
+            "transactions.meta.err",
+            "transactions.transaction.signatures",
+        ]));
+        let block = result["result"].as_object().unwrap();
+        assert_eq!(
+            block.keys().collect::<Vec<_>>(),
+            vec!["blockTime", "transactions"]
+        );
+        let transactions = block["transactions"].as_array().unwrap();
+        for (transaction, signature) in transactions.iter().zip(&confirmed_block_signatures) {
+            assert_eq!(
+                transaction["transaction"],
+                json!({"signatures": [signature.to_string()]})
+            );
+            assert_eq!(transaction["meta"].as_object().unwrap().len(), 1);
+            assert!(transaction["meta"].get("err").is_some());
+        }
+
+        // A whole field takes precedence over its subfields, and unknown fields are ignored
+        let result = get_block(json!(["transactions.meta", "transactions", "unknown"]));
+        let block = result["result"].as_object().unwrap();
+        assert_eq!(block.keys().collect::<Vec<_>>(), vec!["transactions"]);
+        assert!(block["transactions"][0]["transaction"]["message"].is_object());
+
+        let result = get_block(json!(["transactions..meta"]));
+        assert_eq!(
+            result["error"]["code"],
+            json!(ErrorCode::InvalidParams.code())
+        );
+    }
+
     #[test]
     fn test_get_block_production() {
         let bob_pubkey = solana_sdk::pubkey::new_rand();
@@ -6672,6 +9991,35 @@ pub mod tests {
             }
         );
 
+        let result = rpc_request(
+            &io,
+            &meta,
+            "getBlockProduction",
+            json!([{"epoch": 0, "withSkippedSlots": true}]),
+        );
+        let block_production: RpcBlockProduction =
+            serde_json::from_value(result["result"]["value"].clone()).unwrap();
+        assert_eq!(
+            block_production.by_identity.get(&leader_pubkey.to_string()),
+            Some(&(9, 5))
+        );
+        assert_eq!(
+            block_production.skipped_slots_by_identity,
+            Some(
+                vec![(leader_pubkey.to_string(), vec![2, 5, 6, 7])]
+                    .into_iter()
+                    .collect()
+            )
+        );
+
+        let result = rpc_request(
+            &io,
+            &meta,
+            "getBlockProduction",
+            json!([{"epoch": 0, "range": {"firstSlot": 0}}]),
+        );
+        assert_eq!(result["error"]["code"], ErrorCode::InvalidParams.code());
+
         let req = format!(
             r#"{{"jsonrpc":"2.0","id":1,"method":"getBlockProduction","params":[{{"range": {{"firstSlot": 0, "lastSlot": 4}}, "identity": "{}"}}]}}"#,
             bob_pubkey
@@ -6795,40 +10143,168 @@ pub mod tests {
             )
         );
 
-        let req = r#"{"jsonrpc":"2.0","id":1,"method":"getBlocksWithLimit","params":[0,0]}"#;
-        let res = io.handle_request_sync(req, meta.clone());
-        let result: Value = serde_json::from_str(&res.expect("actual response"))
-            .expect("actual response deserialization");
-        let confirmed_blocks: Vec<Slot> = serde_json::from_value(result["result"].clone()).unwrap();
-        assert!(confirmed_blocks.is_empty());
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"getBlocksWithLimit","params":[0,0]}"#;
+        let res = io.handle_request_sync(req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        let confirmed_blocks: Vec<Slot> = serde_json::from_value(result["result"].clone()).unwrap();
+        assert!(confirmed_blocks.is_empty());
+
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"getBlocksWithLimit","params":[2,2]}"#;
+        let res = io.handle_request_sync(req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        let confirmed_blocks: Vec<Slot> = serde_json::from_value(result["result"].clone()).unwrap();
+        assert_eq!(confirmed_blocks, vec![3, 4]);
+
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"getBlocksWithLimit","params":[2,3]}"#;
+        let res = io.handle_request_sync(req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        let confirmed_blocks: Vec<Slot> = serde_json::from_value(result["result"].clone()).unwrap();
+        assert_eq!(confirmed_blocks, vec![3, 4, 8]);
+
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"getBlocksWithLimit","params":[2,500000]}"#;
+        let res = io.handle_request_sync(req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        let confirmed_blocks: Vec<Slot> = serde_json::from_value(result["result"].clone()).unwrap();
+        assert_eq!(confirmed_blocks, vec![3, 4, 8]);
+
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"getBlocksWithLimit","params":[9,500000]}"#;
+        let res = io.handle_request_sync(req, meta);
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        let confirmed_blocks: Vec<Slot> = serde_json::from_value(result["result"].clone()).unwrap();
+        assert_eq!(confirmed_blocks, Vec::<Slot>::new());
+    }
+
+    #[test]
+    fn test_get_blocks_finalized_only() {
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+        let roots = vec![0, 1, 3, 4, 8];
+        let RpcHandler {
+            io,
+            meta,
+            block_commitment_cache,
+            ..
+        } = start_rpc_handler_with_tx_and_blockstore(&bob_pubkey, roots);
+        block_commitment_cache
+            .write()
+            .unwrap()
+            .set_highest_confirmed_root(4);
+
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"getBlocks","params":[0,{"commitment":"processed"}]}"#;
+        let res = io.handle_request_sync(req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(result["error"]["code"], ErrorCode::InvalidParams.code());
+
+        // Every requested commitment is served as finalized
+        let meta = JsonRpcRequestProcessor {
+            config: JsonRpcConfig {
+                enable_rpc_transaction_history: true,
+                finalized_only: true,
+                ..JsonRpcConfig::default()
+            },
+            ..meta
+        };
+        for commitment in ["processed", "confirmed", "finalized"] {
+            let req = format!(
+                r#"{{"jsonrpc":"2.0","id":1,"method":"getBlocks","params":[0,{{"commitment":"{}"}}]}}"#,
+                commitment
+            );
+            let res = io.handle_request_sync(&req, meta.clone());
+            let result: Value = serde_json::from_str(&res.expect("actual response"))
+                .expect("actual response deserialization");
+            let blocks: Vec<Slot> = serde_json::from_value(result["result"].clone()).unwrap();
+            assert_eq!(blocks, vec![1, 3, 4]);
+        }
+    }
+
+    #[test]
+    fn test_get_blocks_reverse_and_by_time() {
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+        let roots = vec![0, 1, 3, 4, 8];
+        let RpcHandler {
+            io,
+            meta,
+            block_commitment_cache,
+            ..
+        } = start_rpc_handler_with_tx_and_blockstore(&bob_pubkey, roots);
+        block_commitment_cache
+            .write()
+            .unwrap()
+            .set_highest_confirmed_root(8);
+        let base_time = meta.genesis_creation_time();
+        for (slot, offset) in [(1, 10), (3, 20), (4, 30), (8, 40)] {
+            meta.blockstore
+                .cache_block_time(slot, base_time + offset)
+                .unwrap();
+        }
+
+        let get_blocks = |method: &str, params: Value| {
+            let result = rpc_request(&io, &meta, method, params);
+            serde_json::from_value::<Vec<Slot>>(result["result"].clone()).unwrap()
+        };
 
-        let req = r#"{"jsonrpc":"2.0","id":1,"method":"getBlocksWithLimit","params":[2,2]}"#;
-        let res = io.handle_request_sync(req, meta.clone());
-        let result: Value = serde_json::from_str(&res.expect("actual response"))
-            .expect("actual response deserialization");
-        let confirmed_blocks: Vec<Slot> = serde_json::from_value(result["result"].clone()).unwrap();
-        assert_eq!(confirmed_blocks, vec![3, 4]);
+        assert_eq!(
+            get_blocks("getBlocks", json!([1, 8, {"reverse": true}])),
+            vec![8, 4, 3, 1]
+        );
+        assert_eq!(
+            get_blocks("getBlocksWithLimit", json!([8, 2, {"reverse": true}])),
+            vec![8, 4]
+        );
+        assert_eq!(
+            get_blocks("getBlocksWithLimit", json!([7, 3, {"reverse": true}])),
+            vec![4, 3, 1]
+        );
 
-        let req = r#"{"jsonrpc":"2.0","id":1,"method":"getBlocksWithLimit","params":[2,3]}"#;
-        let res = io.handle_request_sync(req, meta.clone());
-        let result: Value = serde_json::from_str(&res.expect("actual response"))
-            .expect("actual response deserialization");
-        let confirmed_blocks: Vec<Slot> = serde_json::from_value(result["result"].clone()).unwrap();
-        assert_eq!(confirmed_blocks, vec![3, 4, 8]);
+        let time_range = json!({
+            "minBlockTime": base_time + 15,
+            "maxBlockTime": base_time + 30,
+        });
+        assert_eq!(
+            get_blocks("getBlocks", json!([1, time_range.clone()])),
+            vec![3, 4]
+        );
+        assert_eq!(
+            get_blocks("getBlocksWithLimit", json!([1, 10, time_range])),
+            vec![3, 4]
+        );
+        assert_eq!(
+            get_blocks(
+                "getBlocksWithLimit",
+                json!([8, 10, {"reverse": true, "minBlockTime": base_time + 20}])
+            ),
+            vec![8, 4, 3]
+        );
+        assert_eq!(
+            get_blocks("getBlocks", json!([1, {"minBlockTime": base_time + 41}])),
+            Vec::<Slot>::new()
+        );
+    }
 
-        let req = r#"{"jsonrpc":"2.0","id":1,"method":"getBlocksWithLimit","params":[2,500000]}"#;
-        let res = io.handle_request_sync(req, meta.clone());
-        let result: Value = serde_json::from_str(&res.expect("actual response"))
-            .expect("actual response deserialization");
-        let confirmed_blocks: Vec<Slot> = serde_json::from_value(result["result"].clone()).unwrap();
-        assert_eq!(confirmed_blocks, vec![3, 4, 8]);
+    #[test]
+    fn test_block_time_samples() {
+        let samples = BlockTimeSamples::default();
+        for (slot, block_time) in [(2, 10), (5, 20), (9, 30)] {
+            samples.insert(slot, block_time);
+        }
+        assert_eq!(samples.bounds(20, 0, 10), (Some((2, 10)), Some((5, 20))));
+        assert_eq!(samples.bounds(25, 0, 10), (Some((5, 20)), Some((9, 30))));
+        assert_eq!(samples.bounds(31, 0, 10), (Some((9, 30)), None));
+        assert_eq!(samples.bounds(5, 3, 8), (None, Some((5, 20))));
+        assert_eq!(samples.bounds(5, 6, 8), (None, None));
+        assert_eq!(samples.bounds(5, 8, 6), (None, None));
 
-        let req = r#"{"jsonrpc":"2.0","id":1,"method":"getBlocksWithLimit","params":[9,500000]}"#;
-        let res = io.handle_request_sync(req, meta);
-        let result: Value = serde_json::from_str(&res.expect("actual response"))
-            .expect("actual response deserialization");
-        let confirmed_blocks: Vec<Slot> = serde_json::from_value(result["result"].clone()).unwrap();
-        assert_eq!(confirmed_blocks, Vec::<Slot>::new());
+        for slot in 10..MAX_BLOCK_TIME_SAMPLES as Slot + 10 {
+            samples.insert(slot, 40);
+        }
+        // The oldest slots make room for the new samples
+        assert_eq!(samples.0.read().unwrap().len(), MAX_BLOCK_TIME_SAMPLES);
+        assert_eq!(samples.bounds(0, 0, 10), (None, Some((10, 40))));
     }
 
     #[test]
@@ -7077,6 +10553,32 @@ pub mod tests {
             }
         }
 
+        // Filter request based on stake, leaving out the vote account with no stake
+        {
+            let req = format!(
+                r#"{{"jsonrpc":"2.0","id":1,"method":"getVoteAccounts","params":{}}}"#,
+                json!([RpcGetVoteAccountsConfig {
+                    commitment: Some(CommitmentConfig::processed()),
+                    min_activated_stake: Some(1),
+                    min_epoch_credits: Some(1),
+                    ..RpcGetVoteAccountsConfig::default()
+                }])
+            );
+
+            let res = io.handle_request_sync(&req, meta.clone());
+            let result: Value = serde_json::from_str(&res.expect("actual response"))
+                .expect("actual response deserialization");
+
+            let vote_account_status: RpcVoteAccountStatus =
+                serde_json::from_value(result["result"].clone()).unwrap();
+
+            assert_eq!(vote_account_status.current.len(), 1);
+            assert_eq!(
+                vote_account_status.current[0].vote_pubkey,
+                leader_vote_keypair.pubkey().to_string()
+            );
+        }
+
         // Overflow the epoch credits history and ensure only `MAX_RPC_EPOCH_CREDITS_HISTORY`
         // results are returned
         for _ in 0..(TEST_SLOTS_PER_EPOCH * (MAX_RPC_EPOCH_CREDITS_HISTORY) as u64) {
@@ -7136,6 +10638,141 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_get_vote_accounts_changed_since_slot() {
+        let RpcHandler {
+            io,
+            meta,
+            mut bank,
+            bank_forks,
+            alice,
+            leader_vote_keypair,
+            block_commitment_cache,
+            ..
+        } = start_rpc_handler_with_tx(&solana_sdk::pubkey::new_rand());
+
+        let create_vote_account = |bank: &Bank, vote_keypair: &Keypair| {
+            let instructions = vote_instruction::create_account(
+                &alice.pubkey(),
+                &vote_keypair.pubkey(),
+                &VoteInit {
+                    node_pubkey: alice.pubkey(),
+                    authorized_voter: vote_keypair.pubkey(),
+                    authorized_withdrawer: vote_keypair.pubkey(),
+                    commission: 0,
+                },
+                bank.get_minimum_balance_for_rent_exemption(VoteState::size_of()),
+            );
+            let message = Message::new(&instructions, Some(&alice.pubkey()));
+            let transaction =
+                Transaction::new(&[&alice, vote_keypair], message, bank.last_blockhash());
+            bank.process_transaction(&transaction)
+                .expect("process transaction");
+        };
+        let get_vote_accounts = |changed_since_slot: Slot| {
+            let req = format!(
+                r#"{{"jsonrpc":"2.0","id":1,"method":"getVoteAccounts","params":{}}}"#,
+                json!([RpcGetVoteAccountsConfig {
+                    commitment: Some(CommitmentConfig::processed()),
+                    keep_unstaked_delinquents: Some(true),
+                    changed_since_slot: Some(changed_since_slot),
+                    ..RpcGetVoteAccountsConfig::default()
+                }])
+            );
+            let res = io.handle_request_sync(&req, meta.clone());
+            let result: Value = serde_json::from_str(&res.expect("actual response"))
+                .expect("actual response deserialization");
+            let vote_account_status: RpcVoteAccountStatus =
+                serde_json::from_value(result["result"].clone()).unwrap();
+            let vote_pubkeys = vote_account_status
+                .current
+                .iter()
+                .chain(vote_account_status.delinquent.iter())
+                .map(|vote_account_info| vote_account_info.vote_pubkey.clone())
+                .collect::<HashSet<_>>();
+            (
+                vote_pubkeys,
+                vote_account_status.removed,
+                vote_account_status.slot,
+            )
+        };
+        let vote_pubkeys = |vote_keypairs: &[&Keypair]| {
+            vote_keypairs
+                .iter()
+                .map(|vote_keypair| vote_keypair.pubkey().to_string())
+                .collect::<HashSet<_>>()
+        };
+
+        let changed_vote_keypair = Keypair::new();
+        let removed_vote_keypair = Keypair::new();
+        create_vote_account(&bank, &changed_vote_keypair);
+        create_vote_account(&bank, &removed_vote_keypair);
+        bank.freeze();
+        let slot = bank.slot();
+
+        // The accounts of the slot aren't kept yet, so every account is returned
+        assert_eq!(
+            get_vote_accounts(slot),
+            (
+                vote_pubkeys(&[
+                    &leader_vote_keypair,
+                    &changed_vote_keypair,
+                    &removed_vote_keypair
+                ]),
+                None,
+                Some(slot)
+            )
+        );
+        assert_eq!(
+            get_vote_accounts(slot),
+            (HashSet::new(), Some(vec![]), Some(slot))
+        );
+
+        bank = bank_forks.write().unwrap().insert(Bank::new_from_parent(
+            &bank,
+            &Pubkey::default(),
+            slot + 1,
+        ));
+        advance_block_commitment_cache(&block_commitment_cache, &bank_forks);
+
+        let added_vote_keypair = Keypair::new();
+        create_vote_account(&bank, &added_vote_keypair);
+        let transaction = Transaction::new_signed_with_payer(
+            &[
+                vote_instruction::update_commission(
+                    &changed_vote_keypair.pubkey(),
+                    &changed_vote_keypair.pubkey(),
+                    10,
+                ),
+                vote_instruction::withdraw(
+                    &removed_vote_keypair.pubkey(),
+                    &removed_vote_keypair.pubkey(),
+                    bank.get_balance(&removed_vote_keypair.pubkey()),
+                    &alice.pubkey(),
+                ),
+            ],
+            Some(&alice.pubkey()),
+            &[&alice, &changed_vote_keypair, &removed_vote_keypair],
+            bank.last_blockhash(),
+        );
+        bank.process_transaction(&transaction)
+            .expect("process transaction");
+        bank.freeze();
+
+        assert_eq!(
+            get_vote_accounts(slot),
+            (
+                vote_pubkeys(&[&added_vote_keypair, &changed_vote_keypair]),
+                Some(vec![removed_vote_keypair.pubkey().to_string()]),
+                Some(slot + 1)
+            )
+        );
+        assert_eq!(
+            get_vote_accounts(slot + 1),
+            (HashSet::new(), Some(vec![]), Some(slot + 1))
+        );
+    }
+
     #[test]
     fn test_is_finalized() {
         let bank = Arc::new(Bank::default_for_tests());
@@ -7181,6 +10818,187 @@ pub mod tests {
         ));
     }
 
+    #[test]
+    fn test_token_accounts_by_owner_mints_and_pages() {
+        let RpcHandler { io, meta, bank, .. } =
+            start_rpc_handler_with_tx(&solana_sdk::pubkey::new_rand());
+
+        let owner = SplTokenPubkey::new(&[3; 32]);
+        let mints: Vec<_> = (6..9).map(|i| SplTokenPubkey::new(&[i; 32])).collect();
+        for mint in &mints {
+            let mut mint_data = vec![0; Mint::get_packed_len()];
+            Mint::pack(
+                Mint {
+                    decimals: 2,
+                    is_initialized: true,
+                    ..Mint::default()
+                },
+                &mut mint_data,
+            )
+            .unwrap();
+            let mint_account = AccountSharedData::from(Account {
+                lamports: 111,
+                data: mint_data,
+                owner: spl_token_id_v2_0(),
+                ..Account::default()
+            });
+            bank.store_account(&Pubkey::from_str(&mint.to_string()).unwrap(), &mint_account);
+        }
+        let mut token_account_pubkeys = vec![];
+        for (mint, amount) in [(mints[0], 5), (mints[0], 0), (mints[1], 7), (mints[2], 1)] {
+            let mut account_data = vec![0; TokenAccount::get_packed_len()];
+            TokenAccount::pack(
+                TokenAccount {
+                    mint,
+                    owner,
+                    amount,
+                    state: TokenAccountState::Initialized,
+                    ..TokenAccount::default()
+                },
+                &mut account_data,
+            )
+            .unwrap();
+            let token_account = AccountSharedData::from(Account {
+                lamports: 111,
+                data: account_data,
+                owner: spl_token_id_v2_0(),
+                ..Account::default()
+            });
+            let token_account_pubkey = solana_sdk::pubkey::new_rand();
+            bank.store_account(&token_account_pubkey, &token_account);
+            token_account_pubkeys.push(token_account_pubkey.to_string());
+        }
+
+        let get_token_accounts = |config: Value| {
+            let result = rpc_request(
+                &io,
+                &meta,
+                "getTokenAccountsByOwner",
+                json!([
+                    owner.to_string(),
+                    {"mints": [mints[0].to_string(), mints[1].to_string()]},
+                    config,
+                ]),
+            );
+            let mut pubkeys: Vec<String> =
+                serde_json::from_value::<Vec<RpcKeyedAccount>>(result["result"]["value"].clone())
+                    .unwrap()
+                    .into_iter()
+                    .map(|keyed_account| keyed_account.pubkey)
+                    .collect();
+            pubkeys.sort_unstable();
+            pubkeys
+        };
+        let sorted = |indexes: &[usize]| {
+            let mut pubkeys: Vec<_> = indexes
+                .iter()
+                .map(|i| token_account_pubkeys[*i].clone())
+                .collect();
+            pubkeys.sort_unstable();
+            pubkeys
+        };
+
+        assert_eq!(
+            get_token_accounts(json!({"encoding": "base64"})),
+            sorted(&[0, 1, 2])
+        );
+        assert_eq!(
+            get_token_accounts(json!({"encoding": "base64", "nonZeroBalanceOnly": true})),
+            sorted(&[0, 2])
+        );
+
+        // Pages are ordered by pubkey
+        let mut by_pubkey: Vec<_> = token_account_pubkeys[..3]
+            .iter()
+            .map(|pubkey| Pubkey::from_str(pubkey).unwrap())
+            .collect();
+        by_pubkey.sort_unstable();
+        let first_page = get_token_accounts(json!({"encoding": "base64", "limit": 2}));
+        assert_eq!(
+            first_page,
+            vec![by_pubkey[0].to_string(), by_pubkey[1].to_string()]
+        );
+        assert_eq!(
+            get_token_accounts(
+                json!({"encoding": "base64", "afterPubkey": by_pubkey[1].to_string()})
+            ),
+            vec![by_pubkey[2].to_string()]
+        );
+
+        let result = rpc_request(
+            &io,
+            &meta,
+            "getTokenAccountsByOwner",
+            json!([owner.to_string(), {"mints": []}]),
+        );
+        assert_eq!(result["error"]["code"], ErrorCode::InvalidParams.code());
+    }
+
+    #[test]
+    fn test_get_stake_accounts_by_authority() {
+        let RpcHandler { io, meta, bank, .. } =
+            start_rpc_handler_with_tx(&solana_sdk::pubkey::new_rand());
+
+        let staker = solana_sdk::pubkey::new_rand();
+        let withdrawer = solana_sdk::pubkey::new_rand();
+        let mut stake_account_pubkeys = vec![];
+        for authorized in [
+            Authorized { staker, withdrawer },
+            Authorized {
+                staker,
+                withdrawer: solana_sdk::pubkey::new_rand(),
+            },
+            Authorized::auto(&solana_sdk::pubkey::new_rand()),
+        ] {
+            let stake_state = StakeState::Initialized(Meta {
+                authorized,
+                ..Meta::default()
+            });
+            let stake_account = AccountSharedData::new_data_with_space(
+                42,
+                &stake_state,
+                std::mem::size_of::<StakeState>(),
+                &stake::program::id(),
+            )
+            .unwrap();
+            let stake_account_pubkey = solana_sdk::pubkey::new_rand();
+            bank.store_account(&stake_account_pubkey, &stake_account);
+            stake_account_pubkeys.push(stake_account_pubkey.to_string());
+        }
+
+        let get_stake_accounts = |method: &str, authority: &Pubkey| {
+            let result = rpc_request(
+                &io,
+                &meta,
+                method,
+                json!([authority.to_string(), {"encoding": "base64"}]),
+            );
+            let mut pubkeys: Vec<String> =
+                serde_json::from_value::<Vec<RpcKeyedAccount>>(result["result"]["value"].clone())
+                    .unwrap()
+                    .into_iter()
+                    .map(|keyed_account| keyed_account.pubkey)
+                    .collect();
+            pubkeys.sort_unstable();
+            pubkeys
+        };
+
+        let mut expected = stake_account_pubkeys[..2].to_vec();
+        expected.sort_unstable();
+        assert_eq!(
+            get_stake_accounts("getStakeAccountsByStaker", &staker),
+            expected
+        );
+        assert_eq!(
+            get_stake_accounts("getStakeAccountsByWithdrawer", &withdrawer),
+            vec![stake_account_pubkeys[0].clone()]
+        );
+        assert!(
+            get_stake_accounts("getStakeAccountsByStaker", &solana_sdk::pubkey::new_rand())
+                .is_empty()
+        );
+    }
+
     #[test]
     fn test_token_rpcs() {
         let RpcHandler { io, meta, bank, .. } =
@@ -7582,6 +11400,131 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn test_token_2022_rpcs() {
+        let RpcHandler { io, meta, bank, .. } =
+            start_rpc_handler_with_tx(&solana_sdk::pubkey::new_rand());
+        let token_2022_id = inline_spl_token_2022::id();
+        // Token-2022 accounts with extensions are followed by an account type and the extensions
+        let with_extensions = |mut data: Vec<u8>, account_type: u8| {
+            data.resize(TokenAccount::get_packed_len(), 0);
+            data.extend_from_slice(&[account_type, 1, 2, 3]);
+            data
+        };
+
+        let mint = SplTokenPubkey::new(&[2; 32]);
+        let owner = SplTokenPubkey::new(&[3; 32]);
+        let delegate = SplTokenPubkey::new(&[4; 32]);
+        let mut mint_data = vec![0; Mint::get_packed_len()];
+        Mint::pack(
+            Mint {
+                mint_authority: COption::Some(owner),
+                supply: 500,
+                decimals: 2,
+                is_initialized: true,
+                freeze_authority: COption::None,
+            },
+            &mut mint_data,
+        )
+        .unwrap();
+        let mint_account = AccountSharedData::from(Account {
+            lamports: 111,
+            data: with_extensions(mint_data, 1),
+            owner: token_2022_id,
+            ..Account::default()
+        });
+        let mint_pubkey = Pubkey::new(&mint.to_bytes());
+        bank.store_account(&mint_pubkey, &mint_account);
+
+        let mut account_data = vec![0; TokenAccount::get_packed_len()];
+        TokenAccount::pack(
+            TokenAccount {
+                mint,
+                owner,
+                delegate: COption::Some(delegate),
+                amount: 420,
+                state: TokenAccountState::Initialized,
+                is_native: COption::None,
+                delegated_amount: 30,
+                close_authority: COption::None,
+            },
+            &mut account_data,
+        )
+        .unwrap();
+        let extended_account_pubkey = solana_sdk::pubkey::new_rand();
+        bank.store_account(
+            &extended_account_pubkey,
+            &AccountSharedData::from(Account {
+                lamports: 111,
+                data: with_extensions(account_data.clone(), 2),
+                owner: token_2022_id,
+                ..Account::default()
+            }),
+        );
+        let base_account_pubkey = solana_sdk::pubkey::new_rand();
+        bank.store_account(
+            &base_account_pubkey,
+            &AccountSharedData::from(Account {
+                lamports: 111,
+                data: account_data,
+                owner: token_2022_id,
+                ..Account::default()
+            }),
+        );
+
+        let request = |method: &str, params: Value| rpc_request(&io, &meta, method, params);
+
+        let result = request(
+            "getTokenAccountBalance",
+            json!([extended_account_pubkey.to_string()]),
+        );
+        assert_eq!(result["result"]["value"]["amount"], json!("420"));
+        let result = request("getTokenSupply", json!([mint_pubkey.to_string()]));
+        assert_eq!(result["result"]["value"]["decimals"], json!(2));
+
+        let mut expected_pubkeys = vec![
+            extended_account_pubkey.to_string(),
+            base_account_pubkey.to_string(),
+        ];
+        expected_pubkeys.sort();
+        for (method, address) in [
+            ("getTokenAccountsByOwner", owner),
+            ("getTokenAccountsByDelegate", delegate),
+        ] {
+            for filter in [
+                json!({ "programId": token_2022_id.to_string() }),
+                json!({ "mint": mint_pubkey.to_string() }),
+            ] {
+                let result = request(
+                    method,
+                    json!([address.to_string(), filter, {"encoding": "jsonParsed"}]),
+                );
+                let accounts = result["result"]["value"].as_array().unwrap();
+                let mut pubkeys: Vec<_> = accounts
+                    .iter()
+                    .map(|account| account["pubkey"].as_str().unwrap().to_string())
+                    .collect();
+                pubkeys.sort();
+                assert_eq!(pubkeys, expected_pubkeys);
+                for account in accounts {
+                    let parsed = &account["account"]["data"];
+                    assert_eq!(parsed["program"], json!("spl-token-2022"));
+                    assert_eq!(
+                        parsed["parsed"]["info"]["tokenAmount"]["amount"],
+                        json!("420")
+                    );
+                }
+            }
+        }
+
+        // The legacy token program doesn't own these accounts
+        let result = request(
+            "getTokenAccountsByOwner",
+            json!([owner.to_string(), {"programId": spl_token_id_v2_0().to_string()}]),
+        );
+        assert_eq!(result["result"]["value"], json!([]));
+    }
+
     #[test]
     fn test_token_parsing() {
         let RpcHandler { io, meta, bank, .. } =
@@ -7746,6 +11689,32 @@ pub mod tests {
             ],
         )
         .is_none());
+
+        // Token-2022 accounts with extensions, told apart by their account type
+        let token_2022_filters = [
+            RpcFilterType::Memcmp(Memcmp {
+                offset: 32,
+                bytes: MemcmpEncodedBytes::Bytes(owner.to_bytes().to_vec()),
+                encoding: None,
+            }),
+            RpcFilterType::Memcmp(Memcmp {
+                offset: 165,
+                bytes: MemcmpEncodedBytes::Bytes(vec![2]),
+                encoding: None,
+            }),
+        ];
+        assert_eq!(
+            get_spl_token_owner_filter(
+                &Pubkey::from_str("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb").unwrap(),
+                &token_2022_filters,
+            ),
+            Some(owner)
+        );
+        assert!(get_spl_token_owner_filter(
+            &Pubkey::from_str("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA").unwrap(),
+            &token_2022_filters,
+        )
+        .is_none());
     }
 
     #[test]
@@ -0,0 +1,562 @@
+//! The `rpc_grpc_service` module serves the pubsub subscriptions over gRPC. Each
+//! `Subscribe` stream holds one subscription, shared with websocket subscriptions to the
+//! same params, and receives the same notifications from the broadcast queues, converted
+//! to their protobuf form.
+
+use {
+    crate::{
+        rpc_pubsub::{RpcSolPubSubImpl, RpcSolPubSubInternal},
+        rpc_pubsub_auth::{ApiKey, ApiKeys},
+        rpc_pubsub_service::{
+            forward_notifications, BroadcastHandler, PubSubConfig, STREAM_BUFFER_SIZE,
+        },
+        rpc_subscription_tracker::{CompositeSubscriptions, SubscriptionId},
+        rpc_subscriptions::{RpcSubscriptions, DIRECT_NOTIFICATION_BUFFER_SIZE},
+    },
+    dashmap::DashMap,
+    jsonrpc_core::ErrorCode,
+    solana_account_decoder::{UiAccount, UiAccountData, UiAccountEncoding},
+    solana_client::{
+        rpc_config::{
+            RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcTransactionLogsConfig,
+            RpcTransactionLogsFilter, RpcTransactionSubscribeConfig,
+        },
+        rpc_filter::{Memcmp as RpcMemcmp, MemcmpEncodedBytes, RpcFilterType},
+        rpc_response::{
+            Response as RpcResponse, RpcKeyedAccount, RpcLogsResponse, RpcNotificationsDropped,
+            SlotInfo,
+        },
+    },
+    solana_metrics::TokenCounter,
+    solana_sdk::{clock::Slot, commitment_config::CommitmentConfig},
+    solana_transaction_status::{
+        EncodedConfirmedTransaction, EncodedTransaction, UiTransactionEncoding,
+    },
+    std::{
+        net::SocketAddr,
+        sync::{Arc, RwLock},
+        thread::{self, Builder, JoinHandle},
+    },
+    stream_cancel::{Trigger, Tripwire},
+    tokio::sync::{mpsc, Semaphore},
+    tokio_stream::wrappers::ReceiverStream,
+    tonic::{transport, Request, Response, Status},
+};
+
+tonic::include_proto!("solana.rpc.pubsub");
+
+/// Threads of the runtime serving the streams
+const GRPC_WORKER_THREADS: usize = 4;
+
+struct GrpcPubSub {
+    config: PubSubConfig,
+    subscriptions: Arc<RpcSubscriptions>,
+    api_keys: Option<Arc<ApiKeys>>,
+    /// Permits for `PubSubConfig::max_grpc_streams` streams
+    streams: Arc<Semaphore>,
+    stream_counter: TokenCounter,
+}
+
+impl GrpcPubSub {
+    fn new(config: PubSubConfig, subscriptions: Arc<RpcSubscriptions>) -> Self {
+        let streams = Arc::new(Semaphore::new(config.max_grpc_streams));
+        Self {
+            api_keys: config.api_keys.clone(),
+            config,
+            subscriptions,
+            streams,
+            stream_counter: TokenCounter::new("rpc_grpc_streams"),
+        }
+    }
+
+    /// Authenticates a request with the `authorization: Bearer <KEY>` metadata, when API keys
+    /// are configured
+    fn authenticate(&self, request: &Request<SubscribeRequest>) -> Result<Option<ApiKey>, Status> {
+        let api_keys = match &self.api_keys {
+            Some(api_keys) => api_keys,
+            None => return Ok(None),
+        };
+        let api_key = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| api_keys.authenticate_bearer(value))
+            .ok_or_else(|| Status::unauthenticated("Invalid API key"))?;
+        let method = subscribe_method(request.get_ref());
+        if !method.map_or(true, |method| api_key.allows_method(method)) {
+            return Err(Status::permission_denied(
+                "Method not allowed for this API key",
+            ));
+        }
+        Ok(Some(api_key))
+    }
+}
+
+#[tonic::async_trait]
+impl pub_sub_server::PubSub for GrpcPubSub {
+    type SubscribeStream = ReceiverStream<Result<Notification, Status>>;
+
+    async fn subscribe(
+        &self,
+        request: Request<SubscribeRequest>,
+    ) -> Result<Response<Self::SubscribeStream>, Status> {
+        let api_key = self.authenticate(&request)?;
+        let stream_permit = Arc::clone(&self.streams)
+            .try_acquire_owned()
+            .map_err(|_| Status::resource_exhausted("Too many streams"))?;
+        let stream_token = self.stream_counter.create_token();
+        let subscription_control = self.subscriptions.control();
+        let current_subscriptions = Arc::new(DashMap::new());
+        let composite_subscriptions = Arc::new(RwLock::new(CompositeSubscriptions::default()));
+        let (direct_sender, direct_receiver) = mpsc::channel(DIRECT_NOTIFICATION_BUFFER_SIZE);
+        // Created ahead of the subscription so that its first notification can't be missed
+        let broadcast_receiver = subscription_control.broadcast_receiver();
+        let firehose_receiver = subscription_control.firehose_broadcast_receiver();
+
+        if let Some(api_key) = &api_key {
+            api_key.register_connection(&current_subscriptions);
+        }
+        let rpc_impl = RpcSolPubSubImpl::new(
+            self.config.clone(),
+            subscription_control.clone(),
+            Arc::clone(&current_subscriptions),
+            Arc::clone(&composite_subscriptions),
+            direct_sender,
+            request.remote_addr().map(|addr| addr.ip()),
+            api_key,
+        );
+        subscribe(&rpc_impl, request.into_inner()).map_err(subscription_status)?;
+
+        let (sender, receiver) = mpsc::channel(STREAM_BUFFER_SIZE);
+        let forward = forward_notifications(
+            BroadcastHandler::new(current_subscriptions, composite_subscriptions),
+            broadcast_receiver,
+            firehose_receiver,
+            direct_receiver,
+            sender,
+            self.config.clone(),
+            |id, json| {
+                typed_notification(id, &json).ok_or_else(|| {
+                    warn!("grpc notification of an unexpected shape: {}", json);
+                    Status::internal("Unexpected notification")
+                })
+            },
+            |err| Err(Status::resource_exhausted(err.to_string())),
+        );
+        tokio::spawn(async move {
+            forward.await;
+            drop((stream_permit, stream_token));
+        });
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+}
+
+fn subscription_status(err: jsonrpc_core::Error) -> Status {
+    match err.code {
+        ErrorCode::InvalidParams => Status::invalid_argument(err.message),
+        ErrorCode::MethodNotFound => Status::unimplemented(err.message),
+        _ => Status::resource_exhausted(err.message),
+    }
+}
+
+fn commitment_config(commitment: Commitment) -> CommitmentConfig {
+    match commitment {
+        Commitment::Finalized => CommitmentConfig::finalized(),
+        Commitment::Confirmed => CommitmentConfig::confirmed(),
+        Commitment::Processed => CommitmentConfig::processed(),
+    }
+}
+
+fn account_encoding(encoding: AccountEncoding) -> UiAccountEncoding {
+    match encoding {
+        AccountEncoding::Base64 => UiAccountEncoding::Base64,
+        AccountEncoding::Base64Zstd => UiAccountEncoding::Base64Zstd,
+        AccountEncoding::JsonParsed => UiAccountEncoding::JsonParsed,
+    }
+}
+
+fn rpc_filter(filter: Filter) -> Option<RpcFilterType> {
+    Some(match filter.filter? {
+        filter::Filter::DataSize(size) => RpcFilterType::DataSize(size),
+        filter::Filter::Memcmp(memcmp) => RpcFilterType::Memcmp(RpcMemcmp {
+            offset: memcmp.offset as usize,
+            bytes: MemcmpEncodedBytes::Bytes(memcmp.bytes),
+            encoding: None,
+        }),
+    })
+}
+
+/// Converts the JSON-RPC notification a websocket subscription would receive to its
+/// protobuf form, or `None` if it doesn't have the shape of its method
+fn typed_notification(subscription: SubscriptionId, json: &str) -> Option<Notification> {
+    let mut json: serde_json::Value = serde_json::from_str(json).ok()?;
+    let method = json["method"].as_str()?.to_string();
+    let result = json["params"]["result"].take();
+    let notification = match method.as_str() {
+        "accountNotification" => {
+            let response: RpcResponse<UiAccount> = serde_json::from_value(result).ok()?;
+            notification::Notification::Account(account_notification(
+                response.context.slot,
+                String::new(),
+                response.value,
+            )?)
+        }
+        "programNotification" => {
+            let response: RpcResponse<RpcKeyedAccount> = serde_json::from_value(result).ok()?;
+            notification::Notification::Account(account_notification(
+                response.context.slot,
+                response.value.pubkey,
+                response.value.account,
+            )?)
+        }
+        "slotNotification" => {
+            let SlotInfo { slot, parent, root } = serde_json::from_value(result).ok()?;
+            notification::Notification::Slot(SlotNotification { slot, parent, root })
+        }
+        "logsNotification" => {
+            let response: RpcResponse<RpcLogsResponse> = serde_json::from_value(result).ok()?;
+            let RpcLogsResponse {
+                signature,
+                err,
+                logs,
+            } = response.value;
+            notification::Notification::Logs(LogsNotification {
+                slot: response.context.slot,
+                signature,
+                err: err.map(|err| err.to_string()).unwrap_or_default(),
+                logs,
+            })
+        }
+        "transactionNotification" => {
+            let response: RpcResponse<EncodedConfirmedTransaction> =
+                serde_json::from_value(result).ok()?;
+            notification::Notification::Transaction(transaction_notification(
+                response.context.slot,
+                response.value,
+            )?)
+        }
+        "notificationsDropped" => {
+            let dropped: RpcNotificationsDropped = serde_json::from_value(result).ok()?;
+            notification::Notification::Dropped(NotificationsDropped {
+                count: dropped.count,
+                slots: dropped
+                    .first_slot
+                    .zip(dropped.last_slot)
+                    .map(|(first_slot, last_slot)| SlotRange {
+                        first_slot,
+                        last_slot,
+                    }),
+            })
+        }
+        _ => return None,
+    };
+    Some(Notification {
+        subscription: u64::from(subscription),
+        notification: Some(notification),
+    })
+}
+
+fn account_notification(
+    slot: Slot,
+    pubkey: String,
+    account: UiAccount,
+) -> Option<AccountNotification> {
+    let data = match account.data {
+        UiAccountData::Binary(data, UiAccountEncoding::Base64)
+        | UiAccountData::Binary(data, UiAccountEncoding::Base64Zstd) => {
+            account_notification::Data::Binary(base64::decode(data).ok()?)
+        }
+        UiAccountData::Json(parsed) => {
+            account_notification::Data::JsonParsed(serde_json::to_string(&parsed).ok()?)
+        }
+        _ => return None,
+    };
+    Some(AccountNotification {
+        slot,
+        pubkey,
+        lamports: account.lamports,
+        owner: account.owner,
+        executable: account.executable,
+        rent_epoch: account.rent_epoch,
+        data: Some(data),
+    })
+}
+
+fn transaction_notification(
+    slot: Slot,
+    transaction: EncodedConfirmedTransaction,
+) -> Option<TransactionNotification> {
+    let data = match transaction.transaction.transaction {
+        EncodedTransaction::Binary(data, UiTransactionEncoding::Base64) => {
+            base64::decode(data).ok()?
+        }
+        _ => return None,
+    };
+    let mut notification = TransactionNotification {
+        slot,
+        transaction: data,
+        block_time: transaction.block_time.unwrap_or_default(),
+        ..TransactionNotification::default()
+    };
+    if let Some(meta) = transaction.transaction.meta {
+        notification.err = meta.err.map(|err| err.to_string()).unwrap_or_default();
+        notification.fee = meta.fee;
+        notification.pre_balances = meta.pre_balances;
+        notification.post_balances = meta.post_balances;
+        notification.log_messages = meta.log_messages.unwrap_or_default();
+    }
+    Some(notification)
+}
+
+/// The websocket method a request corresponds to, which API keys are restricted to
+fn subscribe_method(request: &SubscribeRequest) -> Option<&'static str> {
+    Some(match request.subscription.as_ref()? {
+        subscribe_request::Subscription::Account(_) => "accountSubscribe",
+        subscribe_request::Subscription::Program(_) => "programSubscribe",
+        subscribe_request::Subscription::Slot(_) => "slotSubscribe",
+        subscribe_request::Subscription::Logs(_) => "logsSubscribe",
+        subscribe_request::Subscription::Transaction(_) => "transactionSubscribe",
+    })
+}
+
+/// Subscribes through the websocket implementation, so that both transports validate
+/// requests and enforce limits the same way.
+fn subscribe(
+    rpc_impl: &RpcSolPubSubImpl,
+    request: SubscribeRequest,
+) -> jsonrpc_core::Result<SubscriptionId> {
+    let commitment = Some(commitment_config(request.commitment()));
+    match request.subscription {
+        Some(subscribe_request::Subscription::Account(account)) => {
+            let encoding = account_encoding(account.encoding());
+            rpc_impl.account_subscribe(
+                account.pubkey,
+                Some(RpcAccountInfoConfig {
+                    encoding: Some(encoding),
+                    commitment,
+                    ..RpcAccountInfoConfig::default()
+                }),
+            )
+        }
+        Some(subscribe_request::Subscription::Program(program)) => {
+            let encoding = account_encoding(program.encoding());
+            let filters = program
+                .filters
+                .into_iter()
+                .map(rpc_filter)
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| jsonrpc_core::Error::invalid_params("Empty filter provided"))?;
+            rpc_impl.program_subscribe(
+                program.program_id,
+                Some(RpcProgramAccountsConfig {
+                    filters: Some(filters),
+                    account_config: RpcAccountInfoConfig {
+                        encoding: Some(encoding),
+                        commitment,
+                        ..RpcAccountInfoConfig::default()
+                    },
+                    ..RpcProgramAccountsConfig::default()
+                }),
+            )
+        }
+        Some(subscribe_request::Subscription::Slot(_)) => rpc_impl.slot_subscribe(),
+        Some(subscribe_request::Subscription::Logs(logs)) => {
+            let filter = if !logs.mentions.is_empty() {
+                RpcTransactionLogsFilter::Mentions(vec![logs.mentions])
+            } else if logs.include_votes {
+                RpcTransactionLogsFilter::AllWithVotes
+            } else {
+                RpcTransactionLogsFilter::All
+            };
+            rpc_impl.logs_subscribe(
+                filter,
+                Some(RpcTransactionLogsConfig {
+                    commitment,
+                    filter_status: None,
+                    start_slot: None,
+                }),
+            )
+        }
+        Some(subscribe_request::Subscription::Transaction(transaction)) => rpc_impl
+            .transaction_subscribe(
+                transaction.pubkey,
+                Some(RpcTransactionSubscribeConfig {
+                    commitment,
+                    encoding: Some(UiTransactionEncoding::Base64),
+                }),
+            ),
+        None => Err(jsonrpc_core::Error::invalid_params(
+            "No subscription provided",
+        )),
+    }
+}
+
+pub struct GrpcService {
+    thread_hdl: JoinHandle<()>,
+}
+
+impl GrpcService {
+    pub fn new(
+        pubsub_config: PubSubConfig,
+        subscriptions: &Arc<RpcSubscriptions>,
+        grpc_addr: SocketAddr,
+    ) -> (Trigger, Self) {
+        let subscriptions = Arc::clone(subscriptions);
+        info!("rpc_grpc bound to {:?}", grpc_addr);
+
+        let (trigger, tripwire) = Tripwire::new();
+        let thread_hdl = Builder::new()
+            .name("solana-rpc-grpc".to_string())
+            .spawn(move || {
+                let runtime = tokio::runtime::Builder::new_multi_thread()
+                    .worker_threads(GRPC_WORKER_THREADS)
+                    .thread_name("sol-rpc-grpc")
+                    .enable_all()
+                    .build()
+                    .expect("runtime creation failed");
+                let service = GrpcPubSub::new(pubsub_config, subscriptions);
+                if let Err(err) = runtime.block_on(serve(grpc_addr, service, tripwire)) {
+                    error!("grpc service failed: {}", err);
+                };
+            })
+            .expect("thread spawn failed");
+
+        (trigger, Self { thread_hdl })
+    }
+
+    pub fn close(self) -> thread::Result<()> {
+        self.join()
+    }
+
+    pub fn join(self) -> thread::Result<()> {
+        self.thread_hdl.join()
+    }
+}
+
+async fn serve(
+    grpc_addr: SocketAddr,
+    service: GrpcPubSub,
+    tripwire: Tripwire,
+) -> Result<(), transport::Error> {
+    transport::Server::builder()
+        .add_service(pub_sub_server::PubSubServer::new(service))
+        .serve_with_shutdown(grpc_addr, async move {
+            tripwire.await;
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{pub_sub_server::PubSub, *},
+        crate::rpc_subscription_tracker::SubscriptionParams,
+        serial_test::serial,
+        solana_runtime::{
+            bank::Bank,
+            bank_forks::BankForks,
+            genesis_utils::{create_genesis_config, GenesisConfigInfo},
+        },
+        std::time::Duration,
+        tokio_stream::StreamExt,
+    };
+
+    #[test]
+    #[serial]
+    fn test_grpc_subscribe() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let subscriptions = Arc::new(RpcSubscriptions::default_with_bank_forks(bank_forks));
+        let config = PubSubConfig {
+            max_grpc_streams: 1,
+            ..PubSubConfig::default_for_tests()
+        };
+        let service = GrpcPubSub::new(config, Arc::clone(&subscriptions));
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let request = SubscribeRequest {
+                commitment: Commitment::Finalized as i32,
+                subscription: Some(subscribe_request::Subscription::Account(
+                    AccountSubscription {
+                        pubkey: "invalid".to_string(),
+                        encoding: AccountEncoding::Base64 as i32,
+                    },
+                )),
+            };
+            let err = service.subscribe(Request::new(request)).await.unwrap_err();
+            assert_eq!(err.code(), tonic::Code::InvalidArgument);
+
+            let request = || SubscribeRequest {
+                commitment: Commitment::Finalized as i32,
+                subscription: Some(subscribe_request::Subscription::Slot(SlotSubscription {})),
+            };
+            let mut stream = service
+                .subscribe(Request::new(request()))
+                .await
+                .unwrap()
+                .into_inner();
+            let err = service
+                .subscribe(Request::new(request()))
+                .await
+                .unwrap_err();
+            assert_eq!(err.code(), tonic::Code::ResourceExhausted);
+            subscriptions
+                .control()
+                .assert_subscribed(&SubscriptionParams::Slot);
+
+            subscriptions.notify_slot(0, 0, 0);
+            let notification = tokio::time::timeout(Duration::from_secs(5), stream.next())
+                .await
+                .expect("no notification")
+                .unwrap()
+                .unwrap();
+            assert_eq!(notification.subscription, 0);
+            assert_eq!(
+                notification.notification,
+                Some(notification::Notification::Slot(SlotNotification {
+                    slot: 0,
+                    parent: 0,
+                    root: 0,
+                }))
+            );
+        });
+    }
+
+    #[test]
+    fn test_typed_notification() {
+        let json = r#"{"jsonrpc":"2.0","method":"accountNotification","params":{"result":{"context":{"slot":5},"value":{"lamports":10,"data":["AQID","base64"],"owner":"11111111111111111111111111111111","executable":false,"rentEpoch":2}},"subscription":3}}"#;
+        assert_eq!(
+            typed_notification(SubscriptionId::from(3), json),
+            Some(Notification {
+                subscription: 3,
+                notification: Some(notification::Notification::Account(AccountNotification {
+                    slot: 5,
+                    pubkey: String::new(),
+                    lamports: 10,
+                    owner: "11111111111111111111111111111111".to_string(),
+                    executable: false,
+                    rent_epoch: 2,
+                    data: Some(account_notification::Data::Binary(vec![1, 2, 3])),
+                })),
+            })
+        );
+
+        let json = r#"{"jsonrpc":"2.0","method":"notificationsDropped","params":{"result":{"count":4,"firstSlot":1,"lastSlot":2},"subscription":3}}"#;
+        assert_eq!(
+            typed_notification(SubscriptionId::from(3), json)
+                .unwrap()
+                .notification,
+            Some(notification::Notification::Dropped(NotificationsDropped {
+                count: 4,
+                slots: Some(SlotRange {
+                    first_slot: 1,
+                    last_slot: 2,
+                }),
+            }))
+        );
+
+        let json = r#"{"jsonrpc":"2.0","method":"slotNotification","params":{"result":{},"subscription":3}}"#;
+        assert_eq!(typed_notification(SubscriptionId::from(3), json), None);
+    }
+}
@@ -1,13 +1,30 @@
 use {
+    crate::rpc_subscriptions::RpcSubscriptions,
+    solana_client::rpc_response::{RpcHealthReport, RpcHealthState},
     solana_gossip::cluster_info::ClusterInfo,
+    solana_runtime::{snapshot_archive_info::SnapshotArchiveInfoGetter, snapshot_utils},
     solana_sdk::{clock::Slot, pubkey::Pubkey},
     std::{
         collections::HashSet,
+        fs,
+        path::Path,
         sync::atomic::{AtomicBool, Ordering},
-        sync::Arc,
+        sync::{Arc, RwLock, Weak},
+        time::{Duration, Instant, SystemTime},
     },
 };
 
+// List the snapshot archives directory at most this often, as health checks are frequent
+const LATEST_SNAPSHOT_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The latest full or incremental snapshot archive
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct LatestSnapshot {
+    slot: Slot,
+    /// When the archive was written, if the filesystem knows
+    written_at: Option<SystemTime>,
+}
+
 #[derive(PartialEq, Clone, Copy, Debug)]
 pub enum RpcHealthStatus {
     Ok,
@@ -20,6 +37,10 @@ pub struct RpcHealth {
     trusted_validators: Option<HashSet<Pubkey>>,
     health_check_slot_distance: u64,
     override_health_check: Arc<AtomicBool>,
+    /// Weak, so that health reporting doesn't keep the notification threads alive
+    rpc_subscriptions: Option<Weak<RpcSubscriptions>>,
+    /// Latest snapshot archive, and when the directory was listed
+    latest_snapshot: RwLock<Option<(Instant, Option<LatestSnapshot>)>>,
     #[cfg(test)]
     stub_health_status: std::sync::RwLock<Option<RpcHealthStatus>>,
 }
@@ -30,12 +51,15 @@ impl RpcHealth {
         trusted_validators: Option<HashSet<Pubkey>>,
         health_check_slot_distance: u64,
         override_health_check: Arc<AtomicBool>,
+        rpc_subscriptions: Option<&Arc<RpcSubscriptions>>,
     ) -> Self {
         Self {
             cluster_info,
             trusted_validators,
             health_check_slot_distance,
             override_health_check,
+            rpc_subscriptions: rpc_subscriptions.map(Arc::downgrade),
+            latest_snapshot: RwLock::default(),
             #[cfg(test)]
             stub_health_status: std::sync::RwLock::new(None),
         }
@@ -51,30 +75,8 @@ impl RpcHealth {
 
         if self.override_health_check.load(Ordering::Relaxed) {
             RpcHealthStatus::Ok
-        } else if let Some(trusted_validators) = &self.trusted_validators {
-            match (
-                self.cluster_info
-                    .get_accounts_hash_for_node(&self.cluster_info.id(), |hashes| {
-                        hashes
-                            .iter()
-                            .max_by(|a, b| a.0.cmp(&b.0))
-                            .map(|slot_hash| slot_hash.0)
-                    })
-                    .flatten(),
-                trusted_validators
-                    .iter()
-                    .filter_map(|trusted_validator| {
-                        self.cluster_info
-                            .get_accounts_hash_for_node(trusted_validator, |hashes| {
-                                hashes
-                                    .iter()
-                                    .max_by(|a, b| a.0.cmp(&b.0))
-                                    .map(|slot_hash| slot_hash.0)
-                            })
-                            .flatten()
-                    })
-                    .max(),
-            ) {
+        } else if self.trusted_validators.is_some() {
+            match self.accounts_hash_slots() {
                 (
                     Some(latest_account_hash_slot),
                     Some(latest_trusted_validator_account_hash_slot),
@@ -115,6 +117,104 @@ impl RpcHealth {
         }
     }
 
+    /// Returns the latest slot this node has published an accounts hash for, and the latest
+    /// slot any of its trusted validators have
+    pub fn accounts_hash_slots(&self) -> (Option<Slot>, Option<Slot>) {
+        let latest_accounts_hash_slot = |pubkey: &Pubkey| {
+            self.cluster_info
+                .get_accounts_hash_for_node(pubkey, |hashes| {
+                    hashes
+                        .iter()
+                        .max_by(|a, b| a.0.cmp(&b.0))
+                        .map(|slot_hash| slot_hash.0)
+                })
+                .flatten()
+        };
+        (
+            latest_accounts_hash_slot(&self.cluster_info.id()),
+            self.trusted_validators
+                .iter()
+                .flatten()
+                .filter_map(latest_accounts_hash_slot)
+                .max(),
+        )
+    }
+
+    /// Returns the depth and capacity of the pubsub notification queue, if pubsub is running
+    pub fn pubsub_notification_queue_depth(&self) -> Option<(usize, Option<usize>)> {
+        self.rpc_subscriptions
+            .as_ref()
+            .and_then(Weak::upgrade)
+            .map(|rpc_subscriptions| rpc_subscriptions.notification_queue_depth())
+    }
+
+    /// The health report of `getHealth` and `/health`, for a node rooted at `root` that
+    /// writes its snapshot archives to `snapshot_archives_dir`
+    pub fn report(&self, snapshot_archives_dir: Option<&Path>, root: Slot) -> RpcHealthReport {
+        let (status, num_slots_behind) = match self.check() {
+            RpcHealthStatus::Ok => (RpcHealthState::Ok, None),
+            RpcHealthStatus::Behind { num_slots } => (RpcHealthState::Behind, Some(num_slots)),
+            RpcHealthStatus::Unknown => (RpcHealthState::Unknown, None),
+        };
+        let (latest_accounts_hash_slot, latest_trusted_validator_accounts_hash_slot) =
+            self.accounts_hash_slots();
+        let latest_snapshot = snapshot_archives_dir.and_then(|dir| self.latest_snapshot(dir));
+        let pubsub_notification_queue_depth = self.pubsub_notification_queue_depth();
+        RpcHealthReport {
+            status,
+            num_slots_behind,
+            latest_accounts_hash_slot,
+            latest_trusted_validator_accounts_hash_slot,
+            latest_snapshot_slot: latest_snapshot.map(|snapshot| snapshot.slot),
+            latest_snapshot_age_secs: latest_snapshot
+                .and_then(|snapshot| snapshot.written_at?.elapsed().ok())
+                .map(|age| age.as_secs()),
+            snapshot_slots_behind_root: latest_snapshot
+                .map(|snapshot| root.saturating_sub(snapshot.slot)),
+            pubsub_notification_queue_depth: pubsub_notification_queue_depth
+                .map(|(depth, _)| depth),
+            pubsub_notification_queue_capacity: pubsub_notification_queue_depth
+                .and_then(|(_, capacity)| capacity),
+        }
+    }
+
+    /// The latest snapshot archive in `snapshot_archives_dir`, listed again only once the
+    /// previous listing is `LATEST_SNAPSHOT_REFRESH_INTERVAL` old
+    fn latest_snapshot(&self, snapshot_archives_dir: &Path) -> Option<LatestSnapshot> {
+        if let Some((listed_at, latest_snapshot)) = *self.latest_snapshot.read().unwrap() {
+            if listed_at.elapsed() < LATEST_SNAPSHOT_REFRESH_INTERVAL {
+                return latest_snapshot;
+            }
+        }
+        let latest_snapshot = Self::find_latest_snapshot(snapshot_archives_dir);
+        *self.latest_snapshot.write().unwrap() = Some((Instant::now(), latest_snapshot));
+        latest_snapshot
+    }
+
+    fn find_latest_snapshot(snapshot_archives_dir: &Path) -> Option<LatestSnapshot> {
+        let full_snapshot_archive_info =
+            snapshot_utils::get_highest_full_snapshot_archive_info(snapshot_archives_dir)?;
+        let (slot, path) = match snapshot_utils::get_highest_incremental_snapshot_archive_info(
+            snapshot_archives_dir,
+            full_snapshot_archive_info.slot(),
+        ) {
+            Some(incremental_snapshot_archive_info) => (
+                incremental_snapshot_archive_info.slot(),
+                incremental_snapshot_archive_info.path().clone(),
+            ),
+            None => (
+                full_snapshot_archive_info.slot(),
+                full_snapshot_archive_info.path().clone(),
+            ),
+        };
+        Some(LatestSnapshot {
+            slot,
+            written_at: fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .ok(),
+        })
+    }
+
     #[cfg(test)]
     pub(crate) fn stub() -> Arc<Self> {
         use {
@@ -130,6 +230,7 @@ impl RpcHealth {
             None,
             42,
             Arc::new(AtomicBool::new(false)),
+            None,
         ))
     }
 
@@ -2,13 +2,22 @@
 
 use {
     crate::{
+        rpc::optimize_filters,
+        rpc_pubsub_auth::ApiKey,
         rpc_pubsub_service::PubSubConfig,
         rpc_subscription_tracker::{
-            AccountSubscriptionParams, LogsSubscriptionKind, LogsSubscriptionParams,
-            ProgramSubscriptionParams, SignatureSubscriptionParams, SubscriptionControl,
+            AccountSubscriptionParams, AccountsSubscriptionParams, BlockSubscriptionKind,
+            BlockSubscriptionParams, BlockhashSubscriptionParams,
+            BlockhashValiditySubscriptionParams, CompositeSubscriptions,
+            Error as SubscriptionError, LeaderScheduleSubscriptionParams, LogsSubscriptionKind,
+            LogsSubscriptionParams, MintSubscriptionParams, ProgramChangesSubscriptionParams,
+            ProgramSubscriptionParams, RewardsSubscriptionParams, RootSubscriptionParams,
+            SignatureSubscriptionParams, StakeSubscriptionParams, SubscriptionControl,
             SubscriptionId, SubscriptionParams, SubscriptionToken,
+            TokenAccountsByOwnerSubscriptionParams, TransactionSubscriptionParams,
+            VoteSubscriptionParams,
         },
-        rpc_subscriptions::RpcVote,
+        rpc_subscriptions::{DirectNotification, LogsReplayPermit, RpcVote},
     },
     dashmap::DashMap,
     jsonrpc_core::{Error, ErrorCode, Result},
@@ -17,18 +26,40 @@ use {
     solana_account_decoder::{UiAccount, UiAccountEncoding},
     solana_client::{
         rpc_config::{
-            RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSignatureSubscribeConfig,
-            RpcTransactionLogsConfig, RpcTransactionLogsFilter,
+            RpcAccountInfoConfig, RpcBlockSubscribeConfig, RpcBlockSubscribeFilter,
+            RpcBlockhashValiditySubscribeConfig, RpcLeaderScheduleSubscribeConfig,
+            RpcMintSubscribeConfig, RpcMultiplexSubscribeItem, RpcMultiplexSubscribeKind,
+            RpcProgramAccountsConfig, RpcRootSubscribeConfig, RpcSignatureSubscribeConfig,
+            RpcTransactionLogsConfig, RpcTransactionLogsFilter, RpcTransactionSubscribeConfig,
+            RpcVoteSubscribeConfig,
         },
+        rpc_request::{MAX_GET_PROGRAM_ACCOUNT_FILTERS, MAX_PUBSUB_COMPRESSION_LEVEL},
         rpc_response::{
-            Response as RpcResponse, RpcKeyedAccount, RpcLogsResponse, RpcSignatureResult,
-            SlotInfo, SlotUpdate,
+            Response as RpcResponse, RpcAccountChange, RpcBlockUpdate, RpcBlockhash,
+            RpcBlockhashValidity, RpcEpochRewards, RpcEpochStart, RpcKeyedAccount, RpcLogsResponse,
+            RpcMintUpdate, RpcPerfSample, RpcRecentSignatureNotification, RpcRootNotification,
+            RpcSignatureResult, RpcStakeUpdate, RpcUpcomingLeaders, SlotInfo, SlotUpdate,
         },
     },
-    solana_sdk::{clock::Slot, pubkey::Pubkey, signature::Signature},
-    std::{str::FromStr, sync::Arc},
+    solana_sdk::{
+        commitment_config::CommitmentConfig, hash::Hash, pubkey::Pubkey, signature::Signature,
+    },
+    solana_transaction_status::{
+        EncodedConfirmedTransaction, TransactionDetails, UiTransactionEncoding,
+    },
+    std::{
+        collections::HashSet,
+        net::IpAddr,
+        str::FromStr,
+        sync::{atomic::AtomicUsize, Arc, RwLock},
+    },
+    tokio::sync::mpsc,
 };
 
+/// Number of upcoming leader slots in each `leaderScheduleNotification`, unless requested otherwise
+pub const DEFAULT_LEADER_SCHEDULE_SUBSCRIBE_SLOTS: usize = 16;
+pub const MAX_LEADER_SCHEDULE_SUBSCRIBE_SLOTS: usize = 1024;
+
 // We have to keep both of the following traits to not break backwards compatibility.
 // `RpcSolPubSubInternal` is actually used by the current PubSub API implementation.
 // `RpcSolPubSub` and the corresponding `gen_client` module are preserved
@@ -72,6 +103,60 @@ pub trait RpcSolPubSub {
         id: PubSubSubscriptionId,
     ) -> Result<bool>;
 
+    // Get notification every time the data of any account in a set is changed
+    // Accepts an array of pubkey parameters as base-58 encoded strings
+    #[pubsub(
+        subscription = "accountsNotification",
+        subscribe,
+        name = "accountsSubscribe"
+    )]
+    fn accounts_subscribe(
+        &self,
+        meta: Self::Metadata,
+        subscriber: Subscriber<RpcResponse<RpcKeyedAccount>>,
+        pubkey_strs: Vec<String>,
+        config: Option<RpcAccountInfoConfig>,
+    );
+
+    // Unsubscribe from accounts notification subscription.
+    #[pubsub(
+        subscription = "accountsNotification",
+        unsubscribe,
+        name = "accountsUnsubscribe"
+    )]
+    fn accounts_unsubscribe(
+        &self,
+        meta: Option<Self::Metadata>,
+        id: PubSubSubscriptionId,
+    ) -> Result<bool>;
+
+    // Get notification every time a token account owned by a particular wallet is changed
+    // Accepts pubkey parameter as base-58 encoded string
+    #[pubsub(
+        subscription = "tokenAccountsByOwnerNotification",
+        subscribe,
+        name = "tokenAccountsByOwnerSubscribe"
+    )]
+    fn token_accounts_by_owner_subscribe(
+        &self,
+        meta: Self::Metadata,
+        subscriber: Subscriber<RpcResponse<RpcKeyedAccount>>,
+        owner_str: String,
+        config: Option<RpcAccountInfoConfig>,
+    );
+
+    // Unsubscribe from token accounts by owner notification subscription.
+    #[pubsub(
+        subscription = "tokenAccountsByOwnerNotification",
+        unsubscribe,
+        name = "tokenAccountsByOwnerUnsubscribe"
+    )]
+    fn token_accounts_by_owner_unsubscribe(
+        &self,
+        meta: Option<Self::Metadata>,
+        id: PubSubSubscriptionId,
+    ) -> Result<bool>;
+
     // Get notification every time account data owned by a particular program is changed
     // Accepts pubkey parameter as base-58 encoded string
     #[pubsub(
@@ -99,6 +184,33 @@ pub trait RpcSolPubSub {
         id: PubSubSubscriptionId,
     ) -> Result<bool>;
 
+    // Get the pubkeys and balances of the accounts owned by a program that change in a slot
+    // Accepts pubkey parameter as base-58 encoded string
+    #[pubsub(
+        subscription = "programChangesNotification",
+        subscribe,
+        name = "programChangesSubscribe"
+    )]
+    fn program_changes_subscribe(
+        &self,
+        meta: Self::Metadata,
+        subscriber: Subscriber<RpcResponse<Vec<RpcAccountChange>>>,
+        pubkey_str: String,
+        config: Option<CommitmentConfig>,
+    );
+
+    // Unsubscribe from program changes notification subscription.
+    #[pubsub(
+        subscription = "programChangesNotification",
+        unsubscribe,
+        name = "programChangesUnsubscribe"
+    )]
+    fn program_changes_unsubscribe(
+        &self,
+        meta: Option<Self::Metadata>,
+        id: PubSubSubscriptionId,
+    ) -> Result<bool>;
+
     // Get logs for all transactions that reference the specified address
     #[pubsub(subscription = "logsNotification", subscribe, name = "logsSubscribe")]
     fn logs_subscribe(
@@ -121,6 +233,52 @@ pub trait RpcSolPubSub {
         id: PubSubSubscriptionId,
     ) -> Result<bool>;
 
+    // Get notification when the supply, decimals or authorities of an SPL token mint change
+    // Accepts pubkey parameter as base-58 encoded string
+    #[pubsub(subscription = "mintNotification", subscribe, name = "mintSubscribe")]
+    fn mint_subscribe(
+        &self,
+        meta: Self::Metadata,
+        subscriber: Subscriber<RpcResponse<RpcMintUpdate>>,
+        pubkey_str: String,
+        config: Option<RpcMintSubscribeConfig>,
+    );
+
+    // Unsubscribe from mint notification subscription.
+    #[pubsub(
+        subscription = "mintNotification",
+        unsubscribe,
+        name = "mintUnsubscribe"
+    )]
+    fn mint_unsubscribe(
+        &self,
+        meta: Option<Self::Metadata>,
+        id: PubSubSubscriptionId,
+    ) -> Result<bool>;
+
+    // Get parsed stake state and activation when a stake account changes
+    // Accepts pubkey parameter as base-58 encoded string
+    #[pubsub(subscription = "stakeNotification", subscribe, name = "stakeSubscribe")]
+    fn stake_subscribe(
+        &self,
+        meta: Self::Metadata,
+        subscriber: Subscriber<RpcResponse<RpcStakeUpdate>>,
+        pubkey_str: String,
+        config: Option<CommitmentConfig>,
+    );
+
+    // Unsubscribe from stake notification subscription.
+    #[pubsub(
+        subscription = "stakeNotification",
+        unsubscribe,
+        name = "stakeUnsubscribe"
+    )]
+    fn stake_unsubscribe(
+        &self,
+        meta: Option<Self::Metadata>,
+        id: PubSubSubscriptionId,
+    ) -> Result<bool>;
+
     // Get notification when signature is verified
     // Accepts signature parameter as base-58 encoded string
     #[pubsub(
@@ -148,6 +306,40 @@ pub trait RpcSolPubSub {
         id: PubSubSubscriptionId,
     ) -> Result<bool>;
 
+    // Get the final notification of a recently notified signature subscription
+    // Accepts signature parameter as base-58 encoded string
+    #[rpc(name = "getRecentSignatureNotification")]
+    fn get_recent_signature_notification(
+        &self,
+        signature_str: String,
+    ) -> Result<Option<RpcRecentSignatureNotification>>;
+
+    // Get account, program and signature notifications under a single subscription id
+    // Each notification carries the tag of the item it belongs to
+    #[pubsub(
+        subscription = "multiplexNotification",
+        subscribe,
+        name = "multiplexSubscribe"
+    )]
+    fn multiplex_subscribe(
+        &self,
+        meta: Self::Metadata,
+        subscriber: Subscriber<serde_json::Value>,
+        items: Vec<RpcMultiplexSubscribeItem>,
+    );
+
+    // Unsubscribe from multiplexed subscription.
+    #[pubsub(
+        subscription = "multiplexNotification",
+        unsubscribe,
+        name = "multiplexUnsubscribe"
+    )]
+    fn multiplex_unsubscribe(
+        &self,
+        meta: Option<Self::Metadata>,
+        id: PubSubSubscriptionId,
+    ) -> Result<bool>;
+
     // Get notification when slot is encountered
     #[pubsub(subscription = "slotNotification", subscribe, name = "slotSubscribe")]
     fn slot_subscribe(&self, meta: Self::Metadata, subscriber: Subscriber<SlotInfo>);
@@ -164,6 +356,58 @@ pub trait RpcSolPubSub {
         id: PubSubSubscriptionId,
     ) -> Result<bool>;
 
+    // Get notification when the latest blockhash at the given commitment advances
+    #[pubsub(
+        subscription = "blockhashNotification",
+        subscribe,
+        name = "blockhashSubscribe"
+    )]
+    fn blockhash_subscribe(
+        &self,
+        meta: Self::Metadata,
+        subscriber: Subscriber<RpcResponse<RpcBlockhash>>,
+        config: Option<CommitmentConfig>,
+    );
+
+    // Unsubscribe from blockhash notification subscription.
+    #[pubsub(
+        subscription = "blockhashNotification",
+        unsubscribe,
+        name = "blockhashUnsubscribe"
+    )]
+    fn blockhash_unsubscribe(
+        &self,
+        meta: Option<Self::Metadata>,
+        id: PubSubSubscriptionId,
+    ) -> Result<bool>;
+
+    // Get notification when a blockhash is about to expire and once it has expired
+    // Accepts blockhash parameter as base-58 encoded string
+    #[pubsub(
+        subscription = "isBlockhashValidNotification",
+        subscribe,
+        name = "isBlockhashValidSubscribe"
+    )]
+    fn blockhash_validity_subscribe(
+        &self,
+        meta: Self::Metadata,
+        subscriber: Subscriber<RpcResponse<RpcBlockhashValidity>>,
+        blockhash_str: String,
+        config: Option<RpcBlockhashValiditySubscribeConfig>,
+    );
+
+    // Unsubscribe from blockhash validity notification subscription.
+    #[pubsub(
+        subscription = "isBlockhashValidNotification",
+        unsubscribe,
+        name = "isBlockhashValidUnsubscribe"
+    )]
+    fn blockhash_validity_unsubscribe(
+        &self,
+        meta: Option<Self::Metadata>,
+        id: PubSubSubscriptionId,
+    ) -> Result<bool>;
+
     // Get series of updates for all slots
     #[pubsub(
         subscription = "slotsUpdatesNotification",
@@ -188,9 +432,63 @@ pub trait RpcSolPubSub {
         id: PubSubSubscriptionId,
     ) -> Result<bool>;
 
+    // Get notification when a block is confirmed or finalized
+    #[pubsub(subscription = "blockNotification", subscribe, name = "blockSubscribe")]
+    fn block_subscribe(
+        &self,
+        meta: Self::Metadata,
+        subscriber: Subscriber<RpcResponse<RpcBlockUpdate>>,
+        filter: RpcBlockSubscribeFilter,
+        config: Option<RpcBlockSubscribeConfig>,
+    );
+
+    // Unsubscribe from block notification subscription.
+    #[pubsub(
+        subscription = "blockNotification",
+        unsubscribe,
+        name = "blockUnsubscribe"
+    )]
+    fn block_unsubscribe(
+        &self,
+        meta: Option<Self::Metadata>,
+        id: PubSubSubscriptionId,
+    ) -> Result<bool>;
+
+    // Get the full transaction whenever a transaction mentioning an address is confirmed
+    // Accepts pubkey parameter as base-58 encoded string
+    #[pubsub(
+        subscription = "transactionNotification",
+        subscribe,
+        name = "transactionSubscribe"
+    )]
+    fn transaction_subscribe(
+        &self,
+        meta: Self::Metadata,
+        subscriber: Subscriber<RpcResponse<EncodedConfirmedTransaction>>,
+        pubkey_str: String,
+        config: Option<RpcTransactionSubscribeConfig>,
+    );
+
+    // Unsubscribe from transaction notification subscription.
+    #[pubsub(
+        subscription = "transactionNotification",
+        unsubscribe,
+        name = "transactionUnsubscribe"
+    )]
+    fn transaction_unsubscribe(
+        &self,
+        meta: Option<Self::Metadata>,
+        id: PubSubSubscriptionId,
+    ) -> Result<bool>;
+
     // Get notification when vote is encountered
     #[pubsub(subscription = "voteNotification", subscribe, name = "voteSubscribe")]
-    fn vote_subscribe(&self, meta: Self::Metadata, subscriber: Subscriber<RpcVote>);
+    fn vote_subscribe(
+        &self,
+        meta: Self::Metadata,
+        subscriber: Subscriber<RpcVote>,
+        config: Option<RpcVoteSubscribeConfig>,
+    );
 
     // Unsubscribe from vote notification subscription.
     #[pubsub(
@@ -206,7 +504,12 @@ pub trait RpcSolPubSub {
 
     // Get notification when a new root is set
     #[pubsub(subscription = "rootNotification", subscribe, name = "rootSubscribe")]
-    fn root_subscribe(&self, meta: Self::Metadata, subscriber: Subscriber<Slot>);
+    fn root_subscribe(
+        &self,
+        meta: Self::Metadata,
+        subscriber: Subscriber<RpcRootNotification>,
+        config: Option<RpcRootSubscribeConfig>,
+    );
 
     // Unsubscribe from slot notification subscription.
     #[pubsub(
@@ -219,6 +522,97 @@ pub trait RpcSolPubSub {
         meta: Option<Self::Metadata>,
         id: PubSubSubscriptionId,
     ) -> Result<bool>;
+
+    // Get notification when the first slot of a new epoch is rooted
+    #[pubsub(subscription = "epochNotification", subscribe, name = "epochSubscribe")]
+    fn epoch_subscribe(&self, meta: Self::Metadata, subscriber: Subscriber<RpcEpochStart>);
+
+    // Unsubscribe from epoch notification subscription.
+    #[pubsub(
+        subscription = "epochNotification",
+        unsubscribe,
+        name = "epochUnsubscribe"
+    )]
+    fn epoch_unsubscribe(
+        &self,
+        meta: Option<Self::Metadata>,
+        id: PubSubSubscriptionId,
+    ) -> Result<bool>;
+
+    // Get the upcoming leader slots every time a slot is processed
+    #[pubsub(
+        subscription = "leaderScheduleNotification",
+        subscribe,
+        name = "leaderScheduleSubscribe"
+    )]
+    fn leader_schedule_subscribe(
+        &self,
+        meta: Self::Metadata,
+        subscriber: Subscriber<RpcUpcomingLeaders>,
+        config: Option<RpcLeaderScheduleSubscribeConfig>,
+    );
+
+    // Unsubscribe from leader schedule notification subscription.
+    #[pubsub(
+        subscription = "leaderScheduleNotification",
+        unsubscribe,
+        name = "leaderScheduleUnsubscribe"
+    )]
+    fn leader_schedule_unsubscribe(
+        &self,
+        meta: Option<Self::Metadata>,
+        id: PubSubSubscriptionId,
+    ) -> Result<bool>;
+
+    // Get the staking and voting rewards of a set of addresses when epoch rewards are paid
+    // Accepts pubkey parameters as base-58 encoded strings
+    #[pubsub(
+        subscription = "rewardsNotification",
+        subscribe,
+        name = "rewardsSubscribe"
+    )]
+    fn rewards_subscribe(
+        &self,
+        meta: Self::Metadata,
+        subscriber: Subscriber<RpcEpochRewards>,
+        pubkey_strs: Vec<String>,
+    );
+
+    // Unsubscribe from rewards notification subscription.
+    #[pubsub(
+        subscription = "rewardsNotification",
+        unsubscribe,
+        name = "rewardsUnsubscribe"
+    )]
+    fn rewards_unsubscribe(
+        &self,
+        meta: Option<Self::Metadata>,
+        id: PubSubSubscriptionId,
+    ) -> Result<bool>;
+
+    // Get a performance sample every time a sample window closes
+    #[pubsub(
+        subscription = "performanceSamplesNotification",
+        subscribe,
+        name = "performanceSamplesSubscribe"
+    )]
+    fn performance_samples_subscribe(
+        &self,
+        meta: Self::Metadata,
+        subscriber: Subscriber<RpcPerfSample>,
+    );
+
+    // Unsubscribe from performance sample notification subscription.
+    #[pubsub(
+        subscription = "performanceSamplesNotification",
+        unsubscribe,
+        name = "performanceSamplesUnsubscribe"
+    )]
+    fn performance_samples_unsubscribe(
+        &self,
+        meta: Option<Self::Metadata>,
+        id: PubSubSubscriptionId,
+    ) -> Result<bool>;
 }
 
 pub use internal::RpcSolPubSubInternal;
@@ -242,6 +636,32 @@ mod internal {
         #[rpc(name = "accountUnsubscribe")]
         fn account_unsubscribe(&self, id: SubscriptionId) -> Result<bool>;
 
+        // Get notification every time the data of any account in a set is changed
+        // Accepts an array of pubkey parameters as base-58 encoded strings
+        #[rpc(name = "accountsSubscribe")]
+        fn accounts_subscribe(
+            &self,
+            pubkey_strs: Vec<String>,
+            config: Option<RpcAccountInfoConfig>,
+        ) -> Result<SubscriptionId>;
+
+        // Unsubscribe from accounts notification subscription.
+        #[rpc(name = "accountsUnsubscribe")]
+        fn accounts_unsubscribe(&self, id: SubscriptionId) -> Result<bool>;
+
+        // Get notification every time a token account owned by a particular wallet is changed
+        // Accepts pubkey parameter as base-58 encoded string
+        #[rpc(name = "tokenAccountsByOwnerSubscribe")]
+        fn token_accounts_by_owner_subscribe(
+            &self,
+            owner_str: String,
+            config: Option<RpcAccountInfoConfig>,
+        ) -> Result<SubscriptionId>;
+
+        // Unsubscribe from token accounts by owner notification subscription.
+        #[rpc(name = "tokenAccountsByOwnerUnsubscribe")]
+        fn token_accounts_by_owner_unsubscribe(&self, id: SubscriptionId) -> Result<bool>;
+
         // Get notification every time account data owned by a particular program is changed
         // Accepts pubkey parameter as base-58 encoded string
         #[rpc(name = "programSubscribe")]
@@ -255,6 +675,19 @@ mod internal {
         #[rpc(name = "programUnsubscribe")]
         fn program_unsubscribe(&self, id: SubscriptionId) -> Result<bool>;
 
+        // Get the pubkeys and balances of the accounts owned by a program that change in a slot
+        // Accepts pubkey parameter as base-58 encoded string
+        #[rpc(name = "programChangesSubscribe")]
+        fn program_changes_subscribe(
+            &self,
+            pubkey_str: String,
+            config: Option<CommitmentConfig>,
+        ) -> Result<SubscriptionId>;
+
+        // Unsubscribe from program changes notification subscription.
+        #[rpc(name = "programChangesUnsubscribe")]
+        fn program_changes_unsubscribe(&self, id: SubscriptionId) -> Result<bool>;
+
         // Get logs for all transactions that reference the specified address
         #[rpc(name = "logsSubscribe")]
         fn logs_subscribe(
@@ -267,6 +700,32 @@ mod internal {
         #[rpc(name = "logsUnsubscribe")]
         fn logs_unsubscribe(&self, id: SubscriptionId) -> Result<bool>;
 
+        // Get notification when the supply, decimals or authorities of an SPL token mint change
+        // Accepts pubkey parameter as base-58 encoded string
+        #[rpc(name = "mintSubscribe")]
+        fn mint_subscribe(
+            &self,
+            pubkey_str: String,
+            config: Option<RpcMintSubscribeConfig>,
+        ) -> Result<SubscriptionId>;
+
+        // Unsubscribe from mint notification subscription.
+        #[rpc(name = "mintUnsubscribe")]
+        fn mint_unsubscribe(&self, id: SubscriptionId) -> Result<bool>;
+
+        // Get parsed stake state and activation when a stake account changes
+        // Accepts pubkey parameter as base-58 encoded string
+        #[rpc(name = "stakeSubscribe")]
+        fn stake_subscribe(
+            &self,
+            pubkey_str: String,
+            config: Option<CommitmentConfig>,
+        ) -> Result<SubscriptionId>;
+
+        // Unsubscribe from stake notification subscription.
+        #[rpc(name = "stakeUnsubscribe")]
+        fn stake_unsubscribe(&self, id: SubscriptionId) -> Result<bool>;
+
         // Get notification when signature is verified
         // Accepts signature parameter as base-58 encoded string
         #[rpc(name = "signatureSubscribe")]
@@ -280,14 +739,55 @@ mod internal {
         #[rpc(name = "signatureUnsubscribe")]
         fn signature_unsubscribe(&self, id: SubscriptionId) -> Result<bool>;
 
-        // Get notification when slot is encountered
-        #[rpc(name = "slotSubscribe")]
-        fn slot_subscribe(&self) -> Result<SubscriptionId>;
-
-        // Unsubscribe from slot notification subscription.
+        // Get the final notification of a recently notified signature subscription
+        // Accepts signature parameter as base-58 encoded string
+        #[rpc(name = "getRecentSignatureNotification")]
+        fn get_recent_signature_notification(
+            &self,
+            signature_str: String,
+        ) -> Result<Option<RpcRecentSignatureNotification>>;
+
+        // Get account, program and signature notifications under a single subscription id
+        // Each notification carries the tag of the item it belongs to
+        #[rpc(name = "multiplexSubscribe")]
+        fn multiplex_subscribe(
+            &self,
+            items: Vec<RpcMultiplexSubscribeItem>,
+        ) -> Result<SubscriptionId>;
+
+        // Unsubscribe from multiplexed subscription.
+        #[rpc(name = "multiplexUnsubscribe")]
+        fn multiplex_unsubscribe(&self, id: SubscriptionId) -> Result<bool>;
+
+        // Get notification when slot is encountered
+        #[rpc(name = "slotSubscribe")]
+        fn slot_subscribe(&self) -> Result<SubscriptionId>;
+
+        // Unsubscribe from slot notification subscription.
         #[rpc(name = "slotUnsubscribe")]
         fn slot_unsubscribe(&self, id: SubscriptionId) -> Result<bool>;
 
+        // Get notification when the latest blockhash at the given commitment advances
+        #[rpc(name = "blockhashSubscribe")]
+        fn blockhash_subscribe(&self, config: Option<CommitmentConfig>) -> Result<SubscriptionId>;
+
+        // Unsubscribe from blockhash notification subscription.
+        #[rpc(name = "blockhashUnsubscribe")]
+        fn blockhash_unsubscribe(&self, id: SubscriptionId) -> Result<bool>;
+
+        // Get notification when a blockhash is about to expire and once it has expired
+        // Accepts blockhash parameter as base-58 encoded string
+        #[rpc(name = "isBlockhashValidSubscribe")]
+        fn blockhash_validity_subscribe(
+            &self,
+            blockhash_str: String,
+            config: Option<RpcBlockhashValiditySubscribeConfig>,
+        ) -> Result<SubscriptionId>;
+
+        // Unsubscribe from blockhash validity notification subscription.
+        #[rpc(name = "isBlockhashValidUnsubscribe")]
+        fn blockhash_validity_unsubscribe(&self, id: SubscriptionId) -> Result<bool>;
+
         // Get series of updates for all slots
         #[rpc(name = "slotsUpdatesSubscribe")]
         fn slots_updates_subscribe(&self) -> Result<SubscriptionId>;
@@ -296,9 +796,34 @@ mod internal {
         #[rpc(name = "slotsUpdatesUnsubscribe")]
         fn slots_updates_unsubscribe(&self, id: SubscriptionId) -> Result<bool>;
 
+        // Get notification when a block is confirmed or finalized
+        #[rpc(name = "blockSubscribe")]
+        fn block_subscribe(
+            &self,
+            filter: RpcBlockSubscribeFilter,
+            config: Option<RpcBlockSubscribeConfig>,
+        ) -> Result<SubscriptionId>;
+
+        // Unsubscribe from block notification subscription.
+        #[rpc(name = "blockUnsubscribe")]
+        fn block_unsubscribe(&self, id: SubscriptionId) -> Result<bool>;
+
+        // Get the full transaction whenever a transaction mentioning an address is confirmed
+        // Accepts pubkey parameter as base-58 encoded string
+        #[rpc(name = "transactionSubscribe")]
+        fn transaction_subscribe(
+            &self,
+            pubkey_str: String,
+            config: Option<RpcTransactionSubscribeConfig>,
+        ) -> Result<SubscriptionId>;
+
+        // Unsubscribe from transaction notification subscription.
+        #[rpc(name = "transactionUnsubscribe")]
+        fn transaction_unsubscribe(&self, id: SubscriptionId) -> Result<bool>;
+
         // Get notification when vote is encountered
         #[rpc(name = "voteSubscribe")]
-        fn vote_subscribe(&self) -> Result<SubscriptionId>;
+        fn vote_subscribe(&self, config: Option<RpcVoteSubscribeConfig>) -> Result<SubscriptionId>;
 
         // Unsubscribe from vote notification subscription.
         #[rpc(name = "voteUnsubscribe")]
@@ -306,11 +831,47 @@ mod internal {
 
         // Get notification when a new root is set
         #[rpc(name = "rootSubscribe")]
-        fn root_subscribe(&self) -> Result<SubscriptionId>;
+        fn root_subscribe(&self, config: Option<RpcRootSubscribeConfig>) -> Result<SubscriptionId>;
 
         // Unsubscribe from slot notification subscription.
         #[rpc(name = "rootUnsubscribe")]
         fn root_unsubscribe(&self, id: SubscriptionId) -> Result<bool>;
+
+        // Get notification when the first slot of a new epoch is rooted
+        #[rpc(name = "epochSubscribe")]
+        fn epoch_subscribe(&self) -> Result<SubscriptionId>;
+
+        // Unsubscribe from epoch notification subscription.
+        #[rpc(name = "epochUnsubscribe")]
+        fn epoch_unsubscribe(&self, id: SubscriptionId) -> Result<bool>;
+
+        // Get the upcoming leader slots every time a slot is processed
+        #[rpc(name = "leaderScheduleSubscribe")]
+        fn leader_schedule_subscribe(
+            &self,
+            config: Option<RpcLeaderScheduleSubscribeConfig>,
+        ) -> Result<SubscriptionId>;
+
+        // Unsubscribe from leader schedule notification subscription.
+        #[rpc(name = "leaderScheduleUnsubscribe")]
+        fn leader_schedule_unsubscribe(&self, id: SubscriptionId) -> Result<bool>;
+
+        // Get the staking and voting rewards of a set of addresses when epoch rewards are paid
+        // Accepts pubkey parameters as base-58 encoded strings
+        #[rpc(name = "rewardsSubscribe")]
+        fn rewards_subscribe(&self, pubkey_strs: Vec<String>) -> Result<SubscriptionId>;
+
+        // Unsubscribe from rewards notification subscription.
+        #[rpc(name = "rewardsUnsubscribe")]
+        fn rewards_unsubscribe(&self, id: SubscriptionId) -> Result<bool>;
+
+        // Get a performance sample every time a sample window closes
+        #[rpc(name = "performanceSamplesSubscribe")]
+        fn performance_samples_subscribe(&self) -> Result<SubscriptionId>;
+
+        // Unsubscribe from performance sample notification subscription.
+        #[rpc(name = "performanceSamplesUnsubscribe")]
+        fn performance_samples_unsubscribe(&self, id: SubscriptionId) -> Result<bool>;
     }
 }
 
@@ -318,6 +879,12 @@ pub struct RpcSolPubSubImpl {
     config: PubSubConfig,
     subscription_control: SubscriptionControl,
     current_subscriptions: Arc<DashMap<SubscriptionId, SubscriptionToken>>,
+    composite_subscriptions: Arc<RwLock<CompositeSubscriptions>>,
+    direct_sender: mpsc::Sender<DirectNotification>,
+    /// Number of logs replays in progress for the connection
+    pending_replays: Arc<AtomicUsize>,
+    client_ip: Option<IpAddr>,
+    api_key: Option<ApiKey>,
 }
 
 impl RpcSolPubSubImpl {
@@ -325,26 +892,121 @@ impl RpcSolPubSubImpl {
         config: PubSubConfig,
         subscription_control: SubscriptionControl,
         current_subscriptions: Arc<DashMap<SubscriptionId, SubscriptionToken>>,
+        composite_subscriptions: Arc<RwLock<CompositeSubscriptions>>,
+        direct_sender: mpsc::Sender<DirectNotification>,
+        client_ip: Option<IpAddr>,
+        api_key: Option<ApiKey>,
     ) -> Self {
         Self {
             config,
             subscription_control,
             current_subscriptions,
+            composite_subscriptions,
+            direct_sender,
+            pending_replays: Arc::default(),
+            client_ip,
+            api_key,
         }
     }
 
-    fn subscribe(&self, params: SubscriptionParams) -> Result<SubscriptionId> {
-        let token = self
-            .subscription_control
-            .subscribe(params)
-            .map_err(|_| Error {
+    fn check_commitment(&self, params: &SubscriptionParams) -> Result<()> {
+        if !self.config.finalized_only {
+            return Ok(());
+        }
+        let finalized = match params {
+            // Received notifications and followup commitments are reported before finality
+            SubscriptionParams::Signature(params) => {
+                !params.enable_received_notification
+                    && params
+                        .commitments()
+                        .all(|commitment| commitment.is_finalized())
+            }
+            params => params
+                .commitment()
+                .map(|commitment| commitment.is_finalized())
+                .unwrap_or(true),
+        };
+        if !finalized {
+            return Err(Error {
+                code: ErrorCode::InvalidParams,
+                message: "Invalid Request: Only finalized commitment is supported".into(),
+                data: None,
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks that `new` more subscriptions fit within the connection limit. Each item of a
+    /// composite subscription counts as a subscription.
+    fn check_connection_limit(&self, new: usize) -> Result<()> {
+        let total =
+            self.current_subscriptions.len() + self.composite_subscriptions.read().unwrap().items();
+        if total + new > self.config.max_subscriptions_per_connection {
+            inc_new_counter_info!("rpc-subscription-refused-connection-limit-reached", 1);
+            return Err(Error {
+                code: ErrorCode::InternalError,
+                message: "Internal Error: Subscription refused. Connection subscription limit \
+                          reached"
+                    .into(),
+                data: None,
+            });
+        }
+        Ok(())
+    }
+
+    /// Adds a new subscription to the connection, within the quota of its API key if it has
+    /// one. The token is dropped when the subscription is refused.
+    fn insert_within_api_key_quota(
+        &self,
+        id: SubscriptionId,
+        token: SubscriptionToken,
+    ) -> Result<()> {
+        let api_key = match &self.api_key {
+            Some(api_key) => api_key,
+            None => {
+                self.current_subscriptions.insert(id, token);
+                return Ok(());
+            }
+        };
+        if !api_key.insert_within_quota(&self.current_subscriptions, id, token) {
+            inc_new_counter_info!("rpc-subscription-refused-api-key-limit-reached", 1);
+            return Err(Error {
                 code: ErrorCode::InternalError,
-                message: "Internal Error: Subscription refused. Node subscription limit reached"
+                message: "Internal Error: Subscription refused. API key subscription limit \
+                          reached"
                     .into(),
                 data: None,
-            })?;
+            });
+        }
+        Ok(())
+    }
+
+    fn check_draining(&self) -> Result<()> {
+        if self.config.drain.is_draining() {
+            inc_new_counter_info!("rpc-subscription-refused-draining", 1);
+            return Err(Error {
+                code: ErrorCode::InternalError,
+                message: "Internal Error: Subscription refused. Server is shutting down".into(),
+                data: None,
+            });
+        }
+        Ok(())
+    }
+
+    fn subscribe(&self, params: SubscriptionParams) -> Result<SubscriptionId> {
+        self.check_draining()?;
+        self.check_commitment(&params)?;
+        let token = self
+            .subscription_control
+            .subscribe_from(params, self.client_ip)
+            .map_err(subscription_error)?;
         let id = token.id();
-        self.current_subscriptions.insert(id, token);
+        if self.current_subscriptions.contains_key(&id) {
+            self.current_subscriptions.insert(id, token);
+        } else {
+            self.check_connection_limit(1)?;
+            self.insert_within_api_key_quota(id, token)?;
+        }
         Ok(id)
     }
 
@@ -361,6 +1023,16 @@ impl RpcSolPubSubImpl {
     }
 }
 
+fn commitment_rank(commitment: &CommitmentConfig) -> u8 {
+    if commitment.is_finalized() {
+        2
+    } else if commitment.is_confirmed() {
+        1
+    } else {
+        0
+    }
+}
+
 fn param<T: FromStr>(param_str: &str, thing: &str) -> Result<T> {
     param_str.parse::<T>().map_err(|_e| Error {
         code: ErrorCode::InvalidParams,
@@ -369,6 +1041,142 @@ fn param<T: FromStr>(param_str: &str, thing: &str) -> Result<T> {
     })
 }
 
+fn subscription_error(err: SubscriptionError) -> Error {
+    Error {
+        code: ErrorCode::InternalError,
+        message: match err {
+            SubscriptionError::TooManySubscriptions => {
+                "Internal Error: Subscription refused. Node subscription limit reached"
+            }
+            SubscriptionError::TooManyClientSubscriptions => {
+                "Internal Error: Subscription refused. Client subscription limit reached"
+            }
+        }
+        .into(),
+        data: None,
+    }
+}
+
+/// Checks that a compression level is only requested for "base64+zstd" notifications, and
+/// within the levels the node is willing to spend CPU on
+fn compression_level(
+    encoding: UiAccountEncoding,
+    compression_level: Option<i32>,
+) -> Result<Option<i32>> {
+    match compression_level {
+        Some(_) if encoding != UiAccountEncoding::Base64Zstd => Err(Error {
+            code: ErrorCode::InvalidParams,
+            message: "Invalid Request: compressionLevel requires base64+zstd encoding".into(),
+            data: None,
+        }),
+        Some(level) if !(1..=MAX_PUBSUB_COMPRESSION_LEVEL).contains(&level) => Err(Error {
+            code: ErrorCode::InvalidParams,
+            message: format!(
+                "Invalid Request: compressionLevel must be between 1 and {}",
+                MAX_PUBSUB_COMPRESSION_LEVEL
+            ),
+            data: None,
+        }),
+        compression_level => Ok(compression_level),
+    }
+}
+
+fn account_params(
+    pubkey_str: &str,
+    config: &RpcAccountInfoConfig,
+) -> Result<AccountSubscriptionParams> {
+    let encoding = config.encoding.unwrap_or(UiAccountEncoding::Binary);
+    let with_proof = config.with_proof.unwrap_or_default();
+    // The account hash covers the whole account data, which a client can't recover from
+    // sliced or parsed data
+    if with_proof && (config.data_slice.is_some() || encoding == UiAccountEncoding::JsonParsed) {
+        return Err(Error {
+            code: ErrorCode::InvalidParams,
+            message: "Invalid Request: withProof isn't supported with dataSlice or jsonParsed"
+                .into(),
+            data: None,
+        });
+    }
+    Ok(AccountSubscriptionParams {
+        pubkey: param::<Pubkey>(pubkey_str, "pubkey")?,
+        commitment: config.commitment.unwrap_or_default(),
+        data_slice: config.data_slice,
+        encoding,
+        compression_level: compression_level(encoding, config.compression_level)?,
+        with_proof,
+    })
+}
+
+fn program_params(
+    pubkey_str: &str,
+    config: RpcProgramAccountsConfig,
+) -> Result<ProgramSubscriptionParams> {
+    let mut filters = config.filters.unwrap_or_default();
+    if filters.len() > MAX_GET_PROGRAM_ACCOUNT_FILTERS {
+        return Err(Error {
+            code: ErrorCode::InvalidParams,
+            message: format!(
+                "Invalid Request: Too many filters provided; max {}",
+                MAX_GET_PROGRAM_ACCOUNT_FILTERS
+            ),
+            data: None,
+        });
+    }
+    for (index, filter) in filters.iter().enumerate() {
+        filter.verify().map_err(|err| Error {
+            code: ErrorCode::InvalidParams,
+            message: format!("Invalid Request: filter {}: {}", index, err),
+            data: None,
+        })?;
+    }
+    // Decode memcmp bytes once here rather than for every account notified
+    optimize_filters(&mut filters);
+    let encoding = config
+        .account_config
+        .encoding
+        .unwrap_or(UiAccountEncoding::Binary);
+    Ok(ProgramSubscriptionParams {
+        pubkey: param::<Pubkey>(pubkey_str, "pubkey")?,
+        filters,
+        encoding,
+        data_slice: config.account_config.data_slice,
+        commitment: config.account_config.commitment.unwrap_or_default(),
+        compression_level: compression_level(encoding, config.account_config.compression_level)?,
+        with_context: config.with_context.unwrap_or_default(),
+        batch: config.batch.unwrap_or_default(),
+    })
+}
+
+fn signature_params(
+    signature_str: &str,
+    config: RpcSignatureSubscribeConfig,
+) -> Result<SignatureSubscriptionParams> {
+    let (commitment, followup_commitments) = match config.commitment_levels {
+        Some(levels) => {
+            let commitments: Vec<_> = levels
+                .into_iter()
+                .map(|commitment| CommitmentConfig { commitment })
+                .collect();
+            let ranks: Vec<_> = commitments.iter().map(commitment_rank).collect();
+            if commitments.is_empty() || ranks.windows(2).any(|pair| pair[0] >= pair[1]) {
+                return Err(Error {
+                    code: ErrorCode::InvalidParams,
+                    message: "Invalid Request: commitmentLevels must be increasing".into(),
+                    data: None,
+                });
+            }
+            (commitments[0], commitments[1..].to_vec())
+        }
+        None => (config.commitment.unwrap_or_default(), vec![]),
+    };
+    Ok(SignatureSubscriptionParams {
+        signature: param::<Signature>(signature_str, "signature")?,
+        commitment,
+        enable_received_notification: config.enable_received_notification.unwrap_or_default(),
+        followup_commitments,
+    })
+}
+
 impl RpcSolPubSubInternal for RpcSolPubSubImpl {
     fn account_subscribe(
         &self,
@@ -376,16 +1184,76 @@ impl RpcSolPubSubInternal for RpcSolPubSubImpl {
         config: Option<RpcAccountInfoConfig>,
     ) -> Result<SubscriptionId> {
         let config = config.unwrap_or_default();
-        let params = AccountSubscriptionParams {
-            pubkey: param::<Pubkey>(&pubkey_str, "pubkey")?,
+        let params = account_params(&pubkey_str, &config)?;
+        let id = self.subscribe(SubscriptionParams::Account(params.clone()))?;
+        if config.send_initial.unwrap_or_default() {
+            self.subscription_control.send_initial_account_state(
+                params,
+                id,
+                self.direct_sender.clone(),
+            );
+        }
+        Ok(id)
+    }
+
+    fn account_unsubscribe(&self, id: SubscriptionId) -> Result<bool> {
+        self.unsubscribe(id)
+    }
+
+    fn accounts_subscribe(
+        &self,
+        pubkey_strs: Vec<String>,
+        config: Option<RpcAccountInfoConfig>,
+    ) -> Result<SubscriptionId> {
+        if pubkey_strs.is_empty() || pubkey_strs.len() > self.config.max_accounts_per_subscription {
+            return Err(Error {
+                code: ErrorCode::InvalidParams,
+                message: format!(
+                    "Invalid Request: Between 1 and {} pubkeys must be provided",
+                    self.config.max_accounts_per_subscription
+                ),
+                data: None,
+            });
+        }
+        let mut pubkeys = pubkey_strs
+            .iter()
+            .map(|pubkey_str| param::<Pubkey>(pubkey_str, "pubkey"))
+            .collect::<Result<Vec<_>>>()?;
+        pubkeys.sort_unstable();
+        pubkeys.dedup();
+        let config = config.unwrap_or_default();
+        let params = AccountsSubscriptionParams {
+            pubkeys,
             commitment: config.commitment.unwrap_or_default(),
             data_slice: config.data_slice,
             encoding: config.encoding.unwrap_or(UiAccountEncoding::Binary),
         };
-        self.subscribe(SubscriptionParams::Account(params))
+        self.subscribe(SubscriptionParams::Accounts(params))
     }
 
-    fn account_unsubscribe(&self, id: SubscriptionId) -> Result<bool> {
+    fn accounts_unsubscribe(&self, id: SubscriptionId) -> Result<bool> {
+        self.unsubscribe(id)
+    }
+
+    fn token_accounts_by_owner_subscribe(
+        &self,
+        owner_str: String,
+        config: Option<RpcAccountInfoConfig>,
+    ) -> Result<SubscriptionId> {
+        if !self.config.enable_token_accounts_by_owner_subscription {
+            return Err(Error::new(jsonrpc_core::ErrorCode::MethodNotFound));
+        }
+        let config = config.unwrap_or_default();
+        let params = TokenAccountsByOwnerSubscriptionParams {
+            owner: param::<Pubkey>(&owner_str, "owner")?,
+            commitment: config.commitment.unwrap_or_default(),
+            data_slice: config.data_slice,
+            encoding: config.encoding.unwrap_or(UiAccountEncoding::JsonParsed),
+        };
+        self.subscribe(SubscriptionParams::TokenAccountsByOwner(params))
+    }
+
+    fn token_accounts_by_owner_unsubscribe(&self, id: SubscriptionId) -> Result<bool> {
         self.unsubscribe(id)
     }
 
@@ -395,21 +1263,36 @@ impl RpcSolPubSubInternal for RpcSolPubSubImpl {
         config: Option<RpcProgramAccountsConfig>,
     ) -> Result<SubscriptionId> {
         let config = config.unwrap_or_default();
-        let params = ProgramSubscriptionParams {
+        let send_initial = config.account_config.send_initial.unwrap_or_default();
+        let params = program_params(&pubkey_str, config)?;
+        let id = self.subscribe(SubscriptionParams::Program(params.clone()))?;
+        if send_initial {
+            self.subscription_control.send_initial_program_accounts(
+                params,
+                id,
+                self.direct_sender.clone(),
+            );
+        }
+        Ok(id)
+    }
+
+    fn program_unsubscribe(&self, id: SubscriptionId) -> Result<bool> {
+        self.unsubscribe(id)
+    }
+
+    fn program_changes_subscribe(
+        &self,
+        pubkey_str: String,
+        config: Option<CommitmentConfig>,
+    ) -> Result<SubscriptionId> {
+        let params = ProgramChangesSubscriptionParams {
             pubkey: param::<Pubkey>(&pubkey_str, "pubkey")?,
-            filters: config.filters.unwrap_or_default(),
-            encoding: config
-                .account_config
-                .encoding
-                .unwrap_or(UiAccountEncoding::Binary),
-            data_slice: config.account_config.data_slice,
-            commitment: config.account_config.commitment.unwrap_or_default(),
-            with_context: config.with_context.unwrap_or_default(),
+            commitment: config.unwrap_or_default(),
         };
-        self.subscribe(SubscriptionParams::Program(params))
+        self.subscribe(SubscriptionParams::ProgramChanges(params))
     }
 
-    fn program_unsubscribe(&self, id: SubscriptionId) -> Result<bool> {
+    fn program_changes_unsubscribe(&self, id: SubscriptionId) -> Result<bool> {
         self.unsubscribe(id)
     }
 
@@ -418,6 +1301,18 @@ impl RpcSolPubSubInternal for RpcSolPubSubImpl {
         filter: RpcTransactionLogsFilter,
         config: Option<RpcTransactionLogsConfig>,
     ) -> Result<SubscriptionId> {
+        let start_slot = config.as_ref().and_then(|c| c.start_slot);
+        let permit = start_slot
+            .map(|_| {
+                LogsReplayPermit::try_acquire(&self.pending_replays).ok_or_else(|| Error {
+                    code: ErrorCode::InternalError,
+                    message: "Internal Error: Subscription refused. Too many logs replays in \
+                              progress"
+                        .into(),
+                    data: None,
+                })
+            })
+            .transpose()?;
         let params = LogsSubscriptionParams {
             kind: match filter {
                 RpcTransactionLogsFilter::All => LogsSubscriptionKind::All,
@@ -433,26 +1328,67 @@ impl RpcSolPubSubInternal for RpcSolPubSubImpl {
                     LogsSubscriptionKind::Single(param::<Pubkey>(&keys[0], "mentions")?)
                 }
             },
-            commitment: config.and_then(|c| c.commitment).unwrap_or_default(),
+            commitment: config
+                .as_ref()
+                .and_then(|c| c.commitment)
+                .unwrap_or_default(),
+            filter_status: config.and_then(|c| c.filter_status).unwrap_or_default(),
         };
-        self.subscribe(SubscriptionParams::Logs(params))
+        let id = self.subscribe(SubscriptionParams::Logs(params.clone()))?;
+        if let (Some(start_slot), Some(permit)) = (start_slot, permit) {
+            self.subscription_control.send_logs_replay(
+                params,
+                start_slot,
+                id,
+                self.direct_sender.clone(),
+                permit,
+            );
+        }
+        Ok(id)
     }
 
     fn logs_unsubscribe(&self, id: SubscriptionId) -> Result<bool> {
         self.unsubscribe(id)
     }
 
+    fn mint_subscribe(
+        &self,
+        pubkey_str: String,
+        config: Option<RpcMintSubscribeConfig>,
+    ) -> Result<SubscriptionId> {
+        let params = MintSubscriptionParams {
+            pubkey: param::<Pubkey>(&pubkey_str, "pubkey")?,
+            commitment: config.and_then(|c| c.commitment).unwrap_or_default(),
+        };
+        self.subscribe(SubscriptionParams::Mint(params))
+    }
+
+    fn mint_unsubscribe(&self, id: SubscriptionId) -> Result<bool> {
+        self.unsubscribe(id)
+    }
+
+    fn stake_subscribe(
+        &self,
+        pubkey_str: String,
+        config: Option<CommitmentConfig>,
+    ) -> Result<SubscriptionId> {
+        let params = StakeSubscriptionParams {
+            pubkey: param::<Pubkey>(&pubkey_str, "pubkey")?,
+            commitment: config.unwrap_or_default(),
+        };
+        self.subscribe(SubscriptionParams::Stake(params))
+    }
+
+    fn stake_unsubscribe(&self, id: SubscriptionId) -> Result<bool> {
+        self.unsubscribe(id)
+    }
+
     fn signature_subscribe(
         &self,
         signature_str: String,
         config: Option<RpcSignatureSubscribeConfig>,
     ) -> Result<SubscriptionId> {
-        let config = config.unwrap_or_default();
-        let params = SignatureSubscriptionParams {
-            signature: param::<Signature>(&signature_str, "signature")?,
-            commitment: config.commitment.unwrap_or_default(),
-            enable_received_notification: config.enable_received_notification.unwrap_or_default(),
-        };
+        let params = signature_params(&signature_str, config.unwrap_or_default())?;
         self.subscribe(SubscriptionParams::Signature(params))
     }
 
@@ -460,6 +1396,85 @@ impl RpcSolPubSubInternal for RpcSolPubSubImpl {
         self.unsubscribe(id)
     }
 
+    fn get_recent_signature_notification(
+        &self,
+        signature_str: String,
+    ) -> Result<Option<RpcRecentSignatureNotification>> {
+        let signature = param::<Signature>(&signature_str, "signature")?;
+        Ok(self
+            .subscription_control
+            .recent_signatures()
+            .get(&signature))
+    }
+
+    fn multiplex_subscribe(&self, items: Vec<RpcMultiplexSubscribeItem>) -> Result<SubscriptionId> {
+        if items.is_empty() || items.len() > self.config.max_accounts_per_subscription {
+            return Err(Error {
+                code: ErrorCode::InvalidParams,
+                message: format!(
+                    "Invalid Request: Between 1 and {} items must be provided",
+                    self.config.max_accounts_per_subscription
+                ),
+                data: None,
+            });
+        }
+        let mut tags = HashSet::new();
+        let items = items
+            .into_iter()
+            .map(|item| {
+                if !tags.insert(item.tag.clone()) {
+                    return Err(Error {
+                        code: ErrorCode::InvalidParams,
+                        message: format!("Invalid Request: Duplicate tag {}", item.tag),
+                        data: None,
+                    });
+                }
+                let params = match item.kind {
+                    RpcMultiplexSubscribeKind::Account { pubkey, config } => {
+                        SubscriptionParams::Account(account_params(
+                            &pubkey,
+                            &config.unwrap_or_default(),
+                        )?)
+                    }
+                    RpcMultiplexSubscribeKind::Program { pubkey, config } => {
+                        SubscriptionParams::Program(program_params(
+                            &pubkey,
+                            config.unwrap_or_default(),
+                        )?)
+                    }
+                    RpcMultiplexSubscribeKind::Signature { signature, config } => {
+                        SubscriptionParams::Signature(signature_params(
+                            &signature,
+                            config.unwrap_or_default(),
+                        )?)
+                    }
+                };
+                self.check_commitment(&params)?;
+                Ok((item.tag, params))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.check_connection_limit(items.len())?;
+        let token = self
+            .subscription_control
+            .subscribe_composite(items, self.client_ip)
+            .map_err(subscription_error)?;
+        let id = token.id();
+        self.composite_subscriptions.write().unwrap().insert(token);
+        Ok(id)
+    }
+
+    fn multiplex_unsubscribe(&self, id: SubscriptionId) -> Result<bool> {
+        if self.composite_subscriptions.write().unwrap().remove(id) {
+            Ok(true)
+        } else {
+            Err(Error {
+                code: ErrorCode::InvalidParams,
+                message: "Invalid subscription id.".into(),
+                data: None,
+            })
+        }
+    }
+
     fn slot_subscribe(&self) -> Result<SubscriptionId> {
         self.subscribe(SubscriptionParams::Slot)
     }
@@ -468,6 +1483,35 @@ impl RpcSolPubSubInternal for RpcSolPubSubImpl {
         self.unsubscribe(id)
     }
 
+    fn blockhash_subscribe(&self, config: Option<CommitmentConfig>) -> Result<SubscriptionId> {
+        let params = BlockhashSubscriptionParams {
+            commitment: config.unwrap_or_default(),
+        };
+        self.subscribe(SubscriptionParams::Blockhash(params))
+    }
+
+    fn blockhash_unsubscribe(&self, id: SubscriptionId) -> Result<bool> {
+        self.unsubscribe(id)
+    }
+
+    fn blockhash_validity_subscribe(
+        &self,
+        blockhash_str: String,
+        config: Option<RpcBlockhashValiditySubscribeConfig>,
+    ) -> Result<SubscriptionId> {
+        let config = config.unwrap_or_default();
+        let params = BlockhashValiditySubscriptionParams {
+            blockhash: param::<Hash>(&blockhash_str, "blockhash")?,
+            commitment: config.commitment.unwrap_or_default(),
+            expiry_warning_blocks: config.expiry_warning_blocks,
+        };
+        self.subscribe(SubscriptionParams::BlockhashValidity(params))
+    }
+
+    fn blockhash_validity_unsubscribe(&self, id: SubscriptionId) -> Result<bool> {
+        self.unsubscribe(id)
+    }
+
     fn slots_updates_subscribe(&self) -> Result<SubscriptionId> {
         self.subscribe(SubscriptionParams::SlotsUpdates)
     }
@@ -476,56 +1520,250 @@ impl RpcSolPubSubInternal for RpcSolPubSubImpl {
         self.unsubscribe(id)
     }
 
-    fn vote_subscribe(&self) -> Result<SubscriptionId> {
-        if !self.config.enable_vote_subscription {
+    fn block_subscribe(
+        &self,
+        filter: RpcBlockSubscribeFilter,
+        config: Option<RpcBlockSubscribeConfig>,
+    ) -> Result<SubscriptionId> {
+        if !self.config.enable_block_subscription {
             return Err(Error::new(jsonrpc_core::ErrorCode::MethodNotFound));
         }
-        self.subscribe(SubscriptionParams::Vote)
+        let config = config.unwrap_or_default();
+        let commitment = config.commitment.unwrap_or_default();
+        if !commitment.is_at_least_confirmed() {
+            return Err(Error {
+                code: ErrorCode::InvalidParams,
+                message: "Invalid Request: Only confirmed or finalized commitment is supported"
+                    .into(),
+                data: None,
+            });
+        }
+        let params = BlockSubscriptionParams {
+            commitment,
+            encoding: config.encoding.unwrap_or(UiTransactionEncoding::Base64),
+            kind: match filter {
+                RpcBlockSubscribeFilter::All => BlockSubscriptionKind::All,
+                RpcBlockSubscribeFilter::MentionsAccountOrProgram(key) => {
+                    BlockSubscriptionKind::MentionsAccountOrProgram(param::<Pubkey>(
+                        &key,
+                        "mentions_account_or_program",
+                    )?)
+                }
+            },
+            transaction_details: config.transaction_details.unwrap_or_default(),
+            show_rewards: config.show_rewards.unwrap_or_default(),
+        };
+        self.subscribe(SubscriptionParams::Block(params))
     }
 
-    fn vote_unsubscribe(&self, id: SubscriptionId) -> Result<bool> {
-        if !self.config.enable_vote_subscription {
+    fn block_unsubscribe(&self, id: SubscriptionId) -> Result<bool> {
+        if !self.config.enable_block_subscription {
             return Err(Error::new(jsonrpc_core::ErrorCode::MethodNotFound));
         }
         self.unsubscribe(id)
     }
 
-    fn root_subscribe(&self) -> Result<SubscriptionId> {
-        self.subscribe(SubscriptionParams::Root)
+    fn transaction_subscribe(
+        &self,
+        pubkey_str: String,
+        config: Option<RpcTransactionSubscribeConfig>,
+    ) -> Result<SubscriptionId> {
+        if !self.config.enable_transaction_subscription {
+            return Err(Error::new(jsonrpc_core::ErrorCode::MethodNotFound));
+        }
+        let config = config.unwrap_or_default();
+        let commitment = config.commitment.unwrap_or_default();
+        if !commitment.is_at_least_confirmed() {
+            return Err(Error {
+                code: ErrorCode::InvalidParams,
+                message: "Invalid Request: Only confirmed or finalized commitment is supported"
+                    .into(),
+                data: None,
+            });
+        }
+        let params = TransactionSubscriptionParams {
+            pubkey: param::<Pubkey>(&pubkey_str, "pubkey")?,
+            commitment,
+            encoding: config.encoding.unwrap_or(UiTransactionEncoding::JsonParsed),
+        };
+        self.subscribe(SubscriptionParams::Transaction(params))
     }
 
-    fn root_unsubscribe(&self, id: SubscriptionId) -> Result<bool> {
+    fn transaction_unsubscribe(&self, id: SubscriptionId) -> Result<bool> {
+        if !self.config.enable_transaction_subscription {
+            return Err(Error::new(jsonrpc_core::ErrorCode::MethodNotFound));
+        }
         self.unsubscribe(id)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use {
-        super::{RpcSolPubSubInternal, *},
-        crate::{
-            optimistically_confirmed_bank_tracker::OptimisticallyConfirmedBank, rpc_pubsub_service,
-            rpc_subscriptions::RpcSubscriptions,
-        },
-        jsonrpc_core::{IoHandler, Response},
+    fn vote_subscribe(&self, config: Option<RpcVoteSubscribeConfig>) -> Result<SubscriptionId> {
+        if !self.config.enable_vote_subscription {
+            return Err(Error::new(jsonrpc_core::ErrorCode::MethodNotFound));
+        }
+        let config = config.unwrap_or_default();
+        let vote_pubkeys = match config.vote_pubkeys {
+            Some(pubkey_strs) => {
+                if pubkey_strs.is_empty()
+                    || pubkey_strs.len() > self.config.max_accounts_per_subscription
+                {
+                    return Err(Error {
+                        code: ErrorCode::InvalidParams,
+                        message: format!(
+                            "Invalid Request: Between 1 and {} vote pubkeys must be provided",
+                            self.config.max_accounts_per_subscription
+                        ),
+                        data: None,
+                    });
+                }
+                let mut vote_pubkeys = pubkey_strs
+                    .iter()
+                    .map(|pubkey_str| param::<Pubkey>(pubkey_str, "vote pubkey"))
+                    .collect::<Result<Vec<_>>>()?;
+                vote_pubkeys.sort_unstable();
+                vote_pubkeys.dedup();
+                Some(vote_pubkeys)
+            }
+            None => None,
+        };
+        self.subscribe(SubscriptionParams::Vote(VoteSubscriptionParams {
+            vote_pubkeys,
+        }))
+    }
+
+    fn vote_unsubscribe(&self, id: SubscriptionId) -> Result<bool> {
+        if !self.config.enable_vote_subscription {
+            return Err(Error::new(jsonrpc_core::ErrorCode::MethodNotFound));
+        }
+        self.unsubscribe(id)
+    }
+
+    fn root_subscribe(&self, config: Option<RpcRootSubscribeConfig>) -> Result<SubscriptionId> {
+        let config = config.unwrap_or_default();
+        let params = RootSubscriptionParams {
+            enable_block_info: config.enable_block_info.unwrap_or_default(),
+        };
+        self.subscribe(SubscriptionParams::Root(params))
+    }
+
+    fn root_unsubscribe(&self, id: SubscriptionId) -> Result<bool> {
+        self.unsubscribe(id)
+    }
+
+    fn epoch_subscribe(&self) -> Result<SubscriptionId> {
+        self.subscribe(SubscriptionParams::Epoch)
+    }
+
+    fn epoch_unsubscribe(&self, id: SubscriptionId) -> Result<bool> {
+        self.unsubscribe(id)
+    }
+
+    fn leader_schedule_subscribe(
+        &self,
+        config: Option<RpcLeaderScheduleSubscribeConfig>,
+    ) -> Result<SubscriptionId> {
+        let config = config.unwrap_or_default();
+        let num_slots = config
+            .num_slots
+            .unwrap_or(DEFAULT_LEADER_SCHEDULE_SUBSCRIBE_SLOTS);
+        if num_slots == 0 || num_slots > MAX_LEADER_SCHEDULE_SUBSCRIBE_SLOTS {
+            return Err(Error {
+                code: ErrorCode::InvalidParams,
+                message: format!(
+                    "Invalid Request: numSlots must be between 1 and {}",
+                    MAX_LEADER_SCHEDULE_SUBSCRIBE_SLOTS
+                ),
+                data: None,
+            });
+        }
+        let identity = config
+            .identity
+            .map(|identity| param::<Pubkey>(&identity, "identity"))
+            .transpose()?;
+        self.subscribe(SubscriptionParams::LeaderSchedule(
+            LeaderScheduleSubscriptionParams {
+                identity,
+                num_slots,
+            },
+        ))
+    }
+
+    fn leader_schedule_unsubscribe(&self, id: SubscriptionId) -> Result<bool> {
+        self.unsubscribe(id)
+    }
+
+    fn rewards_subscribe(&self, pubkey_strs: Vec<String>) -> Result<SubscriptionId> {
+        if pubkey_strs.is_empty() || pubkey_strs.len() > self.config.max_accounts_per_subscription {
+            return Err(Error {
+                code: ErrorCode::InvalidParams,
+                message: format!(
+                    "Invalid Request: Between 1 and {} addresses must be provided",
+                    self.config.max_accounts_per_subscription
+                ),
+                data: None,
+            });
+        }
+        let mut addresses = pubkey_strs
+            .iter()
+            .map(|pubkey_str| param::<Pubkey>(pubkey_str, "pubkey"))
+            .collect::<Result<Vec<_>>>()?;
+        addresses.sort_unstable();
+        addresses.dedup();
+        self.subscribe(SubscriptionParams::Rewards(RewardsSubscriptionParams {
+            addresses,
+        }))
+    }
+
+    fn rewards_unsubscribe(&self, id: SubscriptionId) -> Result<bool> {
+        self.unsubscribe(id)
+    }
+
+    fn performance_samples_subscribe(&self) -> Result<SubscriptionId> {
+        self.subscribe(SubscriptionParams::PerformanceSamples)
+    }
+
+    fn performance_samples_unsubscribe(&self, id: SubscriptionId) -> Result<bool> {
+        self.unsubscribe(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{RpcSolPubSubInternal, *},
+        crate::{
+            optimistically_confirmed_bank_tracker::OptimisticallyConfirmedBank, rpc_pubsub_service,
+            rpc_subscriptions::RpcSubscriptions,
+        },
+        jsonrpc_core::{IoHandler, Response},
         serial_test::serial,
-        solana_account_decoder::{parse_account_data::parse_account_data, UiAccountEncoding},
-        solana_client::rpc_response::{
-            ProcessedSignatureResult, ReceivedSignatureResult, RpcSignatureResult, SlotInfo,
+        solana_account_decoder::{
+            parse_account_data::parse_account_data,
+            parse_address_lookup_table::address_lookup_table_id, parse_token::spl_token_id_v2_0,
+            UiAccount, UiAccountEncoding, UiDataSliceConfig,
+        },
+        solana_client::{
+            rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType},
+            rpc_response::{
+                ProcessedSignatureResult, ReceivedSignatureResult, RpcAccountProof,
+                RpcSignatureResult, SlotInfo,
+            },
         },
         solana_runtime::{
+            accounts_db::{AccountShrinkThreshold, AccountsDb},
+            accounts_hash::{AccountsHash, MerkleProofLevel},
+            accounts_index::{AccountIndex, AccountSecondaryIndexes},
             bank::Bank,
             bank_forks::BankForks,
             commitment::{BlockCommitmentCache, CommitmentSlots},
             genesis_utils::{
-                create_genesis_config, create_genesis_config_with_vote_accounts, GenesisConfigInfo,
-                ValidatorVoteKeypairs,
+                create_genesis_config, create_genesis_config_with_leader,
+                create_genesis_config_with_vote_accounts, GenesisConfigInfo, ValidatorVoteKeypairs,
             },
         },
         solana_sdk::{
-            account::ReadableAccount,
+            account::{AccountSharedData, ReadableAccount, WritableAccount},
             clock::Slot,
-            commitment_config::CommitmentConfig,
+            commitment_config::{CommitmentConfig, CommitmentLevel},
             hash::Hash,
             message::Message,
             pubkey::Pubkey,
@@ -538,7 +1776,12 @@ mod tests {
             transaction::{self, Transaction},
         },
         solana_stake_program::stake_state,
+        solana_transaction_status::TransactionConfirmationStatus,
         solana_vote_program::vote_state::Vote,
+        spl_token_v2_0::{
+            solana_program::{program_pack::Pack, pubkey::Pubkey as SplTokenPubkey},
+            state::{Account as TokenAccount, AccountState as TokenAccountState},
+        },
         std::{
             sync::{atomic::AtomicBool, RwLock},
             thread::sleep,
@@ -604,8 +1847,10 @@ mod tests {
 
         // Test signature confirmation notification
         let response = receiver.recv();
-        let expected_res =
-            RpcSignatureResult::ProcessedSignature(ProcessedSignatureResult { err: None });
+        let expected_res = RpcSignatureResult::ProcessedSignature(ProcessedSignatureResult {
+            err: None,
+            confirmation_status: None,
+        });
         let expected = json!({
            "jsonrpc": "2.0",
            "method": "signatureNotification",
@@ -631,6 +1876,7 @@ mod tests {
             Some(RpcSignatureSubscribeConfig {
                 commitment: Some(CommitmentConfig::finalized()),
                 enable_received_notification: Some(true),
+                commitment_levels: None,
             }),
         )
         .unwrap();
@@ -665,6 +1911,7 @@ mod tests {
             Some(RpcSignatureSubscribeConfig {
                 commitment: Some(CommitmentConfig::confirmed()),
                 enable_received_notification: Some(true),
+                commitment_levels: None,
             }),
         )
         .unwrap();
@@ -687,70 +1934,813 @@ mod tests {
            }
         });
         assert_eq!(
-            expected,
-            serde_json::from_str::<serde_json::Value>(&response).unwrap(),
+            expected,
+            serde_json::from_str::<serde_json::Value>(&response).unwrap(),
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_signature_subscribe_commitment_levels() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair: alice,
+            ..
+        } = create_genesis_config(10_000);
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+        let bank = Bank::new_for_tests(&genesis_config);
+        let blockhash = bank.last_blockhash();
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let rpc_subscriptions = Arc::new(RpcSubscriptions::new_for_tests(
+            &Arc::new(AtomicBool::new(false)),
+            bank_forks.clone(),
+            Arc::new(RwLock::new(BlockCommitmentCache::new_for_tests())),
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks),
+        ));
+        let tx = system_transaction::transfer(&alice, &bob_pubkey, 20, blockhash);
+        let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&rpc_subscriptions);
+
+        // Levels must be strictly increasing
+        assert!(rpc
+            .signature_subscribe(
+                tx.signatures[0].to_string(),
+                Some(RpcSignatureSubscribeConfig {
+                    commitment_levels: Some(vec![
+                        CommitmentLevel::Finalized,
+                        CommitmentLevel::Confirmed,
+                    ]),
+                    ..RpcSignatureSubscribeConfig::default()
+                }),
+            )
+            .is_err());
+        assert!(rpc
+            .signature_subscribe(
+                tx.signatures[0].to_string(),
+                Some(RpcSignatureSubscribeConfig {
+                    commitment_levels: Some(vec![]),
+                    ..RpcSignatureSubscribeConfig::default()
+                }),
+            )
+            .is_err());
+
+        let sub_id = rpc
+            .signature_subscribe(
+                tx.signatures[0].to_string(),
+                Some(RpcSignatureSubscribeConfig {
+                    commitment_levels: Some(vec![
+                        CommitmentLevel::Processed,
+                        CommitmentLevel::Finalized,
+                    ]),
+                    ..RpcSignatureSubscribeConfig::default()
+                }),
+            )
+            .unwrap();
+
+        process_transaction_and_notify(&bank_forks, &tx, &rpc_subscriptions, 0).unwrap();
+
+        for confirmation_status in &[
+            TransactionConfirmationStatus::Processed,
+            TransactionConfirmationStatus::Finalized,
+        ] {
+            let response = receiver.recv();
+            let expected = json!({
+               "jsonrpc": "2.0",
+               "method": "signatureNotification",
+               "params": {
+                   "result": {
+                       "context": { "slot": 0 },
+                       "value": RpcSignatureResult::ProcessedSignature(ProcessedSignatureResult {
+                           err: None,
+                           confirmation_status: Some(confirmation_status.clone()),
+                       }),
+                   },
+                   "subscription": u64::from(sub_id),
+               }
+            });
+            assert_eq!(
+                expected,
+                serde_json::from_str::<serde_json::Value>(&response).unwrap(),
+            );
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_signature_unsubscribe() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair: alice,
+            ..
+        } = create_genesis_config(10_000);
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+        let bank = Bank::new_for_tests(&genesis_config);
+        let blockhash = bank.last_blockhash();
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+
+        let mut io = IoHandler::<()>::default();
+        let subscriptions = Arc::new(RpcSubscriptions::default_with_bank_forks(bank_forks));
+        let (rpc, _receiver) = rpc_pubsub_service::test_connection(&subscriptions);
+
+        io.extend_with(rpc.to_delegate());
+
+        let tx = system_transaction::transfer(&alice, &bob_pubkey, 20, blockhash);
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"signatureSubscribe","params":["{}"]}}"#,
+            tx.signatures[0]
+        );
+        let _res = io.handle_request_sync(&req);
+
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"signatureUnsubscribe","params":[0]}"#;
+        let res = io.handle_request_sync(req);
+
+        let expected = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+        let expected: Response = serde_json::from_str(expected).unwrap();
+
+        let result: Response = serde_json::from_str(&res.unwrap()).unwrap();
+        assert_eq!(expected, result);
+
+        // Test bad parameter
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"signatureUnsubscribe","params":[1]}"#;
+        let res = io.handle_request_sync(req);
+        let expected = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Invalid subscription id."},"id":1}"#;
+        let expected: Response = serde_json::from_str(expected).unwrap();
+
+        let result: Response = serde_json::from_str(&res.unwrap()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    #[serial]
+    fn test_account_subscribe() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair: alice,
+            ..
+        } = create_genesis_config(10_000);
+
+        let new_stake_authority = solana_sdk::pubkey::new_rand();
+        let stake_authority = Keypair::new();
+        let from = Keypair::new();
+        let stake_account = Keypair::new();
+        let stake_program_id = stake::program::id();
+        let bank = Bank::new_for_tests(&genesis_config);
+        let blockhash = bank.last_blockhash();
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let bank0 = bank_forks.read().unwrap().get(0).unwrap().clone();
+        let bank1 = Bank::new_from_parent(&bank0, &Pubkey::default(), 1);
+        bank_forks.write().unwrap().insert(bank1);
+
+        let rpc_subscriptions = Arc::new(RpcSubscriptions::new_for_tests(
+            &Arc::new(AtomicBool::new(false)),
+            bank_forks.clone(),
+            Arc::new(RwLock::new(BlockCommitmentCache::new_for_tests_with_slots(
+                1, 1,
+            ))),
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks),
+        ));
+
+        let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&rpc_subscriptions);
+
+        let encoding = UiAccountEncoding::Base64;
+
+        rpc.account_subscribe(
+            stake_account.pubkey().to_string(),
+            Some(RpcAccountInfoConfig {
+                commitment: Some(CommitmentConfig::processed()),
+                encoding: Some(encoding),
+                data_slice: None,
+                send_initial: None,
+                compression_level: None,
+                with_proof: None,
+            }),
+        )
+        .unwrap();
+
+        // Make sure the subscription is processed before continuing.
+        let (rpc2, mut receiver2) = rpc_pubsub_service::test_connection(&rpc_subscriptions);
+        rpc2.slot_subscribe().unwrap();
+        rpc_subscriptions.notify_slot(1, 0, 0);
+        receiver2.recv();
+
+        let tx = system_transaction::transfer(&alice, &from.pubkey(), 51, blockhash);
+        process_transaction_and_notify(&bank_forks, &tx, &rpc_subscriptions, 1).unwrap();
+
+        let authorized = Authorized::auto(&stake_authority.pubkey());
+        let ixs = stake_instruction::create_account(
+            &from.pubkey(),
+            &stake_account.pubkey(),
+            &authorized,
+            &Lockup::default(),
+            51,
+        );
+        let message = Message::new(&ixs, Some(&from.pubkey()));
+        let tx = Transaction::new(&[&from, &stake_account], message, blockhash);
+        process_transaction_and_notify(&bank_forks, &tx, &rpc_subscriptions, 1).unwrap();
+
+        // Test signature confirmation notification #1
+        let account = bank_forks
+            .read()
+            .unwrap()
+            .get(1)
+            .unwrap()
+            .get_account(&stake_account.pubkey())
+            .unwrap();
+        let expected_data = account.data();
+        let expected = json!({
+           "jsonrpc": "2.0",
+           "method": "accountNotification",
+           "params": {
+               "result": {
+                   "context": { "slot": 1 },
+                   "value": {
+                       "owner": stake_program_id.to_string(),
+                       "lamports": 51,
+                       "data": [base64::encode(expected_data), encoding],
+                       "executable": false,
+                       "rentEpoch": 0,
+                   },
+               },
+               "subscription": 0,
+           }
+        });
+
+        let response = receiver.recv();
+        assert_eq!(
+            expected,
+            serde_json::from_str::<serde_json::Value>(&response).unwrap(),
+        );
+
+        let tx = system_transaction::transfer(&alice, &stake_authority.pubkey(), 1, blockhash);
+        process_transaction_and_notify(&bank_forks, &tx, &rpc_subscriptions, 1).unwrap();
+        sleep(Duration::from_millis(200));
+        let ix = stake_instruction::authorize(
+            &stake_account.pubkey(),
+            &stake_authority.pubkey(),
+            &new_stake_authority,
+            StakeAuthorize::Staker,
+            None,
+        );
+        let message = Message::new(&[ix], Some(&stake_authority.pubkey()));
+        let tx = Transaction::new(&[&stake_authority], message, blockhash);
+        process_transaction_and_notify(&bank_forks, &tx, &rpc_subscriptions, 1).unwrap();
+        sleep(Duration::from_millis(200));
+
+        let bank = bank_forks.read().unwrap()[1].clone();
+        let account = bank.get_account(&stake_account.pubkey()).unwrap();
+        assert_eq!(
+            stake_state::authorized_from(&account).unwrap().staker,
+            new_stake_authority
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_multiplex_subscribe() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair: alice,
+            ..
+        } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let blockhash = bank.last_blockhash();
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let bank0 = bank_forks.read().unwrap().get(0).unwrap().clone();
+        let bank1 = Bank::new_from_parent(&bank0, &Pubkey::default(), 1);
+        bank_forks.write().unwrap().insert(bank1);
+        let rpc_subscriptions = Arc::new(RpcSubscriptions::new_for_tests(
+            &Arc::new(AtomicBool::new(false)),
+            bank_forks.clone(),
+            Arc::new(RwLock::new(BlockCommitmentCache::new_for_tests_with_slots(
+                1, 1,
+            ))),
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks),
+        ));
+        let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&rpc_subscriptions);
+
+        let to = solana_sdk::pubkey::new_rand();
+        let account_config = RpcAccountInfoConfig {
+            commitment: Some(CommitmentConfig::processed()),
+            ..RpcAccountInfoConfig::default()
+        };
+        let account_item = |tag: &str| RpcMultiplexSubscribeItem {
+            tag: tag.to_string(),
+            kind: RpcMultiplexSubscribeKind::Account {
+                pubkey: to.to_string(),
+                config: Some(account_config.clone()),
+            },
+        };
+        let err = rpc
+            .multiplex_subscribe(vec![account_item("to"), account_item("to")])
+            .unwrap_err();
+        assert_eq!(err.code, ErrorCode::InvalidParams);
+
+        // The account item shares the subscription of the plain account subscription
+        let account_id = rpc
+            .account_subscribe(to.to_string(), Some(account_config.clone()))
+            .unwrap();
+        let composite_id = rpc
+            .multiplex_subscribe(vec![
+                account_item("to"),
+                RpcMultiplexSubscribeItem {
+                    tag: "signature".to_string(),
+                    kind: RpcMultiplexSubscribeKind::Signature {
+                        signature: Signature::default().to_string(),
+                        config: None,
+                    },
+                },
+            ])
+            .unwrap();
+        assert_ne!(account_id, composite_id);
+
+        // Make sure the subscriptions are processed before continuing.
+        let (rpc2, mut receiver2) = rpc_pubsub_service::test_connection(&rpc_subscriptions);
+        rpc2.slot_subscribe().unwrap();
+        rpc_subscriptions.notify_slot(1, 0, 0);
+        receiver2.recv();
+
+        let tx = system_transaction::transfer(&alice, &to, 51, blockhash);
+        process_transaction_and_notify(&bank_forks, &tx, &rpc_subscriptions, 1).unwrap();
+
+        let plain: serde_json::Value = serde_json::from_str(&receiver.recv()).unwrap();
+        assert_eq!(plain["params"]["subscription"], json!(account_id));
+        assert!(plain["params"].get("tag").is_none());
+        let tagged: serde_json::Value = serde_json::from_str(&receiver.recv()).unwrap();
+        assert_eq!(tagged["method"], "multiplexNotification");
+        assert_eq!(tagged["params"]["subscription"], json!(composite_id));
+        assert_eq!(tagged["params"]["tag"], "to");
+        assert_eq!(tagged["params"]["result"], plain["params"]["result"]);
+
+        assert!(rpc.multiplex_unsubscribe(composite_id).is_ok());
+        assert!(rpc.multiplex_unsubscribe(composite_id).is_err());
+        assert!(rpc.account_unsubscribe(account_id).is_ok());
+    }
+
+    #[test]
+    #[serial]
+    fn test_account_subscribe_send_initial() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair: alice,
+            ..
+        } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let blockhash = bank.last_blockhash();
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let bank0 = bank_forks.read().unwrap().get(0).unwrap().clone();
+        let bank1 = Bank::new_from_parent(&bank0, &Pubkey::default(), 1);
+        bank_forks.write().unwrap().insert(bank1);
+        let rpc_subscriptions = Arc::new(RpcSubscriptions::new_for_tests(
+            &Arc::new(AtomicBool::new(false)),
+            bank_forks.clone(),
+            Arc::new(RwLock::new(BlockCommitmentCache::new_for_tests_with_slots(
+                1, 1,
+            ))),
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks),
+        ));
+        let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&rpc_subscriptions);
+
+        let sub_id = rpc
+            .account_subscribe(
+                alice.pubkey().to_string(),
+                Some(RpcAccountInfoConfig {
+                    commitment: Some(CommitmentConfig::processed()),
+                    encoding: Some(UiAccountEncoding::Base64),
+                    send_initial: Some(true),
+                    ..RpcAccountInfoConfig::default()
+                }),
+            )
+            .unwrap();
+
+        // The current state is sent without any bank notification
+        let response: serde_json::Value = serde_json::from_str(&receiver.recv()).unwrap();
+        assert_eq!(response["method"], "accountNotification");
+        assert_eq!(response["params"]["subscription"], u64::from(sub_id));
+        assert_eq!(response["params"]["result"]["context"]["slot"], 1);
+        assert_eq!(response["params"]["result"]["value"]["lamports"], 10_000);
+
+        // Followed by the changes
+        let tx = system_transaction::transfer(&alice, &Pubkey::new_unique(), 1, blockhash);
+        process_transaction_and_notify(&bank_forks, &tx, &rpc_subscriptions, 1).unwrap();
+        let response: serde_json::Value = serde_json::from_str(&receiver.recv()).unwrap();
+        assert_eq!(response["method"], "accountNotification");
+        assert!(
+            response["params"]["result"]["value"]["lamports"]
+                .as_u64()
+                .unwrap()
+                < 10_000
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_account_subscribe_with_proof() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let bank0 = bank_forks.read().unwrap().get(0).unwrap().clone();
+        let bank1 = Bank::new_from_parent(&bank0, &Pubkey::default(), 1);
+        let pubkey = Pubkey::new_unique();
+        bank1.transfer(100, &mint_keypair, &pubkey).unwrap();
+        bank1.freeze();
+        let bank1 = bank_forks.write().unwrap().insert(bank1);
+
+        let rpc_subscriptions = Arc::new(RpcSubscriptions::new_for_tests(
+            &Arc::new(AtomicBool::new(false)),
+            bank_forks.clone(),
+            Arc::new(RwLock::new(BlockCommitmentCache::new_for_tests_with_slots(
+                1, 1,
+            ))),
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks),
+        ));
+        let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&rpc_subscriptions);
+        let subscribe = |encoding, data_slice| {
+            rpc.account_subscribe(
+                pubkey.to_string(),
+                Some(RpcAccountInfoConfig {
+                    commitment: Some(CommitmentConfig::processed()),
+                    encoding: Some(encoding),
+                    data_slice,
+                    with_proof: Some(true),
+                    ..RpcAccountInfoConfig::default()
+                }),
+            )
+        };
+        subscribe(UiAccountEncoding::Base64, None).unwrap();
+
+        // Make sure the subscription is processed before continuing.
+        let (rpc2, mut receiver2) = rpc_pubsub_service::test_connection(&rpc_subscriptions);
+        rpc2.slot_subscribe().unwrap();
+        rpc_subscriptions.notify_slot(1, 0, 0);
+        receiver2.recv();
+
+        rpc_subscriptions.notify_subscribers(CommitmentSlots {
+            slot: 1,
+            ..CommitmentSlots::default()
+        });
+        let response: serde_json::Value = serde_json::from_str(&receiver.recv()).unwrap();
+        let value = &response["params"]["result"]["value"];
+        assert_eq!(value["lamports"], 100);
+        let proof: RpcAccountProof = serde_json::from_value(value["proof"].clone()).unwrap();
+        assert_eq!(proof.slot, 1);
+
+        // The bank hash follows from the account and the proof alone
+        let account = bank1.get_account(&pubkey).unwrap();
+        let account_hash = AccountsDb::hash_account(proof.slot, &account, &pubkey);
+        assert_eq!(proof.account_hash, account_hash.to_string());
+        let path: Vec<_> = proof
+            .inclusion_path
+            .iter()
+            .map(|level| MerkleProofLevel {
+                index: level.index,
+                siblings: level
+                    .siblings
+                    .iter()
+                    .map(|sibling| sibling.parse().unwrap())
+                    .collect(),
+            })
+            .collect();
+        let accounts_delta_hash = AccountsHash::compute_merkle_root_from_proof(account_hash, &path);
+        assert_eq!(proof.accounts_delta_hash, accounts_delta_hash.to_string());
+        assert_eq!(proof.parent_bank_hash, bank0.hash().to_string());
+        let bank_hash = Bank::hash_bank_state(
+            &bank0.hash(),
+            &accounts_delta_hash,
+            proof.signature_count,
+            &proof.last_blockhash.parse().unwrap(),
+        );
+        assert_eq!(bank_hash, bank1.hash());
+        assert_eq!(proof.bank_hash, bank_hash.to_string());
+
+        for (encoding, data_slice) in [
+            (UiAccountEncoding::JsonParsed, None),
+            (
+                UiAccountEncoding::Base64,
+                Some(UiDataSliceConfig {
+                    offset: 0,
+                    length: 1,
+                }),
+            ),
+        ] {
+            let err = subscribe(encoding, data_slice).unwrap_err();
+            assert_eq!(err.code, ErrorCode::InvalidParams);
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_account_subscribe_compression_level() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let rpc_subscriptions = Arc::new(RpcSubscriptions::default_with_bank_forks(
+            bank_forks.clone(),
+        ));
+        let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&rpc_subscriptions);
+        let pubkey = Pubkey::new_unique();
+        let data: Vec<u8> = (0..10_240).map(|i| (i % 7) as u8).collect();
+        let mut account = AccountSharedData::new(1, data.len(), &Pubkey::new_unique());
+        account.set_data(data.clone());
+        bank_forks
+            .read()
+            .unwrap()
+            .working_bank()
+            .store_account(&pubkey, &account);
+        let subscribe = |encoding, compression_level| {
+            rpc.account_subscribe(
+                pubkey.to_string(),
+                Some(RpcAccountInfoConfig {
+                    encoding: Some(encoding),
+                    send_initial: Some(true),
+                    compression_level,
+                    ..RpcAccountInfoConfig::default()
+                }),
+            )
+        };
+
+        for compression_level in [None, Some(1), Some(MAX_PUBSUB_COMPRESSION_LEVEL)] {
+            subscribe(UiAccountEncoding::Base64Zstd, compression_level).unwrap();
+            let response: serde_json::Value = serde_json::from_str(&receiver.recv()).unwrap();
+            let value = &response["params"]["result"]["value"];
+            assert_eq!(value["data"][1], "base64+zstd");
+            let ui_account: UiAccount = serde_json::from_value(value.clone()).unwrap();
+            assert_eq!(
+                ui_account.decode::<AccountSharedData>().unwrap().data(),
+                &data[..]
+            );
+        }
+
+        let err = subscribe(UiAccountEncoding::Base64, Some(1)).unwrap_err();
+        assert_eq!(err.code, ErrorCode::InvalidParams);
+        assert_eq!(
+            err.message,
+            "Invalid Request: compressionLevel requires base64+zstd encoding"
+        );
+        for compression_level in [0, MAX_PUBSUB_COMPRESSION_LEVEL + 1] {
+            let err =
+                subscribe(UiAccountEncoding::Base64Zstd, Some(compression_level)).unwrap_err();
+            assert_eq!(err.code, ErrorCode::InvalidParams);
+        }
+        assert!(rpc
+            .program_subscribe(
+                Pubkey::new_unique().to_string(),
+                Some(RpcProgramAccountsConfig {
+                    account_config: RpcAccountInfoConfig {
+                        encoding: Some(UiAccountEncoding::JsonParsed),
+                        compression_level: Some(1),
+                        ..RpcAccountInfoConfig::default()
+                    },
+                    ..RpcProgramAccountsConfig::default()
+                }),
+            )
+            .is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_account_subscribe_json_parsed_native_accounts() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let rpc_subscriptions = Arc::new(RpcSubscriptions::default_with_bank_forks(
+            bank_forks.clone(),
+        ));
+        let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&rpc_subscriptions);
+
+        let stake_data = bincode::serialize(&stake::state::StakeState::Initialized(
+            stake::state::Meta::default(),
+        ))
+        .unwrap();
+        // An active lookup table without authority, holding one address
+        let address = Pubkey::new_unique();
+        let mut lookup_table_data = vec![1, 0, 0, 0];
+        lookup_table_data.extend_from_slice(&u64::MAX.to_le_bytes());
+        lookup_table_data.resize(56, 0);
+        lookup_table_data.extend_from_slice(address.as_ref());
+
+        for (owner, data, program, account_type) in [
+            (stake::program::id(), stake_data, "stake", "initialized"),
+            (
+                address_lookup_table_id(),
+                lookup_table_data,
+                "address-lookup-table",
+                "lookupTable",
+            ),
+        ] {
+            let pubkey = Pubkey::new_unique();
+            let mut account = AccountSharedData::new(1, data.len(), &owner);
+            account.set_data(data);
+            bank_forks
+                .read()
+                .unwrap()
+                .working_bank()
+                .store_account(&pubkey, &account);
+            rpc.account_subscribe(
+                pubkey.to_string(),
+                Some(RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::JsonParsed),
+                    send_initial: Some(true),
+                    ..RpcAccountInfoConfig::default()
+                }),
+            )
+            .unwrap();
+            let response: serde_json::Value = serde_json::from_str(&receiver.recv()).unwrap();
+            let data = &response["params"]["result"]["value"]["data"];
+            assert_eq!(data["program"], program);
+            assert_eq!(data["parsed"]["type"], account_type);
+            if program == "address-lookup-table" {
+                assert_eq!(
+                    data["parsed"]["info"]["addresses"],
+                    json!([address.to_string()])
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_account_subscribe_deleted() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair: alice,
+            ..
+        } = create_genesis_config(10_000);
+        let bob = Keypair::new();
+        let bank = Bank::new_for_tests(&genesis_config);
+        let blockhash = bank.last_blockhash();
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let bank0 = bank_forks.read().unwrap().get(0).unwrap().clone();
+        let bank1 = Bank::new_from_parent(&bank0, &Pubkey::default(), 1);
+        bank1
+            .process_transaction(&system_transaction::transfer(
+                &alice,
+                &bob.pubkey(),
+                100,
+                blockhash,
+            ))
+            .unwrap();
+        let bank2 = Bank::new_from_parent(&bank1, &Pubkey::default(), 2);
+        bank_forks.write().unwrap().insert(bank1);
+        bank_forks.write().unwrap().insert(bank2);
+        let rpc_subscriptions = Arc::new(RpcSubscriptions::new_for_tests(
+            &Arc::new(AtomicBool::new(false)),
+            bank_forks.clone(),
+            Arc::new(RwLock::new(BlockCommitmentCache::new_for_tests_with_slots(
+                1, 1,
+            ))),
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks),
+        ));
+        let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&rpc_subscriptions);
+        rpc.account_subscribe(
+            bob.pubkey().to_string(),
+            Some(RpcAccountInfoConfig {
+                commitment: Some(CommitmentConfig::processed()),
+                encoding: Some(UiAccountEncoding::Base64),
+                ..RpcAccountInfoConfig::default()
+            }),
+        )
+        .unwrap();
+
+        // Drain bob's account, with alice paying the fee
+        let message = Message::new(
+            &[system_instruction::transfer(
+                &bob.pubkey(),
+                &alice.pubkey(),
+                100,
+            )],
+            Some(&alice.pubkey()),
+        );
+        let tx = Transaction::new(&[&alice, &bob], message, blockhash);
+        process_transaction_and_notify(&bank_forks, &tx, &rpc_subscriptions, 2).unwrap();
+
+        let response: serde_json::Value = serde_json::from_str(&receiver.recv()).unwrap();
+        assert_eq!(response["method"], "accountNotification");
+        assert_eq!(response["params"]["result"]["context"]["slot"], 2);
+        let value = &response["params"]["result"]["value"];
+        assert_eq!(value["lamports"], 0);
+        assert_eq!(value["deleted"], true);
+    }
+
+    #[test]
+    #[serial]
+    fn test_program_subscribe_filters() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let rpc_subscriptions = Arc::new(RpcSubscriptions::default_with_bank_forks(bank_forks));
+        let (rpc, _receiver) = rpc_pubsub_service::test_connection(&rpc_subscriptions);
+        let program_id = Pubkey::new_unique();
+        let subscribe = |filters| {
+            rpc.program_subscribe(
+                program_id.to_string(),
+                Some(RpcProgramAccountsConfig {
+                    filters: Some(filters),
+                    ..RpcProgramAccountsConfig::default()
+                }),
+            )
+        };
+        let memcmp = |bytes| {
+            RpcFilterType::Memcmp(Memcmp {
+                offset: 8,
+                bytes,
+                encoding: None,
+            })
+        };
+
+        // Base58 and base64 encodings of the same bytes are the same subscription
+        let bytes = vec![1, 2, 3, 4];
+        let sub_id = subscribe(vec![memcmp(MemcmpEncodedBytes::Base58(
+            bs58::encode(&bytes).into_string(),
+        ))])
+        .unwrap();
+        assert_eq!(
+            subscribe(vec![memcmp(MemcmpEncodedBytes::Base64(base64::encode(
+                &bytes
+            )))])
+            .unwrap(),
+            sub_id
+        );
+
+        let err = subscribe(vec![
+            RpcFilterType::DataSize(16),
+            memcmp(MemcmpEncodedBytes::Base64("not base64!".to_string())),
+        ])
+        .unwrap_err();
+        assert_eq!(err.code, ErrorCode::InvalidParams);
+        assert_eq!(
+            err.message,
+            "Invalid Request: filter 1: base64 decode error"
         );
+        assert!(subscribe(vec![memcmp(MemcmpEncodedBytes::Bytes(vec![0; 129]))]).is_err());
+        assert!(subscribe(vec![RpcFilterType::DataSize(16); 5]).is_err());
     }
 
     #[test]
     #[serial]
-    fn test_signature_unsubscribe() {
-        let GenesisConfigInfo {
-            genesis_config,
-            mint_keypair: alice,
-            ..
-        } = create_genesis_config(10_000);
-        let bob_pubkey = solana_sdk::pubkey::new_rand();
+    fn test_program_subscribe_send_initial() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
         let bank = Bank::new_for_tests(&genesis_config);
-        let blockhash = bank.last_blockhash();
         let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
-
-        let mut io = IoHandler::<()>::default();
-        let subscriptions = Arc::new(RpcSubscriptions::default_with_bank_forks(bank_forks));
-        let (rpc, _receiver) = rpc_pubsub_service::test_connection(&subscriptions);
-
-        io.extend_with(rpc.to_delegate());
-
-        let tx = system_transaction::transfer(&alice, &bob_pubkey, 20, blockhash);
-        let req = format!(
-            r#"{{"jsonrpc":"2.0","id":1,"method":"signatureSubscribe","params":["{}"]}}"#,
-            tx.signatures[0]
+        let bank0 = bank_forks.read().unwrap().get(0).unwrap().clone();
+        let bank1 = Bank::new_from_parent(&bank0, &Pubkey::default(), 1);
+        let program_id = Pubkey::new_unique();
+        let matching = Pubkey::new_unique();
+        bank1.store_account(&matching, &AccountSharedData::new(1, 16, &program_id));
+        bank1.store_account(
+            &Pubkey::new_unique(),
+            &AccountSharedData::new(1, 8, &program_id),
         );
-        let _res = io.handle_request_sync(&req);
-
-        let req = r#"{"jsonrpc":"2.0","id":1,"method":"signatureUnsubscribe","params":[0]}"#;
-        let res = io.handle_request_sync(req);
-
-        let expected = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
-        let expected: Response = serde_json::from_str(expected).unwrap();
-
-        let result: Response = serde_json::from_str(&res.unwrap()).unwrap();
-        assert_eq!(expected, result);
+        bank_forks.write().unwrap().insert(bank1);
+        let rpc_subscriptions = Arc::new(RpcSubscriptions::new_for_tests(
+            &Arc::new(AtomicBool::new(false)),
+            bank_forks.clone(),
+            Arc::new(RwLock::new(BlockCommitmentCache::new_for_tests_with_slots(
+                1, 1,
+            ))),
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks),
+        ));
+        let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&rpc_subscriptions);
 
-        // Test bad parameter
-        let req = r#"{"jsonrpc":"2.0","id":1,"method":"signatureUnsubscribe","params":[1]}"#;
-        let res = io.handle_request_sync(req);
-        let expected = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Invalid subscription id."},"id":1}"#;
-        let expected: Response = serde_json::from_str(expected).unwrap();
+        let sub_id = rpc
+            .program_subscribe(
+                program_id.to_string(),
+                Some(RpcProgramAccountsConfig {
+                    filters: Some(vec![RpcFilterType::DataSize(16)]),
+                    account_config: RpcAccountInfoConfig {
+                        commitment: Some(CommitmentConfig::processed()),
+                        encoding: Some(UiAccountEncoding::Base64),
+                        send_initial: Some(true),
+                        ..RpcAccountInfoConfig::default()
+                    },
+                    with_context: None,
+                    batch: None,
+                }),
+            )
+            .unwrap();
 
-        let result: Response = serde_json::from_str(&res.unwrap()).unwrap();
-        assert_eq!(expected, result);
+        // Only the account matching the filters is part of the scan
+        let response: serde_json::Value = serde_json::from_str(&receiver.recv()).unwrap();
+        assert_eq!(response["method"], "programInitialAccountsNotification");
+        assert_eq!(response["params"]["subscription"], u64::from(sub_id));
+        assert_eq!(response["params"]["result"]["context"]["slot"], 1);
+        let value = &response["params"]["result"]["value"];
+        assert_eq!(value["index"], 0);
+        assert_eq!(value["last"], true);
+        assert_eq!(value["accounts"].as_array().unwrap().len(), 1);
+        assert_eq!(value["accounts"][0]["pubkey"], matching.to_string());
     }
 
     #[test]
     #[serial]
-    fn test_account_subscribe() {
+    fn test_accounts_subscribe() {
         let GenesisConfigInfo {
             genesis_config,
             mint_keypair: alice,
             ..
         } = create_genesis_config(10_000);
-
-        let new_stake_authority = solana_sdk::pubkey::new_rand();
-        let stake_authority = Keypair::new();
-        let from = Keypair::new();
-        let stake_account = Keypair::new();
-        let stake_program_id = stake::program::id();
         let bank = Bank::new_for_tests(&genesis_config);
         let blockhash = bank.last_blockhash();
         let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
@@ -766,20 +2756,29 @@ mod tests {
             ))),
             OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks),
         ));
-
         let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&rpc_subscriptions);
 
-        let encoding = UiAccountEncoding::Base64;
+        assert!(rpc.accounts_subscribe(vec![], None).is_err());
+        assert!(rpc
+            .accounts_subscribe(vec!["invalid".to_string()], None)
+            .is_err());
 
-        rpc.account_subscribe(
-            stake_account.pubkey().to_string(),
-            Some(RpcAccountInfoConfig {
-                commitment: Some(CommitmentConfig::processed()),
-                encoding: Some(encoding),
-                data_slice: None,
-            }),
-        )
-        .unwrap();
+        let bob = Keypair::new();
+        let carol = Keypair::new();
+        let config = Some(RpcAccountInfoConfig {
+            commitment: Some(CommitmentConfig::processed()),
+            encoding: Some(UiAccountEncoding::Base64),
+            data_slice: None,
+            send_initial: None,
+            compression_level: None,
+            with_proof: None,
+        });
+        let sub_id = rpc
+            .accounts_subscribe(
+                vec![bob.pubkey().to_string(), carol.pubkey().to_string()],
+                config,
+            )
+            .unwrap();
 
         // Make sure the subscription is processed before continuing.
         let (rpc2, mut receiver2) = rpc_pubsub_service::test_connection(&rpc_subscriptions);
@@ -787,74 +2786,115 @@ mod tests {
         rpc_subscriptions.notify_slot(1, 0, 0);
         receiver2.recv();
 
-        let tx = system_transaction::transfer(&alice, &from.pubkey(), 51, blockhash);
-        process_transaction_and_notify(&bank_forks, &tx, &rpc_subscriptions, 1).unwrap();
+        for (keypair, lamports) in vec![(&bob, 10), (&carol, 20)] {
+            let tx = system_transaction::transfer(&alice, &keypair.pubkey(), lamports, blockhash);
+            process_transaction_and_notify(&bank_forks, &tx, &rpc_subscriptions, 1).unwrap();
 
-        let authorized = Authorized::auto(&stake_authority.pubkey());
-        let ixs = stake_instruction::create_account(
-            &from.pubkey(),
-            &stake_account.pubkey(),
-            &authorized,
-            &Lockup::default(),
-            51,
+            let response: serde_json::Value = serde_json::from_str(&receiver.recv()).unwrap();
+            assert_eq!(response["method"], "accountsNotification");
+            let value = &response["params"]["result"]["value"];
+            assert_eq!(value["pubkey"], keypair.pubkey().to_string());
+            assert_eq!(value["account"]["lamports"], lamports);
+        }
+
+        assert!(rpc.accounts_unsubscribe(sub_id).unwrap());
+    }
+
+    #[test]
+    #[serial]
+    fn test_token_accounts_by_owner_subscribe() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank = Bank::new_with_paths_for_tests(
+            &genesis_config,
+            Vec::new(),
+            &[],
+            None,
+            None,
+            AccountSecondaryIndexes {
+                keys: None,
+                indexes: vec![AccountIndex::SplTokenOwner].into_iter().collect(),
+            },
+            false,
+            AccountShrinkThreshold::default(),
+            false,
         );
-        let message = Message::new(&ixs, Some(&from.pubkey()));
-        let tx = Transaction::new(&[&from, &stake_account], message, blockhash);
-        process_transaction_and_notify(&bank_forks, &tx, &rpc_subscriptions, 1).unwrap();
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let bank0 = bank_forks.read().unwrap().get(0).unwrap().clone();
+        let bank1 = Bank::new_from_parent(&bank0, &Pubkey::default(), 1);
 
-        // Test signature confirmation notification #1
-        let account = bank_forks
-            .read()
-            .unwrap()
-            .get(1)
-            .unwrap()
-            .get_account(&stake_account.pubkey())
+        let owner = Pubkey::new_unique();
+        let token_account = |amount| {
+            let mut data = vec![0; TokenAccount::get_packed_len()];
+            TokenAccount::pack(
+                TokenAccount {
+                    mint: SplTokenPubkey::new(&[2; 32]),
+                    owner: SplTokenPubkey::new(owner.as_ref()),
+                    amount,
+                    state: TokenAccountState::Initialized,
+                    ..TokenAccount::default()
+                },
+                &mut data,
+            )
             .unwrap();
-        let expected_data = account.data();
-        let expected = json!({
-           "jsonrpc": "2.0",
-           "method": "accountNotification",
-           "params": {
-               "result": {
-                   "context": { "slot": 1 },
-                   "value": {
-                       "owner": stake_program_id.to_string(),
-                       "lamports": 51,
-                       "data": [base64::encode(expected_data), encoding],
-                       "executable": false,
-                       "rentEpoch": 0,
-                   },
-               },
-               "subscription": 0,
-           }
-        });
+            let mut account = AccountSharedData::new(1, data.len(), &spl_token_id_v2_0());
+            account.set_data(data);
+            account
+        };
+        let existing = Pubkey::new_unique();
+        bank1.store_account(&existing, &token_account(10));
+        bank_forks.write().unwrap().insert(bank1);
 
-        let response = receiver.recv();
-        assert_eq!(
-            expected,
-            serde_json::from_str::<serde_json::Value>(&response).unwrap(),
-        );
+        let rpc_subscriptions = Arc::new(RpcSubscriptions::new_for_tests(
+            &Arc::new(AtomicBool::new(false)),
+            bank_forks.clone(),
+            Arc::new(RwLock::new(BlockCommitmentCache::new_for_tests_with_slots(
+                1, 1,
+            ))),
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks),
+        ));
+        let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&rpc_subscriptions);
+        rpc.token_accounts_by_owner_subscribe(
+            owner.to_string(),
+            Some(RpcAccountInfoConfig {
+                commitment: Some(CommitmentConfig::processed()),
+                encoding: Some(UiAccountEncoding::Base64),
+                ..RpcAccountInfoConfig::default()
+            }),
+        )
+        .unwrap();
 
-        let tx = system_transaction::transfer(&alice, &stake_authority.pubkey(), 1, blockhash);
-        process_transaction_and_notify(&bank_forks, &tx, &rpc_subscriptions, 1).unwrap();
-        sleep(Duration::from_millis(200));
-        let ix = stake_instruction::authorize(
-            &stake_account.pubkey(),
-            &stake_authority.pubkey(),
-            &new_stake_authority,
-            StakeAuthorize::Staker,
-            None,
+        // A new token account of the owner is notified, the unchanged one is not
+        let added = Pubkey::new_unique();
+        let bank1 = bank_forks.read().unwrap().get(1).unwrap().clone();
+        let bank2 = Bank::new_from_parent(&bank1, &Pubkey::default(), 2);
+        bank2.store_account(&added, &token_account(20));
+        bank_forks.write().unwrap().insert(bank2);
+        rpc_subscriptions.notify_subscribers(CommitmentSlots {
+            slot: 2,
+            ..CommitmentSlots::default()
+        });
+        let response: serde_json::Value = serde_json::from_str(&receiver.recv()).unwrap();
+        assert_eq!(response["method"], "tokenAccountsByOwnerNotification");
+        assert_eq!(response["params"]["result"]["context"]["slot"], 2);
+        assert_eq!(
+            response["params"]["result"]["value"]["pubkey"],
+            added.to_string()
         );
-        let message = Message::new(&[ix], Some(&stake_authority.pubkey()));
-        let tx = Transaction::new(&[&stake_authority], message, blockhash);
-        process_transaction_and_notify(&bank_forks, &tx, &rpc_subscriptions, 1).unwrap();
-        sleep(Duration::from_millis(200));
 
-        let bank = bank_forks.read().unwrap()[1].clone();
-        let account = bank.get_account(&stake_account.pubkey()).unwrap();
+        // Changes to the existing account are notified as well
+        let bank2 = bank_forks.read().unwrap().get(2).unwrap().clone();
+        let bank3 = Bank::new_from_parent(&bank2, &Pubkey::default(), 3);
+        bank3.store_account(&existing, &token_account(5));
+        bank_forks.write().unwrap().insert(bank3);
+        rpc_subscriptions.notify_subscribers(CommitmentSlots {
+            slot: 3,
+            ..CommitmentSlots::default()
+        });
+        let response: serde_json::Value = serde_json::from_str(&receiver.recv()).unwrap();
+        assert_eq!(response["params"]["result"]["context"]["slot"], 3);
         assert_eq!(
-            stake_state::authorized_from(&account).unwrap().staker,
-            new_stake_authority
+            response["params"]["result"]["value"]["pubkey"],
+            existing.to_string()
         );
     }
 
@@ -892,6 +2932,9 @@ mod tests {
                 commitment: Some(CommitmentConfig::processed()),
                 encoding: Some(UiAccountEncoding::JsonParsed),
                 data_slice: None,
+                send_initial: None,
+                compression_level: None,
+                with_proof: None,
             }),
         )
         .unwrap();
@@ -1023,6 +3066,9 @@ mod tests {
                 commitment: Some(CommitmentConfig::finalized()),
                 encoding: None,
                 data_slice: None,
+                send_initial: None,
+                compression_level: None,
+                with_proof: None,
             }),
         )
         .unwrap();
@@ -1074,6 +3120,9 @@ mod tests {
                 commitment: Some(CommitmentConfig::finalized()),
                 encoding: None,
                 data_slice: None,
+                send_initial: None,
+                compression_level: None,
+                with_proof: None,
             }),
         )
         .unwrap();
@@ -1207,20 +3256,130 @@ mod tests {
         ));
         // Setup RPC
         let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&subscriptions);
-        rpc.vote_subscribe().unwrap();
+        rpc.vote_subscribe(None).unwrap();
 
+        let vote_pubkey = validator_voting_keypairs[0].vote_keypair.pubkey();
+        let identity = validator_voting_keypairs[0].node_keypair.pubkey();
+        let signature = Signature::new(&[7; 64]);
         let vote = Vote {
             slots: vec![1, 2],
             hash: Hash::default(),
             timestamp: None,
         };
-        subscriptions.notify_vote(&vote);
+        subscriptions.notify_vote(&vote_pubkey, &vote, &signature);
 
         let response = receiver.recv();
+        let expected = json!({
+            "jsonrpc": "2.0",
+            "method": "voteNotification",
+            "params": {
+                "result": {
+                    "slots": [1, 2],
+                    "hash": "11111111111111111111111111111111",
+                    "timestamp": null,
+                    "votePubkey": vote_pubkey.to_string(),
+                    "identity": identity.to_string(),
+                    "signature": signature.to_string(),
+                },
+                "subscription": 0,
+            }
+        });
+        assert_eq!(
+            expected,
+            serde_json::from_str::<serde_json::Value>(&response).unwrap(),
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_vote_subscribe_filtered() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let subscriptions = Arc::new(RpcSubscriptions::default_with_bank_forks(bank_forks));
+        let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&subscriptions);
+
+        let vote_pubkey = Pubkey::new_unique();
+        let other_vote_pubkey = Pubkey::new_unique();
+        assert!(rpc
+            .vote_subscribe(Some(RpcVoteSubscribeConfig {
+                vote_pubkeys: Some(vec![]),
+            }))
+            .is_err());
+        rpc.vote_subscribe(Some(RpcVoteSubscribeConfig {
+            vote_pubkeys: Some(vec![vote_pubkey.to_string()]),
+        }))
+        .unwrap();
+
+        let other_vote = Vote {
+            slots: vec![1, 2],
+            hash: Hash::default(),
+            timestamp: None,
+        };
+        subscriptions.notify_vote(&other_vote_pubkey, &other_vote, &Signature::default());
+        let vote = Vote {
+            slots: vec![3],
+            hash: Hash::default(),
+            timestamp: None,
+        };
+        subscriptions.notify_vote(&vote_pubkey, &vote, &Signature::default());
+
+        // Only the vote from the requested vote account is delivered
+        let response: serde_json::Value = serde_json::from_str(&receiver.recv()).unwrap();
+        assert_eq!(response["params"]["result"]["slots"], json!([3]));
+        assert_eq!(
+            response["params"]["result"]["votePubkey"],
+            json!(vote_pubkey.to_string())
+        );
+        assert_eq!(response["params"]["result"]["identity"], json!(null));
+    }
+
+    #[test]
+    #[serial]
+    fn test_leader_schedule_subscribe() {
+        let leader = solana_sdk::pubkey::new_rand();
+        let GenesisConfigInfo { genesis_config, .. } =
+            create_genesis_config_with_leader(10_000, &leader, 1_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let subscriptions = Arc::new(RpcSubscriptions::default_with_bank_forks(bank_forks));
+        let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&subscriptions);
+
+        assert!(rpc
+            .leader_schedule_subscribe(Some(RpcLeaderScheduleSubscribeConfig {
+                num_slots: Some(0),
+                ..RpcLeaderScheduleSubscribeConfig::default()
+            }))
+            .is_err());
+        assert!(rpc
+            .leader_schedule_subscribe(Some(RpcLeaderScheduleSubscribeConfig {
+                identity: Some("not a pubkey".to_string()),
+                ..RpcLeaderScheduleSubscribeConfig::default()
+            }))
+            .is_err());
+        let sub_id = rpc
+            .leader_schedule_subscribe(Some(RpcLeaderScheduleSubscribeConfig {
+                identity: Some(leader.to_string()),
+                num_slots: Some(3),
+            }))
+            .unwrap();
+
+        subscriptions.notify_slot(1, 0, 0);
+        let response: serde_json::Value = serde_json::from_str(&receiver.recv()).unwrap();
+        assert_eq!(response["method"], "leaderScheduleNotification");
         assert_eq!(
-            response,
-            r#"{"jsonrpc":"2.0","method":"voteNotification","params":{"result":{"slots":[1,2],"hash":"11111111111111111111111111111111","timestamp":null},"subscription":0}}"#
+            response["params"]["result"],
+            json!({
+                "slot": 1,
+                "leaderSlots": [
+                    { "slot": 2, "leader": leader.to_string() },
+                    { "slot": 3, "leader": leader.to_string() },
+                    { "slot": 4, "leader": leader.to_string() },
+                ],
+            })
         );
+
+        assert!(rpc.leader_schedule_unsubscribe(sub_id).is_ok());
     }
 
     #[test]
@@ -1231,9 +3390,133 @@ mod tests {
         let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
         let rpc_subscriptions = Arc::new(RpcSubscriptions::default_with_bank_forks(bank_forks));
         let (rpc, _receiver) = rpc_pubsub_service::test_connection(&rpc_subscriptions);
-        let sub_id = rpc.vote_subscribe().unwrap();
+        let sub_id = rpc.vote_subscribe(None).unwrap();
 
         assert!(rpc.vote_unsubscribe(42.into()).is_err());
         assert!(rpc.vote_unsubscribe(sub_id).is_ok());
     }
+
+    #[test]
+    #[serial]
+    fn test_finalized_only_subscribe() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let rpc_subscriptions = Arc::new(RpcSubscriptions::default_with_bank_forks(bank_forks));
+        let rpc = RpcSolPubSubImpl::new(
+            PubSubConfig {
+                finalized_only: true,
+                ..PubSubConfig::default()
+            },
+            rpc_subscriptions.control().clone(),
+            Arc::new(DashMap::new()),
+            Arc::default(),
+            mpsc::channel(1).0,
+            None,
+            None,
+        );
+        let pubkey = solana_sdk::pubkey::new_rand().to_string();
+
+        let err = rpc
+            .account_subscribe(
+                pubkey.clone(),
+                Some(RpcAccountInfoConfig {
+                    commitment: Some(CommitmentConfig::confirmed()),
+                    ..RpcAccountInfoConfig::default()
+                }),
+            )
+            .unwrap_err();
+        assert_eq!(err.code, ErrorCode::InvalidParams);
+
+        assert!(rpc
+            .account_subscribe(
+                pubkey,
+                Some(RpcAccountInfoConfig {
+                    commitment: Some(CommitmentConfig::finalized()),
+                    ..RpcAccountInfoConfig::default()
+                }),
+            )
+            .is_ok());
+        assert!(rpc.slot_subscribe().is_ok());
+
+        let signature = Signature::new(&[7u8; 64]).to_string();
+        let err = rpc
+            .signature_subscribe(
+                signature.clone(),
+                Some(RpcSignatureSubscribeConfig {
+                    commitment: Some(CommitmentConfig::finalized()),
+                    enable_received_notification: Some(true),
+                    commitment_levels: None,
+                }),
+            )
+            .unwrap_err();
+        assert_eq!(err.code, ErrorCode::InvalidParams);
+        assert!(rpc
+            .signature_subscribe(
+                signature,
+                Some(RpcSignatureSubscribeConfig {
+                    commitment: Some(CommitmentConfig::finalized()),
+                    ..RpcSignatureSubscribeConfig::default()
+                }),
+            )
+            .is_ok());
+    }
+
+    #[test]
+    #[serial]
+    fn test_connection_subscription_limit() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let rpc_subscriptions = Arc::new(RpcSubscriptions::default_with_bank_forks(bank_forks));
+        let rpc = RpcSolPubSubImpl::new(
+            PubSubConfig {
+                max_subscriptions_per_connection: 2,
+                ..PubSubConfig::default()
+            },
+            rpc_subscriptions.control().clone(),
+            Arc::new(DashMap::new()),
+            Arc::default(),
+            mpsc::channel(1).0,
+            None,
+            None,
+        );
+
+        let slot_id = rpc.slot_subscribe().unwrap();
+        assert!(rpc.root_subscribe(None).is_ok());
+        // Subscribing again to the same stream does not take another slot
+        assert_eq!(rpc.slot_subscribe().unwrap(), slot_id);
+        let err = rpc.slots_updates_subscribe().unwrap_err();
+        assert_eq!(err.code, ErrorCode::InternalError);
+
+        assert!(rpc.slot_unsubscribe(slot_id).is_ok());
+        assert!(rpc.slots_updates_subscribe().is_ok());
+    }
+
+    #[test]
+    #[serial]
+    fn test_drain_refuses_subscriptions() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let rpc_subscriptions = Arc::new(RpcSubscriptions::default_with_bank_forks(bank_forks));
+        let config = PubSubConfig::default();
+        let rpc = RpcSolPubSubImpl::new(
+            config.clone(),
+            rpc_subscriptions.control().clone(),
+            Arc::new(DashMap::new()),
+            Arc::default(),
+            mpsc::channel(1).0,
+            None,
+            None,
+        );
+
+        let slot_id = rpc.slot_subscribe().unwrap();
+        config.drain.start(Duration::from_secs(10));
+        assert!(config.drain.is_draining());
+        let err = rpc.root_subscribe(None).unwrap_err();
+        assert_eq!(err.code, ErrorCode::InternalError);
+        // Existing subscriptions are kept until the connection closes
+        assert!(rpc.slot_unsubscribe(slot_id).unwrap());
+    }
 }
@@ -0,0 +1,339 @@
+//! API keys for pubsub connections. Keys are read from a JSON file mapping each key to its
+//! limits, which is read again whenever it changes. The keys are loaded once and shared by
+//! the websocket, SSE and gRPC services, so that a key's quota covers all of them:
+//!
+//! ```json
+//! {
+//!   "key1": { "maxSubscriptions": 100, "methods": ["slotSubscribe", "slotUnsubscribe"] },
+//!   "key2": {}
+//! }
+//! ```
+
+use {
+    crate::rpc_subscription_tracker::{SubscriptionId, SubscriptionToken},
+    dashmap::DashMap,
+    serde_json::json,
+    std::{
+        collections::{HashMap, HashSet},
+        fmt, fs, io,
+        path::{Path, PathBuf},
+        sync::{Arc, Mutex, RwLock, Weak},
+        time::{Duration, Instant, SystemTime},
+    },
+};
+
+/// How often the key file is checked for changes
+const RELOAD_INTERVAL: Duration = Duration::from_secs(1);
+
+type ConnectionSubscriptions = DashMap<SubscriptionId, SubscriptionToken>;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ApiKeyLimits {
+    /// Subscriptions that all the connections using the key may hold together
+    pub max_subscriptions: Option<usize>,
+    /// Methods the key may call; all of them if unset
+    pub methods: Option<HashSet<String>>,
+}
+
+struct KeyFile {
+    keys: HashMap<String, ApiKeyLimits>,
+    modified: Option<SystemTime>,
+    checked_at: Instant,
+}
+
+pub struct ApiKeys {
+    path: PathBuf,
+    key_file: RwLock<KeyFile>,
+    /// Subscriptions of the open connections, by key. Kept apart from the key file so that
+    /// usage survives reloads.
+    connections: Mutex<HashMap<String, Vec<Weak<ConnectionSubscriptions>>>>,
+}
+
+impl fmt::Debug for ApiKeys {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ApiKeys").field("path", &self.path).finish()
+    }
+}
+
+impl ApiKeys {
+    pub fn load(path: PathBuf) -> io::Result<Self> {
+        let (keys, modified) = Self::read(&path)?;
+        Ok(Self {
+            path,
+            key_file: RwLock::new(KeyFile {
+                keys,
+                modified,
+                checked_at: Instant::now(),
+            }),
+            connections: Mutex::default(),
+        })
+    }
+
+    fn read(path: &Path) -> io::Result<(HashMap<String, ApiKeyLimits>, Option<SystemTime>)> {
+        let modified = fs::metadata(path)?.modified().ok();
+        let keys = serde_json::from_slice(&fs::read(path)?)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        Ok((keys, modified))
+    }
+
+    /// Reads the key file again if it changed since it was last read. The current keys are
+    /// kept if it can't be read.
+    fn reload_if_changed(&self) {
+        {
+            let key_file = self.key_file.read().unwrap();
+            if key_file.checked_at.elapsed() < RELOAD_INTERVAL {
+                return;
+            }
+        }
+        let mut key_file = self.key_file.write().unwrap();
+        key_file.checked_at = Instant::now();
+        let modified = fs::metadata(&self.path).and_then(|metadata| metadata.modified());
+        if modified.ok() == key_file.modified && key_file.modified.is_some() {
+            return;
+        }
+        self.reload_locked(&mut key_file);
+    }
+
+    fn reload_locked(&self, key_file: &mut KeyFile) {
+        match Self::read(&self.path) {
+            Ok((keys, modified)) => {
+                info!("pubsub API keys reloaded from {:?}", self.path);
+                key_file.keys = keys;
+                key_file.modified = modified;
+            }
+            Err(err) => warn!("failed to reload pubsub API keys: {}", err),
+        }
+    }
+
+    /// Returns the key `token` if it is valid
+    pub fn authenticate(self: &Arc<Self>, token: &str) -> Option<ApiKey> {
+        self.limits(token).map(|_| ApiKey {
+            keys: Arc::clone(self),
+            token: token.to_string(),
+        })
+    }
+
+    /// Returns the key passed in an `Authorization: Bearer <KEY>` header value, if it is valid
+    pub fn authenticate_bearer(self: &Arc<Self>, authorization: &str) -> Option<ApiKey> {
+        let token = authorization.trim().strip_prefix("Bearer ")?;
+        self.authenticate(token.trim())
+    }
+
+    /// Returns the key passed in the `token` query parameter of a websocket upgrade
+    /// request path, e.g. `/?token=<KEY>`, if it is valid
+    pub fn authenticate_path(self: &Arc<Self>, path: &str) -> Option<ApiKey> {
+        let (_, query) = path.split_once('?')?;
+        let token = query
+            .split('&')
+            .find_map(|param| param.strip_prefix("token="))?;
+        self.authenticate(token)
+    }
+
+    fn limits(&self, token: &str) -> Option<ApiKeyLimits> {
+        self.reload_if_changed();
+        self.key_file.read().unwrap().keys.get(token).cloned()
+    }
+}
+
+/// A key a connection authenticated with. Its limits are looked up on every use, so that
+/// changes to the key file apply to open connections too.
+#[derive(Clone)]
+pub struct ApiKey {
+    keys: Arc<ApiKeys>,
+    token: String,
+}
+
+impl ApiKey {
+    pub(crate) fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// Counts the subscriptions of a connection against the quota of the key, for as long
+    /// as the connection is open.
+    pub(crate) fn register_connection(&self, subscriptions: &Arc<ConnectionSubscriptions>) {
+        let mut connections = self.keys.connections.lock().unwrap();
+        let key_connections = connections.entry(self.token.clone()).or_default();
+        key_connections.retain(|connection| connection.strong_count() > 0);
+        key_connections.push(Arc::downgrade(subscriptions));
+    }
+
+    /// Number of subscriptions held by the connections using the key
+    pub(crate) fn subscriptions(&self) -> usize {
+        let connections = self.keys.connections.lock().unwrap();
+        Self::count_subscriptions(&connections, &self.token)
+    }
+
+    fn count_subscriptions(
+        connections: &HashMap<String, Vec<Weak<ConnectionSubscriptions>>>,
+        token: &str,
+    ) -> usize {
+        connections
+            .get(token)
+            .map(|connections| {
+                connections
+                    .iter()
+                    .filter_map(Weak::upgrade)
+                    .map(|subscriptions| subscriptions.len())
+                    .sum()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Adds a subscription to a connection using the key, if the key may hold another one.
+    /// The quota is checked and the subscription added under the same lock, so that
+    /// concurrent subscribes on connections sharing the key can't exceed it. Revoked keys
+    /// may not subscribe; the token is dropped when the subscription is refused.
+    pub(crate) fn insert_within_quota(
+        &self,
+        subscriptions: &ConnectionSubscriptions,
+        id: SubscriptionId,
+        token: SubscriptionToken,
+    ) -> bool {
+        let limits = match self.keys.limits(&self.token) {
+            Some(limits) => limits,
+            None => return false,
+        };
+        let connections = self.keys.connections.lock().unwrap();
+        if let Some(max_subscriptions) = limits.max_subscriptions {
+            if Self::count_subscriptions(&connections, &self.token) >= max_subscriptions {
+                return false;
+            }
+        }
+        subscriptions.insert(id, token);
+        true
+    }
+
+    pub(crate) fn allows_method(&self, method: &str) -> bool {
+        match self.keys.limits(&self.token) {
+            Some(limits) => limits
+                .methods
+                .map(|methods| methods.contains(method))
+                .unwrap_or(true),
+            None => false,
+        }
+    }
+
+    /// Returns the error response to send instead of handling `request`, if it calls a
+    /// method the key is not allowed to. Batches are refused as a whole.
+    pub(crate) fn refuse_request(&self, request: &str) -> Option<String> {
+        let request: serde_json::Value = serde_json::from_str(request).ok()?;
+        let requests = match &request {
+            serde_json::Value::Array(requests) => requests.iter().collect(),
+            request => vec![request],
+        };
+        let refused = requests.iter().any(|request| {
+            request["method"]
+                .as_str()
+                .map(|method| !self.allows_method(method))
+                .unwrap_or(false)
+        });
+        refused.then(|| {
+            let id = match &request {
+                serde_json::Value::Object(_) => request["id"].clone(),
+                _ => serde_json::Value::Null,
+            };
+            json!({
+                "jsonrpc": "2.0",
+                "error": {
+                    "code": jsonrpc_core::ErrorCode::MethodNotFound.code(),
+                    "message": "Method not allowed for this API key",
+                },
+                "id": id,
+            })
+            .to_string()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            rpc_subscription_tracker::SubscriptionParams, rpc_subscriptions::RpcSubscriptions,
+        },
+        solana_runtime::{
+            bank::Bank,
+            bank_forks::BankForks,
+            genesis_utils::{create_genesis_config, GenesisConfigInfo},
+        },
+    };
+
+    #[test]
+    fn test_api_keys() {
+        let path = std::env::temp_dir().join(format!("test_api_keys-{}.json", std::process::id()));
+        fs::write(
+            &path,
+            r#"{
+                "limited": { "maxSubscriptions": 1, "methods": ["slotSubscribe"] },
+                "unlimited": {}
+            }"#,
+        )
+        .unwrap();
+        let api_keys = Arc::new(ApiKeys::load(path.clone()).unwrap());
+
+        assert!(api_keys.authenticate_path("/").is_none());
+        assert!(api_keys.authenticate_bearer("Bearer unknown").is_none());
+        assert!(api_keys.authenticate_bearer("unlimited").is_none());
+        assert!(api_keys.authenticate_bearer("Bearer unlimited").is_some());
+        assert!(api_keys.authenticate_path("/?token=unknown").is_none());
+        let unlimited = api_keys.authenticate_path("/?a=b&token=unlimited").unwrap();
+        let limited = api_keys.authenticate("limited").unwrap();
+
+        assert!(unlimited.allows_method("accountSubscribe"));
+        assert!(limited.allows_method("slotSubscribe"));
+        assert!(!limited.allows_method("accountSubscribe"));
+        assert!(limited
+            .refuse_request(r#"{"jsonrpc":"2.0","id":1,"method":"slotSubscribe"}"#)
+            .is_none());
+        assert_eq!(
+            limited
+                .refuse_request(r#"{"jsonrpc":"2.0","id":1,"method":"accountSubscribe"}"#)
+                .unwrap(),
+            r#"{"error":{"code":-32601,"message":"Method not allowed for this API key"},"id":1,"jsonrpc":"2.0"}"#
+        );
+
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(Bank::new_for_tests(
+            &genesis_config,
+        ))));
+        let subscriptions = RpcSubscriptions::default_with_bank_forks(bank_forks);
+        let subscribe = |params| {
+            let token = subscriptions.control().subscribe(params).unwrap();
+            (token.id(), token)
+        };
+        let connection = Arc::new(DashMap::new());
+        limited.register_connection(&connection);
+        let (id, token) = subscribe(SubscriptionParams::Slot);
+        assert!(limited.insert_within_quota(&connection, id, token));
+        assert_eq!(limited.subscriptions(), 1);
+
+        // The quota is shared by the connections using the key
+        let other_connection = Arc::new(DashMap::new());
+        limited.register_connection(&other_connection);
+        let (id, token) = subscribe(SubscriptionParams::Epoch);
+        assert!(!limited.insert_within_quota(&other_connection, id, token));
+        assert!(other_connection.is_empty());
+        let unlimited_connection = Arc::new(DashMap::new());
+        unlimited.register_connection(&unlimited_connection);
+        let (id, token) = subscribe(SubscriptionParams::Epoch);
+        assert!(unlimited.insert_within_quota(&unlimited_connection, id, token));
+        drop(connection);
+        let (id, token) = subscribe(SubscriptionParams::SlotsUpdates);
+        assert!(limited.insert_within_quota(&other_connection, id, token));
+
+        // Revoking a key applies to open connections
+        fs::write(&path, r#"{ "limited": {} }"#).unwrap();
+        {
+            let mut key_file = api_keys.key_file.write().unwrap();
+            api_keys.reload_locked(&mut key_file);
+        }
+        let (id, token) = subscribe(SubscriptionParams::PerformanceSamples);
+        assert!(!unlimited.insert_within_quota(&unlimited_connection, id, token));
+        assert!(!unlimited.allows_method("slotSubscribe"));
+        assert!(limited.allows_method("accountSubscribe"));
+
+        fs::remove_file(&path).unwrap();
+    }
+}
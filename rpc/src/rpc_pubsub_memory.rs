@@ -0,0 +1,253 @@
+//! Memory accounting shared by every buffer in the pubsub subsystem.
+//!
+//! The notification queue, the `RecentItems` and serialized results caches, the recent
+//! signature notifications, per-connection websocket buffers and the notifier's
+//! serialization scratch space are each sized independently. The
+//! `MemoryBudget` tracks their combined usage against a single hard cap. As usage
+//! approaches the cap, categories start shedding in priority order: scratch space first,
+//! then cached notifications, then connection buffers, and finally new queue entries.
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+pub const DEFAULT_MAX_MEMORY_BYTES: usize = 1024 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryCategory {
+    /// Serialization scratch space owned by the notifier thread
+    Scratch,
+    /// Serialized notifications retained by `RecentItems`, and serialized results shared
+    /// between subscriptions
+    RecentItems,
+    /// Terminal signature notifications retained for `getRecentSignatureNotification`
+    RecentSignatures,
+    /// Incoming and outgoing websocket buffers of individual connections
+    ConnectionBuffers,
+    /// Entries waiting in the notification queue
+    NotificationQueue,
+}
+
+impl MemoryCategory {
+    const ALL: [MemoryCategory; 5] = [
+        MemoryCategory::Scratch,
+        MemoryCategory::RecentItems,
+        MemoryCategory::RecentSignatures,
+        MemoryCategory::ConnectionBuffers,
+        MemoryCategory::NotificationQueue,
+    ];
+
+    fn index(self) -> usize {
+        match self {
+            MemoryCategory::Scratch => 0,
+            MemoryCategory::RecentItems => 1,
+            MemoryCategory::RecentSignatures => 2,
+            MemoryCategory::ConnectionBuffers => 3,
+            MemoryCategory::NotificationQueue => 4,
+        }
+    }
+
+    /// Percentage of the hard cap beyond which this category starts shedding.
+    fn shed_threshold_percent(self) -> usize {
+        match self {
+            MemoryCategory::Scratch => 50,
+            MemoryCategory::RecentItems | MemoryCategory::RecentSignatures => 75,
+            MemoryCategory::ConnectionBuffers => 90,
+            MemoryCategory::NotificationQueue => 100,
+        }
+    }
+}
+
+struct MemoryBudgetInner {
+    max_bytes: usize,
+    used: [AtomicUsize; 5],
+}
+
+#[derive(Clone)]
+pub struct MemoryBudget(Arc<MemoryBudgetInner>);
+
+impl MemoryBudget {
+    pub fn new(max_bytes: usize) -> Self {
+        Self(Arc::new(MemoryBudgetInner {
+            max_bytes,
+            used: Default::default(),
+        }))
+    }
+
+    pub fn max_bytes(&self) -> usize {
+        self.0.max_bytes
+    }
+
+    pub fn used(&self, category: MemoryCategory) -> usize {
+        self.0.used[category.index()].load(Ordering::Relaxed)
+    }
+
+    pub fn total(&self) -> usize {
+        MemoryCategory::ALL
+            .iter()
+            .map(|category| self.used(*category))
+            .sum()
+    }
+
+    fn threshold(&self, category: MemoryCategory) -> usize {
+        (self.0.max_bytes / 100).saturating_mul(category.shed_threshold_percent())
+    }
+
+    /// Returns true if `category` is over its shedding threshold and should release memory.
+    pub fn should_shed(&self, category: MemoryCategory) -> bool {
+        self.total() > self.threshold(category)
+    }
+
+    /// Accounts `bytes` to `category` if doing so keeps the total below the category's
+    /// shedding threshold. Returns false (and accounts nothing) otherwise.
+    pub fn try_reserve(&self, category: MemoryCategory, bytes: usize) -> bool {
+        if self.total().saturating_add(bytes) > self.threshold(category) {
+            inc_new_counter_info!("rpc-pubsub-memory-shed", 1);
+            return false;
+        }
+        self.reserve(category, bytes);
+        true
+    }
+
+    /// Unconditionally accounts `bytes` to `category`.
+    pub fn reserve(&self, category: MemoryCategory, bytes: usize) {
+        self.0.used[category.index()].fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Releases `bytes` from `category`, never below zero, so that releasing more than was
+    /// reserved can't wrap the usage around and refuse every later reservation.
+    pub fn release(&self, category: MemoryCategory, bytes: usize) {
+        let prev = self.0.used[category.index()]
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| {
+                Some(used.saturating_sub(bytes))
+            })
+            .expect("update always succeeds");
+        if prev < bytes {
+            inc_new_counter_info!("rpc-pubsub-memory-release-underflow", 1);
+        }
+    }
+
+    /// Like `try_reserve`, but releases the bytes when the returned guard is dropped.
+    pub fn try_reservation(&self, category: MemoryCategory, bytes: usize) -> Option<Reservation> {
+        if self.try_reserve(category, bytes) {
+            Some(Reservation {
+                budget: self.clone(),
+                category,
+                bytes,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Like `reserve`, but releases the bytes when the returned guard is dropped.
+    pub fn reservation(&self, category: MemoryCategory, bytes: usize) -> Reservation {
+        self.reserve(category, bytes);
+        Reservation {
+            budget: self.clone(),
+            category,
+            bytes,
+        }
+    }
+
+    pub fn report_metrics(&self) {
+        datapoint_info!(
+            "rpc_pubsub_memory",
+            ("max_bytes", self.max_bytes(), i64),
+            ("total_bytes", self.total(), i64),
+            ("scratch_bytes", self.used(MemoryCategory::Scratch), i64),
+            (
+                "recent_items_bytes",
+                self.used(MemoryCategory::RecentItems),
+                i64
+            ),
+            (
+                "recent_signatures_bytes",
+                self.used(MemoryCategory::RecentSignatures),
+                i64
+            ),
+            (
+                "connection_buffers_bytes",
+                self.used(MemoryCategory::ConnectionBuffers),
+                i64
+            ),
+            (
+                "notification_queue_bytes",
+                self.used(MemoryCategory::NotificationQueue),
+                i64
+            ),
+        );
+    }
+}
+
+/// Memory accounted to a `MemoryBudget` for as long as the guard is alive.
+pub struct Reservation {
+    budget: MemoryBudget,
+    category: MemoryCategory,
+    bytes: usize,
+}
+
+impl Drop for Reservation {
+    fn drop(&mut self) {
+        self.budget.release(self.category, self.bytes);
+    }
+}
+
+impl Default for MemoryBudget {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_MEMORY_BYTES)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shed_in_priority_order() {
+        let budget = MemoryBudget::new(1000);
+        assert!(budget.try_reserve(MemoryCategory::RecentItems, 500));
+        assert!(!budget.should_shed(MemoryCategory::Scratch));
+
+        assert!(!budget.try_reserve(MemoryCategory::Scratch, 1));
+        assert!(budget.try_reserve(MemoryCategory::RecentItems, 250));
+        assert!(budget.should_shed(MemoryCategory::Scratch));
+        assert!(!budget.should_shed(MemoryCategory::RecentItems));
+
+        assert!(!budget.try_reserve(MemoryCategory::RecentItems, 1));
+        assert!(budget.try_reserve(MemoryCategory::ConnectionBuffers, 150));
+        assert!(!budget.try_reserve(MemoryCategory::ConnectionBuffers, 1));
+        assert!(budget.try_reserve(MemoryCategory::NotificationQueue, 100));
+        assert!(!budget.try_reserve(MemoryCategory::NotificationQueue, 1));
+        assert_eq!(budget.total(), 1000);
+
+        budget.release(MemoryCategory::RecentItems, 750);
+        assert_eq!(budget.total(), 250);
+        assert!(budget.try_reserve(MemoryCategory::Scratch, 250));
+    }
+
+    #[test]
+    fn test_reservation_released_on_drop() {
+        let budget = MemoryBudget::new(1000);
+        let reservation = budget
+            .try_reservation(MemoryCategory::ConnectionBuffers, 300)
+            .unwrap();
+        assert_eq!(budget.used(MemoryCategory::ConnectionBuffers), 300);
+        assert!(budget
+            .try_reservation(MemoryCategory::ConnectionBuffers, 700)
+            .is_none());
+        drop(reservation);
+        assert_eq!(budget.total(), 0);
+    }
+
+    #[test]
+    fn test_release_saturates() {
+        let budget = MemoryBudget::new(1000);
+        budget.reserve(MemoryCategory::RecentSignatures, 100);
+        budget.release(MemoryCategory::RecentSignatures, 150);
+        assert_eq!(budget.used(MemoryCategory::RecentSignatures), 0);
+        assert!(budget.try_reserve(MemoryCategory::RecentSignatures, 750));
+        assert!(!budget.try_reserve(MemoryCategory::RecentSignatures, 1));
+    }
+}
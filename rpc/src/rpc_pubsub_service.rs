@@ -3,48 +3,294 @@
 use {
     crate::{
         rpc_pubsub::{RpcSolPubSubImpl, RpcSolPubSubInternal},
+        rpc_pubsub_auth::ApiKeys,
+        rpc_pubsub_memory::{MemoryCategory, DEFAULT_MAX_MEMORY_BYTES},
         rpc_subscription_tracker::{
-            SubscriptionControl, SubscriptionId, SubscriptionParams, SubscriptionToken,
+            CompositeSubscriptions, SubscriptionControl, SubscriptionId, SubscriptionParams,
+            SubscriptionToken,
         },
-        rpc_subscriptions::{RpcNotification, RpcSubscriptions},
+        rpc_subscriptions::{
+            DirectNotification, RpcNotification, RpcSubscriptions, DIRECT_NOTIFICATION_BUFFER_SIZE,
+        },
+        rpc_tls::TlsConfig,
     },
     dashmap::{mapref::entry::Entry, DashMap},
     jsonrpc_core::IoHandler,
-    soketto::handshake::{server, Server},
-    solana_metrics::TokenCounter,
+    serde_json::json,
+    soketto::{
+        data::ByteSlice125,
+        extension::deflate::Deflate,
+        handshake::{server, Server},
+        Incoming, Mode,
+    },
+    solana_metrics::{CounterToken, TokenCounter},
+    solana_rayon_threadlimit::get_thread_count,
+    solana_sdk::clock::Slot,
     std::{
+        convert::TryFrom,
         io,
-        net::SocketAddr,
-        str,
-        sync::Arc,
+        net::{IpAddr, SocketAddr},
+        path::PathBuf,
+        pin::Pin,
+        str::{self, FromStr},
+        sync::{Arc, RwLock},
+        task::{Context, Poll},
         thread::{self, Builder, JoinHandle},
+        time::Duration,
     },
     stream_cancel::{Trigger, Tripwire},
     thiserror::Error,
-    tokio::{net::TcpStream, pin, select, sync::broadcast},
-    tokio_util::compat::TokioAsyncReadCompatExt,
+    tokio::{
+        io::{AsyncRead, AsyncReadExt, AsyncWrite, ReadBuf},
+        pin, select,
+        sync::{broadcast, mpsc, watch},
+        time,
+    },
+    tokio_rustls::TlsAcceptor,
+    tokio_util::compat::{Compat, TokioAsyncReadCompatExt},
+    tracing::{debug_span, Instrument},
 };
 
+#[cfg(unix)]
+use crate::rpc_service::bind_unix_listener;
+
 pub const MAX_ACTIVE_SUBSCRIPTIONS: usize = 1_000_000;
 pub const DEFAULT_QUEUE_CAPACITY_ITEMS: usize = 10_000_000;
 pub const DEFAULT_TEST_QUEUE_CAPACITY_ITEMS: usize = 100;
 pub const DEFAULT_QUEUE_CAPACITY_BYTES: usize = 256 * 1024 * 1024;
+pub const DEFAULT_FIREHOSE_QUEUE_CAPACITY_ITEMS: usize = 1_000_000;
+pub const DEFAULT_SIGNATURE_NOTIFICATION_RETENTION: Duration = Duration::from_secs(30);
+pub const DEFAULT_MAX_ACCOUNTS_PER_SUBSCRIPTION: usize = 1_000;
+pub const DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION: usize = 10_000;
+pub const DEFAULT_MAX_SUBSCRIPTIONS_PER_IP: usize = 100_000;
+pub const DEFAULT_NOTIFICATION_QUEUE_CAPACITY: usize = 10_000;
+pub const DEFAULT_KEEPALIVE_INTERVAL: Duration = Duration::from_secs(30);
+pub const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+pub const DEFAULT_MAX_GRPC_STREAMS: usize = 10_000;
+/// Websocket subprotocol a client offers during the handshake to receive CBOR binary
+/// frames instead of JSON text frames
+pub const CBOR_SUBPROTOCOL: &str = "solana-pubsub-cbor";
+/// How long vote and slot notifications are withheld from a lagging client under
+/// `SlowConsumerPolicy::PauseNonCritical`
+pub const SLOW_CONSUMER_FIREHOSE_PAUSE: Duration = Duration::from_secs(5);
+
+/// What to do with a client that fell so far behind that notifications it has not
+/// received yet were evicted from the broadcast queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlowConsumerPolicy {
+    /// Skip the evicted notifications and continue with the oldest one still queued
+    DropOldest,
+    /// Close the connection
+    Disconnect,
+    /// Skip the evicted notifications and also stop sending vote and slot notifications
+    /// for a while, so that the client can catch up on the other subscriptions
+    PauseNonCritical,
+}
+
+impl FromStr for SlowConsumerPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "drop-oldest" => Ok(Self::DropOldest),
+            "disconnect" => Ok(Self::Disconnect),
+            "pause-non-critical" => Ok(Self::PauseNonCritical),
+            _ => Err(format!("unknown slow consumer policy: {}", s)),
+        }
+    }
+}
+
+/// How messages sent to a websocket client are encoded. Requests are always JSON.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MessageEncoding {
+    Json,
+    Cbor,
+}
+
+impl MessageEncoding {
+    fn negotiate<'a>(mut protocols: impl Iterator<Item = &'a str>) -> Self {
+        if protocols.any(|protocol| protocol == CBOR_SUBPROTOCOL) {
+            Self::Cbor
+        } else {
+            Self::Json
+        }
+    }
+
+    fn protocol(self) -> Option<&'static str> {
+        match self {
+            Self::Json => None,
+            Self::Cbor => Some(CBOR_SUBPROTOCOL),
+        }
+    }
+}
+
+/// Transcodes a JSON message to CBOR, for the messages of a single connection, such as
+/// responses. Notifications are encoded to CBOR once, by the notifier.
+fn json_to_cbor(json: &str) -> Vec<u8> {
+    let value: serde_cbor::Value = serde_json::from_str(json).expect("messages are valid json");
+    serde_cbor::to_vec(&value).expect("cbor serialization failed")
+}
+
+type WebsocketSender<S> = soketto::connection::Sender<Compat<S>>;
+
+async fn send_message<S: AsyncRead + AsyncWrite + Unpin>(
+    sender: &mut WebsocketSender<S>,
+    encoding: MessageEncoding,
+    json: &str,
+) -> Result<(), soketto::connection::Error> {
+    send_notification(sender, encoding, json, None).await
+}
+
+/// Sends a message, using `cbor` if it is the CBOR encoding of `json` already
+async fn send_notification<S: AsyncRead + AsyncWrite + Unpin>(
+    sender: &mut WebsocketSender<S>,
+    encoding: MessageEncoding,
+    json: &str,
+    cbor: Option<&[u8]>,
+) -> Result<(), soketto::connection::Error> {
+    match (encoding, cbor) {
+        (MessageEncoding::Json, _) => sender.send_text(json).await,
+        (MessageEncoding::Cbor, Some(cbor)) => sender.send_binary(cbor).await,
+        (MessageEncoding::Cbor, None) => sender.send_binary(&json_to_cbor(json)).await,
+    }
+}
+
+/// The response to a request refused because connection buffers are being shed
+fn memory_budget_refusal(request: &[u8]) -> String {
+    let id = match serde_json::from_slice(request) {
+        Ok(serde_json::Value::Object(request)) => request.get("id").cloned(),
+        _ => None,
+    };
+    json!({
+        "jsonrpc": "2.0",
+        "error": {
+            "code": jsonrpc_core::ErrorCode::InternalError.code(),
+            "message": "Internal Error: Request refused. Pubsub memory budget exhausted",
+        },
+        "id": id,
+    })
+    .to_string()
+}
+
+/// Drains the pubsub services ahead of a restart. Once started, new connections and
+/// subscriptions are refused, and connected websocket clients are sent a
+/// `serverShuttingDown` notification suggesting when to reconnect, while their existing
+/// subscriptions are still served.
+#[derive(Debug)]
+pub struct PubSubDrain {
+    sender: watch::Sender<Option<Duration>>,
+    receiver: watch::Receiver<Option<Duration>>,
+}
+
+impl Default for PubSubDrain {
+    fn default() -> Self {
+        let (sender, receiver) = watch::channel(None);
+        Self { sender, receiver }
+    }
+}
+
+impl PubSubDrain {
+    pub fn start(&self, reconnect_delay: Duration) {
+        // Can't fail, `self` holds a receiver
+        let _ = self.sender.send(Some(reconnect_delay));
+    }
+
+    pub fn is_draining(&self) -> bool {
+        self.receiver.borrow().is_some()
+    }
+
+    pub(crate) fn subscribe(&self) -> watch::Receiver<Option<Duration>> {
+        self.receiver.clone()
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct PubSubConfig {
+    pub enable_block_subscription: bool,
+    pub enable_transaction_subscription: bool,
+    /// Requires the `spl-token-owner` account index
+    pub enable_token_accounts_by_owner_subscription: bool,
     pub enable_vote_subscription: bool,
     pub max_active_subscriptions: usize,
+    /// Maximum number of subscriptions a single websocket connection may hold
+    pub max_subscriptions_per_connection: usize,
+    /// Maximum number of subscriptions held by all connections from one IP address
+    pub max_subscriptions_per_ip: usize,
+    /// Maximum number of pubkeys accepted by a single `accountsSubscribe` request
+    pub max_accounts_per_subscription: usize,
     pub queue_capacity_items: usize,
     pub queue_capacity_bytes: usize,
+    /// Capacity of the broadcast channel carrying vote and slot notifications
+    pub firehose_queue_capacity_items: usize,
+    pub max_memory_bytes: usize,
+    /// How long terminal signature notifications remain available through
+    /// `getRecentSignatureNotification`; zero disables retention
+    pub signature_notification_retention: Duration,
+    /// Only accept finalized-commitment subscriptions, which lets the notifier skip
+    /// gossip and processed-bank scans entirely
+    pub finalized_only: bool,
+    pub slow_consumer_policy: SlowConsumerPolicy,
+    /// Number of threads scanning subscriptions for each bank and gossip notification;
+    /// zero scans on the notification thread itself. Defaults to a quarter of the cores,
+    /// like the RPC scan threads, so that notifications don't compete with replay.
+    pub notification_threads: usize,
+    /// Number of entries the queue between replay and the notification thread holds.
+    /// Bank and gossip entries that don't fit are merged into the latest one, other
+    /// entries are dropped.
+    pub notification_queue_capacity: usize,
+    /// Accept the permessage-deflate websocket extension from clients that offer it
+    pub enable_compression: bool,
+    /// Also accept websocket connections on this unix socket. Subscriptions made through
+    /// it are not counted against the per-IP limit.
+    pub unix_socket_path: Option<PathBuf>,
+    /// Accept websocket connections over TLS only
+    pub tls: Option<TlsConfig>,
+    /// Only accept connections with one of these API keys, passed in an
+    /// `Authorization: Bearer <KEY>` header or the `token` query parameter of the websocket
+    /// upgrade request. Shared by the websocket, SSE and gRPC services.
+    pub api_keys: Option<Arc<ApiKeys>>,
+    /// Maximum number of webhooks registered through the SSE service by each API key; zero
+    /// disables webhooks
+    pub max_webhooks: usize,
+    /// Maximum number of streams open at once on the gRPC service
+    pub max_grpc_streams: usize,
+    /// How often websocket clients are pinged; zero disables pings and idle timeouts
+    pub keepalive_interval: Duration,
+    /// Websocket clients that send nothing, not even a pong, for this long are disconnected,
+    /// releasing their subscriptions
+    pub idle_timeout: Duration,
+    pub drain: Arc<PubSubDrain>,
 }
 
 impl Default for PubSubConfig {
     fn default() -> Self {
         Self {
+            enable_block_subscription: false,
+            enable_transaction_subscription: false,
+            enable_token_accounts_by_owner_subscription: false,
             enable_vote_subscription: false,
             max_active_subscriptions: MAX_ACTIVE_SUBSCRIPTIONS,
+            max_subscriptions_per_connection: DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION,
+            max_subscriptions_per_ip: DEFAULT_MAX_SUBSCRIPTIONS_PER_IP,
+            max_accounts_per_subscription: DEFAULT_MAX_ACCOUNTS_PER_SUBSCRIPTION,
             queue_capacity_items: DEFAULT_QUEUE_CAPACITY_ITEMS,
             queue_capacity_bytes: DEFAULT_QUEUE_CAPACITY_BYTES,
+            firehose_queue_capacity_items: DEFAULT_FIREHOSE_QUEUE_CAPACITY_ITEMS,
+            max_memory_bytes: DEFAULT_MAX_MEMORY_BYTES,
+            signature_notification_retention: DEFAULT_SIGNATURE_NOTIFICATION_RETENTION,
+            finalized_only: false,
+            slow_consumer_policy: SlowConsumerPolicy::Disconnect,
+            notification_threads: 1.max(get_thread_count() / 4),
+            notification_queue_capacity: DEFAULT_NOTIFICATION_QUEUE_CAPACITY,
+            enable_compression: false,
+            unix_socket_path: None,
+            tls: None,
+            api_keys: None,
+            max_webhooks: 0,
+            max_grpc_streams: DEFAULT_MAX_GRPC_STREAMS,
+            keepalive_interval: DEFAULT_KEEPALIVE_INTERVAL,
+            idle_timeout: DEFAULT_IDLE_TIMEOUT,
+            drain: Arc::default(),
         }
     }
 }
@@ -52,10 +298,32 @@ impl Default for PubSubConfig {
 impl PubSubConfig {
     pub fn default_for_tests() -> Self {
         Self {
+            enable_block_subscription: false,
+            enable_transaction_subscription: false,
+            enable_token_accounts_by_owner_subscription: false,
             enable_vote_subscription: false,
             max_active_subscriptions: MAX_ACTIVE_SUBSCRIPTIONS,
+            max_subscriptions_per_connection: DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION,
+            max_subscriptions_per_ip: DEFAULT_MAX_SUBSCRIPTIONS_PER_IP,
+            max_accounts_per_subscription: DEFAULT_MAX_ACCOUNTS_PER_SUBSCRIPTION,
             queue_capacity_items: DEFAULT_TEST_QUEUE_CAPACITY_ITEMS,
             queue_capacity_bytes: DEFAULT_QUEUE_CAPACITY_BYTES,
+            firehose_queue_capacity_items: DEFAULT_TEST_QUEUE_CAPACITY_ITEMS,
+            max_memory_bytes: DEFAULT_MAX_MEMORY_BYTES,
+            signature_notification_retention: DEFAULT_SIGNATURE_NOTIFICATION_RETENTION,
+            finalized_only: false,
+            slow_consumer_policy: SlowConsumerPolicy::Disconnect,
+            notification_threads: 2,
+            notification_queue_capacity: DEFAULT_NOTIFICATION_QUEUE_CAPACITY,
+            enable_compression: false,
+            unix_socket_path: None,
+            tls: None,
+            api_keys: None,
+            max_webhooks: 0,
+            max_grpc_streams: DEFAULT_MAX_GRPC_STREAMS,
+            keepalive_interval: Duration::ZERO,
+            idle_timeout: DEFAULT_IDLE_TIMEOUT,
+            drain: Arc::default(),
         }
     }
 }
@@ -104,8 +372,15 @@ impl PubSubService {
     }
 }
 
-struct BroadcastHandler {
-    current_subscriptions: Arc<DashMap<SubscriptionId, SubscriptionToken>>,
+/// Maximum number of notifications held for a subscription while its logs replay is in
+/// progress; later ones are dropped
+const MAX_HELD_REPLAY_NOTIFICATIONS: usize = 10_000;
+
+pub(crate) struct BroadcastHandler {
+    pub(crate) current_subscriptions: Arc<DashMap<SubscriptionId, SubscriptionToken>>,
+    pub(crate) composite_subscriptions: Arc<RwLock<CompositeSubscriptions>>,
+    /// Notifications of the subscriptions whose logs replay is in progress
+    held_replays: DashMap<SubscriptionId, Vec<Arc<String>>>,
 }
 
 fn count_final(params: &SubscriptionParams) {
@@ -113,12 +388,45 @@ fn count_final(params: &SubscriptionParams) {
         SubscriptionParams::Account(_) => {
             inc_new_counter_info!("rpc-pubsub-final-accounts", 1);
         }
+        SubscriptionParams::Accounts(_) => {
+            inc_new_counter_info!("rpc-pubsub-final-account-sets", 1);
+        }
+        SubscriptionParams::TokenAccountsByOwner(_) => {
+            inc_new_counter_info!("rpc-pubsub-final-token-owners", 1);
+        }
+        SubscriptionParams::Blockhash(_) => {
+            inc_new_counter_info!("rpc-pubsub-final-blockhashes", 1);
+        }
+        SubscriptionParams::BlockhashValidity(_) => {
+            inc_new_counter_info!("rpc-pubsub-final-blockhash-validities", 1);
+        }
+        SubscriptionParams::Block(_) => {
+            inc_new_counter_info!("rpc-pubsub-final-blocks", 1);
+        }
+        SubscriptionParams::Epoch => {
+            inc_new_counter_info!("rpc-pubsub-final-epochs", 1);
+        }
+        SubscriptionParams::LeaderSchedule(_) => {
+            inc_new_counter_info!("rpc-pubsub-final-leader-schedules", 1);
+        }
         SubscriptionParams::Logs(_) => {
             inc_new_counter_info!("rpc-pubsub-final-logs", 1);
         }
+        SubscriptionParams::Mint(_) => {
+            inc_new_counter_info!("rpc-pubsub-final-mints", 1);
+        }
+        SubscriptionParams::PerformanceSamples => {
+            inc_new_counter_info!("rpc-pubsub-final-performance-samples", 1);
+        }
         SubscriptionParams::Program(_) => {
             inc_new_counter_info!("rpc-pubsub-final-programs", 1);
         }
+        SubscriptionParams::ProgramChanges(_) => {
+            inc_new_counter_info!("rpc-pubsub-final-program-changes", 1);
+        }
+        SubscriptionParams::Rewards(_) => {
+            inc_new_counter_info!("rpc-pubsub-final-rewards", 1);
+        }
         SubscriptionParams::Signature(_) => {
             inc_new_counter_info!("rpc-pubsub-final-signatures", 1);
         }
@@ -128,41 +436,178 @@ fn count_final(params: &SubscriptionParams) {
         SubscriptionParams::SlotsUpdates => {
             inc_new_counter_info!("rpc-pubsub-final-slots-updates", 1);
         }
-        SubscriptionParams::Root => {
+        SubscriptionParams::Root(_) => {
             inc_new_counter_info!("rpc-pubsub-final-roots", 1);
         }
-        SubscriptionParams::Vote => {
+        SubscriptionParams::Stake(_) => {
+            inc_new_counter_info!("rpc-pubsub-final-stakes", 1);
+        }
+        SubscriptionParams::Transaction(_) => {
+            inc_new_counter_info!("rpc-pubsub-final-transactions", 1);
+        }
+        SubscriptionParams::Vote(_) => {
             inc_new_counter_info!("rpc-pubsub-final-votes", 1);
         }
     }
 }
 
+/// Returns the serialized result of a notification written by the notifier, which always
+/// has the layout `{"jsonrpc":"2.0","method":..,"params":{"result":..,"subscription":..}}`
+fn notification_result(json: &str) -> Option<&str> {
+    const RESULT_PREFIX: &str = r#","params":{"result":"#;
+    const RESULT_SUFFIX: &str = r#","subscription":"#;
+    let start = json.find(RESULT_PREFIX)? + RESULT_PREFIX.len();
+    let end = json.rfind(RESULT_SUFFIX)?;
+    json.get(start..end)
+}
+
+/// Writes a `multiplexNotification` of the composite subscription `id` around the already
+/// serialized result of an item notification, adding the tag of the item.
+fn multiplex_notification(result: &str, id: SubscriptionId, tag: &str) -> String {
+    format!(
+        concat!(
+            r#"{{"jsonrpc":"2.0","method":"multiplexNotification","#,
+            r#""params":{{"result":{},"subscription":{},"tag":{}}}}}"#,
+        ),
+        result,
+        u64::from(id),
+        serde_json::to_string(tag).expect("serialization never fails"),
+    )
+}
+
 impl BroadcastHandler {
-    fn handle(&self, notification: RpcNotification) -> Result<Option<Arc<String>>, Error> {
-        if let Entry::Occupied(entry) = self
-            .current_subscriptions
-            .entry(notification.subscription_id)
-        {
+    pub(crate) fn new(
+        current_subscriptions: Arc<DashMap<SubscriptionId, SubscriptionToken>>,
+        composite_subscriptions: Arc<RwLock<CompositeSubscriptions>>,
+    ) -> Self {
+        Self {
+            current_subscriptions,
+            composite_subscriptions,
+            held_replays: DashMap::new(),
+        }
+    }
+
+    /// Counts notifications skipped by the client against subscription `id`. Notifications
+    /// skipped on the broadcast queue are counted once the next notification of their
+    /// subscription arrives.
+    pub(crate) fn record_lag_drops(&self, id: SubscriptionId, skipped: u64) {
+        if let Some(token) = self.current_subscriptions.get(&id) {
+            token.record_lag_drops(skipped);
+        }
+    }
+
+    /// Returns the messages to send for `notification`: the notification itself if the
+    /// connection is subscribed to it, and a tagged copy for each composite subscription
+    /// that includes it.
+    pub(crate) fn handle(&self, notification: RpcNotification) -> Result<Vec<Arc<String>>, Error> {
+        let id = notification.subscription_id;
+        let mut jsons = Vec::new();
+        if let Entry::Occupied(entry) = self.current_subscriptions.entry(id) {
             count_final(entry.get().params());
+            entry.get().record_delivery(notification.sequence);
 
             if notification.is_final {
                 entry.remove();
             }
-            notification
-                .json
-                .upgrade()
-                .ok_or(Error::NotificationIsGone)
-                .map(Some)
-        } else {
-            Ok(None)
+            let json = notification.json.upgrade().ok_or(Error::NotificationIsGone);
+            match self.held_replays.get_mut(&id) {
+                Some(mut held) => {
+                    if held.len() < MAX_HELD_REPLAY_NOTIFICATIONS {
+                        held.push(json?);
+                    } else {
+                        self.record_lag_drops(id, 1);
+                    }
+                }
+                None => jsons.push(json),
+            }
+        }
+
+        // Only the final notification of an item changes the composite subscriptions
+        let composite_subscriptions = self.composite_subscriptions.read().unwrap();
+        let routed = match composite_subscriptions.routes(&id) {
+            Some((params, routes)) => {
+                let json = notification.json.upgrade();
+                let result = json.as_deref().and_then(|json| {
+                    let result = notification_result(json);
+                    if result.is_none() {
+                        warn!("notification of an unexpected layout: {}", json);
+                    }
+                    result
+                });
+                for (composite_id, tag) in routes {
+                    count_final(params);
+                    match (&json, result) {
+                        (Some(_), Some(result)) => jsons.push(Ok(Arc::new(
+                            multiplex_notification(result, *composite_id, tag),
+                        ))),
+                        (Some(_), None) => {}
+                        (None, _) => jsons.push(Err(Error::NotificationIsGone)),
+                    }
+                }
+                true
+            }
+            None => false,
+        };
+        drop(composite_subscriptions);
+        if notification.is_final && routed {
+            self.composite_subscriptions
+                .write()
+                .unwrap()
+                .finish_item(id);
+        }
+        jsons.into_iter().collect()
+    }
+
+    /// Returns the messages to send for a direct notification. Direct notifications are
+    /// dropped if the subscription was closed in the meantime.
+    pub(crate) fn handle_direct(&self, notification: DirectNotification) -> Vec<Arc<String>> {
+        match notification {
+            DirectNotification::Message {
+                subscription_id,
+                json,
+            } => {
+                if self.current_subscriptions.contains_key(&subscription_id) {
+                    vec![json]
+                } else {
+                    vec![]
+                }
+            }
+            DirectNotification::ReplayStarted(subscription_id) => {
+                self.held_replays.insert(subscription_id, vec![]);
+                vec![]
+            }
+            DirectNotification::ReplayFinished {
+                subscription_id,
+                end_slot,
+            } => {
+                let held = match self.held_replays.remove(&subscription_id) {
+                    Some((_, held)) => held,
+                    None => return vec![],
+                };
+                held.into_iter()
+                    .filter(|json| match end_slot {
+                        Some(end_slot) => notification_slot(json) > Some(end_slot),
+                        None => true,
+                    })
+                    .collect()
+            }
         }
     }
 }
 
+/// The context slot of a notification
+fn notification_slot(json: &str) -> Option<Slot> {
+    let notification: serde_json::Value = serde_json::from_str(json).ok()?;
+    notification["params"]["result"]["context"]["slot"].as_u64()
+}
+
 #[cfg(test)]
 pub struct TestBroadcastReceiver {
     handler: BroadcastHandler,
+    pending: std::collections::VecDeque<String>,
     inner: tokio::sync::broadcast::Receiver<RpcNotification>,
+    firehose: tokio::sync::broadcast::Receiver<RpcNotification>,
+    direct: mpsc::Receiver<DirectNotification>,
 }
 
 #[cfg(test)]
@@ -176,15 +621,28 @@ impl TestBroadcastReceiver {
         let started = Instant::now();
 
         loop {
-            match self.inner.try_recv() {
+            if let Some(json) = self.pending.pop_front() {
+                return json;
+            }
+            if let Ok(notification) = self.direct.try_recv() {
+                let jsons = self.handler.handle_direct(notification);
+                self.pending
+                    .extend(jsons.into_iter().map(|json| json.to_string()));
+                continue;
+            }
+            let result = match self.inner.try_recv() {
+                Err(TryRecvError::Empty) => self.firehose.try_recv(),
+                result => result,
+            };
+            match result {
                 Ok(notification) => {
                     debug!(
                         "TestBroadcastReceiver: {:?}ms elapsed",
                         started.elapsed().as_millis()
                     );
-                    if let Some(json) = self.handler.handle(notification).expect("handler failed") {
-                        return json.to_string();
-                    }
+                    let jsons = self.handler.handle(notification).expect("handler failed");
+                    self.pending
+                        .extend(jsons.into_iter().map(|json| json.to_string()));
                 }
                 Err(TryRecvError::Empty) => {
                     assert!(
@@ -204,28 +662,38 @@ pub fn test_connection(
     subscriptions: &Arc<RpcSubscriptions>,
 ) -> (RpcSolPubSubImpl, TestBroadcastReceiver) {
     let current_subscriptions = Arc::new(DashMap::new());
+    let composite_subscriptions = Arc::new(RwLock::new(CompositeSubscriptions::default()));
+    let (direct_sender, direct_receiver) = mpsc::channel(DIRECT_NOTIFICATION_BUFFER_SIZE);
 
     let rpc_impl = RpcSolPubSubImpl::new(
         PubSubConfig {
+            enable_block_subscription: true,
+            enable_transaction_subscription: true,
+            enable_token_accounts_by_owner_subscription: true,
             enable_vote_subscription: true,
             queue_capacity_items: 100,
             ..PubSubConfig::default()
         },
         subscriptions.control().clone(),
         Arc::clone(&current_subscriptions),
+        Arc::clone(&composite_subscriptions),
+        direct_sender,
+        None,
+        None,
     );
-    let broadcast_handler = BroadcastHandler {
-        current_subscriptions,
-    };
+    let broadcast_handler = BroadcastHandler::new(current_subscriptions, composite_subscriptions);
     let receiver = TestBroadcastReceiver {
+        pending: std::collections::VecDeque::new(),
         inner: subscriptions.control().broadcast_receiver(),
+        firehose: subscriptions.control().firehose_broadcast_receiver(),
+        direct: direct_receiver,
         handler: broadcast_handler,
     };
     (rpc_impl, receiver)
 }
 
 #[derive(Debug, Error)]
-enum Error {
+pub(crate) enum Error {
     #[error("handshake error: {0}")]
     Handshake(#[from] soketto::handshake::Error),
     #[error("connection error: {0}")]
@@ -234,66 +702,431 @@ enum Error {
     Broadcast(#[from] broadcast::error::RecvError),
     #[error("client has lagged behind (notification is gone)")]
     NotificationIsGone,
+    #[error("client has lagged behind ({0} notifications dropped)")]
+    SlowConsumer(u64),
+    #[error("tls handshake error: {0}")]
+    Tls(io::Error),
+    #[error("upgrade request error: {0}")]
+    Request(io::Error),
+}
+
+/// Applies `policy` to a client that missed `skipped` notifications. Returns an error if
+/// the client has to be disconnected.
+pub(crate) fn handle_slow_consumer(
+    policy: SlowConsumerPolicy,
+    skipped: u64,
+    firehose_paused_until: &mut Option<time::Instant>,
+) -> Result<(), Error> {
+    match policy {
+        SlowConsumerPolicy::Disconnect => {
+            inc_new_counter_info!("rpc-pubsub-slow-consumer-disconnected", 1);
+            return Err(Error::SlowConsumer(skipped));
+        }
+        SlowConsumerPolicy::DropOldest => {}
+        SlowConsumerPolicy::PauseNonCritical => {
+            if firehose_paused_until.is_none() {
+                inc_new_counter_info!("rpc-pubsub-slow-consumer-paused", 1);
+            }
+            *firehose_paused_until = Some(time::Instant::now() + SLOW_CONSUMER_FIREHOSE_PAUSE);
+        }
+    }
+    debug!(
+        "websocket client lagged behind, {} notifications dropped",
+        skipped
+    );
+    inc_new_counter_info!("rpc-pubsub-slow-consumer-dropped", skipped as usize);
+    Ok(())
+}
+
+/// Notifications buffered for a gRPC or SSE stream before the task forwarding them waits
+/// for the client. A client that stays behind long enough lags on the broadcast queues and
+/// is handled according to `PubSubConfig::slow_consumer_policy`.
+pub(crate) const STREAM_BUFFER_SIZE: usize = 1_024;
+
+/// Forwards the notifications of a stream holding a single subscription, such as a gRPC or
+/// SSE stream, until the client goes away or the subscription ends. Notifications are
+/// converted with `notification`; a client that has to be disconnected gets `error` last.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn forward_notifications<T>(
+    handler: BroadcastHandler,
+    mut broadcast_receiver: broadcast::Receiver<RpcNotification>,
+    mut firehose_receiver: broadcast::Receiver<RpcNotification>,
+    mut direct_receiver: mpsc::Receiver<DirectNotification>,
+    sender: mpsc::Sender<T>,
+    config: PubSubConfig,
+    notification: impl Fn(SubscriptionId, Arc<String>) -> T,
+    error: impl FnOnce(Error) -> T,
+) {
+    // A stream carries a single subscription, so there is nothing to catch up on while
+    // vote and slot notifications are paused
+    let mut firehose_paused_until = None;
+    loop {
+        // Direct notifications are polled before broadcasts, as on websocket connections
+        let result = select! {
+            biased;
+            _ = sender.closed() => return,
+            Some(direct) = direct_receiver.recv() => {
+                let id = direct.subscription_id();
+                for json in handler.handle_direct(direct) {
+                    if sender.send(notification(id, json)).await.is_err() {
+                        return;
+                    }
+                }
+                continue;
+            },
+            result = broadcast_receiver.recv() => result,
+            result = firehose_receiver.recv() => result,
+        };
+        let (id, skipped) = match result {
+            Ok(rpc_notification) => {
+                let id = rpc_notification.subscription_id;
+                match handler.handle(rpc_notification) {
+                    Ok(jsons) => {
+                        for json in jsons {
+                            if sender.send(notification(id, json)).await.is_err() {
+                                return;
+                            }
+                        }
+                        if handler.current_subscriptions.is_empty() {
+                            return;
+                        }
+                        continue;
+                    }
+                    Err(_) => (Some(id), 1),
+                }
+            }
+            Err(broadcast::error::RecvError::Lagged(skipped)) => (None, skipped),
+            Err(broadcast::error::RecvError::Closed) => return,
+        };
+        if let Some(id) = id {
+            handler.record_lag_drops(id, skipped);
+        }
+        if let Err(err) = handle_slow_consumer(
+            config.slow_consumer_policy,
+            skipped,
+            &mut firehose_paused_until,
+        ) {
+            let _ = sender.send(error(err)).await;
+            return;
+        }
+    }
 }
 
-async fn handle_connection(
-    socket: TcpStream,
+/// Longest websocket upgrade request head accepted
+const MAX_REQUEST_HEAD_SIZE: usize = 8 * 1024;
+
+/// Reads from `socket` until the end of the head of the websocket upgrade request. Returns
+/// everything read, which may include data past the head.
+async fn read_request_head<S: AsyncRead + Unpin>(socket: &mut S) -> io::Result<Vec<u8>> {
+    let mut head = Vec::new();
+    let mut buffer = [0; 1024];
+    loop {
+        let len = socket.read(&mut buffer).await?;
+        if len == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        // The end of the head may straddle two reads
+        let start = head.len().saturating_sub(3);
+        head.extend_from_slice(&buffer[..len]);
+        if head[start..].windows(4).any(|window| window == b"\r\n\r\n") {
+            return Ok(head);
+        }
+        if head.len() > MAX_REQUEST_HEAD_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "upgrade request head too large",
+            ));
+        }
+    }
+}
+
+/// Returns the value of the header `name` in a request head
+fn request_header<'a>(head: &'a [u8], name: &str) -> Option<&'a str> {
+    let head = str::from_utf8(head).ok().or_else(|| {
+        // Data past the head isn't necessarily text
+        let end = head.windows(4).position(|window| window == b"\r\n\r\n")?;
+        str::from_utf8(&head[..end]).ok()
+    })?;
+    head.split("\r\n")
+        .skip(1)
+        .take_while(|line| !line.is_empty())
+        .filter_map(|line| line.split_once(':'))
+        .find(|(header, _)| header.trim().eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.trim())
+}
+
+/// A stream that yields `head` before reading from the underlying stream
+struct ReplayStream<S> {
+    head: Vec<u8>,
+    position: usize,
+    inner: S,
+}
+
+impl<S> ReplayStream<S> {
+    fn new(head: Vec<u8>, inner: S) -> Self {
+        Self {
+            head,
+            position: 0,
+            inner,
+        }
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for ReplayStream<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        if self.position < self.head.len() {
+            let len = buf.remaining().min(self.head.len() - self.position);
+            let position = self.position;
+            buf.put_slice(&self.head[position..position + len]);
+            self.position += len;
+            return Poll::Ready(Ok(()));
+        }
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for ReplayStream<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+async fn handle_connection<S: AsyncRead + AsyncWrite + Unpin>(
+    mut socket: S,
+    client_ip: Option<IpAddr>,
     subscription_control: SubscriptionControl,
     config: PubSubConfig,
     mut tripwire: Tripwire,
 ) -> Result<(), Error> {
+    // soketto doesn't expose the authorization header, so the request head is read here
+    // first and replayed to it
+    let (socket, authorization) = match &config.api_keys {
+        Some(_) => {
+            let head = read_request_head(&mut socket)
+                .await
+                .map_err(Error::Request)?;
+            let authorization = request_header(&head, "authorization").map(str::to_string);
+            (ReplayStream::new(head, socket), authorization)
+        }
+        None => (ReplayStream::new(Vec::new(), socket), None),
+    };
     let mut server = Server::new(socket.compat());
+    if config.enable_compression {
+        server.add_extension(Box::new(Deflate::new(Mode::Server)));
+    }
     let request = server.receive_request().await?;
+    let api_key = match &config.api_keys {
+        Some(api_keys) => match authorization
+            .and_then(|authorization| api_keys.authenticate_bearer(&authorization))
+            .or_else(|| api_keys.authenticate_path(request.path()))
+        {
+            Some(api_key) => Some(api_key),
+            None => {
+                inc_new_counter_info!("rpc-pubsub-unauthorized", 1);
+                let reject = server::Response::Reject { status_code: 401 };
+                server.send_response(&reject).await?;
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+    if config.drain.is_draining() {
+        let reject = server::Response::Reject { status_code: 503 };
+        server.send_response(&reject).await?;
+        return Ok(());
+    }
+    let encoding = MessageEncoding::negotiate(request.protocols());
     let accept = server::Response::Accept {
         key: request.key(),
-        protocol: None,
+        protocol: encoding.protocol(),
     };
     server.send_response(&accept).await?;
     let (mut sender, mut receiver) = server.into_builder().finish();
+    let _cbor_client =
+        (encoding == MessageEncoding::Cbor).then(|| subscription_control.cbor_clients().register());
 
+    let memory_budget = subscription_control.memory_budget().clone();
+    let slow_consumer_policy = config.slow_consumer_policy;
+    let keepalive_interval = config.keepalive_interval;
+    let idle_timeout = config.idle_timeout;
+    let mut next_ping =
+        (keepalive_interval > Duration::ZERO).then(|| time::Instant::now() + keepalive_interval);
+    let mut last_received = time::Instant::now();
+    let mut drain_receiver = config.drain.subscribe();
+    let mut drain_notified = false;
     let mut broadcast_receiver = subscription_control.broadcast_receiver();
+    let mut firehose_receiver = subscription_control.firehose_broadcast_receiver();
+    let mut firehose_paused_until = None;
+    let (direct_sender, mut direct_receiver) = mpsc::channel(DIRECT_NOTIFICATION_BUFFER_SIZE);
     let mut data = Vec::new();
     let current_subscriptions = Arc::new(DashMap::new());
+    let composite_subscriptions = Arc::new(RwLock::new(CompositeSubscriptions::default()));
+    if let Some(api_key) = &api_key {
+        api_key.register_connection(&current_subscriptions);
+    }
 
     let mut json_rpc_handler = IoHandler::new();
     let rpc_impl = RpcSolPubSubImpl::new(
         config,
-        subscription_control,
+        subscription_control.clone(),
         Arc::clone(&current_subscriptions),
+        Arc::clone(&composite_subscriptions),
+        direct_sender,
+        client_ip,
+        api_key.clone(),
     );
     json_rpc_handler.extend_with(rpc_impl.to_delegate());
-    let broadcast_handler = BroadcastHandler {
-        current_subscriptions,
-    };
+    let broadcast_handler = BroadcastHandler::new(current_subscriptions, composite_subscriptions);
     loop {
         // Extra block for dropping `receive_future`.
-        {
+        let received_data = {
             // soketto is not cancel safe, so we have to introduce an inner loop to poll
-            // `receive_data` to completion.
-            let receive_future = receiver.receive_data(&mut data);
+            // `receive` to completion.
+            let receive_future = receiver.receive(&mut data);
             pin!(receive_future);
             loop {
-                select! {
+                // Vote and slot notifications arrive on a separate channel, so a client
+                // lagging on them does not hold back the main broadcast queue.
+                // Direct notifications are polled before broadcasts, so that the initial
+                // state of a subscription is written ahead of its first change.
+                let result = select! {
+                    biased;
                     result = &mut receive_future => match result {
-                        Ok(_) => break,
-                        Err(soketto::connection::Error::Closed) => return Ok(()),
+                        Ok(Incoming::Data(_)) => break true,
+                        Ok(Incoming::Pong(_)) => break false,
+                        Ok(Incoming::Closed(_)) | Err(soketto::connection::Error::Closed) => {
+                            return Ok(())
+                        }
                         Err(err) => return Err(err.into()),
                     },
-                    result = broadcast_receiver.recv() => {
-
-                        // In both possible error cases (closed or lagged) we disconnect the client.
-                        if let Some(json) = broadcast_handler.handle(result?)? {
-                            sender.send_text(&*json).await?;
+                    _ = time::sleep_until(next_ping.unwrap_or_else(time::Instant::now)),
+                        if next_ping.is_some() => {
+                        if last_received.elapsed() >= idle_timeout {
+                            inc_new_counter_info!("rpc-pubsub-idle-disconnected", 1);
+                            debug!("disconnecting idle websocket client");
+                            return Ok(());
+                        }
+                        let payload = ByteSlice125::try_from(&[][..]).expect("empty ping payload");
+                        sender.send_ping(payload).await?;
+                        next_ping = Some(time::Instant::now() + keepalive_interval);
+                        continue;
+                    },
+                    Some(notification) = direct_receiver.recv() => {
+                        for json in broadcast_handler.handle_direct(notification) {
+                            let _reservation = memory_budget
+                                .reservation(MemoryCategory::ConnectionBuffers, json.len());
+                            send_message(&mut sender, encoding, &json).await?;
+                        }
+                        continue;
+                    },
+                    result = broadcast_receiver.recv() => result,
+                    result = firehose_receiver.recv(), if firehose_paused_until.is_none() => result,
+                    _ = time::sleep_until(firehose_paused_until.unwrap_or_else(time::Instant::now)),
+                        if firehose_paused_until.is_some() => {
+                        // Resume from the live end of the queue rather than the backlog
+                        firehose_receiver = subscription_control.firehose_broadcast_receiver();
+                        firehose_paused_until = None;
+                        continue;
+                    },
+                    result = drain_receiver.changed(), if !drain_notified => {
+                        drain_notified = true;
+                        let reconnect_delay = *drain_receiver.borrow();
+                        if let (Ok(()), Some(reconnect_delay)) = (result, reconnect_delay) {
+                            let json = json!({
+                                "jsonrpc": "2.0",
+                                "method": "serverShuttingDown",
+                                "params": {
+                                    "reconnectDelayMs": reconnect_delay.as_millis() as u64,
+                                },
+                            });
+                            send_message(&mut sender, encoding, &json.to_string()).await?;
                         }
+                        continue;
                     },
                     _ = &mut tripwire => {
                         warn!("disconnecting websocket client: shutting down");
                         return Ok(())
                     },
+                };
 
+                let notification = match result {
+                    Ok(notification) => notification,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        handle_slow_consumer(
+                            slow_consumer_policy,
+                            skipped,
+                            &mut firehose_paused_until,
+                        )?;
+                        continue;
+                    }
+                    Err(err) => return Err(err.into()),
+                };
+                let span = debug_span!(
+                    "rpc_notification_socket_write",
+                    subscription = u64::from(notification.subscription_id),
+                );
+                span.follows_from(notification.span_id.clone());
+                let id = notification.subscription_id;
+                // Composite subscriptions rewrite the notification, so the CBOR encoding
+                // only applies to the message that is sent as is
+                let json_ptr = notification.json.as_ptr();
+                let cbor = match encoding {
+                    MessageEncoding::Cbor => notification.cbor.upgrade(),
+                    MessageEncoding::Json => None,
+                };
+                let jsons = match broadcast_handler.handle(notification) {
+                    Err(Error::NotificationIsGone) => {
+                        broadcast_handler.record_lag_drops(id, 1);
+                        handle_slow_consumer(slow_consumer_policy, 1, &mut firehose_paused_until)?;
+                        Vec::new()
+                    }
+                    result => result?,
+                };
+                for json in jsons {
+                    let _reservation =
+                        memory_budget.reservation(MemoryCategory::ConnectionBuffers, json.len());
+                    let cbor = cbor
+                        .as_deref()
+                        .filter(|_| Arc::as_ptr(&json) == json_ptr)
+                        .map(Vec::as_slice);
+                    send_notification(&mut sender, encoding, &json, cbor)
+                        .instrument(span.clone())
+                        .await?;
                 }
             }
+        };
+        last_received = time::Instant::now();
+        if !received_data {
+            continue;
         }
+        // Incoming requests are the first thing to be refused once connection buffers have
+        // to be shed. The client stays connected and keeps its subscriptions.
+        let _reservation =
+            match memory_budget.try_reservation(MemoryCategory::ConnectionBuffers, data.len()) {
+                Some(reservation) => reservation,
+                None => {
+                    let refusal = memory_budget_refusal(&data);
+                    send_message(&mut sender, encoding, &refusal).await?;
+                    data.clear();
+                    continue;
+                }
+            };
         let data_str = match str::from_utf8(&data) {
             Ok(str) => str,
             Err(_) => {
@@ -303,8 +1136,13 @@ async fn handle_connection(
             }
         };
 
-        if let Some(response) = json_rpc_handler.handle_request(data_str).await {
-            sender.send_text(&response).await?;
+        let refusal = api_key
+            .as_ref()
+            .and_then(|api_key| api_key.refuse_request(data_str));
+        if let Some(refusal) = refusal {
+            send_message(&mut sender, encoding, &refusal).await?;
+        } else if let Some(response) = json_rpc_handler.handle_request(data_str).await {
+            send_message(&mut sender, encoding, &response).await?;
         }
         data.clear();
     }
@@ -312,6 +1150,49 @@ async fn handle_connection(
     Ok(())
 }
 
+/// Serves a websocket client on a task of its own, until it disconnects or the service
+/// shuts down.
+#[allow(clippy::too_many_arguments)]
+fn spawn_connection<S: AsyncRead + AsyncWrite + Unpin + Send + 'static>(
+    socket: S,
+    tls_acceptor: Option<TlsAcceptor>,
+    client_ip: Option<IpAddr>,
+    peer: String,
+    subscription_control: SubscriptionControl,
+    config: PubSubConfig,
+    tripwire: Tripwire,
+    counter_token: CounterToken,
+) {
+    debug!("new client ({})", peer);
+    tokio::spawn(async move {
+        let result = match tls_acceptor {
+            Some(tls_acceptor) => match tls_acceptor.accept(socket).await {
+                Ok(socket) => {
+                    let handle = handle_connection(
+                        socket,
+                        client_ip,
+                        subscription_control,
+                        config,
+                        tripwire,
+                    );
+                    handle.await
+                }
+                Err(err) => Err(Error::Tls(err)),
+            },
+            None => {
+                let handle =
+                    handle_connection(socket, client_ip, subscription_control, config, tripwire);
+                handle.await
+            }
+        };
+        match result {
+            Ok(()) => debug!("connection closed ({})", peer),
+            Err(err) => warn!("connection handler error ({}): {}", peer, err),
+        }
+        drop(counter_token); // Force moving token into the task.
+    });
+}
+
 async fn listen(
     listen_address: SocketAddr,
     config: PubSubConfig,
@@ -319,27 +1200,36 @@ async fn listen(
     mut tripwire: Tripwire,
 ) -> io::Result<()> {
     let listener = tokio::net::TcpListener::bind(&listen_address).await?;
+    let tls_acceptor = config.tls.as_ref().map(TlsConfig::acceptor).transpose()?;
+    #[cfg(unix)]
+    if let Some(path) = &config.unix_socket_path {
+        let unix_listener = bind_unix_listener(path)?;
+        info!("rpc_pubsub bound to {:?}", path);
+        tokio::spawn(listen_unix(
+            unix_listener,
+            config.clone(),
+            subscription_control.clone(),
+            tripwire.clone(),
+        ));
+    }
+    #[cfg(not(unix))]
+    if config.unix_socket_path.is_some() {
+        warn!("rpc_pubsub unix sockets are not supported on this platform");
+    }
     let counter = TokenCounter::new("rpc_pubsub_connections");
     loop {
         select! {
             result = listener.accept() => match result {
-                Ok((socket, addr)) => {
-                    debug!("new client ({:?})", addr);
-                    let subscription_control = subscription_control.clone();
-                    let config = config.clone();
-                    let tripwire = tripwire.clone();
-                    let counter_token = counter.create_token();
-                    tokio::spawn(async move {
-                        let handle = handle_connection(
-                            socket, subscription_control, config, tripwire
-                        );
-                        match handle.await {
-                            Ok(()) => debug!("connection closed ({:?})", addr),
-                            Err(err) => warn!("connection handler error ({:?}): {}", addr, err),
-                        }
-                        drop(counter_token); // Force moving token into the task.
-                    });
-                }
+                Ok((socket, addr)) => spawn_connection(
+                    socket,
+                    tls_acceptor.clone(),
+                    Some(addr.ip()),
+                    format!("{:?}", addr),
+                    subscription_control.clone(),
+                    config.clone(),
+                    tripwire.clone(),
+                    counter.create_token(),
+                ),
                 Err(e) => error!("couldn't accept connection: {:?}", e),
             },
             _ = &mut tripwire => return Ok(()),
@@ -347,6 +1237,34 @@ async fn listen(
     }
 }
 
+#[cfg(unix)]
+async fn listen_unix(
+    listener: tokio::net::UnixListener,
+    config: PubSubConfig,
+    subscription_control: SubscriptionControl,
+    mut tripwire: Tripwire,
+) {
+    let counter = TokenCounter::new("rpc_pubsub_unix_connections");
+    loop {
+        select! {
+            result = listener.accept() => match result {
+                Ok((socket, _)) => spawn_connection(
+                    socket,
+                    None,
+                    None,
+                    "unix socket".to_string(),
+                    subscription_control.clone(),
+                    config.clone(),
+                    tripwire.clone(),
+                    counter.create_token(),
+                ),
+                Err(e) => error!("couldn't accept unix socket connection: {:?}", e),
+            },
+            _ = &mut tripwire => return,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use {
@@ -360,7 +1278,7 @@ mod tests {
         },
         std::{
             net::{IpAddr, Ipv4Addr},
-            sync::{atomic::AtomicBool, RwLock},
+            sync::{atomic::AtomicBool, RwLock, Weak},
         },
     };
 
@@ -384,4 +1302,215 @@ mod tests {
         let thread = pubsub_service.thread_hdl.thread();
         assert_eq!(thread.name().unwrap(), "solana-pubsub");
     }
+
+    #[test]
+    fn test_handle_slow_consumer() {
+        let mut firehose_paused_until = None;
+        assert!(matches!(
+            handle_slow_consumer(
+                SlowConsumerPolicy::Disconnect,
+                10,
+                &mut firehose_paused_until
+            ),
+            Err(Error::SlowConsumer(10))
+        ));
+        assert!(handle_slow_consumer(
+            SlowConsumerPolicy::DropOldest,
+            10,
+            &mut firehose_paused_until
+        )
+        .is_ok());
+        assert!(firehose_paused_until.is_none());
+
+        assert!(handle_slow_consumer(
+            SlowConsumerPolicy::PauseNonCritical,
+            10,
+            &mut firehose_paused_until
+        )
+        .is_ok());
+        assert!(firehose_paused_until.unwrap() > time::Instant::now());
+
+        assert_eq!(
+            "pause-non-critical".parse::<SlowConsumerPolicy>(),
+            Ok(SlowConsumerPolicy::PauseNonCritical)
+        );
+        assert!("pause".parse::<SlowConsumerPolicy>().is_err());
+    }
+
+    #[test]
+    fn test_memory_budget_refusal() {
+        let refusal: serde_json::Value = serde_json::from_str(&memory_budget_refusal(
+            br#"{"jsonrpc":"2.0","id":7,"method":"slotSubscribe"}"#,
+        ))
+        .unwrap();
+        assert_eq!(refusal["id"], 7);
+        assert_eq!(
+            refusal["error"]["code"],
+            jsonrpc_core::ErrorCode::InternalError.code()
+        );
+
+        let refusal: serde_json::Value =
+            serde_json::from_str(&memory_budget_refusal(b"not json")).unwrap();
+        assert!(refusal["id"].is_null());
+    }
+
+    #[test]
+    fn test_hold_notifications_during_replay() {
+        let exit = Arc::new(AtomicBool::new(false));
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let optimistically_confirmed_bank =
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks);
+        let subscriptions = Arc::new(RpcSubscriptions::new_for_tests(
+            &exit,
+            bank_forks,
+            Arc::new(RwLock::new(BlockCommitmentCache::new_for_tests())),
+            optimistically_confirmed_bank,
+        ));
+        let (rpc, receiver) = test_connection(&subscriptions);
+        let id = rpc
+            .logs_subscribe(
+                solana_client::rpc_config::RpcTransactionLogsFilter::All,
+                None,
+            )
+            .unwrap();
+        let handler = &receiver.handler;
+        let notification_at = |slot: Slot| {
+            Arc::new(
+                json!({
+                    "jsonrpc": "2.0",
+                    "method": "logsNotification",
+                    "params": {"result": {"context": {"slot": slot}}, "subscription": id},
+                })
+                .to_string(),
+            )
+        };
+        let (json1, json3) = (notification_at(1), notification_at(3));
+        let live = |json: &Arc<String>, sequence: u64| RpcNotification {
+            subscription_id: id,
+            sequence,
+            is_final: false,
+            json: Arc::downgrade(json),
+            cbor: Weak::new(),
+            span_id: None,
+        };
+
+        assert!(handler
+            .handle_direct(DirectNotification::ReplayStarted(id))
+            .is_empty());
+        assert!(handler.handle(live(&json1, 1)).unwrap().is_empty());
+        assert!(handler.handle(live(&json3, 2)).unwrap().is_empty());
+        let replayed = handler.handle_direct(DirectNotification::Message {
+            subscription_id: id,
+            json: notification_at(1),
+        });
+        assert_eq!(replayed.len(), 1);
+
+        // Slot 1 was covered by the replay
+        let released = handler.handle_direct(DirectNotification::ReplayFinished {
+            subscription_id: id,
+            end_slot: Some(2),
+        });
+        assert_eq!(released, vec![json3.clone()]);
+        assert_eq!(handler.handle(live(&json3, 3)).unwrap(), vec![json3]);
+    }
+
+    #[test]
+    fn test_message_encoding() {
+        assert_eq!(
+            MessageEncoding::negotiate(vec!["graphql-ws", CBOR_SUBPROTOCOL].into_iter()),
+            MessageEncoding::Cbor
+        );
+        assert_eq!(
+            MessageEncoding::negotiate(vec!["graphql-ws"].into_iter()),
+            MessageEncoding::Json
+        );
+        assert_eq!(MessageEncoding::Json.protocol(), None);
+
+        let data = vec![1u8, 2, 3, 255];
+        let json = json!({
+            "jsonrpc": "2.0",
+            "method": "accountNotification",
+            "params": {
+                "result": {
+                    "context": { "slot": 5 },
+                    "value": {
+                        "data": [base64::encode(&data), "base64"],
+                        "lamports": 42,
+                        "owner": "11111111111111111111111111111111",
+                        "rentEpoch": -1,
+                    },
+                },
+                "subscription": 0,
+            },
+        })
+        .to_string();
+        let value: serde_cbor::Value = serde_cbor::from_slice(&json_to_cbor(&json)).unwrap();
+        let text = |s: &str| serde_cbor::Value::Text(s.to_string());
+        let get = |value: &serde_cbor::Value, key: &str| match value {
+            serde_cbor::Value::Map(map) => map[&text(key)].clone(),
+            _ => panic!("expected a map"),
+        };
+        let account = get(&get(&get(&value, "params"), "result"), "value");
+        // Strings are kept as they are, even if they look like encoded data
+        assert_eq!(
+            get(&account, "data"),
+            serde_cbor::Value::Array(vec![text(&base64::encode(&data)), text("base64")])
+        );
+        assert_eq!(get(&account, "lamports"), serde_cbor::Value::Integer(42));
+        assert_eq!(get(&account, "rentEpoch"), serde_cbor::Value::Integer(-1));
+        assert_eq!(get(&value, "method"), text("accountNotification"));
+    }
+
+    #[test]
+    fn test_multiplex_notification() {
+        // Results may contain fields named like the ones around them
+        let json = concat!(
+            r#"{"jsonrpc":"2.0","method":"accountNotification","#,
+            r#""params":{"result":{"a":1,"subscription":1},"subscription":7}}"#,
+        );
+        let result = notification_result(json).unwrap();
+        assert_eq!(result, r#"{"a":1,"subscription":1}"#);
+        let notification: serde_json::Value =
+            serde_json::from_str(&multiplex_notification(result, 9.into(), "a\"b")).unwrap();
+        assert_eq!(
+            notification,
+            json!({
+                "jsonrpc": "2.0",
+                "method": "multiplexNotification",
+                "params": {
+                    "result": { "a": 1, "subscription": 1 },
+                    "subscription": 9,
+                    "tag": "a\"b",
+                },
+            })
+        );
+        assert!(notification_result(r#"{"jsonrpc":"2.0"}"#).is_none());
+    }
+
+    #[test]
+    fn test_request_head() {
+        let request: &[u8] = b"GET /?a=b HTTP/1.1\r\nHost: localhost\r\n\
+            authorization:  Bearer key \r\n\r\n\x81\xff";
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let mut socket = request;
+            let head = read_request_head(&mut socket).await.unwrap();
+            assert_eq!(request_header(&head, "Authorization"), Some("Bearer key"));
+            assert_eq!(request_header(&head, "host"), Some("localhost"));
+            assert_eq!(request_header(&head, "origin"), None);
+
+            // Everything read is replayed before the rest of the stream
+            let mut replayed = Vec::new();
+            ReplayStream::new(head, socket)
+                .read_to_end(&mut replayed)
+                .await
+                .unwrap();
+            assert_eq!(replayed, request);
+
+            let mut incomplete: &[u8] = b"GET / HTTP/1.1\r\nHost: localhost\r\n";
+            assert!(read_request_head(&mut incomplete).await.is_err());
+        });
+    }
 }
@@ -0,0 +1,313 @@
+//! Rate limiting of JSON RPC requests by client IP address. Each method costs a number of
+//! tokens, taken from a bucket per address that refills at a steady rate; requests the
+//! bucket can't pay for are refused. IPv6 clients usually hold a whole /64, so they share a
+//! bucket per /64 prefix. Limits are read from a JSON file:
+//!
+//! ```json
+//! {
+//!   "tokensPerSecond": 100,
+//!   "burst": 1000,
+//!   "defaultCost": 1,
+//!   "methodCosts": { "getProgramAccounts": 100, "getBlock": 20 },
+//!   "exemptAddresses": ["127.0.0.1"]
+//! }
+//! ```
+
+use {
+    jsonrpc_core::{Call, Request},
+    std::{
+        collections::{HashMap, HashSet},
+        fs, io,
+        net::{IpAddr, Ipv6Addr},
+        path::Path,
+        sync::Mutex,
+        time::{Duration, Instant},
+    },
+};
+
+/// How often buckets that refilled completely are dropped
+const PURGE_INTERVAL: Duration = Duration::from_secs(60);
+/// Buckets kept at most. Once there are this many, new addresses share a single bucket until
+/// the purge makes room again, so that a client with many addresses can't grow the map.
+const MAX_BUCKETS: usize = 100_000;
+
+fn default_cost() -> u64 {
+    1
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct RateLimits {
+    /// Tokens added to the bucket of each address every second
+    pub tokens_per_second: u64,
+    /// Tokens a bucket holds at most, and so the cost of the requests an idle address may
+    /// send at once
+    pub burst: u64,
+    /// Cost of the methods missing from `method_costs`
+    #[serde(default = "default_cost")]
+    pub default_cost: u64,
+    #[serde(default)]
+    pub method_costs: HashMap<String, u64>,
+    /// Addresses that are never limited
+    #[serde(default)]
+    pub exempt_addresses: HashSet<IpAddr>,
+}
+
+impl RateLimits {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let invalid_data = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+        let limits: Self = serde_json::from_slice(&fs::read(path)?)
+            .map_err(|err| invalid_data(err.to_string()))?;
+        if limits.tokens_per_second == 0 || limits.burst == 0 {
+            return Err(invalid_data(format!(
+                "tokensPerSecond and burst must be positive in {:?}",
+                path
+            )));
+        }
+        Ok(limits)
+    }
+
+    fn method_cost(&self, method: &str) -> u64 {
+        self.method_costs
+            .get(method)
+            .copied()
+            .unwrap_or(self.default_cost)
+    }
+}
+
+struct TokenBucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+struct Buckets {
+    by_address: HashMap<IpAddr, TokenBucket>,
+    /// Shared by the addresses that don't fit in `by_address`
+    overflow: Option<TokenBucket>,
+    purged_at: Instant,
+}
+
+/// The address whose bucket `address` uses: IPv6 addresses are truncated to their /64
+/// prefix, and IPv4-mapped ones are IPv4 addresses
+fn bucket_address(address: IpAddr) -> IpAddr {
+    match address {
+        IpAddr::V4(_) => address,
+        IpAddr::V6(address) => match address.to_ipv4() {
+            Some(ipv4) if address.segments()[..6] == [0, 0, 0, 0, 0, 0xffff] => IpAddr::V4(ipv4),
+            _ => {
+                let segments = address.segments();
+                IpAddr::V6(Ipv6Addr::new(
+                    segments[0],
+                    segments[1],
+                    segments[2],
+                    segments[3],
+                    0,
+                    0,
+                    0,
+                    0,
+                ))
+            }
+        },
+    }
+}
+
+pub struct RateLimiter {
+    limits: RateLimits,
+    buckets: Mutex<Buckets>,
+}
+
+impl RateLimiter {
+    pub fn new(limits: RateLimits) -> Self {
+        Self {
+            limits,
+            buckets: Mutex::new(Buckets {
+                by_address: HashMap::new(),
+                overflow: None,
+                purged_at: Instant::now(),
+            }),
+        }
+    }
+
+    pub fn method_cost(&self, method: &str) -> u64 {
+        self.limits.method_cost(method)
+    }
+
+    /// Cost of a parsed request, the sum of the costs of the methods it calls. Invalid calls
+    /// cost the default, as handling them still takes work.
+    pub fn request_cost(&self, request: &Request) -> u64 {
+        let call_cost = |call: &Call| match call {
+            Call::MethodCall(call) => self.method_cost(&call.method),
+            Call::Notification(notification) => self.method_cost(&notification.method),
+            Call::Invalid { .. } => self.limits.default_cost,
+        };
+        match request {
+            Request::Single(call) => call_cost(call),
+            Request::Batch(calls) => calls.iter().map(call_cost).sum(),
+        }
+    }
+
+    /// Cost of a request that doesn't parse
+    pub fn invalid_request_cost(&self) -> u64 {
+        self.limits.default_cost
+    }
+
+    /// Takes `cost` tokens from the bucket of `address`, or returns how long until the bucket
+    /// holds enough of them. Requests costing more than a full bucket need a full bucket.
+    pub fn try_acquire(&self, address: IpAddr, cost: u64) -> Result<(), Duration> {
+        inc_new_counter_info!("rpc-request-cost", cost as usize);
+        self.acquire(address, cost, true)
+    }
+
+    /// Returns how long until the bucket of `address` holds a token again if it is empty,
+    /// without taking any. Lets requests be refused before they are read.
+    pub fn check(&self, address: IpAddr) -> Result<(), Duration> {
+        self.acquire(address, 1, false)
+    }
+
+    fn acquire(&self, address: IpAddr, cost: u64, take: bool) -> Result<(), Duration> {
+        if self.limits.exempt_addresses.contains(&address) {
+            return Ok(());
+        }
+        let address = bucket_address(address);
+        let cost = cost.min(self.limits.burst) as f64;
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+        if now.duration_since(buckets.purged_at) >= PURGE_INTERVAL {
+            self.purge(&mut buckets, now);
+        }
+        let new_bucket = || TokenBucket {
+            tokens: self.limits.burst as f64,
+            updated_at: now,
+        };
+        let buckets = &mut *buckets;
+        let bucket = if buckets.by_address.len() < MAX_BUCKETS
+            || buckets.by_address.contains_key(&address)
+        {
+            buckets.by_address.entry(address).or_insert_with(new_bucket)
+        } else {
+            inc_new_counter_info!("rpc-rate-limit-overflow", 1);
+            buckets.overflow.get_or_insert_with(new_bucket)
+        };
+        bucket.tokens = self.refilled_tokens(bucket, now);
+        bucket.updated_at = now;
+        if bucket.tokens < cost {
+            return Err(Duration::from_secs_f64(
+                (cost - bucket.tokens) / self.limits.tokens_per_second as f64,
+            ));
+        }
+        if take {
+            bucket.tokens -= cost;
+        }
+        Ok(())
+    }
+
+    fn refilled_tokens(&self, bucket: &TokenBucket, now: Instant) -> f64 {
+        let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+        let burst = self.limits.burst as f64;
+        (bucket.tokens + elapsed * self.limits.tokens_per_second as f64).min(burst)
+    }
+
+    /// Drops the buckets that are full again, which are the same as no bucket at all
+    fn purge(&self, buckets: &mut Buckets, now: Instant) {
+        let burst = self.limits.burst as f64;
+        buckets
+            .by_address
+            .retain(|_, bucket| self.refilled_tokens(bucket, now) < burst);
+        if buckets
+            .overflow
+            .as_ref()
+            .map(|bucket| self.refilled_tokens(bucket, now))
+            == Some(burst)
+        {
+            buckets.overflow = None;
+        }
+        buckets.purged_at = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::net::Ipv4Addr};
+
+    #[test]
+    fn test_rate_limiter() {
+        let path =
+            std::env::temp_dir().join(format!("test_rate_limits-{}.json", std::process::id()));
+        fs::write(
+            &path,
+            r#"{
+                "tokensPerSecond": 1,
+                "burst": 10,
+                "methodCosts": { "getProgramAccounts": 4, "getHealth": 0 },
+                "exemptAddresses": ["127.0.0.1"]
+            }"#,
+        )
+        .unwrap();
+        let limiter = RateLimiter::new(RateLimits::load(&path).unwrap());
+
+        let request_cost =
+            |request: &str| limiter.request_cost(&serde_json::from_str(request).unwrap());
+        assert_eq!(limiter.invalid_request_cost(), 1);
+        assert_eq!(
+            request_cost(r#"{"jsonrpc":"2.0","id":1,"method":"getProgramAccounts"}"#),
+            4
+        );
+        assert_eq!(
+            request_cost(
+                r#"[{"jsonrpc":"2.0","id":1,"method":"getProgramAccounts"},
+                    {"jsonrpc":"2.0","method":"getSlot"},
+                    {"jsonrpc":"2.0","id":2,"method":"getHealth"},
+                    {"id":3}]"#
+            ),
+            6
+        );
+
+        let address = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        assert!(limiter.try_acquire(address, 4).is_ok());
+        assert!(limiter.try_acquire(address, 4).is_ok());
+        let retry_after = limiter.try_acquire(address, 4).unwrap_err();
+        assert!(retry_after > Duration::from_millis(1_500));
+        assert!(retry_after <= Duration::from_secs(2));
+        assert!(limiter.check(address).is_ok());
+        assert!(limiter.try_acquire(address, 2).is_ok());
+        assert!(limiter.check(address).is_err());
+        // Other addresses have their own buckets, exempt ones none
+        assert!(limiter
+            .try_acquire(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2)), 100)
+            .is_ok());
+        for _ in 0..100 {
+            assert!(limiter
+                .try_acquire(IpAddr::V4(Ipv4Addr::LOCALHOST), 10)
+                .is_ok());
+        }
+
+        {
+            let mut buckets = limiter.buckets.lock().unwrap();
+            buckets.by_address.get_mut(&address).unwrap().updated_at -= Duration::from_secs(10);
+            limiter.purge(&mut buckets, Instant::now());
+            assert_eq!(buckets.by_address.len(), 1);
+        }
+        assert!(limiter.try_acquire(address, 10).is_ok());
+
+        // IPv6 addresses in the same /64 share a bucket
+        let ipv6 = |address: &str| address.parse::<IpAddr>().unwrap();
+        assert!(limiter.try_acquire(ipv6("2001:db8:1:2::1"), 10).is_ok());
+        assert!(limiter.try_acquire(ipv6("2001:db8:1:2::2"), 1).is_err());
+        assert!(limiter.try_acquire(ipv6("2001:db8:1:3::1"), 1).is_ok());
+        // IPv4-mapped addresses share the bucket of the IPv4 address
+        assert!(limiter.try_acquire(ipv6("::ffff:10.0.0.1"), 1).is_err());
+
+        fs::write(&path, r#"{"tokensPerSecond": 0, "burst": 10}"#).unwrap();
+        assert_eq!(
+            RateLimits::load(&path).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        fs::write(
+            &path,
+            r#"{"tokensPerSecond": 1, "burst": 10, "unknown": 1}"#,
+        )
+        .unwrap();
+        assert!(RateLimits::load(&path).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+}
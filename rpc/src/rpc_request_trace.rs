@@ -0,0 +1,235 @@
+//! Tracing of JSON RPC calls. Each call gets an id, and the time it spends fetching banks,
+//! scanning accounts and serializing its result is recorded as it runs. Calls that take
+//! longer than `JsonRpcConfig::slow_request_threshold` are logged and reported as
+//! datapoints; the others are logged at debug level.
+
+use {
+    crate::rpc::JsonRpcRequestProcessor,
+    jsonrpc_core::{
+        futures::{
+            future::{Either, FutureExt},
+            Future,
+        },
+        middleware, BoxFuture, Call, Middleware, Output, Params,
+    },
+    std::{
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc, Mutex,
+        },
+        time::{Duration, Instant},
+    },
+};
+
+/// Params are truncated to this many bytes in the log
+const MAX_PARAMS_SUMMARY_LEN: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TracePhase {
+    /// Picking the bank for the requested commitment
+    Bank,
+    /// Scanning accounts, by program or by secondary index
+    Scan,
+    /// Encoding accounts, blocks and transactions into their response form
+    Serialize,
+}
+
+const TRACE_PHASES: usize = 3;
+
+pub struct RequestTrace {
+    id: u64,
+    /// Time spent in each phase, by `TracePhase` index
+    phases: Mutex<[Duration; TRACE_PHASES]>,
+}
+
+impl RequestTrace {
+    fn new(id: u64) -> Self {
+        Self {
+            id,
+            phases: Mutex::default(),
+        }
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Runs `f`, counting the time it takes towards `phase`
+    pub fn time<T>(&self, phase: TracePhase, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        self.phases.lock().unwrap()[phase as usize] += start.elapsed();
+        result
+    }
+
+    pub fn phase(&self, phase: TracePhase) -> Duration {
+        self.phases.lock().unwrap()[phase as usize]
+    }
+
+    fn finish(
+        &self,
+        method: &str,
+        params: &str,
+        total: Duration,
+        slow_request_threshold: Option<Duration>,
+    ) {
+        let [bank, scan, serialize] = *self.phases.lock().unwrap();
+        let is_slow = slow_request_threshold
+            .map(|threshold| total >= threshold)
+            .unwrap_or(false);
+        if !is_slow {
+            debug!(
+                "rpc request {} {} {}: {:?} total, {:?} bank, {:?} scan, {:?} serialize",
+                self.id, method, params, total, bank, scan, serialize
+            );
+            return;
+        }
+        warn!(
+            "slow rpc request {} {} {}: {:?} total, {:?} bank, {:?} scan, {:?} serialize",
+            self.id, method, params, total, bank, scan, serialize
+        );
+        datapoint_warn!(
+            "rpc-slow-request",
+            ("id", self.id, i64),
+            ("method", method.to_string(), String),
+            ("total_us", total.as_micros() as i64, i64),
+            ("bank_us", bank.as_micros() as i64, i64),
+            ("scan_us", scan.as_micros() as i64, i64),
+            ("serialize_us", serialize.as_micros() as i64, i64),
+        );
+    }
+}
+
+/// Params of a call, as JSON truncated to `MAX_PARAMS_SUMMARY_LEN`
+fn summarize_params(params: &Params) -> String {
+    let mut summary = serde_json::to_string(params).unwrap_or_default();
+    if summary.len() > MAX_PARAMS_SUMMARY_LEN {
+        let mut end = MAX_PARAMS_SUMMARY_LEN;
+        while !summary.is_char_boundary(end) {
+            end -= 1;
+        }
+        summary.truncate(end);
+        summary.push_str("...");
+    }
+    summary
+}
+
+/// Traces every method call of the JSON RPC service
+#[derive(Default)]
+pub struct RequestTracer {
+    slow_request_threshold: Option<Duration>,
+    next_id: AtomicU64,
+}
+
+impl RequestTracer {
+    pub fn new(slow_request_threshold: Option<Duration>) -> Self {
+        Self {
+            slow_request_threshold,
+            next_id: AtomicU64::default(),
+        }
+    }
+}
+
+impl Middleware<JsonRpcRequestProcessor> for RequestTracer {
+    type Future = middleware::NoopFuture;
+    type CallFuture = BoxFuture<Option<Output>>;
+
+    fn on_call<F, X>(
+        &self,
+        call: Call,
+        mut meta: JsonRpcRequestProcessor,
+        next: F,
+    ) -> Either<Self::CallFuture, X>
+    where
+        F: Fn(Call, JsonRpcRequestProcessor) -> X + Send + Sync,
+        X: Future<Output = Option<Output>> + Send + 'static,
+    {
+        // Skip the work when nothing would be logged
+        if self.slow_request_threshold.is_none() && !log_enabled!(log::Level::Debug) {
+            return Either::Right(next(call, meta));
+        }
+        let (method, params) = match &call {
+            Call::MethodCall(method_call) => (
+                method_call.method.clone(),
+                summarize_params(&method_call.params),
+            ),
+            _ => return Either::Right(next(call, meta)),
+        };
+        let trace = Arc::new(RequestTrace::new(
+            self.next_id.fetch_add(1, Ordering::Relaxed),
+        ));
+        meta.request_trace = Some(Arc::clone(&trace));
+        let slow_request_threshold = self.slow_request_threshold;
+        let start = Instant::now();
+        Either::Left(Box::pin(next(call, meta).map(move |output| {
+            trace.finish(&method, &params, start.elapsed(), slow_request_threshold);
+            output
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        jsonrpc_core::{futures::future, MetaIoHandler, Value},
+        solana_runtime::{
+            bank::Bank,
+            genesis_utils::{create_genesis_config, GenesisConfigInfo},
+        },
+        solana_streamer::socket::SocketAddrSpace,
+    };
+
+    #[test]
+    fn test_request_trace() {
+        let trace = RequestTrace::new(7);
+        assert_eq!(trace.id(), 7);
+        assert_eq!(
+            trace.time(TracePhase::Scan, || {
+                std::thread::sleep(Duration::from_millis(2));
+                42
+            }),
+            42
+        );
+        assert!(trace.phase(TracePhase::Scan) >= Duration::from_millis(2));
+        assert_eq!(trace.phase(TracePhase::Bank), Duration::default());
+
+        assert_eq!(summarize_params(&Params::None), "null");
+        assert_eq!(
+            summarize_params(&Params::Array(vec![Value::from(1), Value::from("a")])),
+            r#"[1,"a"]"#
+        );
+        let summary = summarize_params(&Params::Array(vec![Value::from("é".repeat(200))]));
+        assert!(summary.len() <= MAX_PARAMS_SUMMARY_LEN + 3);
+        assert!(summary.ends_with("..."));
+    }
+
+    #[test]
+    fn test_request_tracer() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+        let request_processor =
+            JsonRpcRequestProcessor::new_from_bank(&bank, SocketAddrSpace::Unspecified);
+        let mut io = MetaIoHandler::with_middleware(RequestTracer::new(Some(Duration::default())));
+        io.add_method_with_meta("getTraceId", |_params, meta: JsonRpcRequestProcessor| {
+            future::ready(Ok(meta
+                .request_trace
+                .map(|trace| Value::from(trace.id()))
+                .unwrap_or(Value::Null)))
+        });
+        let request = r#"[
+            {"jsonrpc":"2.0","id":1,"method":"getTraceId"},
+            {"jsonrpc":"2.0","id":2,"method":"getTraceId"}
+        ]"#;
+        let response = io.handle_request_sync(request, request_processor).unwrap();
+        let response: Value = serde_json::from_str(&response).unwrap();
+        let mut ids: Vec<_> = response
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|output| output["result"].as_u64().unwrap())
+            .collect();
+        ids.sort_unstable();
+        assert_eq!(ids, vec![0, 1]);
+    }
+}
@@ -10,20 +10,29 @@ use {
             rpc_full::*, rpc_minimal::*, rpc_obsolete_v1_7::*, *,
         },
         rpc_health::*,
+        rpc_rate_limit::{RateLimiter, RateLimits},
+        rpc_request_trace::RequestTracer,
+        rpc_subscriptions::RpcSubscriptions,
+    },
+    jsonrpc_core::{
+        futures::{prelude::*, stream},
+        MetaIoHandler,
     },
-    jsonrpc_core::{futures::prelude::*, MetaIoHandler},
     jsonrpc_http_server::{
         hyper, AccessControlAllowOrigin, CloseHandle, DomainsValidation, RequestMiddleware,
         RequestMiddlewareAction, ServerBuilder,
     },
     regex::Regex,
-    solana_client::rpc_cache::LargestAccountsCache,
+    solana_client::{
+        rpc_cache::LargestAccountsCache, rpc_config::RpcGetProgramAccountsConfig,
+        rpc_custom_error::JSON_RPC_SERVER_ERROR_SCAN_QUEUE_FULL,
+    },
     solana_gossip::cluster_info::ClusterInfo,
     solana_ledger::{
         bigtable_upload_service::BigTableUploadService, blockstore::Blockstore,
         leader_schedule_cache::LeaderScheduleCache,
     },
-    solana_metrics::inc_new_counter_info,
+    solana_metrics::{inc_new_counter_info, TokenCounter},
     solana_poh::poh_recorder::PohRecorder,
     solana_runtime::{
         bank_forks::BankForks, commitment::BlockCommitmentCache,
@@ -35,26 +44,70 @@ use {
         native_token::lamports_to_sol, pubkey::Pubkey,
     },
     solana_send_transaction_service::send_transaction_service::{self, SendTransactionService},
+    solana_storage_bigtable::{LedgerArchive, ObjectStoreLedgerStorage},
     std::{
         collections::HashSet,
-        net::SocketAddr,
+        io::Write,
+        net::{IpAddr, SocketAddr},
         path::{Path, PathBuf},
         sync::atomic::{AtomicBool, AtomicU64, Ordering},
         sync::{mpsc::channel, Arc, Mutex, RwLock},
         thread::{self, Builder, JoinHandle},
+        time::{Duration, Instant},
+    },
+    tokio::{
+        io::{AsyncRead, AsyncWrite},
+        select,
+        sync::{Notify, Semaphore},
+        time,
     },
+    tokio_rustls::TlsAcceptor,
     tokio_util::codec::{BytesCodec, FramedRead},
 };
 
 const LARGEST_ACCOUNTS_CACHE_DURATION: u64 = 60 * 60 * 2;
+/// How often the `getLargestAccounts` refresh checks whether the service is exiting
+const LARGEST_ACCOUNTS_REFRESH_EXIT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Streams the result of a `getProgramAccounts` request as newline-delimited JSON
+const PROGRAM_ACCOUNTS_STREAM_PATH: &str = "/v0/program-accounts";
+const PROGRAM_ACCOUNTS_STREAM_CHUNK_SIZE: usize = 1_000;
+
+/// Connections served outside of `jsonrpc_http_server` at once. Further connections wait in
+/// the listen backlog until one closes.
+const MAX_TCP_CONNECTIONS: usize = 10_000;
+/// TLS handshakes that don't complete in time are dropped, so that they don't hold one of
+/// the `MAX_TCP_CONNECTIONS`
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+/// Responses compressed at once. Compression blocks a thread, so one-off responses are sent
+/// uncompressed rather than waiting, and streams wait for their turn between chunks.
+const MAX_CONCURRENT_COMPRESSIONS: usize = 8;
 
 pub struct JsonRpcService {
     thread_hdl: JoinHandle<()>,
+    largest_accounts_refresh_hdl: Option<JoinHandle<()>>,
 
     #[cfg(test)]
     pub request_processor: JsonRpcRequestProcessor, // Used only by test_rpc_new()...
 
-    close_handle: Option<CloseHandle>,
+    close_handle: Option<ServerCloseHandle>,
+}
+
+/// Closes the JSON RPC server, whether it is served by `jsonrpc_http_server` or, with TLS
+/// or response compression, by `serve_tcp`
+#[derive(Clone)]
+enum ServerCloseHandle {
+    Http(CloseHandle),
+    Tcp(Arc<Notify>),
+}
+
+impl ServerCloseHandle {
+    fn close(self) {
+        match self {
+            Self::Http(close_handle) => close_handle.close(),
+            Self::Tcp(close) => close.notify_one(),
+        }
+    }
 }
 
 struct RpcRequestMiddleware {
@@ -64,6 +117,8 @@ struct RpcRequestMiddleware {
     snapshot_config: Option<SnapshotConfig>,
     bank_forks: Arc<RwLock<BankForks>>,
     health: Arc<RpcHealth>,
+    /// Serves `PROGRAM_ACCOUNTS_STREAM_PATH` if set
+    request_processor: Option<JsonRpcRequestProcessor>,
 }
 
 impl RpcRequestMiddleware {
@@ -86,9 +141,19 @@ impl RpcRequestMiddleware {
             snapshot_config,
             bank_forks,
             health,
+            request_processor: None,
         }
     }
 
+    /// Serve streamed `getProgramAccounts` requests with `request_processor`
+    pub fn with_program_accounts_stream(
+        mut self,
+        request_processor: JsonRpcRequestProcessor,
+    ) -> Self {
+        self.request_processor = Some(request_processor);
+        self
+    }
+
     fn redirect(location: &str) -> hyper::Response<hyper::Body> {
         hyper::Response::builder()
             .status(hyper::StatusCode::SEE_OTHER)
@@ -197,6 +262,119 @@ impl RpcRequestMiddleware {
         }
     }
 
+    fn process_program_accounts_stream(
+        request_processor: JsonRpcRequestProcessor,
+        request: hyper::Request<hyper::Body>,
+    ) -> RequestMiddlewareAction {
+        inc_new_counter_info!("rpc-program_accounts_stream", 1);
+        let status_response = |status| {
+            hyper::Response::builder()
+                .status(status)
+                .header(hyper::header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+                .body(hyper::Body::empty())
+                .unwrap()
+        };
+        let error_response = |error: jsonrpc_core::Error| {
+            let status = if error.code
+                == jsonrpc_core::ErrorCode::ServerError(JSON_RPC_SERVER_ERROR_SCAN_QUEUE_FULL)
+            {
+                hyper::StatusCode::SERVICE_UNAVAILABLE
+            } else {
+                hyper::StatusCode::BAD_REQUEST
+            };
+            hyper::Response::builder()
+                .status(status)
+                .header(hyper::header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+                .header(hyper::header::CONTENT_TYPE, "application/json")
+                .body(hyper::Body::from(
+                    serde_json::json!({ "error": error }).to_string(),
+                ))
+                .unwrap()
+        };
+        RequestMiddlewareAction::Respond {
+            should_validate_hosts: true,
+            response: Box::pin(async move {
+                if request.method() != hyper::Method::POST {
+                    return Ok(status_response(hyper::StatusCode::METHOD_NOT_ALLOWED));
+                }
+                let body = match read_body(request.into_body(), MAX_REQUEST_PAYLOAD_SIZE).await? {
+                    Some(body) => body,
+                    None => return Ok(status_response(hyper::StatusCode::PAYLOAD_TOO_LARGE)),
+                };
+                // The body holds the params of the request: `[programId, config]`
+                let params = match serde_json::from_slice::<Vec<serde_json::Value>>(&body) {
+                    Ok(mut params) if (1..=2).contains(&params.len()) => {
+                        let config = params.get(1).cloned().unwrap_or_default();
+                        let program_id = params.swap_remove(0);
+                        serde_json::from_value::<String>(program_id)
+                            .and_then(|program_id| {
+                                let config = serde_json::from_value::<
+                                    Option<RpcGetProgramAccountsConfig>,
+                                >(config)?;
+                                Ok((program_id, config))
+                            })
+                            .map_err(|err| jsonrpc_core::Error::invalid_params(err.to_string()))
+                    }
+                    _ => Err(jsonrpc_core::Error::parse_error()),
+                };
+                let (program_id, config) = match params {
+                    Ok(params) => params,
+                    Err(error) => return Ok(error_response(error)),
+                };
+
+                // Scan up front, so that errors can be reported with the status. Only the
+                // scan runs on the scan pool; the accounts are encoded as the client consumes
+                // them, so that slow clients don't hold up the scan threads.
+                let scanned = request_processor.spawn_scan(move |request_processor| {
+                    request_processor.get_program_accounts_chunks(
+                        &program_id,
+                        config,
+                        PROGRAM_ACCOUNTS_STREAM_CHUNK_SIZE,
+                    )
+                });
+                let chunks = match scanned {
+                    Ok(scanned) => match scanned.await {
+                        Ok(Ok(chunks)) => chunks,
+                        Ok(Err(error)) => return Ok(error_response(error)),
+                        Err(_) => return Ok(Self::internal_server_error()),
+                    },
+                    Err(error) => return Ok(error_response(error)),
+                };
+                let stream = stream::unfold(Some(chunks), |chunks| async move {
+                    let mut chunks = chunks?;
+                    let (chunks, chunk) = tokio::task::spawn_blocking(move || {
+                        let chunk = chunks.next().map(|chunk| {
+                            chunk
+                                .map(|keyed_accounts| {
+                                    let mut bytes = vec![];
+                                    for keyed_account in keyed_accounts {
+                                        serde_json::to_writer(&mut bytes, &keyed_account).unwrap();
+                                        bytes.push(b'\n');
+                                    }
+                                    bytes
+                                })
+                                .map_err(|error| {
+                                    std::io::Error::new(std::io::ErrorKind::Other, error.message)
+                                })
+                        });
+                        (chunks, chunk)
+                    })
+                    .await
+                    .ok()?;
+                    let chunk = chunk?;
+                    let failed = chunk.is_err();
+                    Some((chunk, (!failed).then(|| chunks)))
+                });
+                Ok(hyper::Response::builder()
+                    .status(hyper::StatusCode::OK)
+                    .header(hyper::header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+                    .header(hyper::header::CONTENT_TYPE, "application/x-ndjson")
+                    .body(hyper::Body::wrap_stream(stream))
+                    .unwrap())
+            }),
+        }
+    }
+
     fn health_check(&self) -> &'static str {
         let response = match self.health.check() {
             RpcHealthStatus::Ok => "ok",
@@ -206,6 +384,17 @@ impl RpcRequestMiddleware {
         info!("health check: {}", response);
         response
     }
+
+    /// The report of `getHealth` with `extended` set, as JSON
+    fn health_report(&self) -> String {
+        let snapshot_archives_dir = self
+            .snapshot_config
+            .as_ref()
+            .map(|snapshot_config| snapshot_config.snapshot_archives_dir.as_path());
+        let root = self.bank_forks.read().unwrap().root();
+        let report = self.health.report(snapshot_archives_dir, root);
+        serde_json::to_string(&report).unwrap()
+    }
 }
 
 impl RequestMiddleware for RpcRequestMiddleware {
@@ -244,11 +433,30 @@ impl RequestMiddleware for RpcRequestMiddleware {
         } else if self.is_file_get_path(request.uri().path()) {
             self.process_file_get(request.uri().path())
         } else if request.uri().path() == "/health" {
-            hyper::Response::builder()
-                .status(hyper::StatusCode::OK)
-                .body(hyper::Body::from(self.health_check()))
-                .unwrap()
-                .into()
+            let extended = request.uri().query().map_or(false, |query| {
+                query.split('&').any(|param| param == "extended=true")
+            });
+            if extended {
+                hyper::Response::builder()
+                    .status(hyper::StatusCode::OK)
+                    .header(hyper::header::CONTENT_TYPE, "application/json")
+                    .body(hyper::Body::from(self.health_report()))
+                    .unwrap()
+                    .into()
+            } else {
+                hyper::Response::builder()
+                    .status(hyper::StatusCode::OK)
+                    .body(hyper::Body::from(self.health_check()))
+                    .unwrap()
+                    .into()
+            }
+        } else if request.uri().path() == PROGRAM_ACCOUNTS_STREAM_PATH {
+            match &self.request_processor {
+                Some(request_processor) => {
+                    Self::process_program_accounts_stream(request_processor.clone(), request)
+                }
+                None => RpcRequestMiddleware::not_found().into(),
+            }
         } else {
             request.into()
         }
@@ -280,6 +488,532 @@ fn process_rest(bank_forks: &Arc<RwLock<BankForks>>, path: &str) -> Option<Strin
     }
 }
 
+/// Binds a unix socket at `path`, replacing a socket left behind by a previous run
+#[cfg(unix)]
+pub(crate) fn bind_unix_listener(path: &Path) -> std::io::Result<tokio::net::UnixListener> {
+    use std::os::unix::fs::FileTypeExt;
+
+    if let Ok(metadata) = std::fs::symlink_metadata(path) {
+        if metadata.file_type().is_socket() {
+            std::fs::remove_file(path)?;
+        }
+    }
+    tokio::net::UnixListener::bind(path)
+}
+
+/// Reads a request body, or returns `None` without reading the rest of it once it turns out
+/// to be larger than `limit` bytes
+async fn read_body(mut body: hyper::Body, limit: usize) -> Result<Option<Vec<u8>>, hyper::Error> {
+    use hyper::body::HttpBody;
+
+    if body.size_hint().lower() > limit as u64 {
+        return Ok(None);
+    }
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk?;
+        if bytes.len() + chunk.len() > limit {
+            return Ok(None);
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(Some(bytes))
+}
+
+/// What the connections served outside of `jsonrpc_http_server` share
+#[derive(Clone)]
+struct HttpService {
+    io: Arc<MetaIoHandler<JsonRpcRequestProcessor, RequestTracer>>,
+    request_processor: JsonRpcRequestProcessor,
+    /// Serves the paths other than JSON RPC, if set
+    request_middleware: Option<Arc<RpcRequestMiddleware>>,
+    /// Compress responses of at least this many bytes
+    compression_min_size: Option<usize>,
+    /// Limits the compression work in flight to `MAX_CONCURRENT_COMPRESSIONS`
+    compressions: Arc<Semaphore>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+}
+
+impl HttpService {
+    /// Returns the response refusing a request from `peer_address` if `acquire` refuses it.
+    /// Requests without an address aren't limited.
+    fn refuse_over_rate_limit(
+        &self,
+        peer_address: Option<IpAddr>,
+        acquire: impl FnOnce(&RateLimiter, IpAddr) -> Result<(), Duration>,
+    ) -> Option<hyper::Response<hyper::Body>> {
+        let (rate_limiter, peer_address) = (self.rate_limiter.as_ref()?, peer_address?);
+        let retry_after = acquire(rate_limiter, peer_address).err()?;
+        inc_new_counter_info!("rpc-rate-limited-requests", 1);
+        Some(
+            hyper::Response::builder()
+                .status(hyper::StatusCode::TOO_MANY_REQUESTS)
+                .header(
+                    hyper::header::RETRY_AFTER,
+                    retry_after.as_secs_f64().ceil().max(1.0) as u64,
+                )
+                .body(hyper::Body::empty())
+                .unwrap(),
+        )
+    }
+}
+
+/// Serves JSON RPC over HTTP, or HTTPS with a `tls_acceptor`, with the same paths as the
+/// `jsonrpc_http_server` server. At most `MAX_TCP_CONNECTIONS` are served at once.
+async fn serve_tcp(
+    listener: tokio::net::TcpListener,
+    tls_acceptor: Option<TlsAcceptor>,
+    service: HttpService,
+    close: Arc<Notify>,
+) {
+    let counter = TokenCounter::new(if tls_acceptor.is_some() {
+        "rpc_tls_connections"
+    } else {
+        "rpc_tcp_connections"
+    });
+    let connections = Arc::new(Semaphore::new(MAX_TCP_CONNECTIONS));
+    loop {
+        let permit = select! {
+            permit = Arc::clone(&connections).acquire_owned() => {
+                permit.expect("connection semaphore is never closed")
+            }
+            _ = close.notified() => return,
+        };
+        let (stream, peer_address) = select! {
+            result = listener.accept() => match result {
+                Ok(accepted) => accepted,
+                Err(err) => {
+                    warn!("couldn't accept JSON RPC connection: {:?}", err);
+                    continue;
+                }
+            },
+            _ = close.notified() => return,
+        };
+        let tls_acceptor = tls_acceptor.clone();
+        let service = service.clone();
+        let peer_address = Some(peer_address.ip());
+        let counter_token = counter.create_token();
+        tokio::spawn(async move {
+            match tls_acceptor {
+                Some(tls_acceptor) => {
+                    match time::timeout(TLS_HANDSHAKE_TIMEOUT, tls_acceptor.accept(stream)).await {
+                        Ok(Ok(stream)) => {
+                            serve_http_connection(stream, service, peer_address).await
+                        }
+                        Ok(Err(err)) => debug!("JSON RPC TLS handshake error: {}", err),
+                        Err(_) => debug!("JSON RPC TLS handshake timed out"),
+                    }
+                }
+                None => serve_http_connection(stream, service, peer_address).await,
+            }
+            drop(permit);
+            drop(counter_token); // Force moving token into the task.
+        });
+    }
+}
+
+/// Serves JSON RPC over HTTP on a unix socket. Only JSON RPC requests are handled, the
+/// health check, REST and file download paths are served over TCP. Requests on the socket
+/// are not rate limited.
+#[cfg(unix)]
+async fn serve_unix_socket(listener: tokio::net::UnixListener, service: HttpService) {
+    let counter = TokenCounter::new("rpc_unix_connections");
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(err) => {
+                warn!("couldn't accept JSON RPC unix socket connection: {:?}", err);
+                continue;
+            }
+        };
+        let service = service.clone();
+        let counter_token = counter.create_token();
+        tokio::spawn(async move {
+            serve_http_connection(stream, service, None).await;
+            drop(counter_token); // Force moving token into the task.
+        });
+    }
+}
+
+/// Serves a connection accepted outside of `jsonrpc_http_server`
+async fn serve_http_connection<S: AsyncRead + AsyncWrite + Unpin + Send + 'static>(
+    stream: S,
+    service: HttpService,
+    peer_address: Option<IpAddr>,
+) {
+    let service = hyper::service::service_fn(move |request| {
+        handle_http_request(request, service.clone(), peer_address)
+    });
+    if let Err(err) = hyper::server::conn::Http::new()
+        .serve_connection(stream, service)
+        .await
+    {
+        debug!("JSON RPC connection error: {}", err);
+    }
+}
+
+/// Answers a CORS preflight request the way `jsonrpc_http_server` does, with the CORS
+/// settings of the JSON RPC service: any origin, and any requested headers
+fn cors_preflight_response(request: &hyper::Request<hyper::Body>) -> hyper::Response<hyper::Body> {
+    let mut response = hyper::Response::new(hyper::Body::empty());
+    let headers = response.headers_mut();
+    headers.insert(
+        hyper::header::ACCESS_CONTROL_ALLOW_METHODS,
+        hyper::header::HeaderValue::from_static("OPTIONS, POST"),
+    );
+    let allow_headers = request
+        .headers()
+        .get(hyper::header::ACCESS_CONTROL_REQUEST_HEADERS)
+        .cloned()
+        .unwrap_or_else(|| {
+            hyper::header::HeaderValue::from_static("Origin, Content-Type, Accept, Content-Length")
+        });
+    headers.insert(hyper::header::ACCESS_CONTROL_ALLOW_HEADERS, allow_headers);
+    headers.insert(
+        hyper::header::ACCESS_CONTROL_MAX_AGE,
+        hyper::header::HeaderValue::from_static("86400"),
+    );
+    response
+}
+
+async fn handle_http_request(
+    request: hyper::Request<hyper::Body>,
+    service: HttpService,
+    peer_address: Option<IpAddr>,
+) -> Result<hyper::Response<hyper::Body>, hyper::Error> {
+    let content_encoding = service.compression_min_size.map(|min_size| {
+        let content_encoding = request
+            .headers()
+            .get(hyper::header::ACCEPT_ENCODING)
+            .and_then(|accept_encoding| accept_encoding.to_str().ok())
+            .and_then(ContentEncoding::negotiate);
+        (content_encoding, min_size)
+    });
+    let is_stream = request.uri().path() == PROGRAM_ACCOUNTS_STREAM_PATH;
+    let compressions = Arc::clone(&service.compressions);
+    let mut response = route_http_request(request, service, peer_address).await?;
+    if let Some((content_encoding, min_size)) = content_encoding {
+        response = compress_response(
+            response,
+            content_encoding,
+            min_size,
+            is_stream,
+            compressions,
+        )
+        .await?;
+    }
+    response
+        .headers_mut()
+        .entry(hyper::header::ACCESS_CONTROL_ALLOW_ORIGIN)
+        .or_insert(hyper::header::HeaderValue::from_static("*"));
+    Ok(response)
+}
+
+/// Compresses successful JSON RPC responses of at least `min_size` bytes, and program
+/// accounts streams, with `content_encoding`
+async fn compress_response(
+    mut response: hyper::Response<hyper::Body>,
+    content_encoding: Option<ContentEncoding>,
+    min_size: usize,
+    is_stream: bool,
+    compressions: Arc<Semaphore>,
+) -> Result<hyper::Response<hyper::Body>, hyper::Error> {
+    let is_json = response.headers().get(hyper::header::CONTENT_TYPE)
+        == Some(&hyper::header::HeaderValue::from_static("application/json"));
+    if response.status() != hyper::StatusCode::OK || !(is_json || is_stream) {
+        return Ok(response);
+    }
+    response.headers_mut().insert(
+        hyper::header::VARY,
+        hyper::header::HeaderValue::from_static("accept-encoding"),
+    );
+    let content_encoding = match content_encoding {
+        Some(content_encoding) => content_encoding,
+        None => return Ok(response),
+    };
+    let (mut parts, body) = response.into_parts();
+    let content_encoding_header =
+        hyper::header::HeaderValue::from_static(content_encoding.as_str());
+    if is_stream {
+        inc_new_counter_info!("rpc-compressed-streams", 1);
+        parts
+            .headers
+            .insert(hyper::header::CONTENT_ENCODING, content_encoding_header);
+        let body = compress_stream(body, content_encoding, compressions);
+        return Ok(hyper::Response::from_parts(parts, body));
+    }
+
+    let body = hyper::body::to_bytes(body).await?;
+    if body.len() < min_size {
+        return Ok(hyper::Response::from_parts(parts, hyper::Body::from(body)));
+    }
+    let permit = match compressions.try_acquire_owned() {
+        Ok(permit) => permit,
+        Err(_) => {
+            inc_new_counter_info!("rpc-compression-skipped", 1);
+            return Ok(hyper::Response::from_parts(parts, hyper::Body::from(body)));
+        }
+    };
+    let (body, compressed) = tokio::task::spawn_blocking(move || {
+        let compressed = content_encoding.compress(&body);
+        drop(permit);
+        (body, compressed)
+    })
+    .await
+    .expect("compression task");
+    let body = match compressed {
+        Ok(compressed) => {
+            inc_new_counter_info!("rpc-compressed-responses", 1);
+            parts
+                .headers
+                .insert(hyper::header::CONTENT_ENCODING, content_encoding_header);
+            parts.headers.remove(hyper::header::CONTENT_LENGTH);
+            hyper::Body::from(compressed)
+        }
+        Err(err) => {
+            warn!("couldn't compress JSON RPC response: {}", err);
+            hyper::Body::from(body)
+        }
+    };
+    Ok(hyper::Response::from_parts(parts, body))
+}
+
+/// Compresses the chunks of a streamed response as they come, flushing the encoder after
+/// each of them so that the client can decode everything it received so far
+fn compress_stream(
+    body: hyper::Body,
+    content_encoding: ContentEncoding,
+    compressions: Arc<Semaphore>,
+) -> hyper::Body {
+    let state = Some((body, StreamEncoder::new(content_encoding)));
+    let stream = stream::unfold(state, move |state| {
+        let compressions = Arc::clone(&compressions);
+        async move {
+            let (mut body, encoder) = state?;
+            let mut encoder = match encoder {
+                Ok(encoder) => encoder,
+                Err(err) => return Some((Err(err), None)),
+            };
+            let chunk = match body.next().await {
+                Some(Ok(chunk)) => Some(chunk),
+                Some(Err(err)) => {
+                    let err = std::io::Error::new(std::io::ErrorKind::Other, err);
+                    return Some((Err(err), None));
+                }
+                None => None,
+            };
+            let permit = compressions
+                .acquire_owned()
+                .await
+                .expect("compression semaphore is never closed");
+            let (encoder, encoded) = tokio::task::spawn_blocking(move || {
+                let result = match chunk {
+                    Some(chunk) => {
+                        let encoded = encoder.encode(&chunk);
+                        (Some(encoder), encoded)
+                    }
+                    None => (None, encoder.finish()),
+                };
+                drop(permit);
+                result
+            })
+            .await
+            .expect("compression task");
+            let state = match (&encoded, encoder) {
+                (Ok(_), Some(encoder)) => Some((body, Ok(encoder))),
+                _ => None,
+            };
+            Some((encoded, state))
+        }
+    });
+    hyper::Body::wrap_stream(stream)
+}
+
+async fn route_http_request(
+    request: hyper::Request<hyper::Body>,
+    service: HttpService,
+    peer_address: Option<IpAddr>,
+) -> Result<hyper::Response<hyper::Body>, hyper::Error> {
+    if request.uri().path() == PROGRAM_ACCOUNTS_STREAM_PATH {
+        if let Some(response) =
+            service.refuse_over_rate_limit(peer_address, |rate_limiter, peer_address| {
+                let cost = rate_limiter.method_cost("getProgramAccounts");
+                rate_limiter.try_acquire(peer_address, cost)
+            })
+        {
+            return Ok(response);
+        }
+    }
+    let request = match &service.request_middleware {
+        Some(request_middleware) => match request_middleware.on_request(request) {
+            RequestMiddlewareAction::Proceed { request, .. } => request,
+            RequestMiddlewareAction::Respond { response, .. } => return response.await,
+        },
+        None => request,
+    };
+    let status_response = |status| {
+        hyper::Response::builder()
+            .status(status)
+            .body(hyper::Body::empty())
+            .unwrap()
+    };
+    if request.method() == hyper::Method::OPTIONS {
+        return Ok(cors_preflight_response(&request));
+    }
+    if request.method() != hyper::Method::POST {
+        return Ok(status_response(hyper::StatusCode::METHOD_NOT_ALLOWED));
+    }
+    // Clients out of tokens are refused before their request is read
+    if let Some(response) = service.refuse_over_rate_limit(peer_address, RateLimiter::check) {
+        return Ok(response);
+    }
+    let body = match read_body(request.into_body(), MAX_REQUEST_PAYLOAD_SIZE).await? {
+        Some(body) => body,
+        None => return Ok(status_response(hyper::StatusCode::PAYLOAD_TOO_LARGE)),
+    };
+    let body = match std::str::from_utf8(&body) {
+        Ok(body) => body,
+        Err(_) => return Ok(status_response(hyper::StatusCode::BAD_REQUEST)),
+    };
+    // Parsed here rather than by `MetaIoHandler::handle_request`, so that the rate limiter
+    // can price the request without parsing it again
+    let request = serde_json::from_str::<jsonrpc_core::Request>(body);
+    if let Some(response) =
+        service.refuse_over_rate_limit(peer_address, |rate_limiter, peer_address| {
+            let cost = match &request {
+                Ok(request) => rate_limiter.request_cost(request),
+                Err(_) => rate_limiter.invalid_request_cost(),
+            };
+            rate_limiter.try_acquire(peer_address, cost)
+        })
+    {
+        return Ok(response);
+    }
+    let response = match request {
+        Ok(request) => service
+            .io
+            .handle_rpc_request(request, service.request_processor)
+            .await
+            .map(|response| serde_json::to_string(&response).expect("response serializes")),
+        Err(_) => Some(
+            serde_json::to_string(&jsonrpc_core::Response::from(
+                jsonrpc_core::Error::parse_error(),
+                Some(jsonrpc_core::Version::V2),
+            ))
+            .expect("response serializes"),
+        ),
+    };
+    Ok(hyper::Response::builder()
+        .status(hyper::StatusCode::OK)
+        .header(hyper::header::CONTENT_TYPE, "application/json")
+        .body(hyper::Body::from(response.unwrap_or_default()))
+        .unwrap())
+}
+
+/// Content codings of compressed JSON RPC responses, in order of preference
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ContentEncoding {
+    Zstd,
+    Gzip,
+}
+
+impl ContentEncoding {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Zstd => "zstd",
+            Self::Gzip => "gzip",
+        }
+    }
+
+    /// Picks the preferred content coding allowed by an `Accept-Encoding` request header
+    fn negotiate(accept_encoding: &str) -> Option<Self> {
+        // Each listed coding, and whether it's refused with `q=0`
+        let codings: Vec<_> = accept_encoding
+            .split(',')
+            .filter_map(|coding| {
+                let mut params = coding.split(';');
+                let name = params.next()?.trim().to_ascii_lowercase();
+                let refused = params.any(|param| {
+                    param
+                        .trim()
+                        .strip_prefix("q=")
+                        .and_then(|quality| quality.trim().parse::<f32>().ok())
+                        == Some(0.0)
+                });
+                Some((name, refused))
+            })
+            .collect();
+        let is_allowed = |name: &str| {
+            codings
+                .iter()
+                .find(|(coding, _)| coding == name)
+                .map(|(_, refused)| !refused)
+        };
+        [Self::Zstd, Self::Gzip].iter().copied().find(|encoding| {
+            is_allowed(encoding.as_str())
+                .or_else(|| is_allowed("*"))
+                .unwrap_or(false)
+        })
+    }
+
+    fn compress(self, data: &[u8]) -> std::io::Result<Vec<u8>> {
+        match self {
+            Self::Zstd => {
+                let mut e = zstd::stream::write::Encoder::new(Vec::new(), 0)?;
+                e.write_all(data)?;
+                e.finish()
+            }
+            Self::Gzip => {
+                let mut e =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                e.write_all(data)?;
+                e.finish()
+            }
+        }
+    }
+}
+
+/// Encoder of a compressed response that is sent in chunks
+enum StreamEncoder {
+    Zstd(zstd::stream::write::Encoder<'static, Vec<u8>>),
+    Gzip(flate2::write::GzEncoder<Vec<u8>>),
+}
+
+impl StreamEncoder {
+    fn new(content_encoding: ContentEncoding) -> std::io::Result<Self> {
+        Ok(match content_encoding {
+            ContentEncoding::Zstd => Self::Zstd(zstd::stream::write::Encoder::new(Vec::new(), 0)?),
+            ContentEncoding::Gzip => Self::Gzip(flate2::write::GzEncoder::new(
+                Vec::new(),
+                flate2::Compression::default(),
+            )),
+        })
+    }
+
+    /// Compresses `data` and returns everything compressed since the last call
+    fn encode(&mut self, data: &[u8]) -> std::io::Result<Vec<u8>> {
+        match self {
+            Self::Zstd(e) => {
+                e.write_all(data)?;
+                e.flush()?;
+                Ok(std::mem::take(e.get_mut()))
+            }
+            Self::Gzip(e) => {
+                e.write_all(data)?;
+                e.flush()?;
+                Ok(std::mem::take(e.get_mut()))
+            }
+        }
+    }
+
+    fn finish(self) -> std::io::Result<Vec<u8>> {
+        match self {
+            Self::Zstd(e) => e.finish(),
+            Self::Gzip(e) => e.finish(),
+        }
+    }
+}
+
 impl JsonRpcService {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
@@ -301,6 +1035,7 @@ impl JsonRpcService {
         max_slots: Arc<MaxSlots>,
         leader_schedule_cache: Arc<LeaderScheduleCache>,
         current_transaction_status_slot: Arc<AtomicU64>,
+        rpc_subscriptions: Option<&Arc<RpcSubscriptions>>,
     ) -> Self {
         info!("rpc bound to {:?}", rpc_addr);
         info!("rpc configuration: {:?}", config);
@@ -311,10 +1046,18 @@ impl JsonRpcService {
             trusted_validators,
             config.health_check_slot_distance,
             override_health_check,
+            rpc_subscriptions,
         ));
 
+        let largest_accounts_refresh_interval = config.largest_accounts_refresh_interval;
+        // Refreshed results are served for as long as the refresh keeps failing
+        let largest_accounts_cache_duration = if largest_accounts_refresh_interval.is_some() {
+            u64::MAX
+        } else {
+            LARGEST_ACCOUNTS_CACHE_DURATION
+        };
         let largest_accounts_cache = Arc::new(RwLock::new(LargestAccountsCache::new(
-            LARGEST_ACCOUNTS_CACHE_DURATION,
+            largest_accounts_cache_duration,
         )));
 
         let tpu_address = cluster_info.my_contact_info().tpu;
@@ -336,7 +1079,8 @@ impl JsonRpcService {
 
         let exit_bigtable_ledger_upload_service = Arc::new(AtomicBool::new(false));
 
-        let (bigtable_ledger_storage, _bigtable_ledger_upload_service) =
+        // The long-term ledger storage, and whether to upload to it
+        let ledger_archive: Option<(Arc<dyn LedgerArchive>, bool)> =
             if config.enable_bigtable_ledger_storage || config.enable_bigtable_ledger_upload {
                 runtime
                     .block_on(solana_storage_bigtable::LedgerStorage::new(
@@ -345,35 +1089,69 @@ impl JsonRpcService {
                     ))
                     .map(|bigtable_ledger_storage| {
                         info!("BigTable ledger storage initialized");
-
-                        let bigtable_ledger_upload_service = if config.enable_bigtable_ledger_upload
-                        {
-                            Some(Arc::new(BigTableUploadService::new(
-                                runtime.clone(),
-                                bigtable_ledger_storage.clone(),
-                                blockstore.clone(),
-                                block_commitment_cache.clone(),
-                                exit_bigtable_ledger_upload_service.clone(),
-                            )))
-                        } else {
-                            None
-                        };
-
+                        let bigtable_ledger_storage: Arc<dyn LedgerArchive> =
+                            Arc::new(bigtable_ledger_storage);
                         (
-                            Some(bigtable_ledger_storage),
-                            bigtable_ledger_upload_service,
+                            bigtable_ledger_storage,
+                            config.enable_bigtable_ledger_upload,
                         )
                     })
-                    .unwrap_or_else(|err| {
+                    .map_err(|err| {
                         error!("Failed to initialize BigTable ledger storage: {:?}", err);
-                        (None, None)
                     })
+                    .ok()
+            } else if let Some(object_store_config) = &config.object_store_ledger_storage {
+                ObjectStoreLedgerStorage::new(
+                    object_store_config,
+                    !config.enable_object_store_ledger_upload,
+                    config.rpc_bigtable_timeout,
+                )
+                .map(|object_store_ledger_storage| {
+                    info!("Object store ledger storage initialized");
+                    let object_store_ledger_storage: Arc<dyn LedgerArchive> =
+                        Arc::new(object_store_ledger_storage);
+                    (
+                        object_store_ledger_storage,
+                        config.enable_object_store_ledger_upload,
+                    )
+                })
+                .map_err(|err| {
+                    error!(
+                        "Failed to initialize object store ledger storage: {:?}",
+                        err
+                    );
+                })
+                .ok()
             } else {
-                (None, None)
+                None
             };
 
+        let (bigtable_ledger_storage, _bigtable_ledger_upload_service) = match ledger_archive {
+            Some((ledger_archive, upload)) => {
+                let bigtable_ledger_upload_service = if upload {
+                    Some(Arc::new(BigTableUploadService::new(
+                        runtime.clone(),
+                        ledger_archive.clone(),
+                        blockstore.clone(),
+                        block_commitment_cache.clone(),
+                        config.bigtable_upload_config.clone(),
+                        exit_bigtable_ledger_upload_service.clone(),
+                    )))
+                } else {
+                    None
+                };
+                (Some(ledger_archive), bigtable_ledger_upload_service)
+            }
+            None => (None, None),
+        };
+
         let minimal_api = config.minimal_api;
         let obsolete_v1_7_api = config.obsolete_v1_7_api;
+        let unix_socket_path = config.unix_socket_path.clone();
+        let tls_config = config.tls.clone();
+        let compression_min_size = config.http_compression_min_size;
+        let rate_limits_path = config.rate_limits_path.clone();
+        let slow_request_threshold = config.slow_request_threshold;
         let (request_processor, receiver) = JsonRpcRequestProcessor::new(
             config,
             snapshot_config.clone(),
@@ -405,13 +1183,35 @@ impl JsonRpcService {
         #[cfg(test)]
         let test_request_processor = request_processor.clone();
 
+        let exit_largest_accounts_refresh = Arc::new(AtomicBool::new(false));
+        let largest_accounts_refresh_hdl = largest_accounts_refresh_interval.map(|interval| {
+            let request_processor = request_processor.clone();
+            let exit = exit_largest_accounts_refresh.clone();
+            Builder::new()
+                .name("solana-rpc-largest-accounts".to_string())
+                .spawn(move || {
+                    let mut refreshed_at: Option<Instant> = None;
+                    while !exit.load(Ordering::Relaxed) {
+                        if refreshed_at
+                            .map_or(true, |refreshed_at| refreshed_at.elapsed() >= interval)
+                        {
+                            request_processor.refresh_largest_accounts();
+                            refreshed_at = Some(Instant::now());
+                        }
+                        thread::sleep(LARGEST_ACCOUNTS_REFRESH_EXIT_POLL_INTERVAL);
+                    }
+                })
+                .unwrap()
+        });
+
         let ledger_path = ledger_path.to_path_buf();
 
         let (close_handle_sender, close_handle_receiver) = channel();
         let thread_hdl = Builder::new()
             .name("solana-jsonrpc".to_string())
             .spawn(move || {
-                let mut io = MetaIoHandler::default();
+                let mut io =
+                    MetaIoHandler::with_middleware(RequestTracer::new(slow_request_threshold));
 
                 io.extend_with(rpc_minimal::MinimalImpl.to_delegate());
                 if !minimal_api {
@@ -425,40 +1225,133 @@ impl JsonRpcService {
                     io.extend_with(rpc_obsolete_v1_7::ObsoleteV1_7Impl.to_delegate());
                 }
 
-                let request_middleware = RpcRequestMiddleware::new(
+                let compressions = Arc::new(Semaphore::new(MAX_CONCURRENT_COMPRESSIONS));
+                #[cfg(unix)]
+                let unix_server = unix_socket_path.and_then(|path| {
+                    let _guard = runtime.enter();
+                    match bind_unix_listener(&path) {
+                        Ok(listener) => {
+                            info!("rpc bound to {:?}", path);
+                            Some(runtime.spawn(serve_unix_socket(
+                                listener,
+                                HttpService {
+                                    io: Arc::new(io.clone()),
+                                    request_processor: request_processor.clone(),
+                                    request_middleware: None,
+                                    compression_min_size,
+                                    compressions: compressions.clone(),
+                                    rate_limiter: None,
+                                },
+                            )))
+                        }
+                        Err(err) => {
+                            warn!("JSON RPC unix socket {:?} unavailable: {}", path, err);
+                            None
+                        }
+                    }
+                });
+                #[cfg(not(unix))]
+                if unix_socket_path.is_some() {
+                    warn!("JSON RPC unix sockets are not supported on this platform");
+                }
+
+                let mut request_middleware = RpcRequestMiddleware::new(
                     ledger_path,
                     snapshot_config,
                     bank_forks.clone(),
                     health.clone(),
                 );
-                let server = ServerBuilder::with_meta_extractor(
-                    io,
-                    move |_req: &hyper::Request<hyper::Body>| request_processor.clone(),
-                )
-                .event_loop_executor(runtime.handle().clone())
-                .threads(1)
-                .cors(DomainsValidation::AllowOnly(vec![
-                    AccessControlAllowOrigin::Any,
-                ]))
-                .cors_max_age(86400)
-                .request_middleware(request_middleware)
-                .max_request_body_size(MAX_REQUEST_PAYLOAD_SIZE)
-                .start_http(&rpc_addr);
-
-                if let Err(e) = server {
-                    warn!(
-                        "JSON RPC service unavailable error: {:?}. \n\
-                           Also, check that port {} is not already in use by another application",
-                        e,
-                        rpc_addr.port()
-                    );
-                    return;
+                if !minimal_api {
+                    request_middleware =
+                        request_middleware.with_program_accounts_stream(request_processor.clone());
                 }
+                // `jsonrpc_http_server` can't terminate TLS, rewrite responses or tell the
+                // address of a client, so these are served by `serve_tcp` instead
+                if tls_config.is_some()
+                    || compression_min_size.is_some()
+                    || rate_limits_path.is_some()
+                {
+                    let server = tls_config
+                        .map(|tls_config| tls_config.acceptor())
+                        .transpose()
+                        .and_then(|tls_acceptor| {
+                            let rate_limiter = rate_limits_path
+                                .as_deref()
+                                .map(RateLimits::load)
+                                .transpose()?
+                                .map(|limits| Arc::new(RateLimiter::new(limits)));
+                            let listener =
+                                runtime.block_on(tokio::net::TcpListener::bind(rpc_addr))?;
+                            Ok((listener, tls_acceptor, rate_limiter))
+                        });
+                    let (listener, tls_acceptor, rate_limiter) = match server {
+                        Ok(server) => server,
+                        Err(e) => {
+                            warn!(
+                                "JSON RPC service unavailable error: {:?}. \n\
+                                   Also, check that port {} is not already in use by another \
+                                   application and that the TLS certificate and key and the \
+                                   rate limits are valid",
+                                e,
+                                rpc_addr.port()
+                            );
+                            return;
+                        }
+                    };
+                    let close = Arc::new(Notify::new());
+                    close_handle_sender
+                        .send(ServerCloseHandle::Tcp(Arc::clone(&close)))
+                        .unwrap();
+                    runtime.block_on(serve_tcp(
+                        listener,
+                        tls_acceptor,
+                        HttpService {
+                            io: Arc::new(io),
+                            request_processor,
+                            request_middleware: Some(Arc::new(request_middleware)),
+                            compression_min_size,
+                            compressions,
+                            rate_limiter,
+                        },
+                        close,
+                    ));
+                } else {
+                    let server = ServerBuilder::with_meta_extractor(
+                        io,
+                        move |_req: &hyper::Request<hyper::Body>| request_processor.clone(),
+                    )
+                    .event_loop_executor(runtime.handle().clone())
+                    .threads(1)
+                    .cors(DomainsValidation::AllowOnly(vec![
+                        AccessControlAllowOrigin::Any,
+                    ]))
+                    .cors_max_age(86400)
+                    .request_middleware(request_middleware)
+                    .max_request_body_size(MAX_REQUEST_PAYLOAD_SIZE)
+                    .start_http(&rpc_addr);
+
+                    if let Err(e) = server {
+                        warn!(
+                            "JSON RPC service unavailable error: {:?}. \n\
+                               Also, check that port {} is not already in use by another application",
+                            e,
+                            rpc_addr.port()
+                        );
+                        return;
+                    }
 
-                let server = server.unwrap();
-                close_handle_sender.send(server.close_handle()).unwrap();
-                server.wait();
+                    let server = server.unwrap();
+                    close_handle_sender
+                        .send(ServerCloseHandle::Http(server.close_handle()))
+                        .unwrap();
+                    server.wait();
+                }
+                #[cfg(unix)]
+                if let Some(unix_server) = unix_server {
+                    unix_server.abort();
+                }
                 exit_bigtable_ledger_upload_service.store(true, Ordering::Relaxed);
+                exit_largest_accounts_refresh.store(true, Ordering::Relaxed);
             })
             .unwrap();
 
@@ -470,6 +1363,7 @@ impl JsonRpcService {
             .register_exit(Box::new(move || close_handle_.close()));
         Self {
             thread_hdl,
+            largest_accounts_refresh_hdl,
             #[cfg(test)]
             request_processor: test_request_processor,
             close_handle: Some(close_handle),
@@ -483,7 +1377,11 @@ impl JsonRpcService {
     }
 
     pub fn join(self) -> thread::Result<()> {
-        self.thread_hdl.join()
+        self.thread_hdl.join()?;
+        if let Some(largest_accounts_refresh_hdl) = self.largest_accounts_refresh_hdl {
+            largest_accounts_refresh_hdl.join()?;
+        }
+        Ok(())
     }
 }
 
@@ -492,6 +1390,7 @@ mod tests {
     use {
         super::*,
         crate::rpc::create_validator_exit,
+        solana_client::rpc_response::{RpcHealthReport, RpcHealthState, RpcKeyedAccount},
         solana_gossip::{
             contact_info::ContactInfo,
             crds::GossipRoute,
@@ -503,13 +1402,14 @@ mod tests {
         },
         solana_runtime::bank::Bank,
         solana_sdk::{
+            account::AccountSharedData,
             genesis_config::{ClusterType, DEFAULT_GENESIS_ARCHIVE},
             signature::Signer,
             signer::keypair::Keypair,
         },
         solana_streamer::socket::SocketAddrSpace,
         std::{
-            io::Write,
+            io::{Read, Write},
             net::{IpAddr, Ipv4Addr},
         },
         tokio::runtime::Runtime,
@@ -564,6 +1464,7 @@ mod tests {
             Arc::new(MaxSlots::default()),
             Arc::new(LeaderScheduleCache::default()),
             Arc::new(AtomicU64::default()),
+            None,
         );
         let thread = rpc_service.thread_hdl.thread();
         assert_eq!(thread.name().unwrap(), "solana-jsonrpc");
@@ -599,6 +1500,29 @@ mod tests {
         );
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_bind_unix_listener() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let _guard = runtime.enter();
+        let path = std::env::temp_dir().join(format!(
+            "test_bind_unix_listener-{}.sock",
+            std::process::id()
+        ));
+
+        drop(bind_unix_listener(&path).unwrap());
+        // The socket left behind is replaced
+        assert!(path.exists());
+        let listener = bind_unix_listener(&path).unwrap();
+        drop(listener);
+
+        // Anything else is not
+        std::fs::remove_file(&path).unwrap();
+        std::fs::write(&path, b"").unwrap();
+        assert!(bind_unix_listener(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
     #[test]
     fn test_is_file_get_path() {
         let bank_forks = create_bank_forks();
@@ -731,6 +1655,289 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_program_accounts_stream() {
+        let runtime = Runtime::new().unwrap();
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+        let program_id = solana_sdk::pubkey::new_rand();
+        let mut pubkeys: Vec<_> = (0..3).map(|_| solana_sdk::pubkey::new_rand()).collect();
+        for pubkey in &pubkeys {
+            bank.store_account(pubkey, &AccountSharedData::new(1, 0, &program_id));
+        }
+        pubkeys.sort();
+        let request_processor =
+            JsonRpcRequestProcessor::new_from_bank(&bank, SocketAddrSpace::Unspecified);
+        let rrm = RpcRequestMiddleware::new(
+            PathBuf::from("/"),
+            None,
+            create_bank_forks(),
+            RpcHealth::stub(),
+        )
+        .with_program_accounts_stream(request_processor);
+
+        let stream = |body: String| {
+            let request = hyper::Request::post(PROGRAM_ACCOUNTS_STREAM_PATH)
+                .body(hyper::Body::from(body))
+                .unwrap();
+            match rrm.on_request(request) {
+                RequestMiddlewareAction::Respond { response, .. } => runtime.block_on(async {
+                    let response = response.await.unwrap();
+                    let status = response.status();
+                    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+                    (status, String::from_utf8(body.to_vec()).unwrap())
+                }),
+                RequestMiddlewareAction::Proceed { .. } => {
+                    panic!("Unexpected RequestMiddlewareAction variant")
+                }
+            }
+        };
+
+        let (status, body) = stream(format!(r#"["{}", {{"limit": 2}}]"#, program_id));
+        assert_eq!(status, hyper::StatusCode::OK);
+        let streamed: Vec<_> = body
+            .lines()
+            .map(|line| {
+                serde_json::from_str::<RpcKeyedAccount>(line)
+                    .unwrap()
+                    .pubkey
+            })
+            .collect();
+        assert_eq!(
+            streamed,
+            vec![pubkeys[0].to_string(), pubkeys[1].to_string()]
+        );
+
+        let (status, body) = stream(r#"["not a pubkey"]"#.to_string());
+        assert_eq!(status, hyper::StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(
+            body["error"]["code"],
+            jsonrpc_core::ErrorCode::InvalidParams.code()
+        );
+    }
+
+    #[test]
+    fn test_negotiate_content_encoding() {
+        assert_eq!(ContentEncoding::negotiate(""), None);
+        assert_eq!(ContentEncoding::negotiate("identity"), None);
+        assert_eq!(
+            ContentEncoding::negotiate("gzip, deflate, br"),
+            Some(ContentEncoding::Gzip)
+        );
+        assert_eq!(
+            ContentEncoding::negotiate("GZIP;q=0.5, zstd"),
+            Some(ContentEncoding::Zstd)
+        );
+        assert_eq!(
+            ContentEncoding::negotiate("gzip, zstd;q=0"),
+            Some(ContentEncoding::Gzip)
+        );
+        assert_eq!(ContentEncoding::negotiate("*"), Some(ContentEncoding::Zstd));
+        assert_eq!(
+            ContentEncoding::negotiate("*, zstd; q=0.0"),
+            Some(ContentEncoding::Gzip)
+        );
+        assert_eq!(ContentEncoding::negotiate("*;q=0, br"), None);
+    }
+
+    fn minimal_http_service(
+        compression_min_size: Option<usize>,
+        rate_limiter: Option<Arc<RateLimiter>>,
+    ) -> HttpService {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+        let mut io = MetaIoHandler::with_middleware(RequestTracer::default());
+        io.extend_with(rpc_minimal::MinimalImpl.to_delegate());
+        HttpService {
+            io: Arc::new(io),
+            request_processor: JsonRpcRequestProcessor::new_from_bank(
+                &bank,
+                SocketAddrSpace::Unspecified,
+            ),
+            request_middleware: None,
+            compression_min_size,
+            compressions: Arc::new(Semaphore::new(MAX_CONCURRENT_COMPRESSIONS)),
+            rate_limiter,
+        }
+    }
+
+    fn post_get_version(
+        runtime: &Runtime,
+        service: HttpService,
+        peer_address: Option<IpAddr>,
+        accept_encoding: Option<&str>,
+    ) -> hyper::Response<Vec<u8>> {
+        let mut request = hyper::Request::post("/");
+        if let Some(accept_encoding) = accept_encoding {
+            request = request.header(hyper::header::ACCEPT_ENCODING, accept_encoding);
+        }
+        let request = request
+            .body(hyper::Body::from(
+                r#"{"jsonrpc":"2.0","id":1,"method":"getVersion"}"#,
+            ))
+            .unwrap();
+        runtime.block_on(async {
+            let response = handle_http_request(request, service, peer_address)
+                .await
+                .unwrap();
+            let (parts, body) = response.into_parts();
+            let body = hyper::body::to_bytes(body).await.unwrap();
+            hyper::Response::from_parts(parts, body.to_vec())
+        })
+    }
+
+    #[test]
+    fn test_handle_http_request_compression() {
+        let runtime = Runtime::new().unwrap();
+        let request = |accept_encoding, compression_min_size| {
+            let response = post_get_version(
+                &runtime,
+                minimal_http_service(compression_min_size, None),
+                None,
+                accept_encoding,
+            );
+            let content_encoding = response
+                .headers()
+                .get(hyper::header::CONTENT_ENCODING)
+                .map(|value| value.to_str().unwrap().to_string());
+            (content_encoding, response.into_body())
+        };
+        let (_, uncompressed) = request(None, None);
+        let version: serde_json::Value = serde_json::from_slice(&uncompressed).unwrap();
+        assert!(version["result"]["solana-core"].is_string());
+
+        assert_eq!(request(Some("zstd"), None), (None, uncompressed.clone()));
+        assert_eq!(request(None, Some(0)), (None, uncompressed.clone()));
+        assert_eq!(
+            request(Some("gzip"), Some(uncompressed.len() + 1)),
+            (None, uncompressed.clone())
+        );
+
+        let (content_encoding, body) = request(Some("zstd"), Some(uncompressed.len()));
+        assert_eq!(content_encoding.as_deref(), Some("zstd"));
+        assert_eq!(
+            zstd::stream::decode_all(body.as_slice()).unwrap(),
+            uncompressed
+        );
+
+        let (content_encoding, body) = request(Some("gzip"), Some(0));
+        assert_eq!(content_encoding.as_deref(), Some("gzip"));
+        let mut decompressed = vec![];
+        flate2::read::GzDecoder::new(body.as_slice())
+            .read_to_end(&mut decompressed)
+            .unwrap();
+        assert_eq!(decompressed, uncompressed);
+    }
+
+    #[test]
+    fn test_handle_http_request_limits_and_cors() {
+        let runtime = Runtime::new().unwrap();
+        let service = minimal_http_service(None, None);
+        let handle = |request| {
+            runtime
+                .block_on(handle_http_request(request, service.clone(), None))
+                .unwrap()
+        };
+
+        // Oversized bodies are refused whether or not their length is known up front
+        let oversized = vec![b' '; MAX_REQUEST_PAYLOAD_SIZE + 1];
+        let response = handle(
+            hyper::Request::post("/")
+                .body(hyper::Body::from(oversized.clone()))
+                .unwrap(),
+        );
+        assert_eq!(response.status(), hyper::StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(
+            response.headers()[hyper::header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "*"
+        );
+        let chunks = oversized
+            .chunks(1024)
+            .map(|chunk| Ok::<_, std::io::Error>(chunk.to_vec()))
+            .collect::<Vec<_>>();
+        let response = handle(
+            hyper::Request::post("/")
+                .body(hyper::Body::wrap_stream(
+                    jsonrpc_core::futures::stream::iter(chunks),
+                ))
+                .unwrap(),
+        );
+        assert_eq!(response.status(), hyper::StatusCode::PAYLOAD_TOO_LARGE);
+
+        let response = handle(
+            hyper::Request::options("/")
+                .header(hyper::header::ACCESS_CONTROL_REQUEST_HEADERS, "x-custom")
+                .body(hyper::Body::empty())
+                .unwrap(),
+        );
+        assert_eq!(response.status(), hyper::StatusCode::OK);
+        assert_eq!(
+            response.headers()[hyper::header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "*"
+        );
+        assert_eq!(
+            response.headers()[hyper::header::ACCESS_CONTROL_ALLOW_HEADERS],
+            "x-custom"
+        );
+    }
+
+    #[test]
+    fn test_compress_stream() {
+        let runtime = Runtime::new().unwrap();
+        let lines: Vec<_> = (0..100).map(|i| format!("{{\"line\":{}}}\n", i)).collect();
+        let uncompressed = lines.concat();
+        for content_encoding in [ContentEncoding::Zstd, ContentEncoding::Gzip].iter() {
+            let body = hyper::Body::wrap_stream(stream::iter(
+                lines.clone().into_iter().map(Ok::<_, std::io::Error>),
+            ));
+            let compressions = Arc::new(Semaphore::new(1));
+            let body = compress_stream(body, *content_encoding, compressions);
+            let compressed = runtime.block_on(hyper::body::to_bytes(body)).unwrap();
+            let decompressed = match content_encoding {
+                ContentEncoding::Zstd => zstd::stream::decode_all(compressed.as_ref()).unwrap(),
+                ContentEncoding::Gzip => {
+                    let mut decompressed = vec![];
+                    flate2::read::GzDecoder::new(compressed.as_ref())
+                        .read_to_end(&mut decompressed)
+                        .unwrap();
+                    decompressed
+                }
+            };
+            assert_eq!(decompressed, uncompressed.as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_handle_http_request_rate_limit() {
+        let runtime = Runtime::new().unwrap();
+        let rate_limiter = Arc::new(RateLimiter::new(RateLimits {
+            tokens_per_second: 1,
+            burst: 5,
+            default_cost: 1,
+            method_costs: [("getVersion".to_string(), 2)].iter().cloned().collect(),
+            exempt_addresses: HashSet::new(),
+        }));
+        let service = minimal_http_service(None, Some(rate_limiter));
+        let limited = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        let request =
+            |peer_address| post_get_version(&runtime, service.clone(), peer_address, None);
+
+        assert_eq!(request(Some(limited)).status(), hyper::StatusCode::OK);
+        assert_eq!(request(Some(limited)).status(), hyper::StatusCode::OK);
+        let response = request(Some(limited));
+        assert_eq!(response.status(), hyper::StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[hyper::header::RETRY_AFTER], "1");
+        // Other clients, and clients of the unix socket, aren't affected
+        assert_eq!(
+            request(Some(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2)))).status(),
+            hyper::StatusCode::OK
+        );
+        for _ in 0..5 {
+            assert_eq!(request(None).status(), hyper::StatusCode::OK);
+        }
+    }
+
     #[test]
     fn test_health_check_with_no_trusted_validators() {
         let rm = RpcRequestMiddleware::new(
@@ -740,6 +1947,47 @@ mod tests {
             RpcHealth::stub(),
         );
         assert_eq!(rm.health_check(), "ok");
+        let report: RpcHealthReport = serde_json::from_str(&rm.health_report()).unwrap();
+        assert_eq!(report.status, RpcHealthState::Ok);
+        assert_eq!(report.latest_snapshot_slot, None);
+    }
+
+    #[test]
+    fn test_health_report_latest_snapshot() {
+        let snapshot_archives_dir = std::env::temp_dir().join(format!(
+            "test_health_report_latest_snapshot-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&snapshot_archives_dir).unwrap();
+        let hash = Hash::default();
+        let incremental_snapshot_archive =
+            snapshot_archives_dir.join(format!("incremental-snapshot-100-150-{}.tar.zst", hash));
+        std::fs::write(
+            snapshot_archives_dir.join(format!("snapshot-100-{}.tar.zst", hash)),
+            b"",
+        )
+        .unwrap();
+        std::fs::write(&incremental_snapshot_archive, b"").unwrap();
+        let rm = RpcRequestMiddleware::new(
+            PathBuf::from("/"),
+            Some(SnapshotConfig {
+                snapshot_archives_dir: snapshot_archives_dir.clone(),
+                ..SnapshotConfig::default()
+            }),
+            create_bank_forks(),
+            RpcHealth::stub(),
+        );
+
+        let report: RpcHealthReport = serde_json::from_str(&rm.health_report()).unwrap();
+        assert_eq!(report.latest_snapshot_slot, Some(150));
+        assert!(report.latest_snapshot_age_secs.unwrap() < 60);
+        assert_eq!(report.snapshot_slots_behind_root, Some(0));
+
+        // The listing is cached, so the removed archive is still reported
+        std::fs::remove_file(&incremental_snapshot_archive).unwrap();
+        let report: RpcHealthReport = serde_json::from_str(&rm.health_report()).unwrap();
+        assert_eq!(report.latest_snapshot_slot, Some(150));
+        std::fs::remove_dir_all(&snapshot_archives_dir).unwrap();
     }
 
     #[test]
@@ -762,6 +2010,7 @@ mod tests {
             Some(trusted_validators.clone().into_iter().collect()),
             health_check_slot_distance,
             override_health_check.clone(),
+            None,
         ));
 
         let rm = RpcRequestMiddleware::new(PathBuf::from("/"), None, create_bank_forks(), health);
@@ -0,0 +1,572 @@
+//! The `rpc_sse_service` module serves the pubsub subscriptions over plain HTTP, for
+//! clients that can't hold a websocket open. `GET /events?method=<METHOD>&params=<PARAMS>`
+//! subscribes with a websocket subscribe method and its JSON params, and streams the
+//! notifications of that subscription as server-sent events. When webhooks are enabled,
+//! `POST /webhooks` subscribes on behalf of a webhook and `DELETE /webhooks/<ID>` ends the
+//! subscription again. Webhooks make the node send requests on behalf of clients, so they
+//! are only served to clients with an API key.
+
+use {
+    crate::{
+        rpc_pubsub::{RpcSolPubSubImpl, RpcSolPubSubInternal},
+        rpc_pubsub_auth::{ApiKey, ApiKeys},
+        rpc_pubsub_service::{
+            forward_notifications, BroadcastHandler, PubSubConfig, STREAM_BUFFER_SIZE,
+        },
+        rpc_subscription_tracker::CompositeSubscriptions,
+        rpc_subscriptions::{
+            DirectNotification, RpcNotification, RpcSubscriptions, DIRECT_NOTIFICATION_BUFFER_SIZE,
+        },
+        rpc_webhooks::{self, WebhookTarget, Webhooks},
+    },
+    dashmap::DashMap,
+    jsonrpc_core::{Call, Id, IoHandler, MethodCall, Output, Params, Version},
+    jsonrpc_http_server::hyper::{
+        self,
+        body::HttpBody,
+        server::conn::AddrStream,
+        service::{make_service_fn, service_fn},
+        Body, Method, Request, Response, StatusCode,
+    },
+    serde_json::json,
+    std::{
+        convert::Infallible,
+        net::{IpAddr, SocketAddr},
+        sync::{Arc, RwLock},
+        thread::{self, Builder, JoinHandle},
+    },
+    stream_cancel::{Trigger, Tripwire},
+    tokio::{
+        select,
+        sync::{broadcast, mpsc},
+    },
+    tokio_stream::wrappers::ReceiverStream,
+};
+
+const MAX_WEBHOOK_REGISTRATION_SIZE: usize = 64 * 1024;
+
+struct SseContext {
+    config: PubSubConfig,
+    subscriptions: Arc<RpcSubscriptions>,
+    api_keys: Option<Arc<ApiKeys>>,
+    /// Unset when webhooks are disabled, which they are unless API keys are configured
+    webhooks: Option<Arc<Webhooks>>,
+}
+
+pub struct SseService {
+    thread_hdl: JoinHandle<()>,
+}
+
+impl SseService {
+    pub fn new(
+        pubsub_config: PubSubConfig,
+        subscriptions: &Arc<RpcSubscriptions>,
+        sse_addr: SocketAddr,
+    ) -> (Trigger, Self) {
+        let subscriptions = Arc::clone(subscriptions);
+        info!("rpc_sse bound to {:?}", sse_addr);
+
+        let (trigger, tripwire) = Tripwire::new();
+        let thread_hdl = Builder::new()
+            .name("solana-rpc-sse".to_string())
+            .spawn(move || {
+                let runtime = tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .expect("runtime creation failed");
+                let api_keys = pubsub_config.api_keys.clone();
+                if pubsub_config.max_webhooks > 0 && api_keys.is_none() {
+                    warn!("sse service webhooks disabled, they require pubsub API keys");
+                }
+                let webhooks = (pubsub_config.max_webhooks > 0 && api_keys.is_some())
+                    .then(|| Arc::new(Webhooks::new(pubsub_config.max_webhooks)));
+                let context = Arc::new(SseContext {
+                    config: pubsub_config,
+                    subscriptions,
+                    api_keys,
+                    webhooks,
+                });
+                if let Err(err) = runtime.block_on(serve(sse_addr, context, tripwire)) {
+                    error!("sse service failed: {}", err);
+                };
+            })
+            .expect("thread spawn failed");
+
+        (trigger, Self { thread_hdl })
+    }
+
+    pub fn close(self) -> thread::Result<()> {
+        self.join()
+    }
+
+    pub fn join(self) -> thread::Result<()> {
+        self.thread_hdl.join()
+    }
+}
+
+async fn serve(
+    sse_addr: SocketAddr,
+    context: Arc<SseContext>,
+    tripwire: Tripwire,
+) -> hyper::Result<()> {
+    let make_service = make_service_fn(move |conn: &AddrStream| {
+        let client_ip = conn.remote_addr().ip();
+        let context = Arc::clone(&context);
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let context = Arc::clone(&context);
+                async move {
+                    let response = handle_request(&context, request, client_ip).await;
+                    Ok::<_, Infallible>(response)
+                }
+            }))
+        }
+    });
+    let server = hyper::Server::try_bind(&sse_addr)?.serve(make_service);
+    // Streams never end on their own, so a graceful shutdown would wait for the clients
+    select! {
+        result = server => result,
+        _ = tripwire => Ok(()),
+    }
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .body(Body::from(message.to_string()))
+        .unwrap()
+}
+
+/// Authenticates a request with the `authorization: Bearer <KEY>` header or the `token`
+/// query parameter, when API keys are configured
+fn authenticate(
+    api_keys: &Option<Arc<ApiKeys>>,
+    request: &Request<Body>,
+) -> Result<Option<ApiKey>, Response<Body>> {
+    let api_keys = match api_keys {
+        Some(api_keys) => api_keys,
+        None => return Ok(None),
+    };
+    let header_key = request
+        .headers()
+        .get(hyper::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| api_keys.authenticate_bearer(value));
+    let path = request
+        .uri()
+        .path_and_query()
+        .map(|path| path.as_str())
+        .unwrap_or_default();
+    header_key
+        .or_else(|| api_keys.authenticate_path(path))
+        .map(Some)
+        .ok_or_else(|| {
+            inc_new_counter_info!("rpc-sse-unauthorized", 1);
+            error_response(StatusCode::UNAUTHORIZED, "Invalid API key")
+        })
+}
+
+/// A subscription held by a single stream, such as an SSE stream or a webhook
+pub(crate) struct StreamSubscription {
+    /// The JSON-RPC response to the subscribe request
+    pub(crate) response: Output,
+    pub(crate) handler: BroadcastHandler,
+    pub(crate) broadcast_receiver: broadcast::Receiver<RpcNotification>,
+    pub(crate) firehose_receiver: broadcast::Receiver<RpcNotification>,
+    pub(crate) direct_receiver: mpsc::Receiver<DirectNotification>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct WebhookRegistration {
+    method: String,
+    params: Option<Params>,
+    url: String,
+    secret: String,
+}
+
+/// Subscribes through the websocket implementation, so that all transports validate
+/// requests and enforce limits the same way. The call is handed over already parsed.
+async fn subscribe(
+    context: &SseContext,
+    method: &str,
+    params: Params,
+    client_ip: IpAddr,
+    api_key: Option<ApiKey>,
+) -> Result<StreamSubscription, Response<Body>> {
+    if !method.ends_with("Subscribe") {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            "Missing or invalid subscribe method",
+        ));
+    }
+    if let Some(api_key) = &api_key {
+        if !api_key.allows_method(method) {
+            return Err(error_response(
+                StatusCode::FORBIDDEN,
+                "Method not allowed for this API key",
+            ));
+        }
+    }
+    let call = Call::MethodCall(MethodCall {
+        jsonrpc: Some(Version::V2),
+        method: method.to_string(),
+        params,
+        id: Id::Num(1),
+    });
+
+    let subscription_control = context.subscriptions.control();
+    let current_subscriptions = Arc::new(DashMap::new());
+    let composite_subscriptions = Arc::new(RwLock::new(CompositeSubscriptions::default()));
+    let (direct_sender, direct_receiver) = mpsc::channel(DIRECT_NOTIFICATION_BUFFER_SIZE);
+    // Created ahead of the subscription so that its first notification can't be missed
+    let broadcast_receiver = subscription_control.broadcast_receiver();
+    let firehose_receiver = subscription_control.firehose_broadcast_receiver();
+    if let Some(api_key) = &api_key {
+        api_key.register_connection(&current_subscriptions);
+    }
+
+    let mut json_rpc_handler = IoHandler::new();
+    let rpc_impl = RpcSolPubSubImpl::new(
+        context.config.clone(),
+        subscription_control.clone(),
+        Arc::clone(&current_subscriptions),
+        Arc::clone(&composite_subscriptions),
+        direct_sender,
+        Some(client_ip),
+        api_key,
+    );
+    json_rpc_handler.extend_with(rpc_impl.to_delegate());
+    // Calls with an id always have a response
+    let response = json_rpc_handler.handle_call(call, ()).await.unwrap();
+    drop(json_rpc_handler);
+    if current_subscriptions.is_empty() {
+        let response = serde_json::to_string(&response).unwrap_or_default();
+        return Err(error_response(StatusCode::BAD_REQUEST, &response));
+    }
+    Ok(StreamSubscription {
+        response,
+        handler: BroadcastHandler::new(current_subscriptions, composite_subscriptions),
+        broadcast_receiver,
+        firehose_receiver,
+        direct_receiver,
+    })
+}
+
+async fn handle_request(
+    context: &SseContext,
+    request: Request<Body>,
+    client_ip: IpAddr,
+) -> Response<Body> {
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let result = match (method, &context.webhooks) {
+        (Method::GET, _) if path == "/events" => handle_events(context, request, client_ip).await,
+        (Method::POST, Some(webhooks)) if path == "/webhooks" => {
+            add_webhook(context, webhooks, request, client_ip).await
+        }
+        (Method::DELETE, Some(webhooks)) if path.starts_with("/webhooks/") => {
+            remove_webhook(context, webhooks, &request)
+        }
+        _ => Err(error_response(StatusCode::NOT_FOUND, "Not found")),
+    };
+    result.unwrap_or_else(|response| response)
+}
+
+/// `GET /events?method=<METHOD>&params=<PARAMS>`
+async fn handle_events(
+    context: &SseContext,
+    request: Request<Body>,
+    client_ip: IpAddr,
+) -> Result<Response<Body>, Response<Body>> {
+    let api_key = authenticate(&context.api_keys, &request)?;
+    let mut method = None;
+    let mut params = Params::None;
+    let query = request.uri().query().unwrap_or_default();
+    for (name, value) in url::form_urlencoded::parse(query.as_bytes()) {
+        match name.as_ref() {
+            "method" => method = Some(value.into_owned()),
+            "params" => {
+                let value = serde_json::from_str(&value).map_err(|err| {
+                    error_response(StatusCode::BAD_REQUEST, &format!("Invalid params: {}", err))
+                })?;
+                params = value;
+            }
+            _ => {}
+        }
+    }
+    let subscription = subscribe(
+        context,
+        &method.unwrap_or_default(),
+        params,
+        client_ip,
+        api_key,
+    )
+    .await?;
+
+    let (sender, receiver) = mpsc::channel(STREAM_BUFFER_SIZE);
+    let _ = sender.try_send(Ok::<_, Infallible>(format!(
+        "event: subscribed\ndata: {}\n\n",
+        serde_json::to_string(&subscription.response).unwrap_or_default()
+    )));
+    tokio::spawn(forward_notifications(
+        subscription.handler,
+        subscription.broadcast_receiver,
+        subscription.firehose_receiver,
+        subscription.direct_receiver,
+        sender,
+        context.config.clone(),
+        |_, json| Ok(format!("data: {}\n\n", json)),
+        |err| {
+            let error = json!({ "message": err.to_string() });
+            Ok(format!("event: error\ndata: {}\n\n", error))
+        },
+    ));
+    Ok(Response::builder()
+        .header(hyper::header::CONTENT_TYPE, "text/event-stream")
+        .header(hyper::header::CACHE_CONTROL, "no-cache")
+        .body(Body::wrap_stream(ReceiverStream::new(receiver)))
+        .unwrap())
+}
+
+/// `POST /webhooks` with a `WebhookRegistration` body
+async fn add_webhook(
+    context: &SseContext,
+    webhooks: &Arc<Webhooks>,
+    request: Request<Body>,
+    client_ip: IpAddr,
+) -> Result<Response<Body>, Response<Body>> {
+    let api_key = authenticate(&context.api_keys, &request)?
+        .ok_or_else(|| error_response(StatusCode::UNAUTHORIZED, "Webhooks require an API key"))?;
+    let owner = api_key.token().to_string();
+    let mut body = request.into_body();
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk =
+            chunk.map_err(|err| error_response(StatusCode::BAD_REQUEST, &err.to_string()))?;
+        if bytes.len() + chunk.len() > MAX_WEBHOOK_REGISTRATION_SIZE {
+            return Err(error_response(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "Webhook registration too large",
+            ));
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    let registration: WebhookRegistration = serde_json::from_slice(&bytes).map_err(|err| {
+        error_response(
+            StatusCode::BAD_REQUEST,
+            &format!("Invalid webhook registration: {}", err),
+        )
+    })?;
+    let url = reqwest::Url::parse(&registration.url)
+        .ok()
+        .filter(|url| url.scheme() == "http" || url.scheme() == "https")
+        .ok_or_else(|| error_response(StatusCode::BAD_REQUEST, "Invalid webhook url"))?;
+    rpc_webhooks::resolve(&url)
+        .await
+        .map_err(|err| error_response(StatusCode::BAD_REQUEST, &err))?;
+    let target = WebhookTarget {
+        url,
+        secret: registration.secret,
+    };
+
+    let subscription = subscribe(
+        context,
+        &registration.method,
+        registration.params.unwrap_or(Params::None),
+        client_ip,
+        Some(api_key),
+    )
+    .await?;
+    let response = subscription.response.clone();
+    let id = webhooks
+        .add(subscription, target, context.config.clone(), &owner)
+        .ok_or_else(|| error_response(StatusCode::TOO_MANY_REQUESTS, "Too many webhooks"))?;
+    let body = json!({
+        "id": id,
+        "response": response,
+    });
+    Ok(Response::builder()
+        .header(hyper::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap())
+}
+
+/// `DELETE /webhooks/<ID>`
+fn remove_webhook(
+    context: &SseContext,
+    webhooks: &Webhooks,
+    request: &Request<Body>,
+) -> Result<Response<Body>, Response<Body>> {
+    let api_key = authenticate(&context.api_keys, request)?
+        .ok_or_else(|| error_response(StatusCode::UNAUTHORIZED, "Webhooks require an API key"))?;
+    let id = request.uri().path().trim_start_matches("/webhooks/");
+    if !webhooks.remove(id, api_key.token()) {
+        return Err(error_response(StatusCode::NOT_FOUND, "Unknown webhook"));
+    }
+    Ok(Response::new(Body::empty()))
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::rpc_subscription_tracker::SubscriptionParams,
+        serial_test::serial,
+        solana_client::rpc_response::SlotInfo,
+        solana_runtime::{
+            bank::Bank,
+            bank_forks::BankForks,
+            genesis_utils::{create_genesis_config, GenesisConfigInfo},
+        },
+        std::time::Duration,
+    };
+
+    #[test]
+    #[serial]
+    fn test_sse_subscribe() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let subscriptions = Arc::new(RpcSubscriptions::default_with_bank_forks(bank_forks));
+        let context = SseContext {
+            config: PubSubConfig::default_for_tests(),
+            subscriptions: Arc::clone(&subscriptions),
+            api_keys: None,
+            webhooks: None,
+        };
+        let client_ip = IpAddr::from([127, 0, 0, 1]);
+        let request = |uri: &str| Request::get(uri).body(Body::empty()).unwrap();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let response = handle_request(&context, request("/"), client_ip).await;
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+            let response =
+                handle_request(&context, request("/events?method=getSlot"), client_ip).await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+            let response = handle_request(
+                &context,
+                request("/events?method=accountSubscribe&params=%5B%22invalid%22%5D"),
+                client_ip,
+            )
+            .await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+            let response =
+                handle_request(&context, request("/events?method=slotSubscribe"), client_ip)
+                    .await;
+            assert_eq!(response.status(), StatusCode::OK);
+            subscriptions
+                .control()
+                .assert_subscribed(&SubscriptionParams::Slot);
+            let mut body = response.into_body();
+            let subscribed = hyper::body::HttpBody::data(&mut body).await.unwrap().unwrap();
+            assert_eq!(
+                subscribed,
+                "event: subscribed\ndata: {\"jsonrpc\":\"2.0\",\"result\":0,\"id\":1}\n\n"
+            );
+
+            subscriptions.notify_slot(0, 0, 0);
+            let event = tokio::time::timeout(
+                Duration::from_secs(5),
+                hyper::body::HttpBody::data(&mut body),
+            )
+            .await
+            .expect("no notification")
+            .unwrap()
+            .unwrap();
+            let expected_res = SlotInfo {
+                parent: 0,
+                slot: 0,
+                root: 0,
+            };
+            let expected = format!(
+                "data: {{\"jsonrpc\":\"2.0\",\"method\":\"slotNotification\",\"params\":{{\"result\":{},\"subscription\":0}}}}\n\n",
+                serde_json::to_string(&expected_res).unwrap()
+            );
+            assert_eq!(event, expected);
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn test_sse_webhooks() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let subscriptions = Arc::new(RpcSubscriptions::default_with_bank_forks(bank_forks));
+        let api_keys_path =
+            std::env::temp_dir().join(format!("test_sse_webhooks-{}.json", std::process::id()));
+        std::fs::write(&api_keys_path, r#"{ "key": {}, "other": {} }"#).unwrap();
+        let context = SseContext {
+            config: PubSubConfig::default_for_tests(),
+            subscriptions: Arc::clone(&subscriptions),
+            api_keys: Some(Arc::new(ApiKeys::load(api_keys_path.clone()).unwrap())),
+            webhooks: Some(Arc::new(Webhooks::new(1))),
+        };
+        std::fs::remove_file(&api_keys_path).unwrap();
+        let client_ip = IpAddr::from([127, 0, 0, 1]);
+        let register = |url: &str, token: Option<&str>| {
+            let registration = json!({
+                "method": "slotSubscribe",
+                "url": url,
+                "secret": "secret",
+            });
+            let mut request = Request::post("/webhooks");
+            if let Some(token) = token {
+                request = request.header(hyper::header::AUTHORIZATION, format!("Bearer {}", token));
+            }
+            request.body(Body::from(registration.to_string())).unwrap()
+        };
+        let remove = |id: &str, token: &str| {
+            Request::delete(format!("/webhooks/{}", id))
+                .header(hyper::header::AUTHORIZATION, format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap()
+        };
+        // Never connected to, as there are no notifications
+        let public_url = "http://1.1.1.1:1/";
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let response = handle_request(&context, register(public_url, None), client_ip).await;
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+            for url in &[
+                "http://127.0.0.1:1/",
+                "http://10.0.0.1/",
+                "http://169.254.169.254/latest/meta-data/",
+                "http://[::1]/",
+            ] {
+                let response =
+                    handle_request(&context, register(url, Some("key")), client_ip).await;
+                assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{}", url);
+            }
+
+            let response =
+                handle_request(&context, register(public_url, Some("key")), client_ip).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            subscriptions
+                .control()
+                .assert_subscribed(&SubscriptionParams::Slot);
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(body["response"]["result"], 0);
+            let id = body["id"].as_str().unwrap().to_string();
+
+            let response =
+                handle_request(&context, register(public_url, Some("key")), client_ip).await;
+            assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+            // The limit applies per key
+            let response =
+                handle_request(&context, register(public_url, Some("other")), client_ip).await;
+            assert_eq!(response.status(), StatusCode::OK);
+
+            // Only the key that registered a webhook can remove it
+            let response = handle_request(&context, remove(&id, "other"), client_ip).await;
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+            let response = handle_request(&context, remove(&id, "key"), client_ip).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            let response = handle_request(&context, remove(&id, "key"), client_ip).await;
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        });
+    }
+}
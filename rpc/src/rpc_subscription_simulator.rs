@@ -0,0 +1,551 @@
+//! Deterministic simulation of subscription matching, used for capacity planning.
+//!
+//! A simulation combines an exported subscription set (the subscribe requests clients
+//! send, each with the number of connections that send it) with a recorded slot workload
+//! (account writes and transactions). Instead of timing real code, every matching step is
+//! counted as an operation, so the same inputs always produce the same report.
+
+use {
+    crate::rpc_subscription_tracker::LogsSubscriptionKind,
+    serde_json::Value,
+    solana_account_decoder::{UiAccountEncoding, MAX_BASE58_BYTES},
+    solana_client::{
+        rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcTransactionLogsFilter},
+        rpc_filter::RpcFilterType,
+    },
+    solana_sdk::{pubkey::Pubkey, signature::Signature},
+    std::{
+        collections::{HashMap, HashSet},
+        str::FromStr,
+    },
+};
+
+/// Fixed size of the JSON-RPC envelope and context that wraps every notification
+const NOTIFICATION_OVERHEAD_BYTES: usize = 200;
+/// Approximate size of an `RpcLogsResponse` excluding the log messages
+const LOGS_OVERHEAD_BYTES: usize = 150;
+const DEFAULT_SLOT_DURATION_MS: u64 = 400;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionRecord {
+    pub method: String,
+    #[serde(default)]
+    pub params: Vec<Value>,
+    /// Number of connections holding this subscription
+    #[serde(default = "default_count")]
+    pub count: usize,
+}
+
+fn default_count() -> usize {
+    1
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountWriteRecord {
+    pub pubkey: String,
+    pub owner: String,
+    pub data_len: usize,
+    /// Base64 encoded account data; memcmp filters are assumed to match if absent
+    #[serde(default)]
+    pub data: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionRecord {
+    pub signature: String,
+    #[serde(default)]
+    pub mentions: Vec<String>,
+    #[serde(default)]
+    pub is_vote: bool,
+    #[serde(default)]
+    pub log_bytes: usize,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlotWorkloadRecord {
+    #[serde(default = "default_slot_duration_ms")]
+    pub slot_duration_ms: u64,
+    #[serde(default)]
+    pub account_writes: Vec<AccountWriteRecord>,
+    #[serde(default)]
+    pub transactions: Vec<TransactionRecord>,
+}
+
+fn default_slot_duration_ms() -> u64 {
+    DEFAULT_SLOT_DURATION_MS
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum SimulatedParams {
+    Account {
+        pubkey: Pubkey,
+        encoding: UiAccountEncoding,
+    },
+    Program {
+        pubkey: Pubkey,
+        filters: Vec<RpcFilterType>,
+        encoding: UiAccountEncoding,
+    },
+    Logs(LogsSubscriptionKind),
+    Signature(Signature),
+    /// Slot, root and other node-progress streams: one small notification per slot
+    NodeProgress,
+}
+
+#[derive(Debug, Clone)]
+struct SimulatedSubscription {
+    params: SimulatedParams,
+    count: usize,
+}
+
+struct AccountWrite {
+    pubkey: Pubkey,
+    owner: Pubkey,
+    data_len: usize,
+    data: Option<Vec<u8>>,
+}
+
+struct TransactionWrite {
+    signature: Signature,
+    mentions: HashSet<Pubkey>,
+    is_vote: bool,
+    log_bytes: usize,
+}
+
+pub struct SlotWorkload {
+    slot_duration_ms: u64,
+    account_writes: Vec<AccountWrite>,
+    transactions: Vec<TransactionWrite>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MatchingImplementation {
+    /// Every subscription is evaluated against the bank on every notification
+    Linear,
+    /// Only subscriptions keyed by a written account, owner or mentioned address are evaluated
+    Indexed,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CostModel {
+    pub ns_per_match_op: u64,
+    pub ns_per_serialized_kb: u64,
+}
+
+impl Default for CostModel {
+    fn default() -> Self {
+        Self {
+            ns_per_match_op: 200,
+            ns_per_serialized_kb: 2_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulationReport {
+    pub implementation: MatchingImplementation,
+    pub match_ops: u64,
+    pub notifications_per_slot: u64,
+    pub notifications_per_sec: f64,
+    pub bytes_per_sec: f64,
+    /// Fraction of a slot the notification loop is busy; above 1.0 it falls behind
+    pub loop_utilization: f64,
+}
+
+fn parse<T: FromStr>(value: &str, what: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid {}: {}", what, value))
+}
+
+fn param<T: serde::de::DeserializeOwned + Default>(
+    params: &[Value],
+    index: usize,
+) -> Result<T, String> {
+    match params.get(index) {
+        None | Some(Value::Null) => Ok(T::default()),
+        Some(value) => serde_json::from_value(value.clone()).map_err(|err| err.to_string()),
+    }
+}
+
+fn param_str(params: &[Value], index: usize) -> Result<&str, String> {
+    params
+        .get(index)
+        .and_then(Value::as_str)
+        .ok_or_else(|| format!("missing string parameter at index {}", index))
+}
+
+impl SimulatedSubscription {
+    fn from_record(record: &SubscriptionRecord) -> Result<Self, String> {
+        let params = &record.params;
+        let params = match record.method.as_str() {
+            "accountSubscribe" => {
+                let config: RpcAccountInfoConfig = param(params, 1)?;
+                SimulatedParams::Account {
+                    pubkey: parse(param_str(params, 0)?, "pubkey")?,
+                    encoding: config.encoding.unwrap_or(UiAccountEncoding::Binary),
+                }
+            }
+            "programSubscribe" => {
+                let config: RpcProgramAccountsConfig = param(params, 1)?;
+                SimulatedParams::Program {
+                    pubkey: parse(param_str(params, 0)?, "pubkey")?,
+                    filters: config.filters.unwrap_or_default(),
+                    encoding: config
+                        .account_config
+                        .encoding
+                        .unwrap_or(UiAccountEncoding::Binary),
+                }
+            }
+            "logsSubscribe" => {
+                let filter: RpcTransactionLogsFilter =
+                    serde_json::from_value(params.get(0).cloned().unwrap_or(Value::Null))
+                        .map_err(|err| err.to_string())?;
+                SimulatedParams::Logs(match filter {
+                    RpcTransactionLogsFilter::All => LogsSubscriptionKind::All,
+                    RpcTransactionLogsFilter::AllWithVotes => LogsSubscriptionKind::AllWithVotes,
+                    RpcTransactionLogsFilter::Mentions(keys) => match keys.as_slice() {
+                        [key] => LogsSubscriptionKind::Single(parse(key, "mentions")?),
+                        _ => return Err("only 1 address supported in mentions".to_string()),
+                    },
+                })
+            }
+            "signatureSubscribe" => {
+                SimulatedParams::Signature(parse(param_str(params, 0)?, "signature")?)
+            }
+            "slotSubscribe" | "slotsUpdatesSubscribe" | "rootSubscribe" | "voteSubscribe" => {
+                SimulatedParams::NodeProgress
+            }
+            method => return Err(format!("unsupported method: {}", method)),
+        };
+        Ok(Self {
+            params,
+            count: record.count,
+        })
+    }
+}
+
+impl SlotWorkload {
+    pub fn from_record(record: &SlotWorkloadRecord) -> Result<Self, String> {
+        let account_writes = record
+            .account_writes
+            .iter()
+            .map(|write| {
+                Ok(AccountWrite {
+                    pubkey: parse(&write.pubkey, "pubkey")?,
+                    owner: parse(&write.owner, "owner")?,
+                    data_len: write.data_len,
+                    data: write
+                        .data
+                        .as_ref()
+                        .map(|data| base64::decode(data).map_err(|err| err.to_string()))
+                        .transpose()?,
+                })
+            })
+            .collect::<Result<_, String>>()?;
+        let transactions = record
+            .transactions
+            .iter()
+            .map(|transaction| {
+                Ok(TransactionWrite {
+                    signature: parse(&transaction.signature, "signature")?,
+                    mentions: transaction
+                        .mentions
+                        .iter()
+                        .map(|key| parse(key, "mentions"))
+                        .collect::<Result<_, String>>()?,
+                    is_vote: transaction.is_vote,
+                    log_bytes: transaction.log_bytes,
+                })
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Self {
+            slot_duration_ms: record.slot_duration_ms.max(1),
+            account_writes,
+            transactions,
+        })
+    }
+}
+
+fn encoded_data_len(data_len: usize, encoding: UiAccountEncoding) -> usize {
+    match encoding {
+        UiAccountEncoding::Binary | UiAccountEncoding::Base58 => {
+            if data_len <= MAX_BASE58_BYTES {
+                data_len * 138 / 100 + 1
+            } else {
+                "error: data too large for bs58 encoding".len()
+            }
+        }
+        // Compression ratios are data dependent, so plain base64 serves as an upper bound
+        UiAccountEncoding::Base64 | UiAccountEncoding::Base64Zstd => (data_len + 2) / 3 * 4,
+        UiAccountEncoding::JsonParsed => data_len * 2,
+    }
+}
+
+fn filters_match(filters: &[RpcFilterType], write: &AccountWrite) -> bool {
+    filters.iter().all(|filter| match filter {
+        RpcFilterType::DataSize(size) => write.data_len as u64 == *size,
+        RpcFilterType::Memcmp(compare) => write
+            .data
+            .as_ref()
+            .map(|data| compare.bytes_match(data))
+            .unwrap_or(true),
+    })
+}
+
+/// Notifications and serialized bytes generated by a single (deduplicated) subscription
+#[derive(Default)]
+struct Matches {
+    notifications: u64,
+    bytes: u64,
+}
+
+impl Matches {
+    fn add(&mut self, bytes: usize) {
+        self.notifications += 1;
+        self.bytes += (NOTIFICATION_OVERHEAD_BYTES + bytes) as u64;
+    }
+}
+
+fn logs_match(kind: &LogsSubscriptionKind, transaction: &TransactionWrite) -> bool {
+    match kind {
+        LogsSubscriptionKind::All => !transaction.is_vote,
+        LogsSubscriptionKind::AllWithVotes => true,
+        LogsSubscriptionKind::Single(pubkey) => transaction.mentions.contains(pubkey),
+    }
+}
+
+pub fn simulate(
+    subscriptions: &[SubscriptionRecord],
+    workload: &SlotWorkload,
+    implementation: MatchingImplementation,
+    cost_model: &CostModel,
+) -> Result<SimulationReport, String> {
+    // Identical params share one subscription (and one serialized notification) in
+    // `SubscriptionsTracker`, but every connection receives its own copy.
+    let mut deduplicated: Vec<SimulatedSubscription> = Vec::new();
+    for record in subscriptions {
+        let subscription = SimulatedSubscription::from_record(record)?;
+        match deduplicated
+            .iter_mut()
+            .find(|existing| existing.params == subscription.params)
+        {
+            Some(existing) => existing.count += subscription.count,
+            None => deduplicated.push(subscription),
+        }
+    }
+
+    let mut writes_by_owner: HashMap<Pubkey, Vec<&AccountWrite>> = HashMap::new();
+    for write in &workload.account_writes {
+        writes_by_owner.entry(write.owner).or_default().push(write);
+    }
+    let program_subscriptions: HashSet<Pubkey> = deduplicated
+        .iter()
+        .filter_map(|subscription| match &subscription.params {
+            SimulatedParams::Program { pubkey, .. } => Some(*pubkey),
+            _ => None,
+        })
+        .collect();
+
+    let mut match_ops = 0u64;
+    let mut notifications_per_slot = 0u64;
+    let mut serialized_bytes = 0u64;
+    let mut delivered_bytes = 0u64;
+
+    for subscription in &deduplicated {
+        let mut matches = Matches::default();
+        match &subscription.params {
+            SimulatedParams::Account { pubkey, encoding } => {
+                match_ops += match implementation {
+                    MatchingImplementation::Linear => 1,
+                    MatchingImplementation::Indexed => 0,
+                };
+                for write in workload
+                    .account_writes
+                    .iter()
+                    .filter(|write| &write.pubkey == pubkey)
+                {
+                    matches.add(encoded_data_len(write.data_len, *encoding));
+                }
+            }
+            SimulatedParams::Program {
+                pubkey,
+                filters,
+                encoding,
+            } => {
+                let writes = writes_by_owner
+                    .get(pubkey)
+                    .map(Vec::as_slice)
+                    .unwrap_or(&[]);
+                match_ops += match implementation {
+                    // Every subscription rescans the program's modified accounts
+                    MatchingImplementation::Linear => writes.len() as u64 * 2,
+                    MatchingImplementation::Indexed => writes.len() as u64,
+                };
+                for write in writes.iter().filter(|write| filters_match(filters, write)) {
+                    matches.add(encoded_data_len(write.data_len, *encoding));
+                }
+            }
+            SimulatedParams::Logs(kind) => {
+                for transaction in workload
+                    .transactions
+                    .iter()
+                    .filter(|transaction| logs_match(kind, transaction))
+                {
+                    match_ops += 1;
+                    matches.add(LOGS_OVERHEAD_BYTES + transaction.log_bytes);
+                }
+            }
+            SimulatedParams::Signature(signature) => {
+                match_ops += match implementation {
+                    MatchingImplementation::Linear => 1,
+                    MatchingImplementation::Indexed => 0,
+                };
+                if workload
+                    .transactions
+                    .iter()
+                    .any(|transaction| &transaction.signature == signature)
+                {
+                    matches.add(0);
+                }
+            }
+            SimulatedParams::NodeProgress => matches.add(0),
+        }
+        notifications_per_slot += matches.notifications * subscription.count as u64;
+        serialized_bytes += matches.bytes;
+        delivered_bytes += matches.bytes * subscription.count as u64;
+    }
+
+    if implementation == MatchingImplementation::Indexed {
+        // Building the indexes: one lookup per written account, owner and mentioned address
+        match_ops += workload.account_writes.len() as u64;
+        match_ops += workload
+            .account_writes
+            .iter()
+            .filter(|write| program_subscriptions.contains(&write.owner))
+            .count() as u64;
+        match_ops += workload
+            .transactions
+            .iter()
+            .map(|transaction| transaction.mentions.len() as u64 + 1)
+            .sum::<u64>();
+    }
+
+    let slots_per_sec = 1_000f64 / workload.slot_duration_ms as f64;
+    let busy_ns = match_ops * cost_model.ns_per_match_op
+        + serialized_bytes / 1024 * cost_model.ns_per_serialized_kb;
+    Ok(SimulationReport {
+        implementation,
+        match_ops,
+        notifications_per_slot,
+        notifications_per_sec: notifications_per_slot as f64 * slots_per_sec,
+        bytes_per_sec: delivered_bytes as f64 * slots_per_sec,
+        loop_utilization: busy_ns as f64 / (workload.slot_duration_ms as f64 * 1_000_000f64),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workload(program: &Pubkey, accounts: &[Pubkey]) -> SlotWorkload {
+        SlotWorkload::from_record(&SlotWorkloadRecord {
+            slot_duration_ms: 500,
+            account_writes: accounts
+                .iter()
+                .map(|pubkey| AccountWriteRecord {
+                    pubkey: pubkey.to_string(),
+                    owner: program.to_string(),
+                    data_len: 3,
+                    data: Some(base64::encode(&[1, 2, 3])),
+                })
+                .collect(),
+            transactions: vec![],
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_simulate_is_deterministic() {
+        let program = solana_sdk::pubkey::new_rand();
+        let accounts = vec![
+            solana_sdk::pubkey::new_rand(),
+            solana_sdk::pubkey::new_rand(),
+        ];
+        let workload = workload(&program, &accounts);
+        let subscriptions = vec![
+            SubscriptionRecord {
+                method: "accountSubscribe".to_string(),
+                params: vec![json!(accounts[0].to_string())],
+                count: 3,
+            },
+            SubscriptionRecord {
+                method: "programSubscribe".to_string(),
+                params: vec![json!(program.to_string()), json!({"encoding": "base64"})],
+                count: 1,
+            },
+            SubscriptionRecord {
+                method: "programSubscribe".to_string(),
+                params: vec![json!(program.to_string()), json!({"encoding": "base64"})],
+                count: 1,
+            },
+            SubscriptionRecord {
+                method: "slotSubscribe".to_string(),
+                params: vec![],
+                count: 1,
+            },
+        ];
+
+        let linear = simulate(
+            &subscriptions,
+            &workload,
+            MatchingImplementation::Linear,
+            &CostModel::default(),
+        )
+        .unwrap();
+        // 3 account notifications, 2 connections * 2 program accounts, 1 slot
+        assert_eq!(linear.notifications_per_slot, 8);
+        assert_eq!(linear.notifications_per_sec, 16.0);
+        assert_eq!(
+            linear,
+            simulate(
+                &subscriptions,
+                &workload,
+                MatchingImplementation::Linear,
+                &CostModel::default(),
+            )
+            .unwrap()
+        );
+
+        let indexed = simulate(
+            &subscriptions,
+            &workload,
+            MatchingImplementation::Indexed,
+            &CostModel::default(),
+        )
+        .unwrap();
+        assert_eq!(indexed.notifications_per_slot, 8);
+        assert_eq!(indexed.bytes_per_sec, linear.bytes_per_sec);
+    }
+
+    #[test]
+    fn test_simulate_rejects_unknown_method() {
+        let subscriptions = vec![SubscriptionRecord {
+            method: "fooSubscribe".to_string(),
+            params: vec![],
+            count: 1,
+        }];
+        let workload = workload(&Pubkey::default(), &[]);
+        assert!(simulate(
+            &subscriptions,
+            &workload,
+            MatchingImplementation::Linear,
+            &CostModel::default(),
+        )
+        .is_err());
+    }
+}
@@ -1,29 +1,45 @@
 use {
-    crate::rpc_subscriptions::{NotificationEntry, RpcNotification},
+    crate::{
+        rpc_pubsub_memory::MemoryBudget,
+        rpc_subscriptions::{
+            CborClients, DirectNotification, LogsReplayPermit, NotificationEntry,
+            RecentSignatureNotifications, RpcNotification,
+        },
+    },
     dashmap::{mapref::entry::Entry as DashEntry, DashMap},
     solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig},
-    solana_client::rpc_filter::RpcFilterType,
+    solana_client::{
+        rpc_config::RpcTransactionLogsStatusFilter, rpc_filter::RpcFilterType,
+        rpc_response::RpcMintState,
+    },
     solana_metrics::{CounterToken, TokenCounter},
     solana_runtime::{
+        accounts_update_notifier_interface::AccountsWriteListenerInterface,
         bank::{TransactionLogCollectorConfig, TransactionLogCollectorFilter},
         bank_forks::BankForks,
     },
     solana_sdk::{
-        clock::Slot, commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature,
+        account::AccountSharedData, clock::Slot, commitment_config::CommitmentConfig, hash::Hash,
+        pubkey::Pubkey, signature::Signature,
     },
+    solana_transaction_status::{TransactionDetails, UiTransactionEncoding},
     std::{
+        cmp::Reverse,
         collections::{
             hash_map::{Entry, HashMap},
             HashSet,
         },
-        fmt,
+        fmt, iter,
+        net::IpAddr,
+        slice,
         sync::{
-            atomic::{AtomicU64, Ordering},
+            atomic::{AtomicU64, AtomicUsize, Ordering},
             Arc, RwLock, Weak,
         },
+        time::{Duration, Instant},
     },
     thiserror::Error,
-    tokio::sync::broadcast,
+    tokio::sync::{broadcast, mpsc},
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -44,52 +60,138 @@ impl From<SubscriptionId> for u64 {
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum SubscriptionParams {
     Account(AccountSubscriptionParams),
+    Accounts(AccountsSubscriptionParams),
+    Block(BlockSubscriptionParams),
+    Blockhash(BlockhashSubscriptionParams),
+    BlockhashValidity(BlockhashValiditySubscriptionParams),
+    Epoch,
+    LeaderSchedule(LeaderScheduleSubscriptionParams),
     Logs(LogsSubscriptionParams),
+    Mint(MintSubscriptionParams),
+    PerformanceSamples,
     Program(ProgramSubscriptionParams),
+    ProgramChanges(ProgramChangesSubscriptionParams),
+    Rewards(RewardsSubscriptionParams),
     Signature(SignatureSubscriptionParams),
     Slot,
     SlotsUpdates,
-    Root,
-    Vote,
+    Root(RootSubscriptionParams),
+    Stake(StakeSubscriptionParams),
+    TokenAccountsByOwner(TokenAccountsByOwnerSubscriptionParams),
+    Transaction(TransactionSubscriptionParams),
+    Vote(VoteSubscriptionParams),
 }
 
 impl SubscriptionParams {
     fn method(&self) -> &'static str {
         match self {
             SubscriptionParams::Account(_) => "accountNotification",
+            SubscriptionParams::Accounts(_) => "accountsNotification",
+            SubscriptionParams::Block(_) => "blockNotification",
+            SubscriptionParams::Blockhash(_) => "blockhashNotification",
+            SubscriptionParams::BlockhashValidity(_) => "isBlockhashValidNotification",
+            SubscriptionParams::Epoch => "epochNotification",
+            SubscriptionParams::LeaderSchedule(_) => "leaderScheduleNotification",
             SubscriptionParams::Logs(_) => "logsNotification",
+            SubscriptionParams::Mint(_) => "mintNotification",
+            SubscriptionParams::PerformanceSamples => "performanceSamplesNotification",
             SubscriptionParams::Program(_) => "programNotification",
+            SubscriptionParams::ProgramChanges(_) => "programChangesNotification",
+            SubscriptionParams::Rewards(_) => "rewardsNotification",
             SubscriptionParams::Signature(_) => "signatureNotification",
             SubscriptionParams::Slot => "slotNotification",
             SubscriptionParams::SlotsUpdates => "slotsUpdatesNotification",
-            SubscriptionParams::Root => "rootNotification",
-            SubscriptionParams::Vote => "voteNotification",
+            SubscriptionParams::Root(_) => "rootNotification",
+            SubscriptionParams::Stake(_) => "stakeNotification",
+            SubscriptionParams::TokenAccountsByOwner(_) => "tokenAccountsByOwnerNotification",
+            SubscriptionParams::Transaction(_) => "transactionNotification",
+            SubscriptionParams::Vote(_) => "voteNotification",
         }
     }
 
-    fn commitment(&self) -> Option<CommitmentConfig> {
+    pub(crate) fn commitment(&self) -> Option<CommitmentConfig> {
         match self {
             SubscriptionParams::Account(params) => Some(params.commitment),
+            SubscriptionParams::Accounts(params) => Some(params.commitment),
+            SubscriptionParams::Block(params) => Some(params.commitment),
+            SubscriptionParams::Blockhash(params) => Some(params.commitment),
+            SubscriptionParams::BlockhashValidity(params) => Some(params.commitment),
             SubscriptionParams::Logs(params) => Some(params.commitment),
+            SubscriptionParams::Mint(params) => Some(params.commitment),
             SubscriptionParams::Program(params) => Some(params.commitment),
+            SubscriptionParams::ProgramChanges(params) => Some(params.commitment),
             SubscriptionParams::Signature(params) => Some(params.commitment),
-            SubscriptionParams::Slot
+            SubscriptionParams::Stake(params) => Some(params.commitment),
+            SubscriptionParams::TokenAccountsByOwner(params) => Some(params.commitment),
+            SubscriptionParams::Transaction(params) => Some(params.commitment),
+            SubscriptionParams::Epoch
+            | SubscriptionParams::LeaderSchedule(_)
+            | SubscriptionParams::PerformanceSamples
+            | SubscriptionParams::Rewards(_)
+            | SubscriptionParams::Slot
             | SubscriptionParams::SlotsUpdates
-            | SubscriptionParams::Root
-            | SubscriptionParams::Vote => None,
+            | SubscriptionParams::Root(_)
+            | SubscriptionParams::Vote(_) => None,
         }
     }
 
+    /// These params with the commitment cleared, for subscriptions whose notifications only
+    /// depend on the slot the commitment resolved to. Subscriptions that only differ in
+    /// commitment then produce the same payload for the same slot. `None` if notifications
+    /// also depend on what was previously reported to the subscription.
+    fn payload_params(&self) -> Option<SubscriptionParams> {
+        let commitment = CommitmentConfig::default();
+        let params = match self {
+            SubscriptionParams::Account(params) => {
+                SubscriptionParams::Account(AccountSubscriptionParams {
+                    commitment,
+                    ..params.clone()
+                })
+            }
+            SubscriptionParams::Program(params) => {
+                SubscriptionParams::Program(ProgramSubscriptionParams {
+                    commitment,
+                    ..params.clone()
+                })
+            }
+            SubscriptionParams::ProgramChanges(params) => {
+                SubscriptionParams::ProgramChanges(ProgramChangesSubscriptionParams {
+                    commitment,
+                    ..params.clone()
+                })
+            }
+            _ => return None,
+        };
+        Some(params)
+    }
+
     fn is_commitment_watcher(&self) -> bool {
         let commitment = match self {
             SubscriptionParams::Account(params) => &params.commitment,
+            SubscriptionParams::Accounts(params) => &params.commitment,
+            SubscriptionParams::Block(params) => &params.commitment,
+            SubscriptionParams::Blockhash(params) => &params.commitment,
+            SubscriptionParams::BlockhashValidity(params) => &params.commitment,
             SubscriptionParams::Logs(params) => &params.commitment,
+            SubscriptionParams::Mint(params) => &params.commitment,
             SubscriptionParams::Program(params) => &params.commitment,
-            SubscriptionParams::Signature(params) => &params.commitment,
-            SubscriptionParams::Slot
+            SubscriptionParams::ProgramChanges(params) => &params.commitment,
+            SubscriptionParams::Signature(params) => {
+                return params
+                    .commitments()
+                    .any(|commitment| !commitment.is_confirmed())
+            }
+            SubscriptionParams::Stake(params) => &params.commitment,
+            SubscriptionParams::TokenAccountsByOwner(params) => &params.commitment,
+            SubscriptionParams::Transaction(params) => &params.commitment,
+            SubscriptionParams::Epoch
+            | SubscriptionParams::LeaderSchedule(_)
+            | SubscriptionParams::PerformanceSamples
+            | SubscriptionParams::Rewards(_)
+            | SubscriptionParams::Slot
             | SubscriptionParams::SlotsUpdates
-            | SubscriptionParams::Root
-            | SubscriptionParams::Vote => return false,
+            | SubscriptionParams::Root(_)
+            | SubscriptionParams::Vote(_) => return false,
         };
         !commitment.is_confirmed()
     }
@@ -97,24 +199,69 @@ impl SubscriptionParams {
     fn is_gossip_watcher(&self) -> bool {
         let commitment = match self {
             SubscriptionParams::Account(params) => &params.commitment,
+            SubscriptionParams::Accounts(params) => &params.commitment,
+            SubscriptionParams::Block(params) => &params.commitment,
+            SubscriptionParams::Blockhash(params) => &params.commitment,
+            SubscriptionParams::BlockhashValidity(params) => &params.commitment,
             SubscriptionParams::Logs(params) => &params.commitment,
+            SubscriptionParams::Mint(params) => &params.commitment,
             SubscriptionParams::Program(params) => &params.commitment,
-            SubscriptionParams::Signature(params) => &params.commitment,
-            SubscriptionParams::Slot
+            SubscriptionParams::ProgramChanges(params) => &params.commitment,
+            SubscriptionParams::Signature(params) => {
+                return params
+                    .commitments()
+                    .any(|commitment| commitment.is_confirmed())
+            }
+            SubscriptionParams::Stake(params) => &params.commitment,
+            SubscriptionParams::TokenAccountsByOwner(params) => &params.commitment,
+            SubscriptionParams::Transaction(params) => &params.commitment,
+            SubscriptionParams::Epoch
+            | SubscriptionParams::LeaderSchedule(_)
+            | SubscriptionParams::PerformanceSamples
+            | SubscriptionParams::Rewards(_)
+            | SubscriptionParams::Slot
             | SubscriptionParams::SlotsUpdates
-            | SubscriptionParams::Root
-            | SubscriptionParams::Vote => return false,
+            | SubscriptionParams::Root(_)
+            | SubscriptionParams::Vote(_) => return false,
         };
         commitment.is_confirmed()
     }
 
-    fn is_node_progress_watcher(&self) -> bool {
+    /// High-volume streams that are broadcast on their own channel, so that slow
+    /// consumers of these streams cannot cause other subscribers to lag.
+    pub(crate) fn is_firehose(&self) -> bool {
         matches!(
             self,
-            SubscriptionParams::Slot
+            SubscriptionParams::LeaderSchedule(_)
+                | SubscriptionParams::Slot
                 | SubscriptionParams::SlotsUpdates
-                | SubscriptionParams::Root
-                | SubscriptionParams::Vote
+                | SubscriptionParams::Vote(_)
+        )
+    }
+
+    /// Accounts whose writes are reported to this subscription by the accounts write
+    /// listener; the subscription is only checked after one of them was written.
+    fn written_accounts(&self) -> &[Pubkey] {
+        match self {
+            SubscriptionParams::Account(params) => slice::from_ref(&params.pubkey),
+            SubscriptionParams::Accounts(params) => &params.pubkeys,
+            SubscriptionParams::Mint(params) => slice::from_ref(&params.pubkey),
+            SubscriptionParams::Stake(params) => slice::from_ref(&params.pubkey),
+            _ => &[],
+        }
+    }
+
+    pub(crate) fn is_node_progress_watcher(&self) -> bool {
+        matches!(
+            self,
+            SubscriptionParams::Epoch
+                | SubscriptionParams::LeaderSchedule(_)
+                | SubscriptionParams::PerformanceSamples
+                | SubscriptionParams::Rewards(_)
+                | SubscriptionParams::Slot
+                | SubscriptionParams::SlotsUpdates
+                | SubscriptionParams::Root(_)
+                | SubscriptionParams::Vote(_)
         )
     }
 }
@@ -125,12 +272,61 @@ pub struct AccountSubscriptionParams {
     pub encoding: UiAccountEncoding,
     pub data_slice: Option<UiDataSliceConfig>,
     pub commitment: CommitmentConfig,
+    /// zstd level of "base64+zstd" notifications, zstd's default if `None`
+    pub compression_level: Option<i32>,
+    /// Accompany notifications with the proof that the account was written
+    pub with_proof: bool,
+}
+
+/// A single subscription over a set of accounts. `pubkeys` is kept sorted and
+/// deduplicated so that equivalent requests share one subscription.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AccountsSubscriptionParams {
+    pub pubkeys: Vec<Pubkey>,
+    pub encoding: UiAccountEncoding,
+    pub data_slice: Option<UiDataSliceConfig>,
+    pub commitment: CommitmentConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BlockhashSubscriptionParams {
+    pub commitment: CommitmentConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BlockhashValiditySubscriptionParams {
+    pub blockhash: Hash,
+    pub commitment: CommitmentConfig,
+    pub expiry_warning_blocks: Option<u64>,
+}
+
+/// What a blockhash validity subscription has learned and reported so far
+#[derive(Debug, Default)]
+pub struct BlockhashValidityState {
+    pub last_valid_block_height: Option<u64>,
+    pub expiry_warning_sent: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BlockSubscriptionParams {
+    pub commitment: CommitmentConfig,
+    pub encoding: UiTransactionEncoding,
+    pub kind: BlockSubscriptionKind,
+    pub transaction_details: TransactionDetails,
+    pub show_rewards: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum BlockSubscriptionKind {
+    All,
+    MentionsAccountOrProgram(Pubkey),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct LogsSubscriptionParams {
     pub kind: LogsSubscriptionKind,
     pub commitment: CommitmentConfig,
+    pub filter_status: RpcTransactionLogsStatusFilter,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -140,6 +336,21 @@ pub enum LogsSubscriptionKind {
     Single(Pubkey),
 }
 
+/// All SPL token accounts owned by a wallet, found through the owner secondary index
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TokenAccountsByOwnerSubscriptionParams {
+    pub owner: Pubkey,
+    pub encoding: UiAccountEncoding,
+    pub data_slice: Option<UiDataSliceConfig>,
+    pub commitment: CommitmentConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MintSubscriptionParams {
+    pub pubkey: Pubkey,
+    pub commitment: CommitmentConfig,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ProgramSubscriptionParams {
     pub pubkey: Pubkey,
@@ -147,7 +358,24 @@ pub struct ProgramSubscriptionParams {
     pub encoding: UiAccountEncoding,
     pub data_slice: Option<UiDataSliceConfig>,
     pub commitment: CommitmentConfig,
+    /// zstd level of "base64+zstd" notifications, zstd's default if `None`
+    pub compression_level: Option<i32>,
     pub with_context: bool,
+    /// Notify the accounts that changed in a slot as one array
+    pub batch: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ProgramChangesSubscriptionParams {
+    pub pubkey: Pubkey,
+    pub commitment: CommitmentConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TransactionSubscriptionParams {
+    pub pubkey: Pubkey,
+    pub commitment: CommitmentConfig,
+    pub encoding: UiTransactionEncoding,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -155,6 +383,56 @@ pub struct SignatureSubscriptionParams {
     pub signature: Signature,
     pub commitment: CommitmentConfig,
     pub enable_received_notification: bool,
+    /// Higher commitment levels at which the result is notified again after `commitment`,
+    /// in increasing order. The subscription ends after the last one.
+    pub followup_commitments: Vec<CommitmentConfig>,
+}
+
+impl SignatureSubscriptionParams {
+    /// Every commitment level the subscription is notified at, in order
+    pub fn commitments(&self) -> impl Iterator<Item = CommitmentConfig> + '_ {
+        iter::once(self.commitment).chain(self.followup_commitments.iter().copied())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct VoteSubscriptionParams {
+    /// Sorted vote account pubkeys to notify votes for, or all votes if `None`
+    pub vote_pubkeys: Option<Vec<Pubkey>>,
+}
+
+impl VoteSubscriptionParams {
+    pub fn matches(&self, vote_pubkey: &Pubkey) -> bool {
+        self.vote_pubkeys
+            .as_ref()
+            .map(|vote_pubkeys| vote_pubkeys.binary_search(vote_pubkey).is_ok())
+            .unwrap_or(true)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RewardsSubscriptionParams {
+    /// Sorted addresses to notify staking and voting rewards for
+    pub addresses: Vec<Pubkey>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StakeSubscriptionParams {
+    pub pubkey: Pubkey,
+    pub commitment: CommitmentConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LeaderScheduleSubscriptionParams {
+    /// Validator identity to notify leader slots for, or every leader slot if `None`
+    pub identity: Option<Pubkey>,
+    pub num_slots: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct RootSubscriptionParams {
+    /// Notify with the root's blockhash, block height and parent instead of the bare slot
+    pub enable_block_info: bool,
 }
 
 #[derive(Clone)]
@@ -164,24 +442,61 @@ struct SubscriptionControlInner {
     subscriptions: DashMap<SubscriptionParams, Weak<SubscriptionTokenInner>>,
     next_id: AtomicU64,
     max_active_subscriptions: usize,
+    max_subscriptions_per_ip: usize,
+    subscriptions_per_ip: DashMap<IpAddr, usize>,
     sender: crossbeam_channel::Sender<NotificationEntry>,
+    /// Receives the changes of node progress subscriptions, which are notified by a
+    /// thread of their own
+    node_progress_sender: crossbeam_channel::Sender<NotificationEntry>,
     broadcast_sender: broadcast::Sender<RpcNotification>,
+    firehose_broadcast_sender: broadcast::Sender<RpcNotification>,
+    memory_budget: MemoryBudget,
+    recent_signatures: RecentSignatureNotifications,
     counter: TokenCounter,
+    /// Subscriptions picked up by the notifier threads, for `active_subscriptions`
+    infos: SubscriptionInfos,
+    cbor_clients: CborClients,
+}
+
+impl SubscriptionControlInner {
+    fn sender_for(
+        &self,
+        params: &SubscriptionParams,
+    ) -> &crossbeam_channel::Sender<NotificationEntry> {
+        if params.is_node_progress_watcher() {
+            &self.node_progress_sender
+        } else {
+            &self.sender
+        }
+    }
 }
 
 impl SubscriptionControl {
     pub fn new(
         max_active_subscriptions: usize,
+        max_subscriptions_per_ip: usize,
         sender: crossbeam_channel::Sender<NotificationEntry>,
+        node_progress_sender: crossbeam_channel::Sender<NotificationEntry>,
         broadcast_sender: broadcast::Sender<RpcNotification>,
+        firehose_broadcast_sender: broadcast::Sender<RpcNotification>,
+        memory_budget: MemoryBudget,
+        recent_signatures: RecentSignatureNotifications,
     ) -> Self {
         Self(Arc::new(SubscriptionControlInner {
             subscriptions: DashMap::new(),
             next_id: AtomicU64::new(0),
             max_active_subscriptions,
+            max_subscriptions_per_ip,
+            subscriptions_per_ip: DashMap::new(),
             sender,
+            node_progress_sender,
             broadcast_sender,
+            firehose_broadcast_sender,
+            memory_budget,
+            recent_signatures,
             counter: TokenCounter::new("rpc_pubsub_total_subscriptions"),
+            infos: SubscriptionInfos::default(),
+            cbor_clients: CborClients::default(),
         }))
     }
 
@@ -189,52 +504,244 @@ impl SubscriptionControl {
         self.0.broadcast_sender.subscribe()
     }
 
+    pub fn firehose_broadcast_receiver(&self) -> broadcast::Receiver<RpcNotification> {
+        self.0.firehose_broadcast_sender.subscribe()
+    }
+
+    pub fn memory_budget(&self) -> &MemoryBudget {
+        &self.0.memory_budget
+    }
+
+    pub fn cbor_clients(&self) -> &CborClients {
+        &self.0.cbor_clients
+    }
+
+    pub fn recent_signatures(&self) -> &RecentSignatureNotifications {
+        &self.0.recent_signatures
+    }
+
+    /// Registry that the notifier threads keep the `SubscriptionInfo` of every subscription
+    /// they track in
+    pub(crate) fn infos(&self) -> &SubscriptionInfos {
+        &self.0.infos
+    }
+
+    /// Asks the notifier to send the current state of the account to a single connection,
+    /// ahead of the changes streamed by subscription `id`.
+    pub fn send_initial_account_state(
+        &self,
+        params: AccountSubscriptionParams,
+        id: SubscriptionId,
+        sender: mpsc::Sender<DirectNotification>,
+    ) {
+        let _ = self
+            .0
+            .sender
+            .send(NotificationEntry::InitialAccountState(params, id, sender));
+    }
+
+    /// Asks the notifier to scan the accounts owned by the program and send the ones
+    /// matching the subscription to a single connection, ahead of the changes streamed by
+    /// subscription `id`.
+    pub fn send_initial_program_accounts(
+        &self,
+        params: ProgramSubscriptionParams,
+        id: SubscriptionId,
+        sender: mpsc::Sender<DirectNotification>,
+    ) {
+        let _ = self
+            .0
+            .sender
+            .send(NotificationEntry::InitialProgramAccounts(
+                params, id, sender,
+            ));
+    }
+
+    /// Asks the notifier to read the logs of the transactions from `start_slot` on out of
+    /// the blockstore and send the ones matching the subscription to a single connection,
+    /// ahead of the logs streamed by subscription `id`. The connection holds the logs
+    /// streamed in the meantime until it receives `DirectNotification::ReplayFinished`.
+    pub fn send_logs_replay(
+        &self,
+        params: LogsSubscriptionParams,
+        start_slot: Slot,
+        id: SubscriptionId,
+        sender: mpsc::Sender<DirectNotification>,
+        permit: LogsReplayPermit,
+    ) {
+        // A connection that isn't draining its direct notifications gets no replay
+        if sender
+            .try_send(DirectNotification::ReplayStarted(id))
+            .is_err()
+        {
+            return;
+        }
+        let _ = self.0.sender.send(NotificationEntry::LogsReplay(
+            params, start_slot, id, sender, permit,
+        ));
+    }
+
     pub fn subscribe(&self, params: SubscriptionParams) -> Result<SubscriptionToken, Error> {
+        self.subscribe_from(params, None)
+    }
+
+    /// Like `subscribe`, but also counts the subscription against the per-IP limit of
+    /// `client_ip` until the returned token is dropped.
+    pub fn subscribe_from(
+        &self,
+        params: SubscriptionParams,
+        client_ip: Option<IpAddr>,
+    ) -> Result<SubscriptionToken, Error> {
+        let client_token = client_ip
+            .map(|ip| self.acquire_client_slot(ip))
+            .transpose()?;
         debug!(
             "Total existing subscriptions: {}",
             self.0.subscriptions.len()
         );
         let count = self.0.subscriptions.len();
-        match self.0.subscriptions.entry(params) {
-            DashEntry::Occupied(entry) => Ok(SubscriptionToken(
-                entry
-                    .get()
-                    .upgrade()
-                    .expect("dead subscription encountered in SubscriptionControl"),
-                self.0.counter.create_token(),
-            )),
+        let inner = match self.0.subscriptions.entry(params) {
+            DashEntry::Occupied(entry) => entry
+                .get()
+                .upgrade()
+                .expect("dead subscription encountered in SubscriptionControl"),
             DashEntry::Vacant(entry) => {
                 if count >= self.0.max_active_subscriptions {
                     inc_new_counter_info!("rpc-subscription-refused-limit-reached", 1);
                     return Err(Error::TooManySubscriptions);
                 }
                 let id = SubscriptionId::from(self.0.next_id.fetch_add(1, Ordering::AcqRel));
-                let token = SubscriptionToken(
-                    Arc::new(SubscriptionTokenInner {
-                        control: Arc::clone(&self.0),
-                        params: entry.key().clone(),
-                        id,
-                    }),
-                    self.0.counter.create_token(),
-                );
+                let inner = Arc::new(SubscriptionTokenInner {
+                    control: Arc::clone(&self.0),
+                    params: entry.key().clone(),
+                    id,
+                    created_at: Instant::now(),
+                    clients: DashMap::new(),
+                });
                 let _ = self
                     .0
-                    .sender
-                    .send(NotificationEntry::Subscribed(token.0.params.clone(), id));
-                entry.insert(Arc::downgrade(&token.0));
+                    .sender_for(&inner.params)
+                    .send(NotificationEntry::Subscribed(inner.params.clone(), id));
+                entry.insert(Arc::downgrade(&inner));
                 datapoint_info!(
                     "rpc-subscription",
                     ("total", self.0.subscriptions.len(), i64)
                 );
-                Ok(token)
+                inner
             }
+        };
+        let client_token = client_token.map(|client_token| client_token.hold(&inner));
+        // Notifications sent before the client subscribed were not skipped by it
+        let delivered = self
+            .0
+            .infos
+            .get(&inner.id)
+            .map(|info| info.stats.notifications())
+            .unwrap_or_default();
+        Ok(SubscriptionToken(
+            inner,
+            self.0.counter.create_token(),
+            client_token,
+            Arc::new(AtomicU64::new(delivered)),
+        ))
+    }
+
+    /// Subscribes to every item and registers them together under a new id, so that a
+    /// connection can route their notifications to a single composite subscription. Items
+    /// are shared with regular subscriptions to the same params; nothing is subscribed if
+    /// any of the items is refused.
+    pub fn subscribe_composite(
+        &self,
+        items: Vec<(String, SubscriptionParams)>,
+        client_ip: Option<IpAddr>,
+    ) -> Result<CompositeSubscriptionToken, Error> {
+        let items = items
+            .into_iter()
+            .map(|(tag, params)| {
+                self.subscribe_from(params, client_ip)
+                    .map(|token| (Arc::from(tag), token))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let id = SubscriptionId::from(self.0.next_id.fetch_add(1, Ordering::AcqRel));
+        inc_new_counter_info!("rpc-subscription-composite", 1);
+        Ok(CompositeSubscriptionToken { id, items })
+    }
+
+    fn acquire_client_slot(&self, ip: IpAddr) -> Result<ClientToken, Error> {
+        let mut count = self.0.subscriptions_per_ip.entry(ip).or_insert(0);
+        if *count >= self.0.max_subscriptions_per_ip {
+            inc_new_counter_info!("rpc-subscription-refused-client-limit-reached", 1);
+            return Err(Error::TooManyClientSubscriptions);
         }
+        *count += 1;
+        Ok(ClientToken {
+            control: Arc::clone(&self.0),
+            ip,
+            subscription: Weak::new(),
+        })
     }
 
     pub fn total(&self) -> usize {
         self.0.subscriptions.len()
     }
 
+    /// Number of subscriptions currently held by connections from `ip`
+    pub fn total_from(&self, ip: &IpAddr) -> usize {
+        self.0
+            .subscriptions_per_ip
+            .get(ip)
+            .map(|count| *count)
+            .unwrap_or_default()
+    }
+
+    /// Snapshot of the active subscriptions, ordered by id
+    pub fn active_subscriptions(&self) -> Vec<ActiveSubscription> {
+        let mut subscriptions: Vec<_> = self
+            .0
+            .subscriptions
+            .iter()
+            .filter_map(|item| item.value().upgrade())
+            .map(|inner| {
+                let mut clients: Vec<_> = inner
+                    .clients
+                    .iter()
+                    .map(|client| (*client.key(), *client.value()))
+                    .collect();
+                clients.sort_unstable();
+                let info = self
+                    .0
+                    .infos
+                    .get(&inner.id)
+                    .map(|info| Arc::clone(info.value()));
+                ActiveSubscription {
+                    id: inner.id,
+                    method: inner.params.method(),
+                    params: inner.params.clone(),
+                    commitment: inner.params.commitment(),
+                    age: inner.created_at.elapsed(),
+                    last_notified_slot: info
+                        .as_ref()
+                        .map(|info| *info.last_notified_slot.read().unwrap()),
+                    notifications: info
+                        .as_ref()
+                        .map(|info| info.stats.notifications())
+                        .unwrap_or_default(),
+                    bytes: info
+                        .as_ref()
+                        .map(|info| info.stats.bytes())
+                        .unwrap_or_default(),
+                    lag_drops: info
+                        .as_ref()
+                        .map(|info| info.stats.lag_drops())
+                        .unwrap_or_default(),
+                    clients,
+                }
+            })
+            .collect();
+        subscriptions.sort_unstable_by_key(|subscription| u64::from(subscription.id));
+        subscriptions
+    }
+
     #[cfg(test)]
     pub fn assert_subscribed(&self, params: &SubscriptionParams) {
         assert!(self.0.subscriptions.contains_key(params));
@@ -268,16 +775,149 @@ impl SubscriptionControl {
     }
 }
 
+/// `SubscriptionInfo`s of the subscriptions tracked by the notifier threads, by id
+pub(crate) type SubscriptionInfos = Arc<DashMap<SubscriptionId, Arc<SubscriptionInfo>>>;
+
+/// State of an active subscription, as reported by `SubscriptionControl::active_subscriptions`
+#[derive(Debug, Clone)]
+pub struct ActiveSubscription {
+    pub id: SubscriptionId,
+    pub method: &'static str,
+    pub params: SubscriptionParams,
+    pub commitment: Option<CommitmentConfig>,
+    pub age: Duration,
+    /// `None` until a notifier thread has picked up the subscription
+    pub last_notified_slot: Option<Slot>,
+    pub notifications: u64,
+    pub bytes: u64,
+    pub lag_drops: u64,
+    /// Addresses of the clients holding the subscription, along with the number of times
+    /// each of them subscribed to it. Subscriptions made by the validator itself have none.
+    pub clients: Vec<(IpAddr, usize)>,
+}
+
+/// Delivery counters of a subscription. Notifications are counted once however many
+/// clients hold the subscription.
+#[derive(Debug, Default)]
+pub struct SubscriptionStats {
+    notifications: AtomicU64,
+    bytes: AtomicU64,
+    /// Notifications skipped by lagging clients
+    lag_drops: AtomicU64,
+}
+
+impl SubscriptionStats {
+    /// Counts a notification and returns its sequence number, starting from 1
+    pub fn record_notification(&self, bytes: usize) -> u64 {
+        self.bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+        self.notifications.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    pub fn record_lag_drops(&self, skipped: u64) {
+        self.lag_drops.fetch_add(skipped, Ordering::Relaxed);
+    }
+
+    pub fn notifications(&self) -> u64 {
+        self.notifications.load(Ordering::Relaxed)
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    pub fn lag_drops(&self) -> u64 {
+        self.lag_drops.load(Ordering::Relaxed)
+    }
+}
+
+/// Number of subscriptions reported by `report_subscription_stats`
+const REPORTED_SUBSCRIPTIONS: usize = 10;
+
+/// Submits the counters of the subscriptions that sent the most bytes
+pub(crate) fn report_subscription_stats(infos: &SubscriptionInfos) {
+    let mut infos: Vec<_> = infos.iter().map(|info| Arc::clone(info.value())).collect();
+    infos.sort_unstable_by_key(|info| Reverse(info.stats.bytes()));
+    for info in infos.iter().take(REPORTED_SUBSCRIPTIONS) {
+        datapoint_info!(
+            "rpc-subscription-stats",
+            ("id", u64::from(info.id), i64),
+            ("method", info.method, String),
+            ("notifications", info.stats.notifications(), i64),
+            ("bytes", info.stats.bytes(), i64),
+            ("lag_drops", info.stats.lag_drops(), i64)
+        );
+    }
+}
+
+/// What a subscription remembers between notifications beyond `last_notified_slot`,
+/// for the kinds of subscriptions that need it
+#[derive(Debug)]
+enum SubscriptionState {
+    None,
+    /// Mint state as of `last_notified_slot`, used by mint subscriptions to report the
+    /// previous values alongside the new ones
+    Mint(RwLock<Option<RpcMintState>>),
+    /// Per-account modified slots already reported by multi-account and token owner
+    /// subscriptions
+    AccountSlots(RwLock<HashMap<Pubkey, Slot>>),
+    /// Number of commitment levels already notified by signature subscriptions with
+    /// follow-up commitments
+    CommitmentLevels(AtomicUsize),
+    BlockhashValidity(RwLock<BlockhashValidityState>),
+}
+
+impl SubscriptionState {
+    fn new(params: &SubscriptionParams) -> Self {
+        match params {
+            SubscriptionParams::Mint(_) => SubscriptionState::Mint(RwLock::new(None)),
+            SubscriptionParams::Accounts(_) | SubscriptionParams::TokenAccountsByOwner(_) => {
+                SubscriptionState::AccountSlots(RwLock::default())
+            }
+            SubscriptionParams::Signature(params) if !params.followup_commitments.is_empty() => {
+                SubscriptionState::CommitmentLevels(AtomicUsize::new(0))
+            }
+            SubscriptionParams::BlockhashValidity(_) => {
+                SubscriptionState::BlockhashValidity(RwLock::default())
+            }
+            _ => SubscriptionState::None,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct SubscriptionInfo {
     id: SubscriptionId,
     params: SubscriptionParams,
     method: &'static str,
     pub last_notified_slot: RwLock<Slot>,
+    state: SubscriptionState,
+    /// Highest slot plus one in which a watched account was written without the
+    /// commitment slot covering it yet; zero if there is no such write
+    pending_account_write: AtomicU64,
     commitment: Option<CommitmentConfig>,
+    payload_params: Option<Arc<SubscriptionParams>>,
+    pub stats: SubscriptionStats,
 }
 
 impl SubscriptionInfo {
+    pub(crate) fn new(
+        params: SubscriptionParams,
+        id: SubscriptionId,
+        last_notified_slot: Slot,
+    ) -> Self {
+        Self {
+            last_notified_slot: RwLock::new(last_notified_slot),
+            state: SubscriptionState::new(&params),
+            pending_account_write: AtomicU64::new(0),
+            id,
+            commitment: params.commitment(),
+            payload_params: params.payload_params().map(Arc::new),
+            method: params.method(),
+            params,
+            stats: SubscriptionStats::default(),
+        }
+    }
+
     pub fn id(&self) -> SubscriptionId {
         self.id
     }
@@ -293,12 +933,123 @@ impl SubscriptionInfo {
     pub fn commitment(&self) -> Option<CommitmentConfig> {
         self.commitment
     }
+
+    /// Mint state last reported, for mint subscriptions
+    pub fn last_notified_mint(&self) -> Option<&RwLock<Option<RpcMintState>>> {
+        match &self.state {
+            SubscriptionState::Mint(mint) => Some(mint),
+            _ => None,
+        }
+    }
+
+    /// Modified slots of the accounts last reported, for multi-account and token owner
+    /// subscriptions
+    pub fn last_notified_account_slots(&self) -> Option<&RwLock<HashMap<Pubkey, Slot>>> {
+        match &self.state {
+            SubscriptionState::AccountSlots(account_slots) => Some(account_slots),
+            _ => None,
+        }
+    }
+
+    /// Number of commitment levels notified, for signature subscriptions with follow-up
+    /// commitments
+    pub fn commitment_levels_notified(&self) -> Option<&AtomicUsize> {
+        match &self.state {
+            SubscriptionState::CommitmentLevels(levels) => Some(levels),
+            _ => None,
+        }
+    }
+
+    /// What was learned and reported, for blockhash validity subscriptions
+    pub fn blockhash_validity(&self) -> Option<&RwLock<BlockhashValidityState>> {
+        match &self.state {
+            SubscriptionState::BlockhashValidity(state) => Some(state),
+            _ => None,
+        }
+    }
+
+    /// Key under which the serialized results of this subscription can be shared with
+    /// other subscriptions, see `SubscriptionParams::payload_params`
+    pub fn payload_params(&self) -> Option<&Arc<SubscriptionParams>> {
+        self.payload_params.as_ref()
+    }
+
+    /// Whether the accounts write listener reports writes for this subscription
+    pub fn tracks_account_writes(&self) -> bool {
+        !self.params.written_accounts().is_empty()
+    }
+
+    fn mark_account_written(&self, slot: Slot) {
+        self.pending_account_write
+            .fetch_max(slot + 1, Ordering::Relaxed);
+    }
+
+    /// Highest slot with a watched account write not yet covered by the commitment slot
+    pub fn pending_account_write(&self) -> Option<Slot> {
+        self.pending_account_write
+            .load(Ordering::Relaxed)
+            .checked_sub(1)
+    }
+
+    /// Forget the pending write at `slot`, unless a later write was reported meanwhile
+    pub fn clear_account_write(&self, slot: Slot) {
+        let _ = self.pending_account_write.compare_exchange(
+            slot + 1,
+            0,
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+        );
+    }
+}
+
+/// Account-keyed subscriptions by written account. Installed as the accounts write
+/// listener of the bank forks, so that account writes mark only the subscriptions
+/// watching them and the notifier skips the rest.
+#[derive(Debug, Default)]
+struct AccountWritesIndex {
+    by_pubkey: DashMap<Pubkey, HashMap<SubscriptionId, Arc<SubscriptionInfo>>>,
+}
+
+impl AccountWritesIndex {
+    fn add(&self, info: &Arc<SubscriptionInfo>) {
+        for pubkey in info.params.written_accounts() {
+            self.by_pubkey
+                .entry(*pubkey)
+                .or_default()
+                .insert(info.id, Arc::clone(info));
+        }
+    }
+
+    fn remove(&self, params: &SubscriptionParams, id: SubscriptionId) {
+        for pubkey in params.written_accounts() {
+            if let DashEntry::Occupied(mut entry) = self.by_pubkey.entry(*pubkey) {
+                entry.get_mut().remove(&id);
+                if entry.get().is_empty() {
+                    entry.remove();
+                }
+            } else {
+                warn!("Subscriptions inconsistency (missing entry in account_writes)");
+            }
+        }
+    }
+}
+
+impl AccountsWriteListenerInterface for AccountWritesIndex {
+    fn notify_account_write(&self, slot: Slot, pubkey: &Pubkey, _account: &AccountSharedData) {
+        if let Some(subscriptions) = self.by_pubkey.get(pubkey) {
+            for info in subscriptions.values() {
+                info.mark_account_written(slot);
+            }
+        }
+    }
 }
 
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("node subscription limit reached")]
     TooManySubscriptions,
+    #[error("client subscription limit reached")]
+    TooManyClientSubscriptions,
 }
 
 struct LogsSubscriptionsIndex {
@@ -367,30 +1118,85 @@ impl LogsSubscriptionsIndex {
     }
 }
 
+/// Number of program and program changes subscriptions by program. The programs are the
+/// owners indexed in the accounts write cache, so that the writes of a slot are looked up
+/// per program without a scan.
+struct ProgramSubscriptionsIndex {
+    count: HashMap<Pubkey, usize>,
+
+    bank_forks: Arc<RwLock<BankForks>>,
+}
+
+impl ProgramSubscriptionsIndex {
+    fn add(&mut self, program_id: Pubkey) {
+        *self.count.entry(program_id).or_default() += 1;
+        self.update_config();
+    }
+
+    fn remove(&mut self, program_id: Pubkey) {
+        match self.count.entry(program_id) {
+            Entry::Occupied(mut entry) => {
+                *entry.get_mut() -= 1;
+                if *entry.get() == 0 {
+                    entry.remove();
+                }
+            }
+            Entry::Vacant(_) => error!("missing entry in program subscriptions count"),
+        }
+        self.update_config();
+    }
+
+    fn update_config(&self) {
+        self.bank_forks
+            .read()
+            .unwrap()
+            .root_bank()
+            .rc
+            .accounts
+            .accounts_db
+            .set_indexed_owners(self.count.keys().copied().collect());
+    }
+}
+
 pub struct SubscriptionsTracker {
     logs_subscriptions_index: LogsSubscriptionsIndex,
+    program_subscriptions_index: ProgramSubscriptionsIndex,
+    account_writes: Arc<AccountWritesIndex>,
     by_signature: HashMap<Signature, HashMap<SubscriptionId, Arc<SubscriptionInfo>>>,
-    // Accounts, logs, programs, signatures (not gossip)
+    // Accounts, account sets, blocks, logs, mints, programs, program changes, signatures,
+    // transactions (not gossip)
     commitment_watchers: HashMap<SubscriptionId, Arc<SubscriptionInfo>>,
-    // Accounts, logs, programs, signatures (gossip)
+    // Accounts, account sets, blocks, logs, mints, programs, program changes, signatures,
+    // transactions (gossip)
     gossip_watchers: HashMap<SubscriptionId, Arc<SubscriptionInfo>>,
-    // Slots, slots updates, roots, votes.
-    node_progress_watchers: HashMap<SubscriptionParams, Arc<SubscriptionInfo>>,
 }
 
 impl SubscriptionsTracker {
     pub fn new(bank_forks: Arc<RwLock<BankForks>>) -> Self {
+        let account_writes = Arc::new(AccountWritesIndex::default());
+        bank_forks
+            .read()
+            .unwrap()
+            .root_bank()
+            .rc
+            .accounts
+            .accounts_db
+            .set_accounts_write_listener(Some(account_writes.clone()));
         SubscriptionsTracker {
             logs_subscriptions_index: LogsSubscriptionsIndex {
                 all_count: 0,
                 all_with_votes_count: 0,
                 single_count: HashMap::new(),
+                bank_forks: bank_forks.clone(),
+            },
+            program_subscriptions_index: ProgramSubscriptionsIndex {
+                count: HashMap::new(),
                 bank_forks,
             },
+            account_writes,
             by_signature: HashMap::new(),
             commitment_watchers: HashMap::new(),
             gossip_watchers: HashMap::new(),
-            node_progress_watchers: HashMap::new(),
         }
     }
 
@@ -399,18 +1205,22 @@ impl SubscriptionsTracker {
         params: SubscriptionParams,
         id: SubscriptionId,
         last_notified_slot: impl FnOnce() -> Slot,
-    ) {
-        let info = Arc::new(SubscriptionInfo {
-            last_notified_slot: RwLock::new(last_notified_slot()),
+    ) -> Arc<SubscriptionInfo> {
+        let info = Arc::new(SubscriptionInfo::new(
+            params.clone(),
             id,
-            commitment: params.commitment(),
-            method: params.method(),
-            params: params.clone(),
-        });
+            last_notified_slot(),
+        ));
         match &params {
             SubscriptionParams::Logs(params) => {
                 self.logs_subscriptions_index.add(params);
             }
+            SubscriptionParams::Program(params) => {
+                self.program_subscriptions_index.add(params.pubkey);
+            }
+            SubscriptionParams::ProgramChanges(params) => {
+                self.program_subscriptions_index.add(params.pubkey);
+            }
             SubscriptionParams::Signature(params) => {
                 self.by_signature
                     .entry(params.signature)
@@ -419,16 +1229,25 @@ impl SubscriptionsTracker {
             }
             _ => {}
         }
+        if info.tracks_account_writes() {
+            self.account_writes.add(&info);
+            // Writes made before the index entry existed were not reported, so check
+            // everything up to the working bank once
+            let highest_slot = self
+                .logs_subscriptions_index
+                .bank_forks
+                .read()
+                .unwrap()
+                .highest_slot();
+            info.mark_account_written(highest_slot);
+        }
         if info.params.is_commitment_watcher() {
             self.commitment_watchers.insert(id, Arc::clone(&info));
         }
         if info.params.is_gossip_watcher() {
             self.gossip_watchers.insert(id, Arc::clone(&info));
         }
-        if info.params.is_node_progress_watcher() {
-            self.node_progress_watchers
-                .insert(info.params.clone(), Arc::clone(&info));
-        }
+        info
     }
 
     #[allow(clippy::collapsible_if)]
@@ -437,6 +1256,12 @@ impl SubscriptionsTracker {
             SubscriptionParams::Logs(params) => {
                 self.logs_subscriptions_index.remove(params);
             }
+            SubscriptionParams::Program(params) => {
+                self.program_subscriptions_index.remove(params.pubkey);
+            }
+            SubscriptionParams::ProgramChanges(params) => {
+                self.program_subscriptions_index.remove(params.pubkey);
+            }
             SubscriptionParams::Signature(params) => {
                 if let Entry::Occupied(mut entry) = self.by_signature.entry(params.signature) {
                     if entry.get_mut().remove(&id).is_none() {
@@ -451,6 +1276,7 @@ impl SubscriptionsTracker {
             }
             _ => {}
         }
+        self.account_writes.remove(&params, id);
         if params.is_commitment_watcher() {
             if self.commitment_watchers.remove(&id).is_none() {
                 warn!("Subscriptions inconsistency (missing entry in commitment_watchers)");
@@ -461,11 +1287,6 @@ impl SubscriptionsTracker {
                 warn!("Subscriptions inconsistency (missing entry in gossip_watchers)");
             }
         }
-        if params.is_node_progress_watcher() {
-            if self.node_progress_watchers.remove(&params).is_none() {
-                warn!("Subscriptions inconsistency (missing entry in node_progress_watchers)");
-            }
-        }
     }
 
     pub fn by_signature(
@@ -479,15 +1300,15 @@ impl SubscriptionsTracker {
     pub fn gossip_watchers(&self) -> &HashMap<SubscriptionId, Arc<SubscriptionInfo>> {
         &self.gossip_watchers
     }
-    pub fn node_progress_watchers(&self) -> &HashMap<SubscriptionParams, Arc<SubscriptionInfo>> {
-        &self.node_progress_watchers
-    }
 }
 
 struct SubscriptionTokenInner {
     control: Arc<SubscriptionControlInner>,
     params: SubscriptionParams,
     id: SubscriptionId,
+    created_at: Instant,
+    /// Number of `ClientToken`s held on this subscription, by client address
+    clients: DashMap<IpAddr, usize>,
 }
 
 impl fmt::Debug for SubscriptionTokenInner {
@@ -506,10 +1327,13 @@ impl Drop for SubscriptionTokenInner {
                 warn!("Subscriptions inconsistency (missing entry in by_params)");
             }
             DashEntry::Occupied(entry) => {
-                let _ = self.control.sender.send(NotificationEntry::Unsubscribed(
-                    self.params.clone(),
-                    self.id,
-                ));
+                let _ =
+                    self.control
+                        .sender_for(&self.params)
+                        .send(NotificationEntry::Unsubscribed(
+                            self.params.clone(),
+                            self.id,
+                        ));
                 entry.remove();
                 datapoint_info!(
                     "rpc-subscription",
@@ -520,8 +1344,48 @@ impl Drop for SubscriptionTokenInner {
     }
 }
 
+/// One subscription counted against the per-IP limit of a client.
+struct ClientToken {
+    control: Arc<SubscriptionControlInner>,
+    ip: IpAddr,
+    subscription: Weak<SubscriptionTokenInner>,
+}
+
+impl ClientToken {
+    /// Records the client as a holder of `subscription` until the token is dropped
+    fn hold(mut self, subscription: &Arc<SubscriptionTokenInner>) -> Arc<Self> {
+        *subscription.clients.entry(self.ip).or_insert(0) += 1;
+        self.subscription = Arc::downgrade(subscription);
+        Arc::new(self)
+    }
+}
+
+fn release_client(clients: &DashMap<IpAddr, usize>, ip: IpAddr) {
+    if let DashEntry::Occupied(mut entry) = clients.entry(ip) {
+        *entry.get_mut() -= 1;
+        if *entry.get() == 0 {
+            entry.remove();
+        }
+    }
+}
+
+impl Drop for ClientToken {
+    fn drop(&mut self) {
+        release_client(&self.control.subscriptions_per_ip, self.ip);
+        if let Some(subscription) = self.subscription.upgrade() {
+            release_client(&subscription.clients, self.ip);
+        }
+    }
+}
+
 #[derive(Clone)]
-pub struct SubscriptionToken(Arc<SubscriptionTokenInner>, CounterToken);
+pub struct SubscriptionToken(
+    Arc<SubscriptionTokenInner>,
+    CounterToken,
+    Option<Arc<ClientToken>>,
+    /// Sequence number of the last notification delivered to the client
+    Arc<AtomicU64>,
+);
 
 impl SubscriptionToken {
     pub fn id(&self) -> SubscriptionId {
@@ -531,6 +1395,132 @@ impl SubscriptionToken {
     pub fn params(&self) -> &SubscriptionParams {
         &self.0.params
     }
+
+    /// Counts notifications of this subscription skipped by a lagging client
+    pub fn record_lag_drops(&self, skipped: u64) {
+        if let Some(info) = self.0.control.infos.get(&self.0.id) {
+            info.stats.record_lag_drops(skipped);
+        }
+    }
+
+    /// Records the delivery of notification `sequence` to the client, counting the
+    /// notifications it skipped since the previous one as lag drops
+    pub fn record_delivery(&self, sequence: u64) {
+        let delivered = self.3.swap(sequence, Ordering::Relaxed);
+        let skipped = sequence.saturating_sub(delivered + 1);
+        if skipped > 0 {
+            self.record_lag_drops(skipped);
+        }
+    }
+}
+
+/// Item subscriptions registered together by `SubscriptionControl::subscribe_composite`.
+/// The items are unsubscribed when the token is dropped.
+pub struct CompositeSubscriptionToken {
+    id: SubscriptionId,
+    items: Vec<(Arc<str>, SubscriptionToken)>,
+}
+
+impl CompositeSubscriptionToken {
+    pub fn id(&self) -> SubscriptionId {
+        self.id
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
+
+/// Composite subscriptions of a single connection, along with the routes from the ids of
+/// their items to the composite ids and tags that item notifications are delivered with.
+#[derive(Default)]
+pub struct CompositeSubscriptions {
+    subscriptions: HashMap<SubscriptionId, CompositeSubscriptionToken>,
+    routes: HashMap<SubscriptionId, (SubscriptionParams, Vec<(SubscriptionId, Arc<str>)>)>,
+}
+
+impl CompositeSubscriptions {
+    pub fn insert(&mut self, token: CompositeSubscriptionToken) {
+        for (tag, item) in &token.items {
+            self.routes
+                .entry(item.id())
+                .or_insert_with(|| (item.params().clone(), Vec::new()))
+                .1
+                .push((token.id, Arc::clone(tag)));
+        }
+        self.subscriptions.insert(token.id, token);
+    }
+
+    pub fn remove(&mut self, id: SubscriptionId) -> bool {
+        let token = match self.subscriptions.remove(&id) {
+            Some(token) => token,
+            None => return false,
+        };
+        for (_, item) in &token.items {
+            if let Entry::Occupied(mut entry) = self.routes.entry(item.id()) {
+                entry
+                    .get_mut()
+                    .1
+                    .retain(|(composite_id, _)| *composite_id != id);
+                if entry.get().1.is_empty() {
+                    entry.remove();
+                }
+            }
+        }
+        true
+    }
+
+    pub fn contains(&self, id: &SubscriptionId) -> bool {
+        self.subscriptions.contains_key(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.subscriptions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.subscriptions.is_empty()
+    }
+
+    /// Number of items of all the composite subscriptions
+    pub fn items(&self) -> usize {
+        self.subscriptions.values().map(|token| token.len()).sum()
+    }
+
+    /// Params of the item subscription `item_id` and the composite ids and tags its
+    /// notifications have to be delivered with, if any composite subscription includes it.
+    pub fn routes(
+        &self,
+        item_id: &SubscriptionId,
+    ) -> Option<(&SubscriptionParams, &[(SubscriptionId, Arc<str>)])> {
+        self.routes
+            .get(item_id)
+            .map(|(params, routes)| (params, routes.as_slice()))
+    }
+
+    /// Drops item `item_id` from every composite subscription after its final notification.
+    /// Composite subscriptions left without items are removed.
+    pub fn finish_item(&mut self, item_id: SubscriptionId) {
+        let (_, routes) = match self.routes.remove(&item_id) {
+            Some(routes) => routes,
+            None => return,
+        };
+        for (composite_id, _) in routes {
+            if let Entry::Occupied(mut entry) = self.subscriptions.entry(composite_id) {
+                entry
+                    .get_mut()
+                    .items
+                    .retain(|(_, item)| item.id() != item_id);
+                if entry.get().is_empty() {
+                    entry.remove();
+                }
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -539,7 +1529,7 @@ mod tests {
     use crate::rpc_pubsub_service::PubSubConfig;
     use solana_ledger::genesis_utils::{create_genesis_config, GenesisConfigInfo};
     use solana_runtime::bank::Bank;
-    use std::str::FromStr;
+    use std::{str::FromStr, time::Duration};
 
     struct ControlWrapper {
         control: SubscriptionControl,
@@ -548,13 +1538,23 @@ mod tests {
 
     impl ControlWrapper {
         fn new() -> Self {
+            Self::with_max_subscriptions_per_ip(PubSubConfig::default().max_subscriptions_per_ip)
+        }
+
+        fn with_max_subscriptions_per_ip(max_subscriptions_per_ip: usize) -> Self {
             let (sender, receiver) = crossbeam_channel::unbounded();
             let (broadcast_sender, _broadcast_receiver) = broadcast::channel(42);
+            let (firehose_broadcast_sender, _firehose_broadcast_receiver) = broadcast::channel(42);
 
             let control = SubscriptionControl::new(
                 PubSubConfig::default().max_active_subscriptions,
+                max_subscriptions_per_ip,
+                sender.clone(),
                 sender,
                 broadcast_sender,
+                firehose_broadcast_sender,
+                MemoryBudget::default(),
+                RecentSignatureNotifications::new(Duration::default(), MemoryBudget::default()),
             );
             Self { control, receiver }
         }
@@ -593,6 +1593,98 @@ mod tests {
         control.assert_unsubscribed(&SubscriptionParams::Slot, 0);
     }
 
+    #[test]
+    fn notify_subscribe_node_progress_sender() {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let (node_progress_sender, node_progress_receiver) = crossbeam_channel::unbounded();
+        let (broadcast_sender, _broadcast_receiver) = broadcast::channel(42);
+        let (firehose_broadcast_sender, _firehose_broadcast_receiver) = broadcast::channel(42);
+        let control = SubscriptionControl::new(
+            PubSubConfig::default().max_active_subscriptions,
+            PubSubConfig::default().max_subscriptions_per_ip,
+            sender,
+            node_progress_sender,
+            broadcast_sender,
+            firehose_broadcast_sender,
+            MemoryBudget::default(),
+            RecentSignatureNotifications::new(Duration::default(), MemoryBudget::default()),
+        );
+
+        let slot_token = control.subscribe(SubscriptionParams::Slot).unwrap();
+        let signature_params = SubscriptionParams::Signature(SignatureSubscriptionParams {
+            signature: Signature::default(),
+            commitment: CommitmentConfig::processed(),
+            enable_received_notification: false,
+            followup_commitments: vec![],
+        });
+        let signature_token = control.subscribe(signature_params).unwrap();
+        drop(slot_token);
+        drop(signature_token);
+
+        let kinds = |receiver: &crossbeam_channel::Receiver<NotificationEntry>| {
+            receiver
+                .try_iter()
+                .map(|notification_entry| match notification_entry {
+                    NotificationEntry::Subscribed(params, _) => (true, params.method()),
+                    NotificationEntry::Unsubscribed(params, _) => (false, params.method()),
+                    _ => panic!("unexpected notification"),
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            kinds(&node_progress_receiver),
+            vec![(true, "slotNotification"), (false, "slotNotification")]
+        );
+        assert_eq!(
+            kinds(&receiver),
+            vec![
+                (true, "signatureNotification"),
+                (false, "signatureNotification")
+            ]
+        );
+    }
+
+    #[test]
+    fn composite_subscription_routes() {
+        let control = ControlWrapper::new();
+        let slot_token = control.control.subscribe(SubscriptionParams::Slot).unwrap();
+        control.assert_subscribed(&SubscriptionParams::Slot, 0);
+
+        // The slot item shares the subscription of `slot_token`
+        let composite = control
+            .control
+            .subscribe_composite(
+                vec![
+                    ("slots".to_string(), SubscriptionParams::Slot),
+                    ("updates".to_string(), SubscriptionParams::SlotsUpdates),
+                ],
+                None,
+            )
+            .unwrap();
+        control.assert_subscribed(&SubscriptionParams::SlotsUpdates, 1);
+        assert_eq!(composite.id(), SubscriptionId::from(2));
+
+        let mut composites = CompositeSubscriptions::default();
+        composites.insert(composite);
+        assert_eq!(composites.items(), 2);
+        let (params, routes) = composites.routes(&0.into()).unwrap();
+        assert_eq!(params, &SubscriptionParams::Slot);
+        assert_eq!(routes, &[(SubscriptionId::from(2), Arc::from("slots"))]);
+
+        composites.finish_item(1.into());
+        assert_eq!(composites.items(), 1);
+        control.assert_unsubscribed(&SubscriptionParams::SlotsUpdates, 1);
+        assert!(composites.routes(&1.into()).is_none());
+        assert!(composites.contains(&2.into()));
+
+        assert!(composites.remove(2.into()));
+        assert!(!composites.remove(2.into()));
+        assert!(composites.routes(&0.into()).is_none());
+        control.assert_silence();
+        drop(slot_token);
+        control.assert_unsubscribed(&SubscriptionParams::Slot, 0);
+    }
+
     #[test]
     fn notify_subscribe_multiple() {
         let control = ControlWrapper::new();
@@ -607,6 +1699,128 @@ mod tests {
         control.assert_unsubscribed(&SubscriptionParams::Slot, 0);
     }
 
+    #[test]
+    fn subscribe_from_client_limit() {
+        let control = ControlWrapper::with_max_subscriptions_per_ip(2);
+        let ip = IpAddr::from([10, 0, 0, 1]);
+        let other_ip = IpAddr::from([10, 0, 0, 2]);
+
+        let token1 = control
+            .control
+            .subscribe_from(SubscriptionParams::Slot, Some(ip))
+            .unwrap();
+        let token2 = control
+            .control
+            .subscribe_from(
+                SubscriptionParams::Root(RootSubscriptionParams::default()),
+                Some(ip),
+            )
+            .unwrap();
+        assert_eq!(control.control.total_from(&ip), 2);
+        assert!(matches!(
+            control
+                .control
+                .subscribe_from(SubscriptionParams::SlotsUpdates, Some(ip)),
+            Err(Error::TooManyClientSubscriptions)
+        ));
+        // Other clients and unattributed subscriptions are unaffected
+        let _token3 = control
+            .control
+            .subscribe_from(SubscriptionParams::SlotsUpdates, Some(other_ip))
+            .unwrap();
+        let _token4 = control
+            .control
+            .subscribe(SubscriptionParams::Vote(VoteSubscriptionParams::default()))
+            .unwrap();
+
+        drop(token1);
+        assert_eq!(control.control.total_from(&ip), 1);
+        let _token5 = control
+            .control
+            .subscribe_from(SubscriptionParams::Slot, Some(ip))
+            .unwrap();
+        drop(token2);
+        assert_eq!(control.control.total_from(&ip), 1);
+        assert_eq!(control.control.total_from(&other_ip), 1);
+    }
+
+    #[test]
+    fn active_subscriptions() {
+        let control = ControlWrapper::new();
+        let ip = IpAddr::from([10, 0, 0, 1]);
+        let other_ip = IpAddr::from([10, 0, 0, 2]);
+
+        let token1 = control
+            .control
+            .subscribe_from(SubscriptionParams::Slot, Some(ip))
+            .unwrap();
+        let token2 = control
+            .control
+            .subscribe_from(SubscriptionParams::Slot, Some(ip))
+            .unwrap();
+        let token3 = control
+            .control
+            .subscribe_from(SubscriptionParams::Slot, Some(other_ip))
+            .unwrap();
+        let _token4 = control
+            .control
+            .subscribe(SubscriptionParams::SlotsUpdates)
+            .unwrap();
+
+        let subscriptions = control.control.active_subscriptions();
+        assert_eq!(subscriptions.len(), 2);
+        assert_eq!(subscriptions[0].id, token1.id());
+        assert_eq!(subscriptions[0].method, "slotNotification");
+        assert_eq!(subscriptions[0].params, SubscriptionParams::Slot);
+        assert_eq!(subscriptions[0].last_notified_slot, None);
+        assert_eq!(subscriptions[0].clients, vec![(ip, 2), (other_ip, 1)]);
+        assert!(subscriptions[1].clients.is_empty());
+
+        // The last notified slot is reported once a notifier thread tracks the subscription
+        control.control.infos().insert(
+            token1.id(),
+            Arc::new(SubscriptionInfo::new(
+                SubscriptionParams::Slot,
+                token1.id(),
+                42,
+            )),
+        );
+        assert_eq!(
+            control.control.active_subscriptions()[0].last_notified_slot,
+            Some(42)
+        );
+
+        let info = Arc::clone(control.control.infos().get(&token1.id()).unwrap().value());
+        info.stats.record_notification(100);
+        info.stats.record_notification(20);
+        token2.record_lag_drops(3);
+        let subscription = &control.control.active_subscriptions()[0];
+        assert_eq!(subscription.notifications, 2);
+        assert_eq!(subscription.bytes, 120);
+        assert_eq!(subscription.lag_drops, 3);
+        assert_eq!(control.control.active_subscriptions()[1].notifications, 0);
+
+        // Only the notifications skipped since the client subscribed count against it
+        let token5 = control.control.subscribe(SubscriptionParams::Slot).unwrap();
+        assert_eq!(info.stats.record_notification(10), 3);
+        assert_eq!(info.stats.record_notification(10), 4);
+        token5.record_delivery(4);
+        assert_eq!(control.control.active_subscriptions()[0].lag_drops, 4);
+        assert_eq!(info.stats.record_notification(10), 5);
+        token5.record_delivery(5);
+        assert_eq!(control.control.active_subscriptions()[0].lag_drops, 4);
+        drop(token5);
+
+        drop(token1);
+        drop(token3);
+        assert_eq!(
+            control.control.active_subscriptions()[0].clients,
+            vec![(ip, 1)]
+        );
+        drop(token2);
+        assert_eq!(control.control.active_subscriptions().len(), 1);
+    }
+
     #[test]
     fn notify_subscribe_two_subscriptions() {
         let control = ControlWrapper::new();
@@ -617,6 +1831,7 @@ mod tests {
             signature: Signature::default(),
             commitment: CommitmentConfig::processed(),
             enable_received_notification: false,
+            followup_commitments: vec![],
         });
         let token_signature1 = control.control.subscribe(signature_params.clone()).unwrap();
         control.assert_subscribed(&signature_params, 1);
@@ -645,20 +1860,20 @@ mod tests {
         let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
         let mut tracker = SubscriptionsTracker::new(bank_forks);
 
-        tracker.subscribe(SubscriptionParams::Slot, 0.into(), || 0);
-        let info = tracker
-            .node_progress_watchers
-            .get(&SubscriptionParams::Slot)
-            .unwrap();
+        let info = tracker.subscribe(SubscriptionParams::Slot, 0.into(), || 0);
         assert_eq!(info.commitment, None);
         assert_eq!(info.params, SubscriptionParams::Slot);
         assert_eq!(info.method, SubscriptionParams::Slot.method());
         assert_eq!(info.id, SubscriptionId::from(0));
         assert_eq!(*info.last_notified_slot.read().unwrap(), 0);
+        assert!(info.last_notified_account_slots().is_none());
+        assert!(info.last_notified_mint().is_none());
 
         let account_params = SubscriptionParams::Account(AccountSubscriptionParams {
             pubkey: Pubkey::from_str("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA").unwrap(),
             commitment: CommitmentConfig::finalized(),
+            compression_level: None,
+            with_proof: false,
             encoding: UiAccountEncoding::Base64Zstd,
             data_slice: None,
         });
@@ -677,12 +1892,11 @@ mod tests {
 
     #[test]
     fn subscription_indexes() {
-        fn counts(tracker: &SubscriptionsTracker) -> (usize, usize, usize, usize) {
+        fn counts(tracker: &SubscriptionsTracker) -> (usize, usize, usize) {
             (
                 tracker.by_signature.len(),
                 tracker.commitment_watchers.len(),
                 tracker.gossip_watchers.len(),
-                tracker.node_progress_watchers.len(),
             )
         }
 
@@ -692,40 +1906,90 @@ mod tests {
         let mut tracker = SubscriptionsTracker::new(bank_forks);
 
         tracker.subscribe(SubscriptionParams::Slot, 0.into(), || 0);
-        assert_eq!(counts(&tracker), (0, 0, 0, 1));
+        assert_eq!(counts(&tracker), (0, 0, 0));
         tracker.unsubscribe(SubscriptionParams::Slot, 0.into());
-        assert_eq!(counts(&tracker), (0, 0, 0, 0));
+        assert_eq!(counts(&tracker), (0, 0, 0));
 
         let account_params = SubscriptionParams::Account(AccountSubscriptionParams {
             pubkey: Pubkey::from_str("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA").unwrap(),
             commitment: CommitmentConfig::finalized(),
+            compression_level: None,
+            with_proof: false,
             encoding: UiAccountEncoding::Base64Zstd,
             data_slice: None,
         });
         tracker.subscribe(account_params.clone(), 1.into(), || 0);
-        assert_eq!(counts(&tracker), (0, 1, 0, 0));
+        assert_eq!(counts(&tracker), (0, 1, 0));
         tracker.unsubscribe(account_params, 1.into());
-        assert_eq!(counts(&tracker), (0, 0, 0, 0));
+        assert_eq!(counts(&tracker), (0, 0, 0));
 
         let account_params2 = SubscriptionParams::Account(AccountSubscriptionParams {
             pubkey: Pubkey::from_str("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA").unwrap(),
             commitment: CommitmentConfig::confirmed(),
+            compression_level: None,
+            with_proof: false,
             encoding: UiAccountEncoding::Base64Zstd,
             data_slice: None,
         });
         tracker.subscribe(account_params2.clone(), 2.into(), || 0);
-        assert_eq!(counts(&tracker), (0, 0, 1, 0));
+        assert_eq!(counts(&tracker), (0, 0, 1));
         tracker.unsubscribe(account_params2, 2.into());
-        assert_eq!(counts(&tracker), (0, 0, 0, 0));
+        assert_eq!(counts(&tracker), (0, 0, 0));
 
         let signature_params = SubscriptionParams::Signature(SignatureSubscriptionParams {
             signature: Signature::default(),
             commitment: CommitmentConfig::processed(),
             enable_received_notification: false,
+            followup_commitments: vec![],
         });
         tracker.subscribe(signature_params.clone(), 3.into(), || 0);
-        assert_eq!(counts(&tracker), (1, 1, 0, 0));
+        assert_eq!(counts(&tracker), (1, 1, 0));
         tracker.unsubscribe(signature_params, 3.into());
-        assert_eq!(counts(&tracker), (0, 0, 0, 0));
+        assert_eq!(counts(&tracker), (0, 0, 0));
+    }
+
+    #[test]
+    fn account_writes_mark_watching_subscriptions() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let bank0 = bank_forks.read().unwrap().root_bank();
+        let mut tracker = SubscriptionsTracker::new(bank_forks);
+
+        let watched = solana_sdk::pubkey::new_rand();
+        let unwatched = solana_sdk::pubkey::new_rand();
+        let account_params = SubscriptionParams::Account(AccountSubscriptionParams {
+            pubkey: watched,
+            commitment: CommitmentConfig::processed(),
+            compression_level: None,
+            with_proof: false,
+            encoding: UiAccountEncoding::Base64,
+            data_slice: None,
+        });
+        let info = tracker.subscribe(account_params.clone(), 0.into(), || 0);
+        // Writes preceding the subscription are covered by checking the working bank once
+        assert_eq!(info.pending_account_write(), Some(0));
+        info.clear_account_write(0);
+        assert_eq!(info.pending_account_write(), None);
+
+        let bank1 = Bank::new_from_parent(&bank0, &Pubkey::default(), 1);
+        bank1.store_account(
+            &unwatched,
+            &AccountSharedData::new(1, 0, &Pubkey::default()),
+        );
+        assert_eq!(info.pending_account_write(), None);
+        bank1.store_account(&watched, &AccountSharedData::new(1, 0, &Pubkey::default()));
+        assert_eq!(info.pending_account_write(), Some(1));
+
+        // A pending write is kept if a later one arrives before it is cleared
+        let bank2 = Bank::new_from_parent(&bank1, &Pubkey::default(), 2);
+        bank2.store_account(&watched, &AccountSharedData::new(2, 0, &Pubkey::default()));
+        info.clear_account_write(1);
+        assert_eq!(info.pending_account_write(), Some(2));
+
+        tracker.unsubscribe(account_params, 0.into());
+        info.clear_account_write(2);
+        bank2.store_account(&watched, &AccountSharedData::new(3, 0, &Pubkey::default()));
+        assert_eq!(info.pending_account_write(), None);
     }
 }
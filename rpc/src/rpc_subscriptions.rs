@@ -4,53 +4,93 @@ use {
     crate::{
         optimistically_confirmed_bank_tracker::OptimisticallyConfirmedBank,
         parsed_token_accounts::{get_parsed_token_account, get_parsed_token_accounts},
+        rpc::get_delegation_activation,
+        rpc_pubsub_memory::{MemoryBudget, MemoryCategory},
         rpc_pubsub_service::PubSubConfig,
         rpc_subscription_tracker::{
-            AccountSubscriptionParams, LogsSubscriptionKind, LogsSubscriptionParams,
-            ProgramSubscriptionParams, SignatureSubscriptionParams, SubscriptionControl,
-            SubscriptionId, SubscriptionInfo, SubscriptionParams, SubscriptionsTracker,
+            report_subscription_stats, AccountSubscriptionParams, AccountsSubscriptionParams,
+            BlockSubscriptionKind, BlockSubscriptionParams, BlockhashSubscriptionParams,
+            BlockhashValiditySubscriptionParams, LogsSubscriptionKind, LogsSubscriptionParams,
+            MintSubscriptionParams, ProgramChangesSubscriptionParams, ProgramSubscriptionParams,
+            RootSubscriptionParams, SignatureSubscriptionParams, StakeSubscriptionParams,
+            SubscriptionControl, SubscriptionId, SubscriptionInfo, SubscriptionInfos,
+            SubscriptionParams, SubscriptionsTracker, TokenAccountsByOwnerSubscriptionParams,
+            TransactionSubscriptionParams, VoteSubscriptionParams,
         },
+        upcoming_leaders::UpcomingLeaders,
     },
-    crossbeam_channel::{Receiver, RecvTimeoutError, SendError, Sender},
+    crossbeam_channel::{Receiver, RecvTimeoutError, Select, Sender, TryRecvError, TrySendError},
+    dashmap::DashMap,
+    rayon::{prelude::*, ThreadPool, ThreadPoolBuilder},
     serde::Serialize,
-    solana_account_decoder::{parse_token::spl_token_id_v2_0, UiAccount, UiAccountEncoding},
+    solana_account_decoder::{
+        parse_stake::{parse_stake, StakeAccountType},
+        parse_token::{is_known_spl_token_id, parse_token, TokenAccountType},
+        UiAccount, UiAccountEncoding, UiDataSliceConfig,
+    },
     solana_client::{
+        rpc_config::RpcTransactionLogsStatusFilter,
         rpc_filter::RpcFilterType,
         rpc_response::{
-            ProcessedSignatureResult, ReceivedSignatureResult, Response, RpcKeyedAccount,
-            RpcLogsResponse, RpcResponseContext, RpcSignatureResult, SlotInfo, SlotUpdate,
+            ProcessedSignatureResult, ReceivedSignatureResult, Response, RpcAccountChange,
+            RpcAccountProof, RpcAccountUpdate, RpcBlockUpdate, RpcBlockUpdateError, RpcBlockhash,
+            RpcBlockhashStatus, RpcBlockhashValidity, RpcEpochRewards, RpcEpochStart,
+            RpcKeyedAccount, RpcLogsResponse, RpcMerkleProofLevel, RpcMintState, RpcMintUpdate,
+            RpcNotificationsDropped, RpcPerfSample, RpcProgramAccountsChunk,
+            RpcRecentSignatureNotification, RpcResponseContext, RpcRootInfo, RpcRootNotification,
+            RpcSignatureResult, RpcStakeActivation, RpcStakeActivationSummary, RpcStakeUpdate,
+            RpcUpcomingLeaders, SlotInfo, SlotPruneReason, SlotUpdate, StakeActivationState,
         },
     },
+    solana_ledger::{blockstore::Blockstore, blockstore_meta::PerfSample},
     solana_measure::measure::Measure,
     solana_runtime::{
-        bank::{Bank, TransactionLogInfo},
+        accounts_index::IndexKey,
+        bank::{AccountProofs, Bank, RewardInfo, RewardType, TransactionLogInfo},
         bank_forks::BankForks,
         commitment::{BlockCommitmentCache, CommitmentSlots},
+        inline_spl_token_2022::is_spl_token_account,
+        inline_spl_token_v2_0::SPL_TOKEN_ACCOUNT_OWNER_OFFSET,
     },
     solana_sdk::{
-        account::{AccountSharedData, ReadableAccount},
+        account::{from_account, AccountSharedData, ReadableAccount},
         clock::{Slot, UnixTimestamp},
-        pubkey::Pubkey,
+        commitment_config::CommitmentConfig,
+        hash::Hash,
+        pubkey::{Pubkey, PUBKEY_BYTES},
         signature::Signature,
+        stake::{self, state::StakeState},
+        stake_history::StakeHistory,
+        sysvar,
         timing::timestamp,
         transaction,
     },
-    solana_vote_program::vote_state::Vote,
+    solana_transaction_status::{
+        ConfirmedBlock, ConfirmedTransaction, Reward, TransactionConfirmationStatus,
+        TransactionWithStatusMeta, UiConfirmedBlock,
+    },
+    solana_vote_program::{vote_state::Vote, vote_transaction::parse_vote_transaction},
     std::{
-        collections::{HashMap, VecDeque},
+        collections::{hash_map::Entry, BTreeMap, HashMap, HashSet, VecDeque},
         io::Cursor,
-        iter, str,
+        iter, mem, str,
         sync::{
-            atomic::{AtomicBool, Ordering},
-            Arc, RwLock, Weak,
+            atomic::{AtomicBool, AtomicUsize, Ordering},
+            Arc, Mutex, RwLock, Weak,
         },
-        thread::{Builder, JoinHandle},
-        time::Duration,
+        thread::{sleep, Builder, JoinHandle},
+        time::{Duration, Instant},
     },
-    tokio::sync::broadcast,
+    tokio::sync::{
+        broadcast,
+        mpsc::{self, error::TrySendError},
+    },
+    tracing::{debug_span, info_span},
 };
 
 const RECEIVE_DELAY_MILLIS: u64 = 100;
+const MEMORY_METRICS_INTERVAL: Duration = Duration::from_secs(1);
+const SUBSCRIPTION_STATS_INTERVAL: Duration = Duration::from_secs(10);
 
 fn get_transaction_logs(
     bank: &Bank,
@@ -72,29 +112,137 @@ fn get_transaction_logs(
 
 // A more human-friendly version of Vote, with the bank state signature base58 encoded.
 #[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
 pub struct RpcVote {
     pub slots: Vec<Slot>,
     pub hash: String,
     pub timestamp: Option<UnixTimestamp>,
+    pub vote_pubkey: String,
+    /// Node identity of the vote account, if it is known to the root bank
+    pub identity: Option<String>,
+    pub signature: String,
 }
 
 pub enum NotificationEntry {
     Slot(SlotInfo),
     SlotUpdate(SlotUpdate),
-    Vote(Vote),
-    Root(Slot),
+    Vote(Pubkey, Vote, Signature),
+    Root(RpcRootInfo),
+    Epoch(RpcEpochStart),
+    /// First rooted bank of an epoch, which holds the rewards of the previous epoch
+    EpochRewards(Arc<Bank>),
+    PerformanceSample(RpcPerfSample),
     Bank(CommitmentSlots),
     Gossip(Slot),
     SignaturesReceived((Slot, Vec<Signature>)),
     Subscribed(SubscriptionParams, SubscriptionId),
     Unsubscribed(SubscriptionParams, SubscriptionId),
+    InitialAccountState(
+        AccountSubscriptionParams,
+        SubscriptionId,
+        mpsc::Sender<DirectNotification>,
+    ),
+    InitialProgramAccounts(
+        ProgramSubscriptionParams,
+        SubscriptionId,
+        mpsc::Sender<DirectNotification>,
+    ),
+    /// Logs of the transactions from a start slot on, read out of the blockstore
+    LogsReplay(
+        LogsSubscriptionParams,
+        Slot,
+        SubscriptionId,
+        mpsc::Sender<DirectNotification>,
+        LogsReplayPermit,
+    ),
+    /// Notifications of each kind that were dropped because their queue was full
+    Dropped(BTreeMap<&'static str, RpcNotificationsDropped>),
+}
+
+impl NotificationEntry {
+    fn kind(&self) -> &'static str {
+        match self {
+            NotificationEntry::Slot(_) => "slot",
+            NotificationEntry::SlotUpdate(_) => "slot_update",
+            NotificationEntry::Vote(..) => "vote",
+            NotificationEntry::Root(_) => "root",
+            NotificationEntry::Epoch(_) => "epoch",
+            NotificationEntry::EpochRewards(_) => "epoch_rewards",
+            NotificationEntry::PerformanceSample(_) => "performance_sample",
+            NotificationEntry::Bank(_) => "bank",
+            NotificationEntry::Gossip(_) => "gossip",
+            NotificationEntry::SignaturesReceived(_) => "signatures_received",
+            NotificationEntry::Subscribed(..) => "subscribed",
+            NotificationEntry::Unsubscribed(..) => "unsubscribed",
+            NotificationEntry::InitialAccountState(..) => "initial_account_state",
+            NotificationEntry::InitialProgramAccounts(..) => "initial_program_accounts",
+            NotificationEntry::LogsReplay(..) => "logs_replay",
+            NotificationEntry::Dropped(_) => "dropped",
+        }
+    }
+
+    /// Whether this entry is handled by the node progress notification thread
+    fn is_node_progress(&self) -> bool {
+        matches!(
+            self,
+            NotificationEntry::Slot(_)
+                | NotificationEntry::SlotUpdate(_)
+                | NotificationEntry::Vote(..)
+                | NotificationEntry::Root(_)
+                | NotificationEntry::Epoch(_)
+                | NotificationEntry::EpochRewards(_)
+                | NotificationEntry::PerformanceSample(_)
+        )
+    }
+
+    fn slot(&self) -> Option<Slot> {
+        match self {
+            NotificationEntry::Slot(slot_info) => Some(slot_info.slot),
+            NotificationEntry::SlotUpdate(slot_update) => Some(slot_update.slot()),
+            NotificationEntry::Vote(_, vote, _) => vote.last_voted_slot(),
+            NotificationEntry::Root(root_info) => Some(root_info.slot),
+            NotificationEntry::Epoch(epoch_start) => Some(epoch_start.slot),
+            NotificationEntry::EpochRewards(bank) => Some(bank.slot()),
+            NotificationEntry::PerformanceSample(perf_sample) => Some(perf_sample.slot),
+            NotificationEntry::Bank(commitment_slots) => Some(commitment_slots.slot),
+            NotificationEntry::Gossip(slot) => Some(*slot),
+            NotificationEntry::SignaturesReceived((slot, _)) => Some(*slot),
+            NotificationEntry::Subscribed(..)
+            | NotificationEntry::Unsubscribed(..)
+            | NotificationEntry::InitialAccountState(..)
+            | NotificationEntry::InitialProgramAccounts(..)
+            | NotificationEntry::LogsReplay(..)
+            | NotificationEntry::Dropped(_) => None,
+        }
+    }
+
+    /// Approximate number of bytes this entry occupies while waiting in the queue.
+    fn estimated_size(&self) -> usize {
+        let heap_size = match self {
+            NotificationEntry::Vote(_, vote, _) => vote.slots.len() * mem::size_of::<Slot>(),
+            NotificationEntry::SlotUpdate(SlotUpdate::Dead { err, .. }) => err.len(),
+            NotificationEntry::Root(root_info) => root_info.blockhash.len(),
+            NotificationEntry::SignaturesReceived((_, signatures)) => {
+                signatures.len() * mem::size_of::<Signature>()
+            }
+            _ => 0,
+        };
+        mem::size_of::<Self>() + heap_size
+    }
 }
 
 impl std::fmt::Debug for NotificationEntry {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
-            NotificationEntry::Root(root) => write!(f, "Root({})", root),
-            NotificationEntry::Vote(vote) => write!(f, "Vote({:?})", vote),
+            NotificationEntry::Root(root_info) => write!(f, "Root({})", root_info.slot),
+            NotificationEntry::Epoch(epoch_start) => write!(f, "Epoch({:?})", epoch_start),
+            NotificationEntry::EpochRewards(bank) => write!(f, "EpochRewards({})", bank.slot()),
+            NotificationEntry::PerformanceSample(perf_sample) => {
+                write!(f, "PerformanceSample({:?})", perf_sample)
+            }
+            NotificationEntry::Vote(vote_pubkey, vote, signature) => {
+                write!(f, "Vote({}, {:?}, {})", vote_pubkey, vote, signature)
+            }
             NotificationEntry::Slot(slot_info) => write!(f, "Slot({:?})", slot_info),
             NotificationEntry::SlotUpdate(slot_update) => {
                 write!(f, "SlotUpdate({:?})", slot_update)
@@ -106,14 +254,91 @@ impl std::fmt::Debug for NotificationEntry {
                 write!(f, "SignaturesReceived({:?})", slot_signatures)
             }
             NotificationEntry::Gossip(slot) => write!(f, "Gossip({:?})", slot),
+            NotificationEntry::Dropped(dropped) => write!(f, "Dropped({:?})", dropped),
             NotificationEntry::Subscribed(params, id) => {
                 write!(f, "Subscribed({:?}, {:?})", params, id)
             }
             NotificationEntry::Unsubscribed(params, id) => {
                 write!(f, "Unsubscribed({:?}, {:?})", params, id)
             }
+            NotificationEntry::InitialAccountState(params, id, _) => {
+                write!(f, "InitialAccountState({:?}, {:?})", params, id)
+            }
+            NotificationEntry::InitialProgramAccounts(params, id, _) => {
+                write!(f, "InitialProgramAccounts({:?}, {:?})", params, id)
+            }
+            NotificationEntry::LogsReplay(params, start_slot, id, ..) => {
+                write!(f, "LogsReplay({:?}, {}, {:?})", params, start_slot, id)
+            }
+        }
+    }
+}
+
+fn commitment_slot(commitment: &CommitmentConfig, commitment_slots: &CommitmentSlots) -> Slot {
+    if commitment.is_finalized() {
+        commitment_slots.highest_confirmed_root
+    } else if commitment.is_confirmed() {
+        commitment_slots.highest_confirmed_slot
+    } else {
+        commitment_slots.slot
+    }
+}
+
+/// Runs `check` for subscriptions that track account writes only if one of their accounts
+/// was written since the commitment slot last covered the writes, or if the commitment slot
+/// moved to a fork that doesn't contain the slots last notified, instead of scanning every
+/// subscribed account on every notification.
+fn check_if_account_written(
+    subscription: &SubscriptionInfo,
+    bank_forks: &RwLock<BankForks>,
+    commitment_slots: &CommitmentSlots,
+    check: impl FnOnce() -> bool,
+) -> bool {
+    let commitment = match subscription.commitment() {
+        Some(commitment) => commitment,
+        None => return check(),
+    };
+    let slot = commitment_slot(&commitment, commitment_slots);
+    // Whether the state of `write_slot` is part of the state at the commitment slot. Writes on
+    // forks that were pruned, or that are below the root, can no longer become visible at a
+    // later commitment slot.
+    let is_ancestor = |bank_forks: &BankForks, write_slot: Slot| {
+        write_slot <= slot
+            && (write_slot <= bank_forks.root()
+                || bank_forks
+                    .get(slot)
+                    .map(|bank| bank.ancestors.contains_key(&write_slot))
+                    .unwrap_or(false))
+    };
+
+    let pending_write = subscription.pending_account_write();
+    if pending_write.is_none() {
+        // Switching to a fork where the accounts weren't written reverts them without any
+        // write to report, which the check notifies like the baseline did
+        let bank_forks = bank_forks.read().unwrap();
+        let last_notified_slot = *subscription.last_notified_slot.read().unwrap();
+        let reverted = !is_ancestor(&bank_forks, last_notified_slot)
+            || subscription
+                .last_notified_account_slots()
+                .map_or(false, |account_slots| {
+                    account_slots
+                        .read()
+                        .unwrap()
+                        .values()
+                        .any(|notified_slot| !is_ancestor(&bank_forks, *notified_slot))
+                });
+        if !reverted {
+            return false;
+        }
+    }
+
+    let notified = check();
+    if let Some(write_slot) = pending_write {
+        if is_ancestor(&bank_forks.read().unwrap(), write_slot) {
+            subscription.clear_account_write(write_slot);
         }
     }
+    notified
 }
 
 #[allow(clippy::type_complexity)]
@@ -124,7 +349,7 @@ fn check_commitment_and_notify<P, S, B, F, X>(
     commitment_slots: &CommitmentSlots,
     bank_method: B,
     filter_results: F,
-    notifier: &mut RpcNotifier,
+    notifier: &RpcNotifier,
     is_final: bool,
 ) -> bool
 where
@@ -133,19 +358,22 @@ where
     F: Fn(X, &P, Slot, Arc<Bank>) -> (Box<dyn Iterator<Item = S>>, Slot),
     X: Clone + Default,
 {
-    let commitment = if let Some(commitment) = subscription.commitment() {
-        commitment
+    let slot = if let Some(commitment) = subscription.commitment() {
+        commitment_slot(&commitment, commitment_slots)
     } else {
         error!("missing commitment in check_commitment_and_notify");
         return false;
     };
-    let slot = if commitment.is_finalized() {
-        commitment_slots.highest_confirmed_root
-    } else if commitment.is_confirmed() {
-        commitment_slots.highest_confirmed_slot
-    } else {
-        commitment_slots.slot
-    };
+
+    // The validator installs no tracing subscriber, so spans are recorded through `log`, under
+    // the `tracing::span` targets
+    let _span = debug_span!(
+        "rpc_notification_filter",
+        subscription = u64::from(subscription.id()),
+        method = subscription.method(),
+        slot,
+    )
+    .entered();
 
     let mut notified = false;
     if let Some(bank) = bank_forks.read().unwrap().get(slot).cloned() {
@@ -153,14 +381,15 @@ where
         let mut w_last_notified_slot = subscription.last_notified_slot.write().unwrap();
         let (filter_results, result_slot) =
             filter_results(results, params, *w_last_notified_slot, bank);
-        for result in filter_results {
-            notifier.notify(
+        for (index, result) in filter_results.enumerate() {
+            notifier.notify_result(
                 Response {
                     context: RpcResponseContext { slot },
                     value: result,
                 },
                 subscription,
                 is_final,
+                Some((slot, index)),
             );
             *w_last_notified_slot = result_slot;
             notified = true;
@@ -169,45 +398,337 @@ where
     notified
 }
 
+/// Maximum number of accounts in one `programInitialAccountsNotification`
+pub const INITIAL_PROGRAM_ACCOUNTS_CHUNK_SIZE: usize = 1_000;
+
+/// Maximum number of slots replayed by `logsSubscribe` with `startSlot`
+pub const MAX_LOGS_REPLAY_SLOTS: Slot = 1_000;
+
+/// Maximum number of logs replays in progress for a single connection
+pub const MAX_LOGS_REPLAYS_PER_CONNECTION: usize = 2;
+
+/// Number of threads reading notifications sent to a single connection, such as logs
+/// replays, out of the bank and the blockstore
+const DIRECT_NOTIFICATION_THREADS: usize = 2;
+
+/// Direct notifications buffered per connection
+pub const DIRECT_NOTIFICATION_BUFFER_SIZE: usize = 1_024;
+
+/// How long a direct notification waits for room in the buffer of its connection before the
+/// connection is given up on
+const DIRECT_NOTIFICATION_SEND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A notification written to a single connection instead of being broadcast, such as
+/// the account state requested with `sendInitial` on `accountSubscribe`
+#[derive(Debug)]
+pub enum DirectNotification {
+    Message {
+        subscription_id: SubscriptionId,
+        json: Arc<String>,
+    },
+    /// The logs replay of the subscription has been requested; the connection holds the
+    /// notifications of the subscription until the replay is finished
+    ReplayStarted(SubscriptionId),
+    /// The logs replay of the subscription is finished. The held notifications are sent,
+    /// except for the ones of slots up to `end_slot`, which the replay covered.
+    ReplayFinished {
+        subscription_id: SubscriptionId,
+        end_slot: Option<Slot>,
+    },
+}
+
+impl DirectNotification {
+    /// Waits up to `DIRECT_NOTIFICATION_SEND_TIMEOUT` for room in the buffer of the
+    /// connection, so that a slow connection can't hold a direct notification thread for
+    /// long. Returns whether the connection took the notification.
+    fn send(self, sender: &mpsc::Sender<DirectNotification>) -> bool {
+        let deadline = Instant::now() + DIRECT_NOTIFICATION_SEND_TIMEOUT;
+        let mut notification = self;
+        loop {
+            match sender.try_send(notification) {
+                Ok(()) => return true,
+                Err(TrySendError::Closed(_)) => return false,
+                Err(TrySendError::Full(returned)) => {
+                    if Instant::now() > deadline {
+                        inc_new_counter_info!("rpc-subscription-direct-send-timeout", 1);
+                        return false;
+                    }
+                    notification = returned;
+                    sleep(Duration::from_millis(10));
+                }
+            }
+        }
+    }
+
+    pub fn subscription_id(&self) -> SubscriptionId {
+        match self {
+            Self::Message {
+                subscription_id, ..
+            }
+            | Self::ReplayStarted(subscription_id)
+            | Self::ReplayFinished {
+                subscription_id, ..
+            } => *subscription_id,
+        }
+    }
+}
+
+/// Counts a logs replay against the limit of the connection that asked for it, until the
+/// replay is finished
+#[derive(Debug)]
+pub struct LogsReplayPermit(Arc<AtomicUsize>);
+
+impl LogsReplayPermit {
+    /// `pending` is the number of replays in progress for the connection
+    pub fn try_acquire(pending: &Arc<AtomicUsize>) -> Option<Self> {
+        pending
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |count| {
+                (count < MAX_LOGS_REPLAYS_PER_CONNECTION).then(|| count + 1)
+            })
+            .ok()?;
+        Some(Self(Arc::clone(pending)))
+    }
+}
+
+impl Drop for LogsReplayPermit {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Number of open connections that receive CBOR encoded messages. Notifications are only
+/// encoded to CBOR while there is one.
+#[derive(Debug, Clone, Default)]
+pub struct CborClients(Arc<AtomicUsize>);
+
+impl CborClients {
+    /// Counts a connection until the returned token is dropped
+    pub fn register(&self) -> CborClient {
+        self.0.fetch_add(1, Ordering::AcqRel);
+        CborClient(Arc::clone(&self.0))
+    }
+
+    fn any(&self) -> bool {
+        self.0.load(Ordering::Acquire) > 0
+    }
+}
+
+pub struct CborClient(Arc<AtomicUsize>);
+
+impl Drop for CborClient {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct RpcNotification {
     pub subscription_id: SubscriptionId,
+    /// Position of the notification among those of its subscription, so that clients can
+    /// tell which notifications they skipped
+    pub sequence: u64,
     pub is_final: bool,
     pub json: Weak<String>,
+    /// The notification encoded to CBOR from its typed form, while there are CBOR clients
+    pub cbor: Weak<Vec<u8>>,
+    /// Serialization span, so that the socket write can be linked back to it
+    pub span_id: Option<tracing::Id>,
+}
+
+struct RecentSignatureNotification {
+    slot: Slot,
+    commitment: CommitmentConfig,
+    result: transaction::Result<()>,
+    notified_at: Instant,
+}
+
+/// Memory accounted for each entry of the expiry queue, which also covers the map entry
+/// the queue entry may refer to
+const RECENT_SIGNATURE_NOTIFICATION_BYTES: usize = mem::size_of::<(Instant, Signature)>()
+    + mem::size_of::<(Signature, RecentSignatureNotification)>();
+
+struct RecentSignatureNotificationsInner {
+    by_signature: HashMap<Signature, RecentSignatureNotification>,
+    queue: VecDeque<(Instant, Signature)>,
+    memory_budget: MemoryBudget,
+}
+
+impl Drop for RecentSignatureNotificationsInner {
+    fn drop(&mut self) {
+        self.memory_budget.release(
+            MemoryCategory::RecentSignatures,
+            self.queue.len() * RECENT_SIGNATURE_NOTIFICATION_BYTES,
+        );
+    }
+}
+
+/// Terminal signature notifications retained for a short window, so that clients whose
+/// connection dropped right at confirmation can still recover the result.
+#[derive(Clone)]
+pub struct RecentSignatureNotifications {
+    retention: Duration,
+    inner: Arc<Mutex<RecentSignatureNotificationsInner>>,
+}
+
+fn commitment_rank(commitment: &CommitmentConfig) -> u8 {
+    if commitment.is_finalized() {
+        2
+    } else if commitment.is_confirmed() {
+        1
+    } else {
+        0
+    }
+}
+
+impl RecentSignatureNotifications {
+    pub fn new(retention: Duration, memory_budget: MemoryBudget) -> Self {
+        Self {
+            retention,
+            inner: Arc::new(Mutex::new(RecentSignatureNotificationsInner {
+                by_signature: HashMap::new(),
+                queue: VecDeque::new(),
+                memory_budget,
+            })),
+        }
+    }
+
+    fn purge_expired(&self, inner: &mut RecentSignatureNotificationsInner, now: Instant) {
+        while let Some((notified_at, signature)) = inner.queue.front().copied() {
+            if now.duration_since(notified_at) < self.retention {
+                break;
+            }
+            inner.queue.pop_front();
+            inner.memory_budget.release(
+                MemoryCategory::RecentSignatures,
+                RECENT_SIGNATURE_NOTIFICATION_BYTES,
+            );
+            // The entry may have been replaced by a notification at a higher commitment
+            if let Entry::Occupied(entry) = inner.by_signature.entry(signature) {
+                if entry.get().notified_at == notified_at {
+                    entry.remove();
+                }
+            }
+        }
+    }
+
+    /// Records a signature notification, unless one at a higher commitment is retained or
+    /// the memory budget has no room for it.
+    pub fn insert(
+        &self,
+        signature: Signature,
+        slot: Slot,
+        commitment: CommitmentConfig,
+        result: transaction::Result<()>,
+    ) {
+        if self.retention == Duration::default() {
+            return;
+        }
+        let now = Instant::now();
+        let mut inner = self.inner.lock().unwrap();
+        self.purge_expired(&mut inner, now);
+        if let Some(existing) = inner.by_signature.get(&signature) {
+            if commitment_rank(&existing.commitment) > commitment_rank(&commitment) {
+                return;
+            }
+        }
+        if !inner.memory_budget.try_reserve(
+            MemoryCategory::RecentSignatures,
+            RECENT_SIGNATURE_NOTIFICATION_BYTES,
+        ) {
+            return;
+        }
+        inner.by_signature.insert(
+            signature,
+            RecentSignatureNotification {
+                slot,
+                commitment,
+                result,
+                notified_at: now,
+            },
+        );
+        inner.queue.push_back((now, signature));
+    }
+
+    pub fn get(&self, signature: &Signature) -> Option<RpcRecentSignatureNotification> {
+        let mut inner = self.inner.lock().unwrap();
+        self.purge_expired(&mut inner, Instant::now());
+        inner
+            .by_signature
+            .get(signature)
+            .map(|notification| RpcRecentSignatureNotification {
+                slot: notification.slot,
+                commitment: notification.commitment.commitment,
+                result: RpcSignatureResult::ProcessedSignature(ProcessedSignatureResult {
+                    err: notification.result.clone().err(),
+                    confirmation_status: None,
+                }),
+            })
+    }
+}
+
+struct RecentItem {
+    json: Arc<String>,
+    cbor: Option<Arc<Vec<u8>>>,
+}
+
+impl RecentItem {
+    fn len(&self) -> usize {
+        self.json.len() + self.cbor.as_ref().map_or(0, |cbor| cbor.len())
+    }
 }
 
 struct RecentItems {
-    queue: VecDeque<Arc<String>>,
+    queue: VecDeque<RecentItem>,
     total_bytes: usize,
     max_len: usize,
     max_total_bytes: usize,
+    memory_budget: MemoryBudget,
 }
 
 impl RecentItems {
-    fn new(max_len: usize, max_total_bytes: usize) -> Self {
+    fn new(max_len: usize, max_total_bytes: usize, memory_budget: MemoryBudget) -> Self {
         Self {
             queue: VecDeque::new(),
             total_bytes: 0,
             max_len,
             max_total_bytes,
+            memory_budget,
         }
     }
 
-    fn push(&mut self, item: Arc<String>) {
+    /// Retains `item`, and returns the items evicted to make room for it. Every notifying
+    /// thread pushes under the same lock, so the evicted items are freed by the caller
+    /// once the lock is released.
+    #[must_use]
+    fn push(&mut self, item: RecentItem) -> Vec<RecentItem> {
         self.total_bytes = self
             .total_bytes
             .checked_add(item.len())
             .expect("total bytes overflow");
+        self.memory_budget
+            .reserve(MemoryCategory::RecentItems, item.len());
         self.queue.push_back(item);
 
-        while self.total_bytes > self.max_total_bytes || self.queue.len() > self.max_len {
+        // Keep the most recent item even when shedding, so the notification that was
+        // just generated can still be delivered.
+        let mut evicted = Vec::new();
+        while self.total_bytes > self.max_total_bytes
+            || self.queue.len() > self.max_len
+            || (self.queue.len() > 1 && self.memory_budget.should_shed(MemoryCategory::RecentItems))
+        {
             let item = self.queue.pop_front().expect("can't be empty");
             self.total_bytes = self
                 .total_bytes
                 .checked_sub(item.len())
                 .expect("total bytes underflow");
+            self.memory_budget
+                .release(MemoryCategory::RecentItems, item.len());
+            evicted.push(item);
         }
+        evicted
+    }
 
+    fn report_metrics(&self) {
         datapoint_info!(
             "rpc_subscriptions_recent_items",
             ("num", self.queue.len(), i64),
@@ -216,10 +737,109 @@ impl RecentItems {
     }
 }
 
+impl Drop for RecentItems {
+    fn drop(&mut self) {
+        self.memory_budget
+            .release(MemoryCategory::RecentItems, self.total_bytes);
+    }
+}
+
+/// Number of slots, counted back from the newest one, whose serialized results are kept
+/// around for other subscriptions. Covers the distance between processed and finalized.
+const SERIALIZED_RESULTS_SLOTS: Slot = 64;
+
+type SerializedResultKey = (Arc<SubscriptionParams>, Slot, usize);
+
+/// Serialized results of recent slots, keyed by the payload params of the subscription
+/// (see `SubscriptionInfo::payload_params`), the slot and the position of the result among
+/// the results for that slot. Subscriptions that only differ in commitment reuse the
+/// encoding done for the first one to reach a slot.
+struct SerializedResults {
+    by_key: HashMap<SerializedResultKey, Arc<String>>,
+    newest_slot: Slot,
+    total_bytes: usize,
+    hits: usize,
+    misses: usize,
+    memory_budget: MemoryBudget,
+}
+
+impl SerializedResults {
+    fn new(memory_budget: MemoryBudget) -> Self {
+        Self {
+            by_key: HashMap::new(),
+            newest_slot: 0,
+            total_bytes: 0,
+            hits: 0,
+            misses: 0,
+            memory_budget,
+        }
+    }
+
+    fn get(&mut self, key: &SerializedResultKey) -> Option<Arc<String>> {
+        let result = self.by_key.get(key).cloned();
+        if result.is_some() {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+        }
+        result
+    }
+
+    fn insert(&mut self, key: SerializedResultKey, result: Arc<String>) {
+        let slot = key.1;
+        if slot > self.newest_slot {
+            self.newest_slot = slot;
+            let oldest_slot = slot.saturating_sub(SERIALIZED_RESULTS_SLOTS);
+            let mut released = 0;
+            self.by_key.retain(|(_, slot, _), result| {
+                let retain = *slot > oldest_slot;
+                if !retain {
+                    released += result.len();
+                }
+                retain
+            });
+            self.release(released);
+        } else if slot.saturating_add(SERIALIZED_RESULTS_SLOTS) <= self.newest_slot {
+            return;
+        }
+        self.memory_budget
+            .reserve(MemoryCategory::RecentItems, result.len());
+        self.total_bytes += result.len();
+        if let Some(replaced) = self.by_key.insert(key, result) {
+            self.release(replaced.len());
+        }
+    }
+
+    fn release(&mut self, bytes: usize) {
+        self.total_bytes -= bytes;
+        self.memory_budget
+            .release(MemoryCategory::RecentItems, bytes);
+    }
+
+    /// Returns and resets the number of reused and encoded results
+    fn take_stats(&mut self) -> (usize, usize) {
+        (mem::take(&mut self.hits), mem::take(&mut self.misses))
+    }
+}
+
+impl Drop for SerializedResults {
+    fn drop(&mut self) {
+        self.memory_budget
+            .release(MemoryCategory::RecentItems, self.total_bytes);
+    }
+}
+
 struct RpcNotifier {
     sender: broadcast::Sender<RpcNotification>,
-    buf: Vec<u8>,
-    recent_items: RecentItems,
+    firehose_sender: broadcast::Sender<RpcNotification>,
+    /// Serialization buffers, pooled so that notifications can be serialized in parallel
+    scratch: Mutex<Vec<Vec<u8>>>,
+    serialized_results: Mutex<SerializedResults>,
+    recent_items: Mutex<RecentItems>,
+    recent_signatures: RecentSignatureNotifications,
+    memory_budget: MemoryBudget,
+    account_proofs: AccountProofsCache,
+    cbor_clients: CborClients,
 }
 
 #[derive(Debug, Serialize)]
@@ -236,38 +856,282 @@ struct Notification<T> {
 }
 
 impl RpcNotifier {
-    fn notify<T>(&mut self, value: T, subscription: &SubscriptionInfo, is_final: bool)
+    fn notify<T>(&self, value: T, subscription: &SubscriptionInfo, is_final: bool)
     where
         T: serde::Serialize,
     {
-        self.buf.clear();
+        self.notify_result(value, subscription, is_final, None)
+    }
+
+    /// Like `notify`, with `result_key` identifying `value` as the result at an index among
+    /// the results for a slot. The serialized result is then shared with other subscriptions
+    /// that have the same payload params.
+    fn notify_result<T>(
+        &self,
+        value: T,
+        subscription: &SubscriptionInfo,
+        is_final: bool,
+        result_key: Option<(Slot, usize)>,
+    ) where
+        T: serde::Serialize,
+    {
+        let span = debug_span!(
+            "rpc_notification_serialize",
+            subscription = u64::from(subscription.id()),
+            method = subscription.method(),
+            bytes = tracing::field::Empty,
+        );
+        let _enter = span.enter();
+
+        let cbor = self
+            .cbor_clients
+            .any()
+            .then(|| Arc::new(cbor_notification(subscription, &value)));
+        let mut buf = self.scratch.lock().unwrap().pop().unwrap_or_default();
+        let accounted = buf.capacity();
+        buf.clear();
+        let key = subscription
+            .payload_params()
+            .zip(result_key)
+            .map(|(params, (slot, index))| (params.clone(), slot, index));
+        if let Some(key) = key {
+            let cached = self.serialized_results.lock().unwrap().get(&key);
+            let result = match cached {
+                Some(result) => result,
+                None => {
+                    serde_json::to_writer(Cursor::new(&mut buf), &value)
+                        .expect("serialization never fails");
+                    let result = Arc::new(
+                        str::from_utf8(&buf)
+                            .expect("json is always utf-8")
+                            .to_string(),
+                    );
+                    if !self.memory_budget.should_shed(MemoryCategory::RecentItems) {
+                        self.serialized_results
+                            .lock()
+                            .unwrap()
+                            .insert(key, result.clone());
+                    }
+                    buf.clear();
+                    result
+                }
+            };
+            write_notification(&mut buf, subscription, &result);
+        } else {
+            let notification = Notification {
+                jsonrpc: Some(jsonrpc_core::Version::V2),
+                method: subscription.method(),
+                params: NotificationParams {
+                    result: value,
+                    subscription: subscription.id(),
+                },
+            };
+            serde_json::to_writer(Cursor::new(&mut buf), &notification)
+                .expect("serialization never fails");
+        }
+        let buf_str = str::from_utf8(&buf).expect("json is always utf-8");
+        let buf_arc = Arc::new(String::from(buf_str));
+        self.return_scratch_space(buf, accounted);
+        span.record("bytes", &(buf_arc.len() as u64));
+        self.send(buf_arc, cbor, subscription, is_final, span.id());
+    }
+
+    /// Tells `subscription` that notifications it would have received were dropped
+    fn notify_dropped(&self, dropped: &RpcNotificationsDropped, subscription: &SubscriptionInfo) {
         let notification = Notification {
             jsonrpc: Some(jsonrpc_core::Version::V2),
-            method: subscription.method(),
+            method: "notificationsDropped",
             params: NotificationParams {
-                result: value,
+                result: dropped,
                 subscription: subscription.id(),
             },
         };
-        serde_json::to_writer(Cursor::new(&mut self.buf), &notification)
-            .expect("serialization never fails");
-        let buf_str = str::from_utf8(&self.buf).expect("json is always utf-8");
-        let buf_arc = Arc::new(String::from(buf_str));
+        let json = serde_json::to_string(&notification).expect("serialization never fails");
+        self.send(Arc::new(json), None, subscription, false, None);
+    }
 
+    fn send(
+        &self,
+        buf_arc: Arc<String>,
+        cbor: Option<Arc<Vec<u8>>>,
+        subscription: &SubscriptionInfo,
+        is_final: bool,
+        span_id: Option<tracing::Id>,
+    ) {
+        let sequence = subscription.stats.record_notification(buf_arc.len());
         let notification = RpcNotification {
             subscription_id: subscription.id(),
+            sequence,
             json: Arc::downgrade(&buf_arc),
+            cbor: cbor.as_ref().map(Arc::downgrade).unwrap_or_default(),
             is_final,
+            span_id,
         };
         // There is an unlikely case where this can fail: if the last subscription is closed
         // just as the notifier generates a notification for it.
-        let _ = self.sender.send(notification);
+        let sender = if subscription.params().is_firehose() {
+            &self.firehose_sender
+        } else {
+            &self.sender
+        };
+        let _ = sender.send(notification);
 
         inc_new_counter_info!("rpc-pubsub-messages", 1);
         inc_new_counter_info!("rpc-pubsub-bytes", buf_arc.len());
 
-        self.recent_items.push(buf_arc);
+        let evicted = self.recent_items.lock().unwrap().push(RecentItem {
+            json: buf_arc,
+            cbor,
+        });
+        drop(evicted);
+    }
+
+    /// Keeps the memory budget in sync with the capacity of a serialization buffer that was
+    /// accounted as `accounted` bytes, then returns it to the pool, or releases it entirely if
+    /// scratch space has to be shed.
+    fn return_scratch_space(&self, buf: Vec<u8>, accounted: usize) {
+        let capacity = buf.capacity();
+        if capacity > accounted {
+            self.memory_budget
+                .reserve(MemoryCategory::Scratch, capacity - accounted);
+        } else {
+            self.memory_budget
+                .release(MemoryCategory::Scratch, accounted - capacity);
+        }
+        if self.memory_budget.should_shed(MemoryCategory::Scratch) {
+            self.memory_budget
+                .release(MemoryCategory::Scratch, capacity);
+        } else {
+            self.scratch.lock().unwrap().push(buf);
+        }
+    }
+}
+
+/// Encodes the notification of `result` to CBOR, with the same structure as its JSON
+fn cbor_notification<T: Serialize>(subscription: &SubscriptionInfo, result: &T) -> Vec<u8> {
+    let notification = Notification {
+        jsonrpc: Some(jsonrpc_core::Version::V2),
+        method: subscription.method(),
+        params: NotificationParams {
+            result,
+            subscription: subscription.id(),
+        },
+    };
+    serde_cbor::to_vec(&notification).expect("serialization never fails")
+}
+
+/// Writes the same JSON as serializing a `Notification` would, around an already serialized
+/// result
+fn write_notification(buf: &mut Vec<u8>, subscription: &SubscriptionInfo, result: &str) {
+    buf.extend_from_slice(br#"{"jsonrpc":"2.0","method":"#);
+    serde_json::to_writer(&mut *buf, subscription.method()).expect("serialization never fails");
+    buf.extend_from_slice(br#","params":{"result":"#);
+    buf.extend_from_slice(result.as_bytes());
+    buf.extend_from_slice(br#","subscription":"#);
+    serde_json::to_writer(&mut *buf, &subscription.id()).expect("serialization never fails");
+    buf.extend_from_slice(b"}}");
+}
+
+impl Drop for RpcNotifier {
+    fn drop(&mut self) {
+        let capacity = self
+            .scratch
+            .get_mut()
+            .unwrap()
+            .iter()
+            .map(Vec::capacity)
+            .sum();
+        self.memory_budget
+            .release(MemoryCategory::Scratch, capacity);
+    }
+}
+
+fn encode_account(
+    account: AccountSharedData,
+    params: &AccountSubscriptionParams,
+    bank: Arc<Bank>,
+) -> UiAccount {
+    if is_known_spl_token_id(account.owner()) && params.encoding == UiAccountEncoding::JsonParsed {
+        get_parsed_token_account(bank, &params.pubkey, account)
+    } else {
+        UiAccount::encode_with_compression_level(
+            &params.pubkey,
+            &account,
+            params.encoding,
+            None,
+            None,
+            params.compression_level,
+        )
+    }
+}
+
+/// Number of frozen banks whose account proofs are kept for the subscriptions asking for them
+const MAX_CACHED_ACCOUNT_PROOFS: usize = 4;
+
+/// Account proofs of the last few frozen banks, shared across subscriptions so that the
+/// accounts written in a bank are hashed once rather than once per notification
+#[derive(Default)]
+struct AccountProofsCache(Mutex<VecDeque<(Slot, Hash, Option<Arc<AccountProofs>>)>>);
+
+impl AccountProofsCache {
+    fn get(&self, bank: &Bank) -> Option<Arc<AccountProofs>> {
+        if !bank.is_frozen() {
+            return None;
+        }
+        let (slot, hash) = (bank.slot(), bank.hash());
+        let mut cache = self.0.lock().unwrap();
+        if let Some((.., proofs)) = cache
+            .iter()
+            .find(|(cached_slot, cached_hash, _)| *cached_slot == slot && *cached_hash == hash)
+        {
+            return proofs.clone();
+        }
+        // Hashed under the lock, so that subscriptions notified in parallel wait for the
+        // proofs instead of hashing the same bank again
+        let proofs = bank.get_account_proofs().map(Arc::new);
+        if cache.len() >= MAX_CACHED_ACCOUNT_PROOFS {
+            cache.pop_front();
+        }
+        cache.push_back((slot, hash, proofs.clone()));
+        proofs
+    }
+}
+
+/// Proves that `pubkey` was written at `slot`, using the bank of that slot among `bank` and
+/// its ancestors that are still linked to it. Rooted banks are unlinked from their parents,
+/// so there is no proof of writes older than the bank of a finalized subscription.
+fn get_account_proof(
+    bank: &Arc<Bank>,
+    pubkey: &Pubkey,
+    slot: Slot,
+    account_proofs: &AccountProofsCache,
+) -> Option<RpcAccountProof> {
+    let mut bank = bank.clone();
+    while bank.slot() > slot {
+        bank = bank.parent()?;
+    }
+    if bank.slot() != slot {
+        return None;
     }
+    let proof = account_proofs.get(&bank)?.get(pubkey)?;
+    let delta_proof = proof.accounts_delta_hash_proof;
+    Some(RpcAccountProof {
+        slot,
+        account_hash: delta_proof.account_hash.to_string(),
+        inclusion_path: delta_proof
+            .path
+            .into_iter()
+            .map(|level| RpcMerkleProofLevel {
+                index: level.index,
+                siblings: level.siblings.iter().map(Hash::to_string).collect(),
+            })
+            .collect(),
+        accounts_delta_hash: delta_proof.accounts_delta_hash.to_string(),
+        parent_bank_hash: proof.parent_hash.to_string(),
+        signature_count: proof.signature_count,
+        last_blockhash: proof.last_blockhash.to_string(),
+        bank_hash: proof.bank_hash.to_string(),
+    })
 }
 
 fn filter_account_result(
@@ -275,103 +1139,1174 @@ fn filter_account_result(
     params: &AccountSubscriptionParams,
     last_notified_slot: Slot,
     bank: Arc<Bank>,
-) -> (Box<dyn Iterator<Item = UiAccount>>, Slot) {
+    account_proofs: &AccountProofsCache,
+) -> (Box<dyn Iterator<Item = RpcAccountUpdate>>, Slot) {
+    // A zero-lamport account was closed at `last_modified_slot` and is reported as deleted
+    let deleted = matches!(&result, Some((account, _)) if account.lamports() == 0);
+    let found = result.is_some();
     // If the account is not found, `last_modified_slot` will default to zero and
     // we will notify clients that the account no longer exists if we haven't already
     let (account, last_modified_slot) = result.unwrap_or_default();
 
     // If last_modified_slot < last_notified_slot this means that we last notified for a fork
     // and should notify that the account state has been reverted.
-    let results: Box<dyn Iterator<Item = UiAccount>> = if last_modified_slot != last_notified_slot {
-        if account.owner() == &spl_token_id_v2_0()
-            && params.encoding == UiAccountEncoding::JsonParsed
-        {
-            Box::new(iter::once(get_parsed_token_account(
-                bank,
-                &params.pubkey,
-                account,
-            )))
+    let results: Box<dyn Iterator<Item = RpcAccountUpdate>> =
+        if last_modified_slot != last_notified_slot {
+            let proof = if params.with_proof && found {
+                get_account_proof(&bank, &params.pubkey, last_modified_slot, account_proofs)
+            } else {
+                None
+            };
+            let update = if deleted {
+                RpcAccountUpdate {
+                    account: encode_account(AccountSharedData::default(), params, bank),
+                    deleted: Some(true),
+                    proof,
+                }
+            } else {
+                RpcAccountUpdate {
+                    account: encode_account(account, params, bank),
+                    deleted: None,
+                    proof,
+                }
+            };
+            Box::new(iter::once(update))
         } else {
-            Box::new(iter::once(UiAccount::encode(
-                &params.pubkey,
-                &account,
-                params.encoding,
-                None,
-                None,
-            )))
-        }
-    } else {
-        Box::new(iter::empty())
-    };
+            Box::new(iter::empty())
+        };
 
     (results, last_modified_slot)
 }
 
-fn filter_signature_result(
-    result: Option<transaction::Result<()>>,
-    _params: &SignatureSubscriptionParams,
+fn filter_accounts_results(
+    results: Vec<(Pubkey, Option<(AccountSharedData, Slot)>)>,
+    params: &AccountsSubscriptionParams,
+    last_notified_slot: Slot,
+    last_notified_account_slots: &RwLock<HashMap<Pubkey, Slot>>,
+    bank: Arc<Bank>,
+) -> (Box<dyn Iterator<Item = RpcKeyedAccount>>, Slot) {
+    let notifications = changed_keyed_accounts(
+        results,
+        params.encoding,
+        params.data_slice,
+        last_notified_account_slots,
+        bank,
+        |_account| true,
+    );
+    (Box::new(notifications.into_iter()), last_notified_slot)
+}
+
+fn filter_token_accounts_by_owner_results(
+    results: Vec<(Pubkey, Option<(AccountSharedData, Slot)>)>,
+    params: &TokenAccountsByOwnerSubscriptionParams,
+    last_notified_slot: Slot,
+    last_notified_account_slots: &RwLock<HashMap<Pubkey, Slot>>,
+    bank: Arc<Bank>,
+) -> (Box<dyn Iterator<Item = RpcKeyedAccount>>, Slot) {
+    // Accounts that were closed or transferred to another owner are notified one last time
+    let notifications = changed_keyed_accounts(
+        results,
+        params.encoding,
+        params.data_slice,
+        last_notified_account_slots,
+        bank,
+        |account| get_spl_token_owner(account) == Some(params.owner),
+    );
+    (Box::new(notifications.into_iter()), last_notified_slot)
+}
+
+/// Encodes the accounts whose modified slot differs from the one last notified for them,
+/// following the same rules as `filter_account_result` for each account separately.
+/// Accounts for which `keep_tracking` returns false are forgotten once notified.
+fn changed_keyed_accounts<K>(
+    results: Vec<(Pubkey, Option<(AccountSharedData, Slot)>)>,
+    encoding: UiAccountEncoding,
+    data_slice: Option<UiDataSliceConfig>,
+    last_notified_account_slots: &RwLock<HashMap<Pubkey, Slot>>,
+    bank: Arc<Bank>,
+    keep_tracking: K,
+) -> Vec<RpcKeyedAccount>
+where
+    K: Fn(&AccountSharedData) -> bool,
+{
+    let mut w_last_notified_account_slots = last_notified_account_slots.write().unwrap();
+    let mut notifications = vec![];
+    for (pubkey, result) in results {
+        let (account, last_modified_slot) = result.unwrap_or_default();
+        let notified_slot = w_last_notified_account_slots.entry(pubkey).or_default();
+        if *notified_slot == last_modified_slot {
+            continue;
+        }
+        *notified_slot = last_modified_slot;
+        if !keep_tracking(&account) {
+            w_last_notified_account_slots.remove(&pubkey);
+        }
+        let account = if is_known_spl_token_id(account.owner())
+            && encoding == UiAccountEncoding::JsonParsed
+        {
+            get_parsed_token_account(bank.clone(), &pubkey, account)
+        } else {
+            UiAccount::encode(&pubkey, &account, encoding, None, data_slice)
+        };
+        notifications.push(RpcKeyedAccount {
+            pubkey: pubkey.to_string(),
+            account,
+        });
+    }
+    notifications
+}
+
+fn get_spl_token_owner(account: &AccountSharedData) -> Option<Pubkey> {
+    if !is_spl_token_account(account.owner(), account.data()) {
+        return None;
+    }
+    Some(Pubkey::new(
+        &account.data()
+            [SPL_TOKEN_ACCOUNT_OWNER_OFFSET..SPL_TOKEN_ACCOUNT_OWNER_OFFSET + PUBKEY_BYTES],
+    ))
+}
+
+/// Token accounts owned by `owner` according to the owner index, plus the already tracked
+/// accounts that the index no longer lists, along with their modified slots
+fn get_token_accounts_by_owner_modified_slots(
+    bank: &Bank,
+    owner: &Pubkey,
+    tracked: &HashMap<Pubkey, Slot>,
+) -> Vec<(Pubkey, Option<(AccountSharedData, Slot)>)> {
+    let mut pubkeys: HashSet<Pubkey> = tracked.keys().copied().collect();
+    match bank.get_filtered_indexed_accounts(&IndexKey::SplTokenOwner(*owner), |account| {
+        is_spl_token_account(account.owner(), account.data())
+    }) {
+        Ok(accounts) => pubkeys.extend(accounts.into_iter().map(|(pubkey, _account)| pubkey)),
+        Err(err) => warn!("token accounts scan for owner {} failed: {:?}", owner, err),
+    }
+    pubkeys
+        .into_iter()
+        .map(|pubkey| (pubkey, bank.get_account_modified_slot(&pubkey)))
+        .collect()
+}
+
+fn get_mint_state(account: &AccountSharedData) -> Option<RpcMintState> {
+    if !is_known_spl_token_id(account.owner()) {
+        return None;
+    }
+    match parse_token(account.data(), None) {
+        Ok(TokenAccountType::Mint(mint)) => Some(RpcMintState {
+            supply: mint.supply,
+            decimals: mint.decimals,
+            mint_authority: mint.mint_authority,
+            freeze_authority: mint.freeze_authority,
+        }),
+        _ => None,
+    }
+}
+
+fn filter_mint_result(
+    result: Option<(AccountSharedData, Slot)>,
+    params: &MintSubscriptionParams,
+    last_notified_slot: Slot,
+    last_notified_mint: &RwLock<Option<RpcMintState>>,
+) -> (Box<dyn Iterator<Item = RpcMintUpdate>>, Slot) {
+    let (account, last_modified_slot) = result.unwrap_or_default();
+    let current = match get_mint_state(&account) {
+        Some(current) => current,
+        None => return (Box::new(iter::empty()), last_notified_slot),
+    };
+
+    let mut w_last_notified_mint = last_notified_mint.write().unwrap();
+    if w_last_notified_mint.is_none() && last_modified_slot == last_notified_slot {
+        // The mint has not been written since the subscription was created, so this is
+        // the baseline that the first update is reported against.
+        *w_last_notified_mint = Some(current);
+        return (Box::new(iter::empty()), last_notified_slot);
+    }
+    // Writes that leave supply, decimals and authorities untouched are not reported
+    if w_last_notified_mint.as_ref() == Some(&current) {
+        return (Box::new(iter::empty()), last_notified_slot);
+    }
+    let previous = w_last_notified_mint.replace(current.clone());
+    (
+        Box::new(iter::once(RpcMintUpdate {
+            mint: params.pubkey.to_string(),
+            previous,
+            current,
+        })),
+        last_modified_slot,
+    )
+}
+
+fn filter_stake_result(
+    result: Option<(AccountSharedData, Slot)>,
+    params: &StakeSubscriptionParams,
+    last_notified_slot: Slot,
+    bank: Arc<Bank>,
+) -> (Box<dyn Iterator<Item = RpcStakeUpdate>>, Slot) {
+    let (account, last_modified_slot) = result.unwrap_or_default();
+    if last_modified_slot == last_notified_slot {
+        return (Box::new(iter::empty()), last_notified_slot);
+    }
+    // A closed stake account is reported as uninitialized and empty
+    let (state, activation) = if account.lamports() == 0 {
+        (StakeAccountType::Uninitialized, None)
+    } else {
+        if account.owner() != &stake::program::id() {
+            return (Box::new(iter::empty()), last_notified_slot);
+        }
+        let state = match parse_stake(account.data()) {
+            Ok(state) => state,
+            Err(_) => return (Box::new(iter::empty()), last_notified_slot),
+        };
+        (state, get_stake_account_activation(&account, &bank))
+    };
+    (
+        Box::new(iter::once(RpcStakeUpdate {
+            stake: params.pubkey.to_string(),
+            lamports: account.lamports(),
+            state,
+            activation,
+        })),
+        last_modified_slot,
+    )
+}
+
+fn get_stake_account_activation(
+    account: &AccountSharedData,
+    bank: &Bank,
+) -> Option<RpcStakeActivation> {
+    let stake_state: StakeState = bincode::deserialize(account.data()).ok()?;
+    match stake_state.delegation() {
+        Some(delegation) => {
+            let stake_history = bank
+                .get_account(&sysvar::stake_history::id())
+                .and_then(|account| from_account::<StakeHistory, _>(&account))?;
+            Some(get_delegation_activation(
+                &delegation,
+                bank.epoch(),
+                &stake_history,
+            ))
+        }
+        None => stake_state.meta().map(|meta| RpcStakeActivation {
+            state: StakeActivationState::Inactive,
+            active: 0,
+            inactive: account.lamports().saturating_sub(meta.rent_exempt_reserve),
+        }),
+    }
+}
+
+/// Kinds of subscriptions counted in the metrics of a notification pass
+#[derive(Clone, Copy)]
+enum NotifyKind {
+    Accounts,
+    Logs,
+    Signatures,
+    Programs,
+    Mints,
+    Blocks,
+    Blockhashes,
+    Transactions,
+}
+
+const NUM_NOTIFY_KINDS: usize = 8;
+
+/// Subscriptions checked and notified in a notification pass, by kind. Counted per thread
+/// of the notification pool and merged at the end, rather than through shared counters.
+#[derive(Default)]
+struct NotifyCounts {
+    found: [usize; NUM_NOTIFY_KINDS],
+    notified: [usize; NUM_NOTIFY_KINDS],
+}
+
+impl NotifyCounts {
+    fn record(mut self, (kind, notified): (NotifyKind, bool)) -> Self {
+        self.found[kind as usize] += 1;
+        self.notified[kind as usize] += usize::from(notified);
+        self
+    }
+
+    fn merge(mut self, other: Self) -> Self {
+        for (found, other) in self.found.iter_mut().zip(other.found) {
+            *found += other;
+        }
+        for (notified, other) in self.notified.iter_mut().zip(other.notified) {
+            *notified += other;
+        }
+        self
+    }
+
+    /// Number of subscriptions of `kind` checked, and notified
+    fn get(&self, kind: NotifyKind) -> (usize, usize) {
+        (self.found[kind as usize], self.notified[kind as usize])
+    }
+}
+
+/// Blocks completed in a slot. Each block is read out of the blockstore at most once per
+/// notification pass and shared by every block and transaction subscription.
+#[derive(Default)]
+struct CompletedBlocks(DashMap<Slot, Option<Arc<ConfirmedBlock>>>);
+
+impl CompletedBlocks {
+    /// `None` if the block failed to load
+    fn get_or_load(&self, blockstore: &Blockstore, slot: Slot) -> Option<Arc<ConfirmedBlock>> {
+        self.0
+            .entry(slot)
+            .or_insert_with(|| match blockstore.get_complete_block(slot, false) {
+                Ok(block) => Some(Arc::new(block)),
+                Err(err) => {
+                    warn!("failed to load block {} for notification: {}", slot, err);
+                    None
+                }
+            })
+            .clone()
+    }
+}
+
+/// Vote subscriptions of the node progress thread, indexed by vote account so that a vote is
+/// matched without scanning every subscription
+#[derive(Default)]
+struct VoteWatchers {
+    /// Subscriptions to the votes of every account
+    all: HashMap<SubscriptionId, Arc<SubscriptionInfo>>,
+    by_vote_pubkey: HashMap<Pubkey, HashMap<SubscriptionId, Arc<SubscriptionInfo>>>,
+}
+
+impl VoteWatchers {
+    fn insert(&mut self, params: &VoteSubscriptionParams, info: &Arc<SubscriptionInfo>) {
+        match &params.vote_pubkeys {
+            None => {
+                self.all.insert(info.id(), Arc::clone(info));
+            }
+            Some(vote_pubkeys) => {
+                for vote_pubkey in vote_pubkeys {
+                    self.by_vote_pubkey
+                        .entry(*vote_pubkey)
+                        .or_default()
+                        .insert(info.id(), Arc::clone(info));
+                }
+            }
+        }
+    }
+
+    fn remove(&mut self, params: &VoteSubscriptionParams, id: SubscriptionId) {
+        match &params.vote_pubkeys {
+            None => {
+                self.all.remove(&id);
+            }
+            Some(vote_pubkeys) => {
+                for vote_pubkey in vote_pubkeys {
+                    if let Entry::Occupied(mut entry) = self.by_vote_pubkey.entry(*vote_pubkey) {
+                        entry.get_mut().remove(&id);
+                        if entry.get().is_empty() {
+                            entry.remove();
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Subscriptions to the votes of `vote_pubkey`
+    fn get<'a>(&'a self, vote_pubkey: &Pubkey) -> impl Iterator<Item = &'a Arc<SubscriptionInfo>> {
+        self.all.values().chain(
+            self.by_vote_pubkey
+                .get(vote_pubkey)
+                .into_iter()
+                .flat_map(|subscriptions| subscriptions.values()),
+        )
+    }
+}
+
+fn filter_block_result(
+    block: &ConfirmedBlock,
+    params: &BlockSubscriptionParams,
+) -> Option<UiConfirmedBlock> {
+    let block = match &params.kind {
+        BlockSubscriptionKind::MentionsAccountOrProgram(pubkey) => {
+            let transactions: Vec<_> = block
+                .transactions
+                .iter()
+                .filter(|tx| tx.transaction.message.account_keys.contains(pubkey))
+                .cloned()
+                .collect();
+            if transactions.is_empty() {
+                return None;
+            }
+            ConfirmedBlock {
+                previous_blockhash: block.previous_blockhash.clone(),
+                blockhash: block.blockhash.clone(),
+                parent_slot: block.parent_slot,
+                transactions,
+                rewards: block.rewards.clone(),
+                block_time: block.block_time,
+                block_height: block.block_height,
+            }
+        }
+        _ => block.clone(),
+    };
+    Some(block.configure(
+        params.encoding,
+        params.transaction_details,
+        params.show_rewards,
+    ))
+}
+
+/// Visits every block on the fork of the bank at `commitment` that was completed since
+/// the subscription was last notified. `notify_block` returns whether it notified; it gets
+/// `None` for blocks that failed to load.
+fn check_blocks_and_notify<F>(
+    commitment: &CommitmentConfig,
+    subscription: &SubscriptionInfo,
+    bank_forks: &Arc<RwLock<BankForks>>,
+    blockstore: &Blockstore,
+    completed_blocks: &CompletedBlocks,
+    commitment_slots: &CommitmentSlots,
+    mut notify_block: F,
+) -> bool
+where
+    F: FnMut(Slot, Option<&ConfirmedBlock>) -> bool,
+{
+    let slot = if commitment.is_finalized() {
+        commitment_slots.highest_confirmed_root
+    } else {
+        commitment_slots.highest_confirmed_slot
+    };
+    let bank = match bank_forks.read().unwrap().get(slot).cloned() {
+        Some(bank) => bank,
+        None => return false,
+    };
+
+    let _span = debug_span!(
+        "rpc_notification_filter",
+        subscription = u64::from(subscription.id()),
+        method = subscription.method(),
+        slot,
+    )
+    .entered();
+
+    let mut notified = false;
+    let mut w_last_notified_slot = subscription.last_notified_slot.write().unwrap();
+    for block_slot in (*w_last_notified_slot + 1)..=slot {
+        // Skipped slots and blocks on other forks are never reported
+        if block_slot != slot
+            && !bank.ancestors.contains_key(&block_slot)
+            && !blockstore.is_root(block_slot)
+        {
+            continue;
+        }
+        let block = completed_blocks.get_or_load(blockstore, block_slot);
+        notified |= notify_block(block_slot, block.as_deref());
+        *w_last_notified_slot = block_slot;
+    }
+    notified
+}
+
+fn check_block_commitment_and_notify(
+    params: &BlockSubscriptionParams,
+    subscription: &SubscriptionInfo,
+    bank_forks: &Arc<RwLock<BankForks>>,
+    blockstore: &Blockstore,
+    completed_blocks: &CompletedBlocks,
+    commitment_slots: &CommitmentSlots,
+    notifier: &RpcNotifier,
+) -> bool {
+    check_blocks_and_notify(
+        &params.commitment,
+        subscription,
+        bank_forks,
+        blockstore,
+        completed_blocks,
+        commitment_slots,
+        |slot, block| {
+            let block_update = match block {
+                Some(block) => filter_block_result(block, params).map(|block| RpcBlockUpdate {
+                    slot,
+                    block: Some(block),
+                    err: None,
+                }),
+                None => Some(RpcBlockUpdate {
+                    slot,
+                    block: None,
+                    err: Some(RpcBlockUpdateError::BlockStoreError),
+                }),
+            };
+            match block_update {
+                Some(block_update) => {
+                    notifier.notify(
+                        Response {
+                            context: RpcResponseContext { slot },
+                            value: block_update,
+                        },
+                        subscription,
+                        false,
+                    );
+                    true
+                }
+                None => false,
+            }
+        },
+    )
+}
+
+fn check_transaction_commitment_and_notify(
+    params: &TransactionSubscriptionParams,
+    subscription: &SubscriptionInfo,
+    bank_forks: &Arc<RwLock<BankForks>>,
+    blockstore: &Blockstore,
+    completed_blocks: &CompletedBlocks,
+    commitment_slots: &CommitmentSlots,
+    notifier: &RpcNotifier,
+) -> bool {
+    check_blocks_and_notify(
+        &params.commitment,
+        subscription,
+        bank_forks,
+        blockstore,
+        completed_blocks,
+        commitment_slots,
+        |slot, block| {
+            let block = match block {
+                Some(block) => block,
+                None => return false,
+            };
+            let mut notified = false;
+            for transaction in &block.transactions {
+                if !transaction
+                    .transaction
+                    .message
+                    .account_keys
+                    .contains(&params.pubkey)
+                {
+                    continue;
+                }
+                let transaction = ConfirmedTransaction {
+                    slot,
+                    transaction: transaction.clone(),
+                    block_time: block.block_time,
+                };
+                notifier.notify(
+                    Response {
+                        context: RpcResponseContext { slot },
+                        value: transaction.encode(params.encoding),
+                    },
+                    subscription,
+                    false,
+                );
+                notified = true;
+            }
+            notified
+        },
+    )
+}
+
+/// Notifies a signature subscription with follow-up commitments once for each level that
+/// the transaction has reached, in order, and ends the subscription after the last one.
+fn check_signature_commitment_levels_and_notify(
+    params: &SignatureSubscriptionParams,
+    subscription: &SubscriptionInfo,
+    bank_forks: &RwLock<BankForks>,
+    commitment_slots: &CommitmentSlots,
+    recent_signatures: &RecentSignatureNotifications,
+    notifier: &RpcNotifier,
+) -> bool {
+    let commitments: Vec<_> = params.commitments().collect();
+    let commitment_levels_notified = subscription
+        .commitment_levels_notified()
+        .expect("signature subscription with follow-up commitments");
+    let mut notified = false;
+    loop {
+        let level = commitment_levels_notified.load(Ordering::Relaxed);
+        let commitment = match commitments.get(level) {
+            Some(commitment) => *commitment,
+            None => break,
+        };
+        let slot = commitment_slot(&commitment, commitment_slots);
+        let bank = match bank_forks.read().unwrap().get(slot).cloned() {
+            Some(bank) => bank,
+            None => break,
+        };
+        let result = match bank.get_signature_status_slot(&params.signature) {
+            Some((_slot, result)) => result,
+            None => break,
+        };
+        commitment_levels_notified.store(level + 1, Ordering::Relaxed);
+        recent_signatures.insert(params.signature, slot, commitment, result.clone());
+        let confirmation_status = if commitment.is_finalized() {
+            TransactionConfirmationStatus::Finalized
+        } else if commitment.is_confirmed() {
+            TransactionConfirmationStatus::Confirmed
+        } else {
+            TransactionConfirmationStatus::Processed
+        };
+        notifier.notify(
+            Response {
+                context: RpcResponseContext { slot },
+                value: RpcSignatureResult::ProcessedSignature(ProcessedSignatureResult {
+                    err: result.err(),
+                    confirmation_status: Some(confirmation_status),
+                }),
+            },
+            subscription,
+            level + 1 == commitments.len(),
+        );
+        notified = true;
+    }
+    notified
+}
+
+/// Notifies once the blockhash is within `expiry_warning_blocks` of its last valid block
+/// height, and a final time once it has expired at the subscription's commitment level.
+/// A blockhash unknown to the node is reported as expired right away.
+fn check_blockhash_validity_and_notify(
+    params: &BlockhashValiditySubscriptionParams,
+    subscription: &SubscriptionInfo,
+    bank_forks: &RwLock<BankForks>,
+    commitment_slots: &CommitmentSlots,
+    notifier: &RpcNotifier,
+) -> bool {
+    let slot = commitment_slot(&params.commitment, commitment_slots);
+    let (bank, working_bank) = {
+        let bank_forks = bank_forks.read().unwrap();
+        match bank_forks.get(slot).cloned() {
+            Some(bank) => (bank, bank_forks.working_bank()),
+            None => return false,
+        }
+    };
+    let mut state = subscription
+        .blockhash_validity()
+        .expect("blockhash validity subscription")
+        .write()
+        .unwrap();
+    if state.last_valid_block_height.is_none() {
+        // The blockhash may not have reached the subscription's commitment level yet
+        state.last_valid_block_height = bank
+            .get_blockhash_last_valid_block_height(&params.blockhash)
+            .or_else(|| working_bank.get_blockhash_last_valid_block_height(&params.blockhash));
+    }
+    let block_height = bank.block_height();
+    let valid = state
+        .last_valid_block_height
+        .map(|last_valid_block_height| block_height <= last_valid_block_height)
+        .unwrap_or(false);
+    if valid {
+        let warn = match (params.expiry_warning_blocks, state.last_valid_block_height) {
+            (Some(warning_blocks), Some(last_valid_block_height)) => {
+                !state.expiry_warning_sent
+                    && last_valid_block_height.saturating_sub(block_height) <= warning_blocks
+            }
+            _ => false,
+        };
+        if !warn {
+            return false;
+        }
+        state.expiry_warning_sent = true;
+    }
+    notifier.notify(
+        Response {
+            context: RpcResponseContext { slot },
+            value: RpcBlockhashValidity {
+                blockhash: params.blockhash.to_string(),
+                valid,
+                status: if valid {
+                    RpcBlockhashStatus::Valid
+                } else if state.last_valid_block_height.is_some() {
+                    RpcBlockhashStatus::Expired
+                } else {
+                    RpcBlockhashStatus::Unknown
+                },
+                block_height,
+                last_valid_block_height: state.last_valid_block_height,
+            },
+        },
+        subscription,
+        !valid,
+    );
+    true
+}
+
+fn filter_signature_result(
+    result: Option<transaction::Result<()>>,
+    _params: &SignatureSubscriptionParams,
     last_notified_slot: Slot,
     _bank: Arc<Bank>,
 ) -> (Box<dyn Iterator<Item = RpcSignatureResult>>, Slot) {
     (
         Box::new(result.into_iter().map(|result| {
-            RpcSignatureResult::ProcessedSignature(ProcessedSignatureResult { err: result.err() })
+            RpcSignatureResult::ProcessedSignature(ProcessedSignatureResult {
+                err: result.err(),
+                confirmation_status: None,
+            })
         })),
         last_notified_slot,
     )
 }
 
+/// Accounts modified in a slot, by program. Each program is loaded at most once per
+/// notification pass and shared by every subscription to it, so subscribers only pay for
+/// evaluating their own filters.
+#[derive(Default)]
+struct ProgramWrites(DashMap<(Slot, Pubkey), Arc<Vec<(Pubkey, AccountSharedData)>>>);
+
+impl ProgramWrites {
+    fn get_or_load(
+        &self,
+        bank: &Bank,
+        program_id: &Pubkey,
+    ) -> Arc<Vec<(Pubkey, AccountSharedData)>> {
+        let key = (bank.slot(), *program_id);
+        if let Some(accounts) = self.0.get(&key) {
+            return accounts.clone();
+        }
+        // Loaded without holding the shard lock, the first load to finish is kept
+        let accounts = Arc::new(bank.get_program_accounts_modified_since_parent(program_id));
+        self.0.entry(key).or_insert(accounts).clone()
+    }
+}
+
+fn program_filters_match(filters: &[RpcFilterType], account: &AccountSharedData) -> bool {
+    filters.iter().all(|filter_type| match filter_type {
+        RpcFilterType::DataSize(size) => account.data().len() as u64 == *size,
+        RpcFilterType::Memcmp(compare) => compare.bytes_match(account.data()),
+    })
+}
+
 fn filter_program_results(
-    accounts: Vec<(Pubkey, AccountSharedData)>,
+    accounts: Arc<Vec<(Pubkey, AccountSharedData)>>,
     params: &ProgramSubscriptionParams,
     last_notified_slot: Slot,
     bank: Arc<Bank>,
 ) -> (Box<dyn Iterator<Item = RpcKeyedAccount>>, Slot) {
     let accounts_is_empty = accounts.is_empty();
     let encoding = params.encoding;
-    let filters = params.filters.clone();
-    let keyed_accounts = accounts.into_iter().filter(move |(_, account)| {
-        filters.iter().all(|filter_type| match filter_type {
-            RpcFilterType::DataSize(size) => account.data().len() as u64 == *size,
-            RpcFilterType::Memcmp(compare) => compare.bytes_match(account.data()),
-        })
-    });
-    let accounts: Box<dyn Iterator<Item = RpcKeyedAccount>> = if params.pubkey
-        == spl_token_id_v2_0()
-        && params.encoding == UiAccountEncoding::JsonParsed
-        && !accounts_is_empty
-    {
-        Box::new(get_parsed_token_accounts(bank, keyed_accounts))
-    } else {
-        Box::new(
-            keyed_accounts.map(move |(pubkey, account)| RpcKeyedAccount {
-                pubkey: pubkey.to_string(),
-                account: UiAccount::encode(&pubkey, &account, encoding, None, None),
-            }),
-        )
-    };
+    let data_slice = params.data_slice;
+    let compression_level = params.compression_level;
+    // The accounts are shared with other subscriptions, only clone the ones that match
+    let keyed_accounts: Vec<_> = accounts
+        .iter()
+        .filter(|(_, account)| program_filters_match(&params.filters, account))
+        .cloned()
+        .collect();
+    let keyed_accounts = keyed_accounts.into_iter();
+    let accounts: Box<dyn Iterator<Item = RpcKeyedAccount>> =
+        if is_known_spl_token_id(&params.pubkey)
+            && params.encoding == UiAccountEncoding::JsonParsed
+            && !accounts_is_empty
+        {
+            Box::new(get_parsed_token_accounts(bank, keyed_accounts))
+        } else {
+            Box::new(
+                keyed_accounts.map(move |(pubkey, account)| RpcKeyedAccount {
+                    pubkey: pubkey.to_string(),
+                    account: UiAccount::encode_with_compression_level(
+                        &pubkey,
+                        &account,
+                        encoding,
+                        None,
+                        data_slice,
+                        compression_level,
+                    ),
+                }),
+            )
+        };
     (accounts, last_notified_slot)
 }
 
+/// Like `filter_program_results`, but all the accounts of the slot go in a single result
+fn filter_program_results_batched(
+    accounts: Arc<Vec<(Pubkey, AccountSharedData)>>,
+    params: &ProgramSubscriptionParams,
+    last_notified_slot: Slot,
+    bank: Arc<Bank>,
+) -> (Box<dyn Iterator<Item = Vec<RpcKeyedAccount>>>, Slot) {
+    let (keyed_accounts, last_notified_slot) =
+        filter_program_results(accounts, params, last_notified_slot, bank);
+    let keyed_accounts: Vec<_> = keyed_accounts.collect();
+    if keyed_accounts.is_empty() {
+        return (Box::new(iter::empty()), last_notified_slot);
+    }
+    (Box::new(iter::once(keyed_accounts)), last_notified_slot)
+}
+
+/// Reports every account modified in the slot in a single notification, without data.
+fn filter_program_changes_result(
+    accounts: Arc<Vec<(Pubkey, AccountSharedData)>>,
+    _params: &ProgramChangesSubscriptionParams,
+    last_notified_slot: Slot,
+    _bank: Arc<Bank>,
+) -> (Box<dyn Iterator<Item = Vec<RpcAccountChange>>>, Slot) {
+    if accounts.is_empty() {
+        return (Box::new(iter::empty()), last_notified_slot);
+    }
+    let changes = accounts
+        .iter()
+        .map(|(pubkey, account)| RpcAccountChange {
+            pubkey: pubkey.to_string(),
+            lamports: account.lamports(),
+        })
+        .collect();
+    (Box::new(iter::once(changes)), last_notified_slot)
+}
+
+fn filter_blockhash_result(
+    _result: (),
+    _params: &BlockhashSubscriptionParams,
+    last_notified_slot: Slot,
+    bank: Arc<Bank>,
+) -> (Box<dyn Iterator<Item = RpcBlockhash>>, Slot) {
+    if bank.slot() <= last_notified_slot {
+        return (Box::new(iter::empty()), last_notified_slot);
+    }
+    let blockhash = bank.last_blockhash();
+    let last_valid_block_height = bank
+        .get_blockhash_last_valid_block_height(&blockhash)
+        .expect("bank blockhash queue should contain blockhash");
+    (
+        Box::new(iter::once(RpcBlockhash {
+            blockhash: blockhash.to_string(),
+            last_valid_block_height,
+        })),
+        bank.slot(),
+    )
+}
+
+/// Describes the epoch `bank` starts, if `bank` is the first one rooted in its epoch.
+fn epoch_start(bank: &Bank) -> Option<RpcEpochStart> {
+    let epoch_schedule = bank.epoch_schedule();
+    let epoch = bank.epoch();
+    if epoch_schedule.get_epoch(bank.parent_slot()) == epoch {
+        return None;
+    }
+    let previous_epoch = epoch.saturating_sub(1);
+    let stake_activation = bank
+        .get_account(&sysvar::stake_history::id())
+        .and_then(|account| from_account::<StakeHistory, _>(&account))
+        .and_then(|stake_history| {
+            stake_history
+                .get(&previous_epoch)
+                .map(|entry| RpcStakeActivationSummary {
+                    epoch: previous_epoch,
+                    effective: entry.effective,
+                    activating: entry.activating,
+                    deactivating: entry.deactivating,
+                })
+        });
+    Some(RpcEpochStart {
+        epoch,
+        first_slot: epoch_schedule.get_first_slot_in_epoch(epoch),
+        slots_in_epoch: epoch_schedule.get_slots_in_epoch(epoch),
+        slot: bank.slot(),
+        leader_schedule_ready: bank.epoch_stakes(epoch.saturating_add(1)).is_some(),
+        stake_activation,
+    })
+}
+
 fn filter_logs_results(
     logs: Option<Vec<TransactionLogInfo>>,
-    _params: &LogsSubscriptionParams,
+    params: &LogsSubscriptionParams,
     last_notified_slot: Slot,
     _bank: Arc<Bank>,
 ) -> (Box<dyn Iterator<Item = RpcLogsResponse>>, Slot) {
+    let filter_status = params.filter_status;
     match logs {
         None => (Box::new(iter::empty()), last_notified_slot),
         Some(logs) => (
-            Box::new(logs.into_iter().map(|log| RpcLogsResponse {
-                signature: log.signature.to_string(),
-                err: log.result.err(),
-                logs: log.log_messages,
-            })),
+            Box::new(
+                logs.into_iter()
+                    .filter(move |log| match filter_status {
+                        RpcTransactionLogsStatusFilter::All => true,
+                        RpcTransactionLogsStatusFilter::Succeeded => log.result.is_ok(),
+                        RpcTransactionLogsStatusFilter::Failed => log.result.is_err(),
+                    })
+                    .map(|log| RpcLogsResponse {
+                        signature: log.signature.to_string(),
+                        err: log.result.err(),
+                        logs: log.log_messages,
+                    }),
+            ),
             last_notified_slot,
         ),
     }
 }
 
+fn commitment_bank_slot(
+    commitment: &CommitmentConfig,
+    block_commitment_cache: &RwLock<BlockCommitmentCache>,
+    optimistically_confirmed_bank: &RwLock<OptimisticallyConfirmedBank>,
+) -> Slot {
+    if commitment.is_finalized() {
+        block_commitment_cache
+            .read()
+            .unwrap()
+            .highest_confirmed_root()
+    } else if commitment.is_confirmed() {
+        optimistically_confirmed_bank.read().unwrap().bank.slot()
+    } else {
+        block_commitment_cache.read().unwrap().slot()
+    }
+}
+
+/// Sends the state of the account at the subscription's commitment level to the
+/// connection that asked for it.
+fn send_initial_account_state(
+    params: &AccountSubscriptionParams,
+    subscription_id: SubscriptionId,
+    sender: &mpsc::Sender<DirectNotification>,
+    bank_forks: &RwLock<BankForks>,
+    block_commitment_cache: &RwLock<BlockCommitmentCache>,
+    optimistically_confirmed_bank: &RwLock<OptimisticallyConfirmedBank>,
+) {
+    let slot = commitment_bank_slot(
+        &params.commitment,
+        block_commitment_cache,
+        optimistically_confirmed_bank,
+    );
+    let bank = match bank_forks.read().unwrap().get(slot).cloned() {
+        Some(bank) => bank,
+        None => return,
+    };
+    let (account, _last_modified_slot) = bank
+        .get_account_modified_slot(&params.pubkey)
+        .unwrap_or_default();
+    let notification = Notification {
+        jsonrpc: Some(jsonrpc_core::Version::V2),
+        method: "accountNotification",
+        params: NotificationParams {
+            result: Response {
+                context: RpcResponseContext { slot },
+                value: encode_account(account, params, bank),
+            },
+            subscription: subscription_id,
+        },
+    };
+    let json = serde_json::to_string(&notification).expect("serialization never fails");
+    // The connection may already be gone
+    DirectNotification::Message {
+        subscription_id,
+        json: Arc::new(json),
+    }
+    .send(sender);
+}
+
+/// Scans the accounts owned by the program at the subscription's commitment level and
+/// sends the ones matching its filters to the connection that asked for them, in chunks
+/// of at most `INITIAL_PROGRAM_ACCOUNTS_CHUNK_SIZE` accounts. The last chunk is flagged so
+/// that the client knows that only incremental updates follow.
+fn send_initial_program_accounts(
+    params: &ProgramSubscriptionParams,
+    subscription_id: SubscriptionId,
+    sender: &mpsc::Sender<DirectNotification>,
+    bank_forks: &RwLock<BankForks>,
+    block_commitment_cache: &RwLock<BlockCommitmentCache>,
+    optimistically_confirmed_bank: &RwLock<OptimisticallyConfirmedBank>,
+) {
+    let slot = commitment_bank_slot(
+        &params.commitment,
+        block_commitment_cache,
+        optimistically_confirmed_bank,
+    );
+    let bank = match bank_forks.read().unwrap().get(slot).cloned() {
+        Some(bank) => bank,
+        None => return,
+    };
+    let accounts = match bank.get_program_accounts(&params.pubkey) {
+        Ok(accounts) => accounts,
+        Err(err) => {
+            warn!(
+                "initial scan of program {} failed: {:?}",
+                params.pubkey, err
+            );
+            return;
+        }
+    };
+    let (accounts, _) = filter_program_results(Arc::new(accounts), params, slot, bank);
+    let accounts: Vec<_> = accounts.collect();
+    let mut chunks: Vec<_> = accounts
+        .chunks(INITIAL_PROGRAM_ACCOUNTS_CHUNK_SIZE)
+        .collect();
+    if chunks.is_empty() {
+        chunks.push(&[]);
+    }
+    let last_index = chunks.len() - 1;
+    inc_new_counter_info!("rpc-subscription-initial-program-accounts", accounts.len());
+    for (index, chunk) in chunks.into_iter().enumerate() {
+        let notification = Notification {
+            jsonrpc: Some(jsonrpc_core::Version::V2),
+            method: "programInitialAccountsNotification",
+            params: NotificationParams {
+                result: Response {
+                    context: RpcResponseContext { slot },
+                    value: RpcProgramAccountsChunk {
+                        accounts: chunk.to_vec(),
+                        index,
+                        last: index == last_index,
+                    },
+                },
+                subscription: subscription_id,
+            },
+        };
+        let json = serde_json::to_string(&notification).expect("serialization never fails");
+        let notification = DirectNotification::Message {
+            subscription_id,
+            json: Arc::new(json),
+        };
+        if !notification.send(sender) {
+            // The connection is gone or fell behind
+            return;
+        }
+    }
+}
+
+/// Reads the transactions of the blocks on the fork of the bank at the subscription's
+/// commitment level, from `start_slot` on, out of the blockstore and sends the logs of the
+/// ones matching the subscription to the connection that asked for them. At most the last
+/// `MAX_LOGS_REPLAY_SLOTS` slots are replayed. Returns the last slot of the replay, unless
+/// the bank at the subscription's commitment level is gone.
+#[allow(clippy::too_many_arguments)]
+fn send_logs_replay(
+    params: &LogsSubscriptionParams,
+    start_slot: Slot,
+    subscription_id: SubscriptionId,
+    sender: &mpsc::Sender<DirectNotification>,
+    bank_forks: &RwLock<BankForks>,
+    blockstore: &Blockstore,
+    block_commitment_cache: &RwLock<BlockCommitmentCache>,
+    optimistically_confirmed_bank: &RwLock<OptimisticallyConfirmedBank>,
+) -> Option<Slot> {
+    let end_slot = commitment_bank_slot(
+        &params.commitment,
+        block_commitment_cache,
+        optimistically_confirmed_bank,
+    );
+    let bank = bank_forks.read().unwrap().get(end_slot).cloned()?;
+    let start_slot = start_slot.max(end_slot.saturating_sub(MAX_LOGS_REPLAY_SLOTS - 1));
+    let mut replayed = 0;
+    for block_slot in start_slot..=end_slot {
+        // Skipped slots and blocks on other forks are never replayed
+        if block_slot != end_slot
+            && !bank.ancestors.contains_key(&block_slot)
+            && !blockstore.is_root(block_slot)
+        {
+            continue;
+        }
+        let block = match blockstore.get_complete_block(block_slot, false) {
+            Ok(block) => block,
+            Err(err) => {
+                debug!(
+                    "failed to load block {} for logs replay: {}",
+                    block_slot, err
+                );
+                continue;
+            }
+        };
+        for transaction in block.transactions {
+            let logs = match filter_replayed_logs(transaction, params) {
+                Some(logs) => logs,
+                None => continue,
+            };
+            let notification = Notification {
+                jsonrpc: Some(jsonrpc_core::Version::V2),
+                method: "logsNotification",
+                params: NotificationParams {
+                    result: Response {
+                        context: RpcResponseContext { slot: block_slot },
+                        value: logs,
+                    },
+                    subscription: subscription_id,
+                },
+            };
+            let json = serde_json::to_string(&notification).expect("serialization never fails");
+            let notification = DirectNotification::Message {
+                subscription_id,
+                json: Arc::new(json),
+            };
+            if !notification.send(sender) {
+                // The connection is gone or fell behind
+                inc_new_counter_info!("rpc-subscription-logs-replayed", replayed);
+                return Some(end_slot);
+            }
+            replayed += 1;
+        }
+    }
+    inc_new_counter_info!("rpc-subscription-logs-replayed", replayed);
+    Some(end_slot)
+}
+
+/// The logs of a transaction read out of the blockstore, if it matches the subscription
+fn filter_replayed_logs(
+    transaction: TransactionWithStatusMeta,
+    params: &LogsSubscriptionParams,
+) -> Option<RpcLogsResponse> {
+    let TransactionWithStatusMeta { transaction, meta } = transaction;
+    let meta = meta?;
+    let is_selected = match &params.kind {
+        LogsSubscriptionKind::All => parse_vote_transaction(&transaction).is_none(),
+        LogsSubscriptionKind::AllWithVotes => true,
+        LogsSubscriptionKind::Single(pubkey) => transaction.message.account_keys.contains(pubkey),
+    };
+    let is_selected = is_selected
+        && match params.filter_status {
+            RpcTransactionLogsStatusFilter::All => true,
+            RpcTransactionLogsStatusFilter::Succeeded => meta.status.is_ok(),
+            RpcTransactionLogsStatusFilter::Failed => meta.status.is_err(),
+        };
+    if !is_selected {
+        return None;
+    }
+    Some(RpcLogsResponse {
+        signature: transaction.signatures.get(0)?.to_string(),
+        err: meta.status.err(),
+        logs: meta.log_messages.unwrap_or_default(),
+    })
+}
+
+/// Modified slots of every account in the set, as seen at the subscription's commitment
+/// level; accounts that do not exist yet are recorded at slot zero.
+fn initial_last_notified_account_slots(
+    params: &AccountsSubscriptionParams,
+    bank_forks: &RwLock<BankForks>,
+    block_commitment_cache: &RwLock<BlockCommitmentCache>,
+    optimistically_confirmed_bank: &RwLock<OptimisticallyConfirmedBank>,
+) -> HashMap<Pubkey, Slot> {
+    let slot = commitment_bank_slot(
+        &params.commitment,
+        block_commitment_cache,
+        optimistically_confirmed_bank,
+    );
+    let bank = bank_forks.read().unwrap().get(slot).cloned();
+    params
+        .pubkeys
+        .iter()
+        .map(|pubkey| {
+            let slot = bank
+                .as_ref()
+                .and_then(|bank| bank.get_account_modified_slot(pubkey))
+                .map(|(_account, slot)| slot)
+                .unwrap_or_default();
+            (*pubkey, slot)
+        })
+        .collect()
+}
+
+fn initial_last_notified_token_account_slots(
+    params: &TokenAccountsByOwnerSubscriptionParams,
+    bank_forks: &RwLock<BankForks>,
+    block_commitment_cache: &RwLock<BlockCommitmentCache>,
+    optimistically_confirmed_bank: &RwLock<OptimisticallyConfirmedBank>,
+) -> HashMap<Pubkey, Slot> {
+    let slot = commitment_bank_slot(
+        &params.commitment,
+        block_commitment_cache,
+        optimistically_confirmed_bank,
+    );
+    match bank_forks.read().unwrap().get(slot) {
+        Some(bank) => {
+            get_token_accounts_by_owner_modified_slots(bank, &params.owner, &HashMap::new())
+                .into_iter()
+                .filter_map(|(pubkey, result)| result.map(|(_account, slot)| (pubkey, slot)))
+                .collect()
+        }
+        None => HashMap::new(),
+    }
+}
+
 fn initial_last_notified_slot(
     params: &SubscriptionParams,
     bank_forks: &RwLock<BankForks>,
@@ -379,44 +2314,218 @@ fn initial_last_notified_slot(
     optimistically_confirmed_bank: &RwLock<OptimisticallyConfirmedBank>,
 ) -> Slot {
     match params {
-        SubscriptionParams::Account(params) => {
-            let slot = if params.commitment.is_finalized() {
-                block_commitment_cache
-                    .read()
-                    .unwrap()
-                    .highest_confirmed_root()
-            } else if params.commitment.is_confirmed() {
-                optimistically_confirmed_bank.read().unwrap().bank.slot()
-            } else {
-                block_commitment_cache.read().unwrap().slot()
-            };
+        SubscriptionParams::Account(AccountSubscriptionParams {
+            pubkey, commitment, ..
+        })
+        | SubscriptionParams::Stake(StakeSubscriptionParams { pubkey, commitment }) => {
+            let slot = commitment_bank_slot(
+                commitment,
+                block_commitment_cache,
+                optimistically_confirmed_bank,
+            );
+
+            bank_forks
+                .read()
+                .unwrap()
+                .get(slot)
+                .and_then(|bank| bank.get_account_modified_slot_including_zero_lamport(pubkey))
+                .map(|(_account, slot)| slot)
+                .unwrap_or_default()
+        }
+        SubscriptionParams::Mint(MintSubscriptionParams { pubkey, commitment }) => {
+            let slot = commitment_bank_slot(
+                commitment,
+                block_commitment_cache,
+                optimistically_confirmed_bank,
+            );
 
             if let Some((_account, slot)) = bank_forks
                 .read()
                 .unwrap()
                 .get(slot)
-                .and_then(|bank| bank.get_account_modified_slot(&params.pubkey))
+                .and_then(|bank| bank.get_account_modified_slot(pubkey))
             {
                 slot
             } else {
                 0
             }
         }
+        // Blocks are notified starting from the slot after the current commitment slot
+        SubscriptionParams::Block(BlockSubscriptionParams { commitment, .. })
+        | SubscriptionParams::Transaction(TransactionSubscriptionParams { commitment, .. }) => {
+            if commitment.is_finalized() {
+                block_commitment_cache
+                    .read()
+                    .unwrap()
+                    .highest_confirmed_root()
+            } else {
+                optimistically_confirmed_bank.read().unwrap().bank.slot()
+            }
+        }
+        // Blockhashes are notified whenever the commitment slot advances past the last one
+        // notified, starting from the next bank notification
+        SubscriptionParams::Blockhash(_) => 0,
         // last_notified_slot is not utilized for these subscriptions
-        SubscriptionParams::Logs(_)
+        SubscriptionParams::Accounts(_)
+        | SubscriptionParams::BlockhashValidity(_)
+        | SubscriptionParams::Epoch
+        | SubscriptionParams::LeaderSchedule(_)
+        | SubscriptionParams::Logs(_)
+        | SubscriptionParams::PerformanceSamples
         | SubscriptionParams::Program(_)
+        | SubscriptionParams::ProgramChanges(_)
+        | SubscriptionParams::Rewards(_)
         | SubscriptionParams::Signature(_)
         | SubscriptionParams::Slot
         | SubscriptionParams::SlotsUpdates
-        | SubscriptionParams::Root
-        | SubscriptionParams::Vote => 0,
+        | SubscriptionParams::Root(_)
+        | SubscriptionParams::TokenAccountsByOwner(_)
+        | SubscriptionParams::Vote(_) => 0,
+    }
+}
+
+/// Notifications that didn't fit in a notification queue. Slot and root notifications only
+/// describe the latest state of the node, so just the latest of each kind is kept, holding
+/// its `MemoryCategory::NotificationQueue` reservation. Every other kind is dropped and
+/// counted, so the subscriptions that would have received it can be told once the notifier
+/// catches up.
+#[derive(Debug, Default)]
+struct CoalescedNotifications {
+    slot: Option<SlotInfo>,
+    root: Option<RpcRootInfo>,
+    dropped: BTreeMap<&'static str, RpcNotificationsDropped>,
+}
+
+impl CoalescedNotifications {
+    fn take(&mut self) -> Option<NotificationEntry> {
+        self.slot
+            .take()
+            .map(NotificationEntry::Slot)
+            .or_else(|| self.root.take().map(NotificationEntry::Root))
+            .or_else(|| {
+                (!self.dropped.is_empty())
+                    .then(|| NotificationEntry::Dropped(mem::take(&mut self.dropped)))
+            })
+    }
+
+    fn record_dropped(&mut self, notification_entry: &NotificationEntry) {
+        let slot = notification_entry.slot();
+        self.dropped
+            .entry(notification_entry.kind())
+            .or_default()
+            .merge(&RpcNotificationsDropped {
+                count: 1,
+                first_slot: slot,
+                last_slot: slot,
+            });
+    }
+}
+
+fn enqueue_notification(
+    notification_sender: &Sender<NotificationEntry>,
+    coalesced: &Mutex<CoalescedNotifications>,
+    memory_budget: &MemoryBudget,
+    notification_entry: NotificationEntry,
+) {
+    // Once slot or root notifications overflowed the queue, later ones are merged too until
+    // the notifier catches up, so that they are never processed out of order. The merged
+    // entry keeps the reservation of the one it replaces.
+    let notification_entry = {
+        let mut coalesced = coalesced.lock().unwrap();
+        match notification_entry {
+            NotificationEntry::Slot(slot_info) if coalesced.slot.is_some() => {
+                coalesced.slot = Some(slot_info);
+                return;
+            }
+            NotificationEntry::Root(root_info) if coalesced.root.is_some() => {
+                coalesced.root = Some(root_info);
+                return;
+            }
+            notification_entry => notification_entry,
+        }
+    };
+    let size = notification_entry.estimated_size();
+    if !memory_budget.try_reserve(MemoryCategory::NotificationQueue, size) {
+        inc_new_counter_info!("rpc-subscription-notification-shed", 1);
+        warn!(
+            "Dropped RPC Notification - memory budget exhausted : {:?}",
+            notification_entry
+        );
+        coalesced
+            .lock()
+            .unwrap()
+            .record_dropped(&notification_entry);
+        return;
+    }
+    match notification_sender.try_send(notification_entry) {
+        Ok(()) => (),
+        Err(TrySendError::Full(notification)) => {
+            inc_new_counter_info!("rpc-subscription-notification-queue-full", 1);
+            let mut coalesced = coalesced.lock().unwrap();
+            let replaced = match notification {
+                NotificationEntry::Slot(slot_info) => coalesced.slot.replace(slot_info).is_some(),
+                NotificationEntry::Root(root_info) => coalesced.root.replace(root_info).is_some(),
+                notification => {
+                    warn!("Dropped RPC Notification - queue full : {:?}", notification);
+                    coalesced.record_dropped(&notification);
+                    true
+                }
+            };
+            if replaced {
+                memory_budget.release(MemoryCategory::NotificationQueue, size);
+            }
+        }
+        Err(TrySendError::Disconnected(notification)) => {
+            memory_budget.release(MemoryCategory::NotificationQueue, size);
+            warn!(
+                "Dropped RPC Notification - receiver disconnected : {:?}",
+                notification
+            );
+        }
+    }
+}
+
+/// Receives the next entry for the notification thread. Subscription changes come first, so
+/// that a subscription is in place before the notifications queued after it. Coalesced
+/// notifications come once the queue is drained, as they are newer than anything in it.
+fn receive_notification(
+    control_receiver: &Receiver<NotificationEntry>,
+    notification_receiver: &Receiver<NotificationEntry>,
+    coalesced: &Mutex<CoalescedNotifications>,
+    timeout: Duration,
+) -> Result<NotificationEntry, RecvTimeoutError> {
+    let mut select = Select::new();
+    select.recv(control_receiver);
+    select.recv(notification_receiver);
+    loop {
+        match control_receiver.try_recv() {
+            Ok(notification_entry) => return Ok(notification_entry),
+            Err(TryRecvError::Empty) => (),
+            Err(TryRecvError::Disconnected) => return Err(RecvTimeoutError::Disconnected),
+        }
+        match notification_receiver.try_recv() {
+            Ok(notification_entry) => return Ok(notification_entry),
+            Err(TryRecvError::Empty) => (),
+            Err(TryRecvError::Disconnected) => return Err(RecvTimeoutError::Disconnected),
+        }
+        if let Some(notification_entry) = coalesced.lock().unwrap().take() {
+            return Ok(notification_entry);
+        }
+        select
+            .ready_timeout(timeout)
+            .map_err(|_| RecvTimeoutError::Timeout)?;
     }
 }
 
 pub struct RpcSubscriptions {
     notification_sender: Sender<NotificationEntry>,
+    node_progress_sender: Sender<NotificationEntry>,
+    coalesced_notifications: Arc<Mutex<CoalescedNotifications>>,
+    coalesced_node_progress: Arc<Mutex<CoalescedNotifications>>,
+    memory_budget: MemoryBudget,
 
     t_cleanup: Option<JoinHandle<()>>,
+    t_node_progress: Option<JoinHandle<()>>,
 
     exit: Arc<AtomicBool>,
     control: SubscriptionControl,
@@ -439,6 +2548,7 @@ impl RpcSubscriptions {
     ) -> Self {
         Self::new_with_config(
             exit,
+            None,
             bank_forks,
             block_commitment_cache,
             optimistically_confirmed_bank,
@@ -454,6 +2564,7 @@ impl RpcSubscriptions {
     ) -> Self {
         Self::new_with_config(
             exit,
+            None,
             bank_forks,
             block_commitment_cache,
             optimistically_confirmed_bank,
@@ -461,52 +2572,118 @@ impl RpcSubscriptions {
         )
     }
 
+    /// `blockstore` is required to serve block subscriptions; without it they are accepted
+    /// but never notified.
     pub fn new_with_config(
         exit: &Arc<AtomicBool>,
+        blockstore: Option<Arc<Blockstore>>,
         bank_forks: Arc<RwLock<BankForks>>,
         block_commitment_cache: Arc<RwLock<BlockCommitmentCache>>,
         optimistically_confirmed_bank: Arc<RwLock<OptimisticallyConfirmedBank>>,
         config: &PubSubConfig,
     ) -> Self {
-        let (notification_sender, notification_receiver) = crossbeam_channel::unbounded();
+        let (notification_sender, notification_receiver) =
+            crossbeam_channel::bounded(config.notification_queue_capacity);
+        // Subscription changes are never dropped, their number is bounded by the
+        // subscription limits instead
+        let (control_sender, control_receiver) = crossbeam_channel::unbounded();
+        let (node_progress_sender, node_progress_receiver) =
+            crossbeam_channel::bounded(config.notification_queue_capacity);
+        let (node_progress_control_sender, node_progress_control_receiver) =
+            crossbeam_channel::unbounded();
+        let coalesced_notifications = Arc::new(Mutex::new(CoalescedNotifications::default()));
+        let coalesced_node_progress = Arc::new(Mutex::new(CoalescedNotifications::default()));
 
-        let exit_clone = exit.clone();
         let subscriptions = SubscriptionsTracker::new(bank_forks.clone());
+        let finalized_only = config.finalized_only;
+        let notification_threads = config.notification_threads;
 
         let (broadcast_sender, _) = broadcast::channel(config.queue_capacity_items);
+        let (firehose_broadcast_sender, _) =
+            broadcast::channel(config.firehose_queue_capacity_items);
+        let memory_budget = MemoryBudget::new(config.max_memory_bytes);
+        let recent_signatures = RecentSignatureNotifications::new(
+            config.signature_notification_retention,
+            memory_budget.clone(),
+        );
 
-        let notifier = RpcNotifier {
-            sender: broadcast_sender.clone(),
-            buf: Vec::new(),
-            recent_items: RecentItems::new(
+        let control = SubscriptionControl::new(
+            config.max_active_subscriptions,
+            config.max_subscriptions_per_ip,
+            control_sender,
+            node_progress_control_sender,
+            broadcast_sender.clone(),
+            firehose_broadcast_sender.clone(),
+            memory_budget.clone(),
+            recent_signatures.clone(),
+        );
+        let notifier = Arc::new(RpcNotifier {
+            sender: broadcast_sender,
+            firehose_sender: firehose_broadcast_sender,
+            scratch: Mutex::default(),
+            serialized_results: Mutex::new(SerializedResults::new(memory_budget.clone())),
+            recent_items: Mutex::new(RecentItems::new(
                 config.queue_capacity_items,
                 config.queue_capacity_bytes,
-            ),
-        };
+                memory_budget.clone(),
+            )),
+            recent_signatures,
+            memory_budget: memory_budget.clone(),
+            account_proofs: AccountProofsCache::default(),
+            cbor_clients: control.cbor_clients().clone(),
+        });
+
+        let exit_clone = exit.clone();
+        let notifier_clone = notifier.clone();
+        let bank_forks_clone = bank_forks.clone();
+        let infos = control.infos().clone();
+        let coalesced_node_progress_clone = coalesced_node_progress.clone();
+        let t_node_progress = Builder::new()
+            .name("solana-rpc-node-progress".to_string())
+            .spawn(move || {
+                Self::process_node_progress_notifications(
+                    exit_clone,
+                    notifier_clone,
+                    node_progress_control_receiver,
+                    node_progress_receiver,
+                    coalesced_node_progress_clone,
+                    bank_forks_clone,
+                    infos,
+                );
+            })
+            .unwrap();
+        let exit_clone = exit.clone();
+        let coalesced_notifications_clone = coalesced_notifications.clone();
+        let infos = control.infos().clone();
         let t_cleanup = Builder::new()
             .name("solana-rpc-notifications".to_string())
             .spawn(move || {
                 Self::process_notifications(
                     exit_clone,
                     notifier,
+                    control_receiver,
                     notification_receiver,
+                    coalesced_notifications_clone,
                     subscriptions,
+                    infos,
+                    blockstore,
                     bank_forks,
                     block_commitment_cache,
                     optimistically_confirmed_bank,
+                    finalized_only,
+                    notification_threads,
                 );
             })
             .unwrap();
 
-        let control = SubscriptionControl::new(
-            config.max_active_subscriptions,
-            notification_sender.clone(),
-            broadcast_sender,
-        );
-
         Self {
             notification_sender,
+            node_progress_sender,
+            coalesced_notifications,
+            coalesced_node_progress,
+            memory_budget,
             t_cleanup: Some(t_cleanup),
+            t_node_progress: Some(t_node_progress),
 
             exit: exit.clone(),
             control,
@@ -529,6 +2706,14 @@ impl RpcSubscriptions {
         &self.control
     }
 
+    /// Returns the number of notifications waiting to be processed, and the queue's capacity
+    pub fn notification_queue_depth(&self) -> (usize, Option<usize>) {
+        (
+            self.notification_sender.len(),
+            self.notification_sender.capacity(),
+        )
+    }
+
     /// Notify subscribers of changes to any accounts or new signatures since
     /// the bank's last checkpoint.
     pub fn notify_subscribers(&self, commitment_slots: CommitmentSlots) {
@@ -554,55 +2739,160 @@ impl RpcSubscriptions {
         }));
     }
 
+    pub fn notify_performance_sample(&self, slot: Slot, perf_sample: &PerfSample) {
+        self.enqueue_notification(NotificationEntry::PerformanceSample(RpcPerfSample {
+            slot,
+            num_transactions: perf_sample.num_transactions,
+            num_slots: perf_sample.num_slots,
+            sample_period_secs: perf_sample.sample_period_secs,
+        }));
+    }
+
     pub fn notify_signatures_received(&self, slot_signatures: (Slot, Vec<Signature>)) {
         self.enqueue_notification(NotificationEntry::SignaturesReceived(slot_signatures));
     }
 
-    pub fn notify_vote(&self, vote: &Vote) {
-        self.enqueue_notification(NotificationEntry::Vote(vote.clone()));
+    pub fn notify_vote(&self, vote_pubkey: &Pubkey, vote: &Vote, signature: &Signature) {
+        self.enqueue_notification(NotificationEntry::Vote(
+            *vote_pubkey,
+            vote.clone(),
+            *signature,
+        ));
     }
 
-    pub fn notify_roots(&self, mut rooted_slots: Vec<Slot>) {
-        rooted_slots.sort_unstable();
-        rooted_slots.into_iter().for_each(|root| {
+    pub fn notify_roots(&self, mut rooted_banks: Vec<Arc<Bank>>) {
+        rooted_banks.sort_unstable_by_key(|bank| bank.slot());
+        rooted_banks.into_iter().for_each(|bank| {
             self.enqueue_notification(NotificationEntry::SlotUpdate(SlotUpdate::Root {
-                slot: root,
+                slot: bank.slot(),
                 timestamp: timestamp(),
             }));
-            self.enqueue_notification(NotificationEntry::Root(root));
+            self.enqueue_notification(NotificationEntry::Root(RpcRootInfo {
+                slot: bank.slot(),
+                parent: bank.parent_slot(),
+                blockhash: bank.last_blockhash().to_string(),
+                block_height: bank.block_height(),
+            }));
+            if let Some(epoch_start) = epoch_start(&bank) {
+                self.enqueue_notification(NotificationEntry::Epoch(epoch_start));
+                self.enqueue_notification(NotificationEntry::EpochRewards(bank));
+            }
         });
     }
 
-    fn enqueue_notification(&self, notification_entry: NotificationEntry) {
-        match self.notification_sender.send(notification_entry) {
-            Ok(()) => (),
-            Err(SendError(notification)) => {
-                warn!(
-                    "Dropped RPC Notification - receiver disconnected : {:?}",
-                    notification
-                );
-            }
+    /// Notify slot update subscribers of banks that were dropped from the fork graph when
+    /// `root` was set, so that they learn about skipped slots without tracking roots.
+    pub fn notify_pruned_slots(
+        &self,
+        root: Slot,
+        mut pruned_banks: Vec<(Arc<Bank>, SlotPruneReason)>,
+    ) {
+        pruned_banks.sort_unstable_by_key(|(bank, _)| bank.slot());
+        for (bank, reason) in pruned_banks {
+            self.enqueue_notification(NotificationEntry::SlotUpdate(SlotUpdate::Pruned {
+                slot: bank.slot(),
+                parent: bank.parent_slot(),
+                root,
+                timestamp: timestamp(),
+                reason,
+            }));
         }
     }
 
+    fn enqueue_notification(&self, notification_entry: NotificationEntry) {
+        let (notification_sender, coalesced) = if notification_entry.is_node_progress() {
+            (&self.node_progress_sender, &self.coalesced_node_progress)
+        } else {
+            (&self.notification_sender, &self.coalesced_notifications)
+        };
+        enqueue_notification(
+            notification_sender,
+            coalesced,
+            &self.memory_budget,
+            notification_entry,
+        );
+    }
+
+    #[allow(clippy::too_many_arguments)]
     fn process_notifications(
         exit: Arc<AtomicBool>,
-        mut notifier: RpcNotifier,
+        notifier: Arc<RpcNotifier>,
+        control_receiver: Receiver<NotificationEntry>,
         notification_receiver: Receiver<NotificationEntry>,
+        coalesced_notifications: Arc<Mutex<CoalescedNotifications>>,
         mut subscriptions: SubscriptionsTracker,
+        infos: SubscriptionInfos,
+        blockstore: Option<Arc<Blockstore>>,
         bank_forks: Arc<RwLock<BankForks>>,
         block_commitment_cache: Arc<RwLock<BlockCommitmentCache>>,
         optimistically_confirmed_bank: Arc<RwLock<OptimisticallyConfirmedBank>>,
+        finalized_only: bool,
+        notification_threads: usize,
     ) {
+        // Bank and gossip notifications scan every commitment watcher, so they are sharded
+        // across this pool; without it the scan runs on the notification thread itself.
+        let notification_pool = (notification_threads > 0).then(|| {
+            ThreadPoolBuilder::new()
+                .num_threads(notification_threads)
+                .thread_name(|i| format!("solana-rpc-notify-{}", i))
+                .build()
+                .unwrap()
+        });
+        // Initial states and logs replays read whole program scans or up to
+        // `MAX_LOGS_REPLAY_SLOTS` blocks, so they run on their own pool rather than holding
+        // up the notifications
+        let direct_pool = ThreadPoolBuilder::new()
+            .num_threads(DIRECT_NOTIFICATION_THREADS)
+            .thread_name(|i| format!("solana-rpc-direct-{}", i))
+            .build()
+            .unwrap();
+        let mut last_memory_report = Instant::now();
+        let mut last_stats_report = Instant::now();
+        let mut last_finalized_root = None;
         loop {
             if exit.load(Ordering::Relaxed) {
                 break;
             }
-            match notification_receiver.recv_timeout(Duration::from_millis(RECEIVE_DELAY_MILLIS)) {
+            if last_memory_report.elapsed() > MEMORY_METRICS_INTERVAL {
+                notifier.memory_budget.report_metrics();
+                notifier.recent_items.lock().unwrap().report_metrics();
+                last_memory_report = Instant::now();
+            }
+            if last_stats_report.elapsed() > SUBSCRIPTION_STATS_INTERVAL {
+                report_subscription_stats(&infos);
+                last_stats_report = Instant::now();
+            }
+            match receive_notification(
+                &control_receiver,
+                &notification_receiver,
+                &coalesced_notifications,
+                Duration::from_millis(RECEIVE_DELAY_MILLIS),
+            ) {
                 Ok(notification_entry) => {
+                    let span = info_span!(
+                        "rpc_notification",
+                        kind = notification_entry.kind(),
+                        slot = ?notification_entry.slot(),
+                    );
+                    let _enter = span.enter();
+                    // Entries sent by `SubscriptionControl` bypass the memory budget.
+                    if !matches!(
+                        notification_entry,
+                        NotificationEntry::Subscribed(..)
+                            | NotificationEntry::Unsubscribed(..)
+                            | NotificationEntry::InitialAccountState(..)
+                            | NotificationEntry::InitialProgramAccounts(..)
+                            | NotificationEntry::LogsReplay(..)
+                            | NotificationEntry::Dropped(_)
+                    ) {
+                        notifier.memory_budget.release(
+                            MemoryCategory::NotificationQueue,
+                            notification_entry.estimated_size(),
+                        );
+                    }
                     match notification_entry {
                         NotificationEntry::Subscribed(params, id) => {
-                            subscriptions.subscribe(params.clone(), id, || {
+                            let info = subscriptions.subscribe(params.clone(), id, || {
                                 initial_last_notified_slot(
                                     &params,
                                     &bank_forks,
@@ -610,67 +2900,158 @@ impl RpcSubscriptions {
                                     &optimistically_confirmed_bank,
                                 )
                             });
+                            infos.insert(id, Arc::clone(&info));
+                            match &params {
+                                SubscriptionParams::Accounts(params) => {
+                                    *info
+                                        .last_notified_account_slots()
+                                        .expect("account set subscription")
+                                        .write()
+                                        .unwrap() = initial_last_notified_account_slots(
+                                        params,
+                                        &bank_forks,
+                                        &block_commitment_cache,
+                                        &optimistically_confirmed_bank,
+                                    );
+                                }
+                                SubscriptionParams::TokenAccountsByOwner(params) => {
+                                    *info
+                                        .last_notified_account_slots()
+                                        .expect("token owner subscription")
+                                        .write()
+                                        .unwrap() = initial_last_notified_token_account_slots(
+                                        params,
+                                        &bank_forks,
+                                        &block_commitment_cache,
+                                        &optimistically_confirmed_bank,
+                                    );
+                                }
+                                _ => {}
+                            }
                         }
-                        NotificationEntry::Unsubscribed(params, id) => {
-                            subscriptions.unsubscribe(params, id);
+                        NotificationEntry::InitialAccountState(params, id, sender) => {
+                            let bank_forks = bank_forks.clone();
+                            let block_commitment_cache = block_commitment_cache.clone();
+                            let optimistically_confirmed_bank =
+                                optimistically_confirmed_bank.clone();
+                            direct_pool.spawn(move || {
+                                send_initial_account_state(
+                                    &params,
+                                    id,
+                                    &sender,
+                                    &bank_forks,
+                                    &block_commitment_cache,
+                                    &optimistically_confirmed_bank,
+                                );
+                            });
                         }
-                        NotificationEntry::Slot(slot_info) => {
-                            if let Some(sub) = subscriptions
-                                .node_progress_watchers()
-                                .get(&SubscriptionParams::Slot)
-                            {
-                                debug!("slot notify: {:?}", slot_info);
-                                inc_new_counter_info!("rpc-subscription-notify-slot", 1);
-                                notifier.notify(&slot_info, sub, false);
-                            }
+                        NotificationEntry::InitialProgramAccounts(params, id, sender) => {
+                            let bank_forks = bank_forks.clone();
+                            let block_commitment_cache = block_commitment_cache.clone();
+                            let optimistically_confirmed_bank =
+                                optimistically_confirmed_bank.clone();
+                            direct_pool.spawn(move || {
+                                send_initial_program_accounts(
+                                    &params,
+                                    id,
+                                    &sender,
+                                    &bank_forks,
+                                    &block_commitment_cache,
+                                    &optimistically_confirmed_bank,
+                                );
+                            });
                         }
-                        NotificationEntry::SlotUpdate(slot_update) => {
-                            if let Some(sub) = subscriptions
-                                .node_progress_watchers()
-                                .get(&SubscriptionParams::SlotsUpdates)
-                            {
-                                inc_new_counter_info!("rpc-subscription-notify-slots-updates", 1);
-                                notifier.notify(&slot_update, sub, false);
-                            }
+                        NotificationEntry::LogsReplay(params, start_slot, id, sender, permit) => {
+                            let blockstore = blockstore.clone();
+                            let bank_forks = bank_forks.clone();
+                            let block_commitment_cache = block_commitment_cache.clone();
+                            let optimistically_confirmed_bank =
+                                optimistically_confirmed_bank.clone();
+                            direct_pool.spawn(move || {
+                                let end_slot = blockstore.as_deref().and_then(|blockstore| {
+                                    send_logs_replay(
+                                        &params,
+                                        start_slot,
+                                        id,
+                                        &sender,
+                                        &bank_forks,
+                                        blockstore,
+                                        &block_commitment_cache,
+                                        &optimistically_confirmed_bank,
+                                    )
+                                });
+                                DirectNotification::ReplayFinished {
+                                    subscription_id: id,
+                                    end_slot,
+                                }
+                                .send(&sender);
+                                drop(permit);
+                            });
                         }
-                        // These notifications are only triggered by votes observed on gossip,
-                        // unlike `NotificationEntry::Gossip`, which also accounts for slots seen
-                        // in VoteState's from bank states built in ReplayStage.
-                        NotificationEntry::Vote(ref vote_info) => {
-                            let rpc_vote = RpcVote {
-                                // TODO: Remove clones
-                                slots: vote_info.slots.clone(),
-                                hash: bs58::encode(vote_info.hash).into_string(),
-                                timestamp: vote_info.timestamp,
-                            };
-                            if let Some(sub) = subscriptions
-                                .node_progress_watchers()
-                                .get(&SubscriptionParams::Vote)
-                            {
-                                debug!("vote notify: {:?}", vote_info);
-                                inc_new_counter_info!("rpc-subscription-notify-vote", 1);
-                                notifier.notify(&rpc_vote, sub, false);
-                            }
+                        NotificationEntry::Unsubscribed(params, id) => {
+                            infos.remove(&id);
+                            subscriptions.unsubscribe(params, id);
                         }
-                        NotificationEntry::Root(root) => {
-                            if let Some(sub) = subscriptions
-                                .node_progress_watchers()
-                                .get(&SubscriptionParams::Root)
-                            {
-                                debug!("root notify: {:?}", root);
-                                inc_new_counter_info!("rpc-subscription-notify-root", 1);
-                                notifier.notify(&root, sub, false);
+                        NotificationEntry::Slot(_)
+                        | NotificationEntry::SlotUpdate(_)
+                        | NotificationEntry::Vote(..)
+                        | NotificationEntry::Root(_)
+                        | NotificationEntry::Epoch(_)
+                        | NotificationEntry::PerformanceSample(_)
+                        | NotificationEntry::EpochRewards(_) => {
+                            unreachable!("node progress notifications have a thread of their own")
+                        }
+                        NotificationEntry::Dropped(dropped) => {
+                            for (kind, dropped) in &dropped {
+                                let watchers: Box<dyn Iterator<Item = &Arc<SubscriptionInfo>>> =
+                                    match *kind {
+                                        "bank" => {
+                                            Box::new(subscriptions.commitment_watchers().values())
+                                        }
+                                        "gossip" => {
+                                            Box::new(subscriptions.gossip_watchers().values())
+                                        }
+                                        "signatures_received" => Box::new(
+                                            subscriptions
+                                                .by_signature()
+                                                .values()
+                                                .flat_map(|subs| subs.values())
+                                                .filter(|subscription| {
+                                                    matches!(
+                                                        subscription.params(),
+                                                        SubscriptionParams::Signature(params)
+                                                            if params.enable_received_notification
+                                                    )
+                                                }),
+                                        ),
+                                        _ => Box::new(iter::empty()),
+                                    };
+                                for subscription in watchers {
+                                    notifier.notify_dropped(dropped, subscription);
+                                }
                             }
                         }
                         NotificationEntry::Bank(commitment_slots) => {
+                            // Only finalized subscriptions exist, so there is nothing to do
+                            // until the root advances.
+                            if finalized_only {
+                                let root = commitment_slots.highest_confirmed_root;
+                                if last_finalized_root >= Some(root) {
+                                    continue;
+                                }
+                                last_finalized_root = Some(root);
+                            }
                             RpcSubscriptions::notify_accounts_logs_programs_signatures(
                                 subscriptions.commitment_watchers(),
                                 &bank_forks,
+                                blockstore.as_deref(),
                                 &commitment_slots,
-                                &mut notifier,
+                                &notifier,
+                                notification_pool.as_ref(),
                                 "bank",
                             )
                         }
+                        NotificationEntry::Gossip(_) if finalized_only => {}
                         NotificationEntry::Gossip(slot) => {
                             let commitment_slots = CommitmentSlots {
                                 highest_confirmed_slot: slot,
@@ -680,11 +3061,14 @@ impl RpcSubscriptions {
                             RpcSubscriptions::notify_accounts_logs_programs_signatures(
                                 subscriptions.gossip_watchers(),
                                 &bank_forks,
+                                blockstore.as_deref(),
                                 &commitment_slots,
-                                &mut notifier,
+                                &notifier,
+                                notification_pool.as_ref(),
                                 "gossip",
                             )
                         }
+                        NotificationEntry::SignaturesReceived(_) if finalized_only => {}
                         NotificationEntry::SignaturesReceived((slot, slot_signatures)) => {
                             for slot_signature in &slot_signatures {
                                 if let Some(subs) = subscriptions.by_signature().get(slot_signature)
@@ -725,45 +3109,376 @@ impl RpcSubscriptions {
         }
     }
 
+    /// Notifies the node progress subscriptions: slots, slot updates, votes, roots, epochs,
+    /// performance samples, rewards and leader schedules. These are cheap to produce and
+    /// latency sensitive, so they have a thread and queue of their own rather than waiting
+    /// behind the subscription scans of bank and gossip notifications.
+    fn process_node_progress_notifications(
+        exit: Arc<AtomicBool>,
+        notifier: Arc<RpcNotifier>,
+        control_receiver: Receiver<NotificationEntry>,
+        notification_receiver: Receiver<NotificationEntry>,
+        coalesced_notifications: Arc<Mutex<CoalescedNotifications>>,
+        bank_forks: Arc<RwLock<BankForks>>,
+        infos: SubscriptionInfos,
+    ) {
+        let mut watchers: HashMap<SubscriptionParams, Arc<SubscriptionInfo>> = HashMap::new();
+        let mut vote_watchers = VoteWatchers::default();
+        let mut upcoming_leaders = UpcomingLeaders::default();
+        loop {
+            if exit.load(Ordering::Relaxed) {
+                break;
+            }
+            match receive_notification(
+                &control_receiver,
+                &notification_receiver,
+                &coalesced_notifications,
+                Duration::from_millis(RECEIVE_DELAY_MILLIS),
+            ) {
+                Ok(notification_entry) => {
+                    let span = info_span!(
+                        "rpc_notification",
+                        kind = notification_entry.kind(),
+                        slot = ?notification_entry.slot(),
+                    );
+                    let _enter = span.enter();
+                    match notification_entry {
+                        // Node progress subscriptions don't depend on any slot, so there
+                        // is nothing to initialize
+                        NotificationEntry::Subscribed(params, id) => {
+                            let info = Arc::new(SubscriptionInfo::new(params.clone(), id, 0));
+                            infos.insert(id, Arc::clone(&info));
+                            if let SubscriptionParams::Vote(params) = &params {
+                                vote_watchers.insert(params, &info);
+                            }
+                            watchers.insert(params, info);
+                        }
+                        NotificationEntry::Unsubscribed(params, id) => {
+                            infos.remove(&id);
+                            if let SubscriptionParams::Vote(params) = &params {
+                                vote_watchers.remove(params, id);
+                            }
+                            if watchers.remove(&params).is_none() {
+                                warn!(
+                                    "Subscriptions inconsistency (missing node progress watcher)"
+                                );
+                            }
+                        }
+                        NotificationEntry::Dropped(dropped) => {
+                            for (params, subscription) in &watchers {
+                                let kind = match params {
+                                    SubscriptionParams::Slot => "slot",
+                                    SubscriptionParams::Root(_) => "root",
+                                    SubscriptionParams::SlotsUpdates => "slot_update",
+                                    SubscriptionParams::Vote(_) => "vote",
+                                    SubscriptionParams::Epoch => "epoch",
+                                    SubscriptionParams::Rewards(_) => "epoch_rewards",
+                                    SubscriptionParams::PerformanceSamples => "performance_sample",
+                                    _ => continue,
+                                };
+                                if let Some(dropped) = dropped.get(kind) {
+                                    notifier.notify_dropped(dropped, subscription);
+                                }
+                            }
+                        }
+                        notification_entry => {
+                            notifier.memory_budget.release(
+                                MemoryCategory::NotificationQueue,
+                                notification_entry.estimated_size(),
+                            );
+                            Self::notify_node_progress(
+                                notification_entry,
+                                &watchers,
+                                &vote_watchers,
+                                &notifier,
+                                &bank_forks,
+                                &mut upcoming_leaders,
+                            );
+                        }
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    // not a problem - try reading again
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    warn!("RPC node progress notification thread - sender disconnected");
+                    break;
+                }
+            }
+        }
+    }
+
+    fn notify_node_progress(
+        notification_entry: NotificationEntry,
+        watchers: &HashMap<SubscriptionParams, Arc<SubscriptionInfo>>,
+        vote_watchers: &VoteWatchers,
+        notifier: &RpcNotifier,
+        bank_forks: &RwLock<BankForks>,
+        upcoming_leaders: &mut UpcomingLeaders,
+    ) {
+        match notification_entry {
+            NotificationEntry::Slot(slot_info) => {
+                if let Some(sub) = watchers.get(&SubscriptionParams::Slot) {
+                    tracing::debug!("slot notify: {:?}", slot_info);
+                    inc_new_counter_info!("rpc-subscription-notify-slot", 1);
+                    notifier.notify(&slot_info, sub, false);
+                }
+                let mut leader_schedule_watchers = watchers
+                    .iter()
+                    .filter_map(|(params, sub)| match params {
+                        SubscriptionParams::LeaderSchedule(params) => Some((params, sub)),
+                        _ => None,
+                    })
+                    .peekable();
+                if leader_schedule_watchers.peek().is_none() {
+                    return;
+                }
+                let root_bank = bank_forks.read().unwrap().root_bank();
+                for (params, sub) in leader_schedule_watchers {
+                    let rpc_upcoming_leaders = RpcUpcomingLeaders {
+                        slot: slot_info.slot,
+                        leader_slots: upcoming_leaders.get(params, slot_info.slot, &root_bank),
+                    };
+                    inc_new_counter_info!("rpc-subscription-notify-leader-schedule", 1);
+                    notifier.notify(&rpc_upcoming_leaders, sub, false);
+                }
+            }
+            NotificationEntry::SlotUpdate(slot_update) => {
+                if let Some(sub) = watchers.get(&SubscriptionParams::SlotsUpdates) {
+                    inc_new_counter_info!("rpc-subscription-notify-slots-updates", 1);
+                    notifier.notify(&slot_update, sub, false);
+                }
+            }
+            // These notifications are only triggered by votes observed on gossip,
+            // unlike `NotificationEntry::Gossip`, which also accounts for slots seen
+            // in VoteState's from bank states built in ReplayStage.
+            NotificationEntry::Vote(ref vote_pubkey, ref vote_info, ref signature) => {
+                let mut watchers = vote_watchers.get(vote_pubkey).peekable();
+                if watchers.peek().is_none() {
+                    return;
+                }
+                let identity = bank_forks
+                    .read()
+                    .unwrap()
+                    .root_bank()
+                    .get_vote_account(vote_pubkey)
+                    .and_then(|(_stake, vote_account)| vote_account.node_pubkey())
+                    .map(|node_pubkey| node_pubkey.to_string());
+                let rpc_vote = RpcVote {
+                    // TODO: Remove clones
+                    slots: vote_info.slots.clone(),
+                    hash: bs58::encode(vote_info.hash).into_string(),
+                    timestamp: vote_info.timestamp,
+                    vote_pubkey: vote_pubkey.to_string(),
+                    identity,
+                    signature: signature.to_string(),
+                };
+                for sub in watchers {
+                    tracing::debug!("vote notify: {:?}", vote_info);
+                    inc_new_counter_info!("rpc-subscription-notify-vote", 1);
+                    notifier.notify(&rpc_vote, sub, false);
+                }
+            }
+            NotificationEntry::Root(root_info) => {
+                if let Some(sub) = watchers.get(&SubscriptionParams::Root(RootSubscriptionParams {
+                    enable_block_info: false,
+                })) {
+                    tracing::debug!("root notify: {:?}", root_info.slot);
+                    inc_new_counter_info!("rpc-subscription-notify-root", 1);
+                    notifier.notify(RpcRootNotification::Slot(root_info.slot), sub, false);
+                }
+                if let Some(sub) = watchers.get(&SubscriptionParams::Root(RootSubscriptionParams {
+                    enable_block_info: true,
+                })) {
+                    tracing::debug!("root notify: {:?}", root_info);
+                    inc_new_counter_info!("rpc-subscription-notify-root", 1);
+                    notifier.notify(RpcRootNotification::Info(root_info), sub, false);
+                }
+            }
+            NotificationEntry::Epoch(epoch_start) => {
+                if let Some(sub) = watchers.get(&SubscriptionParams::Epoch) {
+                    tracing::debug!("epoch notify: {:?}", epoch_start.epoch);
+                    inc_new_counter_info!("rpc-subscription-notify-epoch", 1);
+                    notifier.notify(&epoch_start, sub, false);
+                }
+            }
+            NotificationEntry::PerformanceSample(perf_sample) => {
+                if let Some(sub) = watchers.get(&SubscriptionParams::PerformanceSamples) {
+                    tracing::debug!("performance sample notify: {:?}", perf_sample);
+                    inc_new_counter_info!("rpc-subscription-notify-performance-sample", 1);
+                    notifier.notify(&perf_sample, sub, false);
+                }
+            }
+            NotificationEntry::EpochRewards(bank) => {
+                let mut watchers = watchers
+                    .iter()
+                    .filter_map(|(params, sub)| match params {
+                        SubscriptionParams::Rewards(params) => Some((params, sub)),
+                        _ => None,
+                    })
+                    .peekable();
+                if watchers.peek().is_none() {
+                    return;
+                }
+                let rewards = bank.rewards.read().unwrap();
+                let rewards_by_address: HashMap<&Pubkey, &RewardInfo> = rewards
+                    .iter()
+                    .filter(|(_address, reward_info)| {
+                        matches!(
+                            reward_info.reward_type,
+                            RewardType::Staking | RewardType::Voting
+                        )
+                    })
+                    .map(|(address, reward_info)| (address, reward_info))
+                    .collect();
+                for (params, sub) in watchers {
+                    let rewards: Vec<_> = params
+                        .addresses
+                        .iter()
+                        .filter_map(|address| {
+                            rewards_by_address.get(address).map(|reward_info| Reward {
+                                pubkey: address.to_string(),
+                                lamports: reward_info.lamports,
+                                post_balance: reward_info.post_balance,
+                                reward_type: Some(reward_info.reward_type),
+                                commission: reward_info.commission,
+                            })
+                        })
+                        .collect();
+                    if rewards.is_empty() {
+                        continue;
+                    }
+                    let epoch_rewards = RpcEpochRewards {
+                        epoch: bank.epoch().saturating_sub(1),
+                        slot: bank.slot(),
+                        rewards,
+                    };
+                    inc_new_counter_info!("rpc-subscription-notify-rewards", 1);
+                    notifier.notify(&epoch_rewards, sub, false);
+                }
+            }
+            _ => {
+                warn!("subscriptions notification sent to the node progress thread");
+            }
+        }
+    }
+
     fn notify_accounts_logs_programs_signatures(
         subscriptions: &HashMap<SubscriptionId, Arc<SubscriptionInfo>>,
         bank_forks: &Arc<RwLock<BankForks>>,
+        blockstore: Option<&Blockstore>,
         commitment_slots: &CommitmentSlots,
-        notifier: &mut RpcNotifier,
+        notifier: &RpcNotifier,
+        notification_pool: Option<&ThreadPool>,
         source: &'static str,
     ) {
         let mut total_time = Measure::start("notify_accounts_logs_programs_signatures");
-        let mut num_accounts_found = 0;
-        let mut num_accounts_notified = 0;
-
-        let mut num_logs_found = 0;
-        let mut num_logs_notified = 0;
-
-        let mut num_signatures_found = 0;
-        let mut num_signatures_notified = 0;
-
-        let mut num_programs_found = 0;
-        let mut num_programs_notified = 0;
+        let recent_signatures = notifier.recent_signatures.clone();
+        let program_writes = ProgramWrites::default();
+        let completed_blocks = CompletedBlocks::default();
 
-        for subscription in subscriptions.values() {
+        let notify_subscription = |subscription: &Arc<SubscriptionInfo>| {
             match subscription.params() {
                 SubscriptionParams::Account(params) => {
+                    let notified = check_if_account_written(
+                        subscription,
+                        bank_forks,
+                        commitment_slots,
+                        || {
+                            check_commitment_and_notify(
+                                params,
+                                subscription,
+                                bank_forks,
+                                commitment_slots,
+                                |bank, params| {
+                                    bank.get_account_modified_slot_including_zero_lamport(
+                                        &params.pubkey,
+                                    )
+                                },
+                                |result, params, last_notified_slot, bank| {
+                                    filter_account_result(
+                                        result,
+                                        params,
+                                        last_notified_slot,
+                                        bank,
+                                        &notifier.account_proofs,
+                                    )
+                                },
+                                notifier,
+                                false,
+                            )
+                        },
+                    );
+                    Some((NotifyKind::Accounts, notified))
+                }
+                SubscriptionParams::Accounts(params) => {
+                    let account_slots = subscription
+                        .last_notified_account_slots()
+                        .expect("account set subscription");
+                    let notified = check_if_account_written(
+                        subscription,
+                        bank_forks,
+                        commitment_slots,
+                        || {
+                            check_commitment_and_notify(
+                                params,
+                                subscription,
+                                bank_forks,
+                                commitment_slots,
+                                |bank, params| {
+                                    params
+                                        .pubkeys
+                                        .iter()
+                                        .map(|pubkey| {
+                                            (*pubkey, bank.get_account_modified_slot(pubkey))
+                                        })
+                                        .collect::<Vec<_>>()
+                                },
+                                |results, params, last_notified_slot, bank| {
+                                    filter_accounts_results(
+                                        results,
+                                        params,
+                                        last_notified_slot,
+                                        account_slots,
+                                        bank,
+                                    )
+                                },
+                                notifier,
+                                false,
+                            )
+                        },
+                    );
+                    Some((NotifyKind::Accounts, notified))
+                }
+                SubscriptionParams::TokenAccountsByOwner(params) => {
+                    let account_slots = subscription
+                        .last_notified_account_slots()
+                        .expect("token owner subscription");
                     let notified = check_commitment_and_notify(
                         params,
                         subscription,
                         bank_forks,
                         commitment_slots,
-                        |bank, params| bank.get_account_modified_slot(&params.pubkey),
-                        filter_account_result,
+                        |bank, params| {
+                            get_token_accounts_by_owner_modified_slots(
+                                bank,
+                                &params.owner,
+                                &account_slots.read().unwrap(),
+                            )
+                        },
+                        |results, params, last_notified_slot, bank| {
+                            filter_token_accounts_by_owner_results(
+                                results,
+                                params,
+                                last_notified_slot,
+                                account_slots,
+                                bank,
+                            )
+                        },
                         notifier,
                         false,
                     );
-
-                    num_accounts_found += 1;
-
-                    if notified {
-                        num_accounts_notified += 1;
-                    }
+                    Some((NotifyKind::Accounts, notified))
                 }
                 SubscriptionParams::Logs(params) => {
                     let notified = check_commitment_and_notify(
@@ -776,30 +3491,167 @@ impl RpcSubscriptions {
                         notifier,
                         false,
                     );
-                    num_logs_found += 1;
-
-                    if notified {
-                        num_logs_notified += 1;
-                    }
+                    Some((NotifyKind::Logs, notified))
                 }
-                SubscriptionParams::Program(params) => {
+                SubscriptionParams::Block(params) => {
+                    let notified = blockstore.map_or(false, |blockstore| {
+                        check_block_commitment_and_notify(
+                            params,
+                            subscription,
+                            bank_forks,
+                            blockstore,
+                            &completed_blocks,
+                            commitment_slots,
+                            notifier,
+                        )
+                    });
+                    Some((NotifyKind::Blocks, notified))
+                }
+                SubscriptionParams::Transaction(params) => {
+                    let notified = blockstore.map_or(false, |blockstore| {
+                        check_transaction_commitment_and_notify(
+                            params,
+                            subscription,
+                            bank_forks,
+                            blockstore,
+                            &completed_blocks,
+                            commitment_slots,
+                            notifier,
+                        )
+                    });
+                    Some((NotifyKind::Transactions, notified))
+                }
+                SubscriptionParams::Blockhash(params) => {
                     let notified = check_commitment_and_notify(
                         params,
                         subscription,
                         bank_forks,
                         commitment_slots,
-                        |bank, params| {
-                            bank.get_program_accounts_modified_since_parent(&params.pubkey)
+                        |_bank, _params| (),
+                        filter_blockhash_result,
+                        notifier,
+                        false,
+                    );
+                    Some((NotifyKind::Blockhashes, notified))
+                }
+                SubscriptionParams::BlockhashValidity(params) => {
+                    let notified = check_blockhash_validity_and_notify(
+                        params,
+                        subscription,
+                        bank_forks,
+                        commitment_slots,
+                        notifier,
+                    );
+                    Some((NotifyKind::Blockhashes, notified))
+                }
+                SubscriptionParams::Mint(params) => {
+                    let last_notified_mint = subscription
+                        .last_notified_mint()
+                        .expect("mint subscription");
+                    let notified = check_if_account_written(
+                        subscription,
+                        bank_forks,
+                        commitment_slots,
+                        || {
+                            check_commitment_and_notify(
+                                params,
+                                subscription,
+                                bank_forks,
+                                commitment_slots,
+                                |bank, params| bank.get_account_modified_slot(&params.pubkey),
+                                |result, params, last_notified_slot, _bank| {
+                                    filter_mint_result(
+                                        result,
+                                        params,
+                                        last_notified_slot,
+                                        last_notified_mint,
+                                    )
+                                },
+                                notifier,
+                                false,
+                            )
+                        },
+                    );
+                    Some((NotifyKind::Mints, notified))
+                }
+                SubscriptionParams::Stake(params) => {
+                    let notified = check_if_account_written(
+                        subscription,
+                        bank_forks,
+                        commitment_slots,
+                        || {
+                            check_commitment_and_notify(
+                                params,
+                                subscription,
+                                bank_forks,
+                                commitment_slots,
+                                |bank, params| {
+                                    bank.get_account_modified_slot_including_zero_lamport(
+                                        &params.pubkey,
+                                    )
+                                },
+                                filter_stake_result,
+                                notifier,
+                                false,
+                            )
                         },
-                        filter_program_results,
+                    );
+                    Some((NotifyKind::Accounts, notified))
+                }
+                SubscriptionParams::Program(params) => {
+                    let bank_method = |bank: &Bank, params: &ProgramSubscriptionParams| {
+                        program_writes.get_or_load(bank, &params.pubkey)
+                    };
+                    let notified = if params.batch {
+                        check_commitment_and_notify(
+                            params,
+                            subscription,
+                            bank_forks,
+                            commitment_slots,
+                            bank_method,
+                            filter_program_results_batched,
+                            notifier,
+                            false,
+                        )
+                    } else {
+                        check_commitment_and_notify(
+                            params,
+                            subscription,
+                            bank_forks,
+                            commitment_slots,
+                            bank_method,
+                            filter_program_results,
+                            notifier,
+                            false,
+                        )
+                    };
+                    Some((NotifyKind::Programs, notified))
+                }
+                SubscriptionParams::ProgramChanges(params) => {
+                    let notified = check_commitment_and_notify(
+                        params,
+                        subscription,
+                        bank_forks,
+                        commitment_slots,
+                        |bank, params| program_writes.get_or_load(bank, &params.pubkey),
+                        filter_program_changes_result,
                         notifier,
                         false,
                     );
-                    num_programs_found += 1;
-
-                    if notified {
-                        num_programs_notified += 1;
-                    }
+                    Some((NotifyKind::Programs, notified))
+                }
+                SubscriptionParams::Signature(params)
+                    if !params.followup_commitments.is_empty() =>
+                {
+                    let notified = check_signature_commitment_levels_and_notify(
+                        params,
+                        subscription,
+                        bank_forks,
+                        commitment_slots,
+                        &recent_signatures,
+                        notifier,
+                    );
+                    Some((NotifyKind::Signatures, notified))
                 }
                 SubscriptionParams::Signature(params) => {
                     let notified = check_commitment_and_notify(
@@ -810,29 +3662,67 @@ impl RpcSubscriptions {
                         |bank, params| {
                             bank.get_signature_status_processed_since_parent(&params.signature)
                         },
-                        filter_signature_result,
+                        |result, params, last_notified_slot, bank| {
+                            if let Some(result) = &result {
+                                recent_signatures.insert(
+                                    params.signature,
+                                    bank.slot(),
+                                    params.commitment,
+                                    result.clone(),
+                                );
+                            }
+                            filter_signature_result(result, params, last_notified_slot, bank)
+                        },
                         notifier,
                         true, // Unsubscribe.
                     );
-                    num_signatures_found += 1;
-
-                    if notified {
-                        num_signatures_notified += 1;
-                    }
+                    Some((NotifyKind::Signatures, notified))
+                }
+                _ => {
+                    error!("wrong subscription type in alps map");
+                    None
                 }
-                _ => error!("wrong subscription type in alps map"),
             }
-        }
+        };
+        let counts = if let Some(notification_pool) = notification_pool {
+            notification_pool.install(|| {
+                subscriptions
+                    .par_iter()
+                    .filter_map(|(_id, subscription)| notify_subscription(subscription))
+                    .fold(NotifyCounts::default, NotifyCounts::record)
+                    .reduce(NotifyCounts::default, NotifyCounts::merge)
+            })
+        } else {
+            subscriptions
+                .values()
+                .filter_map(notify_subscription)
+                .fold(NotifyCounts::default(), NotifyCounts::record)
+        };
 
         total_time.stop();
+        let (num_accounts_found, num_accounts_notified) = counts.get(NotifyKind::Accounts);
+        let (num_logs_found, num_logs_notified) = counts.get(NotifyKind::Logs);
+        let (num_signatures_found, num_signatures_notified) = counts.get(NotifyKind::Signatures);
+        let (num_programs_found, num_programs_notified) = counts.get(NotifyKind::Programs);
+        let (num_mints_found, num_mints_notified) = counts.get(NotifyKind::Mints);
+        let (num_blocks_found, num_blocks_notified) = counts.get(NotifyKind::Blocks);
+        let (num_blockhashes_found, num_blockhashes_notified) = counts.get(NotifyKind::Blockhashes);
+        let (num_transactions_found, num_transactions_notified) =
+            counts.get(NotifyKind::Transactions);
+        let (num_serialized_results_reused, num_serialized_results_encoded) =
+            notifier.serialized_results.lock().unwrap().take_stats();
 
         let total_notified = num_accounts_notified
+            + num_blockhashes_notified
+            + num_blocks_notified
             + num_logs_notified
+            + num_mints_notified
             + num_programs_notified
-            + num_signatures_notified;
+            + num_signatures_notified
+            + num_transactions_notified;
         let total_ms = total_time.as_ms();
         if total_notified > 0 || total_ms > 10 {
-            debug!(
+            tracing::debug!(
                 "notified({}): accounts: {} / {} logs: {} / {} programs: {} / {} signatures: {} / {}",
                 source,
                 num_accounts_found,
@@ -850,12 +3740,30 @@ impl RpcSubscriptions {
                 ("source", source.to_string(), String),
                 ("num_account_subscriptions", num_accounts_found, i64),
                 ("num_account_pubkeys_notified", num_accounts_notified, i64),
+                ("num_block_subscriptions", num_blocks_found, i64),
+                ("num_blocks_notified", num_blocks_notified, i64),
+                ("num_blockhash_subscriptions", num_blockhashes_found, i64),
+                ("num_blockhashes_notified", num_blockhashes_notified, i64),
                 ("num_logs_subscriptions", num_logs_found, i64),
                 ("num_logs_notified", num_logs_notified, i64),
+                ("num_mint_subscriptions", num_mints_found, i64),
+                ("num_mints_notified", num_mints_notified, i64),
                 ("num_program_subscriptions", num_programs_found, i64),
                 ("num_programs_notified", num_programs_notified, i64),
                 ("num_signature_subscriptions", num_signatures_found, i64),
                 ("num_signatures_notified", num_signatures_notified, i64),
+                ("num_transaction_subscriptions", num_transactions_found, i64),
+                ("num_transactions_notified", num_transactions_notified, i64),
+                (
+                    "num_serialized_results_reused",
+                    num_serialized_results_reused,
+                    i64
+                ),
+                (
+                    "num_serialized_results_encoded",
+                    num_serialized_results_encoded,
+                    i64
+                ),
                 ("notifications_time", total_time.as_us() as i64, i64),
             );
             inc_new_counter_info!(
@@ -882,8 +3790,9 @@ impl RpcSubscriptions {
             info!("RPC Notification thread - shutting down");
             self.exit.store(true, Ordering::Relaxed);
             let x = self.t_cleanup.take().unwrap().join();
+            let y = self.t_node_progress.take().unwrap().join();
             info!("RPC Notification thread - shut down.");
-            x
+            x.and(y)
         } else {
             warn!("RPC Notification thread - already shut down.");
             Ok(())
@@ -904,26 +3813,37 @@ pub(crate) mod tests {
             optimistically_confirmed_bank_tracker::{
                 BankNotification, OptimisticallyConfirmedBank, OptimisticallyConfirmedBankTracker,
             },
+            rpc::create_test_transactions_and_populate_blockstore,
             rpc_pubsub::RpcSolPubSubInternal,
             rpc_pubsub_service,
         },
         serial_test::serial,
+        solana_account_decoder::parse_token::spl_token_id_v2_0,
         solana_client::rpc_config::{
-            RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSignatureSubscribeConfig,
-            RpcTransactionLogsFilter,
+            RpcAccountInfoConfig, RpcBlockSubscribeConfig, RpcBlockSubscribeFilter,
+            RpcBlockhashValiditySubscribeConfig, RpcProgramAccountsConfig, RpcRootSubscribeConfig,
+            RpcSignatureSubscribeConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter,
+            RpcTransactionSubscribeConfig,
         },
+        solana_ledger::get_tmp_ledger_path,
         solana_runtime::{
             commitment::BlockCommitment,
             genesis_utils::{create_genesis_config, GenesisConfigInfo},
         },
         solana_sdk::{
+            account::Account,
             commitment_config::CommitmentConfig,
+            hash::Hash,
             message::Message,
             signature::{Keypair, Signer},
             stake, system_instruction, system_program, system_transaction,
             transaction::Transaction,
         },
-        std::{collections::HashSet, sync::atomic::Ordering::Relaxed},
+        solana_transaction_status::UiTransactionEncoding,
+        std::{
+            collections::HashSet,
+            sync::atomic::{AtomicU64, Ordering::Relaxed},
+        },
     };
 
     fn make_account_result(lamports: u64, subscription: u64, data: &str) -> serde_json::Value {
@@ -946,6 +3866,194 @@ pub(crate) mod tests {
         })
     }
 
+    #[test]
+    fn test_recent_signature_notifications() {
+        let signature = Signature::new(&[1; 64]);
+        let recent =
+            RecentSignatureNotifications::new(Duration::from_secs(60), MemoryBudget::default());
+        assert!(recent.get(&signature).is_none());
+
+        recent.insert(signature, 1, CommitmentConfig::processed(), Ok(()));
+        recent.insert(signature, 1, CommitmentConfig::finalized(), Ok(()));
+        // Lower commitment notifications don't replace a retained finalized one
+        recent.insert(signature, 2, CommitmentConfig::confirmed(), Ok(()));
+        let notification = recent.get(&signature).unwrap();
+        assert_eq!(notification.slot, 1);
+        assert!(CommitmentConfig {
+            commitment: notification.commitment
+        }
+        .is_finalized());
+
+        let memory_budget = MemoryBudget::default();
+        let recent =
+            RecentSignatureNotifications::new(Duration::from_millis(1), memory_budget.clone());
+        recent.insert(signature, 1, CommitmentConfig::processed(), Ok(()));
+        assert_eq!(
+            memory_budget.used(MemoryCategory::RecentSignatures),
+            RECENT_SIGNATURE_NOTIFICATION_BYTES
+        );
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(recent.get(&signature).is_none());
+        assert_eq!(memory_budget.used(MemoryCategory::RecentSignatures), 0);
+
+        // Nothing is retained once the budget has no room left
+        let memory_budget = MemoryBudget::new(RECENT_SIGNATURE_NOTIFICATION_BYTES);
+        let recent = RecentSignatureNotifications::new(Duration::from_secs(60), memory_budget);
+        recent.insert(signature, 1, CommitmentConfig::processed(), Ok(()));
+        assert!(recent.get(&signature).is_none());
+
+        let disabled =
+            RecentSignatureNotifications::new(Duration::default(), MemoryBudget::default());
+        disabled.insert(signature, 1, CommitmentConfig::processed(), Ok(()));
+        assert!(disabled.get(&signature).is_none());
+    }
+
+    #[test]
+    fn test_filter_mint_result() {
+        use {
+            solana_sdk::account::Account,
+            spl_token_v2_0::{solana_program::program_pack::Pack, state::Mint},
+        };
+
+        let params = MintSubscriptionParams {
+            pubkey: solana_sdk::pubkey::new_rand(),
+            commitment: CommitmentConfig::processed(),
+        };
+        let mint_account = |supply: u64| {
+            let mut data = vec![0; Mint::get_packed_len()];
+            Mint::pack(
+                Mint {
+                    supply,
+                    decimals: 6,
+                    is_initialized: true,
+                    ..Mint::default()
+                },
+                &mut data,
+            )
+            .unwrap();
+            AccountSharedData::from(Account {
+                lamports: 1,
+                data,
+                owner: spl_token_id_v2_0(),
+                ..Account::default()
+            })
+        };
+        let last_notified_mint = RwLock::new(None);
+
+        // Not modified since the subscription was created: only records the baseline
+        let (results, slot) = filter_mint_result(
+            Some((mint_account(100), 1)),
+            &params,
+            1,
+            &last_notified_mint,
+        );
+        assert_eq!(results.count(), 0);
+        assert_eq!(slot, 1);
+
+        let (results, slot) = filter_mint_result(
+            Some((mint_account(150), 2)),
+            &params,
+            1,
+            &last_notified_mint,
+        );
+        let updates: Vec<_> = results.collect();
+        assert_eq!(slot, 2);
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].mint, params.pubkey.to_string());
+        assert_eq!(updates[0].previous.as_ref().unwrap().supply, "100");
+        assert_eq!(updates[0].current.supply, "150");
+        assert_eq!(updates[0].current.decimals, 6);
+
+        // Written, but none of the tracked fields changed
+        let (results, slot) = filter_mint_result(
+            Some((mint_account(150), 3)),
+            &params,
+            2,
+            &last_notified_mint,
+        );
+        assert_eq!(results.count(), 0);
+        assert_eq!(slot, 2);
+
+        // Not a mint
+        let (results, _) = filter_mint_result(
+            Some((AccountSharedData::new(1, 0, &system_program::id()), 4)),
+            &params,
+            2,
+            &last_notified_mint,
+        );
+        assert_eq!(results.count(), 0);
+    }
+
+    #[test]
+    fn test_filter_stake_result() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+        let params = StakeSubscriptionParams {
+            pubkey: solana_sdk::pubkey::new_rand(),
+            commitment: CommitmentConfig::processed(),
+        };
+        let vote_pubkey = solana_sdk::pubkey::new_rand();
+        let vote_account = solana_vote_program::vote_state::create_account(
+            &vote_pubkey,
+            &solana_sdk::pubkey::new_rand(),
+            0,
+            100,
+        );
+        let stake_account = solana_stake_program::stake_state::create_account(
+            &solana_sdk::pubkey::new_rand(),
+            &vote_pubkey,
+            &vote_account,
+            &solana_sdk::rent::Rent::free(),
+            1_000,
+        );
+
+        // Not modified since the last notification
+        let (results, slot) =
+            filter_stake_result(Some((stake_account.clone(), 1)), &params, 1, bank.clone());
+        assert_eq!(results.count(), 0);
+        assert_eq!(slot, 1);
+
+        let (results, slot) =
+            filter_stake_result(Some((stake_account, 2)), &params, 1, bank.clone());
+        let updates: Vec<_> = results.collect();
+        assert_eq!(slot, 2);
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].stake, params.pubkey.to_string());
+        assert_eq!(updates[0].lamports, 1_000);
+        assert!(matches!(updates[0].state, StakeAccountType::Delegated(_)));
+        assert_eq!(
+            updates[0].activation,
+            Some(RpcStakeActivation {
+                state: StakeActivationState::Active,
+                active: 1_000,
+                inactive: 0,
+            })
+        );
+
+        // Closed
+        let (results, slot) = filter_stake_result(
+            Some((AccountSharedData::default(), 3)),
+            &params,
+            2,
+            bank.clone(),
+        );
+        let updates: Vec<_> = results.collect();
+        assert_eq!(slot, 3);
+        assert_eq!(updates[0].lamports, 0);
+        assert_eq!(updates[0].state, StakeAccountType::Uninitialized);
+        assert_eq!(updates[0].activation, None);
+
+        // Not a stake account
+        let (results, slot) = filter_stake_result(
+            Some((AccountSharedData::new(1, 0, &system_program::id()), 4)),
+            &params,
+            3,
+            bank,
+        );
+        assert_eq!(results.count(), 0);
+        assert_eq!(slot, 3);
+    }
+
     #[test]
     #[serial]
     fn test_check_account_subscribe() {
@@ -1016,6 +4124,9 @@ pub(crate) mod tests {
                         commitment: Some(CommitmentConfig::processed()),
                         encoding: None,
                         data_slice: None,
+                        send_initial: None,
+                        compression_level: None,
+                        with_proof: None,
                     }),
                 )
                 .unwrap();
@@ -1025,6 +4136,8 @@ pub(crate) mod tests {
                 .assert_subscribed(&SubscriptionParams::Account(AccountSubscriptionParams {
                     pubkey,
                     commitment: CommitmentConfig::processed(),
+                    compression_level: None,
+                    with_proof: false,
                     data_slice: None,
                     encoding: UiAccountEncoding::Binary,
                 }));
@@ -1054,12 +4167,88 @@ pub(crate) mod tests {
                 .assert_unsubscribed(&SubscriptionParams::Account(AccountSubscriptionParams {
                     pubkey,
                     commitment: CommitmentConfig::processed(),
+                    compression_level: None,
+                    with_proof: false,
                     data_slice: None,
                     encoding: UiAccountEncoding::Binary,
                 }));
         }
     }
 
+    #[test]
+    fn test_check_account_subscribe_fork_switch() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(100);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let blockhash = bank.last_blockhash();
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let bank0 = bank_forks.read().unwrap().get(0).unwrap().clone();
+        bank_forks
+            .write()
+            .unwrap()
+            .insert(Bank::new_from_parent(&bank0, &Pubkey::default(), 1));
+        bank_forks
+            .write()
+            .unwrap()
+            .insert(Bank::new_from_parent(&bank0, &Pubkey::default(), 2));
+        let alice = Keypair::new();
+
+        let exit = Arc::new(AtomicBool::new(false));
+        let subscriptions = Arc::new(RpcSubscriptions::new_for_tests(
+            &exit,
+            bank_forks.clone(),
+            Arc::new(RwLock::new(BlockCommitmentCache::new_for_tests_with_slots(
+                1, 1,
+            ))),
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks),
+        ));
+        let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&subscriptions);
+        rpc.account_subscribe(
+            alice.pubkey().to_string(),
+            Some(RpcAccountInfoConfig {
+                commitment: Some(CommitmentConfig::processed()),
+                ..RpcAccountInfoConfig::default()
+            }),
+        )
+        .unwrap();
+
+        // Alice is created on the fork of slot 1 only
+        let tx = system_transaction::create_account(
+            &mint_keypair,
+            &alice,
+            blockhash,
+            1,
+            0,
+            &system_program::id(),
+        );
+        bank_forks
+            .read()
+            .unwrap()
+            .get(1)
+            .unwrap()
+            .process_transaction(&tx)
+            .unwrap();
+        subscriptions.notify_subscribers(CommitmentSlots {
+            slot: 1,
+            ..CommitmentSlots::default()
+        });
+        let response: serde_json::Value = serde_json::from_str(&receiver.recv()).unwrap();
+        assert_eq!(response["params"]["result"]["context"]["slot"], 1);
+        assert_eq!(response["params"]["result"]["value"]["lamports"], 1);
+
+        // Switching to slot 2 reverts the account without any write on that fork
+        subscriptions.notify_subscribers(CommitmentSlots {
+            slot: 2,
+            ..CommitmentSlots::default()
+        });
+        let response: serde_json::Value = serde_json::from_str(&receiver.recv()).unwrap();
+        assert_eq!(response["params"]["result"]["context"]["slot"], 2);
+        assert_eq!(response["params"]["result"]["value"]["lamports"], 0);
+    }
+
     #[test]
     #[serial]
     fn test_check_program_subscribe() {
@@ -1099,67 +4288,303 @@ pub(crate) mod tests {
         ));
         let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&subscriptions);
         let sub_id = rpc
-            .program_subscribe(
+            .program_subscribe(
+                stake::program::id().to_string(),
+                Some(RpcProgramAccountsConfig {
+                    account_config: RpcAccountInfoConfig {
+                        commitment: Some(CommitmentConfig::processed()),
+                        ..RpcAccountInfoConfig::default()
+                    },
+                    ..RpcProgramAccountsConfig::default()
+                }),
+            )
+            .unwrap();
+
+        subscriptions
+            .control
+            .assert_subscribed(&SubscriptionParams::Program(ProgramSubscriptionParams {
+                pubkey: stake::program::id(),
+                filters: Vec::new(),
+                commitment: CommitmentConfig::processed(),
+                compression_level: None,
+                data_slice: None,
+                encoding: UiAccountEncoding::Binary,
+                with_context: false,
+                batch: false,
+            }));
+
+        subscriptions.notify_subscribers(CommitmentSlots::default());
+        let response = receiver.recv();
+        let expected = json!({
+           "jsonrpc": "2.0",
+           "method": "programNotification",
+           "params": {
+               "result": {
+                   "context": { "slot": 0 },
+                   "value": {
+                       "account": {
+                          "data": "1111111111111111",
+                          "executable": false,
+                          "lamports": 1,
+                          "owner": "Stake11111111111111111111111111111111111111",
+                          "rentEpoch": 0,
+                       },
+                       "pubkey": alice.pubkey().to_string(),
+                    },
+               },
+               "subscription": 0,
+           }
+        });
+        assert_eq!(
+            expected,
+            serde_json::from_str::<serde_json::Value>(&response).unwrap(),
+        );
+
+        rpc.program_unsubscribe(sub_id).unwrap();
+        subscriptions
+            .control
+            .assert_unsubscribed(&SubscriptionParams::Program(ProgramSubscriptionParams {
+                pubkey: stake::program::id(),
+                filters: Vec::new(),
+                commitment: CommitmentConfig::processed(),
+                compression_level: None,
+                data_slice: None,
+                encoding: UiAccountEncoding::Binary,
+                with_context: false,
+                batch: false,
+            }));
+    }
+
+    #[test]
+    #[serial]
+    fn test_check_program_subscribe_shared_results() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(100);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let blockhash = bank.last_blockhash();
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let alice = Keypair::new();
+        let tx = system_transaction::create_account(
+            &mint_keypair,
+            &alice,
+            blockhash,
+            1,
+            16,
+            &stake::program::id(),
+        );
+        bank_forks
+            .write()
+            .unwrap()
+            .get(0)
+            .unwrap()
+            .process_transaction(&tx)
+            .unwrap();
+
+        let exit = Arc::new(AtomicBool::new(false));
+        let optimistically_confirmed_bank =
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks);
+        let subscriptions = Arc::new(RpcSubscriptions::new_for_tests(
+            &exit,
+            bank_forks,
+            Arc::new(RwLock::new(BlockCommitmentCache::new_for_tests())),
+            optimistically_confirmed_bank,
+        ));
+        let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&subscriptions);
+        // Both commitments resolve to slot 0, so the second subscription reuses the
+        // result serialized for the first one
+        for commitment in &[CommitmentConfig::processed(), CommitmentConfig::finalized()] {
+            rpc.program_subscribe(
+                stake::program::id().to_string(),
+                Some(RpcProgramAccountsConfig {
+                    account_config: RpcAccountInfoConfig {
+                        commitment: Some(*commitment),
+                        ..RpcAccountInfoConfig::default()
+                    },
+                    ..RpcProgramAccountsConfig::default()
+                }),
+            )
+            .unwrap();
+        }
+
+        subscriptions.notify_subscribers(CommitmentSlots::default());
+        let mut responses: Vec<serde_json::Value> = (0..2)
+            .map(|_| serde_json::from_str(&receiver.recv()).unwrap())
+            .collect();
+        responses.sort_by_key(|response| response["params"]["subscription"].as_u64());
+        for (subscription, response) in responses.into_iter().enumerate() {
+            let expected = json!({
+               "jsonrpc": "2.0",
+               "method": "programNotification",
+               "params": {
+                   "result": {
+                       "context": { "slot": 0 },
+                       "value": {
+                           "account": {
+                              "data": "1111111111111111",
+                              "executable": false,
+                              "lamports": 1,
+                              "owner": "Stake11111111111111111111111111111111111111",
+                              "rentEpoch": 0,
+                           },
+                           "pubkey": alice.pubkey().to_string(),
+                        },
+                   },
+                   "subscription": subscription,
+               }
+            });
+            assert_eq!(expected, response);
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_check_program_subscribe_batch() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(100);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let blockhash = bank.last_blockhash();
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let alice = Keypair::new();
+        let bob = Keypair::new();
+        for keypair in &[&alice, &bob] {
+            let tx = system_transaction::create_account(
+                &mint_keypair,
+                keypair,
+                blockhash,
+                1,
+                16,
+                &stake::program::id(),
+            );
+            bank_forks
+                .read()
+                .unwrap()
+                .get(0)
+                .unwrap()
+                .process_transaction(&tx)
+                .unwrap();
+        }
+
+        let exit = Arc::new(AtomicBool::new(false));
+        let optimistically_confirmed_bank =
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks);
+        let subscriptions = Arc::new(RpcSubscriptions::new_for_tests(
+            &exit,
+            bank_forks,
+            Arc::new(RwLock::new(BlockCommitmentCache::new_for_tests())),
+            optimistically_confirmed_bank,
+        ));
+        let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&subscriptions);
+        rpc.program_subscribe(
+            stake::program::id().to_string(),
+            Some(RpcProgramAccountsConfig {
+                account_config: RpcAccountInfoConfig {
+                    commitment: Some(CommitmentConfig::processed()),
+                    ..RpcAccountInfoConfig::default()
+                },
+                batch: Some(true),
+                ..RpcProgramAccountsConfig::default()
+            }),
+        )
+        .unwrap();
+
+        subscriptions.notify_subscribers(CommitmentSlots::default());
+        let response: serde_json::Value = serde_json::from_str(&receiver.recv()).unwrap();
+        assert_eq!(response["method"], "programNotification");
+        assert_eq!(response["params"]["result"]["context"]["slot"], 0);
+        let mut pubkeys: Vec<_> = response["params"]["result"]["value"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|keyed_account| keyed_account["pubkey"].as_str().unwrap().to_string())
+            .collect();
+        pubkeys.sort();
+        let mut expected = vec![alice.pubkey().to_string(), bob.pubkey().to_string()];
+        expected.sort();
+        assert_eq!(pubkeys, expected);
+    }
+
+    #[test]
+    #[serial]
+    fn test_check_program_changes_subscribe() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(100);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let blockhash = bank.last_blockhash();
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let alice = Keypair::new();
+        let bob = Keypair::new();
+        for &(keypair, lamports) in &[(&alice, 1), (&bob, 2)] {
+            let tx = system_transaction::create_account(
+                &mint_keypair,
+                keypair,
+                blockhash,
+                lamports,
+                16,
+                &stake::program::id(),
+            );
+            bank_forks
+                .write()
+                .unwrap()
+                .get(0)
+                .unwrap()
+                .process_transaction(&tx)
+                .unwrap();
+        }
+
+        let exit = Arc::new(AtomicBool::new(false));
+        let optimistically_confirmed_bank =
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks);
+        let subscriptions = Arc::new(RpcSubscriptions::new_for_tests(
+            &exit,
+            bank_forks,
+            Arc::new(RwLock::new(BlockCommitmentCache::new_for_tests())),
+            optimistically_confirmed_bank,
+        ));
+        let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&subscriptions);
+        let sub_id = rpc
+            .program_changes_subscribe(
                 stake::program::id().to_string(),
-                Some(RpcProgramAccountsConfig {
-                    account_config: RpcAccountInfoConfig {
-                        commitment: Some(CommitmentConfig::processed()),
-                        ..RpcAccountInfoConfig::default()
-                    },
-                    ..RpcProgramAccountsConfig::default()
-                }),
+                Some(CommitmentConfig::processed()),
             )
             .unwrap();
-
-        subscriptions
-            .control
-            .assert_subscribed(&SubscriptionParams::Program(ProgramSubscriptionParams {
-                pubkey: stake::program::id(),
-                filters: Vec::new(),
-                commitment: CommitmentConfig::processed(),
-                data_slice: None,
-                encoding: UiAccountEncoding::Binary,
-                with_context: false,
-            }));
+        let params = SubscriptionParams::ProgramChanges(ProgramChangesSubscriptionParams {
+            pubkey: stake::program::id(),
+            commitment: CommitmentConfig::processed(),
+        });
+        subscriptions.control.assert_subscribed(&params);
 
         subscriptions.notify_subscribers(CommitmentSlots::default());
-        let response = receiver.recv();
-        let expected = json!({
-           "jsonrpc": "2.0",
-           "method": "programNotification",
-           "params": {
-               "result": {
-                   "context": { "slot": 0 },
-                   "value": {
-                       "account": {
-                          "data": "1111111111111111",
-                          "executable": false,
-                          "lamports": 1,
-                          "owner": "Stake11111111111111111111111111111111111111",
-                          "rentEpoch": 0,
-                       },
-                       "pubkey": alice.pubkey().to_string(),
-                    },
-               },
-               "subscription": 0,
-           }
-        });
+        let response: serde_json::Value = serde_json::from_str(&receiver.recv()).unwrap();
+        assert_eq!(response["method"], "programChangesNotification");
+        assert_eq!(response["params"]["result"]["context"]["slot"], 0);
+        let mut changes: Vec<RpcAccountChange> =
+            serde_json::from_value(response["params"]["result"]["value"].clone()).unwrap();
+        changes.sort_by_key(|change| change.lamports);
         assert_eq!(
-            expected,
-            serde_json::from_str::<serde_json::Value>(&response).unwrap(),
+            changes,
+            vec![
+                RpcAccountChange {
+                    pubkey: alice.pubkey().to_string(),
+                    lamports: 1,
+                },
+                RpcAccountChange {
+                    pubkey: bob.pubkey().to_string(),
+                    lamports: 2,
+                },
+            ]
         );
 
-        rpc.program_unsubscribe(sub_id).unwrap();
-        subscriptions
-            .control
-            .assert_unsubscribed(&SubscriptionParams::Program(ProgramSubscriptionParams {
-                pubkey: stake::program::id(),
-                filters: Vec::new(),
-                commitment: CommitmentConfig::processed(),
-                data_slice: None,
-                encoding: UiAccountEncoding::Binary,
-                with_context: false,
-            }));
+        rpc.program_changes_unsubscribe(sub_id).unwrap();
+        subscriptions.control.assert_unsubscribed(&params);
     }
 
     #[test]
@@ -1269,7 +4694,9 @@ pub(crate) mod tests {
                 encoding: UiAccountEncoding::Binary,
                 data_slice: None,
                 commitment: CommitmentConfig::confirmed(),
+                compression_level: None,
                 with_context: false,
+                batch: false,
             }));
 
         let mut highest_confirmed_slot: Slot = 0;
@@ -1435,7 +4862,9 @@ pub(crate) mod tests {
                 encoding: UiAccountEncoding::Binary,
                 data_slice: None,
                 commitment: CommitmentConfig::confirmed(),
+                compression_level: None,
                 with_context: false,
+                batch: false,
             }));
 
         let mut highest_confirmed_slot: Slot = 0;
@@ -1546,7 +4975,9 @@ pub(crate) mod tests {
                 encoding: UiAccountEncoding::Binary,
                 data_slice: None,
                 commitment: CommitmentConfig::confirmed(),
+                compression_level: None,
                 with_context: false,
+                batch: false,
             }));
 
         let mut highest_confirmed_slot: Slot = 0;
@@ -1724,6 +5155,7 @@ pub(crate) mod tests {
                 Some(RpcSignatureSubscribeConfig {
                     commitment: Some(CommitmentConfig::processed()),
                     enable_received_notification: Some(false),
+                    commitment_levels: None,
                 }),
             )
             .unwrap();
@@ -1733,6 +5165,7 @@ pub(crate) mod tests {
                 Some(RpcSignatureSubscribeConfig {
                     commitment: Some(CommitmentConfig::finalized()),
                     enable_received_notification: Some(false),
+                    commitment_levels: None,
                 }),
             )
             .unwrap();
@@ -1742,6 +5175,7 @@ pub(crate) mod tests {
                 Some(RpcSignatureSubscribeConfig {
                     commitment: Some(CommitmentConfig::processed()),
                     enable_received_notification: Some(false),
+                    commitment_levels: None,
                 }),
             )
             .unwrap();
@@ -1751,6 +5185,7 @@ pub(crate) mod tests {
                 Some(RpcSignatureSubscribeConfig {
                     commitment: Some(CommitmentConfig::processed()),
                     enable_received_notification: Some(false),
+                    commitment_levels: None,
                 }),
             )
             .unwrap();
@@ -1762,6 +5197,7 @@ pub(crate) mod tests {
                 Some(RpcSignatureSubscribeConfig {
                     commitment: Some(CommitmentConfig::processed()),
                     enable_received_notification: Some(true),
+                    commitment_levels: None,
                 }),
             )
             .unwrap();
@@ -1779,8 +5215,10 @@ pub(crate) mod tests {
         subscriptions
             .notify_signatures_received((received_slot, vec![unprocessed_tx.signatures[0]]));
         subscriptions.notify_subscribers(commitment_slots);
-        let expected_res =
-            RpcSignatureResult::ProcessedSignature(ProcessedSignatureResult { err: None });
+        let expected_res = RpcSignatureResult::ProcessedSignature(ProcessedSignatureResult {
+            err: None,
+            confirmation_status: None,
+        });
         let received_expected_res =
             RpcSignatureResult::ReceivedSignature(ReceivedSignatureResult::ReceivedSignature);
         struct Notification {
@@ -1858,10 +5296,348 @@ pub(crate) mod tests {
             .control
             .signature_subscribed(&past_bank_tx.signatures[0]));
 
-        // Unprocessed signature subscription should not be removed
-        assert!(subscriptions
-            .control
-            .signature_subscribed(&unprocessed_tx.signatures[0]));
+        // Unprocessed signature subscription should not be removed
+        assert!(subscriptions
+            .control
+            .signature_subscribed(&unprocessed_tx.signatures[0]));
+    }
+
+    #[test]
+    #[serial]
+    fn test_check_finalized_block_subscribe() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let bank0 = bank_forks.read().unwrap().get(0).unwrap().clone();
+        let bank1 = Bank::new_from_parent(&bank0, &Pubkey::default(), 1);
+        bank_forks.write().unwrap().insert(bank1);
+        let bank1 = bank_forks.read().unwrap().get(1).unwrap().clone();
+
+        let ledger_path = get_tmp_ledger_path!();
+        let blockstore = Arc::new(Blockstore::open(&ledger_path).unwrap());
+        let keypair1 = Keypair::new();
+        let keypair2 = Keypair::new();
+        let keypair3 = Keypair::new();
+        create_test_transactions_and_populate_blockstore(
+            vec![&mint_keypair, &keypair1, &keypair2, &keypair3],
+            0,
+            bank1,
+            blockstore.clone(),
+            Arc::new(AtomicU64::default()),
+        );
+
+        let exit = Arc::new(AtomicBool::new(false));
+        let subscriptions = Arc::new(RpcSubscriptions::new_with_config(
+            &exit,
+            Some(blockstore),
+            bank_forks.clone(),
+            Arc::new(RwLock::new(BlockCommitmentCache::new_for_tests())),
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks),
+            &PubSubConfig::default_for_tests(),
+        ));
+        let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&subscriptions);
+        let sub_id = rpc
+            .block_subscribe(
+                RpcBlockSubscribeFilter::MentionsAccountOrProgram(keypair1.pubkey().to_string()),
+                Some(RpcBlockSubscribeConfig {
+                    commitment: Some(CommitmentConfig::finalized()),
+                    ..RpcBlockSubscribeConfig::default()
+                }),
+            )
+            .unwrap();
+
+        subscriptions.notify_subscribers(CommitmentSlots {
+            slot: 1,
+            root: 1,
+            highest_confirmed_slot: 1,
+            highest_confirmed_root: 1,
+        });
+        let response: serde_json::Value = serde_json::from_str(&receiver.recv()).unwrap();
+        assert_eq!(response["method"], "blockNotification");
+        let value = &response["params"]["result"]["value"];
+        assert_eq!(value["slot"], 1);
+        assert_eq!(value["err"], serde_json::Value::Null);
+        // Only the transfer to keypair1 mentions the filtered account
+        assert_eq!(value["block"]["transactions"].as_array().unwrap().len(), 1);
+
+        rpc.block_unsubscribe(sub_id).unwrap();
+    }
+
+    #[test]
+    #[serial]
+    fn test_logs_subscribe_replay() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let bank0 = bank_forks.read().unwrap().get(0).unwrap().clone();
+        let bank1 = Bank::new_from_parent(&bank0, &Pubkey::default(), 1);
+        bank_forks.write().unwrap().insert(bank1);
+        let bank1 = bank_forks.read().unwrap().get(1).unwrap().clone();
+
+        let ledger_path = get_tmp_ledger_path!();
+        let blockstore = Arc::new(Blockstore::open(&ledger_path).unwrap());
+        let keypair1 = Keypair::new();
+        let keypair2 = Keypair::new();
+        let keypair3 = Keypair::new();
+        let signatures = create_test_transactions_and_populate_blockstore(
+            vec![&mint_keypair, &keypair1, &keypair2, &keypair3],
+            0,
+            bank1,
+            blockstore.clone(),
+            Arc::new(AtomicU64::default()),
+        );
+
+        let exit = Arc::new(AtomicBool::new(false));
+        let subscriptions = Arc::new(RpcSubscriptions::new_with_config(
+            &exit,
+            Some(blockstore),
+            bank_forks.clone(),
+            Arc::new(RwLock::new(BlockCommitmentCache::new_for_tests_with_slots(
+                1, 1,
+            ))),
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks),
+            &PubSubConfig::default_for_tests(),
+        ));
+        let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&subscriptions);
+        let sub_id = rpc
+            .logs_subscribe(
+                RpcTransactionLogsFilter::Mentions(vec![keypair1.pubkey().to_string()]),
+                Some(RpcTransactionLogsConfig {
+                    commitment: Some(CommitmentConfig::finalized()),
+                    filter_status: None,
+                    start_slot: Some(0),
+                }),
+            )
+            .unwrap();
+        let response: serde_json::Value = serde_json::from_str(&receiver.recv()).unwrap();
+        assert_eq!(response["method"], "logsNotification");
+        let result = &response["params"]["result"];
+        assert_eq!(result["context"]["slot"], 1);
+        assert_eq!(result["value"]["signature"], signatures[0].to_string());
+        assert_eq!(result["value"]["err"], serde_json::Value::Null);
+        rpc.logs_unsubscribe(sub_id).unwrap();
+
+        // Only the transfer that failed with an instruction error is replayed
+        let sub_id = rpc
+            .logs_subscribe(
+                RpcTransactionLogsFilter::All,
+                Some(RpcTransactionLogsConfig {
+                    commitment: Some(CommitmentConfig::finalized()),
+                    filter_status: Some(RpcTransactionLogsStatusFilter::Failed),
+                    start_slot: Some(1),
+                }),
+            )
+            .unwrap();
+        let response: serde_json::Value = serde_json::from_str(&receiver.recv()).unwrap();
+        let result = &response["params"]["result"];
+        assert_eq!(result["context"]["slot"], 1);
+        assert_eq!(result["value"]["signature"], signatures[1].to_string());
+        assert_ne!(result["value"]["err"], serde_json::Value::Null);
+        rpc.logs_unsubscribe(sub_id).unwrap();
+    }
+
+    #[test]
+    #[serial]
+    fn test_check_transaction_subscribe() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let bank0 = bank_forks.read().unwrap().get(0).unwrap().clone();
+        let bank1 = Bank::new_from_parent(&bank0, &Pubkey::default(), 1);
+        bank_forks.write().unwrap().insert(bank1);
+        let bank1 = bank_forks.read().unwrap().get(1).unwrap().clone();
+
+        let ledger_path = get_tmp_ledger_path!();
+        let blockstore = Arc::new(Blockstore::open(&ledger_path).unwrap());
+        let keypair1 = Keypair::new();
+        let keypair2 = Keypair::new();
+        let keypair3 = Keypair::new();
+        create_test_transactions_and_populate_blockstore(
+            vec![&mint_keypair, &keypair1, &keypair2, &keypair3],
+            0,
+            bank1,
+            blockstore.clone(),
+            Arc::new(AtomicU64::default()),
+        );
+
+        let exit = Arc::new(AtomicBool::new(false));
+        let subscriptions = Arc::new(RpcSubscriptions::new_with_config(
+            &exit,
+            Some(blockstore),
+            bank_forks.clone(),
+            Arc::new(RwLock::new(BlockCommitmentCache::new_for_tests())),
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks),
+            &PubSubConfig::default_for_tests(),
+        ));
+        let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&subscriptions);
+        assert!(rpc
+            .transaction_subscribe(
+                keypair1.pubkey().to_string(),
+                Some(RpcTransactionSubscribeConfig {
+                    commitment: Some(CommitmentConfig::processed()),
+                    ..RpcTransactionSubscribeConfig::default()
+                }),
+            )
+            .is_err());
+        let sub_id = rpc
+            .transaction_subscribe(
+                keypair1.pubkey().to_string(),
+                Some(RpcTransactionSubscribeConfig {
+                    commitment: Some(CommitmentConfig::finalized()),
+                    encoding: Some(UiTransactionEncoding::Json),
+                }),
+            )
+            .unwrap();
+
+        subscriptions.notify_subscribers(CommitmentSlots {
+            slot: 1,
+            root: 1,
+            highest_confirmed_slot: 1,
+            highest_confirmed_root: 1,
+        });
+        let response: serde_json::Value = serde_json::from_str(&receiver.recv()).unwrap();
+        assert_eq!(response["method"], "transactionNotification");
+        let value = &response["params"]["result"]["value"];
+        assert_eq!(value["slot"], 1);
+        let account_keys = value["transaction"]["transaction"]["message"]["accountKeys"]
+            .as_array()
+            .unwrap();
+        assert!(account_keys.contains(&serde_json::Value::String(keypair1.pubkey().to_string())));
+
+        rpc.transaction_unsubscribe(sub_id).unwrap();
+    }
+
+    #[test]
+    #[serial]
+    fn test_check_blockhash_subscribe() {
+        let exit = Arc::new(AtomicBool::new(false));
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let bank0 = bank_forks.read().unwrap().get(0).unwrap().clone();
+        let bank1 = Bank::new_from_parent(&bank0, &Pubkey::default(), 1);
+        bank_forks.write().unwrap().insert(bank1);
+        let bank1 = bank_forks.read().unwrap().get(1).unwrap().clone();
+        let expected_blockhash = bank1.last_blockhash();
+        let expected_last_valid_block_height = bank1
+            .get_blockhash_last_valid_block_height(&expected_blockhash)
+            .unwrap();
+
+        let subscriptions = Arc::new(RpcSubscriptions::new_for_tests(
+            &exit,
+            bank_forks.clone(),
+            Arc::new(RwLock::new(BlockCommitmentCache::new_for_tests())),
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks),
+        ));
+        let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&subscriptions);
+        let sub_id = rpc
+            .blockhash_subscribe(Some(CommitmentConfig::processed()))
+            .unwrap();
+
+        subscriptions.notify_subscribers(CommitmentSlots {
+            slot: 1,
+            ..CommitmentSlots::default()
+        });
+        let response: serde_json::Value = serde_json::from_str(&receiver.recv()).unwrap();
+        assert_eq!(response["method"], "blockhashNotification");
+        let result = &response["params"]["result"];
+        assert_eq!(result["context"]["slot"], 1);
+        assert_eq!(result["value"]["blockhash"], expected_blockhash.to_string());
+        assert_eq!(
+            result["value"]["lastValidBlockHeight"],
+            expected_last_valid_block_height
+        );
+
+        rpc.blockhash_unsubscribe(sub_id).unwrap();
+    }
+
+    #[test]
+    #[serial]
+    fn test_check_blockhash_validity_subscribe() {
+        let exit = Arc::new(AtomicBool::new(false));
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let bank0 = bank_forks.read().unwrap().get(0).unwrap().clone();
+        let bank1 = Bank::new_from_parent(&bank0, &Pubkey::default(), 1);
+        bank_forks.write().unwrap().insert(bank1);
+        let bank1 = bank_forks.read().unwrap().get(1).unwrap().clone();
+        let blockhash = bank1.last_blockhash();
+        let last_valid_block_height = bank1
+            .get_blockhash_last_valid_block_height(&blockhash)
+            .unwrap();
+
+        let subscriptions = Arc::new(RpcSubscriptions::new_for_tests(
+            &exit,
+            bank_forks.clone(),
+            Arc::new(RwLock::new(BlockCommitmentCache::new_for_tests())),
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks),
+        ));
+        let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&subscriptions);
+        let sub_id = rpc
+            .blockhash_validity_subscribe(
+                blockhash.to_string(),
+                Some(RpcBlockhashValiditySubscribeConfig {
+                    commitment: Some(CommitmentConfig::processed()),
+                    expiry_warning_blocks: Some(last_valid_block_height),
+                }),
+            )
+            .unwrap();
+        let commitment_slots = CommitmentSlots {
+            slot: 1,
+            ..CommitmentSlots::default()
+        };
+
+        subscriptions.notify_subscribers(commitment_slots);
+        let response: serde_json::Value = serde_json::from_str(&receiver.recv()).unwrap();
+        assert_eq!(response["method"], "isBlockhashValidNotification");
+        assert_eq!(response["params"]["subscription"], json!(sub_id));
+        assert_eq!(
+            response["params"]["result"]["value"],
+            json!({
+                "blockhash": blockhash.to_string(),
+                "valid": true,
+                "status": "valid",
+                "blockHeight": 1,
+                "lastValidBlockHeight": last_valid_block_height,
+            })
+        );
+
+        // The expiry warning is only sent once, unknown blockhashes are reported right away
+        let unknown_id = rpc
+            .blockhash_validity_subscribe(
+                Hash::new_unique().to_string(),
+                Some(RpcBlockhashValiditySubscribeConfig {
+                    commitment: Some(CommitmentConfig::processed()),
+                    ..RpcBlockhashValiditySubscribeConfig::default()
+                }),
+            )
+            .unwrap();
+        subscriptions.notify_subscribers(commitment_slots);
+        let response: serde_json::Value = serde_json::from_str(&receiver.recv()).unwrap();
+        assert_eq!(response["params"]["subscription"], json!(unknown_id));
+        assert_eq!(response["params"]["result"]["value"]["valid"], false);
+        assert_eq!(response["params"]["result"]["value"]["status"], "unknown");
+        assert_eq!(
+            response["params"]["result"]["value"]["lastValidBlockHeight"],
+            serde_json::Value::Null
+        );
+
+        // Subscriptions to expired or unknown blockhashes are cancelled
+        assert!(rpc.blockhash_validity_unsubscribe(unknown_id).is_err());
+        rpc.blockhash_validity_unsubscribe(sub_id).unwrap();
     }
 
     #[test]
@@ -1885,9 +5661,12 @@ pub(crate) mod tests {
         subscriptions
             .control
             .assert_subscribed(&SubscriptionParams::Slot);
+        let mut main_receiver = subscriptions.control.broadcast_receiver();
 
         subscriptions.notify_slot(0, 0, 0);
         let response = receiver.recv();
+        // Slot notifications only travel on the firehose channel
+        assert!(main_receiver.try_recv().is_err());
 
         let expected_res = SlotInfo {
             parent: 0,
@@ -1910,7 +5689,7 @@ pub(crate) mod tests {
 
     #[test]
     #[serial]
-    fn test_check_root_subscribe() {
+    fn test_cbor_notifications() {
         let exit = Arc::new(AtomicBool::new(false));
         let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
         let bank = Bank::new_for_tests(&genesis_config);
@@ -1924,15 +5703,72 @@ pub(crate) mod tests {
             optimistically_confirmed_bank,
         ));
         let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&subscriptions);
-        let sub_id = rpc.root_subscribe().unwrap();
-
+        rpc.slot_subscribe().unwrap();
         subscriptions
             .control
-            .assert_subscribed(&SubscriptionParams::Root);
+            .assert_subscribed(&SubscriptionParams::Slot);
+        let mut firehose_receiver = subscriptions.control.firehose_broadcast_receiver();
+
+        // Nothing is encoded to CBOR without CBOR clients
+        subscriptions.notify_slot(0, 0, 0);
+        receiver.recv();
+        let notification = firehose_receiver.try_recv().unwrap();
+        assert!(notification.cbor.upgrade().is_none());
+
+        let cbor_client = subscriptions.control.cbor_clients().register();
+        subscriptions.notify_slot(1, 0, 0);
+        let json = receiver.recv();
+        let notification = firehose_receiver.try_recv().unwrap();
+        let cbor = notification.cbor.upgrade().unwrap();
+        assert_eq!(
+            serde_cbor::from_slice::<serde_cbor::Value>(&cbor).unwrap(),
+            serde_json::from_str::<serde_cbor::Value>(&json).unwrap()
+        );
+
+        drop(cbor_client);
+        subscriptions.notify_slot(2, 1, 0);
+        receiver.recv();
+        let notification = firehose_receiver.try_recv().unwrap();
+        assert!(notification.cbor.upgrade().is_none());
+    }
+
+    #[test]
+    #[serial]
+    fn test_check_root_subscribe() {
+        let exit = Arc::new(AtomicBool::new(false));
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank0 = Arc::new(Bank::new_for_tests(&genesis_config));
+        let bank1 = Arc::new(Bank::new_from_parent(&bank0, &Pubkey::default(), 1));
+        let bank2 = Arc::new(Bank::new_from_parent(&bank1, &Pubkey::default(), 2));
+        let bank3 = Arc::new(Bank::new_from_parent(&bank2, &Pubkey::default(), 3));
+        let bank_forks = Arc::new(RwLock::new(BankForks::new_from_banks(&[bank0], 0)));
+        let optimistically_confirmed_bank =
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks);
+        let subscriptions = Arc::new(RpcSubscriptions::new_for_tests(
+            &exit,
+            bank_forks,
+            Arc::new(RwLock::new(BlockCommitmentCache::new_for_tests())),
+            optimistically_confirmed_bank,
+        ));
+        let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&subscriptions);
+        let sub_id = rpc.root_subscribe(None).unwrap();
+        let (rpc_info, mut receiver_info) = rpc_pubsub_service::test_connection(&subscriptions);
+        let sub_id_info = rpc_info
+            .root_subscribe(Some(RpcRootSubscribeConfig {
+                enable_block_info: Some(true),
+            }))
+            .unwrap();
 
-        subscriptions.notify_roots(vec![2, 1, 3]);
+        let params = SubscriptionParams::Root(RootSubscriptionParams::default());
+        let params_info = SubscriptionParams::Root(RootSubscriptionParams {
+            enable_block_info: true,
+        });
+        subscriptions.control.assert_subscribed(&params);
+        subscriptions.control.assert_subscribed(&params_info);
+
+        subscriptions.notify_roots(vec![bank2.clone(), bank1.clone(), bank3.clone()]);
 
-        for expected_root in 1..=3 {
+        for (expected_root, bank) in (1..=3).zip([bank1, bank2, bank3].iter()) {
             let response = receiver.recv();
 
             let expected_res_str =
@@ -1942,12 +5778,284 @@ pub(crate) mod tests {
                 expected_res_str
             );
             assert_eq!(expected, response);
+
+            let response = receiver_info.recv();
+            let expected = json!({
+               "jsonrpc": "2.0",
+               "method": "rootNotification",
+               "params": {
+                   "result": {
+                       "slot": expected_root,
+                       "parent": expected_root - 1,
+                       "blockhash": bank.last_blockhash().to_string(),
+                       "blockHeight": bank.block_height(),
+                   },
+                   "subscription": 1,
+               }
+            });
+            assert_eq!(
+                expected,
+                serde_json::from_str::<serde_json::Value>(&response).unwrap(),
+            );
         }
 
         rpc.root_unsubscribe(sub_id).unwrap();
+        subscriptions.control.assert_unsubscribed(&params);
+        rpc_info.root_unsubscribe(sub_id_info).unwrap();
+        subscriptions.control.assert_unsubscribed(&params_info);
+    }
+
+    #[test]
+    #[serial]
+    fn test_check_epoch_subscribe() {
+        let exit = Arc::new(AtomicBool::new(false));
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let bank0 = bank_forks.read().unwrap().get(0).unwrap().clone();
+        let first_slot = bank0.epoch_schedule().get_first_slot_in_epoch(1);
+        let bank1 = Arc::new(Bank::new_from_parent(&bank0, &Pubkey::default(), 1));
+        let bank2 = Arc::new(Bank::new_from_parent(
+            &bank1,
+            &Pubkey::default(),
+            first_slot + 1,
+        ));
+        let bank3 = Arc::new(Bank::new_from_parent(
+            &bank2,
+            &Pubkey::default(),
+            first_slot + 2,
+        ));
+
+        let subscriptions = Arc::new(RpcSubscriptions::new_for_tests(
+            &exit,
+            bank_forks.clone(),
+            Arc::new(RwLock::new(BlockCommitmentCache::new_for_tests())),
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks),
+        ));
+        let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&subscriptions);
+        let sub_id = rpc.epoch_subscribe().unwrap();
+        subscriptions
+            .control
+            .assert_subscribed(&SubscriptionParams::Epoch);
+
+        // Only the first rooted bank of epoch 1 crosses the boundary, even though the
+        // epoch's first slot was skipped
+        subscriptions.notify_roots(vec![bank3, bank1, bank2]);
+        let response: serde_json::Value = serde_json::from_str(&receiver.recv()).unwrap();
+        assert_eq!(response["method"], "epochNotification");
+        let result = &response["params"]["result"];
+        assert_eq!(result["epoch"], 1);
+        assert_eq!(result["firstSlot"], first_slot);
+        assert_eq!(result["slot"], first_slot + 1);
+        assert_eq!(result["leaderScheduleReady"], true);
+        assert_eq!(result["stakeActivation"]["epoch"], 0);
+
+        rpc.epoch_unsubscribe(sub_id).unwrap();
+        subscriptions
+            .control
+            .assert_unsubscribed(&SubscriptionParams::Epoch);
+    }
+
+    #[test]
+    #[serial]
+    fn test_check_slots_updates_pruned() {
+        let exit = Arc::new(AtomicBool::new(false));
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let bank0 = bank_forks.read().unwrap().get(0).unwrap().clone();
+        let bank2 = Arc::new(Bank::new_from_parent(&bank0, &Pubkey::default(), 2));
+        let bank3 = Arc::new(Bank::new_from_parent(&bank2, &Pubkey::default(), 3));
+
+        let subscriptions = Arc::new(RpcSubscriptions::new_for_tests(
+            &exit,
+            bank_forks.clone(),
+            Arc::new(RwLock::new(BlockCommitmentCache::new_for_tests())),
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks),
+        ));
+        let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&subscriptions);
+        let sub_id = rpc.slots_updates_subscribe().unwrap();
+        subscriptions
+            .control
+            .assert_subscribed(&SubscriptionParams::SlotsUpdates);
+
+        subscriptions.notify_pruned_slots(
+            1,
+            vec![
+                (bank3, SlotPruneReason::Dead),
+                (bank2, SlotPruneReason::AbandonedFork),
+            ],
+        );
+        for &(slot, parent, reason) in &[(2, 0, "abandonedFork"), (3, 2, "dead")] {
+            let response: serde_json::Value = serde_json::from_str(&receiver.recv()).unwrap();
+            assert_eq!(response["method"], "slotsUpdatesNotification");
+            let result = &response["params"]["result"];
+            assert_eq!(result["type"], "pruned");
+            assert_eq!(result["slot"], slot);
+            assert_eq!(result["parent"], parent);
+            assert_eq!(result["root"], 1);
+            assert_eq!(result["reason"], reason);
+        }
+
+        rpc.slots_updates_unsubscribe(sub_id).unwrap();
         subscriptions
             .control
-            .assert_unsubscribed(&SubscriptionParams::Root);
+            .assert_unsubscribed(&SubscriptionParams::SlotsUpdates);
+    }
+
+    #[test]
+    #[serial]
+    fn test_check_rewards_subscribe() {
+        let exit = Arc::new(AtomicBool::new(false));
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let bank0 = bank_forks.read().unwrap().get(0).unwrap().clone();
+        let first_slot = bank0.epoch_schedule().get_first_slot_in_epoch(1);
+        let bank1 = Arc::new(Bank::new_from_parent(
+            &bank0,
+            &Pubkey::default(),
+            first_slot,
+        ));
+        let address = solana_sdk::pubkey::new_rand();
+        let other_address = solana_sdk::pubkey::new_rand();
+        let reward_info = |reward_type: RewardType, lamports: i64| RewardInfo {
+            reward_type,
+            lamports,
+            post_balance: 1_000 + lamports as u64,
+            commission: Some(10),
+        };
+        *bank1.rewards.write().unwrap() = vec![
+            (address, reward_info(RewardType::Staking, 5)),
+            (address, reward_info(RewardType::Fee, 7)),
+            (other_address, reward_info(RewardType::Voting, 3)),
+        ];
+
+        let subscriptions = Arc::new(RpcSubscriptions::new_for_tests(
+            &exit,
+            bank_forks.clone(),
+            Arc::new(RwLock::new(BlockCommitmentCache::new_for_tests())),
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks),
+        ));
+        let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&subscriptions);
+        let sub_id = rpc.rewards_subscribe(vec![address.to_string()]).unwrap();
+
+        subscriptions.notify_roots(vec![bank1]);
+        let response: serde_json::Value = serde_json::from_str(&receiver.recv()).unwrap();
+        assert_eq!(response["method"], "rewardsNotification");
+        // Only the staking reward of the requested address is delivered
+        assert_eq!(
+            response["params"]["result"],
+            json!({
+                "epoch": 0,
+                "slot": first_slot,
+                "rewards": [{
+                    "pubkey": address.to_string(),
+                    "lamports": 5,
+                    "postBalance": 1_005,
+                    "rewardType": "Staking",
+                    "commission": 10,
+                }],
+            })
+        );
+
+        rpc.rewards_unsubscribe(sub_id).unwrap();
+    }
+
+    #[test]
+    #[serial]
+    fn test_check_rewards_subscribe_without_rewards() {
+        let exit = Arc::new(AtomicBool::new(false));
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let bank0 = bank_forks.read().unwrap().get(0).unwrap().clone();
+        let first_slot = bank0.epoch_schedule().get_first_slot_in_epoch(1);
+        let bank1 = Arc::new(Bank::new_from_parent(
+            &bank0,
+            &Pubkey::default(),
+            first_slot,
+        ));
+        let address = solana_sdk::pubkey::new_rand();
+        *bank1.rewards.write().unwrap() = vec![(
+            address,
+            RewardInfo {
+                reward_type: RewardType::Staking,
+                lamports: 5,
+                post_balance: 1_005,
+                commission: Some(10),
+            },
+        )];
+
+        let subscriptions = Arc::new(RpcSubscriptions::new_for_tests(
+            &exit,
+            bank_forks.clone(),
+            Arc::new(RwLock::new(BlockCommitmentCache::new_for_tests())),
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks),
+        ));
+        let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&subscriptions);
+        // Subscriptions are visited in no particular order, so several of them without rewards
+        // make it likely that one is visited before the subscription with rewards
+        let sub_ids_without_rewards: Vec<_> = (0..8)
+            .map(|_| {
+                rpc.rewards_subscribe(vec![solana_sdk::pubkey::new_rand().to_string()])
+                    .unwrap()
+            })
+            .collect();
+        let sub_id = rpc.rewards_subscribe(vec![address.to_string()]).unwrap();
+
+        subscriptions.notify_roots(vec![bank1]);
+        let response: serde_json::Value = serde_json::from_str(&receiver.recv()).unwrap();
+        assert_eq!(response["method"], "rewardsNotification");
+        assert_eq!(response["params"]["subscription"], json!(sub_id));
+        assert_eq!(
+            response["params"]["result"]["rewards"][0]["pubkey"],
+            address.to_string()
+        );
+
+        rpc.rewards_unsubscribe(sub_id).unwrap();
+        for sub_id in sub_ids_without_rewards {
+            rpc.rewards_unsubscribe(sub_id).unwrap();
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_check_performance_samples_subscribe() {
+        let exit = Arc::new(AtomicBool::new(false));
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let subscriptions = Arc::new(RpcSubscriptions::new_for_tests(
+            &exit,
+            bank_forks.clone(),
+            Arc::new(RwLock::new(BlockCommitmentCache::new_for_tests())),
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks),
+        ));
+        let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&subscriptions);
+        let sub_id = rpc.performance_samples_subscribe().unwrap();
+
+        subscriptions.notify_performance_sample(
+            150,
+            &PerfSample {
+                num_transactions: 3_000,
+                num_slots: 150,
+                sample_period_secs: 60,
+            },
+        );
+        let response: serde_json::Value = serde_json::from_str(&receiver.recv()).unwrap();
+        assert_eq!(response["method"], "performanceSamplesNotification");
+        assert_eq!(
+            response["params"]["result"],
+            json!({
+                "slot": 150,
+                "numTransactions": 3_000,
+                "numSlots": 150,
+                "samplePeriodSecs": 60,
+            })
+        );
+
+        rpc.performance_samples_unsubscribe(sub_id).unwrap();
     }
 
     #[test]
@@ -1990,6 +6098,9 @@ pub(crate) mod tests {
                     commitment: Some(CommitmentConfig::confirmed()),
                     encoding: None,
                     data_slice: None,
+                    send_initial: None,
+                    compression_level: None,
+                    with_proof: None,
                 }),
             )
             .unwrap();
@@ -2077,6 +6188,9 @@ pub(crate) mod tests {
                     commitment: Some(CommitmentConfig::confirmed()),
                     encoding: None,
                     data_slice: None,
+                    send_initial: None,
+                    compression_level: None,
+                    with_proof: None,
                 }),
             )
             .unwrap();
@@ -2121,6 +6235,347 @@ pub(crate) mod tests {
         assert!(!subscriptions.control.account_subscribed(&alice.pubkey()));
     }
 
+    #[test]
+    fn test_filter_program_results_data_slice() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(100);
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+        let program_id = Pubkey::new_unique();
+        let pubkey = Pubkey::new_unique();
+        let account = AccountSharedData::from(Account {
+            lamports: 1,
+            data: (0..32).collect(),
+            owner: program_id,
+            executable: false,
+            rent_epoch: 0,
+        });
+        let data = |data_slice| {
+            let params = ProgramSubscriptionParams {
+                pubkey: program_id,
+                filters: vec![RpcFilterType::DataSize(32)],
+                encoding: UiAccountEncoding::Base64,
+                data_slice,
+                commitment: CommitmentConfig::processed(),
+                compression_level: None,
+                with_context: false,
+                batch: false,
+            };
+            let mut results = filter_program_results(
+                Arc::new(vec![(pubkey, account.clone())]),
+                &params,
+                0,
+                bank.clone(),
+            )
+            .0;
+            let keyed_account = results.next().unwrap();
+            assert!(results.next().is_none());
+            keyed_account
+                .account
+                .decode::<AccountSharedData>()
+                .unwrap()
+                .data()
+                .to_vec()
+        };
+
+        // Filters are evaluated against the full data, before slicing
+        assert_eq!(data(None), (0..32).collect::<Vec<u8>>());
+        assert_eq!(
+            data(Some(UiDataSliceConfig {
+                offset: 0,
+                length: 8,
+            })),
+            (0..8).collect::<Vec<u8>>()
+        );
+        assert_eq!(
+            data(Some(UiDataSliceConfig {
+                offset: 30,
+                length: 8,
+            })),
+            vec![30, 31]
+        );
+    }
+
+    #[test]
+    fn test_program_writes_shared() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(100);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let program_id = Pubkey::new_unique();
+        let pubkey = Pubkey::new_unique();
+        let account = AccountSharedData::new(1, 0, &program_id);
+        bank.store_account(&pubkey, &account);
+
+        let program_writes = ProgramWrites::default();
+        let accounts = program_writes.get_or_load(&bank, &program_id);
+        assert_eq!(*accounts, vec![(pubkey, account)]);
+        // Later subscriptions on the same program reuse the first load
+        assert!(Arc::ptr_eq(
+            &accounts,
+            &program_writes.get_or_load(&bank, &program_id)
+        ));
+        assert!(program_writes
+            .get_or_load(&bank, &Pubkey::new_unique())
+            .is_empty());
+    }
+
+    #[test]
+    fn test_completed_blocks_shared() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(10_000);
+        let bank0 = Arc::new(Bank::new_for_tests(&genesis_config));
+        let bank1 = Arc::new(Bank::new_from_parent(&bank0, &Pubkey::default(), 1));
+        let ledger_path = get_tmp_ledger_path!();
+        let blockstore = Arc::new(Blockstore::open(&ledger_path).unwrap());
+        create_test_transactions_and_populate_blockstore(
+            vec![
+                &mint_keypair,
+                &Keypair::new(),
+                &Keypair::new(),
+                &Keypair::new(),
+            ],
+            0,
+            bank1,
+            blockstore.clone(),
+            Arc::new(AtomicU64::default()),
+        );
+
+        let completed_blocks = CompletedBlocks::default();
+        let block = completed_blocks.get_or_load(&blockstore, 1).unwrap();
+        assert!(!block.transactions.is_empty());
+        // Later subscriptions on the same slot reuse the first load
+        assert!(Arc::ptr_eq(
+            &block,
+            &completed_blocks.get_or_load(&blockstore, 1).unwrap()
+        ));
+        assert!(completed_blocks.get_or_load(&blockstore, 2).is_none());
+    }
+
+    #[test]
+    fn test_serialized_results_window() {
+        let params = Arc::new(SubscriptionParams::Slot);
+        let key = |slot, index| (params.clone(), slot, index);
+        let result = Arc::new("{}".to_string());
+        let memory_budget = MemoryBudget::default();
+        let mut results = SerializedResults::new(memory_budget.clone());
+
+        results.insert(key(10, 0), result.clone());
+        assert!(results.get(&key(10, 0)).is_some());
+        assert!(results.get(&key(10, 1)).is_none());
+        assert_eq!(results.take_stats(), (1, 1));
+        assert_eq!(results.take_stats(), (0, 0));
+
+        // Results older than the window are neither kept nor added
+        results.insert(key(10 + SERIALIZED_RESULTS_SLOTS, 0), result.clone());
+        assert!(results.get(&key(10, 0)).is_none());
+        results.insert(key(10, 0), result.clone());
+        assert!(results.get(&key(10, 0)).is_none());
+        assert!(results
+            .get(&key(10 + SERIALIZED_RESULTS_SLOTS, 0))
+            .is_some());
+        assert_eq!(
+            memory_budget.used(MemoryCategory::RecentItems),
+            result.len()
+        );
+        drop(results);
+        assert_eq!(memory_budget.used(MemoryCategory::RecentItems), 0);
+    }
+
+    #[test]
+    fn test_notification_queue_coalescing() {
+        let (control_sender, control_receiver) = crossbeam_channel::unbounded();
+        let (notification_sender, notification_receiver) = crossbeam_channel::bounded(1);
+        let coalesced = Mutex::new(CoalescedNotifications::default());
+        let memory_budget = MemoryBudget::default();
+        let enqueue = |notification_entry| {
+            enqueue_notification(
+                &notification_sender,
+                &coalesced,
+                &memory_budget,
+                notification_entry,
+            )
+        };
+        let slot = |slot| {
+            NotificationEntry::Slot(SlotInfo {
+                slot,
+                parent: slot - 1,
+                root: 0,
+            })
+        };
+        let root = |slot| {
+            NotificationEntry::Root(RpcRootInfo {
+                slot,
+                parent: slot - 1,
+                blockhash: String::new(),
+                block_height: slot,
+            })
+        };
+        let bank = |slot| {
+            NotificationEntry::Bank(CommitmentSlots {
+                slot,
+                ..CommitmentSlots::default()
+            })
+        };
+
+        enqueue(slot(1));
+        // The queue is full, so later slot and root notifications are merged into the latest
+        // one of their kind
+        enqueue(slot(2));
+        enqueue(slot(3));
+        enqueue(root(1));
+        enqueue(root(2));
+        // Other notifications are dropped and counted
+        enqueue(bank(4));
+        enqueue(bank(5));
+        enqueue(NotificationEntry::Gossip(5));
+        control_sender
+            .send(NotificationEntry::Subscribed(
+                SubscriptionParams::Slot,
+                SubscriptionId::from(0),
+            ))
+            .unwrap();
+
+        let receive = || {
+            receive_notification(
+                &control_receiver,
+                &notification_receiver,
+                &coalesced,
+                Duration::from_millis(10),
+            )
+        };
+        let receive_slot = || match receive().unwrap() {
+            NotificationEntry::Slot(slot_info) => slot_info.slot,
+            notification_entry => panic!("unexpected {:?}", notification_entry),
+        };
+        // Subscription changes go first
+        assert_eq!(
+            format!("{:?}", receive().unwrap()),
+            "Subscribed(Slot, SubscriptionId(0))"
+        );
+        assert_eq!(receive_slot(), 1);
+        assert_eq!(receive_slot(), 3);
+        assert_eq!(format!("{:?}", receive().unwrap()), "Root(2)");
+        match receive().unwrap() {
+            NotificationEntry::Dropped(dropped) => {
+                assert_eq!(
+                    dropped.into_iter().collect::<Vec<_>>(),
+                    vec![
+                        (
+                            "bank",
+                            RpcNotificationsDropped {
+                                count: 2,
+                                first_slot: Some(4),
+                                last_slot: Some(5),
+                            }
+                        ),
+                        (
+                            "gossip",
+                            RpcNotificationsDropped {
+                                count: 1,
+                                first_slot: Some(5),
+                                last_slot: Some(5),
+                            }
+                        ),
+                    ]
+                );
+            }
+            notification_entry => panic!("unexpected {:?}", notification_entry),
+        }
+        assert!(matches!(receive(), Err(RecvTimeoutError::Timeout)));
+
+        // Once caught up, notifications are queued again
+        enqueue(bank(6));
+        assert_eq!(format!("{:?}", receive().unwrap()), "Bank({slot: 6})");
+        assert!(coalesced.lock().unwrap().take().is_none());
+    }
+
+    #[test]
+    fn test_filter_logs_results_by_status() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(100);
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+        let succeeded = Signature::new(&[1; 64]);
+        let failed = Signature::new(&[2; 64]);
+        let logs = vec![
+            TransactionLogInfo {
+                signature: succeeded,
+                result: Ok(()),
+                is_vote: false,
+                log_messages: vec![],
+            },
+            TransactionLogInfo {
+                signature: failed,
+                result: Err(transaction::TransactionError::AccountNotFound),
+                is_vote: false,
+                log_messages: vec![],
+            },
+        ];
+        let signatures = |filter_status| {
+            let params = LogsSubscriptionParams {
+                kind: LogsSubscriptionKind::All,
+                commitment: CommitmentConfig::processed(),
+                filter_status,
+            };
+            filter_logs_results(Some(logs.clone()), &params, 0, bank.clone())
+                .0
+                .map(|response| response.signature)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            signatures(RpcTransactionLogsStatusFilter::All),
+            vec![succeeded.to_string(), failed.to_string()]
+        );
+        assert_eq!(
+            signatures(RpcTransactionLogsStatusFilter::Succeeded),
+            vec![succeeded.to_string()]
+        );
+        assert_eq!(
+            signatures(RpcTransactionLogsStatusFilter::Failed),
+            vec![failed.to_string()]
+        );
+    }
+
+    #[test]
+    fn test_vote_watchers() {
+        let vote_pubkey1 = Pubkey::new_unique();
+        let vote_pubkey2 = Pubkey::new_unique();
+        let other_vote_pubkey = Pubkey::new_unique();
+        let mut filtered_pubkeys = vec![vote_pubkey1, vote_pubkey2];
+        filtered_pubkeys.sort();
+        let all = VoteSubscriptionParams { vote_pubkeys: None };
+        let filtered = VoteSubscriptionParams {
+            vote_pubkeys: Some(filtered_pubkeys),
+        };
+        let info = |params: &VoteSubscriptionParams, id: u64| {
+            Arc::new(SubscriptionInfo::new(
+                SubscriptionParams::Vote(params.clone()),
+                SubscriptionId::from(id),
+                0,
+            ))
+        };
+        let mut vote_watchers = VoteWatchers::default();
+        vote_watchers.insert(&all, &info(&all, 1));
+        vote_watchers.insert(&filtered, &info(&filtered, 2));
+        let ids = |vote_watchers: &VoteWatchers, vote_pubkey| {
+            let mut ids: Vec<u64> = vote_watchers
+                .get(vote_pubkey)
+                .map(|sub| sub.id().into())
+                .collect();
+            ids.sort_unstable();
+            ids
+        };
+
+        assert_eq!(ids(&vote_watchers, &vote_pubkey1), vec![1, 2]);
+        assert_eq!(ids(&vote_watchers, &vote_pubkey2), vec![1, 2]);
+        assert_eq!(ids(&vote_watchers, &other_vote_pubkey), vec![1]);
+
+        vote_watchers.remove(&filtered, SubscriptionId::from(2));
+        assert_eq!(ids(&vote_watchers, &vote_pubkey1), vec![1]);
+        assert!(vote_watchers.by_vote_pubkey.is_empty());
+        vote_watchers.remove(&all, SubscriptionId::from(1));
+        assert!(vote_watchers.get(&vote_pubkey1).next().is_none());
+    }
+
     #[test]
     fn test_total_subscriptions() {
         let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(100);
@@ -2161,12 +6616,12 @@ pub(crate) mod tests {
         assert_eq!(subscriptions.total(), 5);
 
         let (rpc6, _receiver6) = rpc_pubsub_service::test_connection(&subscriptions);
-        let sub_id6 = rpc6.vote_subscribe().unwrap();
+        let sub_id6 = rpc6.vote_subscribe(None).unwrap();
 
         assert_eq!(subscriptions.total(), 6);
 
         let (rpc7, _receiver7) = rpc_pubsub_service::test_connection(&subscriptions);
-        let sub_id7 = rpc7.root_subscribe().unwrap();
+        let sub_id7 = rpc7.root_subscribe(None).unwrap();
 
         assert_eq!(subscriptions.total(), 7);
 
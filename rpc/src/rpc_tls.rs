@@ -0,0 +1,77 @@
+//! TLS termination for the JSON RPC and pubsub servers
+
+use {
+    std::{
+        fs::File,
+        io::{self, BufReader},
+        path::PathBuf,
+        sync::Arc,
+    },
+    tokio_rustls::{
+        rustls::{internal::pemfile, NoClientAuth, PrivateKey, ServerConfig},
+        TlsAcceptor,
+    },
+};
+
+/// PEM encoded certificate chain and private key to terminate TLS with
+#[derive(Debug, Clone, PartialEq)]
+pub struct TlsConfig {
+    pub cert_path: PathBuf,
+    /// A PKCS #8 or RSA private key
+    pub key_path: PathBuf,
+}
+
+impl TlsConfig {
+    pub(crate) fn acceptor(&self) -> io::Result<TlsAcceptor> {
+        let invalid_data = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+
+        let certs = pemfile::certs(&mut BufReader::new(File::open(&self.cert_path)?))
+            .map_err(|_| invalid_data(format!("invalid certificates in {:?}", self.cert_path)))?;
+        let read_keys = |read: fn(&mut dyn io::BufRead) -> Result<Vec<PrivateKey>, ()>| {
+            read(&mut BufReader::new(File::open(&self.key_path)?))
+                .map_err(|_| invalid_data(format!("invalid private key in {:?}", self.key_path)))
+        };
+        let mut keys = read_keys(pemfile::pkcs8_private_keys)?;
+        if keys.is_empty() {
+            keys = read_keys(pemfile::rsa_private_keys)?;
+        }
+        let key = keys
+            .into_iter()
+            .next()
+            .ok_or_else(|| invalid_data(format!("no private key in {:?}", self.key_path)))?;
+
+        let mut config = ServerConfig::new(NoClientAuth::new());
+        config
+            .set_single_cert(certs, key)
+            .map_err(|err| invalid_data(err.to_string()))?;
+        Ok(TlsAcceptor::from(Arc::new(config)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tls_acceptor_errors() {
+        let dir = std::env::temp_dir().join(format!("test_tls_acceptor-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let cert_path = dir.join("cert.pem");
+        let key_path = dir.join("key.pem");
+        let config = TlsConfig {
+            cert_path: cert_path.clone(),
+            key_path: key_path.clone(),
+        };
+
+        let err = config.acceptor().err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+        std::fs::write(&cert_path, b"").unwrap();
+        std::fs::write(&key_path, b"").unwrap();
+        let err = config.acceptor().err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().starts_with("no private key"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,336 @@
+//! The `rpc_webhooks` module delivers the notifications of webhook subscriptions. Each
+//! webhook holds one subscription, whose notifications are POSTed to the webhook URL in
+//! order. Every request carries the hex encoded HMAC-SHA256 of its body, keyed with the
+//! webhook secret, in the `X-Solana-Signature` header. Failed deliveries are retried with
+//! exponential backoff and dropped once the attempts run out.
+//!
+//! Webhook URLs are supplied by clients, so their host must resolve to public addresses
+//! only. It is resolved again before every delivery and the request is sent to the checked
+//! address, so that a host can't be pointed at the node's own network once registered.
+
+use {
+    crate::{
+        rpc_pubsub_service::{forward_notifications, Error, PubSubConfig},
+        rpc_sse_service::StreamSubscription,
+    },
+    hmac::{Hmac, Mac, NewMac},
+    rand::Rng,
+    sha2::Sha256,
+    std::{
+        collections::HashMap,
+        net::{IpAddr, Ipv4Addr, SocketAddr},
+        sync::{Arc, Mutex},
+        time::Duration,
+    },
+    stream_cancel::{Trigger, Tripwire},
+    tokio::{net, select, sync::mpsc, time},
+};
+
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "x-solana-signature";
+
+/// Notifications queued for a webhook while earlier ones are being delivered. A webhook
+/// that stays behind long enough lags on the broadcast queues and is handled according to
+/// `PubSubConfig::slow_consumer_policy`.
+const DELIVERY_QUEUE_SIZE: usize = 1_024;
+const MAX_DELIVERY_ATTEMPTS: usize = 5;
+const INITIAL_RETRY_DELAY: Duration = Duration::from_millis(500);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub(crate) struct WebhookTarget {
+    pub(crate) url: reqwest::Url,
+    pub(crate) secret: String,
+}
+
+struct Webhook {
+    /// Token of the API key that registered the webhook
+    owner: String,
+    /// Dropping the trigger ends the subscription of the webhook
+    _trigger: Trigger,
+}
+
+pub(crate) struct Webhooks {
+    /// Maximum number of webhooks per API key
+    max_webhooks: usize,
+    webhooks: Mutex<HashMap<String, Webhook>>,
+}
+
+impl Webhooks {
+    pub(crate) fn new(max_webhooks: usize) -> Self {
+        Self {
+            max_webhooks,
+            webhooks: Mutex::default(),
+        }
+    }
+
+    /// Starts delivering the notifications of `subscription` to `target` on behalf of the
+    /// API key `owner`. Returns the id of the webhook, or `None` if the key has too many
+    /// webhooks already.
+    pub(crate) fn add(
+        self: &Arc<Self>,
+        subscription: StreamSubscription,
+        target: WebhookTarget,
+        config: PubSubConfig,
+        owner: &str,
+    ) -> Option<String> {
+        let id = format!("{:032x}", rand::thread_rng().gen::<u128>());
+        let (trigger, tripwire) = Tripwire::new();
+        {
+            let mut webhooks = self.webhooks.lock().unwrap();
+            let owned = webhooks
+                .values()
+                .filter(|webhook| webhook.owner == owner)
+                .count();
+            if owned >= self.max_webhooks {
+                return None;
+            }
+            webhooks.insert(
+                id.clone(),
+                Webhook {
+                    owner: owner.to_string(),
+                    _trigger: trigger,
+                },
+            );
+        }
+        inc_new_counter_info!("rpc-webhook-added", 1);
+
+        let (sender, receiver) = mpsc::channel(DELIVERY_QUEUE_SIZE);
+        let forward = forward_notifications(
+            subscription.handler,
+            subscription.broadcast_receiver,
+            subscription.firehose_receiver,
+            subscription.direct_receiver,
+            sender,
+            config,
+            |_, json| Ok(json),
+            Err,
+        );
+        let deliver = deliver(target, receiver);
+        let webhooks = Arc::clone(self);
+        let webhook_id = id.clone();
+        tokio::spawn(async move {
+            select! {
+                _ = async { tokio::join!(forward, deliver) } => {}
+                _ = tripwire => {}
+            }
+            webhooks.webhooks.lock().unwrap().remove(&webhook_id);
+        });
+        Some(id)
+    }
+
+    /// Ends the webhook `id` if it was registered by the API key `owner`. Returns whether
+    /// it did, webhooks of other keys are reported as unknown.
+    pub(crate) fn remove(&self, id: &str, owner: &str) -> bool {
+        let mut webhooks = self.webhooks.lock().unwrap();
+        match webhooks.get(id) {
+            Some(webhook) if webhook.owner == owner => webhooks.remove(id).is_some(),
+            _ => false,
+        }
+    }
+}
+
+/// Whether `ip` is a publicly routable address, rather than a loopback, private, link-local,
+/// shared, multicast or otherwise reserved one
+fn is_global(ip: IpAddr) -> bool {
+    fn is_global_v4(ip: Ipv4Addr) -> bool {
+        let [a, b, ..] = ip.octets();
+        !(ip.is_unspecified()
+            || ip.is_loopback()
+            || ip.is_private()
+            || ip.is_link_local()
+            || ip.is_broadcast()
+            || ip.is_documentation()
+            || ip.is_multicast()
+            || a == 0
+            // Shared address space, RFC 6598
+            || (a == 100 && (b & 0b1100_0000) == 64)
+            // IETF protocol assignments, RFC 6890
+            || (a == 192 && b == 0 && ip.octets()[2] == 0)
+            // Benchmarking, RFC 2544
+            || (a == 198 && (b & 0b1111_1110) == 18)
+            // Reserved, RFC 1112
+            || a >= 240)
+    }
+    match ip {
+        IpAddr::V4(ip) => is_global_v4(ip),
+        IpAddr::V6(ip) => {
+            if let Some(ip) = ip.to_ipv4() {
+                // IPv4-mapped and IPv4-compatible addresses reach the IPv4 address
+                return !ip.is_unspecified() && is_global_v4(ip);
+            }
+            let segments = ip.segments();
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_multicast()
+                // Unique local, RFC 4193
+                || (segments[0] & 0xfe00) == 0xfc00
+                // Link-local unicast
+                || (segments[0] & 0xffc0) == 0xfe80
+                // Documentation, RFC 3849
+                || (segments[0] == 0x2001 && segments[1] == 0xdb8)
+                // NAT64, RFC 6052, could reach any IPv4 address
+                || (segments[0] == 0x64 && segments[1] == 0xff9b))
+        }
+    }
+}
+
+/// Resolves the host of a webhook URL. Fails unless every address it resolves to is public.
+pub(crate) async fn resolve(url: &reqwest::Url) -> Result<SocketAddr, String> {
+    let host = url.host_str().ok_or("Webhook url has no host")?;
+    let port = url
+        .port_or_known_default()
+        .ok_or("Webhook url has no port")?;
+    // IPv6 hosts are bracketed in URLs
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let addrs: Vec<_> = net::lookup_host((host, port))
+        .await
+        .map_err(|err| format!("Failed to resolve webhook host: {}", err))?
+        .collect();
+    if addrs.iter().any(|addr| !is_global(addr.ip())) {
+        return Err("Webhook url must only resolve to public addresses".to_string());
+    }
+    addrs
+        .into_iter()
+        .next()
+        .ok_or_else(|| "Webhook host has no addresses".to_string())
+}
+
+/// Client sending requests for `url` to `addr`, the address its host was checked to
+/// resolve to. Redirects aren't followed, as their targets weren't checked.
+fn build_client(url: &reqwest::Url, addr: SocketAddr) -> reqwest::Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder()
+        .timeout(DELIVERY_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none());
+    if let Some(domain) = url.domain() {
+        builder = builder.resolve(domain, addr);
+    }
+    builder.build()
+}
+
+/// Resolves the host of `url` again and returns a client for the address it resolves to,
+/// reusing the `cached` one if the address didn't change
+async fn client_for(
+    url: &reqwest::Url,
+    cached: &mut Option<(SocketAddr, reqwest::Client)>,
+) -> Result<reqwest::Client, String> {
+    let addr = resolve(url).await?;
+    match cached {
+        Some((cached_addr, client)) if *cached_addr == addr => Ok(client.clone()),
+        _ => {
+            let client = build_client(url, addr).map_err(|err| err.to_string())?;
+            *cached = Some((addr, client.clone()));
+            Ok(client)
+        }
+    }
+}
+
+fn sign(secret: &str, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+async fn deliver(target: WebhookTarget, mut receiver: mpsc::Receiver<Result<Arc<String>, Error>>) {
+    // Rebuilt whenever the host resolves to another address
+    let mut client: Option<(SocketAddr, reqwest::Client)> = None;
+    while let Some(notification) = receiver.recv().await {
+        let body = match notification {
+            Ok(body) => body,
+            Err(err) => {
+                warn!("webhook {} subscription ended: {}", target.url, err);
+                return;
+            }
+        };
+        let signature = sign(&target.secret, &body);
+        let mut retry_delay = INITIAL_RETRY_DELAY;
+        for attempt in 1..=MAX_DELIVERY_ATTEMPTS {
+            let result = match client_for(&target.url, &mut client).await {
+                Ok(client) => client
+                    .post(target.url.clone())
+                    .header(reqwest::header::CONTENT_TYPE, "application/json")
+                    .header(WEBHOOK_SIGNATURE_HEADER, &signature)
+                    .body(body.to_string())
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status())
+                    .map_err(|err| err.to_string()),
+                Err(err) => Err(err),
+            };
+            match result {
+                Ok(_) => {
+                    inc_new_counter_info!("rpc-webhook-delivered", 1);
+                    break;
+                }
+                Err(err) if attempt == MAX_DELIVERY_ATTEMPTS => {
+                    inc_new_counter_info!("rpc-webhook-dropped", 1);
+                    warn!("webhook {} delivery failed: {}", target.url, err);
+                }
+                Err(err) => {
+                    debug!("webhook {} delivery attempt failed: {}", target.url, err);
+                    time::sleep(retry_delay).await;
+                    retry_delay = (retry_delay * 2).min(MAX_RETRY_DELAY);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_global() {
+        for ip in &["1.1.1.1", "8.8.8.8", "2606:4700::1111", "::ffff:1.1.1.1"] {
+            assert!(is_global(ip.parse().unwrap()), "{}", ip);
+        }
+        for ip in &[
+            "0.0.0.0",
+            "127.0.0.1",
+            "10.0.0.1",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "224.0.0.1",
+            "255.255.255.255",
+            "::",
+            "::1",
+            "fc00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+            "::ffff:169.254.169.254",
+            "64:ff9b::a9fe:a9fe",
+        ] {
+            assert!(!is_global(ip.parse().unwrap()), "{}", ip);
+        }
+    }
+
+    #[test]
+    fn test_resolve() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let resolve = |url: &str| runtime.block_on(resolve(&reqwest::Url::parse(url).unwrap()));
+        assert_eq!(
+            resolve("https://1.1.1.1/hook"),
+            Ok(SocketAddr::from(([1, 1, 1, 1], 443)))
+        );
+        assert_eq!(
+            resolve("http://[2606:4700::1111]:8080/"),
+            Ok("[2606:4700::1111]:8080".parse().unwrap())
+        );
+        assert!(resolve("http://127.0.0.1/").is_err());
+        assert!(resolve("http://localhost/").is_err());
+        assert!(resolve("http://[::1]/").is_err());
+        assert!(resolve("http://169.254.169.254/latest/meta-data/").is_err());
+    }
+
+    #[test]
+    fn test_sign() {
+        // RFC 4231, test case 2
+        assert_eq!(
+            sign("Jefe", "what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use {
+    crate::transaction_notifier_interface::TransactionNotifierLock,
     crossbeam_channel::{Receiver, RecvTimeoutError},
     itertools::izip,
     solana_ledger::{
@@ -25,9 +26,13 @@ pub struct TransactionStatusService {
 
 impl TransactionStatusService {
     #[allow(clippy::new_ret_no_self)]
+    /// Records the transaction statuses to `blockstore` if `enable_rpc_transaction_history` is
+    /// set, and passes them to `transaction_notifier`
     pub fn new(
         write_transaction_status_receiver: Receiver<TransactionStatusMessage>,
         max_complete_transaction_status_slot: Arc<AtomicU64>,
+        enable_rpc_transaction_history: bool,
+        transaction_notifier: Option<TransactionNotifierLock>,
         blockstore: Arc<Blockstore>,
         exit: &Arc<AtomicBool>,
     ) -> Self {
@@ -41,6 +46,8 @@ impl TransactionStatusService {
                 if let Err(RecvTimeoutError::Disconnected) = Self::write_transaction_status_batch(
                     &write_transaction_status_receiver,
                     &max_complete_transaction_status_slot,
+                    enable_rpc_transaction_history,
+                    transaction_notifier.clone(),
                     &blockstore,
                 ) {
                     break;
@@ -53,6 +60,8 @@ impl TransactionStatusService {
     fn write_transaction_status_batch(
         write_transaction_status_receiver: &Receiver<TransactionStatusMessage>,
         max_complete_transaction_status_slot: &Arc<AtomicU64>,
+        enable_rpc_transaction_history: bool,
+        transaction_notifier: Option<TransactionNotifierLock>,
         blockstore: &Arc<Blockstore>,
     ) -> Result<(), RecvTimeoutError> {
         match write_transaction_status_receiver.recv_timeout(Duration::from_secs(1))? {
@@ -146,6 +155,31 @@ impl TransactionStatusService {
                                 .collect(),
                         );
 
+                        let transaction_status_meta = TransactionStatusMeta {
+                            status,
+                            fee,
+                            pre_balances,
+                            post_balances,
+                            inner_instructions,
+                            log_messages,
+                            pre_token_balances,
+                            post_token_balances,
+                            rewards,
+                        };
+
+                        if let Some(transaction_notifier) = transaction_notifier.as_ref() {
+                            transaction_notifier.write().unwrap().notify_transaction(
+                                slot,
+                                transaction.signature(),
+                                &transaction_status_meta,
+                                &transaction,
+                            );
+                        }
+
+                        if !enable_rpc_transaction_history {
+                            continue;
+                        }
+
                         if let Some(memos) = extract_and_fmt_memos(transaction.message()) {
                             blockstore
                                 .write_transaction_memos(transaction.signature(), memos)
@@ -158,17 +192,7 @@ impl TransactionStatusService {
                                 *transaction.signature(),
                                 tx_account_locks.writable,
                                 tx_account_locks.readonly,
-                                TransactionStatusMeta {
-                                    status,
-                                    fee,
-                                    pre_balances,
-                                    post_balances,
-                                    inner_instructions,
-                                    log_messages,
-                                    pre_token_balances,
-                                    post_token_balances,
-                                    rewards,
-                                },
+                                transaction_status_meta,
                             )
                             .expect("Expect database write to succeed: TransactionStatus");
                     }
@@ -185,3 +209,105 @@ impl TransactionStatusService {
         self.thread_hdl.join()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::transaction_notifier_interface::TransactionNotifier,
+        crossbeam_channel::unbounded,
+        solana_ledger::{genesis_utils::create_genesis_config, get_tmp_ledger_path},
+        solana_runtime::bank::{RentDebits, TransactionBalancesSet},
+        solana_sdk::{
+            clock::Slot,
+            signature::{Keypair, Signature, Signer},
+            system_transaction,
+            transaction::SanitizedTransaction,
+        },
+        solana_transaction_status::token_balances::TransactionTokenBalancesSet,
+        std::sync::{Mutex, RwLock},
+    };
+
+    #[derive(Default)]
+    struct TestTransactionNotifier {
+        notifications: Mutex<Vec<(Slot, Signature, TransactionStatusMeta)>>,
+    }
+
+    impl TransactionNotifier for TestTransactionNotifier {
+        fn notify_transaction(
+            &self,
+            slot: Slot,
+            signature: &Signature,
+            transaction_status_meta: &TransactionStatusMeta,
+            _transaction: &SanitizedTransaction,
+        ) {
+            self.notifications.lock().unwrap().push((
+                slot,
+                *signature,
+                transaction_status_meta.clone(),
+            ));
+        }
+    }
+
+    #[test]
+    fn test_notify_transaction() {
+        let genesis_config_info = create_genesis_config(10_000);
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config_info.genesis_config));
+        let transaction =
+            SanitizedTransaction::from_transaction_for_tests(system_transaction::transfer(
+                &genesis_config_info.mint_keypair,
+                &Keypair::new().pubkey(),
+                1,
+                bank.last_blockhash(),
+            ));
+        let signature = *transaction.signature();
+        let blockstore = Arc::new(Blockstore::open(&get_tmp_ledger_path!()).unwrap());
+        let max_complete_transaction_status_slot = Arc::new(AtomicU64::default());
+
+        for enable_rpc_transaction_history in [false, true] {
+            let transaction_notifier = Arc::new(RwLock::new(TestTransactionNotifier::default()));
+            let (sender, receiver) = unbounded();
+            sender
+                .send(TransactionStatusMessage::Batch(TransactionStatusBatch {
+                    bank: bank.clone(),
+                    transactions: vec![transaction.clone()],
+                    statuses: vec![(Ok(()), None)],
+                    balances: TransactionBalancesSet::new(vec![vec![10_000]], vec![vec![9_994]]),
+                    token_balances: TransactionTokenBalancesSet::new(vec![vec![]], vec![vec![]]),
+                    inner_instructions: None,
+                    transaction_logs: None,
+                    rent_debits: vec![RentDebits::default()],
+                }))
+                .unwrap();
+            TransactionStatusService::write_transaction_status_batch(
+                &receiver,
+                &max_complete_transaction_status_slot,
+                enable_rpc_transaction_history,
+                Some(transaction_notifier.clone()),
+                &blockstore,
+            )
+            .unwrap();
+
+            // Plugins are notified whether or not the transaction history is recorded
+            let notifications = transaction_notifier
+                .read()
+                .unwrap()
+                .notifications
+                .lock()
+                .unwrap()
+                .clone();
+            assert_eq!(notifications.len(), 1);
+            let (slot, notified_signature, transaction_status_meta) = &notifications[0];
+            assert_eq!(*slot, bank.slot());
+            assert_eq!(*notified_signature, signature);
+            assert_eq!(transaction_status_meta.post_balances, vec![9_994]);
+            assert_eq!(
+                blockstore
+                    .read_transaction_status((signature, bank.slot()))
+                    .unwrap()
+                    .map(|transaction_status_meta| transaction_status_meta.post_balances),
+                enable_rpc_transaction_history.then(|| vec![9_994])
+            );
+        }
+    }
+}
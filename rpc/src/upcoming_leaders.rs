@@ -0,0 +1,128 @@
+//! Upcoming leader slots for `leaderScheduleSubscribe`, computed by the notification thread.
+
+use {
+    crate::rpc_subscription_tracker::LeaderScheduleSubscriptionParams,
+    solana_client::rpc_response::RpcLeaderSlot,
+    solana_ledger::{leader_schedule::LeaderSchedule, leader_schedule_utils},
+    solana_runtime::bank::Bank,
+    solana_sdk::clock::{Epoch, Slot},
+    std::{collections::HashMap, sync::Arc},
+};
+
+/// Leader schedules of the epochs that upcoming leader slots are drawn from.
+///
+/// An epoch whose stakes are not yet known to the bank is retried on every call, so
+/// subscribers see its slots as soon as its schedule can be computed.
+#[derive(Default)]
+pub(crate) struct UpcomingLeaders {
+    schedules: HashMap<Epoch, Arc<LeaderSchedule>>,
+}
+
+impl UpcomingLeaders {
+    fn schedule(&mut self, epoch: Epoch, bank: &Bank) -> Option<Arc<LeaderSchedule>> {
+        if let Some(schedule) = self.schedules.get(&epoch) {
+            return Some(schedule.clone());
+        }
+        let schedule = Arc::new(leader_schedule_utils::leader_schedule(epoch, bank)?);
+        self.schedules.insert(epoch, schedule.clone());
+        Some(schedule)
+    }
+
+    /// Returns up to `params.num_slots` leader slots following `slot`, using the leader
+    /// schedules computable from `bank`.
+    pub(crate) fn get(
+        &mut self,
+        params: &LeaderScheduleSubscriptionParams,
+        slot: Slot,
+        bank: &Bank,
+    ) -> Vec<RpcLeaderSlot> {
+        let epoch_schedule = bank.epoch_schedule();
+        let (mut epoch, slot_index) = epoch_schedule.get_epoch_and_slot_index(slot + 1);
+        self.schedules
+            .retain(|schedule_epoch, _| *schedule_epoch >= epoch);
+
+        let mut slot_index = slot_index as usize;
+        let mut leader_slots = Vec::with_capacity(params.num_slots);
+        while leader_slots.len() < params.num_slots {
+            let schedule = match self.schedule(epoch, bank) {
+                Some(schedule) => schedule,
+                None => break,
+            };
+            let first_slot = epoch_schedule.get_first_slot_in_epoch(epoch);
+            let num_slots = schedule.num_slots();
+            let slot_indices: Box<dyn Iterator<Item = usize>> = match &params.identity {
+                Some(identity) => Box::new(
+                    schedule
+                        .get_indices(identity, slot_index)
+                        .take_while(move |index| *index < num_slots),
+                ),
+                None => Box::new(slot_index..num_slots),
+            };
+            let remaining = params.num_slots - leader_slots.len();
+            leader_slots.extend(slot_indices.take(remaining).map(|index| RpcLeaderSlot {
+                slot: first_slot + index as Slot,
+                leader: schedule[index as u64].to_string(),
+            }));
+            epoch += 1;
+            slot_index = 0;
+        }
+        leader_slots
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_runtime::genesis_utils::{create_genesis_config_with_leader, GenesisConfigInfo},
+        solana_sdk::pubkey::Pubkey,
+    };
+
+    #[test]
+    fn test_upcoming_leaders() {
+        let leader = solana_sdk::pubkey::new_rand();
+        let GenesisConfigInfo { genesis_config, .. } =
+            create_genesis_config_with_leader(10_000, &leader, 1_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let slots_in_epoch = bank.get_slots_in_epoch(0);
+        let mut upcoming_leaders = UpcomingLeaders::default();
+
+        let params = LeaderScheduleSubscriptionParams {
+            identity: None,
+            num_slots: 4,
+        };
+        let leader_slots = upcoming_leaders.get(&params, 0, &bank);
+        assert_eq!(
+            leader_slots,
+            (1..5)
+                .map(|slot| RpcLeaderSlot {
+                    slot,
+                    leader: leader.to_string(),
+                })
+                .collect::<Vec<_>>()
+        );
+
+        // Upcoming slots continue into the next epoch once the current one runs out
+        let leader_slots = upcoming_leaders.get(&params, slots_in_epoch - 2, &bank);
+        assert_eq!(
+            leader_slots
+                .iter()
+                .map(|leader_slot| leader_slot.slot)
+                .collect::<Vec<_>>(),
+            (slots_in_epoch - 1..slots_in_epoch + 3).collect::<Vec<_>>()
+        );
+
+        let params = LeaderScheduleSubscriptionParams {
+            identity: Some(leader),
+            num_slots: 2,
+        };
+        assert_eq!(upcoming_leaders.get(&params, 0, &bank).len(), 2);
+
+        // An identity that never leads only gets slots from known schedules
+        let params = LeaderScheduleSubscriptionParams {
+            identity: Some(Pubkey::new_unique()),
+            num_slots: 2,
+        };
+        assert!(upcoming_leaders.get(&params, 0, &bank).is_empty());
+    }
+}
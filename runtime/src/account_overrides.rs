@@ -0,0 +1,21 @@
+use {
+    solana_sdk::{account::AccountSharedData, pubkey::Pubkey},
+    std::collections::HashMap,
+};
+
+/// Accounts loaded in place of the bank's own, so that a simulation can run against state
+/// that was never committed, eg. the results of the earlier transactions of a bundle
+#[derive(Default)]
+pub struct AccountOverrides {
+    accounts: HashMap<Pubkey, AccountSharedData>,
+}
+
+impl AccountOverrides {
+    pub fn set_account(&mut self, pubkey: &Pubkey, account: AccountSharedData) {
+        self.accounts.insert(*pubkey, account);
+    }
+
+    pub fn get(&self, pubkey: &Pubkey) -> Option<&AccountSharedData> {
+        self.accounts.get(pubkey)
+    }
+}
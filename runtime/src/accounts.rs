@@ -1,4 +1,5 @@
 use crate::{
+    account_overrides::AccountOverrides,
     accounts_db::{
         AccountShrinkThreshold, AccountsDb, AccountsDbConfig, BankHashInfo, ErrorCounters,
         LoadHint, LoadedAccount, ScanStorageResult, ACCOUNTS_DB_CONFIG_FOR_BENCHMARKS,
@@ -227,6 +228,7 @@ impl Accounts {
         error_counters: &mut ErrorCounters,
         rent_collector: &RentCollector,
         feature_set: &FeatureSet,
+        account_overrides: Option<&AccountOverrides>,
     ) -> Result<LoadedTransaction> {
         // Copy all the accounts
         let message = tx.message();
@@ -262,23 +264,29 @@ impl Accounts {
                             demote_program_write_locks,
                         )
                     } else {
-                        let (account, rent) = self
-                            .accounts_db
-                            .load_with_fixed_root(ancestors, key)
-                            .map(|(mut account, _)| {
-                                if message.is_writable(i, demote_program_write_locks) {
-                                    let rent_due = rent_collector.collect_from_existing_account(
-                                        key,
-                                        &mut account,
-                                        rent_for_sysvars,
-                                        self.accounts_db.filler_account_suffix.as_ref(),
-                                    );
-                                    (account, rent_due)
-                                } else {
-                                    (account, 0)
-                                }
-                            })
-                            .unwrap_or_default();
+                        let (account, rent) = if let Some(account_override) =
+                            account_overrides.and_then(|overrides| overrides.get(key))
+                        {
+                            (account_override.clone(), 0)
+                        } else {
+                            self.accounts_db
+                                .load_with_fixed_root(ancestors, key)
+                                .map(|(mut account, _)| {
+                                    if message.is_writable(i, demote_program_write_locks) {
+                                        let rent_due = rent_collector
+                                            .collect_from_existing_account(
+                                                key,
+                                                &mut account,
+                                                rent_for_sysvars,
+                                                self.accounts_db.filler_account_suffix.as_ref(),
+                                            );
+                                        (account, rent_due)
+                                    } else {
+                                        (account, 0)
+                                    }
+                                })
+                                .unwrap_or_default()
+                        };
 
                         if bpf_loader_upgradeable::check_id(account.owner()) {
                             if demote_program_write_locks
@@ -469,6 +477,7 @@ impl Accounts {
         error_counters: &mut ErrorCounters,
         rent_collector: &RentCollector,
         feature_set: &FeatureSet,
+        account_overrides: Option<&AccountOverrides>,
     ) -> Vec<TransactionLoadResult> {
         txs.iter()
             .zip(lock_results)
@@ -494,6 +503,7 @@ impl Accounts {
                         error_counters,
                         rent_collector,
                         feature_set,
+                        account_overrides,
                     ) {
                         Ok(loaded_transaction) => loaded_transaction,
                         Err(e) => return (Err(e), None),
@@ -613,6 +623,12 @@ impl Accounts {
         slot: Slot,
         program_id: Option<&Pubkey>,
     ) -> Vec<(Pubkey, AccountSharedData)> {
+        if let Some(program_id) = program_id {
+            // Unflushed slots index the accounts of watched owners, the rest need a scan
+            if let Some(accounts) = self.accounts_db.load_cached_by_owner(slot, program_id) {
+                return accounts;
+            }
+        }
         self.scan_slot(slot, |stored_account| {
             let hit = match program_id {
                 None => true,
@@ -1228,6 +1244,7 @@ mod tests {
             error_counters,
             rent_collector,
             &FeatureSet::all_enabled(),
+            None,
         )
     }
 
@@ -1779,6 +1796,44 @@ mod tests {
         assert_eq!(loaded, vec![]);
     }
 
+    #[test]
+    fn test_load_by_program_slot_cached() {
+        let accounts = Accounts::new_with_config_for_tests(
+            Vec::new(),
+            &ClusterType::Development,
+            AccountSecondaryIndexes::default(),
+            true,
+            AccountShrinkThreshold::default(),
+        );
+
+        let program0 = Pubkey::new(&[2; 32]);
+        let program1 = Pubkey::new(&[3; 32]);
+        // Only program0 is looked up in the owner index, program1 falls back to a scan
+        accounts
+            .accounts_db
+            .set_indexed_owners([program0].iter().copied().collect());
+        let pubkey0 = solana_sdk::pubkey::new_rand();
+        let account0 = AccountSharedData::new(1, 0, &program0);
+        accounts.store_slow_cached(0, &pubkey0, &account0);
+        let pubkey1 = solana_sdk::pubkey::new_rand();
+        accounts.store_slow_cached(0, &pubkey1, &AccountSharedData::new(1, 0, &program0));
+        let account1 = AccountSharedData::new(2, 0, &program1);
+        accounts.store_slow_cached(0, &pubkey1, &account1);
+        // Closed accounts are stored without an owner
+        let pubkey2 = solana_sdk::pubkey::new_rand();
+        accounts.store_slow_cached(0, &pubkey2, &AccountSharedData::new(0, 0, &program0));
+
+        assert_eq!(
+            accounts.load_by_program_slot(0, Some(&program0)),
+            vec![(pubkey0, account0)]
+        );
+        assert_eq!(
+            accounts.load_by_program_slot(0, Some(&program1)),
+            vec![(pubkey1, account1)]
+        );
+        assert_eq!(accounts.load_by_program_slot(1, Some(&program0)), vec![]);
+    }
+
     #[test]
     fn test_load_accounts_executable_with_write_lock() {
         let mut accounts: Vec<(Pubkey, AccountSharedData)> = Vec::new();
@@ -2478,6 +2533,7 @@ mod tests {
             &mut error_counters,
             &rent_collector,
             &FeatureSet::all_enabled(),
+            None,
         )
     }
 
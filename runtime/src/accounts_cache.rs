@@ -7,7 +7,7 @@ use solana_sdk::{
 };
 use std::{
     borrow::Borrow,
-    collections::BTreeSet,
+    collections::{BTreeSet, HashSet},
     ops::Deref,
     sync::{
         atomic::{AtomicBool, AtomicU64, Ordering},
@@ -20,6 +20,11 @@ pub type SlotCache = Arc<SlotCacheInner>;
 #[derive(Default, Debug)]
 pub struct SlotCacheInner {
     cache: DashMap<Pubkey, CachedAccount>,
+    /// Owners indexed in `by_owner`, as of the creation of this slot
+    indexed_owners: Arc<HashSet<Pubkey>>,
+    /// Pubkeys stored in this slot by indexed owner. An account whose owner changed within
+    /// the slot is listed under every indexed owner it had.
+    by_owner: DashMap<Pubkey, HashSet<Pubkey>>,
     same_account_writes: AtomicU64,
     same_account_writes_size: AtomicU64,
     unique_account_writes_size: AtomicU64,
@@ -67,6 +72,12 @@ impl SlotCacheInner {
             self.unique_account_writes_size
                 .fetch_add(account.data().len() as u64, Ordering::Relaxed);
         }
+        if !self.indexed_owners.is_empty() && self.indexed_owners.contains(account.owner()) {
+            self.by_owner
+                .entry(*account.owner())
+                .or_default()
+                .insert(*pubkey);
+        }
         let item = Arc::new(CachedAccountInner {
             account,
             hash: RwLock::new(hash.map(|h| *h.borrow())),
@@ -86,6 +97,25 @@ impl SlotCacheInner {
             .map(|account_ref| account_ref.value().clone())
     }
 
+    /// Accounts stored in this slot that are currently owned by `owner`. `None` if `owner`
+    /// was not indexed when the slot was created.
+    pub fn get_cloned_by_owner(&self, owner: &Pubkey) -> Option<Vec<CachedAccount>> {
+        if !self.indexed_owners.contains(owner) {
+            return None;
+        }
+        let pubkeys = match self.by_owner.get(owner) {
+            Some(pubkeys) => pubkeys,
+            None => return Some(Vec::new()),
+        };
+        Some(
+            pubkeys
+                .iter()
+                .filter_map(|pubkey| self.get_cloned(pubkey))
+                .filter(|cached_account| cached_account.account.owner() == owner)
+                .collect(),
+        )
+    }
+
     pub fn mark_slot_frozen(&self) {
         self.is_frozen.store(true, Ordering::SeqCst);
     }
@@ -146,6 +176,8 @@ pub struct AccountsCache {
     // could have triggered a flush of this slot already
     maybe_unflushed_roots: RwLock<BTreeSet<Slot>>,
     max_flushed_root: AtomicU64,
+    // Owners whose accounts are indexed in the slots created from now on
+    indexed_owners: RwLock<Arc<HashSet<Pubkey>>>,
 }
 
 impl AccountsCache {
@@ -180,19 +212,27 @@ impl AccountsCache {
         hash: Option<impl Borrow<Hash>>,
     ) -> CachedAccount {
         let slot_cache = self.slot_cache(slot).unwrap_or_else(||
-            // DashMap entry.or_insert() returns a RefMut, essentially a write lock,
+            // DashMap entry.or_insert_with() returns a RefMut, essentially a write lock,
             // which is dropped after this block ends, minimizing time held by the lock.
             // However, we still want to persist the reference to the `SlotStores` behind
             // the lock, hence we clone it out, (`SlotStores` is an Arc so is cheap to clone).
             self
                 .cache
                 .entry(slot)
-                .or_insert(Arc::new(SlotCacheInner::default()))
+                .or_insert_with(|| Arc::new(SlotCacheInner {
+                    indexed_owners: self.indexed_owners.read().unwrap().clone(),
+                    ..SlotCacheInner::default()
+                }))
                 .clone());
 
         slot_cache.insert(pubkey, account, hash, slot)
     }
 
+    /// Index the accounts of `owners` in the slots created from now on, and no others
+    pub fn set_indexed_owners(&self, owners: HashSet<Pubkey>) {
+        *self.indexed_owners.write().unwrap() = Arc::new(owners);
+    }
+
     pub fn load(&self, slot: Slot, pubkey: &Pubkey) -> Option<CachedAccount> {
         self.slot_cache(slot)
             .and_then(|slot_cache| slot_cache.get_cloned(pubkey))
@@ -322,4 +362,71 @@ pub mod tests {
         // If the cache is told the size limit is 0, it should return the one frozen slot
         assert_eq!(cache.find_older_frozen_slots(0), vec![inserted_slot]);
     }
+
+    #[test]
+    fn test_get_cloned_by_owner() {
+        let cache = AccountsCache::default();
+        let owner0 = Pubkey::new_unique();
+        let owner1 = Pubkey::new_unique();
+        cache.set_indexed_owners([owner0, owner1].iter().copied().collect());
+        let pubkey0 = Pubkey::new_unique();
+        let pubkey1 = Pubkey::new_unique();
+        let inserted_slot = 0;
+        cache.store(
+            inserted_slot,
+            &pubkey0,
+            AccountSharedData::new(1, 0, &owner0),
+            Some(&Hash::default()),
+        );
+        cache.store(
+            inserted_slot,
+            &pubkey1,
+            AccountSharedData::new(1, 0, &owner0),
+            Some(&Hash::default()),
+        );
+        // Reassigned within the slot, so only listed under its latest owner
+        cache.store(
+            inserted_slot,
+            &pubkey1,
+            AccountSharedData::new(2, 0, &owner1),
+            Some(&Hash::default()),
+        );
+
+        let slot_cache = cache.slot_cache(inserted_slot).unwrap();
+        let pubkeys = |slot_cache: &SlotCache, owner| {
+            slot_cache
+                .get_cloned_by_owner(owner)
+                .map(|cached_accounts| {
+                    cached_accounts
+                        .iter()
+                        .map(|cached_account| cached_account.pubkey())
+                        .collect::<Vec<_>>()
+                })
+        };
+        assert_eq!(pubkeys(&slot_cache, &owner0), Some(vec![pubkey0]));
+        assert_eq!(pubkeys(&slot_cache, &owner1), Some(vec![pubkey1]));
+        assert_eq!(pubkeys(&slot_cache, &Pubkey::new_unique()), None);
+
+        // Owners indexed later only apply to the slots created afterwards
+        let owner2 = Pubkey::new_unique();
+        cache.set_indexed_owners([owner2].iter().copied().collect());
+        let pubkey2 = Pubkey::new_unique();
+        let account2 = AccountSharedData::new(1, 0, &owner2);
+        cache.store(
+            inserted_slot,
+            &pubkey2,
+            account2.clone(),
+            Some(&Hash::default()),
+        );
+        cache.store(
+            inserted_slot + 1,
+            &pubkey2,
+            account2,
+            Some(&Hash::default()),
+        );
+        assert_eq!(pubkeys(&slot_cache, &owner2), None);
+        let next_slot_cache = cache.slot_cache(inserted_slot + 1).unwrap();
+        assert_eq!(pubkeys(&next_slot_cache, &owner2), Some(vec![pubkey2]));
+        assert_eq!(pubkeys(&next_slot_cache, &owner0), None);
+    }
 }
@@ -21,14 +21,16 @@
 use crate::{
     accounts_background_service::{DroppedSlotsSender, SendDroppedBankCallback},
     accounts_cache::{AccountsCache, CachedAccount, SlotCache},
-    accounts_hash::{AccountsHash, CalculateHashIntermediate, HashStats, PreviousPass},
+    accounts_hash::{
+        AccountsDeltaHashTree, AccountsHash, CalculateHashIntermediate, HashStats, PreviousPass,
+    },
     accounts_index::{
         AccountIndexGetResult, AccountSecondaryIndexes, AccountsIndex, AccountsIndexConfig,
         AccountsIndexRootsStats, IndexKey, IndexValue, IsCached, RefCount, ScanResult, SlotList,
         SlotSlice, ZeroLamport, ACCOUNTS_INDEX_CONFIG_FOR_BENCHMARKS,
         ACCOUNTS_INDEX_CONFIG_FOR_TESTING,
     },
-    accounts_update_notifier_interface::AccountsUpdateNotifier,
+    accounts_update_notifier_interface::{AccountsUpdateNotifier, AccountsWriteListener},
     ancestors::Ancestors,
     append_vec::{AppendVec, StoredAccountMeta, StoredMeta, StoredMetaWriteVersion},
     cache_hash_data::CacheHashData,
@@ -1050,6 +1052,9 @@ pub struct AccountsDb {
     /// AccountsDbPlugin accounts update notifier
     accounts_update_notifier: Option<AccountsUpdateNotifier>,
 
+    /// In-process listener told about runtime account writes
+    accounts_write_listener: RwLock<Option<AccountsWriteListener>>,
+
     filler_account_count: usize,
     pub filler_account_suffix: Option<Pubkey>,
 
@@ -1584,6 +1589,7 @@ impl AccountsDb {
             dirty_stores: DashMap::default(),
             zero_lamport_accounts_to_purge_after_full_snapshot: DashSet::default(),
             accounts_update_notifier: None,
+            accounts_write_listener: RwLock::default(),
             filler_account_count: 0,
             filler_account_suffix: None,
             num_hash_scan_passes,
@@ -3150,6 +3156,8 @@ impl AccountsDb {
             IndexKey::ProgramId(key) => key,
             IndexKey::SplTokenMint(key) => key,
             IndexKey::SplTokenOwner(key) => key,
+            IndexKey::StakeStaker(key) => key,
+            IndexKey::StakeWithdrawer(key) => key,
         };
         if !self.account_indexes.include_key(key) {
             // the requested key was not indexed in the secondary index, so do a normal scan
@@ -5808,9 +5816,8 @@ impl AccountsDb {
         self.uncleaned_pubkeys.insert(slot, dirty_pubkeys);
     }
 
-    pub fn get_accounts_delta_hash(&self, slot: Slot) -> Hash {
-        let mut scan = Measure::start("scan");
-
+    /// Returns the hashes of the latest versions of the accounts written in `slot`
+    fn get_slot_account_hashes(&self, slot: Slot) -> Vec<(Pubkey, Hash)> {
         let scan_result: ScanStorageResult<(Pubkey, Hash), DashMapVersionHash> = self
             .scan_account_storage(
                 slot,
@@ -5851,16 +5858,21 @@ impl AccountsDb {
                     }
                 },
             );
-        scan.stop();
-
-        let mut accumulate = Measure::start("accumulate");
-        let hashes: Vec<_> = match scan_result {
+        match scan_result {
             ScanStorageResult::Cached(cached_result) => cached_result,
             ScanStorageResult::Stored(stored_result) => stored_result
                 .into_iter()
                 .map(|(pubkey, (_latest_write_version, hash))| (pubkey, hash))
                 .collect(),
-        };
+        }
+    }
+
+    pub fn get_accounts_delta_hash(&self, slot: Slot) -> Hash {
+        let mut scan = Measure::start("scan");
+        let hashes = self.get_slot_account_hashes(slot);
+        scan.stop();
+
+        let mut accumulate = Measure::start("accumulate");
         let dirty_keys = hashes.iter().map(|(pubkey, _hash)| *pubkey).collect();
 
         let ret = AccountsHash::accumulate_account_hashes(hashes);
@@ -5882,6 +5894,13 @@ impl AccountsDb {
         ret
     }
 
+    /// The merkle tree of the hashes of the accounts written in `slot`, from which the proofs
+    /// of the accounts follow against the accounts delta hash computed from what is currently
+    /// stored for the slot
+    pub fn get_accounts_delta_hash_tree(&self, slot: Slot) -> AccountsDeltaHashTree {
+        AccountsDeltaHashTree::new(self.get_slot_account_hashes(slot))
+    }
+
     // previous_slot_entry_was_cached = true means we just need to assert that after this update is complete
     //  that there are no items we would have put in reclaims that are not cached
     fn update_index<T: ReadableAccount + Sync>(
@@ -6251,6 +6270,36 @@ impl AccountsDb {
         self.store(slot, accounts, self.caching_enabled);
     }
 
+    /// Accounts stored in `slot` that are owned by `owner`, looked up in the owner index of
+    /// the write cache. `None` if the slot is not in the cache or `owner` is not indexed in
+    /// it.
+    pub fn load_cached_by_owner(
+        &self,
+        slot: Slot,
+        owner: &Pubkey,
+    ) -> Option<Vec<(Pubkey, AccountSharedData)>> {
+        let slot_cache = self.accounts_cache.slot_cache(slot)?;
+        Some(
+            slot_cache
+                .get_cloned_by_owner(owner)?
+                .into_iter()
+                .map(|cached_account| (cached_account.pubkey(), cached_account.account.clone()))
+                .collect(),
+        )
+    }
+
+    /// Index the cached writes of `owners` by owner, from the next slot on. Only the owners
+    /// that are looked up are indexed, to keep the cost off every other write.
+    pub fn set_indexed_owners(&self, owners: HashSet<Pubkey>) {
+        self.accounts_cache.set_indexed_owners(owners);
+    }
+
+    /// Install the listener told about every account stored from now on, replacing any
+    /// previous one
+    pub fn set_accounts_write_listener(&self, listener: Option<AccountsWriteListener>) {
+        *self.accounts_write_listener.write().unwrap() = listener;
+    }
+
     /// Store the account update.
     /// only called by tests
     pub fn store_uncached(&self, slot: Slot, accounts: &[(&Pubkey, &AccountSharedData)]) {
@@ -6265,6 +6314,12 @@ impl AccountsDb {
         }
         self.assert_frozen_accounts(accounts);
 
+        if let Some(listener) = self.accounts_write_listener.read().unwrap().as_ref() {
+            for (pubkey, account) in accounts {
+                listener.notify_account_write(slot, pubkey, account);
+            }
+        }
+
         let mut stats = BankHashStats::default();
         let mut total_data = 0;
         accounts.iter().for_each(|(_pubkey, account)| {
@@ -7322,6 +7377,7 @@ pub mod tests {
         accounts_hash::MERKLE_FANOUT,
         accounts_index::RefCount,
         accounts_index::{tests::*, AccountSecondaryIndexesIncludeExclude},
+        accounts_update_notifier_interface::AccountsWriteListenerInterface,
         append_vec::{test_utils::TempFile, AccountMeta},
         inline_spl_token_v2_0,
     };
@@ -7960,6 +8016,41 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn test_accounts_write_listener() {
+        #[derive(Debug, Default)]
+        struct Listener(Mutex<Vec<(Slot, Pubkey)>>);
+        impl AccountsWriteListenerInterface for Listener {
+            fn notify_account_write(
+                &self,
+                slot: Slot,
+                pubkey: &Pubkey,
+                _account: &AccountSharedData,
+            ) {
+                self.0.lock().unwrap().push((slot, *pubkey));
+            }
+        }
+
+        let db = AccountsDb::new_single_for_tests_with_caching();
+        let key0 = solana_sdk::pubkey::new_rand();
+        let key1 = solana_sdk::pubkey::new_rand();
+        let account = AccountSharedData::new(1, 0, &Pubkey::default());
+
+        // Writes before the listener is installed are not reported
+        db.store_cached(0, &[(&key0, &account)]);
+        let listener = Arc::new(Listener::default());
+        db.set_accounts_write_listener(Some(listener.clone()));
+        db.store_cached(1, &[(&key0, &account), (&key1, &account)]);
+        db.store_uncached(2, &[(&key1, &account)]);
+        db.set_accounts_write_listener(None);
+        db.store_cached(3, &[(&key0, &account)]);
+
+        assert_eq!(
+            *listener.0.lock().unwrap(),
+            vec![(1, key0), (1, key1), (2, key1)]
+        );
+    }
+
     const COLLECT_ALL_UNSORTED_FALSE: bool = false;
 
     #[test]
@@ -10,6 +10,101 @@ use std::{convert::TryInto, sync::Mutex};
 pub const ZERO_RAW_LAMPORTS_SENTINEL: u64 = std::u64::MAX;
 pub const MERKLE_FANOUT: usize = 16;
 
+/// One level of the path from a hash to the root of a merkle tree: the hashes hashed along
+/// with the node on the path, and the position of the node among them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleProofLevel {
+    pub index: usize,
+    pub siblings: Vec<Hash>,
+}
+
+/// The hash of an account written in a slot, and its path to the accounts delta hash of the
+/// slot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountsDeltaHashProof {
+    pub account_hash: Hash,
+    pub accounts_delta_hash: Hash,
+    pub path: Vec<MerkleProofLevel>,
+}
+
+/// The merkle tree of the hashes of the accounts written in a slot, kept so that the proofs
+/// of several accounts don't each hash the whole slot again
+#[derive(Debug)]
+pub struct AccountsDeltaHashTree {
+    pubkeys: Vec<Pubkey>,
+    /// The levels of the tree, from the account hashes up to the level hashed into the root
+    levels: Vec<Vec<Hash>>,
+    root: Hash,
+}
+
+impl AccountsDeltaHashTree {
+    pub fn new(mut hashes: Vec<(Pubkey, Hash)>) -> Self {
+        AccountsHash::sort_hashes_by_pubkey(&mut hashes);
+        let (pubkeys, mut level): (Vec<_>, Vec<_>) = hashes.into_iter().unzip();
+        let mut levels = vec![];
+        while level.len() > MERKLE_FANOUT {
+            let next = level.chunks(MERKLE_FANOUT).map(hash_chunk).collect();
+            levels.push(level);
+            level = next;
+        }
+        let root = hash_chunk(&level);
+        levels.push(level);
+        Self {
+            pubkeys,
+            levels,
+            root,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.pubkeys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pubkeys.is_empty()
+    }
+
+    /// The accounts delta hash of the slot
+    pub fn root(&self) -> Hash {
+        self.root
+    }
+
+    /// Returns the proof of the account `pubkey`, or `None` if it wasn't written in the slot
+    pub fn proof(&self, pubkey: &Pubkey) -> Option<AccountsDeltaHashProof> {
+        let mut index = self.pubkeys.binary_search(pubkey).ok()?;
+        let account_hash = self.levels[0][index];
+        let path = self
+            .levels
+            .iter()
+            .map(|level| {
+                let start = index - index % MERKLE_FANOUT;
+                let end = std::cmp::min(start + MERKLE_FANOUT, level.len());
+                let mut siblings = level[start..end].to_vec();
+                siblings.remove(index - start);
+                let proof_level = MerkleProofLevel {
+                    index: index - start,
+                    siblings,
+                };
+                index /= MERKLE_FANOUT;
+                proof_level
+            })
+            .collect();
+        Some(AccountsDeltaHashProof {
+            account_hash,
+            accounts_delta_hash: self.root,
+            path,
+        })
+    }
+}
+
+fn hash_chunk(chunk: &[Hash]) -> Hash {
+    let mut hasher = Hasher::default();
+    for hash in chunk {
+        hasher.hash(hash.as_ref());
+    }
+    hasher.result()
+}
+
 #[derive(Default, Debug)]
 pub struct PreviousPass {
     pub reduced_hashes: Vec<Vec<Hash>>,
@@ -486,6 +581,69 @@ impl AccountsHash {
         Self::compute_merkle_root_loop(hashes, MERKLE_FANOUT, |i| i.1)
     }
 
+    /// Returns the path from `hashes[index]` to the root computed by
+    /// `compute_merkle_root_loop` over `hashes` with the same fanout
+    pub fn compute_merkle_proof(
+        hashes: Vec<Hash>,
+        mut index: usize,
+        fanout: usize,
+    ) -> Vec<MerkleProofLevel> {
+        assert!(index < hashes.len());
+        let mut level = hashes;
+        let mut path = vec![];
+        loop {
+            let start = index - index % fanout;
+            let end = std::cmp::min(start + fanout, level.len());
+            let mut siblings = level[start..end].to_vec();
+            siblings.remove(index - start);
+            path.push(MerkleProofLevel {
+                index: index - start,
+                siblings,
+            });
+            if level.len() <= fanout {
+                return path;
+            }
+            level = level
+                .chunks(fanout)
+                .map(|chunk| {
+                    let mut hasher = Hasher::default();
+                    for hash in chunk {
+                        hasher.hash(hash.as_ref());
+                    }
+                    hasher.result()
+                })
+                .collect();
+            index /= fanout;
+        }
+    }
+
+    /// Returns the root of the merkle tree that `path` leads to from `hash`
+    pub fn compute_merkle_root_from_proof(hash: Hash, path: &[MerkleProofLevel]) -> Hash {
+        path.iter().fold(hash, |hash, level| {
+            let (before, after) = level
+                .siblings
+                .split_at(std::cmp::min(level.index, level.siblings.len()));
+            let mut hasher = Hasher::default();
+            for sibling in before {
+                hasher.hash(sibling.as_ref());
+            }
+            hasher.hash(hash.as_ref());
+            for sibling in after {
+                hasher.hash(sibling.as_ref());
+            }
+            hasher.result()
+        })
+    }
+
+    /// Returns the proof of the account `pubkey` among the hashes of the accounts written in a
+    /// slot, or `None` if the account isn't among them
+    pub fn compute_accounts_delta_hash_proof(
+        hashes: Vec<(Pubkey, Hash)>,
+        pubkey: &Pubkey,
+    ) -> Option<AccountsDeltaHashProof> {
+        AccountsDeltaHashTree::new(hashes).proof(pubkey)
+    }
+
     pub fn sort_hashes_by_pubkey(hashes: &mut Vec<(Pubkey, Hash)>) {
         hashes.par_sort_unstable_by(|a, b| a.0.cmp(&b.0));
     }
@@ -1730,6 +1888,69 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_accountsdb_compute_merkle_proof() {
+        for fanout in [2, 3, MERKLE_FANOUT] {
+            for count in [
+                1,
+                2,
+                fanout,
+                fanout + 1,
+                fanout * fanout,
+                fanout * fanout + 3,
+            ] {
+                let hashes: Vec<_> = (0..count).map(|_| Hash::new_unique()).collect();
+                let root = AccountsHash::compute_merkle_root_recurse(hashes.clone(), fanout);
+                for index in 0..count {
+                    let path = AccountsHash::compute_merkle_proof(hashes.clone(), index, fanout);
+                    assert_eq!(
+                        AccountsHash::compute_merkle_root_from_proof(hashes[index], &path),
+                        root
+                    );
+                    assert_ne!(
+                        AccountsHash::compute_merkle_root_from_proof(Hash::new_unique(), &path),
+                        root
+                    );
+                }
+            }
+        }
+
+        let hashes: Vec<_> = (0..100)
+            .map(|_| (Pubkey::new_unique(), Hash::new_unique()))
+            .collect();
+        let proof =
+            AccountsHash::compute_accounts_delta_hash_proof(hashes.clone(), &hashes[42].0).unwrap();
+        assert_eq!(proof.account_hash, hashes[42].1);
+        assert_eq!(
+            proof.accounts_delta_hash,
+            AccountsHash::accumulate_account_hashes(hashes.clone())
+        );
+        assert!(
+            AccountsHash::compute_accounts_delta_hash_proof(hashes, &Pubkey::new_unique())
+                .is_none()
+        );
+
+        for count in [1, MERKLE_FANOUT, MERKLE_FANOUT * MERKLE_FANOUT + 3] {
+            let hashes: Vec<_> = (0..count)
+                .map(|_| (Pubkey::new_unique(), Hash::new_unique()))
+                .collect();
+            let tree = AccountsDeltaHashTree::new(hashes.clone());
+            assert_eq!(tree.len(), count);
+            assert_eq!(
+                tree.root(),
+                AccountsHash::accumulate_account_hashes(hashes.clone())
+            );
+            for (pubkey, hash) in hashes {
+                let proof = tree.proof(&pubkey).unwrap();
+                assert_eq!(proof.account_hash, hash);
+                assert_eq!(
+                    AccountsHash::compute_merkle_root_from_proof(hash, &proof.path),
+                    tree.root()
+                );
+            }
+        }
+    }
+
     #[test]
     fn test_accountsdb_compute_merkle_root() {
         solana_logger::setup();
@@ -4,7 +4,8 @@ use crate::{
     bucket_map_holder::{Age, BucketMapHolder},
     contains::Contains,
     in_mem_accounts_index::InMemAccountsIndex,
-    inline_spl_token_v2_0::{self, SPL_TOKEN_ACCOUNT_MINT_OFFSET, SPL_TOKEN_ACCOUNT_OWNER_OFFSET},
+    inline_spl_token_2022,
+    inline_spl_token_v2_0::{SPL_TOKEN_ACCOUNT_MINT_OFFSET, SPL_TOKEN_ACCOUNT_OWNER_OFFSET},
     pubkey_bins::PubkeyBinCalculator24,
     secondary_index::*,
 };
@@ -17,6 +18,7 @@ use solana_measure::measure::Measure;
 use solana_sdk::{
     clock::{BankId, Slot},
     pubkey::{Pubkey, PUBKEY_BYTES},
+    stake,
 };
 use std::{
     collections::{btree_map::BTreeMap, HashSet},
@@ -35,6 +37,10 @@ use std::{
 use thiserror::Error;
 
 pub const ITER_BATCH_SIZE: usize = 1000;
+// Offsets of the authorized staker and withdrawer in an initialized or delegated stake account:
+// after the `StakeState` variant tag and `Meta::rent_exempt_reserve`
+pub const STAKE_ACCOUNT_STAKER_OFFSET: usize = 12;
+pub const STAKE_ACCOUNT_WITHDRAWER_OFFSET: usize = STAKE_ACCOUNT_STAKER_OFFSET + PUBKEY_BYTES;
 pub const BINS_DEFAULT: usize = 8192;
 pub const BINS_FOR_TESTING: usize = 2; // we want > 1, but each bin is a few disk files with a disk based index, so fewer is better
 pub const BINS_FOR_BENCHMARKS: usize = 2;
@@ -88,6 +94,8 @@ pub enum IndexKey {
     ProgramId(Pubkey),
     SplTokenMint(Pubkey),
     SplTokenOwner(Pubkey),
+    StakeStaker(Pubkey),
+    StakeWithdrawer(Pubkey),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -95,6 +103,8 @@ pub enum AccountIndex {
     ProgramId,
     SplTokenMint,
     SplTokenOwner,
+    StakeStaker,
+    StakeWithdrawer,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -759,6 +769,8 @@ pub struct AccountsIndex<T: IndexValue> {
     program_id_index: SecondaryIndex<DashMapSecondaryIndexEntry>,
     spl_token_mint_index: SecondaryIndex<DashMapSecondaryIndexEntry>,
     spl_token_owner_index: SecondaryIndex<RwLockSecondaryIndexEntry>,
+    stake_staker_index: SecondaryIndex<RwLockSecondaryIndexEntry>,
+    stake_withdrawer_index: SecondaryIndex<RwLockSecondaryIndexEntry>,
     roots_tracker: RwLock<RootsTracker>,
     ongoing_scan_roots: RwLock<BTreeMap<Slot, u64>>,
     // Each scan has some latest slot `S` that is the tip of the fork the scan
@@ -795,6 +807,12 @@ impl<T: IndexValue> AccountsIndex<T> {
             spl_token_owner_index: SecondaryIndex::<RwLockSecondaryIndexEntry>::new(
                 "spl_token_owner_index_stats",
             ),
+            stake_staker_index: SecondaryIndex::<RwLockSecondaryIndexEntry>::new(
+                "stake_staker_index_stats",
+            ),
+            stake_withdrawer_index: SecondaryIndex::<RwLockSecondaryIndexEntry>::new(
+                "stake_withdrawer_index_stats",
+            ),
             roots_tracker: RwLock::<RootsTracker>::default(),
             ongoing_scan_roots: RwLock::<BTreeMap<Slot, u64>>::default(),
             removed_bank_ids: Mutex::<HashSet<BankId>>::default(),
@@ -1026,6 +1044,24 @@ impl<T: IndexValue> AccountsIndex<T> {
                     Some(max_root),
                 );
             }
+            ScanTypes::Indexed(IndexKey::StakeStaker(staker)) => {
+                self.do_scan_secondary_index(
+                    ancestors,
+                    func,
+                    &self.stake_staker_index,
+                    &staker,
+                    Some(max_root),
+                );
+            }
+            ScanTypes::Indexed(IndexKey::StakeWithdrawer(withdrawer)) => {
+                self.do_scan_secondary_index(
+                    ancestors,
+                    func,
+                    &self.stake_withdrawer_index,
+                    &withdrawer,
+                    Some(max_root),
+                );
+            }
         }
 
         {
@@ -1521,9 +1557,7 @@ impl<T: IndexValue> AccountsIndex<T> {
         // 2) When the fetch from storage occurs, it will return AccountSharedData::Default
         // (as persisted tombstone for snapshots). This will then ultimately be
         // filtered out by post-scan filters, like in `get_filtered_spl_token_accounts_by_owner()`.
-        if *account_owner == inline_spl_token_v2_0::id()
-            && account_data.len() == inline_spl_token_v2_0::state::Account::get_packed_len()
-        {
+        if inline_spl_token_2022::is_spl_token_account(account_owner, account_data) {
             if account_indexes.contains(&AccountIndex::SplTokenOwner) {
                 let owner_key = Pubkey::new(
                     &account_data[SPL_TOKEN_ACCOUNT_OWNER_OFFSET
@@ -1544,6 +1578,32 @@ impl<T: IndexValue> AccountsIndex<T> {
                 }
             }
         }
+
+        // Only initialized and delegated stake accounts, tagged 1 and 2, have authorities
+        if *account_owner == stake::program::id()
+            && account_data.len() >= STAKE_ACCOUNT_WITHDRAWER_OFFSET + PUBKEY_BYTES
+            && matches!(account_data[..4], [1, 0, 0, 0] | [2, 0, 0, 0])
+        {
+            if account_indexes.contains(&AccountIndex::StakeStaker) {
+                let staker = Pubkey::new(
+                    &account_data
+                        [STAKE_ACCOUNT_STAKER_OFFSET..STAKE_ACCOUNT_STAKER_OFFSET + PUBKEY_BYTES],
+                );
+                if account_indexes.include_key(&staker) {
+                    self.stake_staker_index.insert(&staker, pubkey);
+                }
+            }
+
+            if account_indexes.contains(&AccountIndex::StakeWithdrawer) {
+                let withdrawer = Pubkey::new(
+                    &account_data[STAKE_ACCOUNT_WITHDRAWER_OFFSET
+                        ..STAKE_ACCOUNT_WITHDRAWER_OFFSET + PUBKEY_BYTES],
+                );
+                if account_indexes.include_key(&withdrawer) {
+                    self.stake_withdrawer_index.insert(&withdrawer, pubkey);
+                }
+            }
+        }
     }
 
     fn get_account_maps_write_lock(&self, pubkey: &Pubkey) -> AccountMapsWriteLock<T> {
@@ -1708,6 +1768,14 @@ impl<T: IndexValue> AccountsIndex<T> {
         if account_indexes.contains(&AccountIndex::SplTokenMint) {
             self.spl_token_mint_index.remove_by_inner_key(inner_key);
         }
+
+        if account_indexes.contains(&AccountIndex::StakeStaker) {
+            self.stake_staker_index.remove_by_inner_key(inner_key);
+        }
+
+        if account_indexes.contains(&AccountIndex::StakeWithdrawer) {
+            self.stake_withdrawer_index.remove_by_inner_key(inner_key);
+        }
     }
 
     fn purge_older_root_entries(
@@ -1933,6 +2001,7 @@ impl<T: IndexValue> AccountsIndex<T> {
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::inline_spl_token_v2_0;
     use solana_sdk::signature::{Keypair, Signer};
     use std::ops::RangeInclusive;
 
@@ -3941,6 +4010,121 @@ pub mod tests {
         assert!(secondary_index.reverse_index.is_empty());
     }
 
+    #[test]
+    fn test_token_2022_secondary_indexes() {
+        let index = AccountsIndex::<bool>::default_for_tests();
+        let secondary_indexes = spl_token_owner_index_enabled();
+        let account_key = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let mut account_data = vec![0; inline_spl_token_2022::ACCOUNT_TYPE_OFFSET + 10];
+        account_data[SPL_TOKEN_ACCOUNT_OWNER_OFFSET..SPL_TOKEN_ACCOUNT_OWNER_OFFSET + PUBKEY_BYTES]
+            .clone_from_slice(&owner.to_bytes());
+
+        // Extended accounts of the legacy token program aren't token accounts
+        account_data[inline_spl_token_2022::ACCOUNT_TYPE_OFFSET] =
+            inline_spl_token_2022::ACCOUNTTYPE_ACCOUNT;
+        index.update_secondary_indexes(
+            &account_key,
+            &inline_spl_token_v2_0::id(),
+            &account_data,
+            &secondary_indexes,
+        );
+        assert!(index.spl_token_owner_index.index.is_empty());
+
+        // Neither are extended mints
+        account_data[inline_spl_token_2022::ACCOUNT_TYPE_OFFSET] = 1;
+        index.update_secondary_indexes(
+            &account_key,
+            &inline_spl_token_2022::id(),
+            &account_data,
+            &secondary_indexes,
+        );
+        assert!(index.spl_token_owner_index.index.is_empty());
+
+        account_data[inline_spl_token_2022::ACCOUNT_TYPE_OFFSET] =
+            inline_spl_token_2022::ACCOUNTTYPE_ACCOUNT;
+        index.update_secondary_indexes(
+            &account_key,
+            &inline_spl_token_2022::id(),
+            &account_data,
+            &secondary_indexes,
+        );
+        check_secondary_index_mapping_correct(&index.spl_token_owner_index, &[owner], &account_key);
+
+        // Token-2022 accounts without extensions have the legacy layout
+        let index = AccountsIndex::<bool>::default_for_tests();
+        index.update_secondary_indexes(
+            &account_key,
+            &inline_spl_token_2022::id(),
+            &account_data[..inline_spl_token_2022::ACCOUNT_TYPE_OFFSET],
+            &secondary_indexes,
+        );
+        check_secondary_index_mapping_correct(&index.spl_token_owner_index, &[owner], &account_key);
+    }
+
+    #[test]
+    fn test_stake_authority_secondary_indexes() {
+        let index = AccountsIndex::<bool>::default_for_tests();
+        let secondary_indexes = AccountSecondaryIndexes {
+            indexes: [AccountIndex::StakeStaker, AccountIndex::StakeWithdrawer]
+                .iter()
+                .cloned()
+                .collect(),
+            keys: None,
+        };
+        let account_key = Pubkey::new_unique();
+        let staker = Pubkey::new_unique();
+        let withdrawer = Pubkey::new_unique();
+        let mut account_data = vec![0; 200];
+        account_data[STAKE_ACCOUNT_STAKER_OFFSET..STAKE_ACCOUNT_WITHDRAWER_OFFSET]
+            .clone_from_slice(&staker.to_bytes());
+        account_data
+            [STAKE_ACCOUNT_WITHDRAWER_OFFSET..STAKE_ACCOUNT_WITHDRAWER_OFFSET + PUBKEY_BYTES]
+            .clone_from_slice(&withdrawer.to_bytes());
+
+        // Uninitialized stake accounts have no authorities
+        index.update_secondary_indexes(
+            &account_key,
+            &stake::program::id(),
+            &account_data,
+            &secondary_indexes,
+        );
+        assert!(index.stake_staker_index.index.is_empty());
+        assert!(index.stake_withdrawer_index.index.is_empty());
+
+        // Wrong program id
+        account_data[0] = 2;
+        index.update_secondary_indexes(
+            &account_key,
+            &Pubkey::default(),
+            &account_data,
+            &secondary_indexes,
+        );
+        assert!(index.stake_staker_index.index.is_empty());
+
+        index.upsert(
+            0,
+            &account_key,
+            &stake::program::id(),
+            &account_data,
+            &secondary_indexes,
+            true,
+            &mut vec![],
+            UPSERT_PREVIOUS_SLOT_ENTRY_WAS_CACHED_FALSE,
+        );
+        check_secondary_index_mapping_correct(&index.stake_staker_index, &[staker], &account_key);
+        check_secondary_index_mapping_correct(
+            &index.stake_withdrawer_index,
+            &[withdrawer],
+            &account_key,
+        );
+
+        index.slot_list_mut(&account_key, |slot_list| slot_list.clear());
+        index.handle_dead_keys(&[&account_key], &secondary_indexes);
+        assert!(index.stake_staker_index.index.is_empty());
+        assert!(index.stake_withdrawer_index.reverse_index.is_empty());
+    }
+
     #[test]
     fn test_dashmap_secondary_index() {
         let (key_start, key_end, secondary_indexes) = create_dashmap_secondary_index_state();
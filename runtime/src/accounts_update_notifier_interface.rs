@@ -1,6 +1,6 @@
 use {
     crate::append_vec::{StoredAccountMeta, StoredMeta},
-    solana_sdk::{account::AccountSharedData, clock::Slot},
+    solana_sdk::{account::AccountSharedData, clock::Slot, pubkey::Pubkey},
     std::sync::{Arc, RwLock},
 };
 
@@ -26,3 +26,13 @@ pub trait AccountsUpdateNotifierInterface: std::fmt::Debug {
 }
 
 pub type AccountsUpdateNotifier = Arc<RwLock<dyn AccountsUpdateNotifierInterface + Sync + Send>>;
+
+/// In-process counterpart of `AccountsUpdateNotifierInterface` for consumers that only need
+/// to learn which accounts changed, such as RPC account subscriptions. Unlike plugin
+/// notifiers it can be installed on a running AccountsDb.
+pub trait AccountsWriteListenerInterface: std::fmt::Debug {
+    /// Notified when an account is stored at runtime, from the thread storing it
+    fn notify_account_write(&self, slot: Slot, pubkey: &Pubkey, account: &AccountSharedData);
+}
+
+pub type AccountsWriteListener = Arc<dyn AccountsWriteListenerInterface + Sync + Send>;
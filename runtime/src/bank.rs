@@ -34,11 +34,13 @@
 //! on behalf of the caller, and a low-level API for when they have
 //! already been signed and verified.
 use crate::{
+    account_overrides::AccountOverrides,
     accounts::{AccountAddressFilter, Accounts, TransactionAccounts, TransactionLoadResult},
     accounts_db::{
-        AccountShrinkThreshold, AccountsDbConfig, ErrorCounters, SnapshotStorages,
+        AccountShrinkThreshold, AccountsDbConfig, ErrorCounters, LoadHint, SnapshotStorages,
         ACCOUNTS_DB_CONFIG_FOR_BENCHMARKS, ACCOUNTS_DB_CONFIG_FOR_TESTING,
     },
+    accounts_hash::{AccountsDeltaHashProof, AccountsDeltaHashTree},
     accounts_index::{AccountSecondaryIndexes, IndexKey, ScanResult},
     accounts_update_notifier_interface::AccountsUpdateNotifier,
     ancestors::{Ancestors, AncestorsForSerialization},
@@ -492,6 +494,7 @@ pub struct TransactionSimulationResult {
     pub logs: TransactionLogMessages,
     pub post_simulation_accounts: Vec<(Pubkey, AccountSharedData)>,
     pub units_consumed: u64,
+    pub inner_instructions: Option<InnerInstructionsList>,
 }
 pub struct TransactionBalancesSet {
     pub pre_balances: TransactionBalances,
@@ -514,6 +517,50 @@ impl TransactionBalancesSet {
 }
 pub type TransactionBalances = Vec<Vec<u64>>;
 
+/// Everything needed to recompute a bank hash from the hash of an account written in the
+/// bank, and so to check that the account was written as claimed without trusting the node
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountProof {
+    pub accounts_delta_hash_proof: AccountsDeltaHashProof,
+    pub parent_hash: Hash,
+    pub signature_count: u64,
+    pub last_blockhash: Hash,
+    pub bank_hash: Hash,
+}
+
+/// The merkle tree of the accounts written in a frozen bank along with the rest of its bank
+/// hash, from which the proofs of every account written in the bank follow
+#[derive(Debug)]
+pub struct AccountProofs {
+    tree: AccountsDeltaHashTree,
+    parent_hash: Hash,
+    signature_count: u64,
+    last_blockhash: Hash,
+    bank_hash: Hash,
+}
+
+impl AccountProofs {
+    /// Returns the proof that `pubkey` was written in the bank, or `None` if it wasn't
+    pub fn get(&self, pubkey: &Pubkey) -> Option<AccountProof> {
+        Some(AccountProof {
+            accounts_delta_hash_proof: self.tree.proof(pubkey)?,
+            parent_hash: self.parent_hash,
+            signature_count: self.signature_count,
+            last_blockhash: self.last_blockhash,
+            bank_hash: self.bank_hash,
+        })
+    }
+
+    /// Number of accounts written in the bank
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+}
+
 /// An ordered list of instructions that were invoked during a transaction instruction
 pub type InnerInstructions = Vec<CompiledInstruction>;
 
@@ -3308,6 +3355,46 @@ impl Bank {
     pub fn simulate_transaction(
         &self,
         transaction: SanitizedTransaction,
+        enable_cpi_recording: bool,
+    ) -> TransactionSimulationResult {
+        self.simulate_transaction_with_overrides(transaction, enable_cpi_recording, None)
+    }
+
+    /// Run transactions in order against a frozen bank without committing the results, each
+    /// transaction seeing the accounts as left by the ones before it. Simulation stops at the
+    /// first transaction that fails, so only the results up to and including it are returned.
+    pub fn simulate_bundle(
+        &self,
+        transactions: Vec<SanitizedTransaction>,
+        enable_cpi_recording: bool,
+    ) -> Vec<TransactionSimulationResult> {
+        let mut account_overrides = AccountOverrides::default();
+        let mut results = Vec::with_capacity(transactions.len());
+        for transaction in transactions {
+            let result = self.simulate_transaction_with_overrides(
+                transaction,
+                enable_cpi_recording,
+                Some(&account_overrides),
+            );
+            let failed = result.result.is_err();
+            if !failed {
+                for (pubkey, account) in &result.post_simulation_accounts {
+                    account_overrides.set_account(pubkey, account.clone());
+                }
+            }
+            results.push(result);
+            if failed {
+                break;
+            }
+        }
+        results
+    }
+
+    fn simulate_transaction_with_overrides(
+        &self,
+        transaction: SanitizedTransaction,
+        enable_cpi_recording: bool,
+        account_overrides: Option<&AccountOverrides>,
     ) -> TransactionSimulationResult {
         assert!(self.is_frozen(), "simulation bank must be frozen");
 
@@ -3318,7 +3405,7 @@ impl Bank {
         let (
             loaded_transactions,
             executed,
-            _inner_instructions,
+            inner_instructions,
             logs,
             _retryable_transactions,
             _transaction_count,
@@ -3329,12 +3416,14 @@ impl Bank {
             // for processing. During forwarding, the transaction could expire if the
             // delay is not accounted for.
             MAX_PROCESSING_AGE - MAX_TRANSACTION_FORWARDING_DELAY,
-            false,
+            enable_cpi_recording,
             true,
             &mut timings,
+            account_overrides,
         );
 
         let result = executed[0].0.clone().map(|_| ());
+        let inner_instructions = inner_instructions.into_iter().next().flatten();
         let logs = logs.get(0).cloned().flatten().unwrap_or_default();
         let post_simulation_accounts = loaded_transactions
             .into_iter()
@@ -3366,6 +3455,7 @@ impl Bank {
             logs,
             post_simulation_accounts,
             units_consumed,
+            inner_instructions,
         }
     }
 
@@ -3726,6 +3816,7 @@ impl Bank {
         enable_cpi_recording: bool,
         enable_log_recording: bool,
         timings: &mut ExecuteTimings,
+        account_overrides: Option<&AccountOverrides>,
     ) -> (
         Vec<TransactionLoadResult>,
         Vec<TransactionExecutionResult>,
@@ -3772,6 +3863,7 @@ impl Bank {
             &mut error_counters,
             &self.rent_collector,
             &self.feature_set,
+            account_overrides,
         );
         load_time.stop();
 
@@ -4842,6 +4934,7 @@ impl Bank {
             enable_cpi_recording,
             enable_log_recording,
             timings,
+            None,
         );
 
         let results = self.commit_transactions(
@@ -5169,6 +5262,18 @@ impl Bank {
         self.load_slow(&self.ancestors, pubkey)
     }
 
+    /// Like `get_account_modified_slot`, but an account whose lamports dropped to zero is
+    /// returned along with the slot in which that happened instead of as missing
+    pub fn get_account_modified_slot_including_zero_lamport(
+        &self,
+        pubkey: &Pubkey,
+    ) -> Option<(AccountSharedData, Slot)> {
+        self.rc
+            .accounts
+            .accounts_db
+            .load(&self.ancestors, pubkey, LoadHint::Unspecified)
+    }
+
     fn load_slow(
         &self,
         ancestors: &Ancestors,
@@ -5382,15 +5487,12 @@ impl Bank {
     fn hash_internal_state(&self) -> Hash {
         // If there are no accounts, return the hash of the previous state and the latest blockhash
         let accounts_delta_hash = self.rc.accounts.bank_hash_info_at(self.slot());
-        let mut signature_count_buf = [0u8; 8];
-        LittleEndian::write_u64(&mut signature_count_buf[..], self.signature_count() as u64);
-
-        let mut hash = hashv(&[
-            self.parent_hash.as_ref(),
-            accounts_delta_hash.hash.as_ref(),
-            &signature_count_buf,
-            self.last_blockhash().as_ref(),
-        ]);
+        let mut hash = Self::hash_bank_state(
+            &self.parent_hash,
+            &accounts_delta_hash.hash,
+            self.signature_count(),
+            &self.last_blockhash(),
+        );
 
         if let Some(buf) = self
             .hard_forks
@@ -5420,6 +5522,61 @@ impl Bank {
         hash
     }
 
+    /// The bank hash of a slot, other than at hard forks
+    pub fn hash_bank_state(
+        parent_hash: &Hash,
+        accounts_delta_hash: &Hash,
+        signature_count: u64,
+        last_blockhash: &Hash,
+    ) -> Hash {
+        let mut signature_count_buf = [0u8; 8];
+        LittleEndian::write_u64(&mut signature_count_buf[..], signature_count);
+        hashv(&[
+            parent_hash.as_ref(),
+            accounts_delta_hash.as_ref(),
+            &signature_count_buf,
+            last_blockhash.as_ref(),
+        ])
+    }
+
+    /// Returns the proof that `pubkey` was written in this bank, or `None` if it wasn't, or
+    /// if `get_account_proofs` returns `None`
+    pub fn get_account_proof(&self, pubkey: &Pubkey) -> Option<AccountProof> {
+        self.get_account_proofs()?.get(pubkey)
+    }
+
+    /// Hashes the accounts written in this bank once, for the proofs of any of them. Returns
+    /// `None` if no account was written, if the bank isn't frozen yet, or if its hash doesn't
+    /// follow from its accounts alone, as at hard forks or once the slot's storage has been
+    /// shrunk.
+    pub fn get_account_proofs(&self) -> Option<AccountProofs> {
+        if !self.is_frozen() {
+            return None;
+        }
+        let tree = self
+            .rc
+            .accounts
+            .accounts_db
+            .get_accounts_delta_hash_tree(self.slot());
+        if tree.is_empty() {
+            return None;
+        }
+        let last_blockhash = self.last_blockhash();
+        let bank_hash = Self::hash_bank_state(
+            &self.parent_hash,
+            &tree.root(),
+            self.signature_count(),
+            &last_blockhash,
+        );
+        (bank_hash == self.hash()).then(|| AccountProofs {
+            tree,
+            parent_hash: self.parent_hash,
+            signature_count: self.signature_count(),
+            last_blockhash,
+            bank_hash,
+        })
+    }
+
     /// Recalculate the hash_internal_state from the account stores. Would be used to verify a
     /// snapshot.
     /// Only called from startup or test code.
@@ -6335,7 +6492,7 @@ pub fn goto_end_of_slot(bank: &mut Bank) {
     }
 }
 
-fn is_simple_vote_transaction(transaction: &SanitizedTransaction) -> bool {
+pub fn is_simple_vote_transaction(transaction: &SanitizedTransaction) -> bool {
     if transaction.message().instructions().len() == 1 {
         let (program_pubkey, instruction) = transaction
             .message()
@@ -6360,7 +6517,8 @@ pub(crate) mod tests {
     use super::*;
     use crate::{
         accounts_background_service::{AbsRequestHandler, SendDroppedBankCallback},
-        accounts_db::DEFAULT_ACCOUNTS_SHRINK_RATIO,
+        accounts_db::{AccountsDb, DEFAULT_ACCOUNTS_SHRINK_RATIO},
+        accounts_hash::AccountsHash,
         accounts_index::{AccountIndex, AccountSecondaryIndexes, ScanError, ITER_BATCH_SIZE},
         ancestors::Ancestors,
         genesis_utils::{
@@ -8808,6 +8966,51 @@ pub(crate) mod tests {
         assert_eq!(bank.get_balance(&pubkey), 500);
     }
 
+    #[test]
+    fn test_simulate_bundle() {
+        let (genesis_config, mint_keypair) = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        bank.freeze();
+        let blockhash = bank.last_blockhash();
+        let alice = Keypair::new();
+        let bob = solana_sdk::pubkey::new_rand();
+        let transfer = |from: &Keypair, to: &Pubkey, lamports| {
+            SanitizedTransaction::from_transaction_for_tests(system_transaction::transfer(
+                from, to, lamports, blockhash,
+            ))
+        };
+
+        // Alice can only pay Bob out of what the first transaction gave her
+        let results = bank.simulate_bundle(
+            vec![
+                transfer(&mint_keypair, &alice.pubkey(), 1_000),
+                transfer(&alice, &bob, 500),
+            ],
+            false,
+        );
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|result| result.result.is_ok()));
+        let (_, bob_account) = results[1]
+            .post_simulation_accounts
+            .iter()
+            .find(|(pubkey, _)| pubkey == &bob)
+            .unwrap();
+        assert_eq!(bob_account.lamports(), 500);
+        assert_eq!(bank.get_balance(&alice.pubkey()), 0);
+        assert_eq!(bank.get_balance(&bob), 0);
+
+        // Simulation stops at the first failure
+        let results = bank.simulate_bundle(
+            vec![
+                transfer(&alice, &bob, 500),
+                transfer(&mint_keypair, &alice.pubkey(), 1_000),
+            ],
+            false,
+        );
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].result, Err(TransactionError::AccountNotFound));
+    }
+
     #[test]
     fn test_transfer_to_sysvar() {
         solana_logger::setup();
@@ -9471,6 +9674,42 @@ pub(crate) mod tests {
         assert!(bank2.verify_bank_hash(true));
     }
 
+    #[test]
+    fn test_get_account_proof() {
+        let (genesis_config, mint_keypair) = create_genesis_config(2_000);
+        let bank0 = Arc::new(Bank::new_for_tests(&genesis_config));
+        let bank1 = new_from_parent(&bank0);
+        let pubkey = solana_sdk::pubkey::new_rand();
+        bank1.transfer(1_000, &mint_keypair, &pubkey).unwrap();
+        assert_eq!(bank1.get_account_proof(&pubkey), None);
+
+        bank1.freeze();
+        let proof = bank1.get_account_proof(&pubkey).unwrap();
+        let (account, slot) = bank1.get_account_modified_slot(&pubkey).unwrap();
+        assert_eq!(slot, bank1.slot());
+        let account_hash = AccountsDb::hash_account(slot, &account, &pubkey);
+        assert_eq!(proof.accounts_delta_hash_proof.account_hash, account_hash);
+        let accounts_delta_hash = AccountsHash::compute_merkle_root_from_proof(
+            account_hash,
+            &proof.accounts_delta_hash_proof.path,
+        );
+        assert_eq!(proof.parent_hash, bank0.hash());
+        assert_eq!(
+            Bank::hash_bank_state(
+                &proof.parent_hash,
+                &accounts_delta_hash,
+                proof.signature_count,
+                &proof.last_blockhash,
+            ),
+            bank1.hash()
+        );
+        assert_eq!(proof.bank_hash, bank1.hash());
+        assert_eq!(
+            bank1.get_account_proof(&solana_sdk::pubkey::new_rand()),
+            None
+        );
+    }
+
     #[test]
     fn test_bank_hash_internal_state_verify() {
         solana_logger::setup();
@@ -172,12 +172,14 @@ impl BankForks {
         self[self.highest_slot()].clone()
     }
 
+    /// Set a new root and prune the banks that are no longer on rooted paths. Returns the
+    /// pruned banks that were on abandoned forks, i.e. excluding ancestors of the new root.
     pub fn set_root(
         &mut self,
         root: Slot,
         accounts_background_request_sender: &AbsRequestSender,
         highest_confirmed_root: Option<Slot>,
-    ) {
+    ) -> Vec<Arc<Bank>> {
         let old_epoch = self.root_bank().epoch();
         self.root = root;
         let set_root_start = Instant::now();
@@ -248,7 +250,7 @@ impl BankForks {
             root_bank.squash();
         }
         let new_tx_count = root_bank.transaction_count();
-        self.prune_non_rooted(root, highest_confirmed_root);
+        let abandoned_banks = self.prune_non_rooted(root, highest_confirmed_root);
 
         inc_new_counter_info!(
             "bank-forks_set_root_ms",
@@ -258,6 +260,7 @@ impl BankForks {
             "bank-forks_set_root_tx_count",
             (new_tx_count - root_tx_count) as usize
         );
+        abandoned_banks
     }
 
     pub fn root(&self) -> Slot {
@@ -314,7 +317,13 @@ impl BankForks {
     /// i.e. the cluster-confirmed root.  This commitment is stronger than the local node's root.
     /// So (A) and (B) are kept to facilitate RPC at different commitment levels.  Everything below
     /// the highest confirmed root can be pruned.
-    fn prune_non_rooted(&mut self, root: Slot, highest_confirmed_root: Option<Slot>) {
+    ///
+    /// Returns the pruned banks that are not ancestors of the root, i.e. (C) and (E) above.
+    fn prune_non_rooted(
+        &mut self,
+        root: Slot,
+        highest_confirmed_root: Option<Slot>,
+    ) -> Vec<Arc<Bank>> {
         let highest_confirmed_root = highest_confirmed_root.unwrap_or(root);
         let prune_slots: Vec<_> = self
             .banks
@@ -329,13 +338,19 @@ impl BankForks {
                 !keep
             })
             .collect();
-        for slot in prune_slots {
-            self.remove(slot);
-        }
+        let abandoned_banks = prune_slots
+            .into_iter()
+            .filter_map(|slot| {
+                let is_rooted = self.descendants[&slot].contains(&root);
+                let bank = self.remove(slot)?;
+                (!is_rooted).then(|| bank)
+            })
+            .collect();
         datapoint_debug!(
             "bank_forks_purge_non_root",
             ("num_banks_retained", self.banks.len(), i64),
         );
+        abandoned_banks
     }
 
     pub fn set_snapshot_config(&mut self, snapshot_config: Option<SnapshotConfig>) {
@@ -550,11 +565,15 @@ mod tests {
                 (4, vec![]),
             ])
         );
-        bank_forks.set_root(
+        let abandoned_banks = bank_forks.set_root(
             2,
             &AbsRequestSender::default(),
             None, // highest confirmed root
         );
+        let mut abandoned_slots: Vec<_> = abandoned_banks.iter().map(|bank| bank.slot()).collect();
+        abandoned_slots.sort_unstable();
+        // 0 and 1 are pruned as rooted ancestors, not as abandoned forks
+        assert_eq!(abandoned_slots, vec![3, 4]);
         banks[2].squash();
         assert_eq!(bank_forks.ancestors(), make_hash_map(vec![(2, vec![]),]));
         assert_eq!(
@@ -0,0 +1,33 @@
+// Partial SPL Token-2022 declarations inlined to avoid an external dependency on the spl-token-2022 crate
+use {crate::inline_spl_token_v2_0, solana_sdk::pubkey::Pubkey};
+
+solana_sdk::declare_id!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+/*
+    Token-2022 accounts and mints with extensions are padded to at least the length of an
+    spl_token::state::Account, followed by an AccountType byte and the extensions. The base
+    account keeps the spl_token layout, so the mint and owner offsets are shared.
+*/
+pub const ACCOUNT_TYPE_OFFSET: usize = 165;
+pub const ACCOUNTTYPE_ACCOUNT: u8 = 2;
+// spl_token::state::Multisig, which has no AccountType
+const MULTISIG_LEN: usize = 355;
+
+pub fn is_known_spl_token_id(program_id: &Pubkey) -> bool {
+    *program_id == inline_spl_token_v2_0::id() || *program_id == id()
+}
+
+/// Whether the data of an account owned by `account_owner` is that of a token account, with or
+/// without extensions
+pub fn is_spl_token_account(account_owner: &Pubkey, account_data: &[u8]) -> bool {
+    let base_len = inline_spl_token_v2_0::state::Account::get_packed_len();
+    if *account_owner == inline_spl_token_v2_0::id() {
+        account_data.len() == base_len
+    } else if *account_owner == id() {
+        account_data.len() == base_len
+            || (account_data.len() != MULTISIG_LEN
+                && account_data.get(ACCOUNT_TYPE_OFFSET) == Some(&ACCOUNTTYPE_ACCOUNT))
+    } else {
+        false
+    }
+}
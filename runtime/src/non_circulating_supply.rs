@@ -5,30 +5,154 @@ use {
     },
     log::*,
     solana_sdk::{
-        account::ReadableAccount,
+        account::{AccountSharedData, ReadableAccount},
         pubkey::Pubkey,
         stake::{self, state::StakeState},
     },
     solana_stake_program::stake_state,
-    std::{collections::HashSet, sync::Arc},
+    std::{
+        collections::{hash_map::Entry, HashMap},
+        sync::Arc,
+    },
 };
 
 pub struct NonCirculatingSupply {
     pub lamports: u64,
     pub accounts: Vec<Pubkey>,
+    pub breakdown: NonCirculatingBreakdown,
+}
+
+/// Changes to which accounts are considered non-circulating
+#[derive(Debug, Default, Clone)]
+pub struct NonCirculatingSupplyConfig {
+    /// Ignore the built-in account list and stake lockups
+    pub exclude_defaults: bool,
+    /// More accounts to consider non-circulating
+    pub accounts: Vec<Pubkey>,
+    /// Consider all accounts owned by these programs non-circulating
+    pub owners: Vec<Pubkey>,
+}
+
+/// Non-circulating lamports by the reason the accounts holding them are non-circulating. An
+/// account matching several reasons is counted once, under the first one listed here.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct NonCirculatingBreakdown {
+    /// Accounts of the built-in list
+    pub listed: u64,
+    /// Stake accounts with a lockup in force
+    pub locked_stake: u64,
+    /// Stake accounts withdrawable by an autostake authority
+    pub autostaked: u64,
+    /// Accounts of `NonCirculatingSupplyConfig::accounts`
+    pub accounts: u64,
+    /// Accounts owned by a program of `NonCirculatingSupplyConfig::owners`
+    pub owners: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum NonCirculatingReason {
+    Listed,
+    LockedStake,
+    Autostaked,
+    Accounts,
+    Owners,
+}
+
+impl NonCirculatingBreakdown {
+    fn add(&mut self, reason: NonCirculatingReason, lamports: u64) {
+        let total = match reason {
+            NonCirculatingReason::Listed => &mut self.listed,
+            NonCirculatingReason::LockedStake => &mut self.locked_stake,
+            NonCirculatingReason::Autostaked => &mut self.autostaked,
+            NonCirculatingReason::Accounts => &mut self.accounts,
+            NonCirculatingReason::Owners => &mut self.owners,
+        };
+        *total += lamports;
+    }
 }
 
 pub fn calculate_non_circulating_supply(bank: &Arc<Bank>) -> ScanResult<NonCirculatingSupply> {
+    calculate_non_circulating_supply_with_config(bank, &NonCirculatingSupplyConfig::default())
+}
+
+pub fn calculate_non_circulating_supply_with_config(
+    bank: &Arc<Bank>,
+    config: &NonCirculatingSupplyConfig,
+) -> ScanResult<NonCirculatingSupply> {
     debug!("Updating Bank supply, epoch: {}", bank.epoch());
-    let mut non_circulating_accounts_set: HashSet<Pubkey> = HashSet::new();
+    let mut non_circulating_accounts_map: HashMap<Pubkey, NonCirculatingReason> = HashMap::new();
+
+    if !config.exclude_defaults {
+        for key in non_circulating_accounts() {
+            non_circulating_accounts_map.insert(key, NonCirculatingReason::Listed);
+        }
+        let withdraw_authority_list = withdraw_authority();
 
-    for key in non_circulating_accounts() {
-        non_circulating_accounts_set.insert(key);
+        let clock = bank.clock();
+        let stake_accounts = get_program_accounts(bank, &stake::program::id())?;
+
+        for (pubkey, account) in stake_accounts.iter() {
+            let meta = match stake_state::from(account).unwrap_or_default() {
+                StakeState::Initialized(meta) => meta,
+                StakeState::Stake(meta, _stake) => meta,
+                _ => continue,
+            };
+            let reason = if meta.lockup.is_in_force(&clock, None) {
+                NonCirculatingReason::LockedStake
+            } else if withdraw_authority_list.contains(&meta.authorized.withdrawer) {
+                NonCirculatingReason::Autostaked
+            } else {
+                continue;
+            };
+            non_circulating_accounts_map
+                .entry(*pubkey)
+                .or_insert(reason);
+        }
     }
-    let withdraw_authority_list = withdraw_authority();
 
-    let clock = bank.clock();
-    let stake_accounts = if bank
+    for key in &config.accounts {
+        non_circulating_accounts_map
+            .entry(*key)
+            .or_insert(NonCirculatingReason::Accounts);
+    }
+    let mut owned_lamports = HashMap::new();
+    for owner in &config.owners {
+        for (pubkey, account) in get_program_accounts(bank, owner)? {
+            if let Entry::Vacant(entry) = non_circulating_accounts_map.entry(pubkey) {
+                entry.insert(NonCirculatingReason::Owners);
+                owned_lamports.insert(pubkey, account.lamports());
+            }
+        }
+    }
+
+    let mut breakdown = NonCirculatingBreakdown::default();
+    for (pubkey, reason) in &non_circulating_accounts_map {
+        let lamports = owned_lamports
+            .get(pubkey)
+            .copied()
+            .unwrap_or_else(|| bank.get_balance(pubkey));
+        breakdown.add(*reason, lamports);
+    }
+    let NonCirculatingBreakdown {
+        listed,
+        locked_stake,
+        autostaked,
+        accounts,
+        owners,
+    } = breakdown;
+
+    Ok(NonCirculatingSupply {
+        lamports: listed + locked_stake + autostaked + accounts + owners,
+        accounts: non_circulating_accounts_map.into_keys().collect(),
+        breakdown,
+    })
+}
+
+fn get_program_accounts(
+    bank: &Bank,
+    program_id: &Pubkey,
+) -> ScanResult<Vec<(Pubkey, AccountSharedData)>> {
+    if bank
         .rc
         .accounts
         .accounts_db
@@ -36,47 +160,16 @@ pub fn calculate_non_circulating_supply(bank: &Arc<Bank>) -> ScanResult<NonCircu
         .contains(&AccountIndex::ProgramId)
     {
         bank.get_filtered_indexed_accounts(
-            &IndexKey::ProgramId(stake::program::id()),
+            &IndexKey::ProgramId(*program_id),
             // The program-id account index checks for Account owner on inclusion. However, due to
             // the current AccountsDb implementation, an account may remain in storage as a
             // zero-lamport Account::Default() after being wiped and reinitialized in later
             // updates. We include the redundant filter here to avoid returning these accounts.
-            |account| account.owner() == &stake::program::id(),
-        )?
+            |account| account.owner() == program_id,
+        )
     } else {
-        bank.get_program_accounts(&stake::program::id())?
-    };
-
-    for (pubkey, account) in stake_accounts.iter() {
-        let stake_account = stake_state::from(account).unwrap_or_default();
-        match stake_account {
-            StakeState::Initialized(meta) => {
-                if meta.lockup.is_in_force(&clock, None)
-                    || withdraw_authority_list.contains(&meta.authorized.withdrawer)
-                {
-                    non_circulating_accounts_set.insert(*pubkey);
-                }
-            }
-            StakeState::Stake(meta, _stake) => {
-                if meta.lockup.is_in_force(&clock, None)
-                    || withdraw_authority_list.contains(&meta.authorized.withdrawer)
-                {
-                    non_circulating_accounts_set.insert(*pubkey);
-                }
-            }
-            _ => {}
-        }
+        bank.get_program_accounts(program_id)
     }
-
-    let lamports = non_circulating_accounts_set
-        .iter()
-        .map(|pubkey| bank.get_balance(pubkey))
-        .sum();
-
-    Ok(NonCirculatingSupply {
-        lamports,
-        accounts: non_circulating_accounts_set.into_iter().collect(),
-    })
 }
 
 // Mainnet-beta accounts that should be considered non-circulating
@@ -290,6 +383,14 @@ mod tests {
             non_circulating_supply.accounts.len(),
             num_non_circulating_accounts as usize + num_stake_accounts as usize
         );
+        assert_eq!(
+            non_circulating_supply.breakdown,
+            NonCirculatingBreakdown {
+                listed: num_non_circulating_accounts * balance,
+                locked_stake: num_stake_accounts * balance,
+                ..NonCirculatingBreakdown::default()
+            }
+        );
 
         bank = Arc::new(new_from_parent(&bank));
         let new_balance = 11;
@@ -324,4 +425,55 @@ mod tests {
             num_non_circulating_accounts as usize
         );
     }
+
+    #[test]
+    fn test_calculate_non_circulating_supply_with_config() {
+        let balance = 10;
+        let program_id = solana_sdk::pubkey::new_rand();
+        let listed = non_circulating_accounts()[0];
+        let extra = solana_sdk::pubkey::new_rand();
+        let owned = solana_sdk::pubkey::new_rand();
+        let mut accounts = BTreeMap::new();
+        accounts.insert(listed, Account::new(balance, 0, &Pubkey::default()));
+        accounts.insert(extra, Account::new(balance, 0, &Pubkey::default()));
+        accounts.insert(owned, Account::new(balance, 0, &program_id));
+        let genesis_config = GenesisConfig {
+            accounts,
+            cluster_type: ClusterType::MainnetBeta,
+            ..GenesisConfig::default()
+        };
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+
+        let mut config = NonCirculatingSupplyConfig {
+            exclude_defaults: false,
+            accounts: vec![listed, extra],
+            owners: vec![program_id],
+        };
+        let non_circulating_supply =
+            calculate_non_circulating_supply_with_config(&bank, &config).unwrap();
+        assert_eq!(
+            non_circulating_supply.breakdown,
+            NonCirculatingBreakdown {
+                listed: balance,
+                accounts: balance,
+                owners: balance,
+                ..NonCirculatingBreakdown::default()
+            }
+        );
+        assert_eq!(non_circulating_supply.lamports, 3 * balance);
+
+        config.exclude_defaults = true;
+        let non_circulating_supply =
+            calculate_non_circulating_supply_with_config(&bank, &config).unwrap();
+        assert_eq!(
+            non_circulating_supply.breakdown,
+            NonCirculatingBreakdown {
+                accounts: 2 * balance,
+                owners: balance,
+                ..NonCirculatingBreakdown::default()
+            }
+        );
+        assert_eq!(non_circulating_supply.lamports, 3 * balance);
+        assert_eq!(non_circulating_supply.accounts.len(), 3);
+    }
 }
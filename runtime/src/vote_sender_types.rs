@@ -1,7 +1,8 @@
 use crossbeam_channel::{Receiver, Sender};
-use solana_sdk::{hash::Hash, pubkey::Pubkey};
+use solana_sdk::{hash::Hash, pubkey::Pubkey, signature::Signature};
 use solana_vote_program::vote_state::Vote;
 
-pub type ReplayedVote = (Pubkey, Vote, Option<Hash>);
+/// Vote account, vote, switch proof hash and signature of the vote transaction
+pub type ReplayedVote = (Pubkey, Vote, Option<Hash>, Signature);
 pub type ReplayVoteSender = Sender<ReplayedVote>;
 pub type ReplayVoteReceiver = Receiver<ReplayedVote>;
@@ -5,11 +5,11 @@ use {
     solana_runtime::{bank::Bank, bank_forks::BankForks},
     solana_sdk::{hash::Hash, nonce_account, pubkey::Pubkey, signature::Signature},
     std::{
-        collections::HashMap,
+        collections::{HashMap, VecDeque},
         net::{SocketAddr, UdpSocket},
         sync::{
             mpsc::{Receiver, RecvTimeoutError},
-            Arc, RwLock,
+            Arc, Mutex, RwLock,
         },
         thread::{self, Builder, JoinHandle},
         time::{Duration, Instant},
@@ -24,6 +24,8 @@ const DEFAULT_RETRY_RATE_MS: u64 = 2_000;
 const DEFAULT_LEADER_FORWARD_COUNT: u64 = 2;
 /// Default max number of time the service will retry broadcast
 const DEFAULT_SERVICE_MAX_RETRIES: usize = usize::MAX;
+/// How long the final status of a tracked transaction is kept after it leaves the queue
+const RETRY_STATUS_RETENTION: Duration = Duration::from_secs(300);
 
 pub struct SendTransactionService {
     thread: JoinHandle<()>,
@@ -35,9 +37,93 @@ pub struct TransactionInfo {
     pub last_valid_block_height: u64,
     pub durable_nonce_info: Option<(Pubkey, Hash)>,
     pub max_retries: Option<usize>,
+    /// When set, the transaction is retried until it is rooted or expires, regardless of
+    /// `Config::default_max_retries`, and its progress is recorded here
+    pub retry_status_cache: Option<Arc<RetryStatusCache>>,
     retries: usize,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RetryState {
+    /// Not seen by the working bank; the transaction is being rebroadcast
+    Pending,
+    /// Processed by the working bank, waiting to be rooted
+    Processed,
+    Rooted,
+    Expired,
+    Failed,
+    MaxRetriesElapsed,
+    /// Never retried, because the retry queue was full
+    Dropped,
+}
+
+impl RetryState {
+    fn is_final(&self) -> bool {
+        !matches!(self, RetryState::Pending | RetryState::Processed)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryStatus {
+    pub state: RetryState,
+    pub retries: usize,
+    pub last_valid_block_height: u64,
+}
+
+/// The progress of the transactions sent with a `retry_status_cache`
+#[derive(Debug, Default)]
+pub struct RetryStatusCache {
+    statuses: Mutex<RetryStatuses>,
+}
+
+#[derive(Debug, Default)]
+struct RetryStatuses {
+    /// Statuses along with when they became final
+    statuses: HashMap<Signature, (RetryStatus, Option<Instant>)>,
+    /// Signatures in the order their statuses became final, to be pruned once retained long
+    /// enough without scanning the pending ones
+    finalized: VecDeque<(Instant, Signature)>,
+}
+
+impl RetryStatuses {
+    fn prune(&mut self, now: Instant) {
+        while let Some((finalized_at, signature)) = self.finalized.front().copied() {
+            if now.duration_since(finalized_at) < RETRY_STATUS_RETENTION {
+                break;
+            }
+            self.finalized.pop_front();
+            // The transaction may have been sent again since
+            if let Some((_, Some(status_finalized_at))) = self.statuses.get(&signature) {
+                if *status_finalized_at == finalized_at {
+                    self.statuses.remove(&signature);
+                }
+            }
+        }
+    }
+}
+
+impl RetryStatusCache {
+    pub fn get(&self, signature: &Signature) -> Option<RetryStatus> {
+        let mut statuses = self.statuses.lock().unwrap();
+        statuses.prune(Instant::now());
+        statuses.statuses.get(signature).map(|(status, _)| *status)
+    }
+
+    pub fn set(&self, signature: Signature, status: RetryStatus) {
+        self.set_at(signature, status, Instant::now())
+    }
+
+    fn set_at(&self, signature: Signature, status: RetryStatus, now: Instant) {
+        let mut statuses = self.statuses.lock().unwrap();
+        statuses.prune(now);
+        let finalized_at = status.state.is_final().then(|| now);
+        if finalized_at.is_some() {
+            statuses.finalized.push_back((now, signature));
+        }
+        statuses.statuses.insert(signature, (status, finalized_at));
+    }
+}
+
 impl TransactionInfo {
     pub fn new(
         signature: Signature,
@@ -52,9 +138,24 @@ impl TransactionInfo {
             last_valid_block_height,
             durable_nonce_info,
             max_retries,
+            retry_status_cache: None,
             retries: 0,
         }
     }
+
+    /// Records `state` as the progress of the transaction, if it is tracked
+    pub fn set_retry_state(&self, state: RetryState) {
+        if let Some(retry_status_cache) = &self.retry_status_cache {
+            retry_status_cache.set(
+                self.signature,
+                RetryStatus {
+                    state,
+                    retries: self.retries,
+                    last_valid_block_height: self.last_valid_block_height,
+                },
+            );
+        }
+    }
 }
 
 #[derive(Default, Debug, PartialEq)]
@@ -165,9 +266,11 @@ impl SendTransactionService {
                         }
                         if transactions.len() < MAX_TRANSACTION_QUEUE_SIZE {
                             inc_new_counter_info!("send_transaction_service-insert-tx", 1);
+                            transaction_info.set_retry_state(RetryState::Pending);
                             transactions.insert(transaction_info.signature, transaction_info);
                         } else {
                             datapoint_warn!("send_transaction_service-queue-overflow");
+                            transaction_info.set_retry_state(RetryState::Dropped);
                         }
                     }
                 }
@@ -227,6 +330,7 @@ impl SendTransactionService {
                 info!("Transaction is rooted: {}", signature);
                 result.rooted += 1;
                 inc_new_counter_info!("send_transaction_service-rooted", 1);
+                transaction_info.set_retry_state(RetryState::Rooted);
                 return false;
             }
             if let Some((nonce_pubkey, durable_nonce)) = transaction_info.durable_nonce_info {
@@ -237,6 +341,7 @@ impl SendTransactionService {
                     info!("Dropping expired durable-nonce transaction: {}", signature);
                     result.expired += 1;
                     inc_new_counter_info!("send_transaction_service-expired", 1);
+                    transaction_info.set_retry_state(RetryState::Expired);
                     return false;
                 }
             }
@@ -244,19 +349,25 @@ impl SendTransactionService {
                 info!("Dropping expired transaction: {}", signature);
                 result.expired += 1;
                 inc_new_counter_info!("send_transaction_service-expired", 1);
+                transaction_info.set_retry_state(RetryState::Expired);
                 return false;
             }
 
-            let max_retries = transaction_info
-                .max_retries
-                .or(config.default_max_retries)
-                .map(|max_retries| max_retries.min(config.service_max_retries));
+            let max_retries = if transaction_info.retry_status_cache.is_some() {
+                Some(config.service_max_retries)
+            } else {
+                transaction_info
+                    .max_retries
+                    .or(config.default_max_retries)
+                    .map(|max_retries| max_retries.min(config.service_max_retries))
+            };
 
             if let Some(max_retries) = max_retries {
                 if transaction_info.retries >= max_retries {
                     info!("Dropping transaction due to max retries: {}", signature);
                     result.max_retries_elapsed += 1;
                     inc_new_counter_info!("send_transaction_service-max_retries", 1);
+                    transaction_info.set_retry_state(RetryState::MaxRetriesElapsed);
                     return false;
                 }
             }
@@ -288,6 +399,7 @@ impl SendTransactionService {
                             &transaction_info.wire_transaction,
                         );
                     }
+                    transaction_info.set_retry_state(RetryState::Pending);
                     true
                 }
                 Some((_slot, status)) => {
@@ -295,9 +407,11 @@ impl SendTransactionService {
                         info!("Dropping failed transaction: {}", signature);
                         result.failed += 1;
                         inc_new_counter_info!("send_transaction_service-failed", 1);
+                        transaction_info.set_retry_state(RetryState::Failed);
                         false
                     } else {
                         result.retained += 1;
+                        transaction_info.set_retry_state(RetryState::Processed);
                         true
                     }
                 }
@@ -597,6 +711,57 @@ mod test {
                 ..ProcessTransactionsResult::default()
             }
         );
+
+        info!("Tracked transactions ignore the default max_retries and record their progress");
+        let config = Config {
+            default_max_retries: Some(0),
+            ..config
+        };
+        let retry_status_cache = Arc::new(RetryStatusCache::default());
+        for signature in [Signature::default(), non_rooted_signature, rooted_signature] {
+            let mut transaction_info =
+                TransactionInfo::new(signature, vec![], working_bank.block_height(), None, None);
+            transaction_info.retry_status_cache = Some(retry_status_cache.clone());
+            transactions.insert(signature, transaction_info);
+        }
+        let result = SendTransactionService::process_transactions::<NullTpuInfo>(
+            &working_bank,
+            &root_bank,
+            &send_socket,
+            &tpu_address,
+            &mut transactions,
+            &None,
+            &config,
+        );
+        assert_eq!(transactions.len(), 2);
+        assert_eq!(
+            result,
+            ProcessTransactionsResult {
+                rooted: 1,
+                retried: 1,
+                retained: 1,
+                ..ProcessTransactionsResult::default()
+            }
+        );
+        let retry_state = |signature| {
+            retry_status_cache
+                .get(&signature)
+                .map(|status| status.state)
+        };
+        assert_eq!(
+            retry_status_cache.get(&Signature::default()),
+            Some(RetryStatus {
+                state: RetryState::Pending,
+                retries: 1,
+                last_valid_block_height: working_bank.block_height(),
+            })
+        );
+        assert_eq!(
+            retry_state(non_rooted_signature),
+            Some(RetryState::Processed)
+        );
+        assert_eq!(retry_state(rooted_signature), Some(RetryState::Rooted));
+        assert_eq!(retry_state(failed_signature), None);
     }
 
     #[test]
@@ -877,4 +1042,54 @@ mod test {
             }
         );
     }
+
+    #[test]
+    fn test_retry_status_cache_prune() {
+        let retry_status_cache = RetryStatusCache::default();
+        let status = |state| RetryStatus {
+            state,
+            retries: 0,
+            last_valid_block_height: 0,
+        };
+        let (rooted, pending, expired, resent) = (
+            Signature::new(&[1; 64]),
+            Signature::new(&[2; 64]),
+            Signature::new(&[3; 64]),
+            Signature::new(&[4; 64]),
+        );
+        let now = Instant::now();
+        retry_status_cache.set_at(rooted, status(RetryState::Rooted), now);
+        retry_status_cache.set_at(pending, status(RetryState::Pending), now);
+        retry_status_cache.set_at(resent, status(RetryState::Dropped), now);
+        retry_status_cache.set_at(resent, status(RetryState::Pending), now);
+        retry_status_cache.set_at(
+            expired,
+            status(RetryState::Expired),
+            now + RETRY_STATUS_RETENTION / 2,
+        );
+        assert_eq!(
+            retry_status_cache.get(&rooted),
+            Some(status(RetryState::Rooted))
+        );
+
+        // Only the statuses that have been final for long enough are pruned
+        retry_status_cache.set_at(
+            Signature::default(),
+            status(RetryState::Pending),
+            now + RETRY_STATUS_RETENTION,
+        );
+        assert_eq!(retry_status_cache.get(&rooted), None);
+        assert_eq!(
+            retry_status_cache.get(&pending),
+            Some(status(RetryState::Pending))
+        );
+        assert_eq!(
+            retry_status_cache.get(&resent),
+            Some(status(RetryState::Pending))
+        );
+        assert_eq!(
+            retry_status_cache.get(&expired),
+            Some(status(RetryState::Expired))
+        );
+    }
 }
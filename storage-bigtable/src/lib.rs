@@ -1,5 +1,6 @@
 #![allow(clippy::integer_arithmetic)]
 use {
+    async_trait::async_trait,
     log::*,
     serde::{Deserialize, Serialize},
     solana_sdk::{
@@ -30,8 +31,11 @@ extern crate serde_derive;
 mod access_token;
 mod bigtable;
 mod compression;
+mod object_store;
 mod root_ca_certificate;
 
+pub use object_store::{ObjectStoreConfig, ObjectStoreLedgerStorage};
+
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("BigTable: {0}")]
@@ -48,6 +52,9 @@ pub enum Error {
 
     #[error("Signature not found")]
     SignatureNotFound,
+
+    #[error("Object store: {0}")]
+    ObjectStoreError(String),
 }
 
 impl std::convert::From<bigtable::Error> for Error {
@@ -335,6 +342,191 @@ impl From<LegacyTransactionByAddrInfo> for TransactionByAddrInfo {
     }
 }
 
+// The `tx` and `tx-by-addr` cells of a block, by row key
+#[allow(clippy::type_complexity)]
+fn block_cells(
+    slot: Slot,
+    confirmed_block: &ConfirmedBlock,
+) -> (
+    Vec<(String, TransactionInfo)>,
+    Vec<(String, tx_by_addr::TransactionByAddr)>,
+) {
+    let mut by_addr: HashMap<&Pubkey, Vec<TransactionByAddrInfo>> = HashMap::new();
+
+    let mut tx_cells = vec![];
+    for (index, transaction_with_meta) in confirmed_block.transactions.iter().enumerate() {
+        let TransactionWithStatusMeta { meta, transaction } = transaction_with_meta;
+        let err = meta.as_ref().and_then(|meta| meta.status.clone().err());
+        let index = index as u32;
+        let signature = transaction.signatures[0];
+        let memo = extract_and_fmt_memos(&transaction.message);
+
+        for address in &transaction.message.account_keys {
+            if !is_sysvar_id(address) {
+                by_addr
+                    .entry(address)
+                    .or_default()
+                    .push(TransactionByAddrInfo {
+                        signature,
+                        err: err.clone(),
+                        index,
+                        memo: memo.clone(),
+                        block_time: confirmed_block.block_time,
+                    });
+            }
+        }
+
+        tx_cells.push((
+            signature.to_string(),
+            TransactionInfo {
+                slot,
+                index,
+                err,
+                memo,
+            },
+        ));
+    }
+
+    let tx_by_addr_cells = by_addr
+        .into_iter()
+        .map(|(address, transaction_info_by_addr)| {
+            (
+                format!("{}/{}", address, slot_to_tx_by_addr_key(slot)),
+                tx_by_addr::TransactionByAddr {
+                    tx_by_addrs: transaction_info_by_addr
+                        .into_iter()
+                        .map(|by_addr| by_addr.into())
+                        .collect(),
+                },
+            )
+        })
+        .collect();
+    (tx_cells, tx_by_addr_cells)
+}
+
+// The transaction at `index` of the block, if it is the one with `signature`
+fn transaction_from_block(
+    signature: &Signature,
+    slot: Slot,
+    index: u32,
+    block: ConfirmedBlock,
+) -> Option<ConfirmedTransaction> {
+    match block.transactions.into_iter().nth(index as usize) {
+        None => {
+            // report this somewhere actionable?
+            warn!("Transaction info for {} is corrupt", signature);
+            None
+        }
+        Some(bucket_block_transaction) => {
+            if bucket_block_transaction.transaction.signatures[0] != *signature {
+                warn!(
+                    "Transaction info or confirmed block for {} is corrupt",
+                    signature
+                );
+                None
+            } else {
+                Some(ConfirmedTransaction {
+                    slot,
+                    transaction: bucket_block_transaction,
+                    block_time: block.block_time,
+                })
+            }
+        }
+    }
+}
+
+// Where the signatures listed for an address start and end, as set by the `before` and `until`
+// signatures
+struct AddressSignaturesBounds {
+    first_slot: Slot,
+    before_transaction_index: u32,
+    last_slot: Slot,
+    until_transaction_index: u32,
+}
+
+// Adds the signatures of the `tx-by-addr` cell of `slot`, newest first, that fall within
+// `bounds`, and returns whether `limit` was reached
+fn push_address_signatures(
+    infos: &mut Vec<(ConfirmedTransactionStatusWithSignature, u32)>,
+    slot: Slot,
+    cell_data: Vec<TransactionByAddrInfo>,
+    bounds: &AddressSignaturesBounds,
+    limit: usize,
+) -> bool {
+    for tx_by_addr_info in cell_data.into_iter() {
+        // Filter out records before `before_transaction_index`
+        if slot == bounds.first_slot && tx_by_addr_info.index >= bounds.before_transaction_index {
+            continue;
+        }
+        // Filter out records after `until_transaction_index`
+        if slot == bounds.last_slot && tx_by_addr_info.index <= bounds.until_transaction_index {
+            continue;
+        }
+        infos.push((
+            ConfirmedTransactionStatusWithSignature {
+                signature: tx_by_addr_info.signature,
+                slot,
+                err: tx_by_addr_info.err,
+                memo: tx_by_addr_info.memo,
+                block_time: tx_by_addr_info.block_time,
+            },
+            tx_by_addr_info.index,
+        ));
+        // Respect limit
+        if infos.len() >= limit {
+            return true;
+        }
+    }
+    false
+}
+
+/// Long-term storage of the ledger, which serves the blocks and transactions that are no longer
+/// in the blockstore
+#[async_trait]
+pub trait LedgerArchive: Send + Sync {
+    /// Return the available slot that contains a block
+    async fn get_first_available_block(&self) -> Result<Option<Slot>>;
+
+    /// Fetch the next slots after the provided slot that contains a block
+    ///
+    /// start_slot: slot to start the search from (inclusive)
+    /// limit: stop after this many slots have been found; if limit==0, all records after
+    /// start_slot will be read
+    async fn get_confirmed_blocks(&self, start_slot: Slot, limit: usize) -> Result<Vec<Slot>>;
+
+    /// Fetch the confirmed block from the desired slot
+    async fn get_confirmed_block(&self, slot: Slot) -> Result<ConfirmedBlock>;
+
+    async fn get_signature_status(&self, signature: &Signature) -> Result<TransactionStatus>;
+
+    /// Fetch a confirmed transaction
+    async fn get_confirmed_transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<ConfirmedTransaction>>;
+
+    /// Get confirmed signatures for the provided address, in descending ledger order
+    ///
+    /// address: address to search for
+    /// before_signature: start with the first signature older than this one
+    /// until_signature: end with the last signature more recent than this one
+    /// limit: stop after this many signatures
+    async fn get_confirmed_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        before_signature: Option<&Signature>,
+        until_signature: Option<&Signature>,
+        limit: usize,
+    ) -> Result<Vec<(ConfirmedTransactionStatusWithSignature, u32)>>;
+
+    /// Upload a new confirmed block and associated meta data
+    async fn upload_confirmed_block(
+        &self,
+        slot: Slot,
+        confirmed_block: ConfirmedBlock,
+    ) -> Result<()>;
+}
+
 #[derive(Clone)]
 pub struct LedgerStorage {
     connection: bigtable::BigTableConnection,
@@ -426,28 +618,7 @@ impl LedgerStorage {
 
         // Load the block and return the transaction
         let block = self.get_confirmed_block(slot).await?;
-        match block.transactions.into_iter().nth(index as usize) {
-            None => {
-                // report this somewhere actionable?
-                warn!("Transaction info for {} is corrupt", signature);
-                Ok(None)
-            }
-            Some(bucket_block_transaction) => {
-                if bucket_block_transaction.transaction.signatures[0] != *signature {
-                    warn!(
-                        "Transaction info or confirmed block for {} is corrupt",
-                        signature
-                    );
-                    Ok(None)
-                } else {
-                    Ok(Some(ConfirmedTransaction {
-                        slot,
-                        transaction: bucket_block_transaction,
-                        block_time: block.block_time,
-                    }))
-                }
-            }
-        }
+        Ok(transaction_from_block(signature, slot, index, block))
     }
 
     /// Get confirmed signatures for the provided address, in descending ledger order
@@ -495,6 +666,12 @@ impl LedgerStorage {
             }
         };
 
+        let bounds = AddressSignaturesBounds {
+            first_slot,
+            before_transaction_index,
+            last_slot,
+            until_transaction_index,
+        };
         let mut infos = vec![];
 
         let starting_slot_tx_len = bigtable
@@ -530,7 +707,7 @@ impl LedgerStorage {
             )
             .await?;
 
-        'outer: for (row_key, data) in tx_by_addr_data {
+        for (row_key, data) in tx_by_addr_data {
             let slot = !key_to_slot(&row_key[address_prefix.len()..]).ok_or_else(|| {
                 bigtable::Error::ObjectCorrupt(format!(
                     "Failed to convert key to slot: tx-by-addr/{}",
@@ -559,29 +736,8 @@ impl LedgerStorage {
             };
 
             cell_data.reverse();
-            for tx_by_addr_info in cell_data.into_iter() {
-                // Filter out records before `before_transaction_index`
-                if slot == first_slot && tx_by_addr_info.index >= before_transaction_index {
-                    continue;
-                }
-                // Filter out records after `until_transaction_index`
-                if slot == last_slot && tx_by_addr_info.index <= until_transaction_index {
-                    continue;
-                }
-                infos.push((
-                    ConfirmedTransactionStatusWithSignature {
-                        signature: tx_by_addr_info.signature,
-                        slot,
-                        err: tx_by_addr_info.err,
-                        memo: tx_by_addr_info.memo,
-                        block_time: tx_by_addr_info.block_time,
-                    },
-                    tx_by_addr_info.index,
-                ));
-                // Respect limit
-                if infos.len() >= limit {
-                    break 'outer;
-                }
+            if push_address_signatures(&mut infos, slot, cell_data, &bounds, limit) {
+                break;
             }
         }
         Ok(infos)
@@ -594,57 +750,7 @@ impl LedgerStorage {
         confirmed_block: ConfirmedBlock,
     ) -> Result<()> {
         let mut bytes_written = 0;
-
-        let mut by_addr: HashMap<&Pubkey, Vec<TransactionByAddrInfo>> = HashMap::new();
-
-        let mut tx_cells = vec![];
-        for (index, transaction_with_meta) in confirmed_block.transactions.iter().enumerate() {
-            let TransactionWithStatusMeta { meta, transaction } = transaction_with_meta;
-            let err = meta.as_ref().and_then(|meta| meta.status.clone().err());
-            let index = index as u32;
-            let signature = transaction.signatures[0];
-            let memo = extract_and_fmt_memos(&transaction.message);
-
-            for address in &transaction.message.account_keys {
-                if !is_sysvar_id(address) {
-                    by_addr
-                        .entry(address)
-                        .or_default()
-                        .push(TransactionByAddrInfo {
-                            signature,
-                            err: err.clone(),
-                            index,
-                            memo: memo.clone(),
-                            block_time: confirmed_block.block_time,
-                        });
-                }
-            }
-
-            tx_cells.push((
-                signature.to_string(),
-                TransactionInfo {
-                    slot,
-                    index,
-                    err,
-                    memo,
-                },
-            ));
-        }
-
-        let tx_by_addr_cells: Vec<_> = by_addr
-            .into_iter()
-            .map(|(address, transaction_info_by_addr)| {
-                (
-                    format!("{}/{}", address, slot_to_tx_by_addr_key(slot)),
-                    tx_by_addr::TransactionByAddr {
-                        tx_by_addrs: transaction_info_by_addr
-                            .into_iter()
-                            .map(|by_addr| by_addr.into())
-                            .collect(),
-                    },
-                )
-            })
-            .collect();
+        let (tx_cells, tx_by_addr_cells) = block_cells(slot, &confirmed_block);
 
         if !tx_cells.is_empty() {
             bytes_written += self
@@ -783,6 +889,57 @@ impl LedgerStorage {
     }
 }
 
+#[async_trait]
+impl LedgerArchive for LedgerStorage {
+    async fn get_first_available_block(&self) -> Result<Option<Slot>> {
+        LedgerStorage::get_first_available_block(self).await
+    }
+
+    async fn get_confirmed_blocks(&self, start_slot: Slot, limit: usize) -> Result<Vec<Slot>> {
+        LedgerStorage::get_confirmed_blocks(self, start_slot, limit).await
+    }
+
+    async fn get_confirmed_block(&self, slot: Slot) -> Result<ConfirmedBlock> {
+        LedgerStorage::get_confirmed_block(self, slot).await
+    }
+
+    async fn get_signature_status(&self, signature: &Signature) -> Result<TransactionStatus> {
+        LedgerStorage::get_signature_status(self, signature).await
+    }
+
+    async fn get_confirmed_transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<ConfirmedTransaction>> {
+        LedgerStorage::get_confirmed_transaction(self, signature).await
+    }
+
+    async fn get_confirmed_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        before_signature: Option<&Signature>,
+        until_signature: Option<&Signature>,
+        limit: usize,
+    ) -> Result<Vec<(ConfirmedTransactionStatusWithSignature, u32)>> {
+        LedgerStorage::get_confirmed_signatures_for_address(
+            self,
+            address,
+            before_signature,
+            until_signature,
+            limit,
+        )
+        .await
+    }
+
+    async fn upload_confirmed_block(
+        &self,
+        slot: Slot,
+        confirmed_block: ConfirmedBlock,
+    ) -> Result<()> {
+        LedgerStorage::upload_confirmed_block(self, slot, confirmed_block).await
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
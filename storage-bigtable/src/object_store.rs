@@ -0,0 +1,686 @@
+//! Ledger storage in an S3-compatible object store, for operators without a BigTable instance.
+//! Google Cloud Storage works too, through its XML API and HMAC keys.
+//!
+//! Objects are laid out like the rows of the BigTable tables, as `blocks/<slot>`,
+//! `tx/<signature>` and `tx-by-addr/<address>/<inverted slot>`, and hold the same compressed
+//! cell data. Their keys sort like the row keys, so blocks and the signatures of an address are
+//! listed in the same order. Credentials come from the usual AWS sources: the
+//! `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and `AWS_SESSION_TOKEN` environment variables,
+//! the credentials file, or the role of the container or instance. The region is read from
+//! `AWS_REGION`.
+
+use {
+    crate::{
+        bigtable::{self, deserialize_bincode_cell_data, deserialize_protobuf_cell_data},
+        block_cells,
+        compression::compress_best,
+        key_to_slot, push_address_signatures, slot_to_blocks_key, slot_to_tx_by_addr_key,
+        transaction_from_block, AddressSignaturesBounds, Error, LedgerArchive, Result,
+        TransactionInfo,
+    },
+    async_trait::async_trait,
+    futures::{
+        future::Future,
+        stream::{self, StreamExt, TryStreamExt},
+    },
+    log::*,
+    rusoto_core::{credential::DefaultCredentialsProvider, HttpClient, Region, RusotoError},
+    rusoto_s3::{
+        GetObjectError, GetObjectRequest, ListObjectsV2Request, PutObjectRequest, S3Client, S3,
+    },
+    solana_sdk::{clock::Slot, pubkey::Pubkey, signature::Signature},
+    solana_storage_proto::convert::{generated, tx_by_addr},
+    solana_transaction_status::{
+        ConfirmedBlock, ConfirmedTransaction, ConfirmedTransactionStatusWithSignature,
+        TransactionByAddrInfo, TransactionStatus,
+    },
+    std::{convert::TryInto, env, fmt::Display, time::Duration},
+};
+
+// Ask for this many keys per listing request
+const LIST_PAGE_SIZE: usize = 1_000;
+
+// Upload this many objects of a block in parallel
+const NUM_OBJECTS_TO_UPLOAD_IN_PARALLEL: usize = 32;
+
+// Read this many `tx-by-addr` objects of an address in parallel
+const NUM_OBJECTS_TO_READ_IN_PARALLEL: usize = 16;
+
+const DEFAULT_REGION: &str = "us-east-1";
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ObjectStoreConfig {
+    /// Base URL of the service, such as `https://s3.us-east-1.amazonaws.com`
+    pub endpoint: String,
+    pub bucket: String,
+}
+
+fn object_store_error(key: &str, err: impl Display) -> Error {
+    Error::ObjectStoreError(format!("{}: {}", key, err))
+}
+
+fn protobuf_object<T: prost::Message>(data: &T) -> Result<Vec<u8>> {
+    let mut buf = Vec::with_capacity(data.encoded_len());
+    data.encode(&mut buf).unwrap();
+    Ok(compress_best(&buf)?)
+}
+
+// Pages through the keys starting with `prefix`, in lexicographic order
+struct Listing<'a> {
+    storage: &'a ObjectStoreLedgerStorage,
+    prefix: String,
+    start_after: Option<String>,
+    continuation_token: Option<String>,
+    done: bool,
+}
+
+impl<'a> Listing<'a> {
+    // The next page of at most `max_keys` keys, or None once the listing is exhausted
+    async fn next_page(&mut self, max_keys: usize) -> Result<Option<Vec<String>>> {
+        if self.done {
+            return Ok(None);
+        }
+        let request = ListObjectsV2Request {
+            bucket: self.storage.bucket.clone(),
+            prefix: Some(self.prefix.clone()),
+            start_after: self.start_after.take(),
+            continuation_token: self.continuation_token.take(),
+            max_keys: Some(max_keys as i64),
+            ..ListObjectsV2Request::default()
+        };
+        let output = self
+            .storage
+            .timed(self.storage.client.list_objects_v2(request))
+            .await?
+            .map_err(|err| object_store_error(&self.prefix, err))?;
+        // A page may hold fewer keys than asked for, only the truncation flag tells whether
+        // more follow
+        if output.is_truncated == Some(true) {
+            self.continuation_token = output.next_continuation_token;
+        }
+        self.done = self.continuation_token.is_none();
+        Ok(Some(
+            output
+                .contents
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|object| object.key)
+                .collect(),
+        ))
+    }
+}
+
+#[derive(Clone)]
+pub struct ObjectStoreLedgerStorage {
+    client: S3Client,
+    bucket: String,
+    read_only: bool,
+    timeout: Option<Duration>,
+}
+
+impl ObjectStoreLedgerStorage {
+    pub fn new(
+        config: &ObjectStoreConfig,
+        read_only: bool,
+        timeout: Option<Duration>,
+    ) -> Result<Self> {
+        let region = Region::Custom {
+            name: env::var("AWS_REGION").unwrap_or_else(|_| DEFAULT_REGION.to_string()),
+            endpoint: config.endpoint.clone(),
+        };
+        let http_client =
+            HttpClient::new().map_err(|err| Error::ObjectStoreError(err.to_string()))?;
+        let credentials = DefaultCredentialsProvider::new()
+            .map_err(|err| Error::ObjectStoreError(err.to_string()))?;
+        Ok(Self::new_with_client(
+            S3Client::new_with(http_client, credentials, region),
+            config,
+            read_only,
+            timeout,
+        ))
+    }
+
+    fn new_with_client(
+        client: S3Client,
+        config: &ObjectStoreConfig,
+        read_only: bool,
+        timeout: Option<Duration>,
+    ) -> Self {
+        Self {
+            client,
+            bucket: config.bucket.clone(),
+            read_only,
+            timeout,
+        }
+    }
+
+    async fn timed<F: Future>(&self, request: F) -> Result<F::Output> {
+        match self.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, request)
+                .await
+                .map_err(|_| Error::ObjectStoreError("Request timed out".to_string())),
+            None => Ok(request.await),
+        }
+    }
+
+    fn listing(&self, prefix: &str, start_after: Option<String>) -> Listing {
+        Listing {
+            storage: self,
+            prefix: prefix.to_string(),
+            start_after,
+            continuation_token: None,
+            done: false,
+        }
+    }
+
+    async fn get_object(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let request = GetObjectRequest {
+            bucket: self.bucket.clone(),
+            key: key.to_string(),
+            ..GetObjectRequest::default()
+        };
+        self.timed(async {
+            let output = match self.client.get_object(request).await {
+                Ok(output) => output,
+                Err(RusotoError::Service(GetObjectError::NoSuchKey(_))) => return Ok(None),
+                Err(RusotoError::Unknown(response)) if response.status.as_u16() == 404 => {
+                    return Ok(None)
+                }
+                Err(err) => return Err(object_store_error(key, err)),
+            };
+            let data = match output.body {
+                Some(body) => body
+                    .try_fold(vec![], |mut data, chunk| async move {
+                        data.extend_from_slice(&chunk);
+                        Ok(data)
+                    })
+                    .await
+                    .map_err(|err| object_store_error(key, err))?,
+                None => vec![],
+            };
+            Ok(Some(data))
+        })
+        .await?
+    }
+
+    async fn put_object(&self, key: &str, data: Vec<u8>) -> Result<usize> {
+        if self.read_only {
+            return Err(Error::ObjectStoreError(format!(
+                "Can't write {}, storage is read only",
+                key
+            )));
+        }
+        let bytes_written = data.len();
+        let request = PutObjectRequest {
+            bucket: self.bucket.clone(),
+            key: key.to_string(),
+            content_length: Some(bytes_written as i64),
+            body: Some(data.into()),
+            ..PutObjectRequest::default()
+        };
+        self.timed(self.client.put_object(request))
+            .await?
+            .map_err(|err| object_store_error(key, err))?;
+        Ok(bytes_written)
+    }
+
+    async fn get_transaction_info(&self, signature: &Signature) -> Result<TransactionInfo> {
+        let key = signature.to_string();
+        let data = self
+            .get_object(&format!("tx/{}", key))
+            .await?
+            .ok_or(Error::SignatureNotFound)?;
+        Ok(deserialize_bincode_cell_data(
+            &[("bin".to_string(), data)],
+            "tx",
+            key,
+        )?)
+    }
+}
+
+#[async_trait]
+impl LedgerArchive for ObjectStoreLedgerStorage {
+    async fn get_first_available_block(&self) -> Result<Option<Slot>> {
+        let slots = self.get_confirmed_blocks(0, 1).await?;
+        Ok(slots.first().copied())
+    }
+
+    async fn get_confirmed_blocks(&self, start_slot: Slot, limit: usize) -> Result<Vec<Slot>> {
+        let mut slots = vec![];
+        let mut listing = self.listing(
+            "blocks/",
+            start_slot
+                .checked_sub(1)
+                .map(|slot| format!("blocks/{}", slot_to_blocks_key(slot))),
+        );
+        loop {
+            let page_size = if limit == 0 {
+                LIST_PAGE_SIZE
+            } else {
+                (limit - slots.len()).min(LIST_PAGE_SIZE)
+            };
+            let keys = match listing.next_page(page_size).await? {
+                Some(keys) => keys,
+                None => return Ok(slots),
+            };
+            slots.extend(
+                keys.iter()
+                    .filter_map(|key| key_to_slot(&key["blocks/".len()..])),
+            );
+            if slots.len() == limit {
+                return Ok(slots);
+            }
+        }
+    }
+
+    async fn get_confirmed_block(&self, slot: Slot) -> Result<ConfirmedBlock> {
+        let key = slot_to_blocks_key(slot);
+        let data = self
+            .get_object(&format!("blocks/{}", key))
+            .await?
+            .ok_or(Error::BlockNotFound(slot))?;
+        let block: generated::ConfirmedBlock =
+            deserialize_protobuf_cell_data(&[("proto".to_string(), data)], "blocks", key.clone())?;
+        block
+            .try_into()
+            .map_err(|_err| bigtable::Error::ObjectCorrupt(format!("blocks/{}", key)).into())
+    }
+
+    async fn get_signature_status(&self, signature: &Signature) -> Result<TransactionStatus> {
+        Ok(self.get_transaction_info(signature).await?.into())
+    }
+
+    async fn get_confirmed_transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<ConfirmedTransaction>> {
+        let TransactionInfo { slot, index, .. } = self.get_transaction_info(signature).await?;
+        let block = self.get_confirmed_block(slot).await?;
+        Ok(transaction_from_block(signature, slot, index, block))
+    }
+
+    async fn get_confirmed_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        before_signature: Option<&Signature>,
+        until_signature: Option<&Signature>,
+        limit: usize,
+    ) -> Result<Vec<(ConfirmedTransactionStatusWithSignature, u32)>> {
+        let prefix = format!("tx-by-addr/{}/", address);
+
+        let (first_slot, before_transaction_index) = match before_signature {
+            None => (Slot::MAX, 0),
+            Some(before_signature) => {
+                let TransactionInfo { slot, index, .. } =
+                    self.get_transaction_info(before_signature).await?;
+                (slot, index)
+            }
+        };
+        let (last_slot, until_transaction_index) = match until_signature {
+            None => (0, u32::MAX),
+            Some(until_signature) => {
+                let TransactionInfo { slot, index, .. } =
+                    self.get_transaction_info(until_signature).await?;
+                (slot, index)
+            }
+        };
+        let bounds = AddressSignaturesBounds {
+            first_slot,
+            before_transaction_index,
+            last_slot,
+            until_transaction_index,
+        };
+
+        // Keys hold inverted slots, so listing goes back in time from `first_slot` and stops
+        // past `last_slot`
+        let mut listing = self.listing(
+            &prefix,
+            first_slot
+                .checked_add(1)
+                .map(|slot| format!("{}{}", prefix, slot_to_tx_by_addr_key(slot))),
+        );
+        let last_key = format!("{}{}", prefix, slot_to_tx_by_addr_key(last_slot));
+        let mut infos = vec![];
+        while let Some(keys) = listing.next_page(LIST_PAGE_SIZE).await? {
+            let num_keys = keys.len();
+            let keys: Vec<String> = keys
+                .into_iter()
+                .take_while(|key| *key <= last_key)
+                .collect();
+            let past_last_slot = keys.len() < num_keys;
+
+            // The objects are read ahead in parallel, but consumed in order so that reading
+            // stops soon after `limit` is reached
+            let mut objects = stream::iter(keys)
+                .map(|key| async move {
+                    let data = self.get_object(&key).await?;
+                    Ok::<_, Error>((key, data))
+                })
+                .buffered(NUM_OBJECTS_TO_READ_IN_PARALLEL);
+            while let Some((key, data)) = objects.try_next().await? {
+                let data = match data {
+                    Some(data) => data,
+                    None => continue,
+                };
+                let slot = !key_to_slot(&key[prefix.len()..]).ok_or_else(|| {
+                    bigtable::Error::ObjectCorrupt(format!(
+                        "Failed to convert key to slot: {}",
+                        key
+                    ))
+                })?;
+                let tx_by_addr: tx_by_addr::TransactionByAddr = deserialize_protobuf_cell_data(
+                    &[("proto".to_string(), data)],
+                    "tx-by-addr",
+                    key["tx-by-addr/".len()..].to_string(),
+                )?;
+                let mut cell_data: Vec<TransactionByAddrInfo> =
+                    tx_by_addr.try_into().map_err(|error| {
+                        bigtable::Error::ObjectCorrupt(format!(
+                            "Failed to deserialize: {}: {}",
+                            error, key
+                        ))
+                    })?;
+                cell_data.reverse();
+                if push_address_signatures(&mut infos, slot, cell_data, &bounds, limit) {
+                    return Ok(infos);
+                }
+            }
+            if past_last_slot {
+                break;
+            }
+        }
+        Ok(infos)
+    }
+
+    async fn upload_confirmed_block(
+        &self,
+        slot: Slot,
+        confirmed_block: ConfirmedBlock,
+    ) -> Result<()> {
+        let (tx_cells, tx_by_addr_cells) = block_cells(slot, &confirmed_block);
+        let mut objects = Vec::with_capacity(tx_cells.len() + tx_by_addr_cells.len());
+        for (signature, transaction_info) in tx_cells {
+            let data = compress_best(&bincode::serialize(&transaction_info).unwrap())?;
+            objects.push((format!("tx/{}", signature), data));
+        }
+        for (row_key, tx_by_addr) in tx_by_addr_cells {
+            objects.push((
+                format!("tx-by-addr/{}", row_key),
+                protobuf_object(&tx_by_addr)?,
+            ));
+        }
+
+        let mut bytes_written = stream::iter(objects)
+            .map(|(key, data)| async move { self.put_object(&key, data).await })
+            .buffer_unordered(NUM_OBJECTS_TO_UPLOAD_IN_PARALLEL)
+            .try_fold(0, |total, bytes| async move { Ok(total + bytes) })
+            .await?;
+
+        // Store the block itself last, like BigTable does, so that partially uploaded blocks
+        // don't become visible
+        let num_transactions = confirmed_block.transactions.len();
+        let block: generated::ConfirmedBlock = confirmed_block.into();
+        bytes_written += self
+            .put_object(
+                &format!("blocks/{}", slot_to_blocks_key(slot)),
+                protobuf_object(&block)?,
+            )
+            .await?;
+        info!(
+            "uploaded block for slot {}: {} transactions, {} bytes",
+            slot, num_transactions, bytes_written
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        hyper::{
+            service::{make_service_fn, service_fn},
+            Body, Method, Request, Response, Server, StatusCode,
+        },
+        rusoto_core::credential::StaticProvider,
+        solana_sdk::{
+            hash::Hash,
+            signature::{Keypair, Signer},
+            system_transaction,
+        },
+        solana_transaction_status::{TransactionStatusMeta, TransactionWithStatusMeta},
+        std::{
+            collections::{BTreeMap, HashMap},
+            convert::Infallible,
+            net::SocketAddr,
+            ops::Bound,
+            sync::{Arc, Mutex},
+        },
+        tokio::runtime::Runtime,
+    };
+
+    const MOCK_BUCKET: &str = "ledger";
+
+    // Listings of the mock return at most this many keys, so that paging is exercised
+    const MOCK_PAGE_SIZE: usize = 2;
+
+    type MockObjects = Arc<Mutex<BTreeMap<String, Vec<u8>>>>;
+
+    // Serves the GET, PUT and ListObjectsV2 requests of a single path-style bucket from memory
+    fn handle_mock_request(
+        objects: &MockObjects,
+        request: Request<Body>,
+        body: Vec<u8>,
+    ) -> Response<Body> {
+        let key = request
+            .uri()
+            .path()
+            .trim_start_matches('/')
+            .trim_start_matches(MOCK_BUCKET)
+            .trim_start_matches('/')
+            .to_string();
+        let mut objects = objects.lock().unwrap();
+        match *request.method() {
+            Method::PUT => {
+                objects.insert(key, body);
+                Response::new(Body::empty())
+            }
+            Method::GET if key.is_empty() => {
+                let query: HashMap<String, String> =
+                    url::form_urlencoded::parse(request.uri().query().unwrap_or("").as_bytes())
+                        .into_owned()
+                        .collect();
+                let prefix = query.get("prefix").cloned().unwrap_or_default();
+                let after = query
+                    .get("continuation-token")
+                    .or_else(|| query.get("start-after"))
+                    .cloned()
+                    .unwrap_or_default();
+                let max_keys: usize = query["max-keys"].parse().unwrap();
+                let mut keys = objects
+                    .range::<String, _>((Bound::Excluded(after), Bound::Unbounded))
+                    .map(|(key, _)| key)
+                    .filter(|key| key.starts_with(&prefix));
+                let page: Vec<&String> = keys.by_ref().take(max_keys.min(MOCK_PAGE_SIZE)).collect();
+                let is_truncated = keys.next().is_some();
+                let mut listing = format!(
+                    "<?xml version=\"1.0\" encoding=\"UTF-8\"?><ListBucketResult>\
+                    <Name>{}</Name><Prefix>{}</Prefix><IsTruncated>{}</IsTruncated>",
+                    MOCK_BUCKET, prefix, is_truncated
+                );
+                if is_truncated {
+                    listing.push_str(&format!(
+                        "<NextContinuationToken>{}</NextContinuationToken>",
+                        page.last().unwrap()
+                    ));
+                }
+                for key in page {
+                    listing.push_str(&format!("<Contents><Key>{}</Key></Contents>", key));
+                }
+                listing.push_str("</ListBucketResult>");
+                Response::new(Body::from(listing))
+            }
+            Method::GET => match objects.get(&key) {
+                Some(data) => Response::new(Body::from(data.clone())),
+                None => Response::builder()
+                    .status(StatusCode::NOT_FOUND)
+                    .body(Body::from(
+                        "<Error><Code>NoSuchKey</Code><Message>Not found</Message></Error>",
+                    ))
+                    .unwrap(),
+            },
+            _ => Response::builder()
+                .status(StatusCode::METHOD_NOT_ALLOWED)
+                .body(Body::empty())
+                .unwrap(),
+        }
+    }
+
+    fn start_mock_object_store(runtime: &Runtime) -> SocketAddr {
+        let objects = MockObjects::default();
+        let make_service = make_service_fn(move |_| {
+            let objects = objects.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request: Request<Body>| {
+                    let objects = objects.clone();
+                    async move {
+                        let (parts, body) = request.into_parts();
+                        let body = hyper::body::to_bytes(body).await?.to_vec();
+                        let request = Request::from_parts(parts, Body::empty());
+                        Ok::<_, hyper::Error>(handle_mock_request(&objects, request, body))
+                    }
+                }))
+            }
+        });
+        let _guard = runtime.enter();
+        let server = Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(make_service);
+        let address = server.local_addr();
+        runtime.spawn(server);
+        address
+    }
+
+    fn test_block(slot: Slot, payer: &Keypair) -> ConfirmedBlock {
+        let transaction = system_transaction::transfer(
+            payer,
+            &solana_sdk::pubkey::new_rand(),
+            slot,
+            Hash::default(),
+        );
+        ConfirmedBlock {
+            previous_blockhash: Hash::new_unique().to_string(),
+            blockhash: Hash::new_unique().to_string(),
+            parent_slot: slot.saturating_sub(1),
+            transactions: vec![TransactionWithStatusMeta {
+                transaction,
+                meta: Some(TransactionStatusMeta::default()),
+            }],
+            rewards: vec![],
+            block_time: Some(slot as i64),
+            block_height: Some(slot),
+        }
+    }
+
+    #[test]
+    fn test_upload_and_read_back() {
+        let runtime = Runtime::new().unwrap();
+        let address = start_mock_object_store(&runtime);
+        let config = ObjectStoreConfig {
+            endpoint: format!("http://{}", address),
+            bucket: MOCK_BUCKET.to_string(),
+        };
+        let client = S3Client::new_with(
+            HttpClient::new().unwrap(),
+            StaticProvider::new_minimal("access_key_id".to_string(), "secret".to_string()),
+            Region::Custom {
+                name: DEFAULT_REGION.to_string(),
+                endpoint: config.endpoint.clone(),
+            },
+        );
+        let storage = ObjectStoreLedgerStorage::new_with_client(
+            client,
+            &config,
+            false,
+            Some(Duration::from_secs(10)),
+        );
+
+        let payer = Keypair::new();
+        let blocks: Vec<_> = (1..=3).map(|slot| test_block(slot, &payer)).collect();
+        let signature = |slot: Slot| {
+            blocks[slot as usize - 1].transactions[0]
+                .transaction
+                .signatures[0]
+        };
+        runtime.block_on(async {
+            for (slot, block) in (1..).zip(&blocks) {
+                storage
+                    .upload_confirmed_block(slot, block.clone())
+                    .await
+                    .unwrap();
+            }
+
+            // Listing every block takes two pages of the mock
+            assert_eq!(
+                storage
+                    .get_confirmed_blocks(0, LIST_PAGE_SIZE)
+                    .await
+                    .unwrap(),
+                vec![1, 2, 3]
+            );
+            assert_eq!(storage.get_confirmed_blocks(2, 1).await.unwrap(), vec![2]);
+            assert_eq!(storage.get_first_available_block().await.unwrap(), Some(1));
+
+            let block = storage.get_confirmed_block(2).await.unwrap();
+            assert_eq!(block.blockhash, blocks[1].blockhash);
+            assert_eq!(block.block_time, Some(2));
+            assert!(matches!(
+                storage.get_confirmed_block(4).await,
+                Err(Error::BlockNotFound(4))
+            ));
+
+            let transaction = storage
+                .get_confirmed_transaction(&signature(2))
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(transaction.slot, 2);
+            assert_eq!(
+                transaction.transaction.transaction.signatures[0],
+                signature(2)
+            );
+            assert!(matches!(
+                storage.get_signature_status(&Signature::default()).await,
+                Err(Error::SignatureNotFound)
+            ));
+
+            let signatures = |infos: Vec<(ConfirmedTransactionStatusWithSignature, u32)>| {
+                infos
+                    .into_iter()
+                    .map(|(info, _)| info.signature)
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(
+                signatures(
+                    storage
+                        .get_confirmed_signatures_for_address(&payer.pubkey(), None, None, 10)
+                        .await
+                        .unwrap()
+                ),
+                vec![signature(3), signature(2), signature(1)]
+            );
+            assert_eq!(
+                signatures(
+                    storage
+                        .get_confirmed_signatures_for_address(
+                            &payer.pubkey(),
+                            Some(&signature(3)),
+                            None,
+                            1
+                        )
+                        .await
+                        .unwrap()
+                ),
+                vec![signature(2)]
+            );
+        });
+    }
+}
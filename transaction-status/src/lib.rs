@@ -485,7 +485,7 @@ impl From<UiConfirmedBlock> for EncodedConfirmedBlock {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum TransactionDetails {
     Full,
@@ -569,7 +569,38 @@ pub struct TransactionWithStatusMeta {
 }
 
 impl TransactionWithStatusMeta {
+    /// Whether `program_id` is invoked by one of the transaction's instructions or, when the
+    /// status meta records them, by one of its inner instructions
+    pub fn invokes_program(&self, program_id: &Pubkey) -> bool {
+        let account_keys = &self.transaction.message.account_keys;
+        let is_invoked = |instruction: &CompiledInstruction| {
+            account_keys.get(instruction.program_id_index as usize) == Some(program_id)
+        };
+        self.transaction.message.instructions.iter().any(is_invoked)
+            || self
+                .meta
+                .as_ref()
+                .and_then(|meta| meta.inner_instructions.as_ref())
+                .map(|inner_instructions| {
+                    inner_instructions
+                        .iter()
+                        .flat_map(|inner| &inner.instructions)
+                        .any(is_invoked)
+                })
+                .unwrap_or(false)
+    }
+
     fn encode(self, encoding: UiTransactionEncoding) -> EncodedTransactionWithStatusMeta {
+        if encoding == UiTransactionEncoding::Base64Bincode {
+            // The status meta is serialized along with the transaction
+            return EncodedTransactionWithStatusMeta {
+                transaction: EncodedTransaction::Binary(
+                    base64::encode(bincode::serialize(&self).unwrap()),
+                    encoding,
+                ),
+                meta: None,
+            };
+        }
         let message = self.transaction.message();
         let meta = self.meta.map(|meta| meta.encode(encoding, message));
         EncodedTransactionWithStatusMeta {
@@ -595,7 +626,7 @@ impl TransactionStatusMeta {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[serde(rename_all = "camelCase")]
 pub enum UiTransactionEncoding {
     Binary, // Legacy. Retained for RPC backwards compatibility
@@ -603,6 +634,11 @@ pub enum UiTransactionEncoding {
     Base58,
     Json,
     JsonParsed,
+    /// A `TransactionWithStatusMeta` serialized with bincode, so that the status meta comes
+    /// with the transaction. Fields are only ever appended to the status meta, and default
+    /// when deserializing older data.
+    #[serde(rename = "base64+bincode")]
+    Base64Bincode,
 }
 
 impl fmt::Display for UiTransactionEncoding {
@@ -635,6 +671,16 @@ impl EncodedTransaction {
                 base64::encode(bincode::serialize(&transaction).unwrap()),
                 encoding,
             ),
+            UiTransactionEncoding::Base64Bincode => EncodedTransaction::Binary(
+                base64::encode(
+                    bincode::serialize(&TransactionWithStatusMeta {
+                        transaction,
+                        meta: None,
+                    })
+                    .unwrap(),
+                ),
+                encoding,
+            ),
             UiTransactionEncoding::Json | UiTransactionEncoding::JsonParsed => {
                 let message = if encoding == UiTransactionEncoding::Json {
                     UiMessage::Raw(UiRawMessage {
@@ -693,6 +739,9 @@ impl EncodedTransaction {
                 UiTransactionEncoding::Base64 => base64::decode(blob)
                     .ok()
                     .and_then(|bytes| bincode::deserialize(&bytes).ok()),
+                UiTransactionEncoding::Base64Bincode => self
+                    .decode_with_status_meta()
+                    .map(|transaction_with_meta| transaction_with_meta.transaction),
                 UiTransactionEncoding::Binary
                 | UiTransactionEncoding::Json
                 | UiTransactionEncoding::JsonParsed => None,
@@ -700,6 +749,18 @@ impl EncodedTransaction {
         };
         transaction.filter(|transaction| transaction.sanitize().is_ok())
     }
+
+    /// Decodes a transaction encoded with "base64+bincode" along with its status meta
+    pub fn decode_with_status_meta(&self) -> Option<TransactionWithStatusMeta> {
+        match self {
+            EncodedTransaction::Binary(blob, UiTransactionEncoding::Base64Bincode) => {
+                base64::decode(blob)
+                    .ok()
+                    .and_then(|bytes| bincode::deserialize(&bytes).ok())
+            }
+            _ => None,
+        }
+    }
 }
 
 // A serialized `Vec<TransactionByAddrInfo>` is stored in the `tx-by-addr` table.  The row keys are
@@ -715,7 +776,15 @@ pub struct TransactionByAddrInfo {
 
 #[cfg(test)]
 mod test {
-    use super::*;
+    use {
+        super::*,
+        solana_account_decoder::parse_token::spl_token_2022_id,
+        solana_sdk::{
+            hash::Hash,
+            instruction::{AccountMeta, Instruction},
+            system_instruction, system_program,
+        },
+    };
 
     #[test]
     fn test_decode_invalid_transaction() {
@@ -731,6 +800,197 @@ mod test {
         assert!(unsanitary_transaction.decode().is_none());
     }
 
+    #[test]
+    fn test_encode_inner_instructions() {
+        let payer = Pubkey::new_unique();
+        let recipient = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let instruction = Instruction::new_with_bytes(
+            program_id,
+            &[],
+            vec![
+                AccountMeta::new(recipient, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new_readonly(spl_token_2022_id(), false),
+            ],
+        );
+        let message = Message::new(&[instruction], Some(&payer));
+        let index_of = |pubkey| {
+            message
+                .account_keys
+                .iter()
+                .position(|key| key == pubkey)
+                .unwrap() as u8
+        };
+        let transfer = system_instruction::transfer(&payer, &recipient, 42);
+        let inner_instructions = vec![
+            CompiledInstruction {
+                program_id_index: index_of(&system_program::id()),
+                accounts: vec![index_of(&payer), index_of(&recipient)],
+                data: transfer.data,
+            },
+            // Token-2022 ThawAccount
+            CompiledInstruction {
+                program_id_index: index_of(&spl_token_2022_id()),
+                accounts: vec![index_of(&recipient), index_of(&recipient), index_of(&payer)],
+                data: vec![11],
+            },
+            // Unknown to the parsers
+            CompiledInstruction {
+                program_id_index: index_of(&program_id),
+                accounts: vec![index_of(&recipient)],
+                data: vec![1, 2, 3],
+            },
+        ];
+        let transaction_with_meta = TransactionWithStatusMeta {
+            transaction: Transaction::new_unsigned(message),
+            meta: Some(TransactionStatusMeta {
+                inner_instructions: Some(vec![InnerInstructions {
+                    index: 0,
+                    instructions: inner_instructions,
+                }]),
+                ..TransactionStatusMeta::default()
+            }),
+        };
+
+        let encoded = transaction_with_meta
+            .clone()
+            .encode(UiTransactionEncoding::JsonParsed);
+        let instructions = &encoded.meta.unwrap().inner_instructions.unwrap()[0].instructions;
+        let program_names: Vec<_> = instructions
+            .iter()
+            .map(|instruction| match instruction {
+                UiInstruction::Parsed(UiParsedInstruction::Parsed(parsed)) => {
+                    (parsed.program.as_str(), parsed.program_id.clone())
+                }
+                UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(decoded)) => {
+                    ("", decoded.program_id.clone())
+                }
+                UiInstruction::Compiled(_) => panic!("inner instruction not decoded"),
+            })
+            .collect();
+        assert_eq!(
+            program_names,
+            vec![
+                ("system", system_program::id().to_string()),
+                ("spl-token", spl_token_2022_id().to_string()),
+                ("", program_id.to_string()),
+            ]
+        );
+
+        let encoded = transaction_with_meta.encode(UiTransactionEncoding::Json);
+        assert!(encoded.meta.unwrap().inner_instructions.unwrap()[0]
+            .instructions
+            .iter()
+            .all(|instruction| matches!(instruction, UiInstruction::Compiled(_))));
+    }
+
+    #[test]
+    fn test_encode_base64_bincode() {
+        let payer = Pubkey::new_unique();
+        let transfer = system_instruction::transfer(&payer, &Pubkey::new_unique(), 42);
+        let transaction = Transaction::new_unsigned(Message::new(&[transfer], Some(&payer)));
+        let transaction_with_meta = TransactionWithStatusMeta {
+            transaction: transaction.clone(),
+            meta: Some(TransactionStatusMeta {
+                fee: 5000,
+                pre_balances: vec![100_000, 0, 1],
+                post_balances: vec![94_958, 42, 1],
+                log_messages: Some(vec!["Program log: transfer".to_string()]),
+                rewards: Some(vec![Reward {
+                    pubkey: payer.to_string(),
+                    lamports: 7,
+                    post_balance: 94_958,
+                    reward_type: Some(RewardType::Fee),
+                    commission: None,
+                }]),
+                ..TransactionStatusMeta::default()
+            }),
+        };
+
+        let encoded = transaction_with_meta
+            .clone()
+            .encode(UiTransactionEncoding::Base64Bincode);
+        assert!(encoded.meta.is_none());
+        let decoded = encoded.transaction.decode_with_status_meta().unwrap();
+        assert_eq!(decoded, transaction_with_meta);
+        assert_eq!(encoded.transaction.decode(), Some(transaction.clone()));
+        // Decodes to the same response as the JSON encodings
+        for encoding in [
+            UiTransactionEncoding::Json,
+            UiTransactionEncoding::JsonParsed,
+        ] {
+            assert_eq!(
+                decoded.clone().encode(encoding),
+                transaction_with_meta.clone().encode(encoding)
+            );
+        }
+
+        let encoded =
+            EncodedTransaction::encode(transaction.clone(), UiTransactionEncoding::Base64Bincode);
+        assert_eq!(
+            encoded.decode_with_status_meta(),
+            Some(TransactionWithStatusMeta {
+                transaction: transaction.clone(),
+                meta: None,
+            })
+        );
+        assert_eq!(encoded.decode(), Some(transaction.clone()));
+        assert_eq!(
+            EncodedTransaction::encode(transaction, UiTransactionEncoding::Base64)
+                .decode_with_status_meta(),
+            None
+        );
+
+        // Status metas serialized before the appended fields existed still decode
+        let mut legacy_bytes = bincode::serialize(&TransactionWithStatusMeta {
+            meta: transaction_with_meta
+                .meta
+                .clone()
+                .map(|meta| TransactionStatusMeta {
+                    rewards: None,
+                    ..meta
+                }),
+            ..transaction_with_meta.clone()
+        })
+        .unwrap();
+        // Drop the trailing `None` of `rewards`
+        legacy_bytes.pop();
+        let legacy = EncodedTransaction::Binary(
+            base64::encode(legacy_bytes),
+            UiTransactionEncoding::Base64Bincode,
+        );
+        assert_eq!(
+            legacy
+                .decode_with_status_meta()
+                .unwrap()
+                .meta
+                .unwrap()
+                .rewards,
+            None
+        );
+
+        let block = ConfirmedBlock {
+            previous_blockhash: Hash::default().to_string(),
+            blockhash: Hash::new_unique().to_string(),
+            parent_slot: 0,
+            transactions: vec![transaction_with_meta.clone()],
+            rewards: vec![],
+            block_time: None,
+            block_height: None,
+        };
+        let encoded_block = block.configure(
+            UiTransactionEncoding::Base64Bincode,
+            TransactionDetails::Full,
+            false,
+        );
+        let transactions = encoded_block.transactions.unwrap();
+        assert_eq!(
+            transactions[0].transaction.decode_with_status_meta(),
+            Some(transaction_with_meta)
+        );
+    }
+
     #[test]
     fn test_satisfies_commitment() {
         let status = TransactionStatus {
@@ -0,0 +1,31 @@
+//! Loading the account parsers of the `jsonParsed` encoding from dynamic libraries
+
+use {
+    libloading::{Library, Symbol},
+    log::*,
+    solana_account_decoder::parse_account_data::{register_account_parser, AccountParser},
+    solana_sdk::pubkey::Pubkey,
+    std::error::Error,
+};
+
+/// # Safety
+///
+/// This function loads the dynamically linked library specified in the path, which must export
+/// a `C` function `_create_account_parser` returning a boxed `AccountParser` as a raw pointer.
+/// The library is never unloaded, since the parser stays registered for the life of the
+/// process.
+pub unsafe fn load_account_parser(program_id: Pubkey, libpath: &str) -> Result<(), Box<dyn Error>> {
+    type ParserConstructor = unsafe fn() -> *mut dyn AccountParser;
+    let lib = Library::new(libpath)?;
+    let constructor: Symbol<ParserConstructor> = lib.get(b"_create_account_parser")?;
+    let parser = Box::from_raw(constructor());
+    info!(
+        "Parsing the accounts of program {} as {:?}, with {}",
+        program_id,
+        parser.program_name(),
+        libpath
+    );
+    register_account_parser(program_id, parser.into())?;
+    std::mem::forget(lib);
+    Ok(())
+}
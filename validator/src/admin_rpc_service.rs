@@ -5,16 +5,20 @@ use {
     jsonrpc_ipc_server::{RequestContext, ServerBuilder},
     jsonrpc_server_utils::tokio,
     log::*,
+    serde_derive::{Deserialize, Serialize},
     solana_core::{
         consensus::Tower, tower_storage::TowerStorage, validator::ValidatorStartProgress,
     },
     solana_gossip::cluster_info::ClusterInfo,
+    solana_rpc::{rpc_pubsub_service::PubSubDrain, rpc_subscription_tracker::SubscriptionControl},
     solana_sdk::{
+        clock::Slot,
+        commitment_config::CommitmentConfig,
         exit::Exit,
         signature::{read_keypair_file, Keypair, Signer},
     },
     std::{
-        net::SocketAddr,
+        net::{IpAddr, SocketAddr},
         path::{Path, PathBuf},
         sync::{Arc, RwLock},
         thread::{self, Builder},
@@ -31,9 +35,36 @@ pub struct AdminRpcRequestMetadata {
     pub authorized_voter_keypairs: Arc<RwLock<Vec<Arc<Keypair>>>>,
     pub cluster_info: Arc<RwLock<Option<Arc<ClusterInfo>>>>,
     pub tower_storage: Arc<dyn TowerStorage>,
+    pub rpc_pubsub_drain: Arc<PubSubDrain>,
+    pub subscription_control: Arc<RwLock<Option<SubscriptionControl>>>,
 }
 impl Metadata for AdminRpcRequestMetadata {}
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminRpcSubscription {
+    pub id: u64,
+    pub method: String,
+    /// Debug representation of the subscription parameters
+    pub params: String,
+    pub commitment: Option<CommitmentConfig>,
+    pub age_secs: u64,
+    pub last_notified_slot: Option<Slot>,
+    pub notifications: u64,
+    pub bytes: u64,
+    /// Notifications skipped by lagging clients
+    pub lag_drops: u64,
+    pub clients: Vec<AdminRpcSubscriptionClient>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminRpcSubscriptionClient {
+    pub ip: IpAddr,
+    /// Number of times the client subscribed to the subscription
+    pub count: usize,
+}
+
 #[rpc]
 pub trait AdminRpc {
     type Metadata;
@@ -41,6 +72,14 @@ pub trait AdminRpc {
     #[rpc(meta, name = "exit")]
     fn exit(&self, meta: Self::Metadata) -> Result<()>;
 
+    #[rpc(meta, name = "drainRpc")]
+    fn drain_rpc(
+        &self,
+        meta: Self::Metadata,
+        drain_secs: u64,
+        reconnect_delay_secs: u64,
+    ) -> Result<()>;
+
     #[rpc(meta, name = "rpcAddress")]
     fn rpc_addr(&self, meta: Self::Metadata) -> Result<Option<SocketAddr>>;
 
@@ -61,6 +100,9 @@ pub trait AdminRpc {
 
     #[rpc(meta, name = "setIdentity")]
     fn set_identity(&self, meta: Self::Metadata, keypair_file: String) -> Result<()>;
+
+    #[rpc(meta, name = "listSubscriptions")]
+    fn list_subscriptions(&self, meta: Self::Metadata) -> Result<Vec<AdminRpcSubscription>>;
 }
 
 pub struct AdminRpcImpl;
@@ -89,6 +131,26 @@ impl AdminRpc for AdminRpcImpl {
         Ok(())
     }
 
+    fn drain_rpc(
+        &self,
+        meta: Self::Metadata,
+        drain_secs: u64,
+        reconnect_delay_secs: u64,
+    ) -> Result<()> {
+        debug!("drain_rpc admin rpc request received");
+
+        warn!("rpc pubsub drain requested, exiting in {}s", drain_secs);
+        meta.rpc_pubsub_drain
+            .start(Duration::from_secs(reconnect_delay_secs));
+        thread::spawn(move || {
+            // Existing subscriptions keep receiving notifications while clients reconnect
+            // elsewhere
+            thread::sleep(Duration::from_secs(drain_secs));
+            let _ = AdminRpcImpl.exit(meta);
+        });
+        Ok(())
+    }
+
     fn rpc_addr(&self, meta: Self::Metadata) -> Result<Option<SocketAddr>> {
         debug!("rpc_addr admin rpc request received");
         Ok(meta.rpc_addr)
@@ -167,6 +229,35 @@ impl AdminRpc for AdminRpcImpl {
             ))
         }
     }
+
+    fn list_subscriptions(&self, meta: Self::Metadata) -> Result<Vec<AdminRpcSubscription>> {
+        debug!("list_subscriptions request received");
+
+        let subscription_control = meta.subscription_control.read().unwrap();
+        let subscription_control = subscription_control.as_ref().ok_or_else(|| {
+            jsonrpc_core::error::Error::invalid_params("Retry once validator start up is complete")
+        })?;
+        Ok(subscription_control
+            .active_subscriptions()
+            .into_iter()
+            .map(|subscription| AdminRpcSubscription {
+                id: subscription.id.into(),
+                method: subscription.method.to_string(),
+                params: format!("{:?}", subscription.params),
+                commitment: subscription.commitment,
+                age_secs: subscription.age.as_secs(),
+                last_notified_slot: subscription.last_notified_slot,
+                notifications: subscription.notifications,
+                bytes: subscription.bytes,
+                lag_drops: subscription.lag_drops,
+                clients: subscription
+                    .clients
+                    .into_iter()
+                    .map(|(ip, count)| AdminRpcSubscriptionClient { ip, count })
+                    .collect(),
+            })
+            .collect())
+    }
 }
 
 // Start the Admin RPC interface
@@ -516,6 +516,7 @@ fn main() {
     let tower_storage = Arc::new(FileTowerStorage::new(ledger_path.clone()));
 
     let admin_service_cluster_info = Arc::new(RwLock::new(None));
+    let admin_service_subscription_control = Arc::new(RwLock::new(None));
     admin_rpc_service::run(
         &ledger_path,
         admin_rpc_service::AdminRpcRequestMetadata {
@@ -529,6 +530,8 @@ fn main() {
             authorized_voter_keypairs: genesis.authorized_voter_keypairs.clone(),
             cluster_info: admin_service_cluster_info.clone(),
             tower_storage: tower_storage.clone(),
+            rpc_pubsub_drain: genesis.rpc_pubsub_drain.clone(),
+            subscription_control: admin_service_subscription_control.clone(),
         },
     );
     let dashboard = if output == Output::Dashboard {
@@ -603,6 +606,8 @@ fn main() {
     match genesis.start_with_mint_address(mint_address, socket_addr_space) {
         Ok(test_validator) => {
             *admin_service_cluster_info.write().unwrap() = Some(test_validator.cluster_info());
+            *admin_service_subscription_control.write().unwrap() =
+                Some(test_validator.subscription_control());
             if let Some(dashboard) = dashboard {
                 dashboard.run(Duration::from_millis(250));
             }
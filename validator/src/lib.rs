@@ -16,6 +16,7 @@ use {
     },
 };
 
+pub mod account_parsers;
 pub mod admin_rpc_service;
 pub mod bootstrap;
 pub mod dashboard;
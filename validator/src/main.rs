@@ -11,7 +11,7 @@ use {
         input_parsers::{keypair_of, keypairs_of, pubkey_of, value_of},
         input_validators::{
             is_keypair, is_keypair_or_ask_keyword, is_parsable, is_pow2, is_pubkey,
-            is_pubkey_or_keypair, is_slot, is_valid_percentage,
+            is_pubkey_or_keypair, is_slot, is_url, is_valid_percentage,
         },
         keypair::SKIP_SEED_PHRASE_VALIDATION_ARG,
     },
@@ -29,12 +29,20 @@ use {
         cluster_info::{Node, VALIDATOR_PORT_RANGE},
         contact_info::ContactInfo,
     },
-    solana_ledger::blockstore_db::BlockstoreRecoveryMode,
+    solana_ledger::{
+        bigtable_upload::{ConfirmedBlockUploadConfig, EXCLUDABLE_UPLOAD_DATA},
+        blockstore_db::BlockstoreRecoveryMode,
+    },
     solana_metrics::datapoint_info,
     solana_perf::recycler::enable_recycler_warming,
     solana_poh::poh_service,
     solana_replica_lib::accountsdb_repl_server::AccountsDbReplServiceConfig,
-    solana_rpc::{rpc::JsonRpcConfig, rpc_pubsub_service::PubSubConfig},
+    solana_rpc::{
+        rpc::JsonRpcConfig,
+        rpc_pubsub_auth::ApiKeys,
+        rpc_pubsub_service::{PubSubConfig, SlowConsumerPolicy},
+        rpc_tls::TlsConfig,
+    },
     solana_runtime::{
         accounts_db::{
             AccountShrinkThreshold, AccountsDbConfig, DEFAULT_ACCOUNTS_SHRINK_OPTIMIZE_TOTAL_SPACE,
@@ -61,12 +69,14 @@ use {
         signature::{Keypair, Signer},
     },
     solana_send_transaction_service::send_transaction_service,
+    solana_storage_bigtable::ObjectStoreConfig,
     solana_streamer::socket::SocketAddrSpace,
     solana_validator::{
-        admin_rpc_service, bootstrap, dashboard::Dashboard, ledger_lockfile, lock_ledger,
-        new_spinner_progress_bar, println_name_value, redirect_stderr_to_file,
+        account_parsers, admin_rpc_service, bootstrap, dashboard::Dashboard, ledger_lockfile,
+        lock_ledger, new_spinner_progress_bar, println_name_value, redirect_stderr_to_file,
     },
     std::{
+        cmp::Reverse,
         collections::{HashSet, VecDeque},
         env,
         fs::{self, File},
@@ -358,6 +368,15 @@ fn hash_validator(hash: String) -> Result<(), String> {
         .map_err(|e| format!("{:?}", e))
 }
 
+fn is_account_parser(account_parser: String) -> Result<(), String> {
+    let (program_id, _libpath) = account_parser
+        .split_once(':')
+        .ok_or_else(|| format!("expected PROGRAM_ID:LIBRARY, got {}", account_parser))?;
+    Pubkey::from_str(program_id)
+        .map(|_| ())
+        .map_err(|e| format!("{:?}", e))
+}
+
 // This function is duplicated in ledger-tool/src/main.rs...
 fn hardforks_of(matches: &ArgMatches<'_>, name: &str) -> Option<Vec<Slot>> {
     if matches.is_present(name) {
@@ -501,10 +520,39 @@ pub fn main() {
 
     let default_rpc_pubsub_max_active_subscriptions =
         PubSubConfig::default().max_active_subscriptions.to_string();
+    let default_rpc_pubsub_max_subscriptions_per_connection = PubSubConfig::default()
+        .max_subscriptions_per_connection
+        .to_string();
+    let default_rpc_pubsub_max_subscriptions_per_ip =
+        PubSubConfig::default().max_subscriptions_per_ip.to_string();
+    let default_rpc_pubsub_max_webhooks = PubSubConfig::default().max_webhooks.to_string();
+    let default_rpc_grpc_max_streams = PubSubConfig::default().max_grpc_streams.to_string();
+    let default_rpc_pubsub_keepalive_interval_secs = PubSubConfig::default()
+        .keepalive_interval
+        .as_secs()
+        .to_string();
+    let default_rpc_pubsub_idle_timeout_secs =
+        PubSubConfig::default().idle_timeout.as_secs().to_string();
+    let default_rpc_pubsub_max_accounts_per_subscription = PubSubConfig::default()
+        .max_accounts_per_subscription
+        .to_string();
     let default_rpc_pubsub_queue_capacity_items =
         PubSubConfig::default().queue_capacity_items.to_string();
     let default_rpc_pubsub_queue_capacity_bytes =
         PubSubConfig::default().queue_capacity_bytes.to_string();
+    let default_rpc_pubsub_firehose_queue_capacity_items = PubSubConfig::default()
+        .firehose_queue_capacity_items
+        .to_string();
+    let default_rpc_pubsub_signature_notification_retention_secs = PubSubConfig::default()
+        .signature_notification_retention
+        .as_secs()
+        .to_string();
+    let default_rpc_pubsub_max_memory_bytes = PubSubConfig::default().max_memory_bytes.to_string();
+    let default_rpc_pubsub_notification_threads =
+        PubSubConfig::default().notification_threads.to_string();
+    let default_rpc_pubsub_notification_queue_capacity = PubSubConfig::default()
+        .notification_queue_capacity
+        .to_string();
     let default_send_transaction_service_config = send_transaction_service::Config::default();
     let default_rpc_send_transaction_retry_ms = default_send_transaction_service_config
         .retry_rate_ms
@@ -516,6 +564,7 @@ pub fn main() {
         .service_max_retries
         .to_string();
     let default_rpc_threads = num_cpus::get().to_string();
+    let default_rpc_scan_threads = 1.max(num_cpus::get() / 4).to_string();
     let default_accountsdb_repl_threads = num_cpus::get().to_string();
     let default_maximum_full_snapshot_archives_to_retain =
         &DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN.to_string();
@@ -662,12 +711,142 @@ pub fn main() {
                 .validator(solana_validator::port_validator)
                 .help("Enable JSON RPC on this port, and the next port for the RPC websocket"),
         )
+        .arg(
+            Arg::with_name("rpc_grpc_port")
+                .long("rpc-grpc-port")
+                .value_name("PORT")
+                .takes_value(true)
+                .requires("rpc_port")
+                .validator(solana_validator::port_validator)
+                .help("Also serve the RPC PubSub subscriptions over gRPC on this port"),
+        )
+        .arg(
+            Arg::with_name("rpc_grpc_max_streams")
+                .long("rpc-grpc-max-streams")
+                .takes_value(true)
+                .value_name("NUMBER")
+                .validator(is_parsable::<usize>)
+                .default_value(&default_rpc_grpc_max_streams)
+                .help("The maximum number of subscription streams open at once on the \
+                       gRPC port"),
+        )
+        .arg(
+            Arg::with_name("rpc_sse_port")
+                .long("rpc-sse-port")
+                .value_name("PORT")
+                .takes_value(true)
+                .requires("rpc_port")
+                .validator(solana_validator::port_validator)
+                .help("Also serve the RPC PubSub subscriptions as server-sent events on this port"),
+        )
+        .arg(
+            Arg::with_name("rpc_pubsub_max_webhooks")
+                .long("rpc-pubsub-max-webhooks")
+                .takes_value(true)
+                .value_name("NUMBER")
+                .validator(is_parsable::<usize>)
+                .default_value(&default_rpc_pubsub_max_webhooks)
+                .help("The maximum number of webhooks that each API key may register through \
+                       the server-sent events port. Zero disables webhooks. Webhooks also \
+                       require --rpc-pubsub-api-keys."),
+        )
+        .arg(
+            Arg::with_name("rpc_unix_socket")
+                .long("rpc-unix-socket")
+                .value_name("PATH")
+                .takes_value(true)
+                .requires("rpc_port")
+                .help("Also serve JSON RPC over HTTP on this unix socket"),
+        )
+        .arg(
+            Arg::with_name("rpc_pubsub_unix_socket")
+                .long("rpc-pubsub-unix-socket")
+                .value_name("PATH")
+                .takes_value(true)
+                .requires("rpc_port")
+                .help("Also accept RPC PubSub websocket connections on this unix socket"),
+        )
+        .arg(
+            Arg::with_name("rpc_pubsub_api_keys")
+                .long("rpc-pubsub-api-keys")
+                .value_name("FILE")
+                .takes_value(true)
+                .requires("rpc_port")
+                .help("Only accept RPC PubSub connections with one of the API keys in this \
+                       JSON file. Changes to the file apply without a restart"),
+        )
+        .arg(
+            Arg::with_name("account_parser")
+                .long("account-parser")
+                .value_name("PROGRAM_ID:LIBRARY")
+                .takes_value(true)
+                .multiple(true)
+                .validator(is_account_parser)
+                .help("Parse the accounts of PROGRAM_ID in the jsonParsed encoding with the \
+                       parser of this dynamic library, which exports a _create_account_parser \
+                       function"),
+        )
+        .arg(
+            Arg::with_name("rpc_tls_cert")
+                .long("rpc-tls-cert")
+                .value_name("FILE")
+                .takes_value(true)
+                .requires_all(&["rpc_port", "rpc_tls_key"])
+                .help("Serve JSON RPC over HTTPS and RPC PubSub over secure websockets, \
+                       with the PEM encoded certificate chain in this file"),
+        )
+        .arg(
+            Arg::with_name("rpc_tls_key")
+                .long("rpc-tls-key")
+                .value_name("FILE")
+                .takes_value(true)
+                .requires("rpc_tls_cert")
+                .help("The PEM encoded private key of --rpc-tls-cert"),
+        )
+        .arg(
+            Arg::with_name("rpc_http_compression_min_size")
+                .long("rpc-http-compression-min-size")
+                .value_name("BYTES")
+                .takes_value(true)
+                .validator(is_parsable::<usize>)
+                .requires("rpc_port")
+                .help("Compress JSON RPC responses of at least this many bytes with zstd or \
+                       gzip, for clients that accept either in their Accept-Encoding header \
+                       [default: responses aren't compressed]"),
+        )
+        .arg(
+            Arg::with_name("rpc_rate_limits")
+                .long("rpc-rate-limits")
+                .value_name("FILE")
+                .takes_value(true)
+                .requires("rpc_port")
+                .help("Rate limit JSON RPC requests by client IP address, with the method \
+                       costs and token bucket sizes in this JSON file"),
+        )
+        .arg(
+            Arg::with_name("rpc_slow_request_threshold_ms")
+                .long("rpc-slow-request-threshold-ms")
+                .value_name("MILLISECONDS")
+                .takes_value(true)
+                .validator(is_parsable::<u64>)
+                .requires("rpc_port")
+                .help("Log JSON RPC requests that take at least this long, along with the time \
+                       they spent picking a bank, scanning accounts and serializing the result"),
+        )
         .arg(
             Arg::with_name("minimal_rpc_api")
                 .long("--minimal-rpc-api")
                 .takes_value(false)
                 .help("Only expose the RPC methods required to serve snapshots to other nodes"),
         )
+        .arg(
+            Arg::with_name("rpc_finalized_only")
+                .long("rpc-finalized-only")
+                .takes_value(false)
+                .help("Serve JSON RPC reads and RPC PubSub subscriptions at finalized \
+                       commitment only. Non-finalized subscriptions are refused, which \
+                       lets the node skip processed and confirmed notification work"),
+        )
         .arg(
             Arg::with_name("obsolete_v1_7_rpc_api")
                 .long("--enable-rpc-obsolete_v1_7")
@@ -709,6 +888,48 @@ pub fn main() {
                 .takes_value(false)
                 .help("Upload new confirmed blocks into a BigTable instance"),
         )
+        .arg(
+            Arg::with_name("bigtable_upload_exclude")
+                .long("bigtable-upload-exclude")
+                .requires("enable_bigtable_ledger_upload")
+                .takes_value(true)
+                .multiple(true)
+                .value_name("DATA")
+                .possible_values(EXCLUDABLE_UPLOAD_DATA)
+                .help("Leave this data out of the blocks uploaded to BigTable"),
+        )
+        .arg(
+            Arg::with_name("rpc_object_store_endpoint")
+                .long("rpc-object-store-endpoint")
+                .value_name("URL")
+                .takes_value(true)
+                .validator(is_url)
+                .requires_all(&["enable_rpc_transaction_history", "rpc_object_store_bucket"])
+                .conflicts_with_all(&[
+                    "enable_rpc_bigtable_ledger_storage",
+                    "enable_bigtable_ledger_upload",
+                ])
+                .help("Fetch historical transaction info from this S3-compatible object store \
+                       as a fallback to local ledger data. Credentials are read from the \
+                       AWS_ACCESS_KEY_ID, AWS_SECRET_ACCESS_KEY and AWS_SESSION_TOKEN \
+                       environment variables, the AWS credentials file or the instance role, \
+                       and the region from AWS_REGION"),
+        )
+        .arg(
+            Arg::with_name("rpc_object_store_bucket")
+                .long("rpc-object-store-bucket")
+                .value_name("NAME")
+                .takes_value(true)
+                .requires("rpc_object_store_endpoint")
+                .help("Bucket of the object store holding the ledger history"),
+        )
+        .arg(
+            Arg::with_name("enable_object_store_ledger_upload")
+                .long("enable-object-store-ledger-upload")
+                .requires("rpc_object_store_endpoint")
+                .takes_value(false)
+                .help("Upload new confirmed blocks into the object store"),
+        )
         .arg(
             Arg::with_name("enable_cpi_and_log_storage")
                 .long("enable-cpi-and-log-storage")
@@ -1170,6 +1391,17 @@ pub fn main() {
                 .default_value(&default_rpc_threads)
                 .help("Number of threads to use for servicing RPC requests"),
         )
+        .arg(
+            Arg::with_name("rpc_scan_threads")
+                .long("rpc-scan-threads")
+                .value_name("NUMBER")
+                .validator(is_parsable::<usize>)
+                .takes_value(true)
+                .default_value(&default_rpc_scan_threads)
+                .help("Number of threads to use for the RPC methods that scan accounts, \
+                       getProgramAccounts, getLargestAccounts and getSupply, apart from \
+                       --rpc-threads"),
+        )
         .arg(
             Arg::with_name("rpc_bigtable_timeout")
                 .long("rpc-bigtable-timeout")
@@ -1179,12 +1411,54 @@ pub fn main() {
                 .default_value("30")
                 .help("Number of seconds before timing out RPC requests backed by BigTable"),
         )
+        .arg(
+            Arg::with_name("rpc_program_accounts_cache_secs")
+                .long("rpc-program-accounts-cache-secs")
+                .value_name("SECONDS")
+                .validator(is_parsable::<u64>)
+                .takes_value(true)
+                .help("Reuse the results of getProgramAccounts scans for this many seconds, \
+                       so that paging through, sorting or counting the same accounts \
+                       doesn't scan them again [default: scans aren't reused]"),
+        )
+        .arg(
+            Arg::with_name("rpc_largest_accounts_refresh_secs")
+                .long("rpc-largest-accounts-refresh-secs")
+                .value_name("SECONDS")
+                .validator(is_parsable::<u64>)
+                .takes_value(true)
+                .help("Recompute the results of getLargestAccounts in the background this \
+                       often, at the finalized commitment, and always serve them from the \
+                       cache. Other commitments are rejected [default: results are \
+                       recomputed on demand once they are two hours old]"),
+        )
+        .arg(
+            Arg::with_name("rpc_pubsub_enable_block_subscription")
+                .long("rpc-pubsub-enable-block-subscription")
+                .requires("enable_rpc_transaction_history")
+                .takes_value(false)
+                .help("Enable the unstable RPC PubSub `blockSubscribe` subscription"),
+        )
+        .arg(
+            Arg::with_name("rpc_pubsub_enable_transaction_subscription")
+                .long("rpc-pubsub-enable-transaction-subscription")
+                .requires("enable_rpc_transaction_history")
+                .takes_value(false)
+                .help("Enable the unstable RPC PubSub `transactionSubscribe` subscription"),
+        )
         .arg(
             Arg::with_name("rpc_pubsub_enable_vote_subscription")
                 .long("rpc-pubsub-enable-vote-subscription")
                 .takes_value(false)
                 .help("Enable the unstable RPC PubSub `voteSubscribe` subscription"),
         )
+        .arg(
+            Arg::with_name("rpc_pubsub_enable_compression")
+                .long("rpc-pubsub-enable-compression")
+                .takes_value(false)
+                .help("Compress RPC PubSub messages for clients that support the \
+                       permessage-deflate websocket extension"),
+        )
         .arg(
             Arg::with_name("rpc_pubsub_max_connections")
                 .long("rpc-pubsub-max-connections")
@@ -1236,6 +1510,36 @@ pub fn main() {
                 .help("The maximum number of active subscriptions that RPC PubSub will accept \
                        across all connections."),
         )
+        .arg(
+            Arg::with_name("rpc_pubsub_max_subscriptions_per_connection")
+                .long("rpc-pubsub-max-subscriptions-per-connection")
+                .takes_value(true)
+                .value_name("NUMBER")
+                .validator(is_parsable::<usize>)
+                .default_value(&default_rpc_pubsub_max_subscriptions_per_connection)
+                .help("The maximum number of active subscriptions that a single RPC PubSub \
+                       connection may hold."),
+        )
+        .arg(
+            Arg::with_name("rpc_pubsub_max_subscriptions_per_ip")
+                .long("rpc-pubsub-max-subscriptions-per-ip")
+                .takes_value(true)
+                .value_name("NUMBER")
+                .validator(is_parsable::<usize>)
+                .default_value(&default_rpc_pubsub_max_subscriptions_per_ip)
+                .help("The maximum number of active subscriptions that all RPC PubSub \
+                       connections from the same IP address may hold together."),
+        )
+        .arg(
+            Arg::with_name("rpc_pubsub_max_accounts_per_subscription")
+                .long("rpc-pubsub-max-accounts-per-subscription")
+                .takes_value(true)
+                .value_name("NUMBER")
+                .validator(is_parsable::<usize>)
+                .default_value(&default_rpc_pubsub_max_accounts_per_subscription)
+                .help("The maximum number of accounts that a single RPC PubSub \
+                       `accountsSubscribe` request may track."),
+        )
         .arg(
             Arg::with_name("rpc_pubsub_queue_capacity_items")
                 .long("rpc-pubsub-queue-capacity-items")
@@ -1256,6 +1560,91 @@ pub fn main() {
                 .help("The maximum total size of notifications that RPC PubSub will store \
                        across all connections."),
         )
+        .arg(
+            Arg::with_name("rpc_pubsub_firehose_queue_capacity_items")
+                .long("rpc-pubsub-firehose-queue-capacity-items")
+                .takes_value(true)
+                .value_name("NUMBER")
+                .validator(is_parsable::<usize>)
+                .default_value(&default_rpc_pubsub_firehose_queue_capacity_items)
+                .help("The maximum number of vote and slot notifications that RPC PubSub \
+                       will store across all connections. These are queued separately so \
+                       that clients lagging on them do not delay other notifications."),
+        )
+        .arg(
+            Arg::with_name("rpc_pubsub_signature_notification_retention_secs")
+                .long("rpc-pubsub-signature-notification-retention-secs")
+                .takes_value(true)
+                .value_name("SECS")
+                .validator(is_parsable::<u64>)
+                .default_value(&default_rpc_pubsub_signature_notification_retention_secs)
+                .help("How long RPC PubSub keeps final signature notifications available \
+                       through `getRecentSignatureNotification`. 0 disables retention."),
+        )
+        .arg(
+            Arg::with_name("rpc_pubsub_keepalive_interval_secs")
+                .long("rpc-pubsub-keepalive-interval-secs")
+                .takes_value(true)
+                .value_name("SECS")
+                .validator(is_parsable::<u64>)
+                .default_value(&default_rpc_pubsub_keepalive_interval_secs)
+                .help("How often RPC PubSub pings websocket clients. 0 disables pings and \
+                       idle timeouts."),
+        )
+        .arg(
+            Arg::with_name("rpc_pubsub_idle_timeout_secs")
+                .long("rpc-pubsub-idle-timeout-secs")
+                .takes_value(true)
+                .value_name("SECS")
+                .validator(is_parsable::<u64>)
+                .default_value(&default_rpc_pubsub_idle_timeout_secs)
+                .help("Disconnect RPC PubSub websocket clients that send nothing, not even \
+                       a pong, for this long"),
+        )
+        .arg(
+            Arg::with_name("rpc_pubsub_max_memory_bytes")
+                .long("rpc-pubsub-max-memory-bytes")
+                .takes_value(true)
+                .value_name("BYTES")
+                .validator(is_parsable::<usize>)
+                .default_value(&default_rpc_pubsub_max_memory_bytes)
+                .help("Hard cap on the memory used by RPC PubSub buffers (notification queue, \
+                       stored notifications, connection buffers). Buffers are shed in priority \
+                       order as usage approaches this limit; requests are refused, without \
+                       disconnecting the client, once connection buffers have to be shed."),
+        )
+        .arg(
+            Arg::with_name("rpc_pubsub_notification_threads")
+                .long("rpc-pubsub-notification-threads")
+                .takes_value(true)
+                .value_name("NUM_THREADS")
+                .validator(is_parsable::<usize>)
+                .default_value(&default_rpc_pubsub_notification_threads)
+                .help("The number of threads used to check RPC PubSub subscriptions for \
+                       changes on every slot. 0 checks them on the notification thread."),
+        )
+        .arg(
+            Arg::with_name("rpc_pubsub_notification_queue_capacity")
+                .long("rpc-pubsub-notification-queue-capacity")
+                .takes_value(true)
+                .value_name("NUMBER")
+                .validator(is_parsable::<usize>)
+                .default_value(&default_rpc_pubsub_notification_queue_capacity)
+                .help("The maximum number of slot, bank and vote events queued for the RPC \
+                       PubSub notification thread. When it is full, bank events are merged \
+                       into the latest one and other events are dropped."),
+        )
+        .arg(
+            Arg::with_name("rpc_pubsub_slow_consumer_policy")
+                .long("rpc-pubsub-slow-consumer-policy")
+                .takes_value(true)
+                .value_name("POLICY")
+                .possible_values(&["disconnect", "drop-oldest", "pause-non-critical"])
+                .default_value("disconnect")
+                .help("What to do with an RPC PubSub client that fell behind the notification \
+                       queue: close its connection, skip the notifications it missed, or skip \
+                       them and also pause its vote and slot notifications for a while"),
+        )
         .arg(
             Arg::with_name("rpc_send_transaction_retry_ms")
                 .long("rpc-send-retry-ms")
@@ -1340,7 +1729,9 @@ pub fn main() {
                 .takes_value(true)
                 .multiple(true)
                 .hidden(true)
-                .help("Specify the configuration file for the AccountsDb plugin."),
+                .help("Specify the configuration file for the AccountsDb plugin. \
+                       Plugins are notified of account updates, slot status changes, \
+                       transactions and block metadata."),
         )
         .arg(
             Arg::with_name("halt_on_trusted_validators_accounts_hash_mismatch")
@@ -1438,7 +1829,13 @@ pub fn main() {
                 .long("account-index")
                 .takes_value(true)
                 .multiple(true)
-                .possible_values(&["program-id", "spl-token-owner", "spl-token-mint"])
+                .possible_values(&[
+                    "program-id",
+                    "spl-token-owner",
+                    "spl-token-mint",
+                    "stake-staker",
+                    "stake-withdrawer",
+                ])
                 .value_name("INDEX")
                 .help("Enable an accounts index, indexed by the selected account field"),
         )
@@ -1619,6 +2016,26 @@ pub fn main() {
                     .value_name("PERCENT")
                     .help("The maximum delinquent stake % permitted for an exit")
             )
+            .arg(
+                Arg::with_name("drain_rpc_secs")
+                    .long("drain-rpc-secs")
+                    .takes_value(true)
+                    .validator(is_parsable::<u64>)
+                    .default_value("0")
+                    .value_name("SECS")
+                    .help("Drain RPC PubSub for this long before exiting: refuse new \
+                           subscriptions and ask connected clients to reconnect")
+            )
+            .arg(
+                Arg::with_name("rpc_reconnect_delay_secs")
+                    .long("rpc-reconnect-delay-secs")
+                    .takes_value(true)
+                    .validator(is_parsable::<u64>)
+                    .default_value("10")
+                    .value_name("SECS")
+                    .help("How long RPC PubSub clients are asked to wait before reconnecting \
+                           while draining")
+            )
         )
         .subcommand(
             SubCommand::with_name("authorized-voter")
@@ -1650,6 +2067,18 @@ pub fn main() {
             SubCommand::with_name("init")
             .about("Initialize the ledger directory then exit")
         )
+        .subcommand(
+            SubCommand::with_name("list-subscriptions")
+            .about("List the active RPC pubsub subscriptions of the validator")
+            .arg(
+                Arg::with_name("sort_by")
+                    .long("sort-by")
+                    .takes_value(true)
+                    .possible_values(&["id", "notifications", "bytes", "lag-drops"])
+                    .default_value("id")
+                    .help("Order of the listed subscriptions, busiest first unless sorted by id")
+            )
+        )
         .subcommand(
             SubCommand::with_name("monitor")
             .about("Monitor the validator")
@@ -1780,6 +2209,9 @@ pub fn main() {
             let monitor = subcommand_matches.is_present("monitor");
             let max_delinquent_stake =
                 value_t_or_exit!(subcommand_matches, "max_delinquent_stake", u8);
+            let drain_rpc_secs = value_t_or_exit!(subcommand_matches, "drain_rpc_secs", u64);
+            let rpc_reconnect_delay_secs =
+                value_t_or_exit!(subcommand_matches, "rpc_reconnect_delay_secs", u64);
 
             if !force {
                 wait_for_restart_window(&ledger_path, None, min_idle_time, max_delinquent_stake)
@@ -1791,7 +2223,16 @@ pub fn main() {
 
             let admin_client = admin_rpc_service::connect(&ledger_path);
             admin_rpc_service::runtime()
-                .block_on(async move { admin_client.await?.exit().await })
+                .block_on(async move {
+                    let admin_client = admin_client.await?;
+                    if drain_rpc_secs > 0 {
+                        admin_client
+                            .drain_rpc(drain_rpc_secs, rpc_reconnect_delay_secs)
+                            .await
+                    } else {
+                        admin_client.exit().await
+                    }
+                })
                 .unwrap_or_else(|err| {
                     println!("exit request failed: {}", err);
                     exit(1);
@@ -1803,6 +2244,58 @@ pub fn main() {
             }
             return;
         }
+        ("list-subscriptions", Some(subcommand_matches)) => {
+            let admin_client = admin_rpc_service::connect(&ledger_path);
+            let mut subscriptions = admin_rpc_service::runtime()
+                .block_on(async move { admin_client.await?.list_subscriptions().await })
+                .unwrap_or_else(|err| {
+                    println!("listSubscriptions request failed: {}", err);
+                    exit(1);
+                });
+            match subcommand_matches.value_of("sort_by").unwrap() {
+                "notifications" => {
+                    subscriptions.sort_by_key(|subscription| Reverse(subscription.notifications))
+                }
+                "bytes" => subscriptions.sort_by_key(|subscription| Reverse(subscription.bytes)),
+                "lag-drops" => {
+                    subscriptions.sort_by_key(|subscription| Reverse(subscription.lag_drops))
+                }
+                _ => {}
+            }
+            for subscription in &subscriptions {
+                let clients = subscription
+                    .clients
+                    .iter()
+                    .map(|client| format!("{} ({})", client.ip, client.count))
+                    .collect::<Vec<_>>();
+                println!(
+                    "{} {} age: {}s, commitment: {}, last notified slot: {}, clients: {}",
+                    subscription.id,
+                    subscription.method,
+                    subscription.age_secs,
+                    subscription
+                        .commitment
+                        .map(|commitment| format!("{:?}", commitment.commitment))
+                        .unwrap_or_else(|| "-".to_string()),
+                    subscription
+                        .last_notified_slot
+                        .map(|slot| slot.to_string())
+                        .unwrap_or_else(|| "-".to_string()),
+                    if clients.is_empty() {
+                        "-".to_string()
+                    } else {
+                        clients.join(", ")
+                    },
+                );
+                println!(
+                    "  notifications: {}, bytes: {}, lag drops: {}",
+                    subscription.notifications, subscription.bytes, subscription.lag_drops,
+                );
+                println!("  {}", subscription.params);
+            }
+            println!("{} active subscriptions", subscriptions.len());
+            return;
+        }
         ("monitor", _) => {
             monitor_validator(&ledger_path);
             return;
@@ -1975,6 +2468,26 @@ pub fn main() {
     } else {
         bind_address
     };
+    let rpc_tls_config = matches.value_of("rpc_tls_cert").map(|cert_path| TlsConfig {
+        cert_path: PathBuf::from(cert_path),
+        key_path: PathBuf::from(matches.value_of("rpc_tls_key").unwrap()),
+    });
+    // Never serve unauthenticated pubsub connections when the keys can't be read
+    let rpc_pubsub_api_keys = matches.value_of("rpc_pubsub_api_keys").map(|path| {
+        let api_keys = ApiKeys::load(PathBuf::from(path)).unwrap_or_else(|err| {
+            eprintln!("Failed to load RPC PubSub API keys from {}: {}", path, err);
+            exit(1);
+        });
+        Arc::new(api_keys)
+    });
+    for account_parser in matches.values_of("account_parser").into_iter().flatten() {
+        let (program_id, libpath) = account_parser.split_once(':').unwrap();
+        let program_id = Pubkey::from_str(program_id).unwrap();
+        if let Err(err) = unsafe { account_parsers::load_account_parser(program_id, libpath) } {
+            eprintln!("Failed to load the account parser {}: {}", libpath, err);
+            exit(1);
+        }
+    }
 
     let contact_debug_interval = value_t_or_exit!(matches, "contact_debug_interval", u64);
 
@@ -2144,6 +2657,22 @@ pub fn main() {
             enable_bigtable_ledger_storage: matches
                 .is_present("enable_rpc_bigtable_ledger_storage"),
             enable_bigtable_ledger_upload: matches.is_present("enable_bigtable_ledger_upload"),
+            object_store_ledger_storage: matches.value_of("rpc_object_store_endpoint").map(
+                |endpoint| ObjectStoreConfig {
+                    endpoint: endpoint.to_string(),
+                    bucket: matches
+                        .value_of("rpc_object_store_bucket")
+                        .unwrap()
+                        .to_string(),
+                },
+            ),
+            enable_object_store_ledger_upload: matches
+                .is_present("enable_object_store_ledger_upload"),
+            bigtable_upload_config: ConfirmedBlockUploadConfig::default().exclude(
+                matches
+                    .values_of("bigtable_upload_exclude")
+                    .unwrap_or_default(),
+            ),
             faucet_addr: matches.value_of("rpc_faucet_addr").map(|address| {
                 solana_net_utils::parse_host_port(address).expect("failed to parse faucet address")
             }),
@@ -2160,11 +2689,31 @@ pub fn main() {
                 u64
             ),
             rpc_threads: value_t_or_exit!(matches, "rpc_threads", usize),
+            rpc_scan_threads: value_t_or_exit!(matches, "rpc_scan_threads", usize),
             rpc_bigtable_timeout: value_t!(matches, "rpc_bigtable_timeout", u64)
                 .ok()
                 .map(Duration::from_secs),
+            program_accounts_cache_ttl: value_t!(matches, "rpc_program_accounts_cache_secs", u64)
+                .ok()
+                .map(Duration::from_secs),
+            largest_accounts_refresh_interval: value_t!(
+                matches,
+                "rpc_largest_accounts_refresh_secs",
+                u64
+            )
+            .ok()
+            .map(Duration::from_secs),
             account_indexes: account_indexes.clone(),
             rpc_scan_and_fix_roots: matches.is_present("rpc_scan_and_fix_roots"),
+            finalized_only: matches.is_present("rpc_finalized_only"),
+            unix_socket_path: matches.value_of("rpc_unix_socket").map(PathBuf::from),
+            tls: rpc_tls_config.clone(),
+            http_compression_min_size: value_t!(matches, "rpc_http_compression_min_size", usize)
+                .ok(),
+            rate_limits_path: matches.value_of("rpc_rate_limits").map(PathBuf::from),
+            slow_request_threshold: value_t!(matches, "rpc_slow_request_threshold_ms", u64)
+                .ok()
+                .map(Duration::from_millis),
         },
         accountsdb_repl_service_config,
         accountsdb_plugin_config_files,
@@ -2177,13 +2726,39 @@ pub fn main() {
                 // https://github.com/solana-labs/solana/issues/12250
             )
         }),
+        rpc_grpc_addr: value_t!(matches, "rpc_grpc_port", u16)
+            .ok()
+            .map(|rpc_grpc_port| SocketAddr::new(rpc_bind_address, rpc_grpc_port)),
+        rpc_sse_addr: value_t!(matches, "rpc_sse_port", u16)
+            .ok()
+            .map(|rpc_sse_port| SocketAddr::new(rpc_bind_address, rpc_sse_port)),
         pubsub_config: PubSubConfig {
+            enable_block_subscription: matches.is_present("rpc_pubsub_enable_block_subscription"),
+            enable_transaction_subscription: matches
+                .is_present("rpc_pubsub_enable_transaction_subscription"),
+            enable_token_accounts_by_owner_subscription: account_indexes
+                .contains(&AccountIndex::SplTokenOwner),
             enable_vote_subscription: matches.is_present("rpc_pubsub_enable_vote_subscription"),
             max_active_subscriptions: value_t_or_exit!(
                 matches,
                 "rpc_pubsub_max_active_subscriptions",
                 usize
             ),
+            max_subscriptions_per_connection: value_t_or_exit!(
+                matches,
+                "rpc_pubsub_max_subscriptions_per_connection",
+                usize
+            ),
+            max_subscriptions_per_ip: value_t_or_exit!(
+                matches,
+                "rpc_pubsub_max_subscriptions_per_ip",
+                usize
+            ),
+            max_accounts_per_subscription: value_t_or_exit!(
+                matches,
+                "rpc_pubsub_max_accounts_per_subscription",
+                usize
+            ),
             queue_capacity_items: value_t_or_exit!(
                 matches,
                 "rpc_pubsub_queue_capacity_items",
@@ -2194,6 +2769,52 @@ pub fn main() {
                 "rpc_pubsub_queue_capacity_bytes",
                 usize
             ),
+            firehose_queue_capacity_items: value_t_or_exit!(
+                matches,
+                "rpc_pubsub_firehose_queue_capacity_items",
+                usize
+            ),
+            max_memory_bytes: value_t_or_exit!(matches, "rpc_pubsub_max_memory_bytes", usize),
+            signature_notification_retention: Duration::from_secs(value_t_or_exit!(
+                matches,
+                "rpc_pubsub_signature_notification_retention_secs",
+                u64
+            )),
+            finalized_only: matches.is_present("rpc_finalized_only"),
+            slow_consumer_policy: value_t_or_exit!(
+                matches,
+                "rpc_pubsub_slow_consumer_policy",
+                SlowConsumerPolicy
+            ),
+            notification_threads: value_t_or_exit!(
+                matches,
+                "rpc_pubsub_notification_threads",
+                usize
+            ),
+            notification_queue_capacity: value_t_or_exit!(
+                matches,
+                "rpc_pubsub_notification_queue_capacity",
+                usize
+            ),
+            enable_compression: matches.is_present("rpc_pubsub_enable_compression"),
+            unix_socket_path: matches
+                .value_of("rpc_pubsub_unix_socket")
+                .map(PathBuf::from),
+            tls: rpc_tls_config,
+            api_keys: rpc_pubsub_api_keys,
+            max_webhooks: value_t_or_exit!(matches, "rpc_pubsub_max_webhooks", usize),
+            max_grpc_streams: value_t_or_exit!(matches, "rpc_grpc_max_streams", usize),
+            keepalive_interval: Duration::from_secs(value_t_or_exit!(
+                matches,
+                "rpc_pubsub_keepalive_interval_secs",
+                u64
+            )),
+            idle_timeout: Duration::from_secs(value_t_or_exit!(
+                matches,
+                "rpc_pubsub_idle_timeout_secs",
+                u64
+            )),
+            drain: Arc::default(),
         },
         voting_disabled: matches.is_present("no_voting") || restricted_repair_only_mode,
         wait_for_supermajority: value_t!(matches, "wait_for_supermajority", Slot).ok(),
@@ -2442,6 +3063,7 @@ pub fn main() {
 
     let start_progress = Arc::new(RwLock::new(ValidatorStartProgress::default()));
     let admin_service_cluster_info = Arc::new(RwLock::new(None));
+    let admin_service_subscription_control = Arc::new(RwLock::new(None));
     admin_rpc_service::run(
         &ledger_path,
         admin_rpc_service::AdminRpcRequestMetadata {
@@ -2452,6 +3074,8 @@ pub fn main() {
             authorized_voter_keypairs: authorized_voter_keypairs.clone(),
             cluster_info: admin_service_cluster_info.clone(),
             tower_storage: validator_config.tower_storage.clone(),
+            rpc_pubsub_drain: validator_config.pubsub_config.drain.clone(),
+            subscription_control: admin_service_subscription_control.clone(),
         },
     );
 
@@ -2594,6 +3218,8 @@ pub fn main() {
         socket_addr_space,
     );
     *admin_service_cluster_info.write().unwrap() = Some(validator.cluster_info.clone());
+    *admin_service_subscription_control.write().unwrap() =
+        Some(validator.subscription_control.clone());
 
     if let Some(filename) = init_complete_file {
         File::create(filename).unwrap_or_else(|_| {
@@ -2614,6 +3240,8 @@ fn process_account_indexes(matches: &ArgMatches) -> AccountSecondaryIndexes {
             "program-id" => AccountIndex::ProgramId,
             "spl-token-mint" => AccountIndex::SplTokenMint,
             "spl-token-owner" => AccountIndex::SplTokenOwner,
+            "stake-staker" => AccountIndex::StakeStaker,
+            "stake-withdrawer" => AccountIndex::StakeWithdrawer,
             _ => unreachable!(),
         })
         .collect();